@@ -0,0 +1,176 @@
+// src/app.rs
+// The server as a library: build it from config, optionally with your own notifier,
+// repository or package analyzer, then serve its router or mount it in a larger one.
+// The server binary is a thin wrapper around this.
+use axum::Router;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+use crate::{
+    routes,
+    services::{
+        cache_service::{CacheConfig, CacheService},
+        database::Repository,
+        messaging_service::{MockNotificationService, NotificationService},
+        package_analysis::{NoPackageAnalysis, PackageImageAnalyzer},
+    },
+    state::{AppConfig, AppState, ServiceOverrides},
+    workers::WorkerRuntime,
+};
+
+pub struct SparrowAppBuilder {
+    config: AppConfig,
+    overrides: ServiceOverrides,
+    in_memory: bool,
+}
+
+impl SparrowAppBuilder {
+    /// Sends every notification through `notification_service` instead of FCM and APNs
+    pub fn notification_service(mut self, notification_service: Arc<dyn NotificationService>) -> Self {
+        self.overrides.notification_service = Some(notification_service);
+        self
+    }
+
+    pub fn repository(mut self, repository: Arc<dyn Repository>) -> Self {
+        self.overrides.repository = Some(repository);
+        self
+    }
+
+    pub fn package_analyzer(mut self, package_analyzer: Arc<dyn PackageImageAnalyzer>) -> Self {
+        self.overrides.package_analyzer = Some(package_analyzer);
+        self
+    }
+
+    /// Runs on the in-memory cache instead of Redis, with the mock notifier unless another
+    /// is given. Nothing is checked at startup or shared with other instances.
+    pub fn in_memory(mut self) -> Self {
+        self.in_memory = true;
+        self
+    }
+
+    // Must be called inside a Tokio runtime: the app starts its background workers
+    pub async fn build(self) -> Result<SparrowApp, Box<dyn std::error::Error>> {
+        let state = if self.in_memory {
+            let overrides = self.overrides;
+            let mut cache_service = CacheService::new_memory(CacheConfig {
+                format: self.config.cache_format,
+                ..Default::default()
+            });
+            if let Some(repository) = overrides.repository {
+                cache_service = cache_service.with_repository(repository);
+            }
+            AppState::with_services(
+                self.config,
+                Arc::new(cache_service),
+                overrides.notification_service.unwrap_or_else(|| Arc::new(MockNotificationService)),
+                overrides.package_analyzer.unwrap_or_else(|| Arc::new(NoPackageAnalysis)),
+            )
+        } else {
+            AppState::with_overrides(self.config, self.overrides).await?
+        };
+        let state = Arc::new(state);
+        let router = routes::router(state.clone());
+        Ok(SparrowApp { state, router })
+    }
+}
+
+pub struct SparrowApp {
+    state: Arc<AppState>,
+    router: Router,
+}
+
+impl SparrowApp {
+    pub fn builder(config: AppConfig) -> SparrowAppBuilder {
+        SparrowAppBuilder {
+            config,
+            overrides: ServiceOverrides::default(),
+            in_memory: false,
+        }
+    }
+
+    /// Every route with its middleware, ready to serve or nest
+    pub fn router(&self) -> Router {
+        self.router.clone()
+    }
+
+    pub fn state(&self) -> &Arc<AppState> {
+        &self.state
+    }
+
+    /// The background workers the app started
+    pub fn workers(&self) -> &WorkerRuntime {
+        &self.state.workers
+    }
+
+    /// Serve on `listener` until `shutdown` completes, then stop the workers and flush
+    /// queued writes
+    pub async fn serve<F>(self, listener: TcpListener, shutdown: F) -> std::io::Result<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        axum::serve(listener, self.router.clone())
+            .with_graceful_shutdown(shutdown)
+            .await?;
+        self.shutdown().await;
+        Ok(())
+    }
+
+    // Stop background workers and flush queued cache writes; call after the server stops
+    pub async fn shutdown(&self) {
+        self.state.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::{Request, StatusCode}};
+    use tower::ServiceExt;
+
+    use crate::{
+        handlers::request_log::RequestLogConfig,
+        mocks::{fixtures::Faker, messaging::RecordingNotificationService},
+        models::user::UserType,
+        services::{cache_codec::CacheFormat, messaging_service::{NotificationMessage, NotificationPriority}, realtime_publisher::RealtimeProvider, user_service::UserOperations},
+        utils::id_generator::IdFormat,
+    };
+
+    #[tokio::test]
+    async fn test_embedded_app_uses_the_supplied_notifier_and_serves_its_router() {
+        let config = AppConfig {
+            dynamo_url: String::new(),
+            postgres_url: String::new(),
+            redis_url: String::new(),
+            fcm_server_key: None,
+            ably_api_key: String::new(),
+            realtime_provider: RealtimeProvider::SelfHosted,
+            id_format: IdFormat::Legacy,
+            cache_format: CacheFormat::Json,
+            request_log: RequestLogConfig::default(),
+            regions: Vec::new(),
+        };
+        let notifications = Arc::new(RecordingNotificationService::new());
+        let app = SparrowApp::builder(config)
+            .notification_service(notifications.clone())
+            .in_memory()
+            .build()
+            .await
+            .unwrap();
+        assert!(!app.workers().worker_names().is_empty());
+
+        let response = app.router()
+            .oneshot(Request::get("/no-such-route").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let user = app.state().user_service.register_user(Faker::seeded(94).user_registration(UserType::Customer)).await.unwrap();
+        let message = NotificationMessage::new("Hello", "From the host app").with_priority(NotificationPriority::Normal);
+        app.state().notification_service.send_to_user(&user.id, message).await.unwrap();
+        assert!(notifications.sent_to_user(&user.id).iter().any(|sent| sent.message.title == "Hello"));
+
+        app.shutdown().await;
+        assert!(app.workers().worker_names().is_empty());
+    }
+}
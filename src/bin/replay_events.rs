@@ -0,0 +1,75 @@
+// src/bin/replay_events.rs
+// Upload a day's events to the warehouse bucket again, replacing the objects already there.
+//
+//   cargo run --bin replay_events -- --day 2025-09-01 --tenant default
+//
+// Uses the same EVENTS_EXPORT_* settings as the server. Only days still in the outbox
+// can be replayed; see EVENTS_OUTBOX_TTL_SECONDS.
+use chrono::{NaiveDate, Utc};
+use std::sync::Arc;
+
+use sparrow_realtime::{
+    models::tenant::DEFAULT_TENANT_ID,
+    services::{
+        cache_service::{CacheConfig, CacheService},
+        events_export::{EventsExportConfig, EventsExportService, ObjectStoreConfig, ObjectStoreSink},
+        tenant_service::with_tenant,
+    },
+};
+
+struct ReplayOptions {
+    redis_url: String,
+    tenant_id: String,
+    day: NaiveDate,
+}
+
+impl ReplayOptions {
+    fn from_args() -> Result<Self, String> {
+        let mut redis_url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string());
+        let mut tenant_id = DEFAULT_TENANT_ID.to_string();
+        let mut day = None;
+
+        let mut args = std::env::args().skip(1);
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or_else(|| format!("{} needs a value", flag))?;
+            match flag.as_str() {
+                "--redis" => redis_url = value,
+                "--tenant" => tenant_id = value,
+                "--day" => day = Some(NaiveDate::parse_from_str(&value, "%Y-%m-%d").map_err(|_| format!("--day expects YYYY-MM-DD, got {}", value))?),
+                _ => return Err(format!("unknown option {}", flag)),
+            }
+        }
+        Ok(ReplayOptions {
+            redis_url,
+            tenant_id,
+            day: day.ok_or("--day is required")?,
+        })
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt().with_max_level(tracing::Level::INFO).init();
+
+    let options = ReplayOptions::from_args().inspect_err(|_| {
+        eprintln!("usage: replay_events --day YYYY-MM-DD [--tenant ID] [--redis URL]");
+    })?;
+    let store = ObjectStoreConfig::from_env().ok_or("EVENTS_EXPORT_BUCKET and its credentials must be set")?;
+
+    let cache_service = Arc::new(CacheService::with_config(CacheConfig {
+        redis_url: options.redis_url.clone(),
+        ..Default::default()
+    }).await?);
+    let export = EventsExportService::new(cache_service, EventsExportConfig::default());
+    export.attach_sink(Arc::new(ObjectStoreSink::new(store)));
+
+    let run = with_tenant(options.tenant_id.clone(), async {
+        export.replay(options.day).await?;
+        export.export_pending(Utc::now()).await
+    }).await?;
+    println!(
+        "Replayed {} for tenant {}: {} events in {} objects",
+        options.day, options.tenant_id, run.events_exported, run.objects_written,
+    );
+    Ok(())
+}
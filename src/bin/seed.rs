@@ -0,0 +1,140 @@
+// src/bin/seed.rs
+// Fill a development Redis with realistic customers, drivers and jobs.
+//
+//   cargo run --bin seed -- --customers 2000 --drivers 500 --jobs 5000 --seed 42
+//
+// Everything goes through the services, so indexes and demand stats are populated
+// exactly as live traffic would leave them.
+use std::sync::Arc;
+
+use sparrow_realtime::{
+    handlers::request_log::RequestLogConfig,
+    mocks::fixtures::{Faker, CITIES},
+    models::{ids::{DriverId, UserId}, user::UserType},
+    services::{
+        cache_codec::CacheFormat,
+        cache_service::{CacheConfig, CacheService},
+        driver_service::DriverOperations,
+        job_service::JobOperations,
+        messaging_service::MockNotificationService,
+        package_analysis::NoPackageAnalysis,
+        realtime_publisher::RealtimeProvider,
+        region::RegionRegistry,
+        user_service::UserOperations,
+    },
+    state::{AppConfig, AppState},
+    utils::{field_crypto::FieldCipher, id_generator::IdFormat},
+};
+
+struct SeedOptions {
+    redis_url: String,
+    customers: usize,
+    drivers: usize,
+    jobs: usize,
+    seed: Option<u64>,
+}
+
+impl SeedOptions {
+    fn from_args() -> Result<Self, String> {
+        let mut options = SeedOptions {
+            redis_url: std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string()),
+            customers: 1_000,
+            drivers: 200,
+            jobs: 3_000,
+            seed: None,
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or_else(|| format!("{} needs a value", flag))?;
+            let count = || value.parse::<usize>().map_err(|_| format!("{} expects a number, got {}", flag, value));
+            match flag.as_str() {
+                "--redis" => options.redis_url = value.clone(),
+                "--customers" => options.customers = count()?,
+                "--drivers" => options.drivers = count()?,
+                "--jobs" => options.jobs = count()?,
+                "--seed" => options.seed = Some(value.parse().map_err(|_| format!("--seed expects a number, got {}", value))?),
+                _ => return Err(format!("unknown option {}", flag)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt().with_max_level(tracing::Level::WARN).init();
+
+    let options = SeedOptions::from_args().inspect_err(|_| {
+        eprintln!("usage: seed [--redis URL] [--customers N] [--drivers N] [--jobs N] [--seed N]");
+    })?;
+    let mut faker = options.seed.map(Faker::seeded).unwrap_or_default();
+
+    let config = AppConfig {
+        dynamo_url: String::new(),
+        postgres_url: String::new(),
+        redis_url: options.redis_url.clone(),
+        fcm_server_key: None,
+        ably_api_key: String::new(),
+        realtime_provider: RealtimeProvider::Mock,
+        id_format: IdFormat::Legacy,
+        cache_format: CacheFormat::Json,
+        request_log: RequestLogConfig::default(),
+        regions: RegionRegistry::ids_from_env(),
+    };
+    let mut cache_service = CacheService::with_config(CacheConfig {
+        redis_url: config.redis_url.clone(),
+        format: config.cache_format,
+        ..Default::default()
+    }).await?;
+    // Same keys as the server, or it couldn't read what was seeded
+    if let Some(cipher) = FieldCipher::from_env()? {
+        cache_service = cache_service.with_cipher(Arc::new(cipher));
+    }
+    let cache_service = Arc::new(cache_service);
+    // Never push real notifications from a seed run
+    let state = AppState::with_services(config, cache_service.clone(), Arc::new(MockNotificationService), Arc::new(NoPackageAnalysis));
+
+    let mut customers: Vec<UserId> = Vec::with_capacity(options.customers);
+    for _ in 0..options.customers {
+        let user = state.user_service.register_user(faker.user_registration(UserType::Customer)).await?;
+        customers.push(user.id);
+    }
+    println!("Seeded {} customers", customers.len());
+
+    let mut drivers: Vec<DriverId> = Vec::with_capacity(options.drivers);
+    let mut locations = Vec::with_capacity(options.drivers);
+    for i in 0..options.drivers {
+        let user = faker.user(UserType::Driver);
+        let registration = faker.driver_registration(&user);
+        let driver = state.driver_service.register_driver(registration).await?;
+        locations.push((driver.id.clone(), faker.location_update(CITIES[i % CITIES.len()])));
+        drivers.push(driver.id);
+    }
+    cache_service.cache_driver_locations(&locations).await?;
+    println!("Seeded {} drivers", drivers.len());
+
+    if customers.is_empty() && options.jobs > 0 {
+        return Err("jobs need at least one customer".into());
+    }
+    let (mut assigned, mut completed) = (0, 0);
+    for i in 0..options.jobs {
+        let customer_id = &customers[i % customers.len()];
+        let job = state.job_service.create_job(faker.job_request(customer_id)).await?;
+
+        // Leave a realistic mix: mostly delivered, some in flight, the rest still open
+        if drivers.is_empty() || i % 10 >= 8 {
+            continue;
+        }
+        let driver_id = &drivers[i % drivers.len()];
+        state.job_service.assign_driver_to_job(&job.id, driver_id).await?;
+        assigned += 1;
+        if i % 10 < 6 {
+            state.job_service.complete_job(&job.id).await?;
+            completed += 1;
+        }
+    }
+    println!("Seeded {} jobs ({} assigned, {} completed)", options.jobs, assigned, completed);
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+// src/bin/simulate.rs
+// Replay a day of jobs against other dispatch and pricing settings and compare the results.
+//
+//   cargo run --bin simulate -- --day 2025-09-01 --scenarios scenarios.json
+//   cargo run --bin simulate -- --input day.json --scenarios scenarios.json --json
+//
+// The day comes from Redis unless --input names a file written earlier with --save. The
+// scenarios file is a JSON array, e.g.
+//
+//   [{"name": "wider", "search_radius_km": 15.0},
+//    {"name": "broadcast", "strategy": "broadcast", "pricing": {"currency": "GHS", ...}}]
+use chrono::NaiveDate;
+use std::path::PathBuf;
+
+use sparrow_realtime::{
+    models::{
+        simulation::{SimulationDay, SimulationScenario},
+        tenant::DEFAULT_TENANT_ID,
+    },
+    services::{
+        cache_service::{CacheConfig, CacheService},
+        simulator::{compare, load_day, SimulatorConfig},
+        tenant_service::with_tenant,
+    },
+};
+
+struct SimulateOptions {
+    redis_url: String,
+    tenant_id: String,
+    day: Option<NaiveDate>,
+    input: Option<PathBuf>,
+    save: Option<PathBuf>,
+    scenarios: PathBuf,
+    json: bool,
+}
+
+impl SimulateOptions {
+    fn from_args() -> Result<Self, String> {
+        let mut redis_url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string());
+        let mut tenant_id = DEFAULT_TENANT_ID.to_string();
+        let (mut day, mut input, mut save, mut scenarios) = (None, None, None, None);
+        let mut json = false;
+
+        let mut args = std::env::args().skip(1);
+        while let Some(flag) = args.next() {
+            if flag == "--json" {
+                json = true;
+                continue;
+            }
+            let value = args.next().ok_or_else(|| format!("{} needs a value", flag))?;
+            match flag.as_str() {
+                "--redis" => redis_url = value,
+                "--tenant" => tenant_id = value,
+                "--day" => day = Some(NaiveDate::parse_from_str(&value, "%Y-%m-%d").map_err(|_| format!("--day expects YYYY-MM-DD, got {}", value))?),
+                "--input" => input = Some(PathBuf::from(value)),
+                "--save" => save = Some(PathBuf::from(value)),
+                "--scenarios" => scenarios = Some(PathBuf::from(value)),
+                _ => return Err(format!("unknown option {}", flag)),
+            }
+        }
+        if day.is_none() && input.is_none() {
+            return Err("--day or --input is required".to_string());
+        }
+        Ok(SimulateOptions {
+            redis_url,
+            tenant_id,
+            day,
+            input,
+            save,
+            scenarios: scenarios.ok_or("--scenarios is required")?,
+            json,
+        })
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt().with_max_level(tracing::Level::WARN).with_writer(std::io::stderr).init();
+
+    let options = SimulateOptions::from_args().inspect_err(|_| {
+        eprintln!("usage: simulate (--day YYYY-MM-DD [--tenant ID] [--redis URL] | --input FILE) --scenarios FILE [--save FILE] [--json]");
+    })?;
+    let scenarios: Vec<SimulationScenario> = serde_json::from_str(&std::fs::read_to_string(&options.scenarios)?)?;
+
+    let day: SimulationDay = match (&options.input, options.day) {
+        (Some(input), _) => serde_json::from_str(&std::fs::read_to_string(input)?)?,
+        (None, Some(day)) => {
+            let cache_service = CacheService::with_config(CacheConfig {
+                redis_url: options.redis_url.clone(),
+                ..Default::default()
+            }).await?;
+            with_tenant(options.tenant_id.clone(), load_day(&cache_service, day)).await?
+        }
+        (None, None) => unreachable!("checked when parsing options"),
+    };
+    if let Some(save) = &options.save {
+        std::fs::write(save, serde_json::to_string_pretty(&day)?)?;
+    }
+
+    let report = compare(&day, &scenarios, &SimulatorConfig::default());
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}", report);
+    }
+    Ok(())
+}
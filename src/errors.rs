@@ -1,8 +1,14 @@
-use axum::http::StatusCode;
+use axum::http::{header, HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use crate::{handlers::request_id::current_request_id, services::i18n::localize};
+
+/// How long clients should back off when a variant doesn't carry its own hint
+pub const DEFAULT_RETRY_AFTER_SECONDS: u64 = 30;
+
 /// Main error type for the sparrow-realtime service
 #[derive(Debug)]
 pub enum SparrowError {
@@ -12,7 +18,7 @@ pub enum SparrowError {
     Forbidden(String),
     NotFound(String),
     Conflict(String),
-    TooManyRequests(String),
+    TooManyRequests { message: String, retry_after_seconds: u64 },
     InternalServer(String),
 
     // Database and Redis errors
@@ -27,6 +33,8 @@ pub enum SparrowError {
     FcmDelivery(String),
     FcmInvalidToken(String),
     FcmQuotaExceeded,
+    ApnsDelivery(String),
+    ApnsInvalidToken(String),
 
     // Network and HTTP client errors
     NetworkTimeout,
@@ -72,12 +80,14 @@ pub enum SparrowError {
     TokenExpired,
     TokenInvalid,
     InsufficientPermissions,
-    RateLimitExceeded,
+    RateLimitExceeded { retry_after_seconds: u64 },
+    QuotaExceeded { metric: String, limit: u64, resets_at: DateTime<Utc> },
+    DeviceVerificationRequired(String),
 
     // Resource management errors
     ResourceNotAvailable(String),
     ResourceExhausted(String),
-    ServiceUnavailable(String),
+    ServiceUnavailable { service: String, retry_after_seconds: u64 },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -86,12 +96,112 @@ pub struct ValidationError {
     pub message: String,
 }
 
-#[derive(Serialize)]
-struct ErrorResponse {
-    error: String,
-    message: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    details: Option<serde_json::Value>,
+/// Stable, machine-readable error codes returned in `ErrorResponse::code`.
+///
+/// Clients should branch on these rather than on `message`, which is for humans and
+/// may change wording at any time. Codes are only ever added, never renamed or reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// 400: the request was malformed in a way not covered by a more specific code
+    BadRequest,
+    /// 400: one or more fields failed validation; see `details` for the field list
+    ValidationFailed,
+    /// 400: a required field was absent
+    MissingField,
+    /// 400: a field was present but its value was rejected
+    InvalidField,
+    /// 401: no credentials, or credentials we don't recognise
+    Unauthorized,
+    /// 401: the access token has expired; refresh and retry
+    TokenExpired,
+    /// 401: the access token is malformed or has been revoked
+    TokenInvalid,
+    /// 401: login from an unrecognised device; retry with the code sent to the user's other devices
+    DeviceVerificationRequired,
+    /// 403: authenticated, but not allowed to touch this resource
+    Forbidden,
+    /// 403: the caller's role lacks the permission this operation needs
+    InsufficientPermissions,
+    /// 404: generic missing resource
+    NotFound,
+    /// 404: no user with the given ID
+    UserNotFound,
+    /// 404: no driver with the given ID
+    DriverNotFound,
+    /// 404: no job with the given ID
+    JobNotFound,
+    /// 409: the request conflicts with the current state of the resource
+    Conflict,
+    /// 409: the job already has a driver
+    JobAlreadyAssigned,
+    /// 409: the job is finished and can no longer change
+    JobAlreadyCompleted,
+    /// 409: the driver is offline or busy
+    DriverNotAvailable,
+    /// 429: too many requests; wait `retry_after_seconds` before retrying
+    TooManyRequests,
+    /// 429: a per-key rate limit was hit; wait `retry_after_seconds` before retrying
+    RateLimitExceeded,
+    /// 429: a daily or monthly quota on the API key is used up; `details.resets_at` says until when
+    QuotaExceeded,
+    /// 503: a dependency is temporarily down; wait `retry_after_seconds` before retrying
+    ServiceUnavailable,
+    /// 500: something went wrong on our side; quote `request_id` when reporting it
+    InternalError,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::BadRequest => "bad_request",
+            ErrorCode::ValidationFailed => "validation_failed",
+            ErrorCode::MissingField => "missing_field",
+            ErrorCode::InvalidField => "invalid_field",
+            ErrorCode::Unauthorized => "unauthorized",
+            ErrorCode::TokenExpired => "token_expired",
+            ErrorCode::TokenInvalid => "token_invalid",
+            ErrorCode::DeviceVerificationRequired => "device_verification_required",
+            ErrorCode::Forbidden => "forbidden",
+            ErrorCode::InsufficientPermissions => "insufficient_permissions",
+            ErrorCode::NotFound => "not_found",
+            ErrorCode::UserNotFound => "user_not_found",
+            ErrorCode::DriverNotFound => "driver_not_found",
+            ErrorCode::JobNotFound => "job_not_found",
+            ErrorCode::Conflict => "conflict",
+            ErrorCode::JobAlreadyAssigned => "job_already_assigned",
+            ErrorCode::JobAlreadyCompleted => "job_already_completed",
+            ErrorCode::DriverNotAvailable => "driver_not_available",
+            ErrorCode::TooManyRequests => "too_many_requests",
+            ErrorCode::RateLimitExceeded => "rate_limit_exceeded",
+            ErrorCode::QuotaExceeded => "quota_exceeded",
+            ErrorCode::ServiceUnavailable => "service_unavailable",
+            ErrorCode::InternalError => "internal_error",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// JSON body of every error response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub code: ErrorCode,
+    /// Same value as `code`; kept for clients written before `code` existed
+    pub error: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+    /// Also sent as the `Retry-After` header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_seconds: Option<u64>,
+    /// Matches the `x-request-id` response header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 impl fmt::Display for SparrowError {
@@ -102,7 +212,7 @@ impl fmt::Display for SparrowError {
             SparrowError::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
             SparrowError::NotFound(msg) => write!(f, "Not found: {}", msg),
             SparrowError::Conflict(msg) => write!(f, "Conflict: {}", msg),
-            SparrowError::TooManyRequests(msg) => write!(f, "Too many requests: {}", msg),
+            SparrowError::TooManyRequests { message, .. } => write!(f, "Too many requests: {}", message),
             SparrowError::InternalServer(msg) => write!(f, "Internal server error: {}", msg),
 
             SparrowError::RedisConnection(msg) => write!(f, "Redis connection error: {}", msg),
@@ -115,6 +225,8 @@ impl fmt::Display for SparrowError {
             SparrowError::FcmDelivery(msg) => write!(f, "FCM delivery error: {}", msg),
             SparrowError::FcmInvalidToken(msg) => write!(f, "Invalid FCM token: {}", msg),
             SparrowError::FcmQuotaExceeded => write!(f, "FCM quota exceeded"),
+            SparrowError::ApnsDelivery(msg) => write!(f, "APNs delivery error: {}", msg),
+            SparrowError::ApnsInvalidToken(msg) => write!(f, "Invalid APNs token: {}", msg),
 
             SparrowError::NetworkTimeout => write!(f, "Network request timed out"),
             SparrowError::NetworkConnection(msg) => write!(f, "Network connection error: {}", msg),
@@ -159,66 +271,151 @@ impl fmt::Display for SparrowError {
             SparrowError::TokenExpired => write!(f, "Authentication token has expired"),
             SparrowError::TokenInvalid => write!(f, "Authentication token is invalid"),
             SparrowError::InsufficientPermissions => write!(f, "Insufficient permissions for this operation"),
-            SparrowError::RateLimitExceeded => write!(f, "Rate limit exceeded"),
+            SparrowError::RateLimitExceeded { .. } => write!(f, "Rate limit exceeded"),
+            SparrowError::QuotaExceeded { metric, limit, resets_at } => {
+                write!(f, "Quota of {} {} used up until {}", limit, metric, resets_at.to_rfc3339())
+            }
+            SparrowError::DeviceVerificationRequired(msg) => write!(f, "Device verification required: {}", msg),
 
             SparrowError::ResourceNotAvailable(resource) => write!(f, "Resource not available: {}", resource),
             SparrowError::ResourceExhausted(resource) => write!(f, "Resource exhausted: {}", resource),
-            SparrowError::ServiceUnavailable(service) => write!(f, "Service unavailable: {}", service),
+            SparrowError::ServiceUnavailable { service, .. } => write!(f, "Service unavailable: {}", service),
         }
     }
 }
 
 impl std::error::Error for SparrowError {}
 
-impl IntoResponse for SparrowError {
-    fn into_response(self) -> Response {
-        let (status, error_type, message, details) = match self {
-            SparrowError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "bad_request", msg, None),
-            SparrowError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, "unauthorized", msg, None),
-            SparrowError::Forbidden(msg) => (StatusCode::FORBIDDEN, "forbidden", msg, None),
-            SparrowError::NotFound(msg) => (StatusCode::NOT_FOUND, "not_found", msg, None),
-            SparrowError::Conflict(msg) => (StatusCode::CONFLICT, "conflict", msg, None),
-            SparrowError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, "too_many_requests", msg, None),
+impl SparrowError {
+    /// Stable code clients can branch on
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            SparrowError::BadRequest(_) => ErrorCode::BadRequest,
+            SparrowError::Unauthorized(_) => ErrorCode::Unauthorized,
+            SparrowError::Forbidden(_) => ErrorCode::Forbidden,
+            SparrowError::NotFound(_) => ErrorCode::NotFound,
+            SparrowError::Conflict(_) => ErrorCode::Conflict,
+            SparrowError::TooManyRequests { .. } => ErrorCode::TooManyRequests,
+
+            SparrowError::ValidationFailed(_) => ErrorCode::ValidationFailed,
+            SparrowError::MissingRequiredField(_) => ErrorCode::MissingField,
+            SparrowError::InvalidFieldValue { .. } => ErrorCode::InvalidField,
+
+            SparrowError::UserNotFound(_) => ErrorCode::UserNotFound,
+            SparrowError::DriverNotFound(_) => ErrorCode::DriverNotFound,
+            SparrowError::JobNotFound(_) => ErrorCode::JobNotFound,
+
+            SparrowError::JobAlreadyAssigned => ErrorCode::JobAlreadyAssigned,
+            SparrowError::JobAlreadyCompleted => ErrorCode::JobAlreadyCompleted,
+            SparrowError::DriverNotAvailable => ErrorCode::DriverNotAvailable,
+
+            SparrowError::TokenExpired => ErrorCode::TokenExpired,
+            SparrowError::TokenInvalid => ErrorCode::TokenInvalid,
+            SparrowError::DeviceVerificationRequired(_) => ErrorCode::DeviceVerificationRequired,
+            SparrowError::InsufficientPermissions => ErrorCode::InsufficientPermissions,
+            SparrowError::RateLimitExceeded { .. } => ErrorCode::RateLimitExceeded,
+            SparrowError::QuotaExceeded { .. } => ErrorCode::QuotaExceeded,
+
+            SparrowError::ServiceUnavailable { .. }
+            | SparrowError::RedisConnection(_)
+            | SparrowError::RedisTimeout => ErrorCode::ServiceUnavailable,
 
-            SparrowError::ValidationFailed(errors) => {
-                let details = serde_json::to_value(&errors).ok();
-                (StatusCode::BAD_REQUEST, "validation_failed", "Validation errors occurred".to_string(), details)
-            }
-            SparrowError::MissingRequiredField(field) => {
-                (StatusCode::BAD_REQUEST, "missing_field", format!("Missing required field: {}", field), None)
-            }
-            SparrowError::InvalidFieldValue { field, value, reason } => {
-                (StatusCode::BAD_REQUEST, "invalid_field", format!("Invalid value for {}: {}", field, reason), None)
-            }
+            // All other errors are treated as internal server errors
+            _ => ErrorCode::InternalError,
+        }
+    }
 
-            SparrowError::UserNotFound(id) => (StatusCode::NOT_FOUND, "user_not_found", format!("User not found: {}", id), None),
-            SparrowError::DriverNotFound(id) => (StatusCode::NOT_FOUND, "driver_not_found", format!("Driver not found: {}", id), None),
-            SparrowError::JobNotFound(id) => (StatusCode::NOT_FOUND, "job_not_found", format!("Job not found: {}", id), None),
+    pub fn status_code(&self) -> StatusCode {
+        match self.code() {
+            ErrorCode::BadRequest
+            | ErrorCode::ValidationFailed
+            | ErrorCode::MissingField
+            | ErrorCode::InvalidField => StatusCode::BAD_REQUEST,
+            ErrorCode::Unauthorized
+            | ErrorCode::TokenExpired
+            | ErrorCode::TokenInvalid
+            | ErrorCode::DeviceVerificationRequired => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden | ErrorCode::InsufficientPermissions => StatusCode::FORBIDDEN,
+            ErrorCode::NotFound
+            | ErrorCode::UserNotFound
+            | ErrorCode::DriverNotFound
+            | ErrorCode::JobNotFound => StatusCode::NOT_FOUND,
+            ErrorCode::Conflict
+            | ErrorCode::JobAlreadyAssigned
+            | ErrorCode::JobAlreadyCompleted
+            | ErrorCode::DriverNotAvailable => StatusCode::CONFLICT,
+            ErrorCode::TooManyRequests | ErrorCode::RateLimitExceeded | ErrorCode::QuotaExceeded => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::ServiceUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
 
-            SparrowError::JobAlreadyAssigned => (StatusCode::CONFLICT, "job_already_assigned", "Job is already assigned".to_string(), None),
-            SparrowError::JobAlreadyCompleted => (StatusCode::CONFLICT, "job_already_completed", "Job is already completed".to_string(), None),
-            SparrowError::DriverNotAvailable => (StatusCode::CONFLICT, "driver_not_available", "Driver is not available".to_string(), None),
+    /// Seconds the client should wait before retrying, for errors where retrying helps
+    pub fn retry_after_seconds(&self) -> Option<u64> {
+        match self {
+            SparrowError::TooManyRequests { retry_after_seconds, .. }
+            | SparrowError::RateLimitExceeded { retry_after_seconds }
+            | SparrowError::ServiceUnavailable { retry_after_seconds, .. } => Some(*retry_after_seconds),
+            SparrowError::RedisConnection(_) | SparrowError::RedisTimeout => Some(DEFAULT_RETRY_AFTER_SECONDS),
+            SparrowError::QuotaExceeded { resets_at, .. } => Some((*resets_at - Utc::now()).num_seconds().max(1) as u64),
+            _ => None,
+        }
+    }
+}
 
-            SparrowError::TokenExpired => (StatusCode::UNAUTHORIZED, "token_expired", "Authentication token has expired".to_string(), None),
-            SparrowError::TokenInvalid => (StatusCode::UNAUTHORIZED, "token_invalid", "Authentication token is invalid".to_string(), None),
-            SparrowError::InsufficientPermissions => (StatusCode::FORBIDDEN, "insufficient_permissions", "Insufficient permissions".to_string(), None),
-            SparrowError::RateLimitExceeded => (StatusCode::TOO_MANY_REQUESTS, "rate_limit_exceeded", "Rate limit exceeded".to_string(), None),
+impl IntoResponse for SparrowError {
+    fn into_response(self) -> Response {
+        let code = self.code();
+        let status = self.status_code();
+        let retry_after_seconds = self.retry_after_seconds();
 
-            SparrowError::ServiceUnavailable(service) => {
-                (StatusCode::SERVICE_UNAVAILABLE, "service_unavailable", format!("Service unavailable: {}", service), None)
+        let (message, details) = match self {
+            SparrowError::ValidationFailed(errors) => {
+                let errors: Vec<_> = errors.into_iter()
+                    .map(|error| ValidationError { message: localize(&error.message), ..error })
+                    .collect();
+                ("Validation errors occurred".to_string(), serde_json::to_value(&errors).ok())
             }
-
-            // All other errors are treated as internal server errors
-            _ => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error", self.to_string(), None),
+            SparrowError::MissingRequiredField(field) => (format!("Missing required field: {}", field), None),
+            SparrowError::InvalidFieldValue { field, reason, .. } => {
+                (format!("Invalid value for {}: {}", field, reason), None)
+            }
+            SparrowError::BadRequest(msg)
+            | SparrowError::Unauthorized(msg)
+            | SparrowError::Forbidden(msg)
+            | SparrowError::NotFound(msg)
+            | SparrowError::Conflict(msg)
+            | SparrowError::DeviceVerificationRequired(msg)
+            | SparrowError::TooManyRequests { message: msg, .. } => (msg, None),
+            SparrowError::QuotaExceeded { metric, limit, resets_at } => (
+                format!("Quota of {} {} used up", limit, metric),
+                Some(serde_json::json!({ "metric": metric, "limit": limit, "resets_at": resets_at })),
+            ),
+            SparrowError::JobAlreadyAssigned => ("Job is already assigned".to_string(), None),
+            SparrowError::InsufficientPermissions => ("Insufficient permissions".to_string(), None),
+            other => (other.to_string(), None),
         };
 
+        let request_id = current_request_id();
+        if status.is_server_error() {
+            tracing::error!(request_id = request_id.as_deref().unwrap_or("-"), "{}: {}", code, message);
+        }
+        let message = localize(&message);
+
         let error_response = ErrorResponse {
-            error: error_type.to_string(),
+            code,
+            error: code.as_str().to_string(),
             message,
             details,
+            retry_after_seconds,
+            request_id,
         };
 
-        (status, axum::Json(error_response)).into_response()
+        let mut response = (status, axum::Json(error_response)).into_response();
+        if let Some(seconds) = retry_after_seconds {
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        }
+        response
     }
 }
 
@@ -228,10 +425,18 @@ pub type SparrowResult<T> = Result<T, SparrowError>;
 // Conversion implementations for common error types
 impl From<redis::RedisError> for SparrowError {
     fn from(err: redis::RedisError) -> Self {
+        if err.is_timeout() {
+            return SparrowError::RedisTimeout;
+        }
         match err.kind() {
-            redis::ErrorKind::IoError => SparrowError::RedisConnection(err.to_string()),
-            redis::ErrorKind::ResponseError => SparrowError::RedisQuery(err.to_string()),
-            redis::ErrorKind::AuthenticationFailed => SparrowError::RedisConnection("Authentication failed".to_string()),
+            // Wrong credentials won't fix themselves, so this is not a retryable outage
+            redis::ErrorKind::AuthenticationFailed => {
+                SparrowError::ConfigurationError("Redis authentication failed".to_string())
+            }
+            redis::ErrorKind::TypeError => SparrowError::RedisSerialization(err.to_string()),
+            _ if err.is_io_error() || err.is_connection_refusal() || err.is_connection_dropped() => {
+                SparrowError::RedisConnection(err.to_string())
+            }
             _ => SparrowError::RedisQuery(err.to_string()),
         }
     }
@@ -289,6 +494,17 @@ impl SparrowError {
         SparrowError::InternalServer(msg.into())
     }
 
+    pub fn too_many_requests(msg: impl Into<String>, retry_after_seconds: u64) -> Self {
+        SparrowError::TooManyRequests { message: msg.into(), retry_after_seconds }
+    }
+
+    pub fn service_unavailable(service: impl Into<String>) -> Self {
+        SparrowError::ServiceUnavailable {
+            service: service.into(),
+            retry_after_seconds: DEFAULT_RETRY_AFTER_SECONDS,
+        }
+    }
+
     pub fn validation_error(field: impl Into<String>, message: impl Into<String>) -> Self {
         SparrowError::ValidationFailed(vec![ValidationError {
             field: field.into(),
@@ -339,4 +555,52 @@ mod tests {
         assert!(matches!(SparrowError::not_found("test"), SparrowError::NotFound(_)));
         assert!(matches!(SparrowError::internal_error("test"), SparrowError::InternalServer(_)));
     }
+
+    async fn response_body(response: Response) -> ErrorResponse {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_codes_serialize_as_snake_case() {
+        for code in [ErrorCode::JobAlreadyAssigned, ErrorCode::RateLimitExceeded, ErrorCode::InternalError] {
+            assert_eq!(serde_json::to_value(code).unwrap(), serde_json::json!(code.as_str()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_response_has_retry_after() {
+        let response = SparrowError::RateLimitExceeded { retry_after_seconds: 12 }.into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "12");
+
+        let body = response_body(response).await;
+        assert_eq!(body.code, ErrorCode::RateLimitExceeded);
+        assert_eq!(body.error, "rate_limit_exceeded");
+        assert_eq!(body.retry_after_seconds, Some(12));
+    }
+
+    #[test]
+    fn test_redis_outages_are_retryable() {
+        let error = SparrowError::RedisTimeout;
+        assert_eq!(error.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(error.retry_after_seconds(), Some(DEFAULT_RETRY_AFTER_SECONDS));
+        assert_eq!(SparrowError::RedisQuery("WRONGTYPE".to_string()).code(), ErrorCode::InternalError);
+    }
+
+    #[tokio::test]
+    async fn test_response_carries_request_id() {
+        use crate::handlers::request_id::with_request_id;
+
+        let response = with_request_id("req-42".to_string(), async {
+            SparrowError::job_not_found("job-1").into_response()
+        }).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(response.headers().get(header::RETRY_AFTER).is_none());
+
+        let body = response_body(response).await;
+        assert_eq!(body.code, ErrorCode::JobNotFound);
+        assert_eq!(body.request_id.as_deref(), Some("req-42"));
+        assert_eq!(body.retry_after_seconds, None);
+    }
 }
@@ -0,0 +1,947 @@
+// src/handlers/admin_handler.rs
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        admin::{DriverSearchQuery, OperationsDashboard, SearchPage, UserSearchQuery},
+        driver::{DriverResponse, OnboardingReview, OnboardingStatus},
+        moderation::BanRequest,
+        user::UserResponse,
+        api_key::{ApiKeyResponse, CreateApiKeyRequest, IssuedApiKey},
+        broadcast::{Broadcast, CreateBroadcastRequest},
+        calendar::{CalendarPeriod, CreateCalendarPeriodRequest},
+        ledger::{AccountBalance, AccountStatement, CreateLedgerEntryRequest, LedgerAccount, LedgerEntry},
+        commission::{CommissionConfig, UpdateCommissionsRequest},
+        consumer::{ConsumerStatus, ReplayRequest},
+        canary::CanaryStatus,
+        quota::{ApiKeyUsage, UpdateQuotasRequest},
+        runtime_config::RuntimeConfigSnapshot,
+        startup::StartupReport,
+        dispatch::{DispatchSettings, UpdateDispatchSettingsRequest},
+        exchange_rate::{ExchangeRates, UpdateExchangeRatesRequest},
+        invoice::{BillingAccount, Invoice, InvoiceStatus, RecordInvoicePaymentRequest, UpdateBillingAccountRequest},
+        messages::{
+            NotificationBroadcastRequest, NotificationBroadcastResponse, NotificationTemplate, NotificationTemplateRequest,
+            NotificationType, TemplatePreview, TemplatePreviewRequest,
+        },
+        money::{Currency, CurrencyInfo, CURRENCIES},
+        presence::PresenceSnapshot,
+        retention::{LegalHold, PlaceLegalHoldRequest, RetentionReport, RetentionSettings, UpdateRetentionSettingsRequest},
+        tax::{CreateTaxScheduleRequest, TaxSchedule},
+        ids::{DriverId, JobId, UserId},
+        incident::{Incident, IncidentStatus, IncidentUpdateRequest},
+        region::RegionSummary,
+        risk::{DriverRiskReport, RiskFlag},
+        saga::SagaState,
+        job::{JobResponse, OverrideDeliveryCodeRequest},
+        tenant::{CreateTenantRequest, Tenant},
+        zone::{ActivateZoneRequest, CreateZoneRequest, ServiceZone, UpdateZoneRequest, ZoneMatch},
+    },
+    services::{export_service::{ExportFormat, ExportStream}, send_queue::SendQueueMetrics, write_behind::WriteBehindMetrics},
+    state::AppState,
+};
+#[cfg(feature = "payments")]
+use crate::models::{
+    dispute::{DisputeCase, DisputeOutcomeRequest, DisputeStatus, SubmitEvidenceRequest},
+    reconciliation::{Discrepancy, DiscrepancyStatus, ReconcileRequest, ReconciliationRun, ResolveDiscrepancyRequest},
+};
+#[cfg(feature = "chaos")]
+use crate::{models::chaos::{FaultRule, SetFaultsRequest}, services::chaos};
+
+#[derive(Debug, Deserialize)]
+pub struct DashboardQuery {
+    pub stale_after_minutes: Option<i64>,
+}
+
+// GET /admin/dashboard?stale_after_minutes=
+pub async fn get_dashboard(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DashboardQuery>,
+) -> Result<Json<OperationsDashboard>, AppError> {
+    let dashboard = state.dashboard_service
+        .get_dashboard(query.stale_after_minutes)
+        .await?;
+    Ok(Json(dashboard))
+}
+
+// GET /admin/users?status=&user_type=&q=&page=&limit=
+pub async fn search_users(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<UserSearchQuery>,
+) -> Result<Json<SearchPage<UserResponse>>, AppError> {
+    Ok(Json(state.directory_service.search_users(query).await?))
+}
+
+// GET /admin/drivers?status=&is_verified=&q=&page=&limit=
+pub async fn search_drivers(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DriverSearchQuery>,
+) -> Result<Json<SearchPage<DriverResponse>>, AppError> {
+    Ok(Json(state.directory_service.search_drivers(query).await?))
+}
+
+// POST /admin/drivers/:id/onboarding/advance
+pub async fn advance_onboarding(
+    State(state): State<Arc<AppState>>,
+    Path(driver_id): Path<String>,
+    Json(review): Json<OnboardingReview>,
+) -> Result<Json<OnboardingStatus>, AppError> {
+    let status = state.onboarding_service
+        .advance(&DriverId::parse(&driver_id)?, review)
+        .await?;
+    Ok(Json(status))
+}
+
+// POST /admin/drivers/:id/onboarding/reject
+pub async fn reject_onboarding(
+    State(state): State<Arc<AppState>>,
+    Path(driver_id): Path<String>,
+    Json(review): Json<OnboardingReview>,
+) -> Result<Json<OnboardingStatus>, AppError> {
+    let status = state.onboarding_service
+        .reject(&DriverId::parse(&driver_id)?, review)
+        .await?;
+    Ok(Json(status))
+}
+
+// POST /admin/users/:id/ban
+pub async fn ban_user(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    Json(request): Json<BanRequest>,
+) -> Result<Json<UserResponse>, AppError> {
+    let user = state.moderation_service
+        .ban_user(&UserId::parse(&user_id)?, &request.reason)
+        .await?;
+    Ok(Json(user))
+}
+
+// DELETE /admin/users/:id/ban
+pub async fn unban_user(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+) -> Result<Json<UserResponse>, AppError> {
+    let user = state.moderation_service
+        .unban_user(&UserId::parse(&user_id)?)
+        .await?;
+    Ok(Json(user))
+}
+
+// POST /admin/drivers/:id/ban
+pub async fn ban_driver(
+    State(state): State<Arc<AppState>>,
+    Path(driver_id): Path<String>,
+    Json(request): Json<BanRequest>,
+) -> Result<Json<DriverResponse>, AppError> {
+    let driver = state.moderation_service
+        .ban_driver(&DriverId::parse(&driver_id)?, &request.reason)
+        .await?;
+    Ok(Json(driver))
+}
+
+// DELETE /admin/drivers/:id/ban
+pub async fn unban_driver(
+    State(state): State<Arc<AppState>>,
+    Path(driver_id): Path<String>,
+) -> Result<Json<DriverResponse>, AppError> {
+    let driver = state.moderation_service
+        .unban_driver(&DriverId::parse(&driver_id)?)
+        .await?;
+    Ok(Json(driver))
+}
+
+// GET /admin/write-behind
+pub async fn get_write_behind_metrics(
+    State(state): State<Arc<AppState>>,
+) -> Json<WriteBehindMetrics> {
+    Json(state.write_behind.metrics())
+}
+
+// GET /admin/send-queues
+// Outbound WebSocket frames across this instance's connections, and the consumers cut off
+pub async fn get_send_queue_metrics(
+    State(state): State<Arc<AppState>>,
+) -> Json<SendQueueMetrics> {
+    Json(state.send_queues.metrics())
+}
+
+// GET /admin/canary
+// Recent synthetic canary runs with their step timings
+pub async fn get_canary_status(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<CanaryStatus>, AppError> {
+    let status = state.canary_service.status().await?;
+    Ok(Json(status))
+}
+
+// GET /admin/chaos/faults
+// Faults being injected on this instance; only in builds with the `chaos` feature
+#[cfg(feature = "chaos")]
+pub async fn get_faults() -> Json<Vec<FaultRule>> {
+    Json(chaos::faults().rules())
+}
+
+// PUT /admin/chaos/faults
+#[cfg(feature = "chaos")]
+pub async fn set_faults(
+    Json(request): Json<SetFaultsRequest>,
+) -> Result<Json<Vec<FaultRule>>, AppError> {
+    let rules = chaos::faults().set_rules(request)?;
+    Ok(Json(rules))
+}
+
+// DELETE /admin/chaos/faults
+#[cfg(feature = "chaos")]
+pub async fn clear_faults() -> Json<Vec<FaultRule>> {
+    chaos::faults().clear();
+    Json(Vec::new())
+}
+
+// GET /admin/presence
+// Drivers and customers holding a live connection to any instance
+pub async fn get_presence(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<PresenceSnapshot>, AppError> {
+    Ok(Json(state.presence_service.snapshot().await?))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub format: Option<String>,
+}
+
+// GET /admin/exports/jobs?from=&to=&format=csv
+pub async fn export_jobs(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    let format = ExportFormat::parse(query.format.as_deref())?;
+    let stream = state.export_service.export_jobs(query.from, query.to)?;
+    Ok(export_response("jobs", &query, format, stream))
+}
+
+// GET /admin/exports/drivers?from=&to=&format=csv
+pub async fn export_drivers(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    let format = ExportFormat::parse(query.format.as_deref())?;
+    let stream = state.export_service.export_drivers(query.from, query.to).await?;
+    Ok(export_response("drivers", &query, format, stream))
+}
+
+// GET /admin/exports/earnings?from=&to=&format=csv
+pub async fn export_earnings(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    let format = ExportFormat::parse(query.format.as_deref())?;
+    let stream = state.export_service.export_earnings(query.from, query.to).await?;
+    Ok(export_response("earnings", &query, format, stream))
+}
+
+// Chunked download; errors after the first byte abort the transfer
+fn export_response(report: &str, query: &ExportQuery, format: ExportFormat, stream: ExportStream) -> Response {
+    let filename = format!("{}_{}_{}.{}", report, query.from, query.to, format.extension());
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiKeyQuery {
+    pub merchant_id: String,
+}
+
+// POST /admin/api-keys
+pub async fn issue_api_key(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> Result<Json<IssuedApiKey>, AppError> {
+    let issued = state.api_key_service.issue_key(request).await?;
+    Ok(Json(issued))
+}
+
+// GET /admin/api-keys?merchant_id=
+pub async fn list_api_keys(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ApiKeyQuery>,
+) -> Result<Json<Vec<ApiKeyResponse>>, AppError> {
+    let merchant_id = UserId::parse(&query.merchant_id)?;
+    let keys = state.api_key_service.list_keys(&merchant_id).await?;
+    Ok(Json(keys))
+}
+
+// POST /admin/api-keys/:id/rotate
+pub async fn rotate_api_key(
+    State(state): State<Arc<AppState>>,
+    Path(key_id): Path<String>,
+) -> Result<Json<IssuedApiKey>, AppError> {
+    let issued = state.api_key_service.rotate_key(&key_id).await?;
+    Ok(Json(issued))
+}
+
+// GET /admin/api-keys/:id/usage
+pub async fn get_api_key_usage(
+    State(state): State<Arc<AppState>>,
+    Path(key_id): Path<String>,
+) -> Result<Json<ApiKeyUsage>, AppError> {
+    let api_key = state.api_key_service.get_key(&key_id).await?;
+    let usage = state.quota_service.usage(&api_key).await?;
+    Ok(Json(usage))
+}
+
+// PUT /admin/api-keys/:id/quotas
+pub async fn update_api_key_quotas(
+    State(state): State<Arc<AppState>>,
+    Path(key_id): Path<String>,
+    Json(request): Json<UpdateQuotasRequest>,
+) -> Result<Json<ApiKeyResponse>, AppError> {
+    let key = state.api_key_service.update_quotas(&key_id, request).await?;
+    Ok(Json(key))
+}
+
+// DELETE /admin/api-keys/:id
+pub async fn revoke_api_key(
+    State(state): State<Arc<AppState>>,
+    Path(key_id): Path<String>,
+) -> Result<Json<ApiKeyResponse>, AppError> {
+    let revoked = state.api_key_service.revoke_key(&key_id).await?;
+    Ok(Json(revoked))
+}
+
+// POST /admin/tenants
+pub async fn create_tenant(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateTenantRequest>,
+) -> Result<Json<Tenant>, AppError> {
+    let tenant = state.tenant_service.create_tenant(request).await?;
+    Ok(Json(tenant))
+}
+
+// GET /admin/tenants
+pub async fn list_tenants(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<Tenant>>, AppError> {
+    let tenants = state.tenant_service.list_tenants().await?;
+    Ok(Json(tenants))
+}
+
+// GET /admin/commissions
+pub async fn get_commissions(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<CommissionConfig>, AppError> {
+    let commissions = state.earnings_calculator.commissions().await?;
+    Ok(Json(commissions))
+}
+
+// PUT /admin/commissions
+pub async fn update_commissions(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<UpdateCommissionsRequest>,
+) -> Result<Json<CommissionConfig>, AppError> {
+    let commissions = state.earnings_calculator.update_commissions(request).await?;
+    // Driver apps show earnings on offers, so have them pick up the new rates
+    if let Err(e) = state.driver_service.push_config_update("commissions").await {
+        tracing::warn!("Failed to push commission update to drivers: {}", e);
+    }
+    Ok(Json(commissions))
+}
+
+// GET /admin/dispatch-settings
+pub async fn get_dispatch_settings(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<DispatchSettings>, AppError> {
+    let settings = state.dispatch_settings.settings().await?;
+    Ok(Json(settings))
+}
+
+// PUT /admin/dispatch-settings
+pub async fn update_dispatch_settings(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<UpdateDispatchSettingsRequest>,
+) -> Result<Json<DispatchSettings>, AppError> {
+    let settings = state.dispatch_settings.update_settings(request).await?;
+    Ok(Json(settings))
+}
+
+// GET /admin/retention/policies
+pub async fn get_retention_policies(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<RetentionSettings>, AppError> {
+    let settings = state.retention_service.settings().await?;
+    Ok(Json(settings))
+}
+
+// PUT /admin/retention/policies
+pub async fn update_retention_policies(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<UpdateRetentionSettingsRequest>,
+) -> Result<Json<RetentionSettings>, AppError> {
+    let settings = state.retention_service.update_settings(request).await?;
+    Ok(Json(settings))
+}
+
+// GET /admin/retention/report
+// What the purge would remove if it ran now; changes nothing
+pub async fn get_retention_report(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<RetentionReport>, AppError> {
+    let report = state.retention_service.enforce(Utc::now(), true).await?;
+    Ok(Json(report))
+}
+
+// GET /admin/retention/holds
+pub async fn list_legal_holds(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<LegalHold>>, AppError> {
+    let holds = state.retention_service.holds().await?;
+    Ok(Json(holds))
+}
+
+// POST /admin/jobs/:id/delivery-code/override
+pub async fn override_delivery_code(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+    Json(request): Json<OverrideDeliveryCodeRequest>,
+) -> Result<Json<JobResponse>, AppError> {
+    let job = state.job_service.override_delivery_code(&JobId::parse(&job_id)?, request).await?;
+    Ok(Json(job))
+}
+
+// POST /admin/retention/holds
+pub async fn place_legal_hold(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<PlaceLegalHoldRequest>,
+) -> Result<Json<LegalHold>, AppError> {
+    let hold = state.retention_service.place_hold(request).await?;
+    Ok(Json(hold))
+}
+
+// DELETE /admin/retention/holds/:job_id
+pub async fn release_legal_hold(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> Result<Json<LegalHold>, AppError> {
+    let hold = state.retention_service.release_hold(&JobId::parse(&job_id)?).await?;
+    Ok(Json(hold))
+}
+
+// GET /admin/zones
+pub async fn list_zones(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<ServiceZone>>, AppError> {
+    let zones = state.zone_service.list_zones().await?;
+    Ok(Json(zones))
+}
+
+// POST /admin/zones
+pub async fn create_zone(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateZoneRequest>,
+) -> Result<Json<ServiceZone>, AppError> {
+    let zone = state.zone_service.create_zone(request).await?;
+    Ok(Json(zone))
+}
+
+// GET /admin/zones/:id
+pub async fn get_zone(
+    State(state): State<Arc<AppState>>,
+    Path(zone_id): Path<String>,
+) -> Result<Json<ServiceZone>, AppError> {
+    let zone = state.zone_service.get_zone(&zone_id).await?;
+    Ok(Json(zone))
+}
+
+// PUT /admin/zones/:id
+pub async fn update_zone(
+    State(state): State<Arc<AppState>>,
+    Path(zone_id): Path<String>,
+    Json(request): Json<UpdateZoneRequest>,
+) -> Result<Json<ServiceZone>, AppError> {
+    let zone = state.zone_service.update_zone(&zone_id, request).await?;
+    Ok(Json(zone))
+}
+
+// DELETE /admin/zones/:id
+pub async fn delete_zone(
+    State(state): State<Arc<AppState>>,
+    Path(zone_id): Path<String>,
+) -> Result<Json<ServiceZone>, AppError> {
+    let zone = state.zone_service.delete_zone(&zone_id).await?;
+    Ok(Json(zone))
+}
+
+// POST /admin/zones/:id/activate
+pub async fn activate_zone(
+    State(state): State<Arc<AppState>>,
+    Path(zone_id): Path<String>,
+    Json(request): Json<ActivateZoneRequest>,
+) -> Result<Json<ServiceZone>, AppError> {
+    let zone = state.zone_service.activate(&zone_id, request.version).await?;
+    Ok(Json(zone))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ZoneLookupQuery {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+// GET /admin/zones/lookup?latitude=&longitude=
+pub async fn lookup_zone(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ZoneLookupQuery>,
+) -> Result<Json<Option<ZoneMatch>>, AppError> {
+    let found = state.zone_service.zone_at(query.latitude, query.longitude).await?;
+    Ok(Json(found))
+}
+
+// GET /admin/taxes
+pub async fn list_tax_schedules(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<TaxSchedule>>, AppError> {
+    let schedules = state.tax_engine.schedules().await?;
+    Ok(Json(schedules))
+}
+
+// POST /admin/taxes
+pub async fn add_tax_schedule(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateTaxScheduleRequest>,
+) -> Result<Json<Vec<TaxSchedule>>, AppError> {
+    let schedules = state.tax_engine.add_schedule(request).await?;
+    Ok(Json(schedules))
+}
+
+// GET /admin/calendar
+pub async fn list_calendar_periods(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<CalendarPeriod>>, AppError> {
+    let periods = state.calendar_service.periods().await?;
+    Ok(Json(periods))
+}
+
+// POST /admin/calendar
+pub async fn add_calendar_period(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateCalendarPeriodRequest>,
+) -> Result<Json<CalendarPeriod>, AppError> {
+    let period = state.calendar_service.add_period(request).await?;
+    Ok(Json(period))
+}
+
+// DELETE /admin/calendar/:id
+pub async fn remove_calendar_period(
+    State(state): State<Arc<AppState>>,
+    Path(period_id): Path<String>,
+) -> Result<Json<CalendarPeriod>, AppError> {
+    let period = state.calendar_service.remove_period(&period_id).await?;
+    Ok(Json(period))
+}
+
+// POST /admin/ledger/entries
+pub async fn post_ledger_entry(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateLedgerEntryRequest>,
+) -> Result<Json<LedgerEntry>, AppError> {
+    Ok(Json(state.ledger_service.post(request).await?))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LedgerQuery {
+    pub currency: Option<Currency>, // Defaults to cedis
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+// GET /admin/ledger/accounts/:account?currency=
+pub async fn get_account_balance(
+    State(state): State<Arc<AppState>>,
+    Path(account): Path<String>,
+    Query(query): Query<LedgerQuery>,
+) -> Result<Json<AccountBalance>, AppError> {
+    let account: LedgerAccount = account.parse()?;
+    let balance = state.ledger_service.balance(&account, query.currency.unwrap_or_default()).await?;
+    Ok(Json(balance))
+}
+
+// GET /admin/ledger/accounts/:account/statement?currency=&from=&to=
+pub async fn get_account_statement(
+    State(state): State<Arc<AppState>>,
+    Path(account): Path<String>,
+    Query(query): Query<LedgerQuery>,
+) -> Result<Json<AccountStatement>, AppError> {
+    let account: LedgerAccount = account.parse()?;
+    let statement = state.ledger_service
+        .statement(&account, query.currency.unwrap_or_default(), query.from, query.to)
+        .await?;
+    Ok(Json(statement))
+}
+
+// GET /admin/ledger/jobs/:id
+pub async fn get_job_ledger(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> Result<Json<Vec<LedgerEntry>>, AppError> {
+    let entries = state.ledger_service.job_entries(&JobId::parse(&job_id)?).await?;
+    Ok(Json(entries))
+}
+
+#[cfg(feature = "payments")]
+#[derive(Debug, Deserialize)]
+pub struct DiscrepancyQuery {
+    pub status: Option<DiscrepancyStatus>,
+}
+
+// GET /admin/reconciliation?status=open
+#[cfg(feature = "payments")]
+pub async fn list_discrepancies(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DiscrepancyQuery>,
+) -> Result<Json<Vec<Discrepancy>>, AppError> {
+    Ok(Json(state.reconciliation_service.discrepancies(query.status).await?))
+}
+
+// POST /admin/reconciliation/run
+#[cfg(feature = "payments")]
+pub async fn run_reconciliation(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ReconcileRequest>,
+) -> Result<Json<ReconciliationRun>, AppError> {
+    Ok(Json(state.reconciliation_service.reconcile_day(request.day).await?))
+}
+
+// POST /admin/reconciliation/:id/resolve
+#[cfg(feature = "payments")]
+pub async fn resolve_discrepancy(
+    State(state): State<Arc<AppState>>,
+    Path(discrepancy_id): Path<String>,
+    Json(request): Json<ResolveDiscrepancyRequest>,
+) -> Result<Json<Discrepancy>, AppError> {
+    let discrepancy = state.reconciliation_service.resolve(&discrepancy_id, request).await?;
+    Ok(Json(discrepancy))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IncidentQuery {
+    pub status: Option<IncidentStatus>,
+}
+
+// GET /admin/incidents?status=open
+pub async fn list_incidents(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<IncidentQuery>,
+) -> Result<Json<Vec<Incident>>, AppError> {
+    Ok(Json(state.incident_service.incidents(query.status).await?))
+}
+
+// GET /admin/incidents/:id - with its timeline
+pub async fn get_incident(
+    State(state): State<Arc<AppState>>,
+    Path(incident_id): Path<String>,
+) -> Result<Json<Incident>, AppError> {
+    Ok(Json(state.incident_service.get_incident(&incident_id).await?))
+}
+
+// POST /admin/incidents/:id/updates
+pub async fn add_incident_update(
+    State(state): State<Arc<AppState>>,
+    Path(incident_id): Path<String>,
+    Json(request): Json<IncidentUpdateRequest>,
+) -> Result<Json<Incident>, AppError> {
+    Ok(Json(state.incident_service.add_update(&incident_id, request).await?))
+}
+
+// GET /admin/consumers - how far each event consumer has got, and what it parked
+pub async fn list_consumers(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<ConsumerStatus>>, AppError> {
+    Ok(Json(state.event_bus.consumer_status().await?))
+}
+
+// POST /admin/consumers/:group/replay - deliver the events after an offset again
+pub async fn replay_consumer(
+    State(state): State<Arc<AppState>>,
+    Path(group): Path<String>,
+    Json(request): Json<ReplayRequest>,
+) -> Result<Json<ConsumerStatus>, AppError> {
+    Ok(Json(state.event_bus.replay(&group, &request.from).await?))
+}
+
+// GET /admin/sagas - completions and refunds still in progress, or stuck
+pub async fn list_open_sagas(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<SagaState>>, AppError> {
+    Ok(Json(state.job_service.open_sagas().await?))
+}
+
+// POST /admin/jobs/:id/refund - refund a paid, delivered job; 409 while a refund of it is
+// still in progress or if it was rolled back, in which case it can be asked for again
+pub async fn refund_job(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> Result<Json<JobResponse>, AppError> {
+    Ok(Json(state.job_service.refund_job(&JobId::parse(&job_id)?).await?))
+}
+
+// GET /admin/regions - the markets this deployment serves
+pub async fn list_regions(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<RegionSummary>>, AppError> {
+    Ok(Json(state.regions.summaries()))
+}
+
+// GET /admin/risk/drivers - drivers flagged for spoofing their location
+pub async fn list_flagged_drivers(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<RiskFlag>>, AppError> {
+    Ok(Json(state.risk_service.flagged_drivers().await?))
+}
+
+// GET /admin/risk/drivers/:id - strikes, flag and quarantined points
+pub async fn get_driver_risk(
+    State(state): State<Arc<AppState>>,
+    Path(driver_id): Path<String>,
+) -> Result<Json<DriverRiskReport>, AppError> {
+    Ok(Json(state.risk_service.driver_report(&DriverId::parse(&driver_id)?).await?))
+}
+
+// DELETE /admin/risk/drivers/:id
+pub async fn clear_driver_risk(
+    State(state): State<Arc<AppState>>,
+    Path(driver_id): Path<String>,
+) -> Result<Json<DriverRiskReport>, AppError> {
+    Ok(Json(state.risk_service.clear_flag(&DriverId::parse(&driver_id)?).await?))
+}
+
+#[cfg(feature = "payments")]
+#[derive(Debug, Deserialize)]
+pub struct DisputeQuery {
+    pub status: Option<DisputeStatus>,
+}
+
+// GET /admin/disputes?status=open
+#[cfg(feature = "payments")]
+pub async fn list_disputes(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DisputeQuery>,
+) -> Result<Json<Vec<DisputeCase>>, AppError> {
+    Ok(Json(state.dispute_service.cases(query.status).await?))
+}
+
+// GET /admin/disputes/:id
+#[cfg(feature = "payments")]
+pub async fn get_dispute(
+    State(state): State<Arc<AppState>>,
+    Path(case_id): Path<String>,
+) -> Result<Json<DisputeCase>, AppError> {
+    Ok(Json(state.dispute_service.get_case(&case_id).await?))
+}
+
+// POST /admin/disputes/:id/evidence
+#[cfg(feature = "payments")]
+pub async fn submit_dispute_evidence(
+    State(state): State<Arc<AppState>>,
+    Path(case_id): Path<String>,
+    Json(request): Json<SubmitEvidenceRequest>,
+) -> Result<Json<DisputeCase>, AppError> {
+    Ok(Json(state.dispute_service.submit_evidence(&case_id, request).await?))
+}
+
+// POST /admin/disputes/:id/outcome
+#[cfg(feature = "payments")]
+pub async fn record_dispute_outcome(
+    State(state): State<Arc<AppState>>,
+    Path(case_id): Path<String>,
+    Json(request): Json<DisputeOutcomeRequest>,
+) -> Result<Json<DisputeCase>, AppError> {
+    Ok(Json(state.dispute_service.record_outcome(&case_id, request).await?))
+}
+
+// GET /admin/billing/:user_id
+pub async fn get_billing_account(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+) -> Result<Json<BillingAccount>, AppError> {
+    Ok(Json(state.invoice_service.billing_account(&UserId::parse(&user_id)?).await?))
+}
+
+// PUT /admin/billing/:user_id
+pub async fn update_billing_account(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    Json(request): Json<UpdateBillingAccountRequest>,
+) -> Result<Json<BillingAccount>, AppError> {
+    let account = state.invoice_service.update_billing_account(&UserId::parse(&user_id)?, request).await?;
+    Ok(Json(account))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InvoiceQuery {
+    pub status: Option<InvoiceStatus>,
+}
+
+// GET /admin/invoices?status=overdue
+pub async fn list_invoices(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<InvoiceQuery>,
+) -> Result<Json<Vec<Invoice>>, AppError> {
+    Ok(Json(state.invoice_service.invoices(query.status).await?))
+}
+
+// GET /admin/invoices/:id
+pub async fn get_invoice(
+    State(state): State<Arc<AppState>>,
+    Path(invoice_id): Path<String>,
+) -> Result<Json<Invoice>, AppError> {
+    Ok(Json(state.invoice_service.get_invoice(&invoice_id).await?))
+}
+
+// POST /admin/invoices/:id/payment
+pub async fn record_invoice_payment(
+    State(state): State<Arc<AppState>>,
+    Path(invoice_id): Path<String>,
+    Json(request): Json<RecordInvoicePaymentRequest>,
+) -> Result<Json<Invoice>, AppError> {
+    Ok(Json(state.invoice_service.record_payment(&invoice_id, request).await?))
+}
+
+// GET /admin/currencies
+pub async fn list_currencies() -> Json<&'static [CurrencyInfo]> {
+    Json(CURRENCIES)
+}
+
+// GET /admin/exchange-rates
+pub async fn get_exchange_rates(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ExchangeRates>, AppError> {
+    let rates = state.exchange_rates.rates().await?;
+    Ok(Json(rates))
+}
+
+// PUT /admin/exchange-rates
+pub async fn update_exchange_rates(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<UpdateExchangeRatesRequest>,
+) -> Result<Json<ExchangeRates>, AppError> {
+    let rates = state.exchange_rates.update_rates(request).await?;
+    Ok(Json(rates))
+}
+
+// POST /admin/notifications/broadcast
+pub async fn broadcast_notification(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<NotificationBroadcastRequest>,
+) -> Result<Json<NotificationBroadcastResponse>, AppError> {
+    if request.title.trim().is_empty() {
+        return Err(AppError::validation_error("title", "Must not be empty"));
+    }
+    let response = state.notification_batcher.broadcast(request).await?;
+    Ok(Json(response))
+}
+
+// GET /admin/broadcasts
+pub async fn list_broadcasts(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<Broadcast>>, AppError> {
+    let broadcasts = state.broadcast_service.list_broadcasts().await?;
+    Ok(Json(broadcasts))
+}
+
+// POST /admin/broadcasts
+pub async fn create_broadcast(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateBroadcastRequest>,
+) -> Result<Json<Broadcast>, AppError> {
+    let broadcast = state.broadcast_service.create_broadcast(request).await?;
+    Ok(Json(broadcast))
+}
+
+// GET /admin/notification-templates
+pub async fn list_notification_templates(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<NotificationTemplate>>, AppError> {
+    Ok(Json(state.notification_templates.list().await?))
+}
+
+// PUT /admin/notification-templates/:type/:language
+pub async fn put_notification_template(
+    State(state): State<Arc<AppState>>,
+    Path((notification_type, language)): Path<(NotificationType, String)>,
+    Json(request): Json<NotificationTemplateRequest>,
+) -> Result<Json<NotificationTemplate>, AppError> {
+    let template = state.notification_templates.upsert(notification_type, &language, request).await?;
+    announce_config_change(&state).await;
+    Ok(Json(template))
+}
+
+// DELETE /admin/notification-templates/:type/:language
+// Messages of this type and language go back to the built-in copy
+pub async fn delete_notification_template(
+    State(state): State<Arc<AppState>>,
+    Path((notification_type, language)): Path<(NotificationType, String)>,
+) -> Result<Json<NotificationTemplate>, AppError> {
+    let removed = state.notification_templates.delete(notification_type, &language).await?;
+    announce_config_change(&state).await;
+    Ok(Json(removed))
+}
+
+// GET /admin/startup
+pub async fn get_startup_report(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<StartupReport>, AppError> {
+    let report = state.startup.get().cloned()
+        .ok_or_else(|| AppError::NotFound("This instance was started without dependency checks".to_string()))?;
+    Ok(Json(report))
+}
+
+// GET /admin/config
+pub async fn get_runtime_config(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<RuntimeConfigSnapshot>, AppError> {
+    Ok(Json(state.runtime_config.current().as_ref().clone()))
+}
+
+// POST /admin/config/reload
+// Rereads the runtime config file here, then has every other instance do the same
+pub async fn reload_runtime_config(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<RuntimeConfigSnapshot>, AppError> {
+    let snapshot = state.runtime_config.reload().await?;
+    announce_config_change(&state).await;
+    Ok(Json(snapshot.as_ref().clone()))
+}
+
+// Other instances pick the change up on their next reload anyway, so failing to tell
+// them doesn't fail the request
+async fn announce_config_change(state: &AppState) {
+    if let Err(e) = state.runtime_config.announce().await {
+        tracing::warn!("Failed to announce config change: {}", e);
+    }
+}
+
+// POST /admin/notification-templates/preview
+pub async fn preview_notification_template(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<TemplatePreviewRequest>,
+) -> Result<Json<TemplatePreview>, AppError> {
+    Ok(Json(state.notification_templates.preview(request)?))
+}
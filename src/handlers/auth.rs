@@ -0,0 +1,205 @@
+// src/handlers/auth.rs
+// Request authentication extractors, and the route layer enforcing declared scopes
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request, State},
+    http::{header, request::Parts, HeaderMap, Method},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{api_key::{ApiKey, ApiScope}, driver::DriverResponse, ids::{DriverId, UserId}, scope::{grants_any, Scope, READ, WRITE}, user::User},
+    services::driver_service::DriverOperations,
+    state::AppState,
+};
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+// Whoever the request's credentials belong to. The scope layer leaves it in the request
+// extensions, so the extractors below don't authenticate (and rate-limit) a second time.
+#[derive(Clone)]
+pub enum Principal {
+    ApiKey(ApiKey),
+    Session(User),
+}
+
+impl Principal {
+    pub fn grants(&self, required: &Scope) -> bool {
+        match self {
+            Principal::ApiKey(api_key) => api_key.grants(required),
+            Principal::Session(user) => grants_any(&user.scopes(), required),
+        }
+    }
+
+    async fn authenticate(headers: &HeaderMap, state: &AppState) -> Result<Self, AppError> {
+        if let Some(secret) = headers.get(API_KEY_HEADER).and_then(|value| value.to_str().ok()) {
+            return Ok(Principal::ApiKey(state.api_key_service.authenticate(secret.trim()).await?));
+        }
+        match bearer_token(headers) {
+            Some(token) => Ok(Principal::Session(state.user_service.authenticate_session(token).await?)),
+            None => Err(AppError::unauthorized("Missing API key or bearer token")),
+        }
+    }
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+// A route's requirement: a full scope such as "realtime:connect", or just a resource, in
+// which case GET and HEAD need "<resource>:read" and everything else "<resource>:write"
+#[derive(Clone)]
+pub struct RequiredScope {
+    pub state: Arc<AppState>,
+    pub scope: &'static str,
+}
+
+impl RequiredScope {
+    pub fn new(state: &Arc<AppState>, scope: &'static str) -> Self {
+        Self { state: state.clone(), scope }
+    }
+
+    fn for_method(&self, method: &Method) -> Scope {
+        match self.scope.split_once(':') {
+            Some((resource, action)) => Scope::new(resource, action),
+            None if method == Method::GET || method == Method::HEAD => Scope::new(self.scope, READ),
+            None => Scope::new(self.scope, WRITE),
+        }
+    }
+}
+
+pub async fn require_scope(
+    State(required): State<RequiredScope>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let scope = required.for_method(request.method());
+    let principal = Principal::authenticate(request.headers(), &required.state).await?;
+    if !principal.grants(&scope) {
+        tracing::debug!("Request to {} refused, {} not granted", request.uri().path(), scope);
+        return Err(AppError::InsufficientPermissions);
+    }
+    request.extensions_mut().insert(principal);
+    Ok(next.run(request).await)
+}
+
+// Merchant authenticated with the `X-Api-Key` header
+pub struct ApiKeyAuth(pub ApiKey);
+
+impl ApiKeyAuth {
+    pub fn require(&self, scope: ApiScope) -> Result<&ApiKey, AppError> {
+        if self.0.has_scope(&scope) {
+            Ok(&self.0)
+        } else {
+            Err(AppError::InsufficientPermissions)
+        }
+    }
+
+    pub fn merchant_id(&self) -> &UserId {
+        &self.0.merchant_id
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for ApiKeyAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+        if let Some(Principal::ApiKey(api_key)) = parts.extensions.get::<Principal>() {
+            return Ok(ApiKeyAuth(api_key.clone()));
+        }
+        let secret = parts
+            .headers
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| AppError::unauthorized("Missing X-Api-Key header"))?;
+
+        let api_key = state.api_key_service.authenticate(secret.trim()).await?;
+        Ok(ApiKeyAuth(api_key))
+    }
+}
+
+// Dispatcher console user, authenticated with a key carrying the `Dispatch` scope
+pub struct DispatcherAuth(pub ApiKey);
+
+impl DispatcherAuth {
+    pub fn dispatcher_id(&self) -> &UserId {
+        &self.0.merchant_id
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.0.id
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for DispatcherAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+        let auth = ApiKeyAuth::from_request_parts(parts, state).await?;
+        auth.require(ApiScope::Dispatch)?;
+        Ok(DispatcherAuth(auth.0))
+    }
+}
+
+// App user, authenticated with their login token as `Authorization: Bearer <token>`
+pub struct SessionAuth(pub User);
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for SessionAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+        if let Some(Principal::Session(user)) = parts.extensions.get::<Principal>() {
+            return Ok(SessionAuth(user.clone()));
+        }
+        let token = bearer_token(&parts.headers).ok_or_else(|| AppError::unauthorized("Missing bearer token"))?;
+        let user = state.user_service.authenticate_session(token).await?;
+        Ok(SessionAuth(user))
+    }
+}
+
+// Only ever acted on by the account itself
+pub fn own_account(session_user: &UserId, user_id: &str) -> Result<UserId, AppError> {
+    let user_id = UserId::parse(user_id)?;
+    if &user_id != session_user {
+        return Err(AppError::Forbidden("Not your account".to_string()));
+    }
+    Ok(user_id)
+}
+
+// Driver app, signed in with the login of the user the driver was registered for
+pub struct DriverAuth(pub DriverResponse);
+
+impl DriverAuth {
+    /// The driver a `/drivers/:id/...` path names, as long as it's this one
+    pub fn own(&self, driver_id: &str) -> Result<DriverId, AppError> {
+        let driver_id = DriverId::parse(driver_id)?;
+        if driver_id != self.0.id {
+            return Err(AppError::Forbidden("Not your driver account".to_string()));
+        }
+        Ok(driver_id)
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for DriverAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+        let SessionAuth(user) = SessionAuth::from_request_parts(parts, state).await?;
+        let driver = state.driver_service
+            .get_driver_by_user_id(&user.id)
+            .await?
+            .ok_or_else(|| AppError::Forbidden("Not a driver account".to_string()))?;
+        Ok(DriverAuth(driver))
+    }
+}
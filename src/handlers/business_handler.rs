@@ -0,0 +1,94 @@
+// src/handlers/business_handler.rs
+// Staff and shared addresses of a business account, managed by its staff from the app with
+// their own logins; what each role may do is checked in services::business_accounts
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use std::sync::Arc;
+
+use crate::{
+    errors::SparrowError as AppError,
+    handlers::auth::SessionAuth,
+    models::{business::{AddStaffRequest, SharedAddressRequest, StaffMember}, ids::UserId, user::Address},
+    state::AppState,
+};
+
+// GET /businesses/:id/staff
+pub async fn list_staff(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(business_id): Path<String>,
+) -> Result<Json<Vec<StaffMember>>, AppError> {
+    let staff = state.business_accounts.staff(&UserId::parse(&business_id)?, &user.id).await?;
+    Ok(Json(staff))
+}
+
+// POST /businesses/:id/staff
+pub async fn add_staff(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(business_id): Path<String>,
+    Json(request): Json<AddStaffRequest>,
+) -> Result<Json<StaffMember>, AppError> {
+    let member = state.business_accounts.add_staff(&UserId::parse(&business_id)?, &user.id, request).await?;
+    Ok(Json(member))
+}
+
+// DELETE /businesses/:id/staff/:user_id
+pub async fn remove_staff(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path((business_id, user_id)): Path<(String, String)>,
+) -> Result<Json<Vec<StaffMember>>, AppError> {
+    let staff = state.business_accounts
+        .remove_staff(&UserId::parse(&business_id)?, &user.id, &UserId::parse(&user_id)?)
+        .await?;
+    Ok(Json(staff))
+}
+
+// GET /businesses/:id/addresses
+pub async fn list_addresses(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(business_id): Path<String>,
+) -> Result<Json<Vec<Address>>, AppError> {
+    let addresses = state.business_accounts.addresses(&UserId::parse(&business_id)?, &user.id).await?;
+    Ok(Json(addresses))
+}
+
+// POST /businesses/:id/addresses
+pub async fn add_address(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(business_id): Path<String>,
+    Json(request): Json<SharedAddressRequest>,
+) -> Result<Json<Address>, AppError> {
+    let address = state.business_accounts.add_address(&UserId::parse(&business_id)?, &user.id, request).await?;
+    Ok(Json(address))
+}
+
+// PUT /businesses/:id/addresses/:address_id
+pub async fn update_address(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path((business_id, address_id)): Path<(String, String)>,
+    Json(request): Json<SharedAddressRequest>,
+) -> Result<Json<Address>, AppError> {
+    let address = state.business_accounts
+        .update_address(&UserId::parse(&business_id)?, &user.id, &address_id, request)
+        .await?;
+    Ok(Json(address))
+}
+
+// DELETE /businesses/:id/addresses/:address_id
+pub async fn remove_address(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path((business_id, address_id)): Path<(String, String)>,
+) -> Result<Json<Vec<Address>>, AppError> {
+    let addresses = state.business_accounts
+        .remove_address(&UserId::parse(&business_id)?, &user.id, &address_id)
+        .await?;
+    Ok(Json(addresses))
+}
@@ -0,0 +1,123 @@
+// src/handlers/dispatch_handler.rs
+// Dispatcher console, authenticated by an API key with the `Dispatch` scope
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{
+    errors::SparrowError as AppError,
+    handlers::auth::DispatcherAuth,
+    models::{
+        admin::StaleJob,
+        dispatch::{BroadcastRequest, BroadcastResponse, CandidateDriver, DispatchAuditEntry, ForceAssignRequest, ForceUnassignRequest},
+        ids::JobId,
+        job::{JobPool, JobResponse},
+    },
+    services::dispatcher_service::Dispatcher,
+    state::AppState,
+};
+
+fn dispatcher(auth: &DispatcherAuth) -> Dispatcher<'_> {
+    Dispatcher {
+        user_id: auth.dispatcher_id(),
+        api_key_id: auth.key_id(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnassignedQuery {
+    pub older_than_minutes: Option<i64>,
+}
+
+// GET /dispatch/jobs/unassigned?older_than_minutes=
+pub async fn list_unassigned_jobs(
+    State(state): State<Arc<AppState>>,
+    _auth: DispatcherAuth,
+    Query(query): Query<UnassignedQuery>,
+) -> Result<Json<Vec<StaleJob>>, AppError> {
+    let jobs = state.dispatcher_service
+        .unassigned_jobs(query.older_than_minutes)
+        .await?;
+    Ok(Json(jobs))
+}
+
+// GET /dispatch/jobs/:id/candidates
+pub async fn list_candidates(
+    State(state): State<Arc<AppState>>,
+    _auth: DispatcherAuth,
+    Path(job_id): Path<String>,
+) -> Result<Json<Vec<CandidateDriver>>, AppError> {
+    let drivers = state.dispatcher_service
+        .candidate_drivers(&JobId::parse(&job_id)?)
+        .await?;
+    Ok(Json(drivers))
+}
+
+// POST /dispatch/jobs/:id/assign
+pub async fn force_assign(
+    State(state): State<Arc<AppState>>,
+    auth: DispatcherAuth,
+    Path(job_id): Path<String>,
+    Json(request): Json<ForceAssignRequest>,
+) -> Result<Json<JobResponse>, AppError> {
+    let job = state.dispatcher_service
+        .force_assign(&dispatcher(&auth), &JobId::parse(&job_id)?, &request.driver_id, request.reason)
+        .await?;
+    Ok(Json(job))
+}
+
+// POST /dispatch/jobs/:id/unassign
+pub async fn force_unassign(
+    State(state): State<Arc<AppState>>,
+    auth: DispatcherAuth,
+    Path(job_id): Path<String>,
+    request: Option<Json<ForceUnassignRequest>>,
+) -> Result<Json<JobResponse>, AppError> {
+    let Json(request) = request.unwrap_or_default();
+    let job = state.dispatcher_service
+        .force_unassign(&dispatcher(&auth), &JobId::parse(&job_id)?, request.reason)
+        .await?;
+    Ok(Json(job))
+}
+
+// POST /dispatch/jobs/:id/broadcast
+pub async fn broadcast_job(
+    State(state): State<Arc<AppState>>,
+    auth: DispatcherAuth,
+    Path(job_id): Path<String>,
+    request: Option<Json<BroadcastRequest>>,
+) -> Result<Json<BroadcastResponse>, AppError> {
+    let Json(request) = request.unwrap_or_default();
+    let broadcast = state.dispatcher_service
+        .broadcast(&dispatcher(&auth), &JobId::parse(&job_id)?, request.zone)
+        .await?;
+    Ok(Json(broadcast))
+}
+
+// POST /dispatch/pools
+// Runs a pooling pass over the jobs still waiting for a driver
+pub async fn pool_jobs(
+    State(state): State<Arc<AppState>>,
+    _auth: DispatcherAuth,
+) -> Result<Json<Vec<JobPool>>, AppError> {
+    let pools = state.pooling_service.pool_pending_jobs().await?;
+    Ok(Json(pools))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    pub since_hours: Option<i64>,
+}
+
+// GET /dispatch/audit?since_hours=
+pub async fn get_audit_log(
+    State(state): State<Arc<AppState>>,
+    _auth: DispatcherAuth,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<DispatchAuditEntry>>, AppError> {
+    let entries = state.dispatcher_service.audit_log(query.since_hours).await?;
+    Ok(Json(entries))
+}
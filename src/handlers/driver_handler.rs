@@ -0,0 +1,422 @@
+// src/handlers/driver_handler.rs
+use axum::{
+    extract::{ws::{Message, WebSocket, WebSocketUpgrade}, Path, Query, State},
+    response::Response,
+    Json,
+};
+use chrono::Utc;
+use futures::{stream::SplitSink, SinkExt, StreamExt};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::{
+    errors::SparrowError as AppError,
+    handlers::{auth::{own_account, DriverAuth, SessionAuth}, fields::{FieldsQuery, Projected, DRIVER_FIELDS, JOB_FIELDS}},
+    models::{
+        demand::DriverHeatmap,
+        incident::{Incident, SosRequest},
+        odometer::{DriverDistanceStats, VehicleMaintenance},
+        dispatch::{DriverSocketEvent, DriverSocketReply},
+        ids::{DriverId, UserId},
+        moderation::BlockCustomerRequest,
+        job::{AvailableJob, JobHistoryPage, JobHistoryQuery},
+        driver::{DocumentSubmission, DriverEquipment, DriverLocationBatch, DriverRegistration, DriverResponse, DriverStatusUpdate, LocationBatchResponse, OnboardingStatus, StartBreakRequest},
+    },
+    services::{
+        driver_service::DriverOperations,
+        job_service::JobOperations,
+        realtime::{FrameFormat, CBOR_SUBPROTOCOL, JSON_SUBPROTOCOL},
+        send_queue::SendQueue,
+        region::{current_region_id, with_region},
+        tenant_service::{current_tenant_id, with_tenant},
+    },
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct DriverQuery {
+    pub id: String,
+}
+
+// GET /drivers?id=&fields=
+pub async fn get_driver(
+    State(state): State<Arc<AppState>>,
+    auth: DriverAuth,
+    Query(query): Query<DriverQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Projected<DriverResponse>, AppError> {
+    let selection = fields.selection(DRIVER_FIELDS)?;
+    let driver_id = auth.own(&query.id)?;
+    let driver = state.driver_service
+        .get_driver(&driver_id)
+        .await?
+        .ok_or_else(|| AppError::driver_not_found(driver_id))?;
+    Ok(Projected::new(driver, selection))
+}
+
+// GET /drivers/:id/profile?fields=
+// The driver's own view, with the reliability breakdown behind their dispatch priority
+pub async fn get_driver_profile(
+    State(state): State<Arc<AppState>>,
+    auth: DriverAuth,
+    Path(driver_id): Path<String>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Projected<DriverResponse>, AppError> {
+    let selection = fields.selection(DRIVER_FIELDS)?;
+    let driver = state.driver_service
+        .get_driver_profile(&auth.own(&driver_id)?)
+        .await?;
+    Ok(Projected::new(driver, selection))
+}
+
+// GET /drivers/:id/jobs?cursor=&limit=&fields=
+pub async fn list_jobs(
+    State(state): State<Arc<AppState>>,
+    auth: DriverAuth,
+    Path(driver_id): Path<String>,
+    Query(query): Query<JobHistoryQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Projected<JobHistoryPage>, AppError> {
+    let selection = fields.selection(JOB_FIELDS)?;
+    let page = state.job_service
+        .get_jobs_by_driver(&auth.own(&driver_id)?, query)
+        .await?;
+    Ok(Projected::page(page, selection))
+}
+
+// GET /drivers/:id/available-jobs
+pub async fn get_available_jobs(
+    State(state): State<Arc<AppState>>,
+    auth: DriverAuth,
+    Path(driver_id): Path<String>,
+) -> Result<Json<Vec<AvailableJob>>, AppError> {
+    let jobs = state.job_service
+        .get_available_jobs(&auth.own(&driver_id)?)
+        .await?;
+    Ok(Json(jobs))
+}
+
+// POST /drivers - signed in as the user the driver is registered for
+pub async fn create_driver(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Json(registration): Json<DriverRegistration>,
+) -> Result<Json<DriverResponse>, AppError> {
+    own_account(&user.id, registration.user_id.as_str())?;
+    let driver = state.driver_service.register_driver(registration).await?;
+    Ok(Json(driver))
+}
+
+// GET /drivers/:id/onboarding
+pub async fn get_onboarding(
+    State(state): State<Arc<AppState>>,
+    auth: DriverAuth,
+    Path(driver_id): Path<String>,
+) -> Result<Json<OnboardingStatus>, AppError> {
+    let status = state.onboarding_service
+        .status(&auth.own(&driver_id)?)
+        .await?;
+    Ok(Json(status))
+}
+
+// POST /drivers/:id/onboarding/documents
+pub async fn submit_documents(
+    State(state): State<Arc<AppState>>,
+    auth: DriverAuth,
+    Path(driver_id): Path<String>,
+    Json(submission): Json<DocumentSubmission>,
+) -> Result<Json<OnboardingStatus>, AppError> {
+    let status = state.onboarding_service
+        .submit_documents(&auth.own(&driver_id)?, submission.documents)
+        .await?;
+    Ok(Json(status))
+}
+
+// GET /drivers/:id/blocked-customers
+pub async fn list_blocked_customers(
+    State(state): State<Arc<AppState>>,
+    auth: DriverAuth,
+    Path(driver_id): Path<String>,
+) -> Result<Json<Vec<UserId>>, AppError> {
+    let blocked = state.moderation_service
+        .blocked_customers(&auth.own(&driver_id)?)
+        .await?;
+    Ok(Json(blocked))
+}
+
+// POST /drivers/:id/blocked-customers
+pub async fn block_customer(
+    State(state): State<Arc<AppState>>,
+    auth: DriverAuth,
+    Path(driver_id): Path<String>,
+    Json(request): Json<BlockCustomerRequest>,
+) -> Result<Json<Vec<UserId>>, AppError> {
+    let blocked = state.moderation_service
+        .block_customer(&auth.own(&driver_id)?, &request.user_id)
+        .await?;
+    Ok(Json(blocked))
+}
+
+// DELETE /drivers/:id/blocked-customers/:user_id
+pub async fn unblock_customer(
+    State(state): State<Arc<AppState>>,
+    auth: DriverAuth,
+    Path((driver_id, user_id)): Path<(String, String)>,
+) -> Result<Json<Vec<UserId>>, AppError> {
+    let blocked = state.moderation_service
+        .unblock_customer(&auth.own(&driver_id)?, &UserId::parse(&user_id)?)
+        .await?;
+    Ok(Json(blocked))
+}
+
+// POST /drivers/:id/locations/batch
+pub async fn batch_update_locations(
+    State(state): State<Arc<AppState>>,
+    auth: DriverAuth,
+    Path(driver_id): Path<String>,
+    Json(batch): Json<DriverLocationBatch>,
+) -> Result<Json<LocationBatchResponse>, AppError> {
+    let response = state.location_service
+        .ingest_batch(&auth.own(&driver_id)?, batch.locations)
+        .await?;
+    Ok(Json(response))
+}
+
+// POST /drivers/:id/break/start
+pub async fn start_break(
+    State(state): State<Arc<AppState>>,
+    auth: DriverAuth,
+    Path(driver_id): Path<String>,
+    request: Option<Json<StartBreakRequest>>,
+) -> Result<Json<DriverResponse>, AppError> {
+    let Json(request) = request.unwrap_or_default();
+    let driver = state.driver_service
+        .start_break(&auth.own(&driver_id)?, request.minutes)
+        .await?;
+    Ok(Json(driver))
+}
+
+// POST /drivers/:id/break/end
+pub async fn end_break(
+    State(state): State<Arc<AppState>>,
+    auth: DriverAuth,
+    Path(driver_id): Path<String>,
+) -> Result<Json<DriverResponse>, AppError> {
+    let driver = state.driver_service
+        .end_break(&auth.own(&driver_id)?)
+        .await?;
+    Ok(Json(driver))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HeatmapQuery {
+    pub window_minutes: Option<i64>,
+    pub precision: Option<usize>,
+}
+
+// POST /drivers/:id/sos - the body is optional, so a bare press still gets through
+pub async fn driver_sos(
+    State(state): State<Arc<AppState>>,
+    auth: DriverAuth,
+    Path(driver_id): Path<String>,
+    request: Option<Json<SosRequest>>,
+) -> Result<Json<Incident>, AppError> {
+    let Json(request) = request.unwrap_or_default();
+    let incident = state.incident_service
+        .driver_sos(&auth.own(&driver_id)?, request)
+        .await?;
+    Ok(Json(incident))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DistanceQuery {
+    pub days: Option<i64>,
+}
+
+// GET /drivers/:id/distance?days=
+pub async fn get_distance(
+    State(state): State<Arc<AppState>>,
+    auth: DriverAuth,
+    Path(driver_id): Path<String>,
+    Query(query): Query<DistanceQuery>,
+) -> Result<Json<DriverDistanceStats>, AppError> {
+    let stats = state.odometer
+        .stats(&auth.own(&driver_id)?, query.days)
+        .await?;
+    Ok(Json(stats))
+}
+
+// POST /drivers/:id/vehicle/service
+pub async fn record_vehicle_service(
+    State(state): State<Arc<AppState>>,
+    auth: DriverAuth,
+    Path(driver_id): Path<String>,
+) -> Result<Json<VehicleMaintenance>, AppError> {
+    let maintenance = state.odometer
+        .record_service(&auth.own(&driver_id)?)
+        .await?;
+    Ok(Json(maintenance))
+}
+
+// PUT /drivers/:id/equipment
+pub async fn update_equipment(
+    State(state): State<Arc<AppState>>,
+    auth: DriverAuth,
+    Path(driver_id): Path<String>,
+    Json(equipment): Json<DriverEquipment>,
+) -> Result<Json<DriverResponse>, AppError> {
+    let driver = state.driver_service
+        .update_equipment(&auth.own(&driver_id)?, equipment)
+        .await?;
+    Ok(Json(driver))
+}
+
+// GET /drivers/heatmap?window_minutes=&precision=
+pub async fn get_heatmap(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HeatmapQuery>,
+) -> Result<Json<DriverHeatmap>, AppError> {
+    let heatmap = state.demand_service
+        .get_heatmap(query.window_minutes, query.precision)
+        .await?;
+    Ok(Json(heatmap))
+}
+
+// How often an open socket checks its offers for lapsed deadlines
+const OFFER_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+// GET /ws/drivers/:id
+// Live channel for job offers, the driver's answers to them, and their locations and status.
+// Frames are JSON text unless the client asks for the CBOR subprotocol.
+pub async fn driver_socket(
+    State(state): State<Arc<AppState>>,
+    auth: DriverAuth,
+    Path(driver_id): Path<String>,
+    socket: WebSocketUpgrade,
+) -> Result<Response, AppError> {
+    let driver_id = auth.own(&driver_id)?;
+    let driver = state.driver_service
+        .get_driver(&driver_id)
+        .await?
+        .ok_or_else(|| AppError::driver_not_found(driver_id.clone()))?;
+    if driver.is_banned {
+        return Err(AppError::Forbidden("Driver is banned".to_string()));
+    }
+    // The socket outlives the request, and with it the tenant and region scopes
+    let tenant_id = current_tenant_id();
+    let region_id = current_region_id();
+    Ok(socket
+        .protocols([CBOR_SUBPROTOCOL, JSON_SUBPROTOCOL])
+        .on_upgrade(move |socket| with_region(region_id, with_tenant(tenant_id, run_driver_socket(state, driver_id, socket)))))
+}
+
+async fn run_driver_socket(state: Arc<AppState>, driver_id: DriverId, socket: WebSocket) {
+    let format = FrameFormat::from_subprotocol(socket.protocol().and_then(|protocol| protocol.to_str().ok()));
+    let (connection_id, mut events) = match state.driver_channel.connect(&driver_id).await {
+        Ok(connection) => connection,
+        Err(e) => {
+            tracing::error!("Failed to open channel for driver {}: {}", driver_id, e);
+            return;
+        }
+    };
+    let (sink, mut stream) = socket.split();
+    let queue = Arc::new(state.send_queues.queue());
+    let mut writer = tokio::spawn(write_frames(queue.clone(), driver_id.clone(), format, sink));
+    let mut sweep = tokio::time::interval(OFFER_SWEEP_INTERVAL);
+    let mut heartbeat = tokio::time::interval(state.presence_service.heartbeat_interval());
+
+    loop {
+        let event = tokio::select! {
+            event = events.recv() => match event {
+                Some(event) => event,
+                None => break, // Replaced by a newer connection
+            },
+            frame = stream.next() => match frame {
+                Some(Ok(Message::Text(text))) => decode_reply(&state, &driver_id, FrameFormat::Json, text.as_bytes()).await,
+                Some(Ok(Message::Binary(bytes))) => decode_reply(&state, &driver_id, FrameFormat::Cbor, &bytes).await,
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue, // Pings are answered by axum
+            },
+            _ = sweep.tick() => {
+                match state.driver_channel.expire_offers(&driver_id, Utc::now()).await {
+                    Ok(expired) => for job_id in expired {
+                        state.driver_channel.send(&driver_id, DriverSocketEvent::OfferExpired { job_id }).await;
+                    },
+                    Err(e) => tracing::warn!("Failed to expire offers of driver {}: {}", driver_id, e),
+                }
+                continue;
+            }
+            _ = heartbeat.tick() => {
+                if let Err(e) = state.driver_channel.heartbeat(&driver_id, connection_id).await {
+                    tracing::warn!("Failed to refresh presence of driver {}: {}", driver_id, e);
+                }
+                continue;
+            }
+            _ = &mut writer => break, // The socket closed or fell too far behind
+        };
+        if queue.push(event).is_err() {
+            tracing::warn!("Closing socket of driver {}: too slow to keep up", driver_id);
+            break;
+        }
+    }
+
+    writer.abort();
+    state.driver_channel.disconnect(&driver_id, connection_id).await;
+}
+
+// Drains the connection's queue, so a stalled write holds up nothing but this socket
+async fn write_frames(
+    queue: Arc<SendQueue>,
+    driver_id: DriverId,
+    format: FrameFormat,
+    mut sink: SplitSink<WebSocket, Message>,
+) {
+    loop {
+        let event = queue.pop().await;
+        let frame = match format.encode(&event) {
+            Ok(bytes) if format == FrameFormat::Json => Message::Text(String::from_utf8_lossy(&bytes).into_owned()),
+            Ok(bytes) => Message::Binary(bytes),
+            Err(e) => {
+                tracing::error!("Failed to encode event for driver {}: {}", driver_id, e);
+                continue;
+            }
+        };
+        let started = Instant::now();
+        if sink.send(frame).await.is_err() {
+            return;
+        }
+        if matches!(event, DriverSocketEvent::SessionRevoked { .. }) {
+            let _ = sink.close().await;
+            return;
+        }
+        if queue.written(started.elapsed()).is_err() {
+            tracing::warn!("Closing socket of driver {}: writes keep stalling", driver_id);
+            return;
+        }
+    }
+}
+
+// Either format is read whatever was negotiated; replies go back in the negotiated one
+async fn decode_reply(state: &AppState, driver_id: &DriverId, format: FrameFormat, bytes: &[u8]) -> DriverSocketEvent {
+    match format.decode::<DriverSocketReply>(bytes) {
+        Ok(reply) => answer_reply(state, driver_id, reply).await,
+        Err(e) => DriverSocketEvent::Error { job_id: None, message: format!("Unrecognised message: {}", e) },
+    }
+}
+
+async fn answer_reply(state: &AppState, driver_id: &DriverId, reply: DriverSocketReply) -> DriverSocketEvent {
+    let answered = match reply {
+        DriverSocketReply::Accept { job_id } => return state.dispatcher_service.answer_offer(driver_id, job_id, true).await,
+        DriverSocketReply::Reject { job_id } => return state.dispatcher_service.answer_offer(driver_id, job_id, false).await,
+        DriverSocketReply::Locations { locations } => state.location_service
+            .ingest_batch(driver_id, locations)
+            .await
+            .map(|response| DriverSocketEvent::LocationsReceived { received: response.received, accepted: response.accepted }),
+        DriverSocketReply::Status { status } => state.driver_service
+            .update_driver_status(DriverStatusUpdate { driver_id: driver_id.clone(), status, location: None })
+            .await
+            .map(|driver| DriverSocketEvent::StatusUpdated { status: driver.status }),
+    };
+    answered.unwrap_or_else(|e| DriverSocketEvent::Error { job_id: None, message: e.to_string() })
+}
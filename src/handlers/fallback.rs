@@ -0,0 +1,85 @@
+// src/handlers/fallback.rs
+// Last-resort responses: unknown routes and handler panics both come back as the usual
+// error JSON instead of an empty body or a dropped connection
+use axum::{
+    http::{Method, Uri},
+    response::{IntoResponse, Response},
+};
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::panic;
+
+use crate::{
+    errors::SparrowError as AppError,
+    handlers::request_id::current_request_id,
+};
+
+// Any /unknown path
+pub async fn not_found(method: Method, uri: Uri) -> AppError {
+    AppError::NotFound(format!("No route for {} {}", method, uri.path()))
+}
+
+// Passed to `CatchPanicLayer::custom`. The panic itself is logged by the hook below,
+// which still has the backtrace; the client only learns that something broke.
+pub fn handle_panic(payload: Box<dyn Any + Send + 'static>) -> Response {
+    tracing::error!(
+        request_id = current_request_id().as_deref().unwrap_or("-"),
+        "Handler panicked: {}",
+        panic_message(payload.as_ref())
+    );
+    AppError::internal_error("Unexpected error while handling the request").into_response()
+}
+
+/// Log panics through tracing with their backtrace and the request they happened in.
+/// Call once at startup, before serving.
+pub fn install_panic_hook() {
+    panic::set_hook(Box::new(|info| {
+        let location = info
+            .location()
+            .map(|location| location.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        tracing::error!(
+            request_id = current_request_id().as_deref().unwrap_or("-"),
+            location = %location,
+            "panic: {}\n{}",
+            panic_message(info.payload()),
+            Backtrace::force_capture()
+        );
+    }));
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "non-string panic payload"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+
+    #[test]
+    fn test_panic_becomes_internal_error() {
+        let response = handle_panic(Box::new("boom"));
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn test_panic_message() {
+        assert_eq!(panic_message(&"static"), "static");
+        assert_eq!(panic_message(&"owned".to_string()), "owned");
+        assert_eq!(panic_message(&42), "non-string panic payload");
+    }
+
+    #[tokio::test]
+    async fn test_not_found_names_route() {
+        let error = not_found(Method::GET, Uri::from_static("/nowhere?x=1")).await;
+        assert!(matches!(&error, AppError::NotFound(msg) if msg == "No route for GET /nowhere"));
+        assert_eq!(error.into_response().status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,164 @@
+// src/handlers/fields.rs
+// `?fields=id,status,pricing.total` on job and driver reads: the response keeps only the
+// named fields, so list views don't pay for whole packages and locations. Each endpoint
+// names the top-level fields that may be asked for; a dotted path picks a field inside one
+// of them. Without `fields` the response is unchanged.
+use axum::{
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::errors::SparrowError as AppError;
+
+pub const JOB_FIELDS: &[&str] = &[
+    "id", "customer_id", "driver_id", "status", "priority", "pickup_location", "dropoff_location",
+    "estimated_distance_km", "estimated_duration_min", "package", "package_photo", "created_at",
+    "pickup_time", "dropoff_time", "promised_by", "escalation", "recipient_preferences", "pool_id",
+    "delivery_code", "dropoff_change", "arrival_calls", "frozen_by", "region", "pricing", "payment_status", "tracking_code", "notes", "rating",
+];
+
+pub const DRIVER_FIELDS: &[&str] = &[
+    "id", "first_name", "last_name", "phone_number", "status", "current_location", "vehicle",
+    "equipment", "rating", "total_rides", "is_verified", "onboarding_state", "suspended_reason",
+    "is_banned", "current_ride_id", "queued_job_id", "break_ends_at", "last_seen_at", "reliability",
+];
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FieldsQuery {
+    pub fields: Option<String>,
+}
+
+impl FieldsQuery {
+    /// The fields asked for, checked against `allowed`; none means everything
+    pub fn selection(&self, allowed: &[&str]) -> Result<Option<FieldSelection>, AppError> {
+        self.fields.as_deref().map(|fields| FieldSelection::parse(fields, allowed)).transpose()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSelection {
+    paths: Vec<Vec<String>>,
+}
+
+impl FieldSelection {
+    pub fn parse(fields: &str, allowed: &[&str]) -> Result<Self, AppError> {
+        let mut paths = Vec::new();
+        for field in fields.split(',').map(str::trim).filter(|field| !field.is_empty()) {
+            let path: Vec<String> = field.split('.').map(str::to_string).collect();
+            if path.iter().any(String::is_empty) || !allowed.contains(&path[0].as_str()) {
+                return Err(AppError::validation_error(
+                    "fields",
+                    format!("Cannot select {}; choose from {}", field, allowed.join(", ")),
+                ));
+            }
+            paths.push(path);
+        }
+        if paths.is_empty() {
+            return Err(AppError::validation_error("fields", "Name at least one field"));
+        }
+        Ok(Self { paths })
+    }
+
+    /// Only the selected fields of `value`, or of each element when it is a list. Fields
+    /// that are absent, such as a path into a null, are left out.
+    pub fn project(&self, value: Value) -> Value {
+        match value {
+            Value::Array(items) => Value::Array(items.into_iter().map(|item| self.project(item)).collect()),
+            value => {
+                let mut projected = Map::new();
+                for path in &self.paths {
+                    if let Some(found) = lookup(&value, path) {
+                        insert(&mut projected, path, found.clone());
+                    }
+                }
+                Value::Object(projected)
+            }
+        }
+    }
+}
+
+fn lookup<'a>(value: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |value, key| value.get(key))
+}
+
+fn insert(target: &mut Map<String, Value>, path: &[String], value: Value) {
+    let (last, parents) = path.split_last().expect("paths are never empty");
+    let mut target = target;
+    for key in parents {
+        let entry = target.entry(key.clone()).or_insert_with(|| Value::Object(Map::new()));
+        if !entry.is_object() {
+            // Already selected whole by a shorter path
+            return;
+        }
+        target = entry.as_object_mut().expect("checked above");
+    }
+    target.insert(last.clone(), value);
+}
+
+/// A response trimmed to the requested fields, or whole when none were asked for
+pub struct Projected<T> {
+    value: T,
+    selection: Option<FieldSelection>,
+    items_only: bool,
+}
+
+impl<T> Projected<T> {
+    pub fn new(value: T, selection: Option<FieldSelection>) -> Self {
+        Self { value, selection, items_only: false }
+    }
+
+    /// A page envelope whose `items` are trimmed; the cursor and the rest are kept
+    pub fn page(value: T, selection: Option<FieldSelection>) -> Self {
+        Self { value, selection, items_only: true }
+    }
+}
+
+impl<T: Serialize> IntoResponse for Projected<T> {
+    fn into_response(self) -> Response {
+        let Some(selection) = self.selection else {
+            return Json(self.value).into_response();
+        };
+        match serde_json::to_value(self.value) {
+            Ok(Value::Object(mut page)) if self.items_only => {
+                if let Some(items) = page.remove("items") {
+                    page.insert("items".to_string(), selection.project(items));
+                }
+                Json(Value::Object(page)).into_response()
+            }
+            Ok(value) => Json(selection.project(value)).into_response(),
+            Err(e) => AppError::from(e).into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_projection_keeps_selected_paths_and_rejects_others() {
+        let job = json!({
+            "id": "job_1",
+            "status": "Pending",
+            "pricing": {"total": {"minor": 4500, "currency": "GHS"}, "tip": {"minor": 0, "currency": "GHS"}},
+            "tracking_code": "SPR-1",
+            "driver_id": null,
+        });
+        let selection = FieldSelection::parse("id, pricing.total,tracking_code,driver_id.name", JOB_FIELDS).unwrap();
+        let expected = json!({"id": "job_1", "pricing": {"total": {"minor": 4500, "currency": "GHS"}}, "tracking_code": "SPR-1"});
+        assert_eq!(selection.project(job.clone()), expected);
+        assert_eq!(selection.project(json!([job])), json!([expected]));
+
+        // A whole object and a path inside it give the whole object
+        let selection = FieldSelection::parse("pricing,pricing.total", JOB_FIELDS).unwrap();
+        assert_eq!(selection.project(json!({"pricing": {"total": 1, "tip": 0}}))["pricing"], json!({"total": 1, "tip": 0}));
+
+        assert!(FieldSelection::parse("id,customer.secret", JOB_FIELDS).is_err());
+        assert!(FieldSelection::parse("pricing..total", JOB_FIELDS).is_err());
+        assert!(FieldSelection::parse(" , ", JOB_FIELDS).is_err());
+        assert!(FieldSelection::parse("tracking_code", DRIVER_FIELDS).is_err());
+    }
+}
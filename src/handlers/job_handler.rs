@@ -0,0 +1,308 @@
+// src/handlers/job_handler.rs
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap},
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{
+    errors::SparrowError as AppError,
+    handlers::{auth::{own_account, ApiKeyAuth, DriverAuth, SessionAuth}, fields::{FieldsQuery, Projected, JOB_FIELDS}},
+    models::{ids::JobId, job::{ApproveDropoffChangeRequest, BulkJobRequest, BulkJobResponse, ChangeDropoffRequest, ConfirmDeliveryRequest, DriverJobStatusRequest, DropoffChange, HandoffCodes, HandoffScanRequest, JobBatchStatus, JobEstimate, JobEstimateRequest, JobPool, JobRequest, JobResponse, JobRoute, NavigationPlan, ShareTripRequest, SharedTripView, TrackingView, TripShare, UpdateRecipientPreferencesRequest}, quota::QuotaMetric, user::User},
+    services::{driver_service::DriverOperations, job_service::{parse_job_manifest, JobOperations}, region::{current_region_id, with_region}},
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct JobQuery {
+    pub id: String,
+}
+
+// The job, as long as the session belongs to its customer or its driver
+async fn job_for_party(state: &AppState, user: &User, job_id: &str) -> Result<JobResponse, AppError> {
+    let job_id = JobId::parse(job_id)?;
+    let job = state.job_service
+        .get_job(&job_id)
+        .await?
+        .ok_or_else(|| AppError::job_not_found(job_id))?;
+    if job.customer_id == user.id {
+        return Ok(job);
+    }
+    if let Some(driver_id) = &job.driver_id {
+        let driver = state.driver_service.get_driver_by_user_id(&user.id).await?;
+        if driver.is_some_and(|driver| &driver.id == driver_id) {
+            return Ok(job);
+        }
+    }
+    Err(AppError::Forbidden("Not your job".to_string()))
+}
+
+// GET /jobs?id=&fields=
+pub async fn get_job(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Query(query): Query<JobQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Projected<JobResponse>, AppError> {
+    let selection = fields.selection(JOB_FIELDS)?;
+    let job = job_for_party(&state, &user, &query.id).await?;
+    Ok(Projected::new(job, selection))
+}
+
+// POST /jobs - booked in the region of its pickup. A saved pickup address is looked
+// up in the request's own region, so it stays there.
+pub async fn create_job(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Json(request): Json<JobRequest>,
+) -> Result<Json<JobResponse>, AppError> {
+    own_account(&user.id, request.customer_id.as_str())?;
+    let region_id = match &request.pickup_location {
+        Some(pickup) => state.regions.for_country(&pickup.country)?.id.clone(),
+        None => current_region_id(),
+    };
+    let job = with_region(region_id, state.job_service.create_job(request)).await?;
+    Ok(Json(job))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssignDriverRequest {
+    pub driver_id: String,
+}
+
+// POST /jobs/:id/assign - a driver taking the job for themselves
+pub async fn assign_driver(
+    State(state): State<Arc<AppState>>,
+    driver: DriverAuth,
+    Path(job_id): Path<String>,
+    Json(request): Json<AssignDriverRequest>,
+) -> Result<Json<JobResponse>, AppError> {
+    let job_id = JobId::parse(&job_id)?;
+    let driver_id = driver.own(&request.driver_id)?;
+    let job = state.job_service.assign_driver_to_job(&job_id, &driver_id).await?;
+    Ok(Json(job))
+}
+
+// POST /jobs/estimate - priced in the region of the pickup
+pub async fn estimate_job(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<JobEstimateRequest>,
+) -> Result<Json<JobEstimate>, AppError> {
+    let region_id = state.regions.for_country(&request.pickup_location.country)?.id.clone();
+    let estimate = with_region(region_id, state.job_service.calculate_estimate(request)).await?;
+    Ok(Json(estimate))
+}
+
+// POST /jobs/:id/complete
+pub async fn complete_job(
+    State(state): State<Arc<AppState>>,
+    DriverAuth(driver): DriverAuth,
+    Path(job_id): Path<String>,
+) -> Result<Json<JobResponse>, AppError> {
+    let job = state.job_service.complete_job_as_driver(&JobId::parse(&job_id)?, &driver.id).await?;
+    Ok(Json(job))
+}
+
+// POST /jobs/:id/status
+pub async fn update_job_status(
+    State(state): State<Arc<AppState>>,
+    DriverAuth(driver): DriverAuth,
+    Path(job_id): Path<String>,
+    Json(request): Json<DriverJobStatusRequest>,
+) -> Result<Json<JobResponse>, AppError> {
+    let job = state.job_service.advance_job_as_driver(&JobId::parse(&job_id)?, &driver.id, request).await?;
+    Ok(Json(job))
+}
+
+// POST /jobs/:id/delivery-code
+pub async fn confirm_delivery(
+    State(state): State<Arc<AppState>>,
+    DriverAuth(driver): DriverAuth,
+    Path(job_id): Path<String>,
+    Json(request): Json<ConfirmDeliveryRequest>,
+) -> Result<Json<JobResponse>, AppError> {
+    let job = state.job_service.confirm_delivery(&JobId::parse(&job_id)?, &driver.id, request).await?;
+    Ok(Json(job))
+}
+
+// PATCH /jobs/:id/dropoff - priced, but only applied once approved
+pub async fn change_dropoff(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(job_id): Path<String>,
+    Json(request): Json<ChangeDropoffRequest>,
+) -> Result<Json<DropoffChange>, AppError> {
+    let change = state.job_service.request_dropoff_change(&JobId::parse(&job_id)?, &user.id, request).await?;
+    Ok(Json(change))
+}
+
+// POST /jobs/:id/dropoff/approve
+pub async fn approve_dropoff_change(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(job_id): Path<String>,
+    Json(request): Json<ApproveDropoffChangeRequest>,
+) -> Result<Json<JobResponse>, AppError> {
+    let job = state.job_service.approve_dropoff_change(&JobId::parse(&job_id)?, &user.id, request).await?;
+    Ok(Json(job))
+}
+
+// GET /jobs/:id/handoff-codes
+pub async fn get_handoff_codes(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(job_id): Path<String>,
+) -> Result<Json<HandoffCodes>, AppError> {
+    let codes = state.handoff_service.codes(&JobId::parse(&job_id)?, &user.id).await?;
+    Ok(Json(codes))
+}
+
+// POST /jobs/:id/scan
+pub async fn scan_handoff(
+    State(state): State<Arc<AppState>>,
+    DriverAuth(driver): DriverAuth,
+    Path(job_id): Path<String>,
+    Json(request): Json<HandoffScanRequest>,
+) -> Result<Json<JobResponse>, AppError> {
+    let job = state.handoff_service.scan(&JobId::parse(&job_id)?, &driver.id, request).await?;
+    Ok(Json(job))
+}
+
+// GET /jobs/:id/pool
+// The pool the job shares a driver with, and the order of its stops
+pub async fn get_job_pool(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(job_id): Path<String>,
+) -> Result<Json<JobPool>, AppError> {
+    let job = job_for_party(&state, &user, &job_id).await?;
+    let pool_id = job.pool_id.ok_or_else(|| AppError::NotFound("Job is not pooled".to_string()))?;
+    let pool = state.pooling_service.get_pool(&pool_id).await?
+        .ok_or_else(|| AppError::NotFound("Pool not found".to_string()))?;
+    Ok(Json(pool))
+}
+
+// GET /track/:tracking_code
+pub async fn track_delivery(
+    State(state): State<Arc<AppState>>,
+    Path(tracking_code): Path<String>,
+) -> Result<Json<TrackingView>, AppError> {
+    let view = state.tracking_service.track(&tracking_code).await?;
+    Ok(Json(view))
+}
+
+// PUT /track/:tracking_code/preferences
+pub async fn update_recipient_preferences(
+    State(state): State<Arc<AppState>>,
+    Path(tracking_code): Path<String>,
+    Json(request): Json<UpdateRecipientPreferencesRequest>,
+) -> Result<Json<TrackingView>, AppError> {
+    let view = state.tracking_service.update_preferences(&tracking_code, request).await?;
+    Ok(Json(view))
+}
+
+// POST /jobs/:id/share
+pub async fn share_trip(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(job_id): Path<String>,
+    request: Option<Json<ShareTripRequest>>,
+) -> Result<Json<TripShare>, AppError> {
+    let Json(request) = request.unwrap_or_default();
+    let share = state.tracking_service.share(&JobId::parse(&job_id)?, &user.id, request).await?;
+    Ok(Json(share))
+}
+
+// GET /jobs/:id/share
+pub async fn list_trip_shares(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(job_id): Path<String>,
+) -> Result<Json<Vec<TripShare>>, AppError> {
+    let shares = state.tracking_service.shares(&JobId::parse(&job_id)?, &user.id).await?;
+    Ok(Json(shares))
+}
+
+// DELETE /jobs/:id/share/:token
+pub async fn revoke_trip_share(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path((job_id, token)): Path<(String, String)>,
+) -> Result<Json<Vec<TripShare>>, AppError> {
+    let shares = state.tracking_service.revoke_share(&JobId::parse(&job_id)?, &user.id, &token).await?;
+    Ok(Json(shares))
+}
+
+// GET /shared/:token - public, read-only
+pub async fn get_shared_trip(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> Result<Json<SharedTripView>, AppError> {
+    let view = state.tracking_service.shared_trip(&token).await?;
+    Ok(Json(view))
+}
+
+// GET /jobs/:id/route
+pub async fn get_job_route(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(job_id): Path<String>,
+) -> Result<Json<JobRoute>, AppError> {
+    let job = job_for_party(&state, &user, &job_id).await?;
+    let route = state.route_service.get_route(&job.id).await?;
+    Ok(Json(route))
+}
+
+// GET /jobs/:id/navigation - the driver's remaining stops and Maps links
+pub async fn get_job_navigation(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(job_id): Path<String>,
+) -> Result<Json<NavigationPlan>, AppError> {
+    let job = job_for_party(&state, &user, &job_id).await?;
+    let navigation = state.route_service.get_navigation(&job.id).await?;
+    Ok(Json(navigation))
+}
+
+// POST /jobs/bulk - JSON `{"jobs": [...]}` or a `text/csv` manifest
+pub async fn create_jobs_bulk(
+    State(state): State<Arc<AppState>>,
+    auth: ApiKeyAuth,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<BulkJobResponse>, AppError> {
+    let is_csv = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/csv"));
+
+    let requests = if is_csv {
+        parse_job_manifest(&body)?
+    } else {
+        serde_json::from_slice::<BulkJobRequest>(&body)?.jobs
+    };
+
+    // The whole import has to fit in what's left of the key's quotas
+    state.quota_service.check_room(&auth.0, QuotaMetric::JobsCreated, requests.len() as u64).await?;
+    let response = state.job_service.create_jobs_bulk(auth.merchant_id(), requests).await?;
+    for _ in &response.jobs {
+        state.quota_service.record(&auth.0, QuotaMetric::JobsCreated).await?;
+    }
+    Ok(Json(response))
+}
+
+// GET /jobs/bulk/:batch_id
+pub async fn get_job_batch(
+    State(state): State<Arc<AppState>>,
+    auth: ApiKeyAuth,
+    Path(batch_id): Path<String>,
+) -> Result<Json<JobBatchStatus>, AppError> {
+    let batch = state.job_service
+        .get_job_batch(&batch_id, auth.merchant_id())
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Job batch {} not found", batch_id)))?;
+    Ok(Json(batch))
+}
@@ -0,0 +1,26 @@
+// src/handlers/language.rs
+// Picks the language of a request's responses from its `Accept-Language` header and runs
+// the rest of the stack inside it
+use axum::{
+    extract::Request,
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::services::i18n::{negotiate, with_language};
+
+pub async fn resolve_language(request: Request, next: Next) -> Response {
+    let language = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(negotiate)
+        .unwrap_or_else(|| negotiate(""));
+
+    // Negotiated from our own catalogs, so always a legal header value
+    let content_language = HeaderValue::from_str(&language).expect("language code is a valid header value");
+    let mut response = with_language(language, next.run(request)).await;
+    response.headers_mut().insert(header::CONTENT_LANGUAGE, content_language);
+    response
+}
@@ -0,0 +1,128 @@
+// src/handlers/merchant_handler.rs
+// Server-to-server endpoints for business accounts, authenticated by API key; the scopes
+// each needs are declared with the routes
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use std::sync::Arc;
+
+use crate::{
+    errors::SparrowError as AppError,
+    handlers::{auth::ApiKeyAuth, fields::{FieldsQuery, Projected, JOB_FIELDS}},
+    models::{ids::JobId, invoice::{Invoice, InvoiceDraft}, job::{JobHistoryPage, JobHistoryQuery, JobRequest, JobResponse}, quota::{ApiKeyUsage, QuotaMetric}, status_feed::{FeedSettings, UpdateFeedSettingsRequest}, user::Address},
+    services::job_service::JobOperations,
+    state::AppState,
+};
+
+// POST /merchant/jobs
+pub async fn create_job(
+    State(state): State<Arc<AppState>>,
+    auth: ApiKeyAuth,
+    Json(mut request): Json<JobRequest>,
+) -> Result<Json<JobResponse>, AppError> {
+    // Jobs are always booked on the key owner's account
+    request.customer_id = auth.merchant_id().clone();
+    state.quota_service.check(&auth.0, QuotaMetric::JobsCreated).await?;
+    let job = state.job_service.create_job(request).await?;
+    state.quota_service.record(&auth.0, QuotaMetric::JobsCreated).await?;
+    Ok(Json(job))
+}
+
+// GET /merchant/jobs?cursor=&limit=&fields=
+pub async fn list_jobs(
+    State(state): State<Arc<AppState>>,
+    auth: ApiKeyAuth,
+    Query(query): Query<JobHistoryQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Projected<JobHistoryPage>, AppError> {
+    let selection = fields.selection(JOB_FIELDS)?;
+    let page = state.job_service.get_jobs_by_customer(auth.merchant_id(), query).await?;
+    Ok(Projected::page(page, selection))
+}
+
+// GET /merchant/jobs/:id?fields=
+pub async fn get_job(
+    State(state): State<Arc<AppState>>,
+    auth: ApiKeyAuth,
+    Path(job_id): Path<String>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Projected<JobResponse>, AppError> {
+    let selection = fields.selection(JOB_FIELDS)?;
+    // Other merchants' jobs are reported as missing rather than forbidden
+    let job_id = JobId::parse(&job_id)?;
+    let job = state.job_service
+        .get_job(&job_id)
+        .await?
+        .filter(|job| &job.customer_id == auth.merchant_id())
+        .ok_or_else(|| AppError::job_not_found(job_id))?;
+    Ok(Projected::new(job, selection))
+}
+
+// GET /merchant/addresses - the shared address book, whose IDs jobs can be booked with
+pub async fn list_addresses(
+    State(state): State<Arc<AppState>>,
+    auth: ApiKeyAuth,
+) -> Result<Json<Vec<Address>>, AppError> {
+    let addresses = state.business_accounts.addresses(auth.merchant_id(), auth.merchant_id()).await?;
+    Ok(Json(addresses))
+}
+
+// GET /merchant/feed
+pub async fn get_feed_settings(
+    State(state): State<Arc<AppState>>,
+    auth: ApiKeyAuth,
+) -> Result<Json<FeedSettings>, AppError> {
+    let settings = state.status_feed.settings(auth.merchant_id()).await?;
+    Ok(Json(settings))
+}
+
+// PUT /merchant/feed
+pub async fn update_feed_settings(
+    State(state): State<Arc<AppState>>,
+    auth: ApiKeyAuth,
+    Json(request): Json<UpdateFeedSettingsRequest>,
+) -> Result<Json<FeedSettings>, AppError> {
+    let settings = state.status_feed.update_settings(auth.merchant_id(), request).await?;
+    Ok(Json(settings))
+}
+
+// GET /merchant/invoices
+pub async fn list_invoices(
+    State(state): State<Arc<AppState>>,
+    auth: ApiKeyAuth,
+) -> Result<Json<Vec<Invoice>>, AppError> {
+    let invoices = state.invoice_service.merchant_invoices(auth.merchant_id()).await?;
+    Ok(Json(invoices))
+}
+
+// GET /merchant/invoices/draft
+pub async fn get_draft_invoices(
+    State(state): State<Arc<AppState>>,
+    auth: ApiKeyAuth,
+) -> Result<Json<Vec<InvoiceDraft>>, AppError> {
+    let drafts = state.invoice_service.drafts(auth.merchant_id()).await?;
+    Ok(Json(drafts))
+}
+
+// GET /merchant/invoices/:id
+pub async fn get_invoice(
+    State(state): State<Arc<AppState>>,
+    auth: ApiKeyAuth,
+    Path(invoice_id): Path<String>,
+) -> Result<Json<Invoice>, AppError> {
+    let invoice = state.invoice_service.get_invoice(&invoice_id).await?;
+    if &invoice.merchant_id != auth.merchant_id() {
+        return Err(AppError::NotFound(format!("Invoice {} not found", invoice_id)));
+    }
+    Ok(Json(invoice))
+}
+
+// GET /merchant/usage
+pub async fn get_usage(
+    State(state): State<Arc<AppState>>,
+    auth: ApiKeyAuth,
+) -> Result<Json<ApiKeyUsage>, AppError> {
+    let usage = state.quota_service.usage(&auth.0).await?;
+    Ok(Json(usage))
+}
@@ -0,0 +1,19 @@
+#[cfg(feature = "admin")]
+pub mod admin_handler;
+pub mod auth;
+pub mod business_handler;
+pub mod dispatch_handler;
+pub mod driver_handler;
+pub mod fallback;
+pub mod fields;
+pub mod job_handler;
+pub mod language;
+pub mod merchant_handler;
+#[cfg(feature = "realtime-ably")]
+pub mod realtime_handler;
+pub mod region;
+pub mod request_id;
+pub mod request_log;
+pub mod tenant;
+pub mod user_handler;
+pub mod webhook_handler;
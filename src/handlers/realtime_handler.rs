@@ -0,0 +1,21 @@
+// src/handlers/realtime_handler.rs
+// Credentials for clients that connect to the realtime provider directly
+use axum::{extract::State, Json};
+use std::sync::Arc;
+
+use crate::{
+    errors::SparrowError as AppError,
+    handlers::auth::SessionAuth,
+    models::realtime::AblyTokenRequest,
+    state::AppState,
+};
+
+// POST /realtime/token
+// The Ably SDK's auth URL: called on connect and again before each token expires
+pub async fn issue_token(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+) -> Result<Json<AblyTokenRequest>, AppError> {
+    let request = state.ably_auth.token_request(&user).await?;
+    Ok(Json(request))
+}
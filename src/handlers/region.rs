@@ -0,0 +1,38 @@
+// src/handlers/region.rs
+// Resolves which region a request is served in and runs the rest of the stack inside it
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::region::DEFAULT_REGION_ID,
+    services::region::with_region,
+    state::AppState,
+};
+
+pub const REGION_HEADER: &str = "x-sparrow-region";
+
+// Apps in other markets name their region; everything else is served from the home region.
+// New bookings are moved to the region of their pickup by the job handlers.
+pub async fn resolve_region(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let region_id = request
+        .headers()
+        .get(REGION_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_ascii_lowercase())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| DEFAULT_REGION_ID.to_string());
+
+    let region = state.regions.get(&region_id)
+        .ok_or_else(|| AppError::NotFound(format!("Region {} is not served here", region_id)))?;
+
+    Ok(with_region(region.id.clone(), next.run(request)).await)
+}
@@ -0,0 +1,76 @@
+// src/handlers/request_id.rs
+// Gives every request an ID that is echoed back in the `x-request-id` header and in
+// error bodies, so a client report can be matched to our logs
+use axum::{
+    extract::Request,
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+use std::future::Future;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+// Longest caller-supplied ID we will adopt; anything else gets a fresh one
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: String;
+}
+
+/// ID of the request being handled, if any
+pub fn current_request_id() -> Option<String> {
+    CURRENT_REQUEST_ID.try_with(|request_id| request_id.clone()).ok()
+}
+
+/// Run `future` with `request_id` as the current request ID
+pub async fn with_request_id<F: Future>(request_id: String, future: F) -> F::Output {
+    CURRENT_REQUEST_ID.scope(request_id, future).await
+}
+
+// Keep an upstream proxy's ID when it looks sane so the trail spans both systems
+fn accept_request_id(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= MAX_REQUEST_ID_LEN
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+pub async fn assign_request_id(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| accept_request_id(value))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    // Validated above or generated, so always a legal header value
+    let header_value = HeaderValue::from_str(&request_id).expect("request ID is a valid header value");
+    request.headers_mut().insert(REQUEST_ID_HEADER, header_value.clone());
+
+    let mut response = with_request_id(request_id, next.run(request)).await;
+    response.headers_mut().insert(REQUEST_ID_HEADER, header_value);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_request_id() {
+        assert!(accept_request_id("3f2b9c1e-7a4d-4c1b-9e55-0d6a1c2b3e4f"));
+        assert!(accept_request_id("edge.req_42"));
+        assert!(!accept_request_id(""));
+        assert!(!accept_request_id("has space"));
+        assert!(!accept_request_id(&"a".repeat(MAX_REQUEST_ID_LEN + 1)));
+    }
+
+    #[tokio::test]
+    async fn test_current_request_id_is_scoped() {
+        assert_eq!(current_request_id(), None);
+        let seen = with_request_id("req-1".to_string(), async { current_request_id() }).await;
+        assert_eq!(seen.as_deref(), Some("req-1"));
+    }
+}
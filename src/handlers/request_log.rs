@@ -0,0 +1,184 @@
+// src/handlers/request_log.rs
+// One log line per request. Bodies are opt-in, sampled, and scrubbed of credentials and
+// payment details before they are formatted.
+use axum::{
+    body::{Body, HttpBody},
+    extract::{Request, State},
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::{handlers::request_id::current_request_id, state::AppState};
+
+const REDACTED: &str = "[REDACTED]";
+
+#[derive(Debug, Clone)]
+pub struct RequestLogConfig {
+    pub log_bodies: bool,
+    pub body_sample_rate: f64,   // Fraction of requests whose bodies are logged, 0.0..=1.0
+    pub max_body_bytes: usize,   // Larger or streamed bodies are never buffered
+}
+
+impl Default for RequestLogConfig {
+    fn default() -> Self {
+        Self {
+            log_bodies: false,
+            body_sample_rate: 0.1,
+            max_body_bytes: 16 * 1024,
+        }
+    }
+}
+
+pub async fn log_requests(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let config = &state.config.request_log;
+    let method = request.method().clone();
+    let path = request.uri().path().to_string(); // Query strings can carry tokens; leave them out
+    let started = Instant::now();
+    let with_bodies = config.log_bodies && rand::random::<f64>() < config.body_sample_rate;
+
+    let (request, request_body) = if with_bodies {
+        let (parts, body) = request.into_parts();
+        let (body, logged) = capture_body(body, &parts.headers, config.max_body_bytes).await;
+        (Request::from_parts(parts, body), logged)
+    } else {
+        (request, None)
+    };
+
+    let response = next.run(request).await;
+
+    let (response, response_body) = if with_bodies {
+        let (parts, body) = response.into_parts();
+        let (body, logged) = capture_body(body, &parts.headers, config.max_body_bytes).await;
+        (Response::from_parts(parts, body), logged)
+    } else {
+        (response, None)
+    };
+
+    tracing::info!(
+        request_id = current_request_id().as_deref().unwrap_or("-"),
+        method = %method,
+        path = %path,
+        status = response.status().as_u16(),
+        latency_ms = started.elapsed().as_millis() as u64,
+        request_body = request_body.as_deref(),
+        response_body = response_body.as_deref(),
+        "request"
+    );
+
+    response
+}
+
+// Buffer a small JSON body so it can be logged, handing back an equivalent body to pass on
+async fn capture_body(body: Body, headers: &HeaderMap, max_bytes: usize) -> (Body, Option<String>) {
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    let fits = body.size_hint().upper().is_some_and(|upper| upper as usize <= max_bytes);
+    if !is_json || !fits {
+        return (body, None);
+    }
+
+    match axum::body::to_bytes(body, max_bytes).await {
+        Ok(bytes) => {
+            let logged = redact_body(&bytes);
+            (Body::from(bytes), Some(logged))
+        }
+        Err(e) => {
+            tracing::warn!("Failed to buffer body for logging: {}", e);
+            (Body::empty(), None)
+        }
+    }
+}
+
+/// Render a JSON body for the logs with every sensitive field masked
+pub fn redact_body(bytes: &[u8]) -> String {
+    match serde_json::from_slice::<Value>(bytes) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            value.to_string()
+        }
+        // Can't tell what's in it, so don't print it
+        Err(_) => format!("<{} bytes, not valid JSON>", bytes.len()),
+    }
+}
+
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if is_sensitive_field(key) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+// Passwords, push tokens, session tokens, API key secrets and card/mobile money numbers
+fn is_sensitive_field(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    key.contains("password")
+        || key.contains("secret")
+        || matches!(
+            key.as_str(),
+            "token" | "access_token" | "refresh_token" | "device_token" | "device_tokens" | "account_number"
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redacts_nested_sensitive_fields() {
+        let body = json!({
+            "email": "ama@example.com",
+            "password": "hunter2",
+            "device_tokens": ["fcm-1", "fcm-2"],
+            "payment_methods": [{ "provider": "MTN Mobile Money", "account_number": "0241234567" }],
+            "api_key": { "secret": "sk_live_abc" }
+        });
+        let logged: Value = serde_json::from_str(&redact_body(body.to_string().as_bytes())).unwrap();
+
+        assert_eq!(logged["email"], "ama@example.com");
+        assert_eq!(logged["password"], REDACTED);
+        assert_eq!(logged["device_tokens"], REDACTED);
+        assert_eq!(logged["payment_methods"][0]["provider"], "MTN Mobile Money");
+        assert_eq!(logged["payment_methods"][0]["account_number"], REDACTED);
+        assert_eq!(logged["api_key"]["secret"], REDACTED);
+    }
+
+    #[test]
+    fn test_invalid_json_is_not_echoed() {
+        let logged = redact_body(b"password=hunter2");
+        assert!(!logged.contains("hunter2"));
+    }
+
+    #[tokio::test]
+    async fn test_capture_body_skips_non_json() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, "text/csv".parse().unwrap());
+        let (_, logged) = capture_body(Body::from("a,b\n1,2\n"), &headers, 1024).await;
+        assert!(logged.is_none());
+
+        headers.insert(header::CONTENT_TYPE, "application/json".parse().unwrap());
+        let (body, logged) = capture_body(Body::from(r#"{"password":"x"}"#), &headers, 1024).await;
+        assert_eq!(logged.as_deref(), Some(r#"{"password":"[REDACTED]"}"#));
+        let passed_on = axum::body::to_bytes(body, 1024).await.unwrap();
+        assert_eq!(&passed_on[..], br#"{"password":"x"}"#);
+    }
+}
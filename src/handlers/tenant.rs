@@ -0,0 +1,59 @@
+// src/handlers/tenant.rs
+// Resolves which tenant a request belongs to and runs the rest of the stack inside it
+use axum::{
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+use crate::{
+    errors::SparrowError as AppError,
+    handlers::auth::API_KEY_HEADER,
+    models::tenant::DEFAULT_TENANT_ID,
+    services::tenant_service::with_tenant,
+    state::AppState,
+};
+
+// Tenant comes from the Host header or the API key's owner. When both are present
+// they must agree, so a key issued by one brand cannot be replayed against another.
+pub async fn resolve_tenant(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok());
+    let from_host = match host {
+        Some(host) => state.tenant_service.resolve_host(host).await?,
+        None => None,
+    };
+
+    let secret = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok());
+    let from_key = match secret {
+        Some(secret) => state.api_key_service.tenant_for_secret(secret.trim()).await?,
+        None => None,
+    };
+
+    let tenant_id = match (from_host, from_key) {
+        (Some(host_tenant), Some(key_tenant)) if host_tenant != key_tenant => {
+            return Err(AppError::Forbidden("API key does not belong to this tenant".to_string()));
+        }
+        (Some(tenant_id), _) | (None, Some(tenant_id)) => tenant_id,
+        (None, None) => DEFAULT_TENANT_ID.to_string(),
+    };
+
+    let tenant = state.tenant_service.get_tenant(&tenant_id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Tenant {} not found", tenant_id)))?;
+    if !tenant.is_active {
+        return Err(AppError::Forbidden(format!("Tenant {} is suspended", tenant.id)));
+    }
+
+    Ok(with_tenant(tenant.id, next.run(request)).await)
+}
@@ -0,0 +1,228 @@
+// src/handlers/user_handler.rs
+use axum::{
+    extract::{ws::{Message, WebSocket, WebSocketUpgrade}, Path, Query, State},
+    http::HeaderMap,
+    response::Response,
+    Json,
+};
+use futures::StreamExt;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{
+    errors::SparrowError as AppError,
+    handlers::{auth::{own_account, SessionAuth}, fields::{FieldsQuery, Projected, JOB_FIELDS}},
+    models::{broadcast::{TopicSubscriptions, UpdateTopicsRequest}, device::UserDevice, ids::{DriverId, UserId}, incident::{Incident, SosRequest}, job::{JobHistoryPage, JobHistoryQuery}, moderation::BlockDriverRequest, presence::PresenceKind, user::{CreditBalance, LoginResponse, UserLogin, UserRegistration, UserResponse}},
+    services::{job_service::JobOperations, realtime_bus::user_topic, region::{current_region_id, with_region}, tenant_service::{current_tenant_id, with_tenant}, user_service::UserOperations},
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct UserQuery {
+    pub id: String,
+}
+
+// GET /users?id=
+pub async fn get_user(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Query(query): Query<UserQuery>,
+) -> Result<Json<UserResponse>, AppError> {
+    let user_id = own_account(&user.id, &query.id)?;
+    let user = state.user_service
+        .get_user(&user_id)
+        .await?
+        .ok_or_else(|| AppError::user_not_found(user_id))?;
+    Ok(Json(user))
+}
+
+// GET /users/:id/blocked-drivers
+pub async fn list_blocked_drivers(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(user_id): Path<String>,
+) -> Result<Json<Vec<DriverId>>, AppError> {
+    let blocked = state.moderation_service
+        .blocked_drivers(&own_account(&user.id, &user_id)?)
+        .await?;
+    Ok(Json(blocked))
+}
+
+// GET /users/:id/jobs?cursor=&limit=&fields=
+pub async fn list_jobs(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(user_id): Path<String>,
+    Query(query): Query<JobHistoryQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Projected<JobHistoryPage>, AppError> {
+    let selection = fields.selection(JOB_FIELDS)?;
+    let page = state.job_service
+        .get_jobs_by_customer(&own_account(&user.id, &user_id)?, query)
+        .await?;
+    Ok(Projected::page(page, selection))
+}
+
+// POST /users/:id/blocked-drivers
+pub async fn block_driver(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(user_id): Path<String>,
+    Json(request): Json<BlockDriverRequest>,
+) -> Result<Json<Vec<DriverId>>, AppError> {
+    let blocked = state.moderation_service
+        .block_driver(&own_account(&user.id, &user_id)?, &request.driver_id)
+        .await?;
+    Ok(Json(blocked))
+}
+
+// DELETE /users/:id/blocked-drivers/:driver_id
+pub async fn unblock_driver(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path((user_id, driver_id)): Path<(String, String)>,
+) -> Result<Json<Vec<DriverId>>, AppError> {
+    let blocked = state.moderation_service
+        .unblock_driver(&own_account(&user.id, &user_id)?, &DriverId::parse(&driver_id)?)
+        .await?;
+    Ok(Json(blocked))
+}
+
+// GET /users/:id/devices
+pub async fn list_devices(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(user_id): Path<String>,
+) -> Result<Json<Vec<UserDevice>>, AppError> {
+    let user_id = own_account(&user.id, &user_id)?;
+    let devices = state.device_service.devices(&user_id).await?;
+    Ok(Json(devices))
+}
+
+// DELETE /users/:id/devices/:device_id
+pub async fn revoke_device(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path((user_id, device_id)): Path<(String, String)>,
+) -> Result<Json<UserDevice>, AppError> {
+    let user_id = own_account(&user.id, &user_id)?;
+    let device = state.device_service.revoke(&user_id, &device_id).await?;
+    Ok(Json(device))
+}
+
+// POST /users/:id/sos - the body is optional, so a bare press still gets through
+pub async fn customer_sos(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(user_id): Path<String>,
+    request: Option<Json<SosRequest>>,
+) -> Result<Json<Incident>, AppError> {
+    let user_id = own_account(&user.id, &user_id)?;
+    let Json(request) = request.unwrap_or_default();
+    let incident = state.incident_service.customer_sos(&user_id, request).await?;
+    Ok(Json(incident))
+}
+
+// POST /users
+pub async fn create_user(
+    State(state): State<Arc<AppState>>,
+    Json(registration): Json<UserRegistration>,
+) -> Result<Json<UserResponse>, AppError> {
+    let user = state.user_service.register_user(registration).await?;
+    Ok(Json(user))
+}
+
+// Set by the edge proxy from the caller's address, replacing any value the client sent
+pub const CLIENT_CITY_HEADER: &str = "x-client-city";
+
+// POST /users/login - the city new devices are checked against is the edge's, not the app's
+pub async fn login(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(mut login): Json<UserLogin>,
+) -> Result<Json<LoginResponse>, AppError> {
+    login.city = headers
+        .get(CLIENT_CITY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|city| !city.is_empty())
+        .map(str::to_string);
+    let (user, access_token) = state.user_service.login_user(login).await?;
+    Ok(Json(LoginResponse { user, access_token }))
+}
+
+// GET /users/credits?id=
+pub async fn get_credit_balance(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Query(query): Query<UserQuery>,
+) -> Result<Json<CreditBalance>, AppError> {
+    let user_id = own_account(&user.id, &query.id)?;
+    let balance = state.user_service.get_credit_balance(&user_id).await?;
+    Ok(Json(balance))
+}
+
+// PUT /users/topics?id=
+pub async fn update_topic_subscriptions(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Query(query): Query<UserQuery>,
+    Json(request): Json<UpdateTopicsRequest>,
+) -> Result<Json<TopicSubscriptions>, AppError> {
+    let user_id = own_account(&user.id, &query.id)?;
+    let subscriptions = state.broadcast_service.update_subscriptions(&user_id, request).await?;
+    Ok(Json(subscriptions))
+}
+
+// GET /ws/users/:id
+// Held open by the customer app while it is in the foreground, so dispatchers can see it
+pub async fn user_socket(
+    State(state): State<Arc<AppState>>,
+    SessionAuth(user): SessionAuth,
+    Path(user_id): Path<String>,
+    socket: WebSocketUpgrade,
+) -> Result<Response, AppError> {
+    let user_id = own_account(&user.id, &user_id)?;
+    let tenant_id = current_tenant_id();
+    let region_id = current_region_id();
+    Ok(socket.on_upgrade(move |socket| with_region(region_id, with_tenant(tenant_id, run_user_socket(state, user_id, socket)))))
+}
+
+async fn run_user_socket(state: Arc<AppState>, user_id: UserId, mut socket: WebSocket) {
+    let presence = match state.presence_service.connected(PresenceKind::Customer, user_id.as_str()).await {
+        Ok(presence) => presence,
+        Err(e) => {
+            tracing::error!("Failed to record presence of user {}: {}", user_id, e);
+            return;
+        }
+    };
+    let mut heartbeat = tokio::time::interval(state.presence_service.heartbeat_interval());
+    // What the self-hosted realtime provider publishes for this user, already JSON
+    let topic = user_topic(&user_id);
+    let (subscription_id, mut messages) = state.realtime_bus.subscribe(&topic);
+
+    loop {
+        tokio::select! {
+            frame = socket.next() => match frame {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {} // Nothing is read yet; pings are answered by axum
+            },
+            Some(payload) = messages.recv() => {
+                let frame = Message::Text(String::from_utf8_lossy(&payload).into_owned());
+                if socket.send(frame).await.is_err() {
+                    break;
+                }
+            }
+            _ = heartbeat.tick() => {
+                if let Err(e) = state.presence_service.heartbeat(&presence).await {
+                    tracing::warn!("Failed to refresh presence of user {}: {}", user_id, e);
+                }
+            }
+        }
+    }
+
+    state.realtime_bus.unsubscribe(&topic, subscription_id);
+    if let Err(e) = state.presence_service.disconnected(&presence).await {
+        tracing::warn!("Failed to clear presence of user {}: {}", user_id, e);
+    }
+}
@@ -0,0 +1,42 @@
+// src/handlers/webhook_handler.rs
+// Notifications pushed to us by payment and telephony providers, each signed with the
+// secret shared with that provider
+use axum::{body::Bytes, extract::State, http::HeaderMap, Json};
+use std::sync::Arc;
+
+#[cfg(feature = "payments")]
+use crate::{models::dispute::{ChargebackNotification, DisputeCase}, services::dispute_service::WEBHOOK_SIGNATURE_HEADER};
+use crate::{
+    errors::SparrowError as AppError,
+    models::{job::JobEvent, voice::CallStatusCallback},
+    services::arrival_calls::VOICE_SIGNATURE_HEADER,
+    state::AppState,
+};
+
+// POST /webhooks/payments/chargebacks
+// The signature covers the raw body, so it is checked before the JSON is parsed
+#[cfg(feature = "payments")]
+pub async fn chargeback(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<DisputeCase>, AppError> {
+    let signature = headers.get(WEBHOOK_SIGNATURE_HEADER).and_then(|value| value.to_str().ok());
+    state.dispute_service.verify_webhook(&body, signature)?;
+    let notification: ChargebackNotification = serde_json::from_slice(&body)?;
+    let case = state.dispute_service.open_case(notification).await?;
+    Ok(Json(case))
+}
+
+// POST /webhooks/voice/calls - how an arrival call went; returns the job event recorded
+pub async fn voice_call_status(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<JobEvent>, AppError> {
+    let signature = headers.get(VOICE_SIGNATURE_HEADER).and_then(|value| value.to_str().ok());
+    state.arrival_calls.verify_webhook(&body, signature)?;
+    let callback: CallStatusCallback = serde_json::from_slice(&body)?;
+    let event = state.arrival_calls.record_status(callback).await?;
+    Ok(Json(event))
+}
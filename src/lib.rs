@@ -1,13 +1,22 @@
+pub mod app;
 pub mod errors;
 pub mod models;
 pub mod state;
 pub mod services;
 pub mod utils {
     pub mod id_generator;
+    pub mod geo;
+    pub mod polyline;
+    pub mod geohash;
+    pub mod secret_hash;
+    pub mod field_crypto;
 }
 pub mod handlers;
+pub mod routes;
 pub mod mocks;
+pub mod workers;
 
 
 // Re-export commonly used types
+pub use app::{SparrowApp, SparrowAppBuilder};
 pub use errors::{SparrowError as AppError, SparrowResult, ValidationError};
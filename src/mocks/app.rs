@@ -0,0 +1,1077 @@
+// src/mocks/app.rs
+// The whole application wired against the in-memory cache and the mock notifier, driven
+// through the real router without a socket, Redis or FCM
+use axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderMap, Method, Request, StatusCode},
+    Router,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+use tower::ServiceExt;
+
+use crate::{
+    errors::ErrorResponse,
+    handlers::request_log::RequestLogConfig,
+    mocks::fixtures::Faker,
+    models::{ids::{DriverId, UserId}, user::UserType},
+    routes,
+    services::{
+        cache_codec::CacheFormat,
+        cache_service::{CacheConfig, CacheService},
+        database::{MemoryRepository, Repository},
+        messaging_service::{MockNotificationService, NotificationService},
+        package_analysis::{NoPackageAnalysis, PackageImageAnalyzer},
+        realtime_publisher::RealtimeProvider,
+    },
+    state::{AppConfig, AppState},
+    utils::{id_generator::IdFormat, secret_hash::salted_hash},
+};
+
+pub struct TestAppBuilder {
+    config: AppConfig,
+    cache_config: CacheConfig,
+    repository: Arc<dyn Repository>,
+    notification_service: Arc<dyn NotificationService>,
+    package_analyzer: Arc<dyn PackageImageAnalyzer>,
+}
+
+impl TestAppBuilder {
+    pub fn id_format(mut self, id_format: IdFormat) -> Self {
+        self.config.id_format = id_format;
+        self
+    }
+
+    /// Serve these regions as well as Ghana
+    pub fn regions(mut self, regions: &[&str]) -> Self {
+        self.config.regions = regions.iter().map(|region| region.to_string()).collect();
+        self
+    }
+
+    pub fn cache_config(mut self, cache_config: CacheConfig) -> Self {
+        self.cache_config = cache_config;
+        self
+    }
+
+    pub fn repository(mut self, repository: Arc<dyn Repository>) -> Self {
+        self.repository = repository;
+        self
+    }
+
+    pub fn notification_service(mut self, notification_service: Arc<dyn NotificationService>) -> Self {
+        self.notification_service = notification_service;
+        self
+    }
+
+    pub fn package_analyzer(mut self, package_analyzer: Arc<dyn PackageImageAnalyzer>) -> Self {
+        self.package_analyzer = package_analyzer;
+        self
+    }
+
+    // Must be called inside a Tokio runtime: the app starts its background workers
+    pub fn build(self) -> TestApp {
+        let cache_service = Arc::new(CacheService::new_memory(self.cache_config).with_repository(self.repository));
+        let state = Arc::new(AppState::with_services(self.config, cache_service, self.notification_service, self.package_analyzer));
+        let router = routes::router(state.clone());
+        TestApp { state, router, admin_token: OnceCell::new() }
+    }
+}
+
+pub struct TestApp {
+    pub state: Arc<AppState>,
+    router: Router,
+    admin_token: OnceCell<String>,
+}
+
+impl TestApp {
+    pub fn builder() -> TestAppBuilder {
+        TestAppBuilder {
+            config: AppConfig {
+                dynamo_url: String::new(),
+                postgres_url: String::new(),
+                redis_url: "memory://".to_string(),
+                fcm_server_key: None,
+                ably_api_key: String::new(),
+                realtime_provider: RealtimeProvider::SelfHosted,
+                id_format: IdFormat::Legacy,
+                cache_format: CacheFormat::Json,
+                request_log: RequestLogConfig::default(),
+                regions: Vec::new(),
+            },
+            cache_config: CacheConfig::default(),
+            repository: Arc::new(MemoryRepository::new()),
+            notification_service: Arc::new(MockNotificationService),
+            package_analyzer: Arc::new(NoPackageAnalysis),
+        }
+    }
+
+    pub fn new() -> Self {
+        Self::builder().build()
+    }
+
+    // Router with all middleware, for driving with `ServiceExt::oneshot` directly
+    pub fn router(&self) -> Router {
+        self.router.clone()
+    }
+
+    pub async fn send(&self, request: Request<Body>) -> TestResponse {
+        let response = self.router().oneshot(request).await.expect("router is infallible");
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("response body is readable");
+        TestResponse { status, headers, body }
+    }
+
+    pub async fn get(&self, uri: &str) -> TestResponse {
+        self.send(Request::get(uri).body(Body::empty()).unwrap()).await
+    }
+
+    pub async fn post_json<T: Serialize>(&self, uri: &str, body: &T) -> TestResponse {
+        self.send(json_request(Method::POST, uri, body)).await
+    }
+
+    // Session of a platform admin with the default admin scopes, signed in on first use
+    pub async fn admin_token(&self) -> &str {
+        self.admin_token.get_or_init(|| async {
+            let mut admin = Faker::seeded(1).user(UserType::Admin);
+            let token = format!("token_{}_admin", admin.id);
+            admin.current_session = Some(salted_hash(&token));
+            self.state.cache_service.cache_user(&admin).await.expect("admin is cached");
+            token
+        }).await
+    }
+
+    pub async fn send_as_admin(&self, request: Request<Body>) -> TestResponse {
+        let token = self.admin_token().await.to_string();
+        self.send_as(&token, request).await
+    }
+
+    pub async fn admin_get(&self, uri: &str) -> TestResponse {
+        self.send_as_admin(Request::get(uri).body(Body::empty()).unwrap()).await
+    }
+
+    pub async fn admin_post_json<T: Serialize>(&self, uri: &str, body: &T) -> TestResponse {
+        self.send_as_admin(json_request(Method::POST, uri, body)).await
+    }
+
+    // Session of an already registered user, as if they had just logged in on the app.
+    // Signing in again gives the same token.
+    pub async fn sign_in(&self, user_id: &UserId) -> String {
+        let mut user = self.state.cache_service.load_user(user_id).await
+            .expect("user loads")
+            .expect("user is registered");
+        let token = format!("token_{}_test", user.id);
+        user.current_session = Some(salted_hash(&token));
+        self.state.cache_service.cache_user(&user).await.expect("user is cached");
+        token
+    }
+
+    // Session of the user a cached driver was registered for
+    pub async fn sign_in_driver(&self, driver_id: &DriverId) -> String {
+        let driver = self.state.cache_service.get_driver(driver_id).await
+            .expect("driver loads")
+            .expect("driver is registered");
+        self.sign_in(&driver.user_id).await
+    }
+
+    pub async fn send_as(&self, token: &str, mut request: Request<Body>) -> TestResponse {
+        let bearer = format!("Bearer {}", token);
+        request.headers_mut().insert(header::AUTHORIZATION, bearer.parse().unwrap());
+        self.send(request).await
+    }
+
+    pub async fn get_as(&self, token: &str, uri: &str) -> TestResponse {
+        self.send_as(token, Request::get(uri).body(Body::empty()).unwrap()).await
+    }
+
+    pub async fn post_json_as<T: Serialize>(&self, token: &str, uri: &str, body: &T) -> TestResponse {
+        self.send_as(token, json_request(Method::POST, uri, body)).await
+    }
+}
+
+impl Default for TestApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn json_request<T: Serialize>(method: Method, uri: &str, body: &T) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(body).expect("request body serializes")))
+        .unwrap()
+}
+
+#[derive(Debug)]
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl TestResponse {
+    // Panics with the raw body if it isn't a `T`, which is what a failing test wants
+    pub fn json<T: DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(&self.body).unwrap_or_else(|e| {
+            panic!("{} from body {:?}", e, String::from_utf8_lossy(&self.body))
+        })
+    }
+
+    pub fn error(&self) -> ErrorResponse {
+        self.json()
+    }
+
+    pub fn assert_ok(&self) -> &Self {
+        assert!(
+            self.status.is_success(),
+            "expected success, got {}: {}",
+            self.status,
+            String::from_utf8_lossy(&self.body)
+        );
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    use crate::{
+        errors::ErrorCode,
+        handlers::{auth::API_KEY_HEADER, request_id::REQUEST_ID_HEADER, user_handler::CLIENT_CITY_HEADER},
+        mocks::messaging::{Recipient, RecordingNotificationService},
+        services::{cache_service::CacheKeys, job_service::JobOperations},
+        models::{
+            api_key::{ApiScope, CreateApiKeyRequest},
+            money::{Currency, Money},
+            driver::{DriverResponse, DriverStatus, OnboardingReview, OnboardingState},
+            ids::DriverId,
+            job::{AvailableJob, BulkJobResponse, JobBatchStatus, JobResponse, JobStatus, PaymentStatus},
+            quota::{QuotaLimit, QuotaMetric, QuotaPeriod},
+            user::{LoginResponse, UserResponse},
+        },
+    };
+    // Only the tests that go through /admin use these
+    #[cfg(feature = "admin")]
+    use crate::{
+        services::{dispatcher_service::Dispatcher, driver_service::DriverOperations, user_service::UserOperations, write_behind::WriteBehindMetrics},
+        models::{
+            admin::StaleJob,
+            api_key::IssuedApiKey,
+            broadcast::{Broadcast, BroadcastStatus, TopicSubscriptions},
+            commission::CommissionConfig,
+            device::{DevicePlatform, DeviceToken},
+            exchange_rate::ExchangeRates,
+            presence::PresenceSnapshot,
+            tax::TaxSchedule,
+            tenant::{PricingConfig, Surcharges, Tenant},
+            dispatch::{BroadcastResponse, DispatchAction, DispatchAuditEntry, DriverSocketEvent},
+            driver::Location,
+            user::{UserCredit, UserLogin},
+        },
+    };
+
+    fn registration(email: &str, phone: &str, user_type: &str) -> Value {
+        json!({
+            "user_type": user_type,
+            "email": email,
+            "phone_number": phone,
+            "country_code": "+233",
+            "first_name": "Ama",
+            "last_name": "Mensah",
+            "password": "correct horse battery staple"
+        })
+    }
+
+    fn location(address: &str, latitude: f64, longitude: f64) -> Value {
+        json!({
+            "latitude": latitude,
+            "longitude": longitude,
+            "address": address,
+            "city": "Accra",
+            "region": "Greater Accra",
+            "country": "Ghana",
+            "postal_code": null,
+            "contact_name": "Kofi Boateng",
+            "contact_phone": "+233241234567",
+            "instructions": null
+        })
+    }
+
+    fn job_request(customer_id: &str) -> Value {
+        json!({
+            "customer_id": customer_id,
+            "pickup_location": location("Osu Oxford Street", 5.5560, -0.1830),
+            "dropoff_location": location("East Legon", 5.6370, -0.1610),
+            "package": {
+                "package_type": "SmallPackage",
+                "description": "Phone accessories",
+                "weight_kg": 1.5,
+                "dimensions": { "length_cm": 20.0, "width_cm": 15.0, "height_cm": 10.0 },
+                "estimated_value": 250.0,
+                "is_fragile": false,
+                "requires_signature": false,
+                "contains": null
+            },
+            "priority": "Standard",
+            "payment_method_id": "pay-mobile-money",
+            "notes": null,
+            "desired_pickup_time": null
+        })
+    }
+
+    async fn register_user(app: &TestApp, email: &str, phone: &str, user_type: &str) -> UserResponse {
+        app.post_json("/users", &registration(email, phone, user_type)).await.assert_ok().json()
+    }
+
+    async fn register_driver(app: &TestApp) -> DriverResponse {
+        let user = register_user(app, "kwame@example.com", "201112222", "Driver").await;
+        let driver = json!({
+            "user_id": user.id,
+            "first_name": "Kwame",
+            "last_name": "Asante",
+            "phone_number": "201112222",
+            "email": "kwame@example.com",
+            "license_plate": "GR 1234-24",
+            "vehicle_type": "Motorcycle",
+            "vehicle_make": "Honda",
+            "vehicle_model": "CG125",
+            "vehicle_year": 2021,
+            "vehicle_color": "Red",
+            "capacity_kg": 20.0,
+            "documents": [
+                { "kind": "DriversLicense", "url": "https://files.example/licence.jpg", "expires_at": "2030-06-30" },
+                { "kind": "NationalId", "url": "https://files.example/ghana-card.jpg" },
+                { "kind": "VehicleRegistration", "url": "https://files.example/registration.jpg" }
+            ]
+        });
+        let token = app.sign_in(&user.id).await;
+        let driver: DriverResponse = app.post_json_as(&token, "/drivers", &driver).await.assert_ok().json();
+
+        // Through review, so the driver can be dispatched. Straight to the onboarding service,
+        // as builds without the admin API run this too.
+        for stage in [OnboardingState::DocumentsSubmitted, OnboardingState::BackgroundCheck, OnboardingState::VehicleInspection] {
+            app.state.onboarding_service.advance(&driver.id, OnboardingReview { stage, note: None }).await.unwrap();
+        }
+        app.get_as(&token, &format!("/drivers?id={}", driver.id)).await.assert_ok().json()
+    }
+
+    #[tokio::test]
+    async fn test_job_lifecycle() {
+        let notifications = RecordingNotificationService::new();
+        let app = TestApp::builder()
+            .notification_service(Arc::new(notifications.clone()))
+            .build();
+
+        let customer = register_user(&app, "ama@example.com", "241234567", "Customer").await;
+        let as_customer = app.sign_in(&customer.id).await;
+        let fetched: UserResponse = app.get_as(&as_customer, &format!("/users?id={}", customer.id)).await.assert_ok().json();
+        assert_eq!(fetched.email, "ama@example.com");
+        assert_eq!(app.get(&format!("/users?id={}", customer.id)).await.status, StatusCode::UNAUTHORIZED);
+
+        let driver = register_driver(&app).await;
+        let as_driver = app.sign_in_driver(&driver.id).await;
+
+        let job: JobResponse = app
+            .post_json_as(&as_customer, "/jobs", &job_request(customer.id.as_str()))
+            .await
+            .assert_ok()
+            .json();
+        assert_eq!(job.status, JobStatus::Pending);
+        assert_eq!(job.customer_id, customer.id);
+        assert!(job.pricing.total.minor() > 0);
+
+        let assigned: JobResponse = app
+            .post_json_as(&as_driver, &format!("/jobs/{}/assign", job.id), &json!({ "driver_id": driver.id }))
+            .await
+            .assert_ok()
+            .json();
+        assert_eq!(assigned.status, JobStatus::DriverAssigned);
+        assert_eq!(assigned.driver_id.as_ref(), Some(&driver.id));
+        // Nothing to hand over until the package is on board, and only by that driver
+        let complete = format!("/jobs/{}/complete", job.id);
+        assert_eq!(app.post_json_as(&as_driver, &complete, &json!({})).await.status, StatusCode::CONFLICT);
+
+        // The driver reports their way along the route, only ever forward
+        let status = format!("/jobs/{}/status", job.id);
+        assert_eq!(app.post_json_as(&as_customer, &status, &json!({ "status": "DriverEnRoute" })).await.status, StatusCode::FORBIDDEN);
+        for step in ["DriverEnRoute", "ArrivedAtPickup", "PackagePickedUp"] {
+            let moved: JobResponse = app.post_json_as(&as_driver, &status, &json!({ "status": step })).await.assert_ok().json();
+            assert_eq!(serde_json::to_value(&moved.status).unwrap(), json!(step));
+        }
+        assert_eq!(app.post_json_as(&as_driver, &status, &json!({ "status": "ArrivedAtPickup" })).await.status, StatusCode::CONFLICT);
+        assert_eq!(app.post_json_as(&as_driver, &status, &json!({ "status": "DeliveryCompleted" })).await.status, StatusCode::BAD_REQUEST);
+        assert_eq!(app.post_json_as(&as_customer, &complete, &json!({})).await.status, StatusCode::FORBIDDEN);
+
+        let completed: JobResponse = app
+            .post_json_as(&as_driver, &format!("/jobs/{}/complete", job.id), &json!({}))
+            .await
+            .assert_ok()
+            .json();
+        assert_eq!(completed.status, JobStatus::DeliveryCompleted);
+        assert_eq!(completed.payment_status, PaymentStatus::Paid);
+        assert!(completed.dropoff_time.is_some());
+
+        let stored: JobResponse = app.get_as(&as_customer, &format!("/jobs?id={}", job.id)).await.assert_ok().json();
+        assert_eq!(stored.status, JobStatus::DeliveryCompleted);
+        assert_eq!(stored.driver_id.as_ref(), Some(&driver.id));
+        let history = format!("/users/{}/jobs", customer.id);
+        let page: Value = app.get_as(&as_customer, &history).await.assert_ok().json();
+        assert_eq!(page["items"].as_array().map(Vec::len), Some(1));
+        assert_eq!(app.get_as(&as_driver, &history).await.status, StatusCode::FORBIDDEN);
+
+        // Only the customer decides which drivers they won't be matched with again
+        let blocked_drivers = format!("/users/{}/blocked-drivers", customer.id);
+        let block = json!({ "driver_id": driver.id });
+        assert_eq!(app.post_json_as(&as_driver, &blocked_drivers, &block).await.status, StatusCode::FORBIDDEN);
+        let blocked: Vec<DriverId> = app.post_json_as(&as_customer, &blocked_drivers, &block).await.assert_ok().json();
+        assert_eq!(blocked, vec![driver.id.clone()]);
+        assert_eq!(app.get_as(&as_driver, &blocked_drivers).await.status, StatusCode::FORBIDDEN);
+
+        let to_customer: Vec<_> = notifications
+            .sent_to_user(&customer.id)
+            .into_iter()
+            .filter_map(|sent| sent.kind().map(str::to_string))
+            .collect();
+        assert_eq!(to_customer, vec!["welcome", "package_picked_up", "delivery_completed"]);
+
+        let assigned = notifications.of_kind("driver_assigned");
+        assert_eq!(assigned.len(), 1);
+        assert_eq!(assigned[0].recipient, Recipient::Driver(driver.id));
+    }
+
+    #[tokio::test]
+    async fn test_reads_fall_back_to_repository_after_cache_loss() {
+        let app = TestApp::new();
+        let customer = register_user(&app, "ama@example.com", "241234567", "Customer").await;
+        let as_customer = app.sign_in(&customer.id).await;
+        let job: JobResponse = app
+            .post_json_as(&as_customer, "/jobs", &job_request(customer.id.as_str()))
+            .await
+            .assert_ok()
+            .json();
+
+        // As after a Redis restart
+        let cache = &app.state.cache_service;
+        cache.invalidate_user(&customer.id).await.unwrap();
+        cache.invalidate_job(&job.id).await.unwrap();
+
+        let user: UserResponse = app.get_as(&as_customer, &format!("/users?id={}", customer.id)).await.assert_ok().json();
+        assert_eq!(user.email, "ama@example.com");
+        let stored: JobResponse = app.get_as(&as_customer, &format!("/jobs?id={}", job.id)).await.assert_ok().json();
+        assert_eq!(stored.tracking_code, job.tracking_code);
+
+        // Repopulated on the way through
+        assert!(cache.get_job(&CacheKeys::job_by_id(&job.id)).await.unwrap().is_some());
+        assert!(cache.get_user(&CacheKeys::user_by_id(&customer.id)).await.unwrap().is_some());
+    }
+
+    #[cfg(feature = "admin")]
+    #[tokio::test]
+    async fn test_location_batches_are_written_behind() {
+        let app = TestApp::new();
+        let driver = register_driver(&app).await;
+        let as_driver = app.sign_in_driver(&driver.id).await;
+        let now = chrono::Utc::now();
+        let batch = json!({
+            "locations": [
+                { "latitude": 5.5560, "longitude": -0.1830, "timestamp": now - chrono::Duration::seconds(30) },
+                { "latitude": 5.5600, "longitude": -0.1800, "timestamp": now }
+            ]
+        });
+        let uri = format!("/drivers/{}/locations/batch", driver.id);
+        assert_eq!(app.post_json(&uri, &batch).await.status, StatusCode::UNAUTHORIZED);
+        app.post_json_as(&as_driver, &uri, &batch).await.assert_ok();
+
+        app.state.write_behind.shutdown().await;
+        let cache = &app.state.cache_service;
+        let location = cache.get_driver_location(&driver.id).await.unwrap().expect("live position flushed");
+        assert_eq!(location.latitude, 5.5600);
+        assert!(cache.get_driver_last_seen(&driver.id).await.unwrap().is_some());
+
+        let metrics: WriteBehindMetrics = app.admin_get("/admin/write-behind").await.assert_ok().json();
+        assert_eq!((metrics.written, metrics.depth, metrics.dropped), (2, 0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_driver_break_is_guarded_and_blocks_dispatch() {
+        let app = TestApp::new();
+        let customer = register_user(&app, "ama@example.com", "241234567", "Customer").await;
+        let as_customer = app.sign_in(&customer.id).await;
+        let driver = register_driver(&app).await;
+        let as_driver = app.sign_in_driver(&driver.id).await;
+        let job: JobResponse = app.post_json_as(&as_customer, "/jobs", &job_request(customer.id.as_str())).await.assert_ok().json();
+        app.post_json_as(&as_driver, &format!("/jobs/{}/assign", job.id), &json!({ "driver_id": driver.id })).await.assert_ok();
+
+        // Package on board: the break waits
+        app.post_json_as(&as_driver, &format!("/jobs/{}/status", job.id), &json!({ "status": "PackagePickedUp" })).await.assert_ok();
+        let start = format!("/drivers/{}/break/start", driver.id);
+        let response = app.post_json_as(&as_driver, &start, &json!({})).await;
+        assert_eq!(response.status, StatusCode::CONFLICT);
+        // Only the driver themselves
+        assert_eq!(app.post_json_as(&as_customer, &start, &json!({})).await.status, StatusCode::FORBIDDEN);
+        let sos = format!("/drivers/{}/sos", driver.id);
+        assert_eq!(app.post_json_as(&as_customer, &sos, &json!({})).await.status, StatusCode::FORBIDDEN);
+
+        app.post_json_as(&as_driver, &format!("/jobs/{}/complete", job.id), &json!({})).await.assert_ok();
+        let response = app.post_json_as(&as_driver, &start, &json!({ "minutes": 600 })).await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+        let on_break: DriverResponse = app.post_json_as(&as_driver, &start, &json!({ "minutes": 20 })).await.assert_ok().json();
+        assert_eq!(on_break.status, DriverStatus::OnBreak);
+        assert!(on_break.break_ends_at.is_some());
+
+        let next: JobResponse = app.post_json_as(&as_customer, "/jobs", &job_request(customer.id.as_str())).await.assert_ok().json();
+        let response = app.post_json_as(&as_driver, &format!("/jobs/{}/assign", next.id), &json!({ "driver_id": driver.id })).await;
+        assert_eq!(response.error().code, ErrorCode::DriverNotAvailable);
+
+        let end = format!("/drivers/{}/break/end", driver.id);
+        let back: DriverResponse = app.post_json_as(&as_driver, &end, &json!({})).await.assert_ok().json();
+        assert_eq!(back.status, DriverStatus::Online);
+        assert!(back.break_ends_at.is_none());
+        assert_eq!(app.post_json_as(&as_driver, &end, &json!({})).await.status, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_driver_feed_lists_offers_and_nearby_jobs_that_fit() {
+        let app = TestApp::new();
+        let customer = register_user(&app, "ama@example.com", "241234567", "Customer").await;
+        let as_customer = app.sign_in(&customer.id).await;
+        let driver = register_driver(&app).await;
+        let as_driver = app.sign_in_driver(&driver.id).await;
+        let uri = format!("/drivers/{}/available-jobs", driver.id);
+        assert_eq!(app.get_as(&as_driver, &uri).await.status, StatusCode::CONFLICT);
+
+        let batch = json!({ "locations": [{ "latitude": 5.5570, "longitude": -0.1820, "timestamp": chrono::Utc::now() }] });
+        app.post_json_as(&as_driver, &format!("/drivers/{}/locations/batch", driver.id), &batch).await.assert_ok();
+        app.state.write_behind.shutdown().await;
+
+        let mut tipped_request = job_request(customer.id.as_str());
+        tipped_request["tip"] = json!(5.0);
+        let nearby: JobResponse = app.post_json_as(&as_customer, "/jobs", &tipped_request).await.assert_ok().json();
+        let mut heavy_request = job_request(customer.id.as_str());
+        heavy_request["package"]["weight_kg"] = json!(250.0);
+        app.post_json_as(&as_customer, "/jobs", &heavy_request).await.assert_ok();
+        // Kumasi, well outside the search radius, but offered directly
+        let mut far_request = job_request(customer.id.as_str());
+        far_request["pickup_location"] = location("Adum", 6.6885, -1.6244);
+        let far: JobResponse = app.post_json_as(&as_customer, "/jobs", &far_request).await.assert_ok().json();
+        let mut job = app.state.cache_service.load_job(&far.id).await.unwrap().unwrap();
+        job.offered_to_drivers.push(driver.id.clone());
+        app.state.cache_service.cache_job(&job).await.unwrap();
+
+        let feed: Vec<AvailableJob> = app.get_as(&as_driver, &uri).await.assert_ok().json();
+        let ids: Vec<_> = feed.iter().map(|available| &available.job.id).collect();
+        assert_eq!(ids, vec![&far.id, &nearby.id]);
+        assert!(feed[0].offered && !feed[1].offered);
+        assert!(feed[1].distance_to_pickup_km < 1.0);
+        assert!(feed[1].estimated_earnings > 0.0 && feed[1].estimated_earnings < nearby.pricing.total.to_major());
+        // The tip goes to the driver in full, on top of the fare after commission
+        let earnings = &feed[1].earnings;
+        assert_eq!(earnings.tip.minor(), 500);
+        assert!(earnings.commission.minor() > 0);
+        assert_eq!(feed[1].estimated_earnings, earnings.total.to_major());
+        assert_eq!(earnings.total, earnings.fare - earnings.commission + earnings.tip);
+    }
+
+    #[cfg(feature = "admin")]
+    #[tokio::test]
+    async fn test_admin_commissions_set_the_driver_cut() {
+        let app = TestApp::new();
+        let defaults: CommissionConfig = app.admin_get("/admin/commissions").await.assert_ok().json();
+        assert!(defaults.rules.is_empty() && defaults.updated_at.is_none());
+
+        let invalid = json!({ "default_rate": 1.5 });
+        let response = app.send_as_admin(json_request(Method::PUT, "/admin/commissions", &invalid)).await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+        let commissions = json!({
+            "default_rate": 0.2,
+            "rules": [{ "vehicle_type": "Motorcycle", "zone": " Greater Accra ", "rate": 0.1 }]
+        });
+        let saved: CommissionConfig = app.send_as_admin(json_request(Method::PUT, "/admin/commissions", &commissions)).await.assert_ok().json();
+        assert_eq!(saved.rules[0].zone.as_deref(), Some("Greater Accra"));
+
+        let customer = register_user(&app, "ama@example.com", "241234567", "Customer").await;
+        let as_customer = app.sign_in(&customer.id).await;
+        let driver = register_driver(&app).await;
+        let as_driver = app.sign_in_driver(&driver.id).await;
+        let batch = json!({ "locations": [{ "latitude": 5.5570, "longitude": -0.1820, "timestamp": chrono::Utc::now() }] });
+        app.post_json_as(&as_driver, &format!("/drivers/{}/locations/batch", driver.id), &batch).await.assert_ok();
+        app.state.write_behind.shutdown().await;
+        let job: JobResponse = app.post_json_as(&as_customer, "/jobs", &job_request(customer.id.as_str())).await.assert_ok().json();
+
+        let feed: Vec<AvailableJob> = app.get_as(&as_driver, &format!("/drivers/{}/available-jobs", driver.id)).await.assert_ok().json();
+        assert_eq!(feed[0].earnings.commission_rate, 0.1);
+
+        // Assignment locks in the rate the driver was shown
+        app.post_json_as(&as_driver, &format!("/jobs/{}/assign", job.id), &json!({ "driver_id": driver.id })).await.assert_ok();
+        let stored = app.state.cache_service.load_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(stored.commission_rate, Some(0.1));
+    }
+
+    #[cfg(feature = "admin")]
+    #[tokio::test]
+    async fn test_tax_schedule_changes_only_reach_new_jobs() {
+        let app = TestApp::new();
+        let customer = register_user(&app, "ama@example.com", "241234567", "Customer").await;
+        let as_customer = app.sign_in(&customer.id).await;
+        let before: JobResponse = app.post_json_as(&as_customer, "/jobs", &job_request(customer.id.as_str())).await.assert_ok().json();
+        let codes: Vec<_> = before.pricing.tax_lines.iter().map(|line| line.code.as_str()).collect();
+        assert_eq!(codes, vec!["NHIL", "GETFUND", "COVID19", "VAT"]);
+        let levies = Money::sum(before.pricing.currency, before.pricing.tax_lines.iter().map(|line| &line.amount));
+        assert_eq!(before.pricing.tax, levies);
+
+        let backdated = json!({ "effective_from": "2020-01-01T00:00:00Z", "levies": [{ "code": "VAT", "name": "VAT", "rate": 0.2 }] });
+        assert_eq!(app.admin_post_json("/admin/taxes", &backdated).await.status, StatusCode::BAD_REQUEST);
+
+        let vat_only = json!({ "levies": [{ "code": "vat", "name": "Value Added Tax", "rate": 0.2 }] });
+        let schedules: Vec<TaxSchedule> = app.admin_post_json("/admin/taxes", &vat_only).await.assert_ok().json();
+        assert_eq!(schedules.len(), 2);
+
+        let after: JobResponse = app.post_json_as(&as_customer, "/jobs", &job_request(customer.id.as_str())).await.assert_ok().json();
+        assert_eq!(after.pricing.tax_lines.len(), 1);
+        assert_eq!(after.pricing.tax_lines[0].code, "VAT");
+        // The earlier job keeps the levies it was priced with
+        let stored = app.state.cache_service.load_job(&before.id).await.unwrap().unwrap();
+        assert_eq!(stored.pricing.tax_lines, before.pricing.tax_lines);
+    }
+
+    #[tokio::test]
+    async fn test_nigerian_jobs_are_priced_in_naira_with_no_exchange_rates_published() {
+        let app = TestApp::builder().regions(&["ng"]).build();
+        let customer = register_user(&app, "ama@example.com", "241234567", "Customer").await;
+        let as_customer = app.sign_in(&customer.id).await;
+        let mut request = job_request(customer.id.as_str());
+        for (end, (latitude, longitude)) in [("pickup_location", (6.6018, 3.3515)), ("dropoff_location", (6.4281, 3.4219))] {
+            request[end]["latitude"] = json!(latitude);
+            request[end]["longitude"] = json!(longitude);
+            request[end]["city"] = json!("Lagos");
+            request[end]["country"] = json!("Nigeria");
+        }
+        request["priority"] = json!("Express");
+        assert!(app.state.exchange_rates.rates().await.unwrap().rates.is_empty());
+
+        let job: JobResponse = app.post_json_as(&as_customer, "/jobs", &request).await.assert_ok().json();
+        let naira = Currency::parse("NGN").unwrap();
+        assert_eq!(job.pricing.currency, naira);
+        assert_eq!(job.pricing.package_surcharge, Money::from_major(500.0, naira));
+        assert_eq!(job.pricing.priority_surcharge, Money::from_major(1000.0, naira));
+    }
+
+    #[cfg(feature = "admin")]
+    #[tokio::test]
+    async fn test_jobs_priced_in_a_second_currency() {
+        let app = TestApp::new();
+        let customer = register_user(&app, "ama@example.com", "241234567", "Customer").await;
+        let as_customer = app.sign_in(&customer.id).await;
+        let naira = Currency::parse("NGN").unwrap();
+        let mut request = job_request(customer.id.as_str());
+        request["currency"] = json!("NGN");
+
+        let response = app.post_json_as(&as_customer, "/jobs", &request).await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+        assert_eq!(response.error().details.unwrap()[0]["field"], "currency");
+
+        // Priced in naira throughout, with no exchange rate needed
+        let mut tenant = Tenant::default_tenant();
+        let surcharges = Surcharges { small_package: 500.0, ..Surcharges::default() };
+        tenant.currency_pricing.push(PricingConfig { currency: naira, per_km: 250.0, surcharges: Some(surcharges), ..PricingConfig::default() });
+        app.state.cache_service.cache_tenant(&tenant).await.unwrap();
+        let job: JobResponse = app.post_json_as(&as_customer, "/jobs", &request).await.assert_ok().json();
+        assert_eq!(job.pricing.currency, naira);
+        assert_eq!(job.pricing.tax.currency(), naira);
+        assert_eq!(job.pricing.package_surcharge, Money::from_major(500.0, naira));
+        let cedi_job: JobResponse = app.post_json_as(&as_customer, "/jobs", &job_request(customer.id.as_str())).await.assert_ok().json();
+        assert_eq!(cedi_job.pricing.currency, Currency::GHS);
+        assert_eq!(cedi_job.pricing.package_surcharge, Money::from_major(5.0, Currency::GHS));
+
+        let rates = json!({ "base": "GHS", "rates": { "NGN": 100.0 } });
+        let saved: ExchangeRates = app.send_as_admin(json_request(Method::PUT, "/admin/exchange-rates", &rates)).await.assert_ok().json();
+        assert_eq!(saved.rate(Currency::GHS, naira), Some(100.0));
+
+        // A wallet never mixes currencies
+        let credit = |amount: Money| UserCredit {
+            id: format!("crd-{}", amount.currency()),
+            user_id: customer.id.clone(),
+            amount,
+            reason: "sla_breach".to_string(),
+            job_id: None,
+            created_at: chrono::Utc::now(),
+        };
+        let uri = format!("/users/credits?id={}", customer.id);
+        app.state.cache_service.append_user_credit(&credit(Money::from_major(5.0, Currency::GHS))).await.unwrap();
+        let balance: Value = app.get_as(&as_customer, &uri).await.assert_ok().json();
+        assert_eq!(balance["balance"], json!(5.0));
+        assert_eq!(balance["currency"], "GHS");
+
+        app.state.cache_service.append_user_credit(&credit(Money::from_major(500.0, naira))).await.unwrap();
+        let response = app.get_as(&as_customer, &uri).await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+        assert!(response.error().details.unwrap()[0]["message"].as_str().unwrap().contains("Cannot combine GHS with NGN"));
+    }
+
+    #[cfg(feature = "admin")]
+    #[tokio::test]
+    async fn test_zone_broadcasts_reach_subscribers_in_their_language() {
+        let notifications = RecordingNotificationService::new();
+        let app = TestApp::builder()
+            .notification_service(Arc::new(notifications.clone()))
+            .build();
+        for (email, phone, language) in [("ama@example.com", "241234567", "en"), ("awa@example.com", "241234568", "fr-GH")] {
+            let user = register_user(&app, email, phone, "Customer").await;
+            let mut stored = app.state.cache_service.load_user(&user.id).await.unwrap().unwrap();
+            stored.language = language.to_string();
+            stored.device_tokens = vec![DeviceToken::new(format!("{}-phone", phone), DevicePlatform::Android)];
+            app.state.cache_service.cache_user(&stored).await.unwrap();
+            let zones = json!({ "zones": ["Greater Accra", "greater accra"] });
+            let token = app.sign_in(&user.id).await;
+            let subscribed: TopicSubscriptions = app.send_as(&token, json_request(Method::PUT, &format!("/users/topics?id={}", user.id), &zones)).await.assert_ok().json();
+            assert_eq!(subscribed.zones, vec!["Greater Accra"]);
+        }
+        assert_eq!(notifications.subscribers("default.zone.greater-accra.fr"), vec!["241234568-phone"]);
+
+        let no_fallback = json!({ "zones": ["Greater Accra"], "messages": { "fr": { "title": "Promo", "body": "-20%" } } });
+        assert_eq!(app.admin_post_json("/admin/broadcasts", &no_fallback).await.status, StatusCode::BAD_REQUEST);
+
+        let promotion = json!({
+            "zones": ["Greater Accra", "Ashanti"],
+            "messages": {
+                "en": { "title": "Weekend deal", "body": "20% off deliveries in Accra" },
+                "fr": { "title": "Offre du week-end", "body": "20% de réduction à Accra" }
+            }
+        });
+        let sent: Broadcast = app.admin_post_json("/admin/broadcasts", &promotion).await.assert_ok().json();
+        assert_eq!(sent.status, BroadcastStatus::Sent);
+        // Nobody follows Ashanti, so there is nothing to push there
+        assert_eq!(sent.topics, vec!["default.zone.greater-accra.en", "default.zone.greater-accra.fr"]);
+        assert_eq!(notifications.sent_to_topic("default.zone.greater-accra.fr")[0].message.title, "Offre du week-end");
+
+        let mut scheduled = promotion.clone();
+        scheduled["send_at"] = json!(chrono::Utc::now() + chrono::Duration::hours(1));
+        let pending: Broadcast = app.admin_post_json("/admin/broadcasts", &scheduled).await.assert_ok().json();
+        assert_eq!(pending.status, BroadcastStatus::Scheduled);
+        assert_eq!(notifications.sent_to_topic("default.zone.greater-accra.en").len(), 1);
+        let sent_later = app.state.broadcast_service.send_due(chrono::Utc::now() + chrono::Duration::hours(2)).await.unwrap();
+        assert_eq!(sent_later, 1);
+        let broadcasts: Vec<Broadcast> = app.admin_get("/admin/broadcasts").await.assert_ok().json();
+        assert!(broadcasts.iter().all(|broadcast| broadcast.status == BroadcastStatus::Sent));
+    }
+
+    #[cfg(feature = "admin")]
+    async fn dispatch_request(app: &TestApp, method: Method, uri: &str, secret: &str, body: &Value) -> TestResponse {
+        let mut request = json_request(method, uri, body);
+        request.headers_mut().insert(API_KEY_HEADER, secret.parse().unwrap());
+        app.send(request).await
+    }
+
+    #[cfg(feature = "admin")]
+    #[tokio::test]
+    async fn test_sessions_carry_no_more_than_the_scopes_they_asked_for() {
+        let app = TestApp::new();
+        assert_eq!(app.get("/admin/commissions").await.status, StatusCode::UNAUTHORIZED);
+
+        let admin = register_user(&app, "kofi@example.com", "201234567", "Admin").await;
+        let login = |scopes: Value| UserLogin {
+            email: Some(admin.email.clone()),
+            phone_number: None,
+            password: "correct horse battery staple".to_string(),
+            device_token: None,
+            device_platform: DevicePlatform::default(),
+            scopes: serde_json::from_value(scopes).unwrap(),
+            device: None,
+            city: None,
+            verification_code: None,
+        };
+        assert!(app.state.user_service.login_user(login(json!(["dispatch:read"]))).await.is_err());
+        let (_, token) = app.state.user_service.login_user(login(json!(["admin:read"]))).await.unwrap();
+        let as_reader = |method: Method, body: Value| {
+            let mut request = json_request(method, "/admin/commissions", &body);
+            request.headers_mut().insert(header::AUTHORIZATION, format!("Bearer {}", token).parse().unwrap());
+            request
+        };
+
+        let commissions: CommissionConfig = app.send(as_reader(Method::GET, json!(null))).await.assert_ok().json();
+        let response = app.send(as_reader(Method::PUT, serde_json::to_value(&commissions).unwrap())).await;
+        assert_eq!(response.status, StatusCode::FORBIDDEN);
+        app.send_as_admin(json_request(Method::PUT, "/admin/commissions", &commissions)).await.assert_ok();
+    }
+
+    #[cfg(feature = "admin")]
+    #[tokio::test]
+    async fn test_dispatcher_console_overrides_are_audited() {
+        let app = TestApp::new();
+        let customer = register_user(&app, "ama@example.com", "241234567", "Customer").await;
+        let as_customer = app.sign_in(&customer.id).await;
+        let driver = register_driver(&app).await;
+        let job: JobResponse = app.post_json_as(&as_customer, "/jobs", &job_request(customer.id.as_str())).await.assert_ok().json();
+
+        // Online and parked next to the pickup
+        let mut stored = app.state.cache_service.get_driver(&driver.id).await.unwrap().unwrap();
+        stored.status = DriverStatus::Online;
+        stored.current_location = Some(Location {
+            latitude: 5.5565,
+            longitude: -0.1825,
+            accuracy: None,
+            heading: None,
+            speed: None,
+            timestamp: chrono::Utc::now(),
+        });
+        app.state.cache_service.cache_driver(&stored).await.unwrap();
+
+        let dispatcher = register_user(&app, "esi@example.com", "557654321", "Dispatcher").await;
+        let key_request = json!({ "merchant_id": dispatcher.id, "name": "Console", "scopes": ["dispatch:*"] });
+        let issued: IssuedApiKey = app.admin_post_json("/admin/api-keys", &key_request).await.assert_ok().json();
+        let secret = issued.secret.as_str();
+
+        // Business scopes can't be issued to a dispatcher, and the console needs a key
+        let merchant_scopes = json!({ "merchant_id": dispatcher.id, "name": "Shop", "scopes": ["jobs:write"] });
+        assert_eq!(app.admin_post_json("/admin/api-keys", &merchant_scopes).await.status, StatusCode::BAD_REQUEST);
+        assert_eq!(app.get("/dispatch/jobs/unassigned").await.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(dispatch_request(&app, Method::GET, "/admin/api-keys", secret, &json!(null)).await.status, StatusCode::FORBIDDEN);
+
+        let waiting: Vec<StaleJob> = dispatch_request(&app, Method::GET, "/dispatch/jobs/unassigned?older_than_minutes=0", secret, &json!(null))
+            .await
+            .assert_ok()
+            .json();
+        assert_eq!(waiting.len(), 1);
+        assert_eq!(waiting[0].job_id, job.id);
+
+        let broadcast: BroadcastResponse = dispatch_request(&app, Method::POST, &format!("/dispatch/jobs/{}/broadcast", job.id), secret, &json!({}))
+            .await
+            .assert_ok()
+            .json();
+        assert_eq!(broadcast.offered_to, vec![driver.id.clone()]);
+
+        let assign = json!({ "driver_id": driver.id, "reason": "Customer called in" });
+        let assigned: JobResponse = dispatch_request(&app, Method::POST, &format!("/dispatch/jobs/{}/assign", job.id), secret, &assign)
+            .await
+            .assert_ok()
+            .json();
+        assert_eq!(assigned.status, JobStatus::DriverAssigned);
+
+        let released: JobResponse = dispatch_request(&app, Method::POST, &format!("/dispatch/jobs/{}/unassign", job.id), secret, &json!({}))
+            .await
+            .assert_ok()
+            .json();
+        assert_eq!(released.status, JobStatus::Searching);
+        assert!(released.driver_id.is_none());
+
+        let audit: Vec<DispatchAuditEntry> = dispatch_request(&app, Method::GET, "/dispatch/audit", secret, &json!(null))
+            .await
+            .assert_ok()
+            .json();
+        let actions: Vec<_> = audit.iter().map(|entry| entry.action).collect();
+        assert_eq!(actions, vec![DispatchAction::ForceUnassign, DispatchAction::ForceAssign, DispatchAction::Broadcast]);
+        assert_eq!(audit[0].previous_driver_id.as_ref(), Some(&driver.id));
+        assert_eq!(audit[1].dispatcher_id, dispatcher.id);
+    }
+
+    #[cfg(feature = "admin")]
+    #[tokio::test]
+    async fn test_offers_go_over_open_sockets_and_fall_back_to_push() {
+        let notifications = RecordingNotificationService::new();
+        let app = TestApp::builder()
+            .notification_service(Arc::new(notifications.clone()))
+            .build();
+        let customer = register_user(&app, "ama@example.com", "241234567", "Customer").await;
+        let as_customer = app.sign_in(&customer.id).await;
+        let connected = register_driver(&app).await;
+        let offline = crate::mocks::fixtures::Faker::seeded(11).driver();
+        for driver_id in [&connected.id, &offline.id] {
+            let mut stored = app.state.cache_service.get_driver(driver_id).await.unwrap().unwrap_or_else(|| offline.clone());
+            stored.status = DriverStatus::Online;
+            stored.current_location = Some(Location {
+                latitude: 5.5565,
+                longitude: -0.1825,
+                accuracy: None,
+                heading: None,
+                speed: None,
+                timestamp: chrono::Utc::now(),
+            });
+            app.state.cache_service.cache_driver(&stored).await.unwrap();
+        }
+
+        let channel = &app.state.driver_channel;
+        let (_, mut events) = channel.connect(&connected.id).await.unwrap();
+        let online = app.state.driver_service.get_online_drivers().await.unwrap();
+        assert_eq!(online.iter().map(|driver| &driver.id).collect::<Vec<_>>(), vec![&connected.id]);
+        let presence: PresenceSnapshot = app.admin_get("/admin/presence").await.assert_ok().json();
+        assert_eq!(presence.drivers.iter().map(|entry| entry.id.as_str()).collect::<Vec<_>>(), vec![connected.id.as_str()]);
+        let as_connected = app.sign_in_driver(&connected.id).await;
+        let seen: DriverResponse = app.get_as(&as_connected, &format!("/drivers?id={}", connected.id)).await.assert_ok().json();
+        assert!(seen.last_seen_at.is_some());
+        // Only the driver's own session gets as far as the upgrade,
+        let socket = format!("/ws/drivers/{}", connected.id);
+        assert_eq!(app.get(&socket).await.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(app.get_as(&as_customer, &socket).await.status, StatusCode::FORBIDDEN);
+        // which a plain GET, missing the handshake headers, then fails
+        assert_eq!(app.get_as(&as_connected, &socket).await.status, StatusCode::BAD_REQUEST);
+
+        // Onboarding already pushed the connected driver their review updates
+        let onboarding_pushes = notifications.sent_to_driver(&connected.id).len();
+        let dispatcher = Dispatcher { user_id: &customer.id, api_key_id: "test" };
+        let first: JobResponse = app.post_json_as(&as_customer, "/jobs", &job_request(customer.id.as_str())).await.assert_ok().json();
+        app.state.dispatcher_service.broadcast(&dispatcher, &first.id, None).await.unwrap();
+        assert!(matches!(events.try_recv().unwrap(), DriverSocketEvent::JobOffer { job_id, .. } if job_id == first.id));
+        assert_eq!(notifications.sent_to_driver(&connected.id).len(), onboarding_pushes);
+        assert_eq!(notifications.sent_to_driver(&offline.id).len(), 1);
+
+        let answer = app.state.dispatcher_service.answer_offer(&connected.id, first.id.clone(), true).await;
+        assert_eq!(answer, DriverSocketEvent::OfferAccepted { job_id: first.id.clone() });
+        let assigned: JobResponse = app.get_as(&as_connected, &format!("/jobs?id={}", first.id)).await.assert_ok().json();
+        assert_eq!(assigned.driver_id.as_ref(), Some(&connected.id));
+        // Push-only drivers answer over HTTP, not the socket
+        let answer = app.state.dispatcher_service.answer_offer(&offline.id, first.id.clone(), true).await;
+        assert!(matches!(answer, DriverSocketEvent::Error { .. }));
+
+        // Unanswered offers lapse
+        let second: JobResponse = app.post_json_as(&as_customer, "/jobs", &job_request(customer.id.as_str())).await.assert_ok().json();
+        app.state.dispatcher_service.broadcast(&dispatcher, &second.id, None).await.unwrap();
+        let later = chrono::Utc::now() + chrono::Duration::minutes(1);
+        assert_eq!(channel.expire_offers(&connected.id, later).await.unwrap(), vec![second.id.clone()]);
+        let answer = app.state.dispatcher_service.answer_offer(&connected.id, second.id.clone(), true).await;
+        assert!(matches!(answer, DriverSocketEvent::Error { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_registration_is_rejected() {
+        let app = TestApp::new();
+        register_user(&app, "ama@example.com", "241234567", "Customer").await;
+
+        let response = app.post_json("/users", &registration("ama@example.com", "509998888", "Customer")).await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+        assert_eq!(response.error().code, ErrorCode::ValidationFailed);
+    }
+
+    #[tokio::test]
+    async fn test_errors_follow_accept_language_with_english_fallback() {
+        let app = TestApp::new();
+        register_user(&app, "ama@example.com", "241234567", "Customer").await;
+
+        let mut request = json_request(Method::POST, "/users", &registration("kofi@example.com", "241234567", "Customer"));
+        request.headers_mut().insert(header::ACCEPT_LANGUAGE, "fr-CI, en;q=0.5".parse().unwrap());
+        let response = app.send(request).await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers[header::CONTENT_LANGUAGE], "fr");
+        let error = response.error();
+        assert_eq!(error.message, "Des erreurs de validation sont survenues");
+        assert_eq!(error.details.unwrap()[0]["message"], "Un compte existe déjà avec ce numéro de téléphone");
+
+        let response = app.post_json("/users", &registration("kofi@example.com", "241234567", "Customer")).await;
+        assert_eq!(response.headers[header::CONTENT_LANGUAGE], "en");
+        assert_eq!(response.error().details.unwrap()[0]["message"], "User already exists with this phone number");
+    }
+
+    #[tokio::test]
+    async fn test_assigning_unknown_driver_fails() {
+        let app = TestApp::new();
+        let customer = register_user(&app, "ama@example.com", "241234567", "Customer").await;
+        let as_customer = app.sign_in(&customer.id).await;
+        let job: JobResponse = app.post_json_as(&as_customer, "/jobs", &job_request(customer.id.as_str())).await.assert_ok().json();
+
+        // Drivers only take jobs for themselves
+        let driver = register_driver(&app).await;
+        let as_driver = app.sign_in_driver(&driver.id).await;
+        let response = app
+            .post_json_as(&as_driver, &format!("/jobs/{}/assign", job.id), &json!({ "driver_id": DriverId::generate() }))
+            .await;
+        assert_eq!(response.status, StatusCode::FORBIDDEN);
+        let response = app
+            .post_json_as(&as_customer, &format!("/jobs/{}/assign", job.id), &json!({ "driver_id": driver.id }))
+            .await;
+        assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+        let stored: JobResponse = app.get_as(&as_customer, &format!("/jobs?id={}", job.id)).await.assert_ok().json();
+        assert_eq!(stored.status, JobStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn test_login_checks_new_devices_against_the_edges_city() {
+        let app = TestApp::new();
+        let customer = register_user(&app, "ama@example.com", "241234567", "Customer").await;
+        let login = |body: Value, city: &str| {
+            let mut request = json_request(Method::POST, "/users/login", &body);
+            request.headers_mut().insert(CLIENT_CITY_HEADER, city.parse().unwrap());
+            app.send(request)
+        };
+        let credentials = json!({ "email": "ama@example.com", "password": "correct horse battery staple" });
+
+        // The first device is trusted, and its token is the session
+        let mut on_phone = credentials.clone();
+        on_phone["device"] = json!({ "device_id": "phone" });
+        let session: LoginResponse = login(on_phone, "Accra").await.assert_ok().json();
+        app.get_as(&session.access_token, &format!("/users?id={}", customer.id)).await.assert_ok();
+
+        // No device named and a city from the app rather than the edge: still unseen, somewhere new
+        let mut unnamed = credentials.clone();
+        unnamed["city"] = json!("Accra");
+        let response = login(unnamed, "Kumasi").await;
+        assert_eq!(response.error().code, ErrorCode::DeviceVerificationRequired);
+        login(credentials, "accra").await.assert_ok();
+    }
+
+    #[tokio::test]
+    async fn test_bulk_imports_are_booked_on_the_merchant_within_its_quota() {
+        let app = TestApp::new();
+        let customer = register_user(&app, "ama@example.com", "241234567", "Customer").await;
+        let as_customer = app.sign_in(&customer.id).await;
+        let merchant = Faker::seeded(73).user(UserType::Business);
+        app.state.cache_service.cache_user(&merchant).await.unwrap();
+        let issued = app.state.api_key_service.issue_key(CreateApiKeyRequest {
+            merchant_id: merchant.id.clone(),
+            name: "Storefront".to_string(),
+            scopes: vec![ApiScope::CreateJobs, ApiScope::ReadOwnJobs],
+            rate_limit_per_minute: None,
+            quotas: vec![QuotaLimit { metric: QuotaMetric::JobsCreated, period: QuotaPeriod::Daily, limit: 3 }],
+        }).await.unwrap();
+        let with_key = |mut request: Request<Body>| {
+            request.headers_mut().insert(API_KEY_HEADER, issued.secret.parse().unwrap());
+            app.send(request)
+        };
+
+        // Rows naming someone else are still booked on the merchant
+        let import = json!({ "jobs": [job_request(customer.id.as_str()), job_request(customer.id.as_str())] });
+        assert_eq!(app.post_json_as(&as_customer, "/jobs/bulk", &import).await.status, StatusCode::UNAUTHORIZED);
+        let imported: BulkJobResponse = with_key(json_request(Method::POST, "/jobs/bulk", &import)).await.assert_ok().json();
+        assert!(imported.jobs.iter().all(|job| job.customer_id == merchant.id));
+        assert!(app.state.job_service.get_jobs_by_customer(&customer.id, Default::default()).await.unwrap().items.is_empty());
+
+        // Two more would take the key past its three a day, so none are created
+        let response = with_key(json_request(Method::POST, "/jobs/bulk", &import)).await;
+        assert_eq!(response.error().code, ErrorCode::QuotaExceeded);
+
+        let uri = format!("/jobs/bulk/{}", imported.batch_id);
+        let batch: JobBatchStatus = with_key(Request::get(&uri).body(Body::empty()).unwrap()).await.assert_ok().json();
+        assert_eq!(batch.total, 2);
+        assert_eq!(app.get_as(&as_customer, &uri).await.status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_route_returns_error_json_with_request_id() {
+        let app = TestApp::new();
+        let response = app.get("/nowhere").await;
+
+        assert_eq!(response.status, StatusCode::NOT_FOUND);
+        let request_id = response.headers[REQUEST_ID_HEADER].to_str().unwrap().to_string();
+        let error = response.error();
+        assert_eq!(error.code, ErrorCode::NotFound);
+        assert_eq!(error.request_id, Some(request_id));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_id_is_a_validation_error() {
+        let app = TestApp::new();
+        let customer = register_user(&app, "ama@example.com", "241234567", "Customer").await;
+        let as_customer = app.sign_in(&customer.id).await;
+        let response = app.get_as(&as_customer, "/jobs?id=not-a-job").await;
+
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+        assert_eq!(response.error().code, ErrorCode::ValidationFailed);
+    }
+}
@@ -0,0 +1,44 @@
+// src/mocks/events.rs
+// A subscriber that keeps every event it hears, so tests can assert on what was published
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::domain_event::{DomainEvent, EventEnvelope},
+    services::event_bus::EventSubscriber,
+};
+
+// Clones share one log, so keep a handle after subscribing it
+#[derive(Debug, Clone, Default)]
+pub struct RecordingSubscriber {
+    seen: Arc<Mutex<Vec<EventEnvelope>>>,
+}
+
+impl RecordingSubscriber {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every event heard so far, oldest first
+    pub fn events(&self) -> Vec<DomainEvent> {
+        self.seen.lock().unwrap().iter().map(|envelope| envelope.event.clone()).collect()
+    }
+
+    /// How many events of `kind` (as in `DomainEvent::kind`) were heard
+    pub fn count(&self, kind: &str) -> usize {
+        self.seen.lock().unwrap().iter().filter(|envelope| envelope.event.kind() == kind).count()
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for RecordingSubscriber {
+    fn name(&self) -> &'static str {
+        "recording"
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<(), AppError> {
+        self.seen.lock().unwrap().push(envelope.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,470 @@
+// src/mocks/fixtures.rs
+// Realistic fake users, drivers and jobs around Accra and Kumasi, for tests, load tests
+// and the `seed` binary. Seeded fakers are deterministic.
+use chrono::Utc;
+use rand::{rngs::StdRng, seq::IndexedRandom, Rng, SeedableRng};
+
+use crate::{
+    models::{
+        driver::{self, Driver, DriverEquipment, DriverRegistration, DriverStatus, OnboardingState, Vehicle, VehicleType, MAX_RELIABILITY_SCORE},
+        ids::{DriverId, UserId},
+        job::{Dimensions, Job, JobPriority, JobRequest, Location, LocationUpdate, PackageDetails, PackageType, Pricing},
+        money::{Currency, Money},
+        tenant::DEFAULT_TENANT_ID,
+        user::{default_language, NameVisibility, User, UserRegistration, UserStatus, UserType},
+    },
+    utils::{
+        geo::haversine_km,
+        id_generator::{IdGenerator, IdType},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct City {
+    pub name: &'static str,
+    pub region: &'static str,
+    pub center: (f64, f64), // (latitude, longitude)
+    pub radius_km: f64,     // Points are drawn within this distance of the centre
+    pub neighbourhoods: &'static [&'static str],
+}
+
+pub const ACCRA: City = City {
+    name: "Accra",
+    region: "Greater Accra",
+    center: (5.6037, -0.1870),
+    radius_km: 12.0,
+    neighbourhoods: &["Osu", "East Legon", "Airport Residential", "Labone", "Cantonments", "Dansoman", "Madina", "Adabraka", "Kaneshie", "Spintex"],
+};
+
+pub const KUMASI: City = City {
+    name: "Kumasi",
+    region: "Ashanti",
+    center: (6.6885, -1.6244),
+    radius_km: 9.0,
+    neighbourhoods: &["Adum", "Asokwa", "Bantama", "Nhyiaeso", "Ahodwo", "Suame", "Kwadaso", "Tafo", "Asafo", "Ayigya"],
+};
+
+pub const CITIES: [City; 2] = [ACCRA, KUMASI];
+
+// Mobile network prefixes after +233: MTN, Telecel, AirtelTigo
+const PHONE_PREFIXES: &[&str] = &["24", "54", "55", "59", "20", "50", "26", "56", "27", "57"];
+
+const FIRST_NAMES: &[&str] = &[
+    "Kwame", "Ama", "Kofi", "Akosua", "Yaw", "Abena", "Kwabena", "Efua", "Kojo", "Adwoa",
+    "Kwaku", "Afua", "Kwesi", "Esi", "Fiifi", "Yaa", "Nana", "Akua", "Ekow", "Adjoa",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Mensah", "Asante", "Boateng", "Owusu", "Osei", "Agyeman", "Darko", "Appiah", "Addo", "Ofori",
+    "Amoah", "Acheampong", "Quaye", "Tetteh", "Ansah", "Sarpong", "Frimpong", "Nkrumah",
+];
+
+const STREETS: &[&str] = &[
+    "Oxford Street", "Liberation Road", "Ring Road", "Independence Avenue", "Kojo Thompson Road",
+    "Lagos Avenue", "Prempeh II Street", "Harper Road", "Boundary Road", "Castle Road",
+];
+
+const VEHICLES: &[(VehicleType, &str, &str, f32)] = &[
+    (VehicleType::Motorcycle, "Honda", "CG125", 20.0),
+    (VehicleType::Motorcycle, "Bajaj", "Boxer", 20.0),
+    (VehicleType::Car, "Toyota", "Corolla", 150.0),
+    (VehicleType::Car, "Hyundai", "Elantra", 150.0),
+    (VehicleType::Van, "Toyota", "HiAce", 800.0),
+    (VehicleType::Bicycle, "Raleigh", "Roadster", 8.0),
+];
+
+const COLORS: &[&str] = &["Red", "Black", "White", "Silver", "Blue", "Green"];
+
+pub struct Faker {
+    rng: StdRng,
+}
+
+impl Default for Faker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Faker {
+    pub fn new() -> Self {
+        Self { rng: StdRng::from_os_rng() }
+    }
+
+    /// Same seed, same sequence of entities
+    pub fn seeded(seed: u64) -> Self {
+        Self { rng: StdRng::seed_from_u64(seed) }
+    }
+
+    pub fn city(&mut self) -> City {
+        *CITIES.choose(&mut self.rng).unwrap()
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        items.choose(&mut self.rng).unwrap()
+    }
+
+    /// National significant number without the +233, e.g. "241234567"
+    pub fn phone_number(&mut self) -> String {
+        let prefix = self.pick(PHONE_PREFIXES);
+        format!("{}{:07}", prefix, self.rng.random_range(0..10_000_000))
+    }
+
+    pub fn full_name(&mut self) -> (String, String) {
+        (self.pick(FIRST_NAMES).to_string(), self.pick(LAST_NAMES).to_string())
+    }
+
+    pub fn email(&mut self, first_name: &str, last_name: &str) -> String {
+        format!(
+            "{}.{}{}@example.com",
+            first_name.to_lowercase(),
+            last_name.to_lowercase(),
+            self.rng.random_range(1..100_000)
+        )
+    }
+
+    /// Uniformly spread over the disc of `city.radius_km` around its centre
+    pub fn coordinates(&mut self, city: City) -> (f64, f64) {
+        let distance_km = city.radius_km * self.rng.random::<f64>().sqrt();
+        let bearing = self.rng.random_range(0.0..std::f64::consts::TAU);
+        let km_per_degree = 111.32;
+        let latitude = city.center.0 + distance_km * bearing.cos() / km_per_degree;
+        let longitude = city.center.1
+            + distance_km * bearing.sin() / (km_per_degree * city.center.0.to_radians().cos());
+        (latitude, longitude)
+    }
+
+    pub fn location(&mut self, city: City) -> Location {
+        let (latitude, longitude) = self.coordinates(city);
+        let (first_name, last_name) = self.full_name();
+        Location {
+            latitude,
+            longitude,
+            address: format!(
+                "{} {}, {}",
+                self.rng.random_range(1..200),
+                self.pick(STREETS),
+                self.pick(city.neighbourhoods)
+            ),
+            city: city.name.to_string(),
+            region: city.region.to_string(),
+            country: "Ghana".to_string(),
+            postal_code: None,
+            contact_name: format!("{} {}", first_name, last_name),
+            contact_phone: format!("+233{}", self.phone_number()),
+            instructions: None,
+        }
+    }
+
+    /// A GPS fix as a driver's phone would report it
+    pub fn gps_fix(&mut self, city: City) -> driver::Location {
+        let (latitude, longitude) = self.coordinates(city);
+        driver::Location {
+            latitude,
+            longitude,
+            accuracy: Some(self.rng.random_range(3.0..25.0)),
+            heading: Some(self.rng.random_range(0.0..360.0)),
+            speed: Some(self.rng.random_range(0.0..45.0)),
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// The same kind of fix, in the shape the location ingest endpoint takes
+    pub fn location_update(&mut self, city: City) -> LocationUpdate {
+        let fix = self.gps_fix(city);
+        LocationUpdate {
+            latitude: fix.latitude,
+            longitude: fix.longitude,
+            timestamp: fix.timestamp,
+            accuracy: fix.accuracy,
+            heading: fix.heading,
+            speed: fix.speed,
+            is_mock: false,
+        }
+    }
+
+    pub fn user_registration(&mut self, user_type: UserType) -> UserRegistration {
+        let (first_name, last_name) = self.full_name();
+        UserRegistration {
+            user_type,
+            email: self.email(&first_name, &last_name),
+            phone_number: self.phone_number(),
+            country_code: "+233".to_string(),
+            first_name,
+            last_name,
+            password: format!("fixture-{}", self.rng.random::<u32>()),
+        }
+    }
+
+    pub fn user(&mut self, user_type: UserType) -> User {
+        let registration = self.user_registration(user_type);
+        let now = Utc::now();
+        User {
+            id: UserId::generate(),
+            tenant_id: DEFAULT_TENANT_ID.to_string(),
+            user_type: registration.user_type,
+            status: UserStatus::Active,
+            email: registration.email,
+            phone_number: registration.phone_number,
+            country_code: registration.country_code,
+            first_name: registration.first_name,
+            last_name: registration.last_name,
+            display_name: None,
+            language: default_language(),
+            name_visibility: NameVisibility::default(),
+            quiet_hours: None,
+            is_email_verified: true,
+            is_phone_verified: true,
+            device_tokens: Vec::new(),
+            last_login: None,
+            current_session: None,
+            session_scopes: None,
+            session_device: None,
+            ban: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    // Regional letters as on Ghanaian plates, e.g. "GR 4821-23" for Greater Accra
+    pub fn license_plate(&mut self) -> String {
+        format!(
+            "{} {}-{}",
+            self.pick(&["GR", "GW", "GE", "AS", "GT"]),
+            self.rng.random_range(1000..10_000),
+            self.rng.random_range(15..26)
+        )
+    }
+
+    pub fn driver_registration(&mut self, user: &User) -> DriverRegistration {
+        let (vehicle_type, make, model, capacity_kg) = self.pick(VEHICLES).clone();
+        DriverRegistration {
+            user_id: user.id.clone(),
+            first_name: user.first_name.clone(),
+            last_name: user.last_name.clone(),
+            phone_number: user.phone_number.clone(),
+            email: user.email.clone(),
+            license_plate: self.license_plate(),
+            vehicle_type,
+            vehicle_make: make.to_string(),
+            vehicle_model: model.to_string(),
+            vehicle_year: self.rng.random_range(2010..2026),
+            vehicle_color: self.pick(COLORS).to_string(),
+            capacity_kg,
+            equipment: DriverEquipment::default(),
+            documents: Vec::new(),
+        }
+    }
+
+    pub fn driver(&mut self) -> Driver {
+        let user = self.user(UserType::Driver);
+        let registration = self.driver_registration(&user);
+        let city = self.city();
+        let now = Utc::now();
+        Driver {
+            id: DriverId::generate(),
+            tenant_id: DEFAULT_TENANT_ID.to_string(),
+            user_id: user.id,
+            first_name: registration.first_name,
+            last_name: registration.last_name,
+            phone_number: registration.phone_number,
+            email: registration.email,
+            status: self.pick(&[DriverStatus::Online, DriverStatus::Offline, DriverStatus::OnBreak]).clone(),
+            current_location: Some(self.gps_fix(city)),
+            vehicle: Vehicle {
+                id: IdGenerator::generate(IdType::Vehicle),
+                license_plate: registration.license_plate,
+                vehicle_type: registration.vehicle_type,
+                make: registration.vehicle_make,
+                model: registration.vehicle_model,
+                year: registration.vehicle_year,
+                color: registration.vehicle_color,
+                capacity_kg: registration.capacity_kg,
+            },
+            // Fully kitted out, so any package can be dispatched to them
+            equipment: DriverEquipment { insulated_bag: true, upright_carrier: true },
+            rating: self.rng.random_range(35..=50) as f32 / 10.0,
+            total_rides: self.rng.random_range(0..2_000),
+            reliability_score: MAX_RELIABILITY_SCORE,
+            is_verified: self.rng.random_bool(0.8),
+            onboarding_state: OnboardingState::Approved, // Past review, so dispatchable
+            documents: Vec::new(),
+            onboarding_history: Vec::new(),
+            is_active: true,
+            suspended_reason: None,
+            ban: None,
+            current_ride_id: None,
+            queued_job_id: None,
+            device_token: None,
+            break_started_at: None,
+            break_ends_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn package(&mut self) -> PackageDetails {
+        let (package_type, description, weight_range) = self.pick(&[
+            (PackageType::Document, "Signed contract", (0.1, 0.5)),
+            (PackageType::SmallPackage, "Phone accessories", (0.5, 5.0)),
+            (PackageType::MediumPackage, "Kitchen appliance", (5.0, 15.0)),
+            (PackageType::Food, "Jollof and chicken", (0.5, 3.0)),
+            (PackageType::Grocery, "Market shopping", (2.0, 12.0)),
+            (PackageType::Pharmacy, "Prescription refill", (0.1, 1.0)),
+        ]).clone();
+        PackageDetails {
+            package_type,
+            description: description.to_string(),
+            weight_kg: self.rng.random_range(weight_range.0..weight_range.1) as f32,
+            dimensions: Dimensions {
+                length_cm: self.rng.random_range(10.0..60.0),
+                width_cm: self.rng.random_range(10.0..40.0),
+                height_cm: self.rng.random_range(2.0..40.0),
+            },
+            // Below every delivery code threshold and not signed for, so a faked job never
+            // waits on a delivery code unless its test asks for one
+            estimated_value: Some(self.rng.random_range(20..500) as f64),
+            is_fragile: self.rng.random_bool(0.2),
+            requires_signature: false,
+            contains: None,
+        }
+    }
+
+    /// A booking with both ends in the same city, as almost all real jobs are
+    pub fn job_request(&mut self, customer_id: &UserId) -> JobRequest {
+        let city = self.city();
+        JobRequest {
+            customer_id: customer_id.clone(),
+            pickup_location: Some(self.location(city)),
+            pickup_address_id: None,
+            dropoff_location: Some(self.location(city)),
+            dropoff_address_id: None,
+            package: self.package(),
+            priority: self.pick(&[JobPriority::Standard, JobPriority::Standard, JobPriority::Express, JobPriority::SameDay]).clone(),
+            payment_method_id: IdGenerator::generate(IdType::Payment),
+            notes: None,
+            desired_pickup_time: None,
+            tip: None,
+            currency: None,
+            package_photo_url: None,
+            arrival_calls: None,
+        }
+    }
+
+    // Priced with flat default rates; use JobService when exact tenant pricing matters
+    pub fn job(&mut self, customer_id: &UserId) -> Job {
+        let request = self.job_request(customer_id);
+        let pickup = request.pickup_location.clone().unwrap();
+        let dropoff = request.dropoff_location.clone().unwrap();
+        let distance_km = haversine_km((pickup.latitude, pickup.longitude), (dropoff.latitude, dropoff.longitude));
+
+        let base_fare = 10.0;
+        let distance_fare = (distance_km * 2.5 * 100.0).round() / 100.0;
+        let service_fee = 2.0;
+        let money = |amount: f64| Money::from_major(amount, Currency::GHS);
+        let pricing = Pricing {
+            base_fare: money(base_fare),
+            distance_fare: money(distance_fare),
+            time_fare: money(0.0),
+            package_surcharge: money(0.0),
+            priority_surcharge: money(0.0),
+            holiday_surcharge: money(0.0),
+            service_fee: money(service_fee),
+            tax: money(0.0),
+            tax_lines: Vec::new(),
+            tip: money(0.0),
+            discount: money(0.0),
+            total: money(base_fare + distance_fare + service_fee),
+            currency: Currency::GHS,
+            estimated_cost: true,
+        };
+
+        let mut job = Job::new(request, pickup, dropoff, pricing);
+        job.tenant_id = DEFAULT_TENANT_ID.to_string();
+        job.estimated_distance_km = distance_km;
+        job.estimated_duration_min = (distance_km / 25.0 * 60.0).ceil() as i32; // ~25 km/h in traffic
+        job
+    }
+}
+
+/// proptest strategies over the same realistic value space as `Faker`
+#[cfg(test)]
+pub mod strategies {
+    use super::*;
+    use proptest::prelude::*;
+
+    pub fn city() -> impl Strategy<Value = City> {
+        prop::sample::select(CITIES.to_vec())
+    }
+
+    pub fn phone_number() -> impl Strategy<Value = String> {
+        (prop::sample::select(PHONE_PREFIXES.to_vec()), 0u32..10_000_000)
+            .prop_map(|(prefix, number)| format!("{}{:07}", prefix, number))
+    }
+
+    pub fn coordinates() -> impl Strategy<Value = (City, (f64, f64))> {
+        (city(), any::<u64>()).prop_map(|(city, seed)| (city, Faker::seeded(seed).coordinates(city)))
+    }
+
+    pub fn location() -> impl Strategy<Value = Location> {
+        (city(), any::<u64>()).prop_map(|(city, seed)| Faker::seeded(seed).location(city))
+    }
+
+    pub fn job() -> impl Strategy<Value = Job> {
+        any::<u64>().prop_map(|seed| Faker::seeded(seed).job(&UserId::generate()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::strategies;
+    use proptest::prelude::*;
+
+    use crate::utils::{geohash, polyline};
+
+    #[test]
+    fn test_seeded_faker_is_deterministic() {
+        let (mut a, mut b) = (Faker::seeded(7), Faker::seeded(7));
+        assert_eq!(a.phone_number(), b.phone_number());
+        assert_eq!(a.location(ACCRA).address, b.location(ACCRA).address);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_coordinates_stay_in_city((city, (lat, lon)) in strategies::coordinates()) {
+            prop_assert!(haversine_km(city.center, (lat, lon)) <= city.radius_km + 0.1);
+        }
+
+        #[test]
+        fn prop_phone_numbers_are_ghanaian(phone in strategies::phone_number()) {
+            prop_assert_eq!(phone.len(), 9);
+            prop_assert!(PHONE_PREFIXES.contains(&&phone[..2]));
+            prop_assert!(phone.chars().all(|c| c.is_ascii_digit()));
+        }
+
+        #[test]
+        fn prop_geohash_cell_contains_point(location in strategies::location()) {
+            let hash = geohash::encode(location.latitude, location.longitude, 7);
+            let (lat, lon) = geohash::decode_center(&hash).unwrap();
+            // A precision-7 cell is ~153m x 153m, so its centre is within ~110m
+            prop_assert!(haversine_km((lat, lon), (location.latitude, location.longitude)) < 0.12);
+        }
+
+        #[test]
+        fn prop_polyline_round_trips(locations in prop::collection::vec(strategies::location(), 1..20)) {
+            let points: Vec<(f64, f64)> = locations.iter().map(|l| (l.latitude, l.longitude)).collect();
+            let decoded = polyline::decode(&polyline::encode(&points)).unwrap();
+            prop_assert_eq!(decoded.len(), points.len());
+            for (a, b) in decoded.iter().zip(&points) {
+                prop_assert!((a.0 - b.0).abs() < 1e-5 && (a.1 - b.1).abs() < 1e-5);
+            }
+        }
+
+        #[test]
+        fn prop_fake_jobs_are_priced_and_local(job in strategies::job()) {
+            prop_assert_eq!(&job.pickup_location.city, &job.dropoff_location.city);
+            prop_assert!(job.pricing.total.minor() > 0);
+            prop_assert!(job.estimated_distance_km < 2.0 * ACCRA.radius_km + 0.1);
+        }
+    }
+}
@@ -0,0 +1,240 @@
+// src/mocks/messaging.rs
+// Notification service, SMS sender and voice gateway that keep everything they are asked to
+// send, so tests can assert on it
+use async_trait::async_trait;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{driver::Driver, ids::{DriverId, UserId}, job::{CallLanguage, DriverEarnings, Job}},
+    services::{messaging_service::{JobNotificationDetails, NotificationMessage, NotificationService}, sms::SmsSender, voice::VoiceGateway},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Recipient {
+    Device(String),
+    Devices(Vec<String>), // One multicast request
+    Topic(String),
+    Driver(DriverId),
+    User(UserId),
+}
+
+#[derive(Debug, Clone)]
+pub struct SentNotification {
+    pub recipient: Recipient,
+    pub message: NotificationMessage,
+}
+
+impl SentNotification {
+    pub fn kind(&self) -> Option<&str> {
+        self.message.kind()
+    }
+}
+
+// Clones share one log, so keep a handle after passing the service to `TestApp`
+#[derive(Debug, Clone, Default)]
+pub struct RecordingNotificationService {
+    sent: Arc<Mutex<Vec<SentNotification>>>,
+    topics: Arc<Mutex<BTreeMap<String, BTreeSet<String>>>>,
+}
+
+impl RecordingNotificationService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Everything sent so far, oldest first
+    pub fn sent(&self) -> Vec<SentNotification> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    pub fn sent_to_user(&self, user_id: &UserId) -> Vec<SentNotification> {
+        self.matching(|sent| matches!(&sent.recipient, Recipient::User(id) if id == user_id))
+    }
+
+    pub fn sent_to_driver(&self, driver_id: &DriverId) -> Vec<SentNotification> {
+        self.matching(|sent| matches!(&sent.recipient, Recipient::Driver(id) if id == driver_id))
+    }
+
+    /// By the `type` tag in the message data, e.g. "delivery_completed"
+    pub fn of_kind(&self, kind: &str) -> Vec<SentNotification> {
+        self.matching(|sent| sent.kind() == Some(kind))
+    }
+
+    pub fn sent_to_topic(&self, topic: &str) -> Vec<SentNotification> {
+        self.matching(|sent| matches!(&sent.recipient, Recipient::Topic(name) if name == topic))
+    }
+
+    /// Device tokens currently subscribed to `topic`
+    pub fn subscribers(&self, topic: &str) -> Vec<String> {
+        self.topics.lock().unwrap().get(topic).map(|tokens| tokens.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.sent.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.sent.lock().unwrap().clear();
+    }
+
+    fn matching(&self, predicate: impl Fn(&SentNotification) -> bool) -> Vec<SentNotification> {
+        self.sent.lock().unwrap().iter().filter(|sent| predicate(sent)).cloned().collect()
+    }
+
+    fn record(&self, recipient: Recipient, message: NotificationMessage) {
+        self.sent.lock().unwrap().push(SentNotification { recipient, message });
+    }
+}
+
+// Routes through the same message builders as `FcmNotificationService`, minus delivery
+#[async_trait]
+impl NotificationService for RecordingNotificationService {
+    async fn send_to_device(&self, device_token: &str, message: NotificationMessage) -> Result<(), AppError> {
+        self.record(Recipient::Device(device_token.to_string()), message);
+        Ok(())
+    }
+
+    async fn send_multicast(&self, device_tokens: &[String], message: NotificationMessage) -> Result<(), AppError> {
+        self.record(Recipient::Devices(device_tokens.to_vec()), message);
+        Ok(())
+    }
+
+    async fn send_to_topic(&self, topic: &str, message: NotificationMessage) -> Result<(), AppError> {
+        self.record(Recipient::Topic(topic.to_string()), message);
+        Ok(())
+    }
+
+    async fn subscribe_to_topic(&self, topic: &str, device_tokens: &[String]) -> Result<(), AppError> {
+        self.topics.lock().unwrap().entry(topic.to_string()).or_default().extend(device_tokens.iter().cloned());
+        Ok(())
+    }
+
+    async fn unsubscribe_from_topic(&self, topic: &str, device_tokens: &[String]) -> Result<(), AppError> {
+        if let Some(subscribed) = self.topics.lock().unwrap().get_mut(topic) {
+            subscribed.retain(|token| !device_tokens.contains(token));
+        }
+        Ok(())
+    }
+
+    async fn send_to_driver(&self, driver_id: &DriverId, message: NotificationMessage) -> Result<(), AppError> {
+        self.record(Recipient::Driver(driver_id.clone()), message);
+        Ok(())
+    }
+
+    async fn send_to_user(&self, user_id: &UserId, message: NotificationMessage) -> Result<(), AppError> {
+        self.record(Recipient::User(user_id.clone()), message);
+        Ok(())
+    }
+
+    async fn notify_driver_assigned(&self, job: &Job, driver: &Driver, earnings: &DriverEarnings, details: &JobNotificationDetails) -> Result<(), AppError> {
+        self.send_to_driver(&driver.id, NotificationMessage::driver_assigned(job, earnings, details)).await
+    }
+
+    async fn notify_package_picked_up(&self, job: &Job, details: &JobNotificationDetails) -> Result<(), AppError> {
+        self.send_to_user(&job.customer_id, NotificationMessage::package_picked_up(job, details)).await
+    }
+
+    async fn notify_delivery_completed(&self, job: &Job) -> Result<(), AppError> {
+        self.send_to_user(&job.customer_id, NotificationMessage::delivery_completed(job)).await
+    }
+
+    async fn notify_ride_status_update(&self, job: &Job, status: &str) -> Result<(), AppError> {
+        self.send_to_user(&job.customer_id, NotificationMessage::status_update(job, status)).await
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SentSms {
+    pub to: String,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RecordingSmsSender {
+    sent: Arc<Mutex<Vec<SentSms>>>,
+}
+
+impl RecordingSmsSender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every text sent so far, oldest first
+    pub fn sent(&self) -> Vec<SentSms> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl SmsSender for RecordingSmsSender {
+    async fn send(&self, to: &str, body: &str) -> Result<(), AppError> {
+        self.sent.lock().unwrap().push(SentSms { to: to.to_string(), body: body.to_string() });
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacedCall {
+    pub call_id: String,
+    pub to: String,
+    pub language: CallLanguage,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RecordingVoiceGateway {
+    placed: Arc<Mutex<Vec<PlacedCall>>>,
+}
+
+impl RecordingVoiceGateway {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every call placed so far, oldest first; IDs are "call-1", "call-2", ...
+    pub fn placed(&self) -> Vec<PlacedCall> {
+        self.placed.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl VoiceGateway for RecordingVoiceGateway {
+    async fn call(&self, to: &str, language: CallLanguage, message: &str) -> Result<String, AppError> {
+        let mut placed = self.placed.lock().unwrap();
+        let call_id = format!("call-{}", placed.len() + 1);
+        placed.push(PlacedCall { call_id: call_id.clone(), to: to.to_string(), language, message: message.to_string() });
+        Ok(call_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_queries_filter_by_recipient_and_kind() {
+        let recorder = RecordingNotificationService::new();
+        let handle = recorder.clone();
+        let user_id = UserId::generate();
+        let driver_id = DriverId::generate();
+
+        let welcome = NotificationMessage::new("Welcome", "Hi").with_data(serde_json::json!({ "type": "welcome" }));
+        recorder.send_to_user(&user_id, welcome).await.unwrap();
+        recorder.send_to_driver(&driver_id, NotificationMessage::new("Ping", "Hello")).await.unwrap();
+
+        assert_eq!(handle.len(), 2);
+        assert_eq!(handle.sent_to_user(&user_id).len(), 1);
+        assert_eq!(handle.sent_to_driver(&driver_id)[0].message.title, "Ping");
+        assert_eq!(handle.of_kind("welcome")[0].recipient, Recipient::User(user_id));
+        assert!(handle.of_kind("driver_assigned").is_empty());
+
+        handle.clear();
+        assert!(recorder.is_empty());
+    }
+}
@@ -0,0 +1,4 @@
+pub mod app;
+pub mod events;
+pub mod fixtures;
+pub mod messaging;
@@ -0,0 +1,178 @@
+// src/models/admin.rs
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+use crate::models::{
+    commission::CommissionConfig,
+    money::Currency,
+    driver::{Driver, DriverStatus, OnboardingState, VehicleType},
+    ids::{DriverId, JobId, UserId},
+    job::{Job, JobPriority, JobStatus, PaymentStatus},
+    user::{UserStatus, UserType},
+};
+
+// GET /admin/users. `q` matches part of a name, email or phone number, any case.
+#[derive(Debug, Deserialize)]
+pub struct UserSearchQuery {
+    pub status: Option<UserStatus>,
+    pub user_type: Option<UserType>,
+    pub q: Option<String>,
+    pub page: Option<String>,      // `next_page` of the previous page; omitted for the first
+    pub limit: Option<usize>,
+}
+
+// GET /admin/drivers. `q` also matches the licence plate.
+#[derive(Debug, Deserialize)]
+pub struct DriverSearchQuery {
+    pub status: Option<DriverStatus>,
+    pub is_verified: Option<bool>,
+    pub onboarding_state: Option<OnboardingState>, // e.g. the applications waiting on a background check
+    pub q: Option<String>,
+    pub page: Option<String>,
+    pub limit: Option<usize>,
+}
+
+// Results in ID order; `next_page` is None on the last page
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchPage<T> {
+    pub items: Vec<T>,
+    pub next_page: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OperationsDashboard {
+    pub generated_at: DateTime<Utc>,
+    pub active_job_count: u32,
+    pub active_jobs_by_status: BTreeMap<String, u32>,
+    pub online_driver_count: u32,
+    pub average_assignment_latency_seconds: Option<f64>, // created_at -> accepted_at
+    pub stale_after_minutes: i64,
+    pub stale_unassigned_jobs: Vec<StaleJob>,
+    pub surge_multipliers: BTreeMap<String, f64>,       // zone/region -> multiplier
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StaleJob {
+    pub job_id: JobId,
+    pub status: JobStatus,
+    pub priority: JobPriority,
+    pub pickup_city: String,
+    pub created_at: DateTime<Utc>,
+    pub waiting_minutes: i64,
+}
+
+// Report exports - one CSV row each, column order follows field order
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JobExportRow {
+    pub job_id: JobId,
+    pub tracking_code: String,
+    pub created_at: DateTime<Utc>,
+    pub status: JobStatus,
+    pub priority: JobPriority,
+    pub customer_id: UserId,
+    pub driver_id: Option<DriverId>,
+    pub pickup_city: String,
+    pub dropoff_city: String,
+    pub estimated_distance_km: f64,
+    pub total: f64,
+    pub currency: Currency,
+    pub payment_status: PaymentStatus,
+}
+
+impl From<Job> for JobExportRow {
+    fn from(job: Job) -> Self {
+        Self {
+            job_id: job.id,
+            tracking_code: job.tracking_code,
+            created_at: job.created_at,
+            status: job.status,
+            priority: job.priority,
+            customer_id: job.customer_id,
+            driver_id: job.driver_id,
+            pickup_city: job.pickup_location.city,
+            dropoff_city: job.dropoff_location.city,
+            estimated_distance_km: job.estimated_distance_km,
+            total: job.pricing.total.to_major(),
+            currency: job.pricing.currency,
+            payment_status: job.payment_status,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DriverExportRow {
+    pub driver_id: DriverId,
+    pub first_name: String,
+    pub last_name: String,
+    pub phone_number: String,
+    pub status: DriverStatus,
+    pub vehicle_type: VehicleType,
+    pub license_plate: String,
+    pub rating: f32,
+    pub total_rides: u32,
+    pub is_verified: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Driver> for DriverExportRow {
+    fn from(driver: Driver) -> Self {
+        Self {
+            driver_id: driver.id,
+            first_name: driver.first_name,
+            last_name: driver.last_name,
+            phone_number: driver.phone_number,
+            status: driver.status,
+            vehicle_type: driver.vehicle.vehicle_type,
+            license_plate: driver.vehicle.license_plate,
+            rating: driver.rating,
+            total_rides: driver.total_rides,
+            is_verified: driver.is_verified,
+            created_at: driver.created_at,
+        }
+    }
+}
+
+// Completed deliveries only - what finance pays drivers out on
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EarningsExportRow {
+    pub job_id: JobId,
+    pub driver_id: DriverId,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub fare: f64, // Total less service fee, tax and tip
+    pub tip: f64,
+    pub commission: f64,
+    pub service_fee: f64,
+    pub tax: f64,
+    pub total: f64,
+    pub platform_revenue: f64, // Service fee plus commission
+    pub currency: Currency,
+}
+
+impl EarningsExportRow {
+    // Jobs assigned before commissions were recorded fall back to today's rates
+    pub fn from_job(job: Job, commissions: &CommissionConfig) -> Option<Self> {
+        if job.status != JobStatus::DeliveryCompleted {
+            return None;
+        }
+        let commission_rate = job.commission_rate.unwrap_or_else(|| {
+            commissions.rate_for(None, &job.pickup_location.region, &job.priority)
+        });
+        let pricing = job.pricing;
+        let fare = pricing.driver_fare();
+        let commission = fare.times(commission_rate);
+        Some(Self {
+            job_id: job.id,
+            driver_id: job.driver_id?,
+            completed_at: job.dropoff_time,
+            fare: fare.to_major(),
+            tip: pricing.tip.to_major(),
+            commission: commission.to_major(),
+            service_fee: pricing.service_fee.to_major(),
+            tax: pricing.tax.to_major(),
+            total: pricing.total.to_major(),
+            platform_revenue: (pricing.service_fee + commission).to_major(),
+            currency: pricing.currency,
+        })
+    }
+}
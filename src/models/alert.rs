@@ -0,0 +1,59 @@
+// src/models/alert.rs
+// Operational alerts posted to the ops chat when a signal crosses its threshold
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    DispatchLatency,   // Jobs are taking too long to get a driver
+    PushFailureRate,   // Too many pushes failing to send
+    RedisErrors,       // Redis unreachable for several checks in a row
+    UnassignedBacklog, // Jobs waiting for a driver are piling up
+    CanaryFailed,      // The synthetic job didn't make it through its lifecycle
+    CanaryLatency,     // The synthetic job made it, but slowly
+    Sos,               // A driver or customer asked for help
+    GpsSpoofing,       // A driver keeps sending faked locations
+}
+
+impl fmt::Display for AlertKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertKind::DispatchLatency => write!(f, "dispatch_latency"),
+            AlertKind::PushFailureRate => write!(f, "push_failure_rate"),
+            AlertKind::RedisErrors => write!(f, "redis_errors"),
+            AlertKind::UnassignedBacklog => write!(f, "unassigned_backlog"),
+            AlertKind::CanaryFailed => write!(f, "canary_failed"),
+            AlertKind::CanaryLatency => write!(f, "canary_latency"),
+            AlertKind::Sos => write!(f, "sos"),
+            AlertKind::GpsSpoofing => write!(f, "gps_spoofing"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub tenant_id: Option<String>, // None for problems with the platform itself
+    pub summary: String,
+    pub value: f64,
+    pub threshold: f64,
+    pub raised_at: DateTime<Utc>,
+}
+
+impl Alert {
+    // One line, as it appears in the chat
+    pub fn text(&self) -> String {
+        let scope = self.tenant_id.as_deref().map(|tenant| format!(" [{}]", tenant)).unwrap_or_default();
+        // Nothing to measure against a threshold; the summary says it all
+        if self.kind == AlertKind::Sos {
+            return format!("🆘 {}{}: {}", self.kind, scope, self.summary);
+        }
+        format!("🚨 {}{}: {} (now {}, threshold {})", self.kind, scope, self.summary, round(self.value), round(self.threshold))
+    }
+}
+
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
@@ -0,0 +1,129 @@
+// src/models/api_key.rs
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::models::{ids::UserId, quota::QuotaLimit, scope::{Scope, ANY, READ, WRITE}, tenant::default_tenant_id, user::UserType};
+
+// Written as the scope each grants; keys stored before scopes were keep their old names
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum ApiScope {
+    #[serde(rename = "jobs:write", alias = "CreateJobs")]
+    CreateJobs,   // Book deliveries on the merchant's account
+    #[serde(rename = "jobs:read", alias = "ReadOwnJobs")]
+    ReadOwnJobs,  // Track the merchant's own deliveries
+    #[serde(rename = "invoices:read", alias = "ReadInvoices")]
+    ReadInvoices, // The merchant's monthly invoices and the month so far
+    #[serde(rename = "dispatch:*", alias = "Dispatch")]
+    Dispatch,     // Dispatcher console; only issued to dispatcher accounts
+}
+
+impl ApiScope {
+    pub fn scope(&self) -> Scope {
+        match self {
+            ApiScope::CreateJobs => Scope::new("jobs", WRITE),
+            ApiScope::ReadOwnJobs => Scope::new("jobs", READ),
+            ApiScope::ReadInvoices => Scope::new("invoices", READ),
+            ApiScope::Dispatch => Scope::new("dispatch", ANY),
+        }
+    }
+
+    // Account type a key with this scope must belong to
+    pub fn owner_type(&self) -> UserType {
+        match self {
+            ApiScope::CreateJobs | ApiScope::ReadOwnJobs | ApiScope::ReadInvoices => UserType::Business,
+            ApiScope::Dispatch => UserType::Dispatcher,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiKey {
+    pub id: String,
+    #[serde(default = "default_tenant_id")]
+    pub tenant_id: String,          // Requests made with the key run as this tenant
+    pub merchant_id: UserId,        // Business (or dispatcher) user that owns the key
+    pub name: String,               // e.g., "Shopify integration"
+    pub prefix: String,             // First characters of the secret, for recognising keys
+    pub secret_hash: String,        // SHA-256 of the secret - the secret itself is never stored
+    pub scopes: Vec<ApiScope>,
+    pub rate_limit_per_minute: u32,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>, // Set on the old key when it is rotated
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub rotated_to: Option<String>,        // Replacement key ID after rotation
+    #[serde(default)]
+    pub quotas: Vec<QuotaLimit>,
+    #[serde(default)]
+    pub usage_of: Option<String>,          // Key whose quota usage this one carries on, after rotation
+}
+
+// Request/Response Models
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub merchant_id: UserId,
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+    pub rate_limit_per_minute: Option<u32>,
+    #[serde(default)]
+    pub quotas: Vec<QuotaLimit>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiKeyResponse {
+    pub id: String,
+    pub merchant_id: UserId,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<ApiScope>,
+    pub rate_limit_per_minute: u32,
+    pub quotas: Vec<QuotaLimit>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+// Only returned at issuance/rotation - the secret cannot be retrieved later
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IssuedApiKey {
+    pub key: ApiKeyResponse,
+    pub secret: String,
+}
+
+impl ApiKey {
+    pub fn is_usable(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|expires_at| now < expires_at)
+    }
+
+    pub fn has_scope(&self, scope: &ApiScope) -> bool {
+        self.scopes.contains(scope)
+    }
+
+    pub fn grants(&self, required: &Scope) -> bool {
+        self.scopes.iter().any(|scope| scope.scope().grants(required))
+    }
+
+    // Usage is counted under the first key of a rotation chain, so rotating doesn't reset it
+    pub fn quota_id(&self) -> &str {
+        self.usage_of.as_deref().unwrap_or(&self.id)
+    }
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(key: ApiKey) -> Self {
+        Self {
+            id: key.id,
+            merchant_id: key.merchant_id,
+            name: key.name,
+            prefix: key.prefix,
+            scopes: key.scopes,
+            rate_limit_per_minute: key.rate_limit_per_minute,
+            quotas: key.quotas,
+            created_at: key.created_at,
+            last_used_at: key.last_used_at,
+            expires_at: key.expires_at,
+            revoked_at: key.revoked_at,
+        }
+    }
+}
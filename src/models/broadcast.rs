@@ -0,0 +1,94 @@
+// src/models/broadcast.rs
+// Promotional pushes to everyone following a zone. Devices subscribe to one FCM topic
+// per zone and language, so each subscriber gets the broadcast in their own language.
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+use crate::models::{ids::UserId, user::DEFAULT_LANGUAGE};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LocalizedText {
+    pub title: String,
+    pub body: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateBroadcastRequest {
+    pub zones: Vec<String>,                        // Regions or cities, e.g. "Greater Accra", "Kumasi"
+    pub messages: BTreeMap<String, LocalizedText>, // By language; English is required as the fallback
+    #[serde(default)]
+    pub send_at: Option<DateTime<Utc>>,            // Sent straight away when unset or past
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum BroadcastStatus {
+    Scheduled,
+    Sent,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Broadcast {
+    pub id: String,
+    pub zones: Vec<String>,
+    pub messages: BTreeMap<String, LocalizedText>,
+    pub send_at: DateTime<Utc>,
+    pub status: BroadcastStatus,
+    pub topics: Vec<String>,       // Topics pushed to once sent
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub sent_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateTopicsRequest {
+    pub zones: Vec<String>,
+}
+
+// The zones a user follows, in the language they were subscribed in
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TopicSubscriptions {
+    pub user_id: UserId,
+    pub zones: Vec<String>,
+    pub language: String,
+    pub device_tokens: Vec<String>,
+}
+
+impl Broadcast {
+    /// Text for subscribers speaking `language`, English when there is no translation
+    pub fn message_for(&self, language: &str) -> Option<&LocalizedText> {
+        self.messages.get(language).or_else(|| self.messages.get(DEFAULT_LANGUAGE))
+    }
+}
+
+/// Primary subtag, lowercased: "fr-GH" -> "fr"
+pub fn language_tag(language: &str) -> String {
+    language.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase()
+}
+
+/// "Cape Coast/Elmina" -> "cape-coast-elmina"
+pub fn zone_slug(zone: &str) -> String {
+    zone.to_ascii_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// FCM topic for a zone and language, namespaced by tenant since brands share one FCM project,
+/// e.g. "sparrow.zone.greater-accra.en"
+pub fn zone_topic(tenant_id: &str, zone: &str, language: &str) -> String {
+    format!("{}.zone.{}.{}", tenant_id, zone_slug(zone), language_tag(language))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zone_topics_are_valid_fcm_names() {
+        assert_eq!(zone_topic("sparrow", " Greater Accra ", "fr-GH"), "sparrow.zone.greater-accra.fr");
+        assert_eq!(zone_topic("kwik-gh", "Cape Coast/Elmina", "EN"), "kwik-gh.zone.cape-coast-elmina.en");
+    }
+}
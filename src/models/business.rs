@@ -0,0 +1,53 @@
+// src/models/business.rs
+// Staff of a business account: people with their own logins who book deliveries for the
+// business, and the address book (warehouse, branches) they all book from
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::models::ids::UserId;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StaffRole {
+    Admin,  // Manages staff and the address book
+    Editor, // Manages the address book
+    Booker, // Books from the address book
+}
+
+impl StaffRole {
+    pub fn can_edit_addresses(&self) -> bool {
+        matches!(self, StaffRole::Admin | StaffRole::Editor)
+    }
+
+    pub fn can_manage_staff(&self) -> bool {
+        *self == StaffRole::Admin
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StaffMember {
+    pub user_id: UserId,
+    pub role: StaffRole,
+    pub added_by: UserId,
+    pub added_at: DateTime<Utc>,
+}
+
+// POST /businesses/:id/staff - also changes the role of someone already on the staff
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddStaffRequest {
+    pub user_id: UserId,
+    pub role: StaffRole,
+}
+
+// POST /businesses/:id/addresses and PUT /businesses/:id/addresses/:address_id
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SharedAddressRequest {
+    pub label: String, // e.g. "Tema warehouse", "Osu branch"
+    pub street: String,
+    pub city: String,
+    pub region: String,
+    pub country: String,
+    pub postal_code: Option<String>,
+    pub latitude: f64, // Required, unlike personal addresses: shared ones are booked from as they are
+    pub longitude: f64,
+}
@@ -0,0 +1,56 @@
+// src/models/calendar.rs
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Duration, Utc};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum PeriodKind {
+    Holiday, // Public holidays, including the moveable ones like Easter and Eid
+    Peak,    // Busy spells that aren't holidays, e.g. the week before Christmas
+}
+
+// A stretch of time priced and promised differently, configured by operations. The
+// fixed-date public holidays are built in and don't need one.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CalendarPeriod {
+    pub id: String,
+    pub name: String,
+    pub kind: PeriodKind,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>, // Exclusive
+    pub price_multiplier: f64,  // 1.2 = 20% on top of the fare
+    pub sla_extension_hours: i64, // Added to Express and SameDay delivery promises
+}
+
+impl CalendarPeriod {
+    pub fn covers(&self, at: DateTime<Utc>) -> bool {
+        self.starts_at <= at && at < self.ends_at
+    }
+}
+
+// Multiplier and extension fall back to the calendar's defaults for the kind
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateCalendarPeriodRequest {
+    pub name: String,
+    pub kind: PeriodKind,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    #[serde(default)]
+    pub price_multiplier: Option<f64>,
+    #[serde(default)]
+    pub sla_extension_hours: Option<i64>,
+}
+
+// What the calendar says about a moment; the strongest of any overlapping periods
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CalendarAdjustment {
+    pub name: String, // e.g. "Christmas Day"
+    pub kind: PeriodKind,
+    pub price_multiplier: f64,
+    pub sla_extension_hours: i64,
+}
+
+impl CalendarAdjustment {
+    pub fn sla_extension(&self) -> Duration {
+        Duration::hours(self.sla_extension_hours)
+    }
+}
@@ -0,0 +1,67 @@
+// src/models/canary.rs
+// Results of the synthetic canary job, walked through its lifecycle on a schedule
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+use crate::models::ids::{DriverId, JobId, UserId};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CanaryStep {
+    Setup,    // Registering the mock customer and driver, first run only
+    Create,
+    Assign,
+    PickUp,
+    Transit,
+    Complete,
+}
+
+impl fmt::Display for CanaryStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CanaryStep::Setup => write!(f, "setup"),
+            CanaryStep::Create => write!(f, "create"),
+            CanaryStep::Assign => write!(f, "assign"),
+            CanaryStep::PickUp => write!(f, "pick_up"),
+            CanaryStep::Transit => write!(f, "transit"),
+            CanaryStep::Complete => write!(f, "complete"),
+        }
+    }
+}
+
+// The mock accounts every run reuses
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CanaryParticipants {
+    pub customer_id: UserId,
+    pub driver_id: DriverId,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CanaryStepTiming {
+    pub step: CanaryStep,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CanaryRun {
+    pub job_id: Option<JobId>,
+    pub started_at: DateTime<Utc>,
+    pub steps: Vec<CanaryStepTiming>, // Those that finished, in order
+    pub total_ms: u64,
+    pub failed_step: Option<CanaryStep>,
+    pub error: Option<String>,
+}
+
+impl CanaryRun {
+    pub fn succeeded(&self) -> bool {
+        self.failed_step.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CanaryStatus {
+    pub runs: Vec<CanaryRun>, // Most recent first
+    pub failures: usize,
+    pub average_total_ms: Option<f64>, // Of the runs that succeeded
+}
@@ -0,0 +1,79 @@
+// src/models/chaos.rs
+// Faults injected into the cache, notification and payment layers for resilience testing
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+// Any operation of the layer
+pub const ANY_OPERATION: &str = "*";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultLayer {
+    Cache,        // Every Redis call
+    Notification, // Pushes through FCM and APNs
+    Payment,      // Ledger postings, settlements included
+}
+
+impl FaultLayer {
+    // Names a rule can target, as passed to `chaos::inject`
+    pub fn operations(&self) -> &'static [&'static str] {
+        match self {
+            FaultLayer::Cache => &[
+                "get", "set", "get_or_set", "delete", "exists", "sadd", "smembers", "srem",
+                "geoadd", "geosearch", "georem", "rpush", "lrange", "incr",
+            ],
+            FaultLayer::Notification => &["send_to_device", "send_multicast", "send_to_topic"],
+            FaultLayer::Payment => &["post_entry"],
+        }
+    }
+}
+
+impl fmt::Display for FaultLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FaultLayer::Cache => write!(f, "cache"),
+            FaultLayer::Notification => write!(f, "notification"),
+            FaultLayer::Payment => write!(f, "payment"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FaultRule {
+    pub layer: FaultLayer,
+    pub operation: String,    // One of the layer's operations, or "*"
+    pub failure_percent: f64, // Of matching calls that fail outright
+    pub latency_ms: u64,
+    pub latency_percent: f64, // Of matching calls delayed by `latency_ms`
+    pub expires_at: DateTime<Utc>, // So a fault that locks out the admin API still goes away
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FaultRuleRequest {
+    pub layer: FaultLayer,
+    #[serde(default = "any_operation")]
+    pub operation: String,
+    #[serde(default)]
+    pub failure_percent: f64,
+    #[serde(default)]
+    pub latency_ms: u64,
+    #[serde(default)]
+    pub latency_percent: f64,
+    #[serde(default = "default_duration_seconds")]
+    pub duration_seconds: u64,
+}
+
+fn any_operation() -> String {
+    ANY_OPERATION.to_string()
+}
+
+fn default_duration_seconds() -> u64 {
+    300
+}
+
+// Replaces every rule in force
+#[derive(Debug, Deserialize)]
+pub struct SetFaultsRequest {
+    pub rules: Vec<FaultRuleRequest>,
+}
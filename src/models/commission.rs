@@ -0,0 +1,107 @@
+// src/models/commission.rs
+// The platform's take-rate on fares, set per tenant by admins
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::models::{driver::VehicleType, job::JobPriority};
+
+// Applies to jobs matching every field it sets; unset fields match anything
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CommissionRule {
+    #[serde(default)]
+    pub vehicle_type: Option<VehicleType>,
+    #[serde(default)]
+    pub zone: Option<String>,        // Pickup region, e.g. "Greater Accra"
+    #[serde(default)]
+    pub priority: Option<JobPriority>,
+    pub rate: f64,                   // Fraction of the fare, 0.15 = 15%
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CommissionConfig {
+    pub default_rate: f64,
+    pub rules: Vec<CommissionRule>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl Default for CommissionConfig {
+    fn default() -> Self {
+        Self {
+            default_rate: 0.15,
+            rules: Vec::new(),
+            updated_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateCommissionsRequest {
+    pub default_rate: f64,
+    #[serde(default)]
+    pub rules: Vec<CommissionRule>,
+}
+
+impl CommissionRule {
+    // A rule naming a vehicle type never matches when the vehicle is unknown
+    fn matches(&self, vehicle_type: Option<&VehicleType>, zone: &str, priority: &JobPriority) -> bool {
+        self.vehicle_type.as_ref().is_none_or(|wanted| vehicle_type == Some(wanted))
+            && self.zone.as_ref().is_none_or(|wanted| wanted.eq_ignore_ascii_case(zone))
+            && self.priority.as_ref().is_none_or(|wanted| wanted == priority)
+    }
+
+    fn specificity(&self) -> usize {
+        [self.vehicle_type.is_some(), self.zone.is_some(), self.priority.is_some()]
+            .into_iter()
+            .filter(|set| *set)
+            .count()
+    }
+}
+
+impl CommissionConfig {
+    /// Rate of the most specific matching rule, the later one on a tie; the default otherwise
+    pub fn rate_for(&self, vehicle_type: Option<&VehicleType>, zone: &str, priority: &JobPriority) -> f64 {
+        let mut best: Option<&CommissionRule> = None;
+        for rule in self.rules.iter().filter(|rule| rule.matches(vehicle_type, zone, priority)) {
+            if best.is_none_or(|best| rule.specificity() >= best.specificity()) {
+                best = Some(rule);
+            }
+        }
+        best.map_or(self.default_rate, |rule| rule.rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(vehicle_type: Option<VehicleType>, zone: Option<&str>, priority: Option<JobPriority>, rate: f64) -> CommissionRule {
+        CommissionRule {
+            vehicle_type,
+            zone: zone.map(str::to_string),
+            priority,
+            rate,
+        }
+    }
+
+    #[test]
+    fn test_most_specific_rule_wins() {
+        let config = CommissionConfig {
+            default_rate: 0.15,
+            rules: vec![
+                rule(Some(VehicleType::Motorcycle), None, None, 0.12),
+                rule(Some(VehicleType::Motorcycle), Some("Greater Accra"), None, 0.18),
+                rule(None, None, Some(JobPriority::Emergency), 0.25),
+            ],
+            updated_at: None,
+        };
+
+        let motorcycle = Some(&VehicleType::Motorcycle);
+        assert_eq!(config.rate_for(motorcycle, "Ashanti", &JobPriority::Standard), 0.12);
+        assert_eq!(config.rate_for(motorcycle, "greater accra", &JobPriority::Standard), 0.18);
+        assert_eq!(config.rate_for(Some(&VehicleType::Van), "Ashanti", &JobPriority::Standard), 0.15);
+        // Ties go to the later rule
+        assert_eq!(config.rate_for(motorcycle, "Ashanti", &JobPriority::Emergency), 0.25);
+        // Vehicle rules need a known vehicle
+        assert_eq!(config.rate_for(None, "Greater Accra", &JobPriority::Standard), 0.15);
+    }
+}
@@ -0,0 +1,60 @@
+// src/models/consumer.rs
+// Bookkeeping for the consumers that take domain events off the event log: how far each
+// has got, and the events it gave up on.
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+// Offsets are the log's entry IDs, e.g. "1718000000000-0"; "0" is the start of the log
+pub const LOG_START: &str = "0";
+
+// Each consumer in a group keeps its own, so instances never write over each other's
+// counts; the group's is the sum of them
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ConsumerCheckpoint {
+    pub group: String,
+    pub consumer: Option<String>, // None when summed over the group
+    pub offset: String,  // Last entry the consumer acknowledged
+    pub handled: u64,    // Since the checkpoint was first written
+    pub skipped: u64,    // Redeliveries of entries it had already handled
+    pub parked: u64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ConsumerCheckpoint {
+    pub fn new(group: &str, consumer: Option<&str>) -> Self {
+        Self {
+            group: group.to_string(),
+            consumer: consumer.map(str::to_string),
+            offset: LOG_START.to_string(),
+            handled: 0,
+            skipped: 0,
+            parked: 0,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+// An entry that kept failing, set aside so the ones behind it can be handled
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ParkedEvent {
+    pub group: String,
+    pub offset: String,
+    pub payload: String, // As written to the log, which may not even parse
+    pub deliveries: u64,
+    pub error: String,
+    pub parked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplayRequest {
+    // Entries after this one are delivered again; "0" replays the whole log
+    pub from: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConsumerStatus {
+    pub group: String,
+    pub checkpoint: ConsumerCheckpoint, // The furthest offset and total counts across the group
+    pub consumers: Vec<ConsumerCheckpoint>,
+    pub parked: Vec<ParkedEvent>,
+}
@@ -0,0 +1,61 @@
+// src/models/demand.rs
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+use crate::models::{ids::JobId, job::Job};
+
+// One job pickup, recorded at creation time for demand analytics
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DemandPoint {
+    pub job_id: JobId,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DemandPoint {
+    pub fn from_job(job: &Job) -> Self {
+        Self {
+            job_id: job.id.clone(),
+            latitude: job.pickup_location.latitude,
+            longitude: job.pickup_location.longitude,
+            created_at: job.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HeatmapCell {
+    pub geohash: String,
+    pub latitude: f64,  // Cell centre
+    pub longitude: f64,
+    pub pickups: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ZoneForecast {
+    pub zone: String, // Geohash at forecast precision
+    pub expected_pickups: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DriverHeatmap {
+    pub generated_at: DateTime<Utc>,
+    pub window_minutes: i64,
+    pub precision: usize,
+    pub cells: Vec<HeatmapCell>,           // Busiest first
+    pub next_hour_forecast: Vec<ZoneForecast>, // Empty until the forecast worker has run
+}
+
+// Average pickups per hour, per zone, per hour of the week (Monday 00:00 UTC = slot 0)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DemandForecast {
+    pub generated_at: DateTime<Utc>,
+    pub weeks_sampled: u32,
+    pub zones: BTreeMap<String, Vec<f64>>, // 168 slots per zone
+}
+
+impl DemandForecast {
+    pub const SLOTS_PER_WEEK: usize = 7 * 24;
+}
@@ -0,0 +1,120 @@
+// src/models/device.rs
+// Push tokens and the platform that issued them. iOS tokens are APNs device tokens and
+// are sent to Apple directly; every other token belongs to FCM.
+use serde::{Deserialize, Deserializer, Serialize};
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DevicePlatform {
+    #[default]
+    Android,
+    Ios,
+    Web,
+}
+
+impl fmt::Display for DevicePlatform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DevicePlatform::Android => write!(f, "android"),
+            DevicePlatform::Ios => write!(f, "ios"),
+            DevicePlatform::Web => write!(f, "web"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct DeviceToken {
+    pub token: String,
+    pub platform: DevicePlatform,
+}
+
+// Bare strings were stored before platforms were tracked, when every token was an FCM one
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DeviceTokenRepr {
+    Tracked { token: String, #[serde(default)] platform: DevicePlatform },
+    Legacy(String),
+}
+
+impl<'de> Deserialize<'de> for DeviceToken {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match DeviceTokenRepr::deserialize(deserializer)? {
+            DeviceTokenRepr::Tracked { token, platform } => DeviceToken::new(token, platform),
+            DeviceTokenRepr::Legacy(token) => DeviceToken::new(token, DevicePlatform::Android),
+        })
+    }
+}
+
+impl DeviceToken {
+    pub fn new(token: impl Into<String>, platform: DevicePlatform) -> Self {
+        Self {
+            token: token.into(),
+            platform,
+        }
+    }
+
+    /// Delivered by APNs rather than FCM
+    pub fn is_apns(&self) -> bool {
+        self.platform == DevicePlatform::Ios
+    }
+}
+
+/// Tokens FCM can deliver to, e.g. for topic subscriptions
+pub fn fcm_tokens(devices: &[DeviceToken]) -> Vec<String> {
+    devices.iter()
+        .filter(|device| !device.is_apns())
+        .map(|device| device.token.clone())
+        .collect()
+}
+
+// What the app reports about the handset it is logging in from
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DeviceInfo {
+    pub device_id: String,           // Stable for the install, e.g. the vendor identifier
+    #[serde(default)]
+    pub model: Option<String>,       // e.g. "Pixel 8", "iPhone15,2"
+    #[serde(default)]
+    pub os_version: Option<String>,  // e.g. "Android 15", "iOS 18.1"
+    #[serde(default)]
+    pub app_version: Option<String>,
+}
+
+// A device a user has logged in from. Revoked devices stay listed, and have to be
+// confirmed again like any unseen one if they come back from a new city.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UserDevice {
+    pub id: String,                 // Fingerprint of the platform and device id
+    pub platform: DevicePlatform,
+    pub model: Option<String>,
+    pub os_version: Option<String>,
+    pub app_version: Option<String>,
+    pub push_token: Option<String>,
+    pub last_city: Option<String>,  // Where the last login came from
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl UserDevice {
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_tokens_read_as_fcm() {
+        let devices: Vec<DeviceToken> = serde_json::from_value(serde_json::json!([
+            "fcm-1",
+            { "token": "apns-1", "platform": "ios" },
+        ])).unwrap();
+        assert_eq!(devices[0], DeviceToken::new("fcm-1", DevicePlatform::Android));
+        assert!(devices[1].is_apns());
+        assert_eq!(fcm_tokens(&devices), vec!["fcm-1".to_string()]);
+    }
+}
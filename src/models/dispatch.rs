@@ -0,0 +1,165 @@
+// src/models/dispatch.rs
+// Dispatcher console: manual overrides of automatic dispatch, and their audit trail.
+// Also the per-zone tuning of automatic dispatch that admins manage.
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+
+use crate::models::{
+    driver::{DriverResponse, DriverStatus},
+    ids::{DriverId, JobId, UserId},
+    job::{LocationUpdate, RecipientPreferences},
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CandidateDriver {
+    pub driver: DriverResponse,
+    pub distance_km: f64,        // From the driver's last known position to the pickup
+    pub reliability_score: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForceAssignRequest {
+    pub driver_id: DriverId,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ForceUnassignRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BroadcastRequest {
+    pub zone: Option<String>, // Geohash cell; defaults to the pickup's zone
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BroadcastResponse {
+    pub job_id: JobId,
+    pub zone: String,
+    pub offered_to: Vec<DriverId>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum DispatchAction {
+    ForceAssign,
+    ForceUnassign,
+    Broadcast,
+}
+
+// One manual action, kept per tenant
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DispatchAuditEntry {
+    pub action: DispatchAction,
+    pub dispatcher_id: UserId,
+    pub api_key_id: String,             // Credential the action was made with
+    pub job_id: JobId,
+    pub driver_id: Option<DriverId>,    // Driver assigned or removed
+    pub previous_driver_id: Option<DriverId>,
+    pub zone: Option<String>,
+    pub drivers_offered: Option<usize>,
+    pub reason: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+// Unset fields keep the value from the level below: the zone's, then the platform default
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct DispatchOverride {
+    #[serde(default)]
+    pub search_radius_km: Option<f64>,
+    #[serde(default)]
+    pub max_candidates: Option<usize>,
+    #[serde(default)]
+    pub offer_timeout_seconds: Option<i64>,
+}
+
+// Applies between two local times; may span midnight
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TimeOfDayOverride {
+    pub start: NaiveTime,           // e.g. "17:00:00"
+    pub end: NaiveTime,             // e.g. "20:00:00"
+    #[serde(flatten)]
+    pub settings: DispatchOverride,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ZoneDispatchSettings {
+    pub zone: String,               // Pickup region, e.g. "Greater Accra"
+    #[serde(default)]
+    pub utc_offset_minutes: Option<i32>, // The region's unless given; filled in when saved
+    #[serde(flatten)]
+    pub settings: DispatchOverride,
+    #[serde(default)]
+    pub time_of_day: Vec<TimeOfDayOverride>, // The first that covers the time wins
+}
+
+impl ZoneDispatchSettings {
+    // The zone's own settings, overlaid with the time-of-day override in force at `at`
+    pub fn overrides_at(&self, at: DateTime<Utc>) -> Vec<&DispatchOverride> {
+        let local = (at + Duration::minutes(self.utc_offset_minutes.unwrap_or_default() as i64)).time();
+        let window = self.time_of_day.iter().find(|window| {
+            if window.start <= window.end {
+                window.start <= local && local < window.end
+            } else {
+                window.start <= local || local < window.end
+            }
+        });
+        std::iter::once(&self.settings).chain(window.map(|window| &window.settings)).collect()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct DispatchSettings {
+    pub zones: Vec<ZoneDispatchSettings>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateDispatchSettingsRequest {
+    #[serde(default)]
+    pub zones: Vec<ZoneDispatchSettings>,
+}
+
+impl DispatchSettings {
+    pub fn zone(&self, zone: &str) -> Option<&ZoneDispatchSettings> {
+        self.zones.iter().find(|settings| settings.zone.eq_ignore_ascii_case(zone))
+    }
+}
+
+// Sent down a driver's WebSocket, tagged by `type`; JSON unless the socket asked for CBOR
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DriverSocketEvent {
+    JobOffer {
+        job_id: JobId,
+        expires_at: DateTime<Utc>,     // Answers after this are refused
+        offer: serde_json::Value,      // Same payload as the push offer
+    },
+    OfferAccepted { job_id: JobId },
+    OfferRejected { job_id: JobId },
+    OfferExpired { job_id: JobId },
+    OfferWithdrawn { job_id: JobId },  // Another driver took the job
+    LocationsReceived { received: usize, accepted: usize },
+    StatusUpdated { status: DriverStatus },
+    Error { job_id: Option<JobId>, message: String },
+    SessionRevoked { reason: String }, // Last frame before the server closes the socket
+    DeliveryPreferencesUpdated { job_id: JobId, preferences: RecipientPreferences },
+    Message { name: String, data: serde_json::Value }, // Published through the realtime provider
+}
+
+impl DriverSocketEvent {
+    // Location frames only matter as the newest of their kind; the rest are status frames
+    pub fn is_location(&self) -> bool {
+        matches!(self, DriverSocketEvent::LocationsReceived { .. })
+    }
+}
+
+// Sent up the same socket by the driver: answers to offers, and their own position and status
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DriverSocketReply {
+    Accept { job_id: JobId },
+    Reject { job_id: JobId },
+    Locations { locations: Vec<LocationUpdate> },
+    Status { status: DriverStatus },
+}
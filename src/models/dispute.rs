@@ -0,0 +1,80 @@
+// src/models/dispute.rs
+// Chargebacks raised against a job's payment, and the case we build to contest them
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::models::{ids::{DriverId, JobId}, money::Money};
+
+// A provider's chargeback notice, as posted to `/webhooks/payments/chargebacks`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChargebackNotification {
+    pub provider: String,  // e.g. "paystack"
+    pub event_id: String,  // The provider's ID for this notice; repeats are ignored
+    pub reference: String, // Our merchant reference for the charge, the job ID
+    pub amount: Money,
+    pub reason: String,    // As the provider words it, e.g. "fraudulent"
+    #[serde(default)]
+    pub respond_by: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeStatus {
+    Open,
+    EvidenceSubmitted,
+    Won,  // The charge stands; frozen earnings go back to the driver
+    Lost, // The customer was refunded
+}
+
+impl DisputeStatus {
+    pub fn is_closed(&self) -> bool {
+        matches!(self, DisputeStatus::Won | DisputeStatus::Lost)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DisputeEvidence {
+    pub description: String,
+    pub url: Option<String>, // e.g. a handoff photo or signed delivery receipt
+    pub submitted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DisputeCase {
+    pub id: String,
+    pub job_id: JobId,
+    pub driver_id: Option<DriverId>,
+    pub provider: String,
+    pub provider_event_id: String,
+    pub amount: Money,
+    pub reason: String,
+    pub respond_by: Option<DateTime<Utc>>,
+    pub status: DisputeStatus,
+    pub frozen_earnings: Money,         // Taken from the driver's balance until the case closes
+    pub ledger_entry_ids: Vec<String>, // Freeze, then release or loss
+    pub evidence: Vec<DisputeEvidence>,
+    pub outcome_note: Option<String>,
+    pub opened_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubmitEvidenceRequest {
+    pub description: String,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeOutcome {
+    Won,
+    Lost,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DisputeOutcomeRequest {
+    pub outcome: DisputeOutcome,
+    #[serde(default)]
+    pub note: Option<String>,
+}
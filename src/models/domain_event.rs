@@ -0,0 +1,64 @@
+// src/models/domain_event.rs
+// The things that happen in the business, as facts other parts of the system react to.
+// Services publish them on the `EventBus` once the change is saved; notifications and
+// analytics subscribe instead of being called from the middle of each service.
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::models::{ids::{DriverId, JobId, UserId}, money::Money};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DomainEvent {
+    JobCreated { job_id: JobId, customer_id: UserId },
+    JobAssigned {
+        job_id: JobId,
+        driver_id: DriverId,
+        // The driver had already been offered the job; a manual assignment counts as an offer
+        #[serde(default)]
+        offered: bool,
+    },
+    JobPickedUp { job_id: JobId, driver_id: Option<DriverId> },
+    JobCancelled {
+        job_id: JobId,
+        customer_id: UserId,
+        driver_id: Option<DriverId>,
+        // Called off by the assigned driver rather than the customer or support
+        #[serde(default)]
+        by_driver: bool,
+    },
+    JobCompleted { job_id: JobId, customer_id: UserId, driver_id: Option<DriverId> },
+    DriverWentOnline { driver_id: DriverId },
+    DriverWentOffline { driver_id: DriverId },
+    PaymentCaptured { job_id: JobId, customer_id: UserId, amount: Money },
+    PaymentRefunded { job_id: JobId, customer_id: UserId, amount: Money },
+}
+
+impl DomainEvent {
+    // The `type` tag, e.g. "job_created"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DomainEvent::JobCreated { .. } => "job_created",
+            DomainEvent::JobAssigned { .. } => "job_assigned",
+            DomainEvent::JobPickedUp { .. } => "job_picked_up",
+            DomainEvent::JobCancelled { .. } => "job_cancelled",
+            DomainEvent::JobCompleted { .. } => "job_completed",
+            DomainEvent::DriverWentOnline { .. } => "driver_went_online",
+            DomainEvent::DriverWentOffline { .. } => "driver_went_offline",
+            DomainEvent::PaymentCaptured { .. } => "payment_captured",
+            DomainEvent::PaymentRefunded { .. } => "payment_refunded",
+        }
+    }
+}
+
+// An event as it travels: who it belongs to travels with it, since subscribers may run
+// on another instance, outside the request that raised it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EventEnvelope {
+    pub event_id: String,
+    pub tenant_id: String,
+    pub region_id: String,
+    pub occurred_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: DomainEvent,
+}
@@ -1,7 +1,16 @@
 // src/models/driver.rs
 // Created on 28-08-2025 by Alfred Lotsu
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::models::{device::DeviceToken, ids::{DriverId, JobId, UserId}, job::{HandlingRequirements, LocationUpdate, PackageDetails}, moderation::AccountBan, tenant::default_tenant_id};
+
+// Every driver starts fully reliable; abandoned assignments take points off
+pub const MAX_RELIABILITY_SCORE: f32 = 100.0;
+
+pub fn default_reliability_score() -> f32 {
+    MAX_RELIABILITY_SCORE
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum DriverStatus {
@@ -12,6 +21,76 @@ pub enum DriverStatus {
     Maintenance,   // Vehicle is in maintenance
 }
 
+// Review stages a new driver passes through, in order, before they can take work
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum OnboardingState {
+    DocumentsSubmitted, // Waiting for an admin to check the documents
+    BackgroundCheck,    // Documents accepted; background check under way
+    VehicleInspection,  // Background check passed; vehicle to be inspected
+    Approved,           // Can go online and be dispatched
+    Rejected,           // Failed a stage; resubmitting documents starts over
+}
+
+impl OnboardingState {
+    // The stage an admin passing this one moves the driver to
+    pub fn next(self) -> Option<Self> {
+        match self {
+            OnboardingState::DocumentsSubmitted => Some(OnboardingState::BackgroundCheck),
+            OnboardingState::BackgroundCheck => Some(OnboardingState::VehicleInspection),
+            OnboardingState::VehicleInspection => Some(OnboardingState::Approved),
+            OnboardingState::Approved | OnboardingState::Rejected => None,
+        }
+    }
+}
+
+// Drivers registered before the review pipeline existed were let straight in
+pub fn default_onboarding_state() -> OnboardingState {
+    OnboardingState::Approved
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum DocumentKind {
+    DriversLicense,
+    NationalId,
+    VehicleRegistration,
+    Insurance,
+    RoadworthyCertificate,
+}
+
+// Reviewed by an admin; the file itself lives in object storage
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DriverDocument {
+    pub kind: DocumentKind,
+    pub url: String,
+    pub submitted_at: DateTime<Utc>,
+    #[serde(default)]
+    pub expires_at: Option<NaiveDate>, // Last day it is valid
+    #[serde(default)]
+    pub reminded_days_before: Option<i64>, // Closest expiry reminder sent so far
+}
+
+impl DriverDocument {
+    pub fn has_lapsed(&self, today: NaiveDate) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at < today)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DriverDocumentUpload {
+    pub kind: DocumentKind,
+    pub url: String,
+    #[serde(default)]
+    pub expires_at: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OnboardingTransition {
+    pub from: OnboardingState,
+    pub to: OnboardingState,
+    pub note: Option<String>, // Reviewer's note, shown to the driver
+    pub at: DateTime<Utc>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum VehicleType {
     Motorcycle,
@@ -21,6 +100,22 @@ pub enum VehicleType {
     Bicycle,
 }
 
+// Carrying gear the driver says they have; checked against a package's handling requirements
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct DriverEquipment {
+    #[serde(default)]
+    pub insulated_bag: bool,
+    #[serde(default)]
+    pub upright_carrier: bool, // Top box, rack or boot that keeps a package level
+}
+
+impl DriverEquipment {
+    // Transit time depends on the trip, not the driver, so it isn't checked here
+    pub fn covers(&self, handling: &HandlingRequirements) -> bool {
+        (!handling.insulated_bag || self.insulated_bag) && (!handling.upright_only || self.upright_carrier)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Vehicle {
     pub id: String,
@@ -45,8 +140,10 @@ pub struct Location {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Driver {
-    pub id: String,
-    pub user_id: String,        // Reference to user account
+    pub id: DriverId,
+    #[serde(default = "default_tenant_id")]
+    pub tenant_id: String,           // Brand this record belongs to
+    pub user_id: UserId,        // Reference to user account
     pub first_name: String,
     pub last_name: String,
     pub phone_number: String,
@@ -54,19 +151,60 @@ pub struct Driver {
     pub status: DriverStatus,
     pub current_location: Option<Location>,
     pub vehicle: Vehicle,
+    #[serde(default)]
+    pub equipment: DriverEquipment,
     pub rating: f32,            // Average rating (0-5)
     pub total_rides: u32,       // Total completed deliveries
-    pub is_verified: bool,
-    pub is_active: bool,
-    pub current_ride_id: Option<String>, // Currently assigned ride
-    pub device_token: Option<String>,    // For push notifications
+    #[serde(default = "default_reliability_score")]
+    pub reliability_score: f32, // 0-100, lowered when the driver abandons an assignment
+    pub is_verified: bool,      // Set once onboarding is approved
+    #[serde(default = "default_onboarding_state")]
+    pub onboarding_state: OnboardingState,
+    #[serde(default)]
+    pub documents: Vec<DriverDocument>, // Latest of each kind
+    #[serde(default)]
+    pub onboarding_history: Vec<OnboardingTransition>,
+    pub is_active: bool,        // False while suspended or banned
+    #[serde(default)]
+    pub suspended_reason: Option<String>,
+    #[serde(default)]
+    pub ban: Option<AccountBan>,
+    pub current_ride_id: Option<JobId>, // Currently assigned ride
+    #[serde(default)]
+    pub queued_job_id: Option<JobId>, // Accepted while delivering; started when the current one completes
+    pub device_token: Option<DeviceToken>, // For push notifications
+    #[serde(default)]
+    pub break_started_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub break_ends_at: Option<DateTime<Utc>>, // Set back to Offline if not ended by then
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl Driver {
+    // Kept out of dispatch until the break is ended
+    pub fn is_on_break(&self) -> bool {
+        self.status == DriverStatus::OnBreak
+    }
+
+    pub fn is_approved(&self) -> bool {
+        self.onboarding_state == OnboardingState::Approved
+    }
+
+    // Approved, not suspended and not on a break
+    pub fn can_take_work(&self) -> bool {
+        self.is_approved() && self.is_active && !self.is_on_break()
+    }
+
+    // Within the vehicle's load and the driver's equipment
+    pub fn can_carry(&self, package: &PackageDetails) -> bool {
+        package.weight_kg <= self.vehicle.capacity_kg && self.equipment.covers(&package.handling())
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DriverRegistration {
-    pub user_id: String,
+    pub user_id: UserId,
     pub first_name: String,
     pub last_name: String,
     pub phone_number: String,
@@ -78,32 +216,244 @@ pub struct DriverRegistration {
     pub vehicle_year: u16,
     pub vehicle_color: String,
     pub capacity_kg: f32,
+    #[serde(default)]
+    pub equipment: DriverEquipment,
+    #[serde(default)]
+    pub documents: Vec<DriverDocumentUpload>, // May also be sent afterwards
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DocumentSubmission {
+    pub documents: Vec<DriverDocumentUpload>,
+}
+
+// An admin's decision on the stage a driver is at. `stage` must be the driver's current
+// one, so two reviewers acting on the same application can't skip a stage between them.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OnboardingReview {
+    pub stage: OnboardingState,
+    pub note: Option<String>, // Required when rejecting
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OnboardingStatus {
+    pub driver_id: DriverId,
+    pub state: OnboardingState,
+    pub documents: Vec<DriverDocument>,
+    pub missing_documents: Vec<DocumentKind>, // Needed before documents can be accepted
+    pub history: Vec<OnboardingTransition>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DriverStatusUpdate {
-    pub driver_id: String,
+    pub driver_id: DriverId,
     pub status: DriverStatus,
     pub location: Option<Location>,
 }
 
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StartBreakRequest {
+    pub minutes: Option<u32>, // Defaults to the maximum allowed break
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DriverLocationUpdate {
-    pub driver_id: String,
+    pub driver_id: DriverId,
     pub location: Location,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DriverLocationBatch {
+    pub locations: Vec<LocationUpdate>, // Oldest first is preferred, but order is not required; may be a backlog from offline
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocationBatchResponse {
+    pub driver_id: DriverId,
+    pub received: usize,  // Points in the request
+    pub accepted: usize,  // Points kept after screening and downsampling
+    pub historical: usize, // Of those, points too old to move the live position, such as an offline backlog
+    pub rejected: usize,  // Too imprecise to use
+    pub quarantined: usize, // Held back as likely spoofed
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DriverResponse {
-    pub id: String,
+    pub id: DriverId,
     pub first_name: String,
     pub last_name: String,
     pub phone_number: String,
     pub status: DriverStatus,
     pub current_location: Option<Location>,
     pub vehicle: Vehicle,
+    #[serde(default)]
+    pub equipment: DriverEquipment,
     pub rating: f32,
     pub total_rides: u32,
     pub is_verified: bool,
-    pub current_ride_id: Option<String>,
-}
\ No newline at end of file
+    #[serde(default = "default_onboarding_state")]
+    pub onboarding_state: OnboardingState,
+    #[serde(default)]
+    pub suspended_reason: Option<String>,
+    #[serde(default)]
+    pub is_banned: bool,
+    pub current_ride_id: Option<JobId>,
+    #[serde(default)]
+    pub queued_job_id: Option<JobId>,
+    #[serde(default)]
+    pub break_ends_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_seen_at: Option<DateTime<Utc>>, // Last socket heartbeat or location update; lookups by ID only
+    // Only filled in on the driver's own profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reliability: Option<DriverReliability>,
+}
+
+impl From<Driver> for DriverResponse {
+    fn from(driver: Driver) -> Self {
+        Self {
+            id: driver.id,
+            first_name: driver.first_name,
+            last_name: driver.last_name,
+            phone_number: driver.phone_number,
+            status: driver.status,
+            current_location: driver.current_location,
+            vehicle: driver.vehicle,
+            equipment: driver.equipment,
+            rating: driver.rating,
+            total_rides: driver.total_rides,
+            is_verified: driver.is_verified,
+            onboarding_state: driver.onboarding_state,
+            suspended_reason: driver.suspended_reason,
+            is_banned: driver.ban.is_some(),
+            current_ride_id: driver.current_ride_id,
+            queued_job_id: driver.queued_job_id,
+            break_ends_at: driver.break_ends_at,
+            last_seen_at: None,
+            reliability: None,
+        }
+    }
+}
+
+// What happened each time a job was put in front of a driver
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DispatchOutcomeKind {
+    Offered,
+    Accepted,
+    Cancelled,     // Driver dropped the job after accepting it
+    PickedUp,
+    Unresponsive,  // Driver went silent before pickup and was released
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DispatchOutcome {
+    pub kind: DispatchOutcomeKind,
+    pub job_id: JobId,
+    #[serde(default)]
+    pub minutes_to_pickup: Option<i64>, // PickedUp only: time since acceptance
+    pub at: DateTime<Utc>,
+}
+
+// Composite score weights; they sum to 1
+const ACCEPTANCE_WEIGHT: f32 = 0.4;
+const COMPLETION_WEIGHT: f32 = 0.3;
+const ON_TIME_WEIGHT: f32 = 0.3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriverReliability {
+    pub offers: u32,
+    pub acceptances: u32,
+    pub cancellations: u32,
+    pub pickups: u32,
+    pub on_time_pickups: u32,
+    pub unresponsive_incidents: u32,
+    pub acceptance_rate: f32,      // Rates are 0-1 and count as perfect with no history
+    pub cancellation_rate: f32,
+    pub on_time_pickup_rate: f32,
+    pub score: f32,                // 0-100
+    pub computed_at: DateTime<Utc>,
+}
+
+impl DriverReliability {
+    pub fn from_outcomes(
+        outcomes: &[DispatchOutcome],
+        on_time_pickup_minutes: i64,
+        unresponsive_penalty: f32,
+        computed_at: DateTime<Utc>,
+    ) -> Self {
+        let count = |kind: DispatchOutcomeKind| outcomes.iter().filter(|outcome| outcome.kind == kind).count() as u32;
+        let offers = count(DispatchOutcomeKind::Offered);
+        let acceptances = count(DispatchOutcomeKind::Accepted);
+        let cancellations = count(DispatchOutcomeKind::Cancelled);
+        let pickups = count(DispatchOutcomeKind::PickedUp);
+        let unresponsive_incidents = count(DispatchOutcomeKind::Unresponsive);
+        let on_time_pickups = outcomes
+            .iter()
+            .filter(|outcome| outcome.kind == DispatchOutcomeKind::PickedUp)
+            .filter(|outcome| outcome.minutes_to_pickup.is_some_and(|minutes| minutes <= on_time_pickup_minutes))
+            .count() as u32;
+
+        let ratio = |part: u32, whole: u32, empty: f32| {
+            if whole == 0 { empty } else { (part as f32 / whole as f32).min(1.0) }
+        };
+        let acceptance_rate = ratio(acceptances, offers, 1.0);
+        let cancellation_rate = ratio(cancellations, acceptances, 0.0);
+        let on_time_pickup_rate = ratio(on_time_pickups, pickups, 1.0);
+
+        let weighted = ACCEPTANCE_WEIGHT * acceptance_rate
+            + COMPLETION_WEIGHT * (1.0 - cancellation_rate)
+            + ON_TIME_WEIGHT * on_time_pickup_rate;
+        let score = (MAX_RELIABILITY_SCORE * weighted - unresponsive_penalty * unresponsive_incidents as f32)
+            .clamp(0.0, MAX_RELIABILITY_SCORE);
+
+        Self {
+            offers,
+            acceptances,
+            cancellations,
+            pickups,
+            on_time_pickups,
+            unresponsive_incidents,
+            acceptance_rate,
+            cancellation_rate,
+            on_time_pickup_rate,
+            score,
+            computed_at,
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(kind: DispatchOutcomeKind, minutes_to_pickup: Option<i64>) -> DispatchOutcome {
+        DispatchOutcome {
+            kind,
+            job_id: JobId::generate(),
+            minutes_to_pickup,
+            at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_reliability_blends_rates_and_penalizes_incidents() {
+        let now = Utc::now();
+        let fresh = DriverReliability::from_outcomes(&[], 30, 10.0, now);
+        assert_eq!(fresh.score, MAX_RELIABILITY_SCORE);
+
+        let history = [
+            outcome(DispatchOutcomeKind::Offered, None),
+            outcome(DispatchOutcomeKind::Offered, None),
+            outcome(DispatchOutcomeKind::Accepted, None),
+            outcome(DispatchOutcomeKind::Accepted, None),
+            outcome(DispatchOutcomeKind::Cancelled, None),
+            outcome(DispatchOutcomeKind::PickedUp, Some(12)),
+            outcome(DispatchOutcomeKind::Unresponsive, None),
+        ];
+        let reliability = DriverReliability::from_outcomes(&history, 30, 10.0, now);
+        assert_eq!(reliability.acceptance_rate, 1.0);
+        assert_eq!(reliability.cancellation_rate, 0.5);
+        assert_eq!(reliability.on_time_pickup_rate, 1.0);
+        // 40 + 15 + 30, less one incident
+        assert!((reliability.score - 75.0).abs() < 1e-4);
+    }
+}
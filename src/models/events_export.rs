@@ -0,0 +1,48 @@
+// src/models/events_export.rs
+// Domain events as the data warehouse receives them, one JSON object per line
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::models::{ids::JobId, job::JobEvent};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExportedEvent {
+    pub event_id: String,     // Repeated by retries and replays, so the warehouse can drop duplicates
+    pub tenant_id: String,
+    pub occurred_at: DateTime<Utc>,
+    pub entity_type: String,  // e.g. "job"
+    pub entity_id: String,
+    pub event_type: String,   // e.g. "DriverAssigned"
+    pub actor: String,
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+impl ExportedEvent {
+    pub fn from_job_event(tenant_id: &str, job_id: &JobId, event: &JobEvent) -> Self {
+        let event_type = serde_json::to_value(&event.event_type).ok()
+            .and_then(|value| value.as_str().map(str::to_string))
+            .unwrap_or_else(|| format!("{:?}", event.event_type));
+        Self {
+            event_id: Uuid::new_v4().to_string(),
+            tenant_id: tenant_id.to_string(),
+            occurred_at: event.timestamp,
+            entity_type: "job".to_string(),
+            entity_id: job_id.to_string(),
+            event_type,
+            actor: event.actor.clone(),
+            data: json!({
+                "location": event.location,
+                "notes": event.notes,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExportRun {
+    pub objects_written: usize,
+    pub events_exported: usize,
+}
@@ -0,0 +1,76 @@
+// src/models/exchange_rate.rs
+// Rates between the registered currencies, quoted against one base currency and
+// crossed through it, e.g. NGN -> XOF goes NGN -> GHS -> XOF
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+use crate::models::money::{Currency, Money};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExchangeRates {
+    pub base: Currency,
+    pub rates: BTreeMap<Currency, f64>, // Units of each currency per one unit of `base`
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl Default for ExchangeRates {
+    fn default() -> Self {
+        Self {
+            base: Currency::GHS,
+            rates: BTreeMap::new(),
+            updated_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateExchangeRatesRequest {
+    pub base: Currency,
+    pub rates: BTreeMap<Currency, f64>,
+}
+
+impl ExchangeRates {
+    /// Units of `to` per unit of `from`; None while either currency has no rate
+    pub fn rate(&self, from: Currency, to: Currency) -> Option<f64> {
+        if from == to {
+            return Some(1.0);
+        }
+        let per_base = |currency: Currency| {
+            if currency == self.base {
+                Some(1.0)
+            } else {
+                self.rates.get(&currency).copied()
+            }
+        };
+        Some(per_base(to)? / per_base(from)?)
+    }
+
+    pub fn convert(&self, amount: Money, to: Currency) -> Option<Money> {
+        self.rate(amount.currency(), to)
+            .map(|rate| Money::from_major(amount.to_major() * rate, to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversion_crosses_through_the_base() {
+        let currency = |code: &str| Currency::parse(code).unwrap();
+        let rates = ExchangeRates {
+            base: Currency::GHS,
+            rates: BTreeMap::from([(currency("NGN"), 100.0), (currency("XOF"), 40.0)]),
+            updated_at: None,
+        };
+
+        let cedis = Money::from_major(12.5, Currency::GHS);
+        assert_eq!(rates.convert(cedis, currency("NGN")), Some(Money::from_major(1250.0, currency("NGN"))));
+        assert_eq!(rates.convert(cedis, Currency::GHS), Some(cedis));
+        // 1000 naira is 10 cedis, 400 francs
+        let naira = Money::from_major(1000.0, currency("NGN"));
+        assert_eq!(rates.convert(naira, currency("XOF")).map(|francs| francs.minor()), Some(400));
+        assert_eq!(rates.convert(naira, currency("KES")), None);
+    }
+}
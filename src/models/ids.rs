@@ -0,0 +1,133 @@
+// src/models/ids.rs
+// Typed identifiers. A `DriverId` can only be built from a string that parses as a
+// driver ID, so it cannot be handed to a user lookup by mistake.
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{
+    errors::SparrowError as AppError,
+    utils::id_generator::{IdGenerator, IdType},
+};
+
+macro_rules! typed_id {
+    ($(#[$meta:meta])* $name:ident, $id_type:expr, $field:literal, $message:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+        #[serde(transparent)]
+        pub struct $name(String);
+
+        impl $name {
+            pub const ID_TYPE: IdType = $id_type;
+
+            /// New ID in the configured `IdFormat`
+            pub fn generate() -> Self {
+                Self(IdGenerator::generate(Self::ID_TYPE))
+            }
+
+            /// Validate `value` as an ID of this type, legacy or sortable
+            pub fn parse(value: &str) -> Result<Self, AppError> {
+                if IdGenerator::validate_id(value, Some(Self::ID_TYPE)) {
+                    Ok(Self(value.to_string()))
+                } else {
+                    Err(AppError::validation_error($field, $message))
+                }
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            pub fn into_inner(self) -> String {
+                self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = AppError;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                Self::parse(value)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        // Same wire format as a plain string, but malformed IDs are rejected on the way in
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                Self::parse(&value).map_err(|_| {
+                    serde::de::Error::custom(format!("invalid {}: {}", $field, value))
+                })
+            }
+        }
+    };
+}
+
+typed_id!(
+    /// ID of a user account (customers, merchants, admins)
+    UserId, IdType::User, "user_id", "Invalid user ID format"
+);
+
+typed_id!(
+    /// ID of a driver profile; not the driver's user account
+    DriverId, IdType::Driver, "driver_id", "Invalid driver ID format"
+);
+
+typed_id!(
+    /// ID of a delivery job
+    JobId, IdType::Job, "job_id", "Invalid job ID format"
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_checks_type() {
+        let user_id = IdGenerator::generate(IdType::User);
+        assert!(UserId::parse(&user_id).is_ok());
+        assert!(DriverId::parse(&user_id).is_err());
+        assert!(JobId::parse("not-an-id").is_err());
+    }
+
+    #[test]
+    fn test_serde_is_transparent_and_validated() {
+        let job_id = JobId::generate();
+        let json = serde_json::to_string(&job_id).unwrap();
+        assert_eq!(json, format!("\"{}\"", job_id));
+        assert_eq!(serde_json::from_str::<JobId>(&json).unwrap(), job_id);
+
+        let driver_id = serde_json::to_string(&DriverId::generate()).unwrap();
+        assert!(serde_json::from_str::<JobId>(&driver_id).is_err());
+    }
+}
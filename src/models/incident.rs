@@ -0,0 +1,83 @@
+// src/models/incident.rs
+// Safety incidents opened when a driver or customer presses SOS, and the timeline ops keep
+// while following them up
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::models::ids::{DriverId, JobId, UserId};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", content = "id", rename_all = "snake_case")]
+pub enum IncidentReporter {
+    Driver(DriverId),
+    Customer(UserId),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IncidentPriority {
+    High,   // Someone may be in danger: pages on-call ops straight away
+    Normal,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IncidentStatus {
+    Open,
+    Acknowledged, // Someone in ops is on it
+    Resolved,     // Releases the job it froze
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct IncidentLocation {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub accuracy: Option<f64>,
+    pub captured_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct IncidentUpdate {
+    pub at: DateTime<Utc>,
+    pub actor: String, // "system", or whoever in ops made the update
+    pub status: Option<IncidentStatus>, // Set when the update moved the incident on
+    pub note: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Incident {
+    pub id: String,
+    pub reporter: IncidentReporter,
+    pub priority: IncidentPriority,
+    pub status: IncidentStatus,
+    pub job_id: Option<JobId>, // Frozen until the incident is resolved
+    pub location: Option<IncidentLocation>, // As sent with the SOS, else the driver's last known position
+    pub message: Option<String>,
+    pub timeline: Vec<IncidentUpdate>, // Oldest first, starting with the SOS itself
+    pub opened_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+// POST /drivers/:id/sos and POST /users/:id/sos
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SosRequest {
+    #[serde(default)]
+    pub job_id: Option<JobId>, // A driver's current job when left out; customers must name theirs
+    #[serde(default)]
+    pub latitude: Option<f64>,
+    #[serde(default)]
+    pub longitude: Option<f64>,
+    #[serde(default)]
+    pub accuracy: Option<f64>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+// POST /admin/incidents/:id/updates
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IncidentUpdateRequest {
+    pub actor: String,
+    pub note: String,
+    #[serde(default)]
+    pub status: Option<IncidentStatus>,
+}
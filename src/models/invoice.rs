@@ -0,0 +1,95 @@
+// src/models/invoice.rs
+// Monthly invoices for business accounts that would rather not be charged per job
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::models::{ids::{JobId, UserId}, money::{Currency, Money}};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BillingMode {
+    #[default]
+    PerJob,         // Each job is charged to its payment method on completion
+    MonthlyInvoice, // Completed jobs are billed together at the end of the month
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BillingAccount {
+    pub merchant_id: UserId,
+    pub mode: BillingMode,
+    pub payment_terms_days: u32,       // Invoices fall due this long after they are issued
+    pub billing_email: Option<String>, // Where invoices go; the account email when unset
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateBillingAccountRequest {
+    pub mode: BillingMode,
+    #[serde(default)]
+    pub payment_terms_days: Option<u32>,
+    #[serde(default)]
+    pub billing_email: Option<String>,
+}
+
+// Calendar month a job is billed in, e.g. "2026-09"
+pub fn billing_period(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m").to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InvoiceLine {
+    pub job_id: JobId,
+    pub description: String,   // e.g. "Delivery JOB-4821 to East Legon"
+    pub completed_at: DateTime<Utc>,
+    pub amount: Money,         // Including tax
+    pub tax: Money,
+}
+
+// The month so far, before it is issued
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InvoiceDraft {
+    pub merchant_id: UserId,
+    pub period: String,
+    pub lines: Vec<InvoiceLine>,
+    pub totals: Vec<Money>, // One per currency billed
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum InvoiceStatus {
+    Issued,
+    Overdue, // Past its due date and still unpaid
+    Paid,
+}
+
+impl InvoiceStatus {
+    pub fn is_open(&self) -> bool {
+        matches!(self, InvoiceStatus::Issued | InvoiceStatus::Overdue)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Invoice {
+    pub id: String,
+    pub merchant_id: UserId,
+    pub period: String,
+    pub currency: Currency,
+    pub lines: Vec<InvoiceLine>,
+    pub subtotal: Money,
+    pub tax: Money,
+    pub total: Money,
+    pub status: InvoiceStatus,
+    pub issued_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub emailed_to: Option<String>,
+    pub reminders_sent: u32,
+    pub last_reminded_at: Option<DateTime<Utc>>,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub payment_reference: Option<String>, // e.g. the bank transfer reference
+    pub ledger_entry_id: Option<String>,   // The payment's ledger entry
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordInvoicePaymentRequest {
+    pub reference: String,
+}
@@ -1,10 +1,15 @@
 // src/models/job.rs
 // Created on 28-08-2025 by Alfred Lotsu
 use serde::{Deserialize, Serialize};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use std::fmt;
 
+use crate::models::{calendar::CalendarAdjustment, ids::{DriverId, JobId, UserId}, money::{Currency, Money}, region::{default_region_id, Region}, tax::{TaxLine, TaxLineRecord}, tenant::default_tenant_id, user::Address};
+use crate::errors::SparrowError as AppError;
+use crate::services::{region::current_region_id, tenant_service::current_tenant_id};
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum JobStatus {
     Pending,           // Job created, waiting for driver acceptance
@@ -29,6 +34,33 @@ pub enum JobPriority {
     Emergency,   // Immediate delivery (within 1 hour)
 }
 
+impl JobPriority {
+    // Promised delivery deadline for priorities that carry a time guarantee, for a job booked
+    // in a region `utc_offset_minutes` ahead of UTC
+    pub fn delivery_deadline(&self, created_at: DateTime<Utc>, utc_offset_minutes: i32) -> Option<DateTime<Utc>> {
+        match self {
+            JobPriority::Express => Some(created_at + chrono::Duration::hours(4)),
+            JobPriority::SameDay => {
+                // End of the local calendar day
+                let offset = chrono::Duration::minutes(utc_offset_minutes as i64);
+                let end_of_day = (created_at + offset).date_naive().and_hms_opt(23, 59, 59)?.and_utc();
+                Some(end_of_day - offset)
+            }
+            JobPriority::Standard | JobPriority::Emergency => None,
+        }
+    }
+
+    // The next faster tier, offered when a job at this priority found no driver
+    pub fn next_tier(&self) -> Option<JobPriority> {
+        match self {
+            JobPriority::Standard => Some(JobPriority::SameDay),
+            JobPriority::SameDay => Some(JobPriority::Express),
+            JobPriority::Express => Some(JobPriority::Emergency),
+            JobPriority::Emergency => None,
+        }
+    }
+}
+
 impl fmt::Display for JobPriority {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -54,6 +86,47 @@ pub enum PackageType {
     Fragile,     // Fragile items requiring special care
 }
 
+impl PackageType {
+    // What the package needs from whoever carries it
+    pub fn handling_requirements(&self) -> HandlingRequirements {
+        match self {
+            PackageType::Food => HandlingRequirements {
+                insulated_bag: true,
+                upright_only: true,
+                max_transit_minutes: Some(45),
+            },
+            PackageType::Pharmacy => HandlingRequirements {
+                insulated_bag: true,
+                upright_only: false,
+                max_transit_minutes: Some(90),
+            },
+            PackageType::Grocery => HandlingRequirements {
+                insulated_bag: true,
+                upright_only: false,
+                max_transit_minutes: Some(120),
+            },
+            PackageType::Fragile | PackageType::Electronics => HandlingRequirements {
+                upright_only: true,
+                ..HandlingRequirements::default()
+            },
+            _ => HandlingRequirements::default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct HandlingRequirements {
+    pub insulated_bag: bool,              // Hot or chilled contents
+    pub upright_only: bool,               // Must ride in a carrier that keeps it level
+    pub max_transit_minutes: Option<i32>, // Longest it should spend between pickup and dropoff
+}
+
+impl HandlingRequirements {
+    pub fn is_special(&self) -> bool {
+        self.insulated_bag || self.upright_only || self.max_transit_minutes.is_some()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Location {
     pub latitude: f64,
@@ -80,6 +153,15 @@ pub struct PackageDetails {
     pub contains: Option<String>, // What's inside the package
 }
 
+impl PackageDetails {
+    // The package type's requirements; anything marked fragile also travels upright
+    pub fn handling(&self) -> HandlingRequirements {
+        let mut handling = self.package_type.handling_requirements();
+        handling.upright_only |= self.is_fragile;
+        handling
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Dimensions {
     pub length_cm: f32,
@@ -87,25 +169,209 @@ pub struct Dimensions {
     pub height_cm: f32,
 }
 
+impl Dimensions {
+    // Zero when any side wasn't given
+    pub fn volume_cm3(&self) -> f32 {
+        self.length_cm * self.width_cm * self.height_cm
+    }
+}
+
+// What the image-analysis provider made of a package photo
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PackageEstimate {
+    pub package_type: PackageType,
+    pub dimensions: Dimensions,
+    pub confidence: f32, // 0-1
+}
+
+// The customer's photo of the package, shown to the driver who collects it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PackagePhoto {
+    pub url: String,
+    pub estimate: Option<PackageEstimate>, // None without a provider, or when it couldn't tell
+    #[serde(default)]
+    pub size_mismatch: bool,               // Detected as clearly bigger than declared
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(from = "PricingRecord", into = "PricingRecord")]
 pub struct Pricing {
-    pub base_fare: f64,
-    pub distance_fare: f64,
-    pub time_fare: f64,
-    pub package_surcharge: f64,
-    pub priority_surcharge: f64,
-    pub service_fee: f64,
-    pub tax: f64,
-    pub total: f64,
-    pub currency: String, // "GHS" for Ghana Cedis
+    pub base_fare: Money,
+    pub distance_fare: Money,
+    pub time_fare: Money,
+    pub package_surcharge: Money,
+    pub priority_surcharge: Money,
+    pub holiday_surcharge: Money, // Public holidays and peak periods, on the fare before fees
+    pub service_fee: Money,
+    pub tax: Money, // Sum of the tax lines
+    pub tax_lines: Vec<TaxLine>,
+    pub tip: Money, // Passed on to the driver in full
+    pub discount: Money, // Already taken off the total, e.g. for sharing a pooled ride
+    pub total: Money,
+    pub currency: Currency, // GHS for Ghana Cedis
     pub estimated_cost: bool, // Whether this is an estimate or final price
 }
 
+// Wire and cache format of `Pricing`: decimal amounts beside one currency code
+#[derive(Serialize, Deserialize)]
+struct PricingRecord {
+    base_fare: f64,
+    distance_fare: f64,
+    time_fare: f64,
+    package_surcharge: f64,
+    priority_surcharge: f64,
+    #[serde(default)]
+    holiday_surcharge: f64,
+    service_fee: f64,
+    tax: f64,
+    #[serde(default)]
+    tax_lines: Vec<TaxLineRecord>,
+    #[serde(default)]
+    tip: f64,
+    #[serde(default)]
+    discount: f64,
+    total: f64,
+    currency: Currency,
+    estimated_cost: bool,
+}
+
+impl From<PricingRecord> for Pricing {
+    fn from(record: PricingRecord) -> Self {
+        let currency = record.currency;
+        let money = |amount: f64| Money::from_major(amount, currency);
+        Self {
+            base_fare: money(record.base_fare),
+            distance_fare: money(record.distance_fare),
+            time_fare: money(record.time_fare),
+            package_surcharge: money(record.package_surcharge),
+            priority_surcharge: money(record.priority_surcharge),
+            holiday_surcharge: money(record.holiday_surcharge),
+            service_fee: money(record.service_fee),
+            tax: money(record.tax),
+            tax_lines: record.tax_lines.into_iter().map(|line| line.into_line(currency)).collect(),
+            tip: money(record.tip),
+            discount: money(record.discount),
+            total: money(record.total),
+            currency,
+            estimated_cost: record.estimated_cost,
+        }
+    }
+}
+
+impl From<Pricing> for PricingRecord {
+    fn from(pricing: Pricing) -> Self {
+        Self {
+            base_fare: pricing.base_fare.to_major(),
+            distance_fare: pricing.distance_fare.to_major(),
+            time_fare: pricing.time_fare.to_major(),
+            package_surcharge: pricing.package_surcharge.to_major(),
+            priority_surcharge: pricing.priority_surcharge.to_major(),
+            holiday_surcharge: pricing.holiday_surcharge.to_major(),
+            service_fee: pricing.service_fee.to_major(),
+            tax: pricing.tax.to_major(),
+            tax_lines: pricing.tax_lines.into_iter().map(TaxLineRecord::from).collect(),
+            tip: pricing.tip.to_major(),
+            discount: pricing.discount.to_major(),
+            total: pricing.total.to_major(),
+            currency: pricing.currency,
+            estimated_cost: pricing.estimated_cost,
+        }
+    }
+}
+
+impl Pricing {
+    // The fare for the delivery itself: the total less our service fee, tax and the tip
+    pub fn driver_fare(&self) -> Money {
+        self.total - self.service_fee - self.tax - self.tip
+    }
+}
+
+// The driver's cut of a job, as shown before they accept it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(from = "DriverEarningsRecord", into = "DriverEarningsRecord")]
+pub struct DriverEarnings {
+    pub fare: Money,
+    pub commission_rate: f64,
+    pub commission: Money,
+    pub tip: Money,
+    pub surge_bonus: Money, // The driver's share of the surge at the pickup
+    pub escalation_bonus: Money, // Paid on jobs that waited long for a driver
+    pub total: Money,
+    pub currency: Currency,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DriverEarningsRecord {
+    fare: f64,
+    commission_rate: f64,
+    commission: f64,
+    tip: f64,
+    surge_bonus: f64,
+    #[serde(default)]
+    escalation_bonus: f64,
+    total: f64,
+    currency: Currency,
+}
+
+impl From<DriverEarningsRecord> for DriverEarnings {
+    fn from(record: DriverEarningsRecord) -> Self {
+        let money = |amount: f64| Money::from_major(amount, record.currency);
+        Self {
+            fare: money(record.fare),
+            commission_rate: record.commission_rate,
+            commission: money(record.commission),
+            tip: money(record.tip),
+            surge_bonus: money(record.surge_bonus),
+            escalation_bonus: money(record.escalation_bonus),
+            total: money(record.total),
+            currency: record.currency,
+        }
+    }
+}
+
+impl From<DriverEarnings> for DriverEarningsRecord {
+    fn from(earnings: DriverEarnings) -> Self {
+        Self {
+            fare: earnings.fare.to_major(),
+            commission_rate: earnings.commission_rate,
+            commission: earnings.commission.to_major(),
+            tip: earnings.tip.to_major(),
+            surge_bonus: earnings.surge_bonus.to_major(),
+            escalation_bonus: earnings.escalation_bonus.to_major(),
+            total: earnings.total.to_major(),
+            currency: earnings.currency,
+        }
+    }
+}
+
+// How far a job waiting for a driver has been escalated. Each step only ever raises these;
+// the booked `priority` and the customer's price stay as they were.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JobEscalation {
+    pub level: usize,                 // Escalation steps applied so far, from 1
+    pub priority: JobPriority,        // What dispatch treats the job as
+    pub radius_multiplier: f64,       // Applied to the dispatch search radius
+    pub payout_bonus: Money,          // Added to the driver's earnings, paid by us
+    pub escalated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeliverySla {
+    pub promised_by: DateTime<Utc>,
+    pub at_risk_notified_at: Option<DateTime<Utc>>,
+    pub breached_at: Option<DateTime<Utc>>,
+    pub goodwill_credit: Option<Money>, // Amount credited to the customer on breach
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Job {
-    pub id: String,
-    pub customer_id: String,
-    pub driver_id: Option<String>,
+    pub id: JobId,
+    #[serde(default = "default_tenant_id")]
+    pub tenant_id: String,           // Brand this record belongs to
+    #[serde(default = "default_region_id")]
+    pub region: String,              // Market it was booked in, from the pickup's country
+    pub customer_id: UserId,
+    pub driver_id: Option<DriverId>,
     pub status: JobStatus,
     pub priority: JobPriority,
     
@@ -117,6 +383,8 @@ pub struct Job {
     
     // Package information
     pub package: PackageDetails,
+    #[serde(default)]
+    pub package_photo: Option<PackagePhoto>,
     
     // Timing information
     pub created_at: DateTime<Utc>,
@@ -125,9 +393,29 @@ pub struct Job {
     pub dropoff_time: Option<DateTime<Utc>>,
     pub cancelled_at: Option<DateTime<Utc>>,
     pub expires_at: DateTime<Utc>, // When job will expire if not accepted
+    #[serde(default)]
+    pub sla: Option<DeliverySla>,  // Only for priorities with a delivery guarantee
+    #[serde(default)]
+    pub escalation: Option<JobEscalation>, // Set once the job has waited too long for a driver
+    #[serde(default)]
+    pub recipient_preferences: Option<RecipientPreferences>, // Left by the dropoff contact on the tracking page
+    #[serde(default)]
+    pub pool_id: Option<String>, // Shares a driver and route with other jobs; tracked on its own
+    #[serde(default)]
+    pub delivery_code: Option<DeliveryCode>, // Texted to the recipient for valuable or signed-for packages
+    #[serde(default)]
+    pub dropoff_change: Option<DropoffChange>, // Awaiting the customer's approval of its price
+    #[serde(default)]
+    pub arrival_calls: Option<ArrivalCalls>,
+    #[serde(default)]
+    pub frozen_by: Option<String>, // An open SOS incident; the job goes nowhere until ops resolve it
+    #[serde(default)]
+    pub anonymized_at: Option<DateTime<Utc>>, // Set by the retention purge; see Job::anonymize
     
     // Pricing information
     pub pricing: Pricing,
+    #[serde(default)]
+    pub commission_rate: Option<f64>, // Fixed when a driver takes the job
     pub payment_method_id: String,
     pub payment_status: PaymentStatus,
     
@@ -138,8 +426,8 @@ pub struct Job {
     pub feedback: Option<String>,
     
     // Driver assignment history
-    pub offered_to_drivers: Vec<String>, // Driver IDs who were offered this job
-    pub rejected_by_drivers: Vec<String>, // Driver IDs who rejected this job
+    pub offered_to_drivers: Vec<DriverId>, // Driver IDs who were offered this job
+    pub rejected_by_drivers: Vec<DriverId>, // Driver IDs who rejected this job
     
     pub updated_at: DateTime<Utc>,
 }
@@ -148,23 +436,42 @@ pub struct Job {
 pub enum PaymentStatus {
     Pending,
     Authorized,
+    Voided,             // Authorization released without capture
     Paid,
     Failed,
     Refunded,
     PartiallyRefunded,
+    Disputed,           // A chargeback is open against the payment
+    Invoiced,           // Billed on the merchant's monthly invoice, not yet paid
 }
 
 // Request/Response Models
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JobRequest {
-    pub customer_id: String,
-    pub pickup_location: Location,
-    pub dropoff_location: Location,
+    pub customer_id: UserId,
+    // Either a full location or the ID of one of the customer's saved addresses, or of
+    // the shared addresses of the business they book for
+    #[serde(default)]
+    pub pickup_location: Option<Location>,
+    #[serde(default)]
+    pub pickup_address_id: Option<String>,
+    #[serde(default)]
+    pub dropoff_location: Option<Location>,
+    #[serde(default)]
+    pub dropoff_address_id: Option<String>,
     pub package: PackageDetails,
     pub priority: JobPriority,
     pub payment_method_id: String,
     pub notes: Option<String>,
     pub desired_pickup_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub tip: Option<f64>,
+    #[serde(default)]
+    pub currency: Option<Currency>, // The tenant's home currency when unset
+    #[serde(default)]
+    pub package_photo_url: Option<String>, // Uploaded beforehand, like driver documents
+    #[serde(default)]
+    pub arrival_calls: Option<ArrivalCalls>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -173,13 +480,71 @@ pub struct JobEstimateRequest {
     pub dropoff_location: Location,
     pub package: PackageDetails,
     pub priority: JobPriority,
+    #[serde(default)]
+    pub currency: Option<Currency>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobEstimate {
+    #[serde(flatten)]
+    pub pricing: Pricing,
+    pub handling: HandlingRequirements,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>, // Shown to the customer before they book
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub occasion: Option<CalendarAdjustment>, // The holiday or peak behind `holiday_surcharge`
+}
+
+// Newest first; `next_cursor` fetches the page after this one and is absent on the last
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobHistoryPage {
+    pub items: Vec<JobResponse>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct JobHistoryQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+// Where a page of job history stopped: the last job's creation time, then its ID to order
+// jobs created in the same millisecond. Opaque to clients.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobCursor {
+    pub created_at_ms: i64,
+    pub job_id: JobId,
+}
+
+impl JobCursor {
+    pub fn after(job: &JobResponse) -> Self {
+        Self {
+            created_at_ms: job.created_at.timestamp_millis(),
+            job_id: job.id.clone(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}:{}", self.created_at_ms, self.job_id))
+    }
+
+    pub fn parse(cursor: &str) -> Result<Self, AppError> {
+        let invalid = || AppError::validation_error("cursor", "Not a cursor from a previous page");
+        let decoded = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+        let (created_at_ms, job_id) = decoded.split_once(':').ok_or_else(invalid)?;
+        Ok(Self {
+            created_at_ms: created_at_ms.parse().map_err(|_| invalid())?,
+            job_id: JobId::parse(job_id).map_err(|_| invalid())?,
+        })
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JobResponse {
-    pub id: String,
-    pub customer_id: String,
-    pub driver_id: Option<String>,
+    pub id: JobId,
+    pub customer_id: UserId,
+    pub driver_id: Option<DriverId>,
     pub status: JobStatus,
     pub priority: JobPriority,
     pub pickup_location: Location,
@@ -187,9 +552,28 @@ pub struct JobResponse {
     pub estimated_distance_km: f64,
     pub estimated_duration_min: i32,
     pub package: PackageDetails,
+    #[serde(default)]
+    pub package_photo: Option<PackagePhoto>,
     pub created_at: DateTime<Utc>,
     pub pickup_time: Option<DateTime<Utc>>,
     pub dropoff_time: Option<DateTime<Utc>>,
+    pub promised_by: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub escalation: Option<JobEscalation>,
+    #[serde(default)]
+    pub recipient_preferences: Option<RecipientPreferences>,
+    #[serde(default)]
+    pub pool_id: Option<String>,
+    #[serde(default)]
+    pub delivery_code: Option<DeliveryCode>,
+    #[serde(default)]
+    pub dropoff_change: Option<DropoffChange>,
+    #[serde(default)]
+    pub arrival_calls: Option<ArrivalCalls>,
+    #[serde(default)]
+    pub frozen_by: Option<String>,
+    #[serde(default = "default_region_id")]
+    pub region: String,
     pub pricing: Pricing,
     pub payment_status: PaymentStatus,
     pub tracking_code: String,
@@ -197,31 +581,319 @@ pub struct JobResponse {
     pub rating: Option<f32>,
 }
 
+// Compatible jobs carried together by one driver: every pickup first, then every dropoff.
+// Each job keeps its own status, tracking code and price.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JobPool {
+    pub id: String,
+    pub job_ids: Vec<JobId>,
+    pub stops: Vec<PoolStop>, // In the order the driver makes them
+    pub discount_rate: f64,   // Taken off each job's fare
+    pub combined_weight_kg: f32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StopKind {
+    Pickup,
+    Dropoff,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PoolStop {
+    pub job_id: JobId,
+    pub kind: StopKind,
+    pub location: Location,
+}
+
+// What the dropoff contact asked for through the tracking link; they needn't have an account
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct RecipientPreferences {
+    pub instructions: Option<String>,
+    pub safe_drop: Option<String>,            // Where the package may be left if nobody answers
+    pub deliver_after: Option<DateTime<Utc>>, // Delivery rescheduled to no earlier than this
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// PUT /track/:tracking_code/preferences. Fields left out are kept; an empty one clears it.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct UpdateRecipientPreferencesRequest {
+    pub instructions: Option<String>,
+    pub safe_drop: Option<String>,
+    pub deliver_after: Option<DateTime<Utc>>,
+}
+
+// The public tracking page: enough for the recipient, nothing about the sender or payment
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrackingView {
+    pub tracking_code: String,
+    pub status: JobStatus,
+    pub dropoff_address: String,
+    pub driver_name: Option<String>, // First name only
+    pub pickup_time: Option<DateTime<Utc>>,
+    pub dropoff_time: Option<DateTime<Utc>>,
+    pub promised_by: Option<DateTime<Utc>>,
+    pub recipient_preferences: Option<RecipientPreferences>,
+}
+
+// A link the customer hands to friends or family to follow a delivery live. It shows less
+// than the tracking page and grants nothing: the tracking code lets its holder change how
+// the package is delivered.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TripShare {
+    pub token: String,
+    pub job_id: JobId,
+    pub path: String, // e.g. /shared/3q2-...; the app puts its own host in front
+    pub created_by: UserId,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>, // Or earlier, once the delivery is over
+}
+
+// POST /jobs/:id/share
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ShareTripRequest {
+    #[serde(default)]
+    pub expires_in_minutes: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SharedDriverLocation {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+// GET /shared/:token - read-only, and only while the delivery is under way
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SharedTripView {
+    pub status: JobStatus,
+    pub driver_name: Option<String>, // First name only
+    pub driver_location: Option<SharedDriverLocation>, // Once a driver has the job
+    pub dropoff_address: String,
+    pub dropoff_latitude: f64,
+    pub dropoff_longitude: f64,
+    pub promised_by: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+}
+
+// Which end of the trip a handoff code is for: the sender shows one at pickup, the recipient
+// the other at dropoff
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HandoffStage {
+    Pickup,
+    Dropoff,
+}
+
+impl fmt::Display for HandoffStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandoffStage::Pickup => write!(f, "pickup"),
+            HandoffStage::Dropoff => write!(f, "dropoff"),
+        }
+    }
+}
+
+// QR payloads for the customer app to render
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HandoffCodes {
+    pub job_id: JobId,
+    pub pickup: String,
+    pub dropoff: String,
+}
+
+// POST /jobs/:id/scan
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HandoffScanRequest {
+    pub payload: String, // Exactly as read from the QR code
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CallLanguage {
+    English,
+    Twi,
+}
+
+// Automated voice calls announcing the driver's arrival, for contacts without a smartphone
+// to follow the tracking link on; see services::arrival_calls
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArrivalCalls {
+    pub language: CallLanguage,
+    #[serde(default = "default_true")]
+    pub pickup: bool,  // Call the pickup contact when the driver arrives to collect
+    #[serde(default = "default_true")]
+    pub dropoff: bool, // Call the dropoff contact when the driver arrives to deliver
+}
+
+fn default_true() -> bool {
+    true
+}
+
+// A code texted to the dropoff contact when the driver arrives, which the driver must enter
+// to complete the delivery. The code itself is only kept hashed; see services::delivery_code.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DeliveryCode {
+    pub sent_to: String,                // Masked
+    pub sent_at: Option<DateTime<Utc>>, // None when the text never went out
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub overridden_by: Option<String>, // Support agent who completed the delivery without it
+    pub override_reason: Option<String>,
+}
+
+impl DeliveryCode {
+    pub fn is_settled(&self) -> bool {
+        self.confirmed_at.is_some() || self.overridden_by.is_some()
+    }
+}
+
+// POST /jobs/:id/status - the driver's progress up to the dropoff; completing goes
+// through /jobs/:id/complete
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DriverJobStatusRequest {
+    pub status: JobStatus,
+    pub notes: Option<String>,
+}
+
+// POST /jobs/:id/delivery-code
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfirmDeliveryRequest {
+    pub code: String, // As read out by the recipient
+}
+
+// POST /admin/jobs/:id/delivery-code/override
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OverrideDeliveryCodeRequest {
+    pub agent: String,  // Who approved it, for the job's event log
+    pub reason: String, // e.g. what the recipient showed instead
+}
+
+// A new dropoff the customer asked for mid-delivery, re-priced. The job keeps its old
+// dropoff and price until the customer approves the new total.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DropoffChange {
+    pub dropoff_location: Location,
+    pub estimated_distance_km: f64,
+    pub estimated_duration_min: i32,
+    pub pricing: Pricing,
+    pub previous_total: Money,
+    pub requested_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>, // After which the customer has to ask again
+}
+
+// PATCH /jobs/:id/dropoff - a location or one of the customer's saved addresses
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangeDropoffRequest {
+    pub dropoff_location: Option<Location>,
+    pub dropoff_address_id: Option<String>,
+}
+
+// POST /jobs/:id/dropoff/approve
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApproveDropoffChangeRequest {
+    pub total: f64, // The new total as shown to the customer; must match the change's
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JobStatusUpdate {
-    pub job_id: String,
+    pub job_id: JobId,
     pub status: JobStatus,
-    pub driver_id: Option<String>,
+    pub driver_id: Option<DriverId>,
     pub notes: Option<String>, // Reason for cancellation, etc.
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JobAssignment {
-    pub job_id: String,
-    pub driver_id: String,
+    pub job_id: JobId,
+    pub driver_id: DriverId,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JobRejection {
-    pub job_id: String,
-    pub driver_id: String,
+    pub job_id: JobId,
+    pub driver_id: DriverId,
     pub reason: Option<String>, // Why driver rejected the job
 }
 
+// Bulk import Models
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkJobRequest {
+    pub jobs: Vec<JobRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkJobResponse {
+    pub batch_id: String,
+    pub created: usize,
+    pub jobs: Vec<JobResponse>,
+}
+
+// One row of a CSV manifest. Each side is either a saved address ID
+// or the full set of pickup_*/dropoff_* columns.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobManifestRow {
+    pub customer_id: UserId,
+    pub pickup_address_id: Option<String>,
+    pub pickup_latitude: Option<f64>,
+    pub pickup_longitude: Option<f64>,
+    pub pickup_address: Option<String>,
+    pub pickup_city: Option<String>,
+    pub pickup_region: Option<String>,
+    pub pickup_contact_name: Option<String>,
+    pub pickup_contact_phone: Option<String>,
+    pub dropoff_address_id: Option<String>,
+    pub dropoff_latitude: Option<f64>,
+    pub dropoff_longitude: Option<f64>,
+    pub dropoff_address: Option<String>,
+    pub dropoff_city: Option<String>,
+    pub dropoff_region: Option<String>,
+    pub dropoff_contact_name: Option<String>,
+    pub dropoff_contact_phone: Option<String>,
+    pub package_type: PackageType,
+    pub package_description: String,
+    pub weight_kg: f32,
+    #[serde(default)]
+    pub is_fragile: bool,
+    #[serde(default)]
+    pub requires_signature: bool,
+    pub priority: JobPriority,
+    pub payment_method_id: String,
+    pub notes: Option<String>,
+}
+
+// Jobs created together by one bulk import
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JobBatch {
+    pub id: String,
+    #[serde(default)]
+    pub merchant_id: Option<UserId>, // Batches cached before merchants were recorded have none
+    pub job_ids: Vec<JobId>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobBatchItem {
+    pub job_id: JobId,
+    pub tracking_code: String,
+    pub status: JobStatus,
+    pub driver_id: Option<DriverId>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobBatchStatus {
+    pub batch_id: String,
+    pub created_at: DateTime<Utc>,
+    pub total: usize,
+    pub assigned: usize,  // Jobs that have a driver
+    pub completed: usize, // Jobs in a terminal state
+    pub jobs: Vec<JobBatchItem>,
+}
+
 // Tracking Models
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JobTracking {
-    pub job_id: String,
+    pub job_id: JobId,
     pub status: JobStatus,
     pub current_location: Option<LocationUpdate>,
     pub driver_location: Option<LocationUpdate>,
@@ -229,7 +901,7 @@ pub struct JobTracking {
     pub events: Vec<JobEvent>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct LocationUpdate {
     pub latitude: f64,
     pub longitude: f64,
@@ -237,9 +909,53 @@ pub struct LocationUpdate {
     pub accuracy: Option<f64>,
     pub heading: Option<f64>,
     pub speed: Option<f64>,
+    #[serde(default)]
+    pub is_mock: bool, // The phone reported the fix as coming from a mock location provider
+}
+
+// Route history - stored as encoded polyline segments, one per location batch
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RouteSegment {
+    pub polyline: String,
+    pub point_count: usize,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+pub struct JobRoute {
+    pub job_id: JobId,
+    pub polyline: String,           // Full traveled path as one encoded polyline
+    pub point_count: usize,
+    pub actual_distance_km: f64,    // Sum of distances between recorded points
+    pub estimated_distance_km: f64, // What the customer was quoted on
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+// Where the driver still has to go for a job, and links that open turn-by-turn directions.
+// Sent with the assignment and again whenever a stop moves.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NavigationPlan {
+    pub job_id: JobId,
+    pub origin: Option<(f64, f64)>,      // Driver's last reported position; None lets Maps use the device's
+    pub destination: NavigationStop,
+    pub waypoints: Vec<NavigationStop>,  // In the order to make them, before the destination
+    pub google_maps_url: String,         // Universal link; opens the app where installed
+    pub google_maps_app_url: String,     // comgooglemaps:// scheme for iOS
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NavigationStop {
+    pub job_id: JobId, // Which job the stop belongs to; differs from the plan's on a pooled route
+    pub kind: StopKind,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub address: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct JobEvent {
     pub event_type: JobEventType,
     pub timestamp: DateTime<Utc>,
@@ -248,7 +964,7 @@ pub struct JobEvent {
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum JobEventType {
     JobCreated,
     DriverAssigned,
@@ -259,16 +975,35 @@ pub enum JobEventType {
     ArrivedAtDropoff,
     DeliveryCompleted,
     JobCancelled,
+    JobExpired,
+    DriverUnresponsive,
+    DriverUnassigned,
+    PriorityEscalated,
+    HandoffScanned,
+    DeliveryCodeSent,
+    DeliveryCodeConfirmed,
+    DeliveryCodeOverridden,
+    DropoffChanged,
+    ArrivalCallPlaced,
+    ArrivalCallUpdated, // The voice gateway reported how the call went
+    IncidentOpened,     // An SOS froze the job
+    IncidentResolved,
+    RecipientPreferencesUpdated,
+    Pooled,
     StatusUpdated,
     LocationUpdated,
     PaymentProcessed,
+    PaymentDisputed,
 }
 
 // Driver Job Models
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AvailableJob {
     pub job: JobResponse,
+    #[serde(default)]
+    pub offered: bool,              // Offered to this driver, rather than just nearby
     pub estimated_earnings: f64,
+    pub earnings: DriverEarnings,
     pub distance_to_pickup_km: f64,
     pub time_to_pickup_min: i32,
     pub customer_rating: Option<f32>,
@@ -276,7 +1011,7 @@ pub struct AvailableJob {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DriverJobStats {
-    pub driver_id: String,
+    pub driver_id: DriverId,
     pub total_jobs: u32,
     pub completed_jobs: u32,
     pub active_jobs: u32,
@@ -294,8 +1029,8 @@ pub struct JobFilter {
     pub status: Option<Vec<JobStatus>>,
     pub priority: Option<Vec<JobPriority>>,
     pub date_range: Option<DateRange>,
-    pub customer_id: Option<String>,
-    pub driver_id: Option<String>,
+    pub customer_id: Option<UserId>,
+    pub driver_id: Option<DriverId>,
     pub has_rating: Option<bool>,
 }
 
@@ -341,28 +1076,79 @@ pub struct RegionStats {
 }
 
 // Helper implementations
+impl Location {
+    // Keeps the city, and the coordinates to roughly a kilometre
+    fn anonymize(&mut self) {
+        self.latitude = (self.latitude * 100.0).round() / 100.0;
+        self.longitude = (self.longitude * 100.0).round() / 100.0;
+        self.address = String::new();
+        self.postal_code = None;
+        self.contact_name = String::new();
+        self.contact_phone = String::new();
+        self.instructions = None;
+    }
+}
+
 impl Job {
-    pub fn new(job_request: JobRequest, pricing: Pricing) -> Self {
+    /// Strips everything that identifies the people involved, keeping what the books and
+    /// analytics need: ids, status, timings, pricing and the rough route
+    pub fn anonymize(&mut self, at: DateTime<Utc>) {
+        self.pickup_location.anonymize();
+        self.dropoff_location.anonymize();
+        self.package.description = String::new();
+        self.package.contains = None;
+        self.package_photo = None;
+        self.recipient_preferences = None;
+        self.notes = None;
+        self.feedback = None;
+        self.anonymized_at = Some(at);
+        self.updated_at = at;
+    }
+
+    // The priority dispatch and commissions go by: the escalated one, if any
+    pub fn effective_priority(&self) -> &JobPriority {
+        self.escalation.as_ref().map_or(&self.priority, |escalation| &escalation.priority)
+    }
+
+    // Locations are passed separately since the request may only reference saved addresses
+    pub fn new(job_request: JobRequest, pickup_location: Location, dropoff_location: Location, pricing: Pricing) -> Self {
         let tracking_code = format!("GH{}", Uuid::new_v4().to_string()[..8].to_uppercase());
+        let created_at = Utc::now();
+        let region = current_region_id();
+        let utc_offset_minutes = Region::builtin(&region).map_or(0, |region| region.utc_offset_minutes);
+        let sla = job_request.priority.delivery_deadline(created_at, utc_offset_minutes).map(DeliverySla::new);
         
         Self {
-            id: Uuid::new_v4().to_string(),
+            id: JobId::generate(),
+            tenant_id: current_tenant_id(),
+            region,
             customer_id: job_request.customer_id,
             driver_id: None,
             status: JobStatus::Pending,
             priority: job_request.priority,
-            pickup_location: job_request.pickup_location,
-            dropoff_location: job_request.dropoff_location,
+            pickup_location,
+            dropoff_location,
             estimated_distance_km: 0.0, // Will be calculated
             estimated_duration_min: 0,   // Will be calculated
             package: job_request.package,
-            created_at: Utc::now(),
+            package_photo: None,
+            created_at,
             accepted_at: None,
             pickup_time: None,
             dropoff_time: None,
             cancelled_at: None,
-            expires_at: Utc::now() + chrono::Duration::hours(2), // 2 hours to accept
+            expires_at: created_at + chrono::Duration::hours(2), // 2 hours to accept
+            sla,
+            escalation: None,
+            recipient_preferences: None,
+            pool_id: None,
+            delivery_code: None,
+            dropoff_change: None,
+            arrival_calls: job_request.arrival_calls,
+            frozen_by: None,
+            anonymized_at: None,
             pricing,
+            commission_rate: None,
             payment_method_id: job_request.payment_method_id,
             payment_status: PaymentStatus::Pending,
             tracking_code,
@@ -376,6 +1162,177 @@ impl Job {
     }
 }
 
+impl JobStatus {
+    // No further transitions are expected
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::DeliveryCompleted | JobStatus::Cancelled | JobStatus::Failed | JobStatus::Expired
+        )
+    }
+
+    // A driver holds the job but hasn't collected the package yet
+    pub fn is_awaiting_pickup(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::DriverAssigned | JobStatus::DriverEnRoute | JobStatus::ArrivedAtPickup
+        )
+    }
+
+    // The driver has the package on board
+    pub fn is_carrying_package(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::PackagePickedUp | JobStatus::InTransit | JobStatus::ArrivedAtDropoff
+        )
+    }
+
+    // Where a job stands on the route its driver reports as they go, from assignment to
+    // arriving at the dropoff
+    pub fn route_step(&self) -> Option<u8> {
+        match self {
+            JobStatus::DriverAssigned => Some(0),
+            JobStatus::DriverEnRoute => Some(1),
+            JobStatus::ArrivedAtPickup => Some(2),
+            JobStatus::PackagePickedUp => Some(3),
+            JobStatus::InTransit => Some(4),
+            JobStatus::ArrivedAtDropoff => Some(5),
+            _ => None,
+        }
+    }
+
+    // The customer can still send the package somewhere else
+    pub fn allows_dropoff_change(&self) -> bool {
+        !self.is_terminal() && *self != JobStatus::ArrivedAtDropoff
+    }
+
+    // A driver is attached and moving the job forward
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::DriverAssigned
+                | JobStatus::DriverEnRoute
+                | JobStatus::ArrivedAtPickup
+                | JobStatus::PackagePickedUp
+                | JobStatus::InTransit
+                | JobStatus::ArrivedAtDropoff
+        )
+    }
+}
+
+impl Location {
+    // Build a job location from a saved address; None if the address was never geocoded
+    pub fn from_saved_address(address: &Address, contact_name: String, contact_phone: String) -> Option<Self> {
+        Some(Self {
+            latitude: address.latitude?,
+            longitude: address.longitude?,
+            address: address.street.clone(),
+            city: address.city.clone(),
+            region: address.region.clone(),
+            country: address.country.clone(),
+            postal_code: address.postal_code.clone(),
+            contact_name,
+            contact_phone,
+            instructions: None,
+        })
+    }
+}
+
+impl DeliverySla {
+    pub fn new(promised_by: DateTime<Utc>) -> Self {
+        Self {
+            promised_by,
+            at_risk_notified_at: None,
+            breached_at: None,
+            goodwill_credit: None,
+        }
+    }
+}
+
+impl JobManifestRow {
+    pub fn into_request(self) -> Result<JobRequest, String> {
+        let pickup_location = manifest_location(
+            "pickup",
+            self.pickup_latitude,
+            self.pickup_longitude,
+            self.pickup_address,
+            self.pickup_city,
+            self.pickup_region,
+            self.pickup_contact_name,
+            self.pickup_contact_phone,
+        )?;
+        let dropoff_location = manifest_location(
+            "dropoff",
+            self.dropoff_latitude,
+            self.dropoff_longitude,
+            self.dropoff_address,
+            self.dropoff_city,
+            self.dropoff_region,
+            self.dropoff_contact_name,
+            self.dropoff_contact_phone,
+        )?;
+
+        Ok(JobRequest {
+            customer_id: self.customer_id,
+            pickup_location,
+            pickup_address_id: self.pickup_address_id,
+            dropoff_location,
+            dropoff_address_id: self.dropoff_address_id,
+            package: PackageDetails {
+                package_type: self.package_type,
+                description: self.package_description,
+                weight_kg: self.weight_kg,
+                dimensions: Dimensions { length_cm: 0.0, width_cm: 0.0, height_cm: 0.0 }, // Not captured in manifests
+                estimated_value: None,
+                is_fragile: self.is_fragile,
+                requires_signature: self.requires_signature,
+                contains: None,
+            },
+            priority: self.priority,
+            payment_method_id: self.payment_method_id,
+            notes: self.notes,
+            desired_pickup_time: None,
+            tip: None,
+            currency: None,
+            package_photo_url: None,
+            arrival_calls: None,
+        })
+    }
+}
+
+// Build a location from manifest columns; None when the row uses a saved address instead
+#[allow(clippy::too_many_arguments)]
+fn manifest_location(
+    side: &str,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    address: Option<String>,
+    city: Option<String>,
+    region: Option<String>,
+    contact_name: Option<String>,
+    contact_phone: Option<String>,
+) -> Result<Option<Location>, String> {
+    let (latitude, longitude) = match (latitude, longitude) {
+        (Some(latitude), Some(longitude)) => (latitude, longitude),
+        (None, None) => return Ok(None),
+        _ => return Err(format!("{}_latitude and {}_longitude must be given together", side, side)),
+    };
+    let contact_phone = contact_phone.ok_or_else(|| format!("{}_contact_phone is required", side))?;
+
+    Ok(Some(Location {
+        latitude,
+        longitude,
+        address: address.ok_or_else(|| format!("{}_address is required", side))?,
+        city: city.unwrap_or_default(),
+        region: region.unwrap_or_default(),
+        country: "Ghana".to_string(),
+        postal_code: None,
+        contact_name: contact_name.unwrap_or_default(),
+        contact_phone,
+        instructions: None,
+    }))
+}
+
 impl Dimensions {
     pub fn volume(&self) -> f32 {
         self.length_cm * self.width_cm * self.height_cm
@@ -397,4 +1354,81 @@ impl PackageType {
             PackageType::Fragile => 10.0,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_delivery_deadline() {
+        let created_at = Utc.with_ymd_and_hms(2025, 9, 1, 10, 30, 0).unwrap();
+
+        assert_eq!(
+            JobPriority::Express.delivery_deadline(created_at, 0),
+            Some(Utc.with_ymd_and_hms(2025, 9, 1, 14, 30, 0).unwrap())
+        );
+        assert_eq!(
+            JobPriority::SameDay.delivery_deadline(created_at, 0),
+            Some(Utc.with_ymd_and_hms(2025, 9, 1, 23, 59, 59).unwrap())
+        );
+        assert_eq!(JobPriority::Standard.delivery_deadline(created_at, 0), None);
+
+        // Same day ends at local midnight: 21:00 in Nairobi, and the next UTC day already
+        // for a job booked there at 22:30 UTC
+        assert_eq!(
+            JobPriority::SameDay.delivery_deadline(created_at, 180),
+            Some(Utc.with_ymd_and_hms(2025, 9, 1, 20, 59, 59).unwrap())
+        );
+        let late = Utc.with_ymd_and_hms(2025, 9, 1, 22, 30, 0).unwrap();
+        assert_eq!(
+            JobPriority::SameDay.delivery_deadline(late, 180),
+            Some(Utc.with_ymd_and_hms(2025, 9, 2, 20, 59, 59).unwrap())
+        );
+    }
+
+    fn manifest_row() -> JobManifestRow {
+        JobManifestRow {
+            customer_id: UserId::generate(),
+            pickup_address_id: Some("add_warehouse".to_string()),
+            pickup_latitude: None,
+            pickup_longitude: None,
+            pickup_address: None,
+            pickup_city: None,
+            pickup_region: None,
+            pickup_contact_name: None,
+            pickup_contact_phone: None,
+            dropoff_address_id: None,
+            dropoff_latitude: Some(5.5560),
+            dropoff_longitude: Some(-0.1820),
+            dropoff_address: Some("12 Oxford St, Osu".to_string()),
+            dropoff_city: Some("Accra".to_string()),
+            dropoff_region: None,
+            dropoff_contact_name: None,
+            dropoff_contact_phone: Some("+233201234567".to_string()),
+            package_type: PackageType::SmallPackage,
+            package_description: "Shoes".to_string(),
+            weight_kg: 1.5,
+            is_fragile: false,
+            requires_signature: false,
+            priority: JobPriority::Standard,
+            payment_method_id: "pay_test".to_string(),
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn test_manifest_row_into_request() {
+        let request = manifest_row().into_request().unwrap();
+        assert_eq!(request.pickup_address_id.as_deref(), Some("add_warehouse"));
+        assert!(request.pickup_location.is_none());
+        assert_eq!(request.dropoff_location.unwrap().city, "Accra");
+    }
+
+    #[test]
+    fn test_manifest_row_requires_both_coordinates() {
+        let row = JobManifestRow { dropoff_longitude: None, ..manifest_row() };
+        assert!(row.into_request().is_err());
+    }
+}
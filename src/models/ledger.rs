@@ -0,0 +1,213 @@
+// src/models/ledger.rs
+// Double-entry bookkeeping for every movement of money: each entry debits some accounts
+// and credits others by the same total, so the books always balance.
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{ids::{DriverId, JobId, UserId}, money::Money},
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PlatformAccount {
+    Cash,        // Held at payment providers on our behalf
+    Commission,  // Our revenue from fares
+    Tax,         // Collected on fares, owed to the revenue authority
+    Promotions,  // Bonuses and goodwill we pay for
+    Disputes,    // Driver earnings frozen while a chargeback is open
+    Chargebacks, // What lost chargebacks cost us beyond the frozen earnings
+}
+
+impl PlatformAccount {
+    pub const ALL: [PlatformAccount; 6] = [
+        PlatformAccount::Cash,
+        PlatformAccount::Commission,
+        PlatformAccount::Tax,
+        PlatformAccount::Promotions,
+        PlatformAccount::Disputes,
+        PlatformAccount::Chargebacks,
+    ];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            PlatformAccount::Cash => "cash",
+            PlatformAccount::Commission => "commission",
+            PlatformAccount::Tax => "tax",
+            PlatformAccount::Promotions => "promotions",
+            PlatformAccount::Disputes => "disputes",
+            PlatformAccount::Chargebacks => "chargebacks",
+        }
+    }
+}
+
+// Whose money an account tracks. Written as `customer:usr-...`, `driver:drv-...` or
+// `platform:commission` in paths and cache keys.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LedgerAccount {
+    Customer(UserId), // Wallet balance we owe the customer
+    Driver(DriverId), // Earnings we owe the driver, less payouts
+    Platform(PlatformAccount),
+}
+
+impl LedgerAccount {
+    // The side that increases the account, so balances read as positive in normal use
+    pub fn normal_side(&self) -> EntrySide {
+        match self {
+            LedgerAccount::Platform(PlatformAccount::Cash | PlatformAccount::Promotions | PlatformAccount::Chargebacks) => EntrySide::Debit,
+            _ => EntrySide::Credit,
+        }
+    }
+}
+
+impl fmt::Display for LedgerAccount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerAccount::Customer(user_id) => write!(f, "customer:{}", user_id),
+            LedgerAccount::Driver(driver_id) => write!(f, "driver:{}", driver_id),
+            LedgerAccount::Platform(account) => write!(f, "platform:{}", account.as_str()),
+        }
+    }
+}
+
+impl FromStr for LedgerAccount {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || AppError::validation_error("account", format!("{} is not a ledger account", value));
+        let (kind, id) = value.split_once(':').ok_or_else(invalid)?;
+        match kind {
+            "customer" => Ok(LedgerAccount::Customer(UserId::parse(id)?)),
+            "driver" => Ok(LedgerAccount::Driver(DriverId::parse(id)?)),
+            "platform" => PlatformAccount::ALL
+                .into_iter()
+                .find(|account| account.as_str() == id)
+                .map(LedgerAccount::Platform)
+                .ok_or_else(invalid),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Serialize for LedgerAccount {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for LedgerAccount {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntrySide {
+    Debit,
+    Credit,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Posting {
+    pub account: LedgerAccount,
+    pub side: EntrySide,
+    pub amount: Money, // Always positive; the side says which way it moves
+}
+
+impl Posting {
+    pub fn debit(account: LedgerAccount, amount: Money) -> Self {
+        Self { account, side: EntrySide::Debit, amount }
+    }
+
+    pub fn credit(account: LedgerAccount, amount: Money) -> Self {
+        Self { account, side: EntrySide::Credit, amount }
+    }
+
+    // The change this posting makes to its account's balance
+    pub fn signed_amount(&self) -> Money {
+        if self.side == self.account.normal_side() { self.amount } else { -self.amount }
+    }
+}
+
+// One balanced transaction. Entries are never edited or removed; mistakes are put right
+// with a reversing entry.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LedgerEntry {
+    pub id: String,
+    pub posting_key: String, // Unique per tenant; posting the same key twice records one entry
+    pub description: String,
+    pub job_id: Option<JobId>,
+    #[serde(default)]
+    pub provider_reference: Option<String>, // How the payment provider knows the money, for reconciliation
+    pub postings: Vec<Posting>,
+    pub posted_at: DateTime<Utc>,
+}
+
+impl LedgerEntry {
+    // What moved through `account` in this entry, in its normal direction
+    pub fn net_for(&self, account: &LedgerAccount) -> Option<Money> {
+        self.postings.iter()
+            .filter(|posting| &posting.account == account)
+            .map(|posting| posting.signed_amount())
+            .reduce(|total, amount| total + amount)
+    }
+}
+
+// Manual adjustments posted by finance
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateLedgerEntryRequest {
+    pub posting_key: String,
+    pub description: String,
+    #[serde(default)]
+    pub job_id: Option<JobId>,
+    #[serde(default)]
+    pub provider_reference: Option<String>,
+    pub postings: Vec<Posting>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AccountBalance {
+    pub account: LedgerAccount,
+    pub balance: Money, // In the account's normal direction
+    pub entries: usize,
+}
+
+// An entry as it touched one account, with the balance after it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StatementLine {
+    pub entry_id: String,
+    pub description: String,
+    pub job_id: Option<JobId>,
+    pub side: EntrySide,
+    pub amount: Money,
+    pub balance: Money,
+    pub posted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AccountStatement {
+    pub account: LedgerAccount,
+    pub opening_balance: Money,
+    pub closing_balance: Money,
+    pub lines: Vec<StatementLine>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accounts_round_trip_through_their_names() {
+        let driver = LedgerAccount::Driver(DriverId::generate());
+        assert_eq!(driver.to_string().parse::<LedgerAccount>().unwrap(), driver);
+        assert_eq!("platform:tax".parse::<LedgerAccount>().unwrap(), LedgerAccount::Platform(PlatformAccount::Tax));
+        assert!("platform:vault".parse::<LedgerAccount>().is_err());
+        assert!("driver:usr-1".parse::<LedgerAccount>().is_err());
+        assert_eq!(serde_json::to_value(LedgerAccount::Platform(PlatformAccount::Cash)).unwrap(), "platform:cash");
+    }
+}
@@ -1,4 +1,11 @@
-enum NotificationType {
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::{models::ids::UserId, services::messaging_service};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationType {
     DriverAssigned,        // "Your driver Kwame is coming!"
     PackagePickedUp,       // "Your package has been collected"
     DriverNearby,          // "Your driver is 5min away!"  
@@ -6,12 +13,162 @@ enum NotificationType {
     RideStatusUpdate,      // "Status changed to In Progress"
     PaymentConfirmed,      // "Payment received via Mobile Money"
     GhanaPromotional,      // "Weekend discount for Accra deliveries!"
+    JobExpired,            // "No driver available"
+    DriverReassigning,     // "Finding you a new driver"
+    JobOffer,              // "Delivery available nearby"
+    JobUnassigned,         // "Delivery reassigned"
+    OnboardingUpdate,      // "Background check passed"
+    DocumentExpiring,      // "Your insurance expires in 7 days"
+    DriverSuspended,       // "Account suspended: licence expired"
+    DriverReinstated,      // "You're back on the road"
+    DeliveryPreferencesUpdated, // "Recipient added a safe-drop spot"
+}
+
+impl NotificationType {
+    pub const ALL: [NotificationType; 16] = [
+        NotificationType::DriverAssigned,
+        NotificationType::PackagePickedUp,
+        NotificationType::DriverNearby,
+        NotificationType::DeliveryCompleted,
+        NotificationType::RideStatusUpdate,
+        NotificationType::PaymentConfirmed,
+        NotificationType::GhanaPromotional,
+        NotificationType::JobExpired,
+        NotificationType::DriverReassigning,
+        NotificationType::JobOffer,
+        NotificationType::JobUnassigned,
+        NotificationType::OnboardingUpdate,
+        NotificationType::DocumentExpiring,
+        NotificationType::DriverSuspended,
+        NotificationType::DriverReinstated,
+        NotificationType::DeliveryPreferencesUpdated,
+    ];
+
+    // The `type` tag carried in a message's data
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NotificationType::DriverAssigned => "driver_assigned",
+            NotificationType::PackagePickedUp => "package_picked_up",
+            NotificationType::DriverNearby => "driver_nearby",
+            NotificationType::DeliveryCompleted => "delivery_completed",
+            NotificationType::RideStatusUpdate => "status_update",
+            NotificationType::PaymentConfirmed => "payment_confirmed",
+            NotificationType::GhanaPromotional => "promotion",
+            NotificationType::JobExpired => "job_expired",
+            NotificationType::DriverReassigning => "driver_reassigning",
+            NotificationType::JobOffer => "job_offer",
+            NotificationType::JobUnassigned => "job_unassigned",
+            NotificationType::OnboardingUpdate => "onboarding_update",
+            NotificationType::DocumentExpiring => "document_expiring",
+            NotificationType::DriverSuspended => "driver_suspended",
+            NotificationType::DriverReinstated => "driver_reinstated",
+            NotificationType::DeliveryPreferencesUpdated => "delivery_preferences_updated",
+        }
+    }
+
+    // Everything but promotions concerns a delivery someone is waiting on
+    pub fn is_critical(&self) -> bool {
+        *self != NotificationType::GhanaPromotional
+    }
+
+    pub fn from_kind(kind: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|notification_type| notification_type.kind() == kind)
+    }
+
+    /// Placeholders a template of this type may use, filled from the message's data
+    pub fn variables(&self) -> &'static [&'static str] {
+        match self {
+            NotificationType::DriverAssigned => &["job_id", "amount", "pickup_address", "dropoff_address", "customer_name", "eta_to_pickup_minutes", "priority"],
+            NotificationType::PackagePickedUp => &["job_id", "driver_name", "estimated_arrival", "estimated_arrival_minutes"],
+            NotificationType::DeliveryCompleted => &["job_id", "amount", "completion_time"],
+            NotificationType::RideStatusUpdate => &["job_id", "status", "timestamp"],
+            NotificationType::JobExpired => &["job_id", "reason", "suggested_priority"],
+            NotificationType::DriverReassigning => &["job_id", "status"],
+            NotificationType::JobOffer => &["job_id", "amount", "pickup_address", "dropoff_address", "priority"],
+            NotificationType::OnboardingUpdate => &["driver_id", "state", "note"],
+            NotificationType::DocumentExpiring => &["driver_id", "document", "expires_at", "days_left"],
+            NotificationType::DriverSuspended => &["driver_id", "reason"],
+            NotificationType::DriverReinstated => &["driver_id"],
+            NotificationType::DeliveryPreferencesUpdated => &["job_id", "tracking_code", "instructions", "safe_drop", "deliver_after"],
+            NotificationType::DriverNearby | NotificationType::PaymentConfirmed | NotificationType::JobUnassigned => &["job_id"],
+            NotificationType::GhanaPromotional => &[],
+        }
+    }
+}
+
+// Admin-managed copy for one type of notification in one language. `title` and `body`
+// may hold `{{variable}}` placeholders; see `NotificationType::variables`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NotificationTemplate {
+    pub notification_type: NotificationType,
+    pub language: String,    // e.g. "en", "fr"
+    pub title: String,
+    pub body: String,
+    pub active: bool,        // Inactive templates are kept but not sent
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationTemplateRequest {
+    pub title: String,
+    pub body: String,
+    #[serde(default = "default_template_active")]
+    pub active: bool,
+}
+
+fn default_template_active() -> bool {
+    true
 }
 
-// English + Local language support
-struct NotificationMessage {
-    english: String,
-    twi: Option<String>,     // For Akan speakers
-    ga: Option<String>,      // For Ga speakers
-    data: serde_json::Value, // App-specific data
-}
\ No newline at end of file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplatePreviewRequest {
+    pub notification_type: NotificationType,
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub variables: serde_json::Map<String, serde_json::Value>, // Anything left out is shown as its name
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplatePreview {
+    pub title: String,
+    pub body: String,
+}
+
+// A low-priority notification held for the recipient's next digest
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DigestItem {
+    pub title: String,
+    pub body: String,
+    pub kind: Option<String>,
+    pub queued_at: DateTime<Utc>,
+}
+
+// A notification held back by the recipient's quiet hours
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeferredNotification {
+    pub message: messaging_service::NotificationMessage,
+    pub deferred_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationBroadcastRequest {
+    pub user_ids: Vec<UserId>,
+    pub title: String,
+    pub body: String,
+    #[serde(default = "default_broadcast_kind")]
+    pub kind: String,        // `type` tag on the message, and the rate cap it counts against
+}
+
+fn default_broadcast_kind() -> String {
+    "promotion".to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationBroadcastResponse {
+    pub recipients: usize,   // Users the broadcast reached
+    pub capped: usize,       // Users skipped for having hit the cap on this kind
+    pub deferred: usize,     // Users in quiet hours, who get it once they end
+    pub devices: usize,
+    pub requests: usize,     // FCM multicast requests made
+}
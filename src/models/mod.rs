@@ -3,6 +3,48 @@ pub mod driver;
 pub mod user;
 pub mod job;
 pub mod messages;
+pub mod admin;
+pub mod commission;
+pub mod api_key;
+pub mod demand;
+pub mod dispatch;
+pub mod tenant;
+pub mod tax;
+pub mod ids;
+pub mod money;
+pub mod exchange_rate;
+pub mod broadcast;
+pub mod device;
+pub mod presence;
+pub mod moderation;
+pub mod realtime;
+pub mod zone;
+pub mod calendar;
+pub mod ledger;
+pub mod reconciliation;
+pub mod dispute;
+pub mod invoice;
+pub mod scope;
+pub mod otp;
+pub mod retention;
+pub mod events_export;
+pub mod alert;
+pub mod canary;
+pub mod chaos;
+pub mod quota;
+pub mod runtime_config;
+pub mod startup;
+pub mod simulation;
+pub mod status_feed;
+pub mod business;
+pub mod voice;
+pub mod incident;
+pub mod odometer;
+pub mod risk;
+pub mod region;
+pub mod domain_event;
+pub mod consumer;
+pub mod saga;
 
 pub use user::*;
 pub use driver::*;
@@ -0,0 +1,30 @@
+// src/models/moderation.rs
+// Blocks between customers and drivers, and platform bans
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::models::ids::{DriverId, UserId};
+
+// Set by an admin; the account can't log in, book or drive until it is lifted
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AccountBan {
+    pub reason: String,
+    pub banned_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BanRequest {
+    pub reason: String,
+}
+
+// POST /users/:id/blocked-drivers
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlockDriverRequest {
+    pub driver_id: DriverId,
+}
+
+// POST /drivers/:id/blocked-customers
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlockCustomerRequest {
+    pub user_id: UserId,
+}
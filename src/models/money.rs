@@ -0,0 +1,310 @@
+// src/models/money.rs
+// Amounts are held as whole minor units (pesewas for cedis) so fares, commission and
+// tax add up exactly. Rates are still applied in floating point, then rounded once.
+//
+// Clients have always sent and received plain decimal numbers next to a separate
+// `currency` field; the `*Record` shims next to each money-carrying model keep that
+// wire format and fold the currency back into every amount on the way in.
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use crate::errors::SparrowError as AppError;
+
+#[derive(Debug, Serialize, Clone, Copy)]
+pub struct CurrencyInfo {
+    pub code: &'static str,
+    pub name: &'static str,
+    pub minor_units: u32, // Decimal places, 0 for the CFA franc
+}
+
+// Currencies jobs can be priced and paid in
+pub const CURRENCIES: &[CurrencyInfo] = &[
+    CurrencyInfo { code: "GHS", name: "Ghanaian cedi", minor_units: 2 },
+    CurrencyInfo { code: "NGN", name: "Nigerian naira", minor_units: 2 },
+    CurrencyInfo { code: "XOF", name: "West African CFA franc", minor_units: 0 },
+    CurrencyInfo { code: "KES", name: "Kenyan shilling", minor_units: 2 },
+    CurrencyInfo { code: "USD", name: "US dollar", minor_units: 2 },
+];
+
+// ISO 4217 code of a registered currency, e.g. "GHS"
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+    pub const GHS: Currency = Currency(*b"GHS");
+
+    pub fn parse(code: &str) -> Result<Self, AppError> {
+        let code = code.trim().to_ascii_uppercase();
+        match <[u8; 3]>::try_from(code.as_bytes()) {
+            Ok(bytes) if CURRENCIES.iter().any(|info| info.code == code) => Ok(Currency(bytes)),
+            Ok(_) => Err(AppError::validation_error("currency", format!("{} is not a supported currency", code))),
+            Err(_) => Err(AppError::validation_error("currency", "Expected a three-letter ISO 4217 code")),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only ever built from ASCII letters
+        std::str::from_utf8(&self.0).unwrap_or("???")
+    }
+
+    pub fn info(&self) -> &'static CurrencyInfo {
+        CURRENCIES.iter()
+            .find(|info| info.code == self.as_str())
+            .expect("currencies are only parsed from the registry")
+    }
+
+    fn minor_per_major(&self) -> f64 {
+        10f64.powi(self.info().minor_units as i32)
+    }
+}
+
+impl Default for Currency {
+    fn default() -> Self {
+        Currency::GHS
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Currency({})", self.as_str())
+    }
+}
+
+impl FromStr for Currency {
+    type Err = AppError;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        Currency::parse(code)
+    }
+}
+
+impl Serialize for Currency {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Currency {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        Currency::parse(&code).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct Money {
+    minor: i64,
+    currency: Currency,
+}
+
+// Bare numbers were written before amounts carried their currency, when everything was in cedis
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MoneyRepr {
+    Amount { minor: i64, currency: Currency },
+    Legacy(f64),
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match MoneyRepr::deserialize(deserializer)? {
+            MoneyRepr::Amount { minor, currency } => Money::from_minor(minor, currency),
+            MoneyRepr::Legacy(amount) => Money::from_major(amount, Currency::GHS),
+        })
+    }
+}
+
+impl Money {
+    pub fn from_minor(minor: i64, currency: Currency) -> Self {
+        Self { minor, currency }
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Self::from_minor(0, currency)
+    }
+
+    /// Nearest minor unit to a decimal amount, halves away from zero
+    pub fn from_major(amount: f64, currency: Currency) -> Self {
+        Self::from_minor((amount * currency.minor_per_major()).round() as i64, currency)
+    }
+
+    pub fn minor(&self) -> i64 {
+        self.minor
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    /// Decimal amount, for display and the legacy wire format
+    pub fn to_major(&self) -> f64 {
+        self.minor as f64 / self.currency.minor_per_major()
+    }
+
+    /// This amount scaled by `factor` (a rate or multiplier), rounded to the minor unit
+    pub fn times(&self, factor: f64) -> Self {
+        Self::from_minor((self.minor as f64 * factor).round() as i64, self.currency)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.minor == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.minor < 0
+    }
+
+    /// `self + other`, or a validation error naming both currencies when they differ
+    pub fn checked_add(self, other: Money) -> Result<Money, AppError> {
+        self.ensure_same_currency(&other)?;
+        Ok(self + other)
+    }
+
+    pub fn checked_sub(self, other: Money) -> Result<Money, AppError> {
+        self.ensure_same_currency(&other)?;
+        Ok(self - other)
+    }
+
+    pub fn ensure_same_currency(&self, other: &Money) -> Result<(), AppError> {
+        if self.currency != other.currency {
+            return Err(AppError::validation_error(
+                "currency",
+                format!("Cannot combine {} with {}; convert one of them first", self.currency, other.currency),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Total of `amounts`, zero when there are none
+    pub fn sum<'a>(currency: Currency, amounts: impl IntoIterator<Item = &'a Money>) -> Self {
+        amounts.into_iter().fold(Self::zero(currency), |total, amount| total + *amount)
+    }
+
+    fn same_currency(&self, other: &Money) -> Currency {
+        assert_eq!(
+            self.currency, other.currency,
+            "cannot combine {} with {}", self.currency, other.currency
+        );
+        self.currency
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let decimals = self.currency.info().minor_units as usize;
+        write!(f, "{:.*} {}", decimals, self.to_major(), self.currency)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        let currency = self.same_currency(&other);
+        Money::from_minor(self.minor + other.minor, currency)
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, other: Money) -> Money {
+        let currency = self.same_currency(&other);
+        Money::from_minor(self.minor - other.minor, currency)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, other: Money) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money::from_minor(-self.minor, self.currency)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::job::Pricing;
+
+    #[test]
+    fn test_amounts_add_up_exactly() {
+        // 0.1 + 0.2 != 0.3 in f64
+        let a = Money::from_major(0.1, Currency::GHS);
+        let b = Money::from_major(0.2, Currency::GHS);
+        assert_eq!(a + b, Money::from_major(0.3, Currency::GHS));
+        assert_eq!((a + b).minor(), 30);
+
+        let fare = Money::from_major(37.45, Currency::GHS);
+        assert_eq!(fare.times(0.15).minor(), 562); // 5.6175 rounds to 5.62
+        assert_eq!(fare.to_string(), "37.45 GHS");
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot combine")]
+    fn test_mixed_currencies_do_not_add() {
+        let _ = Money::from_major(1.0, Currency::GHS) + Money::from_major(1.0, Currency::parse("ngn").unwrap());
+    }
+
+    #[test]
+    fn test_pricing_keeps_the_decimal_wire_format() {
+        let legacy = serde_json::json!({
+            "base_fare": 15.0, "distance_fare": 12.346, "time_fare": 1.2, "package_surcharge": 5.0,
+            "priority_surcharge": 0.0, "service_fee": 3.35, "tax": 1.01, "total": 37.9,
+            "currency": "GHS", "estimated_cost": true
+        });
+        let pricing: Pricing = serde_json::from_value(legacy).unwrap();
+        assert_eq!(pricing.distance_fare.minor(), 1235);
+        assert!(pricing.tip.is_zero() && pricing.tax_lines.is_empty());
+
+        let json = serde_json::to_value(&pricing).unwrap();
+        assert_eq!(json["total"], serde_json::json!(37.9));
+        assert_eq!(json["currency"], "GHS");
+    }
+
+    #[test]
+    fn test_mixed_currencies_are_a_validation_error() {
+        let cedis = Money::from_major(10.0, Currency::GHS);
+        let francs = Money::from_major(500.0, Currency::parse("XOF").unwrap());
+        assert!(cedis.checked_add(cedis).is_ok());
+        assert!(cedis.checked_sub(francs).is_err());
+        assert_eq!(francs.minor(), 500);
+        assert_eq!(francs.to_string(), "500 XOF");
+    }
+
+    #[test]
+    fn test_legacy_numbers_read_as_cedis() {
+        let legacy: Money = serde_json::from_str("12.5").unwrap();
+        assert_eq!(legacy, Money::from_minor(1250, Currency::GHS));
+        let current: Money = serde_json::from_str(&serde_json::to_string(&legacy).unwrap()).unwrap();
+        assert_eq!(current, legacy);
+    }
+
+    #[test]
+    fn test_currency_codes() {
+        assert_eq!(Currency::parse(" ghs ").unwrap(), Currency::GHS);
+        assert!(Currency::parse("GH").is_err());
+        assert!(Currency::parse("ZZZ").is_err());
+        assert_eq!(serde_json::to_string(&Currency::GHS).unwrap(), "\"GHS\"");
+    }
+}
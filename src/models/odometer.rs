@@ -0,0 +1,61 @@
+// src/models/odometer.rs
+// Distance actually driven, added up from each driver's location stream: per driver per day
+// for their stats, and per vehicle for its maintenance schedule
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::models::ids::DriverId;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DailyDistance {
+    pub date: NaiveDate,
+    pub km: f64,
+}
+
+// A vehicle's running total and where it stands against its next service
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct VehicleOdometer {
+    pub vehicle_id: String,
+    pub total_km: f64,
+    pub last_service_km: f64,
+    pub last_serviced_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub reminded_for_km: Option<f64>, // The service the driver was last reminded of
+}
+
+impl VehicleOdometer {
+    pub fn new(vehicle_id: &str) -> Self {
+        Self {
+            vehicle_id: vehicle_id.to_string(),
+            total_km: 0.0,
+            last_service_km: 0.0,
+            last_serviced_at: None,
+            reminded_for_km: None,
+        }
+    }
+
+    pub fn next_service_km(&self, interval_km: f64) -> f64 {
+        self.last_service_km + interval_km
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VehicleMaintenance {
+    pub vehicle_id: String,
+    pub license_plate: String,
+    pub total_km: f64,
+    pub last_service_km: f64,
+    pub last_serviced_at: Option<DateTime<Utc>>,
+    pub next_service_km: f64,
+    pub service_due: bool,
+}
+
+// GET /drivers/:id/distance?days=
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DriverDistanceStats {
+    pub driver_id: DriverId,
+    pub today_km: f64,
+    pub period_km: f64,
+    pub days: Vec<DailyDistance>, // Oldest first, today last; days without driving show 0
+    pub vehicle: VehicleMaintenance,
+}
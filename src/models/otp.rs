@@ -0,0 +1,10 @@
+// src/models/otp.rs
+// One-time codes as they are kept: never the code itself, only a salted hash of it
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StoredOtp {
+    pub hash: String,              // See utils::secret_hash
+    pub expires_at: DateTime<Utc>, // Also the cache TTL, for stores that honour one
+}
@@ -0,0 +1,37 @@
+// src/models/presence.rs
+// Who holds a live connection right now, for the dispatcher console
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenceKind {
+    Driver,
+    Customer,
+}
+
+impl PresenceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PresenceKind::Driver => "driver",
+            PresenceKind::Customer => "customer",
+        }
+    }
+}
+
+// One open connection, kept in Redis so every instance sees the same picture
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PresenceEntry {
+    pub id: String,                  // Driver or user ID
+    pub kind: PresenceKind,
+    pub connection_id: String,       // Tells a reconnect on another instance from the one it replaced
+    pub connected_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>, // Last heartbeat from the holding instance
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PresenceSnapshot {
+    pub drivers: Vec<PresenceEntry>,
+    pub customers: Vec<PresenceEntry>,
+    pub generated_at: DateTime<Utc>,
+}
@@ -0,0 +1,100 @@
+// src/models/quota.rs
+// Daily and monthly usage allowances on merchant API keys, on top of the per-minute rate limit
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use std::fmt;
+
+// Share of a quota after which the merchant is warned, once per period
+pub const QUOTA_WARNING_RATIO: f64 = 0.8;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaMetric {
+    JobsCreated,
+    SmsSent, // Texts sent on the merchant's behalf, e.g. to their recipients
+}
+
+impl fmt::Display for QuotaMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotaMetric::JobsCreated => write!(f, "jobs_created"),
+            QuotaMetric::SmsSent => write!(f, "sms_sent"),
+        }
+    }
+}
+
+// Calendar periods in UTC
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaPeriod {
+    Daily,
+    Monthly,
+}
+
+impl QuotaPeriod {
+    pub fn starts_at(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let day = now.date_naive();
+        let first = match self {
+            QuotaPeriod::Daily => day,
+            QuotaPeriod::Monthly => day.with_day(1).unwrap_or(day),
+        };
+        Utc.from_utc_datetime(&first.and_hms_opt(0, 0, 0).unwrap_or_default())
+    }
+
+    pub fn resets_at(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let start = self.starts_at(now);
+        match self {
+            QuotaPeriod::Daily => start + Duration::days(1),
+            QuotaPeriod::Monthly => {
+                let (year, month) = if start.month() == 12 { (start.year() + 1, 1) } else { (start.year(), start.month() + 1) };
+                let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap_or(start.date_naive());
+                Utc.from_utc_datetime(&first.and_hms_opt(0, 0, 0).unwrap_or_default())
+            }
+        }
+    }
+
+    // Names the period `now` falls in, e.g. 20251017 or 202510
+    pub fn window(&self, now: DateTime<Utc>) -> String {
+        match self {
+            QuotaPeriod::Daily => now.format("%Y%m%d").to_string(),
+            QuotaPeriod::Monthly => now.format("%Y%m").to_string(),
+        }
+    }
+}
+
+impl fmt::Display for QuotaPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotaPeriod::Daily => write!(f, "daily"),
+            QuotaPeriod::Monthly => write!(f, "monthly"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QuotaLimit {
+    pub metric: QuotaMetric,
+    pub period: QuotaPeriod,
+    pub limit: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateQuotasRequest {
+    pub quotas: Vec<QuotaLimit>, // Replaces the key's quotas; empty lifts them all
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QuotaUsage {
+    pub metric: QuotaMetric,
+    pub period: QuotaPeriod,
+    pub limit: u64,
+    pub used: u64,
+    pub remaining: u64,
+    pub resets_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ApiKeyUsage {
+    pub key_id: String,
+    pub quotas: Vec<QuotaUsage>,
+}
@@ -0,0 +1,16 @@
+// src/models/realtime.rs
+use serde::{Deserialize, Serialize};
+
+/// A signed Ably token request. The client SDK trades it with Ably for a token carrying these
+/// capabilities, so our key's secret never leaves the server.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AblyTokenRequest {
+    pub key_name: String,
+    pub ttl: i64,           // Milliseconds
+    pub capability: String, // JSON object of channel name to allowed operations
+    pub client_id: String,
+    pub timestamp: i64,     // Milliseconds since the epoch
+    pub nonce: String,
+    pub mac: String,
+}
@@ -0,0 +1,72 @@
+// src/models/reconciliation.rs
+// Checking what the payment providers say they settled against what the ledger recorded
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::models::{ledger::Posting, money::Money};
+
+// One line of a provider's settlement report
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SettlementRecord {
+    pub provider: String,  // e.g. "mtn-momo"
+    pub reference: String, // Our merchant reference, as sent with the charge
+    pub amount: Money,
+    pub settled_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscrepancyKind {
+    MissingFromLedger,   // The provider settled money we have no entry for
+    MissingFromProvider, // We recorded a payment no provider reported
+    AmountMismatch,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscrepancyStatus {
+    Open,
+    Resolved,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DiscrepancyResolution {
+    pub note: String,
+    pub adjustment_entry_id: Option<String>, // The correcting ledger entry, when one was posted
+    pub resolved_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Discrepancy {
+    pub id: String,
+    pub day: NaiveDate, // The settlement day being reconciled
+    pub kind: DiscrepancyKind,
+    pub reference: String,
+    pub provider: Option<String>,
+    pub ledger_entry_id: Option<String>,
+    pub ledger_amount: Option<Money>,
+    pub provider_amount: Option<Money>,
+    pub status: DiscrepancyStatus,
+    pub resolution: Option<DiscrepancyResolution>,
+    pub flagged_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResolveDiscrepancyRequest {
+    pub note: String,
+    #[serde(default)]
+    pub adjustment: Vec<Posting>, // Posted to the ledger as one entry when given
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReconcileRequest {
+    pub day: NaiveDate,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ReconciliationRun {
+    pub day: NaiveDate,
+    pub settlements: usize,
+    pub matched: usize,
+    pub flagged: usize, // New discrepancies; ones already in the queue aren't counted again
+}
@@ -0,0 +1,92 @@
+// src/models/region.rs
+// A market this deployment operates in: a country (or a few run as one) with its own data,
+// currency, rates and phone numbering. Not to be confused with a location's `region`, which
+// is the administrative region inside a country, such as "Greater Accra".
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{money::Currency, tax::TaxSchedule, tenant::{PricingConfig, Surcharges}},
+    services::country::{self, CountryRules},
+};
+
+// The market the service launched in; its data keeps the legacy un-prefixed keys
+pub const DEFAULT_REGION_ID: &str = "gh";
+
+pub fn default_region_id() -> String {
+    DEFAULT_REGION_ID.to_string()
+}
+
+// Built from the country's rules; deployments choose which of them to serve
+#[derive(Clone)]
+pub struct Region {
+    pub id: String,              // Slug, e.g. "gh"
+    pub name: String,
+    pub countries: Vec<String>,  // ISO 3166 codes and names a pickup's `country` is matched against
+    pub dialing_code: String,    // e.g. "+233"
+    pub currency: Currency,
+    pub utc_offset_minutes: i32, // Local time, for deadlines, quiet hours and dispatch windows
+    pub pricing: PricingConfig,  // For tenants with no rates in the region's currency
+    pub taxes: TaxSchedule,      // For tenants with no tax schedule of their own here
+    rules: Arc<dyn CountryRules>,
+}
+
+impl Region {
+    pub fn new(rules: Arc<dyn CountryRules>) -> Region {
+        Region {
+            id: rules.region_id().to_string(),
+            name: rules.name().to_string(),
+            countries: rules.countries().iter().map(|country| country.to_string()).collect(),
+            dialing_code: rules.dialing_code().to_string(),
+            currency: rules.currency(),
+            utc_offset_minutes: rules.utc_offset_minutes(),
+            pricing: rules.base_fares(),
+            taxes: rules.tax_schedule(),
+            rules,
+        }
+    }
+
+    // Markets the service knows how to run in; which of them a deployment serves is configured
+    pub fn builtin(id: &str) -> Option<Region> {
+        country::builtin(id).map(Region::new)
+    }
+
+    pub fn covers_country(&self, country: &str) -> bool {
+        let country = country.trim();
+        self.countries.iter().any(|covered| covered.eq_ignore_ascii_case(country))
+    }
+
+    /// Surcharges under `rates`: their own, else the region's if they're in its currency.
+    /// Rates in another currency with none of their own add none.
+    pub fn surcharges_for(&self, rates: &PricingConfig) -> Surcharges {
+        match &rates.surcharges {
+            Some(surcharges) => surcharges.clone(),
+            None if rates.currency == self.pricing.currency => self.pricing.surcharges.clone().unwrap_or_default(),
+            None => Surcharges::default(),
+        }
+    }
+
+    /// The country's public holiday on the local date of `at`, if any
+    pub fn public_holiday_at(&self, at: DateTime<Utc>) -> Option<&'static str> {
+        let local = at + Duration::minutes(self.utc_offset_minutes as i64);
+        self.rules.public_holiday(local.date_naive())
+    }
+
+    /// The number in international form, e.g. "+233241234567", by the country's numbering plan
+    pub fn normalize_phone(&self, phone: &str) -> Result<String, AppError> {
+        self.rules.normalize_phone(phone)
+    }
+}
+
+// GET /admin/regions
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RegionSummary {
+    pub id: String,
+    pub name: String,
+    pub countries: Vec<String>,
+    pub dialing_code: String,
+    pub currency: Currency,
+    pub is_home: bool, // Requests that name no region are served here
+}
@@ -0,0 +1,108 @@
+// src/models/retention.rs
+// How long each kind of data is kept, and the legal holds that keep a record past that
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+use crate::models::ids::JobId;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum DataClass {
+    LocationTraces, // Raw GPS recorded along each job's route
+    JobEvents,      // Each job's lifecycle log, with where each step happened
+    Jobs,           // The job records themselves
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionAction {
+    Delete,
+    Anonymize, // Kept for the books, without anything identifying the people involved
+}
+
+impl DataClass {
+    pub const ALL: [DataClass; 3] = [DataClass::LocationTraces, DataClass::JobEvents, DataClass::Jobs];
+
+    // Jobs back ledger entries and invoices, so they are never deleted outright
+    pub fn action(&self) -> RetentionAction {
+        match self {
+            DataClass::LocationTraces | DataClass::JobEvents => RetentionAction::Delete,
+            DataClass::Jobs => RetentionAction::Anonymize,
+        }
+    }
+
+    pub fn default_retain_days(&self) -> u32 {
+        match self {
+            DataClass::LocationTraces => 30,
+            DataClass::JobEvents => 365,
+            DataClass::Jobs => 365 * 7,
+        }
+    }
+}
+
+impl fmt::Display for DataClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataClass::LocationTraces => write!(f, "location_traces"),
+            DataClass::JobEvents => write!(f, "job_events"),
+            DataClass::Jobs => write!(f, "jobs"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct RetentionPolicy {
+    pub class: DataClass,
+    pub retain_days: u32, // Counted from when the job was created
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct RetentionSettings {
+    pub policies: Vec<RetentionPolicy>, // Classes left out keep their default
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl RetentionSettings {
+    pub fn policy(&self, class: DataClass) -> RetentionPolicy {
+        self.policies.iter()
+            .find(|policy| policy.class == class)
+            .copied()
+            .unwrap_or(RetentionPolicy { class, retain_days: class.default_retain_days() })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateRetentionSettingsRequest {
+    pub policies: Vec<RetentionPolicy>,
+}
+
+// Keeps everything about one job, whatever the policies say, until it is released
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LegalHold {
+    pub job_id: JobId,
+    pub reason: String, // e.g. the case or court order reference
+    pub placed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlaceLegalHoldRequest {
+    pub job_id: JobId,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ClassRetentionReport {
+    pub class: DataClass,
+    pub action: RetentionAction,
+    pub cutoff: DateTime<Utc>,  // Data from jobs created before this is due
+    pub affected: usize,        // Jobs whose data was (or would be) removed
+    pub held: Vec<JobId>,       // Due, but under a legal hold
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RetentionReport {
+    pub dry_run: bool,
+    pub generated_at: DateTime<Utc>,
+    pub classes: Vec<ClassRetentionReport>,
+}
@@ -0,0 +1,41 @@
+// src/models/risk.rs
+// Signs that a driver is gaming the platform. For now that means faking their GPS: points
+// that fail the location checks are kept aside for review, and a driver who keeps sending
+// them is flagged for ops.
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::models::{ids::DriverId, job::LocationUpdate};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SpoofReason {
+    MockLocation, // The phone itself said the fix was mocked
+    Teleport,     // Further from the previous point than any vehicle could have gone
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QuarantinedLocation {
+    pub location: LocationUpdate,
+    pub reason: SpoofReason,
+    pub speed_kmh: Option<f64>, // Implied by the jump, for teleports
+    pub received_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RiskFlag {
+    pub driver_id: DriverId,
+    pub strikes: u32, // Batches with spoofed points in the current window
+    pub last_reason: SpoofReason,
+    pub flagged_at: DateTime<Utc>,
+    pub last_strike_at: DateTime<Utc>,
+}
+
+// GET /admin/risk/drivers/:id
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DriverRiskReport {
+    pub driver_id: DriverId,
+    pub strikes: u32,
+    pub flag: Option<RiskFlag>,
+    pub quarantined: Vec<QuarantinedLocation>, // Most recent last
+}
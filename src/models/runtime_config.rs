@@ -0,0 +1,33 @@
+// src/models/runtime_config.rs
+// Settings an instance picks up while running, from the runtime config file or when another
+// instance announces a change; see services::runtime_config
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+use crate::models::{dispatch::DispatchOverride, tenant::PricingConfig};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RuntimeConfig {
+    #[serde(default)]
+    pub pricing: PricingConfig, // Rates a tenant starts on when created without its own
+    #[serde(default)]
+    pub dispatch: DispatchOverride, // Over the built-in dispatch defaults, under each zone's settings
+    #[serde(default)]
+    pub features: BTreeMap<String, bool>, // Unlisted features are off
+}
+
+impl RuntimeConfig {
+    pub fn feature_enabled(&self, name: &str) -> bool {
+        self.features.get(name).copied().unwrap_or(false)
+    }
+}
+
+// What services read; swapped whole on every reload
+#[derive(Debug, Serialize, Clone)]
+pub struct RuntimeConfigSnapshot {
+    pub config: RuntimeConfig,
+    pub version: u64, // Goes up with every reload, so copies made from an older snapshot can tell
+    pub loaded_at: DateTime<Utc>,
+    pub source: Option<String>, // The file it was read from; none when running on defaults
+}
@@ -0,0 +1,72 @@
+// src/models/saga.rs
+// The saved progress of a multi-step workflow, so one interrupted by a crash or a failing
+// step can be picked up where it stopped, or undone.
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SagaKind {
+    JobCompletion,
+    JobRefund,
+}
+
+impl SagaKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SagaKind::JobCompletion => "job_completion",
+            SagaKind::JobRefund => "job_refund",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        [SagaKind::JobCompletion, SagaKind::JobRefund].into_iter().find(|kind| kind.as_str() == value)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SagaStatus {
+    Running,
+    Compensating, // A step gave up; the ones before it are being undone
+    Completed,
+    Compensated,
+    Failed,       // Undoing a step gave up too; someone has to look at it
+}
+
+impl SagaStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, SagaStatus::Completed | SagaStatus::Compensated | SagaStatus::Failed)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Pending,
+    Done,
+    Compensated,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SagaStepState {
+    pub name: String,
+    pub status: StepStatus,
+    pub attempts: u32, // Failures of whatever the step is doing now, running or undoing
+    pub last_error: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SagaState {
+    pub id: String,
+    pub kind: SagaKind,
+    pub key: String, // What the saga is about, e.g. a job ID; one saga of a kind per key
+    pub tenant_id: String,
+    pub region_id: String,
+    pub context: serde_json::Value, // What the steps need to run again after a restart
+    pub status: SagaStatus,
+    pub steps: Vec<SagaStepState>,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
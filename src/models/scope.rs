@@ -0,0 +1,104 @@
+// src/models/scope.rs
+// Permissions carried by API keys and login sessions, written "resource:action", e.g.
+// "jobs:write". An action of "*" covers every action on the resource, so "admin:*" is
+// full admin access. Routes declare what they need in src/routes.rs.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::{errors::SparrowError as AppError, models::user::UserType};
+
+pub const READ: &str = "read";
+pub const WRITE: &str = "write";
+pub const ANY: &str = "*";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Scope {
+    resource: String,
+    action: String,
+}
+
+impl Scope {
+    pub fn new(resource: &str, action: &str) -> Self {
+        Self {
+            resource: resource.to_string(),
+            action: action.to_string(),
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, AppError> {
+        let invalid = || AppError::validation_error("scope", format!("Expected resource:action, got {:?}", value));
+        let (resource, action) = value.split_once(':').ok_or_else(invalid)?;
+        let well_formed = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_lowercase() || c == '-');
+        if !well_formed(resource) || !(action == ANY || well_formed(action)) {
+            return Err(invalid());
+        }
+        Ok(Self::new(resource, action))
+    }
+
+    pub fn resource(&self) -> &str {
+        &self.resource
+    }
+
+    /// Whether holding this scope allows what `required` asks for
+    pub fn grants(&self, required: &Scope) -> bool {
+        self.resource == required.resource && (self.action == ANY || self.action == required.action)
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.resource, self.action)
+    }
+}
+
+impl TryFrom<String> for Scope {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Scope::parse(&value)
+    }
+}
+
+impl From<Scope> for String {
+    fn from(scope: Scope) -> Self {
+        scope.to_string()
+    }
+}
+
+pub fn grants_any(held: &[Scope], required: &Scope) -> bool {
+    held.iter().any(|scope| scope.grants(required))
+}
+
+impl UserType {
+    // What a session gets when the login asks for nothing narrower
+    pub fn default_scopes(&self) -> Vec<Scope> {
+        let scopes: &[(&str, &str)] = match self {
+            UserType::Customer => &[("users", ANY), ("jobs", ANY), ("realtime", "connect")],
+            UserType::Driver => &[("users", ANY), ("jobs", ANY), ("drivers", ANY), ("realtime", "connect")],
+            UserType::Admin => &[("admin", ANY), ("realtime", "connect")],
+            UserType::Dispatcher => &[("dispatch", ANY), ("realtime", "connect")],
+            UserType::Business => &[("users", ANY), ("jobs", ANY), ("invoices", READ), ("realtime", "connect")],
+        };
+        scopes.iter().map(|(resource, action)| Scope::new(resource, action)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcards_cover_only_their_resource() {
+        let admin = Scope::parse("admin:*").unwrap();
+        assert!(admin.grants(&Scope::new("admin", READ)) && admin.grants(&Scope::new("admin", WRITE)));
+        assert!(!admin.grants(&Scope::new("dispatch", READ)));
+        let read = Scope::parse("jobs:read").unwrap();
+        assert!(read.grants(&Scope::new("jobs", READ)) && !read.grants(&Scope::new("jobs", WRITE)));
+        for malformed in ["jobs", "*:read", "Jobs:read", "jobs:", ":read"] {
+            assert!(Scope::parse(malformed).is_err(), "{}", malformed);
+        }
+        let scopes: Vec<Scope> = serde_json::from_str(r#"["drivers:read","admin:*"]"#).unwrap();
+        assert_eq!(serde_json::to_string(&scopes).unwrap(), r#"["drivers:read","admin:*"]"#);
+    }
+}
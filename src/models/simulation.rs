@@ -0,0 +1,123 @@
+// src/models/simulation.rs
+// A recorded day of jobs and drivers, the dispatch and pricing settings to replay it with,
+// and what each replay came to; see services::simulator
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::fmt;
+
+use crate::models::{
+    dispatch::DispatchOverride,
+    ids::{DriverId, JobId},
+    job::JobPriority,
+    money::{Currency, Money},
+    tenant::PricingConfig,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SimulatedJob {
+    pub job_id: JobId,
+    pub priority: JobPriority,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,   // Left unassigned if no driver takes it by then
+    pub pickup: (f64, f64),          // (latitude, longitude)
+    pub dropoff: (f64, f64),
+    pub distance_km: f64,
+    pub duration_min: i32,
+    #[serde(default)]
+    pub rejected_by: Vec<DriverId>,  // Turn the offer down again when replayed
+    #[serde(default)]
+    pub ignored_by: Vec<DriverId>,   // Were offered it and never answered; let the offer time out again
+    // What actually happened, for the historical figures
+    pub driver_id: Option<DriverId>,
+    pub assigned_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub fare: Money,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DriverPosition {
+    pub at: DateTime<Utc>,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SimulatedDriver {
+    pub driver_id: DriverId,
+    pub reliability_score: f32,
+    pub online_from: DateTime<Utc>,
+    pub online_until: DateTime<Utc>,
+    pub positions: Vec<DriverPosition>, // Oldest first
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SimulationDay {
+    pub day: NaiveDate,
+    pub tenant_id: String,
+    pub currency: Currency, // Of every fare in `jobs`
+    pub jobs: Vec<SimulatedJob>,
+    pub drivers: Vec<SimulatedDriver>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OfferStrategy {
+    #[default]
+    Sequential, // One driver at a time down the ranked list, as automatic dispatch does
+    Broadcast,  // Everyone in range at once; the best ranked driver who would accept gets it
+}
+
+// One set of settings to replay the day with
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SimulationScenario {
+    pub name: String,
+    #[serde(default)]
+    pub strategy: OfferStrategy,
+    #[serde(default, flatten)]
+    pub dispatch: DispatchOverride, // Over the platform dispatch defaults
+    #[serde(default)]
+    pub reliability_weight: Option<f64>,
+    #[serde(default)]
+    pub pricing: Option<PricingConfig>, // Each job keeps the fare it was charged without one
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SimulationReport {
+    pub scenario: String,
+    pub jobs: usize,
+    pub assigned: usize,
+    pub unassigned: usize,
+    pub mean_assignment_seconds: f64, // From creation to a driver accepting, over assigned jobs
+    pub p90_assignment_seconds: i64,
+    pub mean_pickup_km: f64,          // From the assigned driver to the pickup
+    pub drivers_used: usize,
+    pub driver_utilization: f64,      // Share of online time spent on jobs, averaged over drivers
+    pub revenue: Money,               // Fares of assigned jobs
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ComparisonReport {
+    pub day: NaiveDate,
+    pub tenant_id: String,
+    pub historical: SimulationReport, // What the day's own dispatch achieved
+    pub scenarios: Vec<SimulationReport>,
+}
+
+// A table with one row per scenario, for the terminal
+impl fmt::Display for ComparisonReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Replay of {} for tenant {} ({} jobs)", self.day, self.tenant_id, self.historical.jobs)?;
+        write!(
+            f, "\n  {:<20} {:>8} {:>10} {:>10} {:>9} {:>8} {:>12} {:>16}",
+            "scenario", "assigned", "mean wait", "p90 wait", "pickup", "drivers", "utilization", "revenue",
+        )?;
+        for report in std::iter::once(&self.historical).chain(&self.scenarios) {
+            write!(
+                f, "\n  {:<20} {:>8} {:>9.0}s {:>9}s {:>6.2} km {:>8} {:>11.1}% {:>16}",
+                report.scenario, report.assigned, report.mean_assignment_seconds, report.p90_assignment_seconds,
+                report.mean_pickup_km, report.drivers_used, report.driver_utilization * 100.0, report.revenue.to_string(),
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+// src/models/startup.rs
+// What boot found of the services the app depends on
+use serde::Serialize;
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyStatus {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DependencyCheck {
+    pub name: String,
+    pub required: bool, // Boot fails without it; otherwise the app runs degraded
+    pub status: DependencyStatus,
+    pub attempts: u32,
+    pub elapsed_ms: u64, // Across every attempt and the waits between them
+    pub error: Option<String>, // From the last attempt, when it never came up
+}
+
+impl DependencyCheck {
+    pub fn is_up(&self) -> bool {
+        self.status == DependencyStatus::Up
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct StartupReport {
+    pub checks: Vec<DependencyCheck>, // In the order they were made
+    pub degraded: bool,               // Some optional dependency was down at boot
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+}
+
+impl StartupReport {
+    /// Required dependencies that never came up
+    pub fn failed(&self) -> Vec<&DependencyCheck> {
+        self.checks.iter().filter(|check| check.required && !check.is_up()).collect()
+    }
+}
+
+// One line per dependency, for the log or the terminal
+impl fmt::Display for StartupReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elapsed = (self.finished_at - self.started_at).num_milliseconds();
+        write!(f, "Startup checks took {} ms", elapsed)?;
+        for check in &self.checks {
+            let kind = if check.required { "required" } else { "optional" };
+            let plural = if check.attempts == 1 { "" } else { "s" };
+            write!(f, "\n  {} ({}): ", check.name, kind)?;
+            match check.status {
+                DependencyStatus::Up => write!(f, "up after {} attempt{} in {} ms", check.attempts, plural, check.elapsed_ms)?,
+                DependencyStatus::Down => write!(
+                    f, "DOWN after {} attempt{} in {} ms: {}",
+                    check.attempts, plural, check.elapsed_ms, check.error.as_deref().unwrap_or("no error given"),
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+// src/models/status_feed.rs
+// How often each consumer wants live updates for its jobs: driver location frames over
+// realtime, and status changes POSTed in bulk to its webhook; see services::status_feed
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::models::{
+    ids::{DriverId, JobId, UserId},
+    job::{JobStatus, LocationUpdate},
+};
+
+// One per customer account; a merchant's settings apply to every job booked with its keys
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FeedSettings {
+    pub consumer_id: UserId,
+    pub location_interval_seconds: u64, // At most one location frame per job this often
+    pub webhook_url: Option<String>,    // Status changes are only POSTed when set
+    pub webhook_secret: String,         // Signs each delivery; see X-Sparrow-Signature
+    pub webhook_interval_seconds: u64,  // At most one delivery this often
+    #[serde(default)]
+    pub webhook_locations: bool,        // Also deliver the latest driver position while in transit
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct UpdateFeedSettingsRequest {
+    pub location_interval_seconds: Option<u64>,
+    pub webhook_url: Option<String>, // Empty string removes the webhook
+    pub webhook_interval_seconds: Option<u64>,
+    pub webhook_locations: Option<bool>,
+}
+
+// The latest state of one job, as delivered to a webhook
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StatusFeedEvent {
+    pub job_id: JobId,
+    pub tracking_code: String,
+    pub status: JobStatus,
+    pub driver_id: Option<DriverId>,
+    pub location: Option<LocationUpdate>, // Only for consumers taking locations
+    pub updated_at: DateTime<Utc>,
+}
+
+// Body of one webhook POST: every job that changed since the last delivery, once each
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatusFeedDelivery {
+    pub consumer_id: UserId,
+    pub sent_at: DateTime<Utc>,
+    pub events: Vec<StatusFeedEvent>,
+    pub suppressed: usize, // Updates left out: replaced by a later one for the same job, or repeating the last delivered
+}
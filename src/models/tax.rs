@@ -0,0 +1,143 @@
+// src/models/tax.rs
+// Some countries charge VAT on top of other levies, as Ghana does, so tax is worked out
+// levy by levy and itemized on every price. Each country's standard levies live with its
+// rules in `services::country`.
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::models::money::{Currency, Money};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TaxLevy {
+    pub code: String,        // e.g. "VAT", "NHIL"
+    pub name: String,
+    pub rate: f64,           // Fraction, 0.025 = 2.5%
+    #[serde(default)]
+    pub on_levies: bool,     // Charged on the subtotal plus the other levies, as VAT is
+}
+
+// The levies in force from `effective_from` until the next schedule starts
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TaxSchedule {
+    pub effective_from: DateTime<Utc>,
+    pub levies: Vec<TaxLevy>,
+}
+
+// One itemized levy on a price
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaxLine {
+    pub code: String,
+    pub name: String,
+    pub rate: f64,
+    pub taxable_amount: Money,
+    pub amount: Money,
+}
+
+// Serialized inside `Pricing`, which carries the currency
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaxLineRecord {
+    pub code: String,
+    pub name: String,
+    pub rate: f64,
+    pub taxable_amount: f64,
+    pub amount: f64,
+}
+
+impl TaxLineRecord {
+    pub fn into_line(self, currency: Currency) -> TaxLine {
+        TaxLine {
+            code: self.code,
+            name: self.name,
+            rate: self.rate,
+            taxable_amount: Money::from_major(self.taxable_amount, currency),
+            amount: Money::from_major(self.amount, currency),
+        }
+    }
+}
+
+impl From<TaxLine> for TaxLineRecord {
+    fn from(line: TaxLine) -> Self {
+        Self {
+            code: line.code,
+            name: line.name,
+            rate: line.rate,
+            taxable_amount: line.taxable_amount.to_major(),
+            amount: line.amount.to_major(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateTaxScheduleRequest {
+    pub effective_from: Option<DateTime<Utc>>, // Defaults to now; never in the past
+    pub levies: Vec<TaxLevy>,
+}
+
+impl TaxLevy {
+    pub fn new(code: &str, name: &str, rate: f64, on_levies: bool) -> Self {
+        Self {
+            code: code.to_string(),
+            name: name.to_string(),
+            rate,
+            on_levies,
+        }
+    }
+}
+
+impl TaxSchedule {
+    /// Levies on `subtotal`: the flat ones first, then those charged on top of them
+    pub fn apply(&self, subtotal: Money) -> Vec<TaxLine> {
+        let line = |levy: &TaxLevy, taxable_amount: Money| TaxLine {
+            code: levy.code.clone(),
+            name: levy.name.clone(),
+            rate: levy.rate,
+            taxable_amount,
+            amount: taxable_amount.times(levy.rate),
+        };
+
+        let mut lines: Vec<TaxLine> = self.levies.iter()
+            .filter(|levy| !levy.on_levies)
+            .map(|levy| line(levy, subtotal))
+            .collect();
+        let levy_inclusive = subtotal + Money::sum(subtotal.currency(), lines.iter().map(|line| &line.amount));
+        lines.extend(self.levies.iter().filter(|levy| levy.on_levies).map(|levy| line(levy, levy_inclusive)));
+        lines
+    }
+}
+
+/// The schedule in force at `at`: the latest one to have started by then
+pub fn schedule_at(schedules: &[TaxSchedule], at: DateTime<Utc>) -> Option<&TaxSchedule> {
+    schedules.iter()
+        .filter(|schedule| schedule.effective_from <= at)
+        .max_by_key(|schedule| schedule.effective_from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use crate::services::country::{ghana::Ghana, CountryRules};
+
+    #[test]
+    fn test_vat_is_charged_on_top_of_levies() {
+        let lines = Ghana.tax_schedule().apply(Money::from_major(100.0, Currency::GHS));
+        let amounts: Vec<_> = lines.iter().map(|line| (line.code.as_str(), line.amount.minor())).collect();
+        assert_eq!(amounts, vec![("NHIL", 250), ("GETFUND", 250), ("COVID19", 100), ("VAT", 1590)]);
+        assert_eq!(lines[3].taxable_amount.to_major(), 106.0);
+    }
+
+    #[test]
+    fn test_schedule_at_picks_latest_started() {
+        let now = Utc::now();
+        let current = Ghana.tax_schedule();
+        let upcoming = TaxSchedule {
+            effective_from: now + Duration::days(30),
+            levies: vec![TaxLevy::new("VAT", "Value Added Tax", 0.2, true)],
+        };
+        let schedules = vec![upcoming.clone(), current.clone()];
+
+        assert_eq!(schedule_at(&schedules, now), Some(&current));
+        assert_eq!(schedule_at(&schedules, now + Duration::days(31)), Some(&upcoming));
+        assert_eq!(schedule_at(&schedules, DateTime::UNIX_EPOCH - Duration::days(1)), None);
+    }
+}
@@ -0,0 +1,158 @@
+// src/models/tenant.rs
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::{errors::SparrowError as AppError, models::{job::{JobPriority, PackageType}, money::Currency}, services::realtime::FrameFormat};
+
+// Tenant used when a request matches no other brand; its data keeps the legacy un-prefixed keys
+pub const DEFAULT_TENANT_ID: &str = "default";
+
+pub fn default_tenant_id() -> String {
+    DEFAULT_TENANT_ID.to_string()
+}
+
+// One white-label delivery brand running on this deployment
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Tenant {
+    pub id: String,                   // Slug, e.g. "sparrow" or "kwik-gh"
+    pub name: String,
+    pub hosts: Vec<String>,           // Hostnames that resolve to this tenant
+    pub pricing: PricingConfig,       // Rates in the tenant's home currency, used when a booking names none
+    #[serde(default)]
+    pub currency_pricing: Vec<PricingConfig>, // Rates for any other currency jobs may be booked in
+    pub service_regions: Vec<String>, // Regions jobs may be booked in; empty means everywhere
+    #[serde(default)]
+    pub mqtt_enabled: bool,           // Drivers also take offers and send locations over MQTT
+    #[serde(default)]
+    pub mqtt_format: FrameFormat,     // Encoding of offers published to them
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PricingConfig {
+    pub currency: Currency,
+    pub base_fare_standard: f64,
+    pub base_fare_express: f64,
+    pub base_fare_same_day: f64,
+    pub base_fare_emergency: f64,
+    pub per_km: f64,
+    pub per_minute: f64,
+    pub service_fee_rate: f64,
+    #[serde(default)]
+    pub surcharges: Option<Surcharges>, // None takes the region's, when it prices in the same currency
+}
+
+// Ghana pricing the service launched with
+impl Default for PricingConfig {
+    fn default() -> Self {
+        Self {
+            currency: Currency::GHS,
+            base_fare_standard: 15.0,
+            base_fare_express: 25.0,
+            base_fare_same_day: 40.0,
+            base_fare_emergency: 60.0,
+            per_km: 2.5,
+            per_minute: 0.2,
+            service_fee_rate: 0.1,
+            surcharges: None,
+        }
+    }
+}
+
+// Flat amounts added to the fare for bulkier or more delicate packages and faster
+// service, in the currency of the pricing they belong to. Documents and standard
+// delivery carry none.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Surcharges {
+    pub small_package: f64,
+    pub medium_package: f64,
+    pub large_package: f64,
+    pub extra_large: f64,
+    pub food: f64,
+    pub grocery: f64,
+    pub pharmacy: f64,
+    pub electronics: f64,
+    pub fragile: f64,
+    pub express: f64,
+    pub same_day: f64,
+    pub emergency: f64,
+}
+
+impl Surcharges {
+    pub fn for_package(&self, package_type: &PackageType) -> f64 {
+        match package_type {
+            PackageType::Document => 0.0,
+            PackageType::SmallPackage => self.small_package,
+            PackageType::MediumPackage => self.medium_package,
+            PackageType::LargePackage => self.large_package,
+            PackageType::ExtraLarge => self.extra_large,
+            PackageType::Food => self.food,
+            PackageType::Grocery => self.grocery,
+            PackageType::Pharmacy => self.pharmacy,
+            PackageType::Electronics => self.electronics,
+            PackageType::Fragile => self.fragile,
+        }
+    }
+
+    pub fn for_priority(&self, priority: &JobPriority) -> f64 {
+        match priority {
+            JobPriority::Standard => 0.0,
+            JobPriority::Express => self.express,
+            JobPriority::SameDay => self.same_day,
+            JobPriority::Emergency => self.emergency,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateTenantRequest {
+    pub id: String,
+    pub name: String,
+    pub hosts: Vec<String>,
+    pub pricing: Option<PricingConfig>,
+    #[serde(default)]
+    pub currency_pricing: Vec<PricingConfig>,
+    #[serde(default)]
+    pub service_regions: Vec<String>,
+    #[serde(default)]
+    pub mqtt_enabled: bool,
+    #[serde(default)]
+    pub mqtt_format: FrameFormat,
+}
+
+impl Tenant {
+    pub fn default_tenant() -> Self {
+        let now = Utc::now();
+        Self {
+            id: default_tenant_id(),
+            name: "Sparrow".to_string(),
+            hosts: Vec::new(),
+            pricing: PricingConfig::default(),
+            currency_pricing: Vec::new(),
+            service_regions: Vec::new(),
+            mqtt_enabled: false,
+            mqtt_format: FrameFormat::Json,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Rates for jobs priced in `currency`, the home currency when None
+    pub fn pricing_for(&self, currency: Option<Currency>) -> Result<&PricingConfig, AppError> {
+        let Some(currency) = currency else {
+            return Ok(&self.pricing);
+        };
+        std::iter::once(&self.pricing)
+            .chain(&self.currency_pricing)
+            .find(|pricing| pricing.currency == currency)
+            .ok_or_else(|| AppError::validation_error("currency", format!("{} does not take bookings in {}", self.name, currency)))
+    }
+
+    pub fn serves_region(&self, region: &str) -> bool {
+        self.service_regions.is_empty()
+            || self.service_regions.iter().any(|served| served.eq_ignore_ascii_case(region))
+    }
+}
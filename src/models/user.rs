@@ -1,6 +1,17 @@
 // src/models/user.rs
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{device::{DeviceInfo, DevicePlatform, DeviceToken}, ids::{JobId, UserId}, moderation::AccountBan, money::{Currency, Money}, scope::Scope, tenant::default_tenant_id},
+};
+
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+pub fn default_language() -> String {
+    DEFAULT_LANGUAGE.to_string()
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum UserType {
@@ -8,6 +19,7 @@ pub enum UserType {
     Driver,      // Someone delivering packages
     Admin,       // Platform administrator
     Dispatcher,  // Manages deliveries and drivers
+    Business,    // Merchant integrating server-to-server with API keys
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -16,6 +28,7 @@ pub enum UserStatus {
     Inactive,
     Suspended,
     PendingVerification,
+    Banned,             // By an admin; see `User::ban`
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -55,9 +68,21 @@ pub enum PaymentMethodType {
     Cash,         // Cash on delivery
 }
 
+// How much of their name a customer lets their driver see
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NameVisibility {
+    Full,
+    #[default]
+    FirstName,
+    Hidden,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UserPreferences {
     pub language: String,           // e.g., "en", "fr", "ak", "tw"
+    #[serde(default)]
+    pub name_visibility: NameVisibility,
     pub currency: String,           // e.g., "GHS" for Ghana Cedis
     pub notifications: NotificationPreferences,
     pub theme: String,              // e.g., "light", "dark", "system"
@@ -72,11 +97,33 @@ pub struct NotificationPreferences {
     pub ride_updates: bool,
     pub promotional_offers: bool,
     pub security_alerts: bool,
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+}
+
+// Local times between which only critical notifications go out; may span midnight
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct QuietHours {
+    pub start: NaiveTime,          // e.g. "22:00:00"
+    pub end: NaiveTime,            // e.g. "07:00:00"
+    #[serde(default)]
+    pub utc_offset_minutes: Option<i32>, // The user's region's unless given; filled in when saved
+}
+
+impl QuietHours {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let local = (at + Duration::minutes(self.utc_offset_minutes.unwrap_or_default() as i64)).time();
+        if self.start <= self.end {
+            self.start <= local && local < self.end
+        } else {
+            self.start <= local || local < self.end
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UserProfile {
-    pub user_id: String,
+    pub user_id: UserId,
     pub date_of_birth: Option<DateTime<Utc>>,
     pub gender: Option<String>,      // e.g., "male", "female", "other"
     pub profile_picture: Option<String>, // URL to profile image
@@ -90,7 +137,9 @@ pub struct UserProfile {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct User {
-    pub id: String,
+    pub id: UserId,
+    #[serde(default = "default_tenant_id")]
+    pub tenant_id: String,           // Brand this record belongs to
     pub user_type: UserType,
     pub status: UserStatus,
     pub email: String,
@@ -99,15 +148,43 @@ pub struct User {
     pub first_name: String,
     pub last_name: String,
     pub display_name: Option<String>,
+    #[serde(default = "default_language")]
+    pub language: String,        // Preferred language for notifications, e.g. "en", "fr"
+    #[serde(default)]
+    pub name_visibility: NameVisibility,
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>, // Copied from the notification preferences
     pub is_email_verified: bool,
     pub is_phone_verified: bool,
-    pub device_tokens: Vec<String>, // For push notifications
+    pub device_tokens: Vec<DeviceToken>, // For push notifications
     pub last_login: Option<DateTime<Utc>>,
-    pub current_session: Option<String>,
+    pub current_session: Option<String>, // Salted hash of the session token, never the token itself
+    #[serde(default)]
+    pub session_scopes: Option<Vec<Scope>>, // What the current session may do; None for the account type's defaults
+    #[serde(default)]
+    pub session_device: Option<String>,     // The device the current session logged in from
+    #[serde(default)]
+    pub ban: Option<AccountBan>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl User {
+    pub fn scopes(&self) -> Vec<Scope> {
+        self.session_scopes.clone().unwrap_or_else(|| self.user_type.default_scopes())
+    }
+
+    // The name shown to drivers on their jobs; a display name stands in for the real one
+    pub fn name_for_drivers(&self) -> Option<String> {
+        match (self.name_visibility, &self.display_name) {
+            (NameVisibility::Hidden, _) => None,
+            (_, Some(display_name)) => Some(display_name.clone()),
+            (NameVisibility::Full, None) => Some(format!("{} {}", self.first_name, self.last_name)),
+            (NameVisibility::FirstName, None) => Some(self.first_name.clone()),
+        }
+    }
+}
+
 // Request/Response Models
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserRegistration {
@@ -126,6 +203,16 @@ pub struct UserLogin {
     pub phone_number: Option<String>,
     pub password: String,
     pub device_token: Option<String>, // For push notifications
+    #[serde(default)]
+    pub device_platform: DevicePlatform,
+    #[serde(default)]
+    pub scopes: Option<Vec<Scope>>, // Narrower than the account's defaults, for least-privilege tokens
+    #[serde(default)]
+    pub device: Option<DeviceInfo>,
+    #[serde(skip_deserializing)]
+    pub city: Option<String>,       // Where the login comes from, worked out server-side; never taken from the body
+    #[serde(default)]
+    pub verification_code: Option<String>, // Sent to the user's other devices when this one needs confirming
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -163,7 +250,7 @@ pub struct PaymentMethodCreate {
 // Response Models (for API responses)
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserResponse {
-    pub id: String,
+    pub id: UserId,
     pub user_type: UserType,
     pub status: UserStatus,
     pub email: String,
@@ -178,18 +265,36 @@ pub struct UserResponse {
     pub created_at: DateTime<Utc>,
 }
 
+impl From<User> for UserResponse {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id,
+            user_type: user.user_type,
+            status: user.status,
+            email: user.email,
+            phone_number: user.phone_number,
+            country_code: user.country_code,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            display_name: user.display_name,
+            is_email_verified: user.is_email_verified,
+            is_phone_verified: user.is_phone_verified,
+            profile_picture: None, // Would come from user profile
+            created_at: user.created_at,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LoginResponse {
     pub user: UserResponse,
-    pub access_token: String,
-    pub refresh_token: String,
-    pub expires_in: i64,
+    pub access_token: String, // Sent as `Authorization: Bearer <token>`; logging in again replaces it
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AuthToken {
     pub token: String,
-    pub user_id: String,
+    pub user_id: UserId,
     pub expires_at: DateTime<Utc>,
     pub device_id: Option<String>,
     pub is_revoked: bool,
@@ -198,7 +303,7 @@ pub struct AuthToken {
 // Statistics and analytics
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserStats {
-    pub user_id: String,
+    pub user_id: UserId,
     pub total_rides: u32,
     pub completed_rides: u32,
     pub cancelled_rides: u32,
@@ -211,7 +316,7 @@ pub struct UserStats {
 // Support and verification
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VerificationRequest {
-    pub user_id: String,
+    pub user_id: UserId,
     pub document_type: String,   // e.g., "national_id", "driver_license", "passport"
     pub document_front: String,  // URL or base64 encoded image
     pub document_back: Option<String>,
@@ -234,7 +339,7 @@ pub enum VerificationStatus {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SupportTicket {
     pub id: String,
-    pub user_id: String,
+    pub user_id: UserId,
     pub category: String,        // e.g., "payment", "delivery", "technical"
     pub subject: String,
     pub description: String,
@@ -262,10 +367,91 @@ pub enum TicketPriority {
     Urgent,
 }
 
+// Account credits (goodwill, refunds as credit) applied to future jobs
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(from = "UserCreditRecord", into = "UserCreditRecord")]
+pub struct UserCredit {
+    pub id: String,
+    pub user_id: UserId,
+    pub amount: Money,
+    pub reason: String,          // e.g., "sla_breach"
+    pub job_id: Option<JobId>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Wire and cache format of `UserCredit`, with the amount as a decimal
+#[derive(Serialize, Deserialize)]
+struct UserCreditRecord {
+    id: String,
+    user_id: UserId,
+    amount: f64,
+    currency: Currency,
+    reason: String,
+    job_id: Option<JobId>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<UserCreditRecord> for UserCredit {
+    fn from(record: UserCreditRecord) -> Self {
+        Self {
+            id: record.id,
+            user_id: record.user_id,
+            amount: Money::from_major(record.amount, record.currency),
+            reason: record.reason,
+            job_id: record.job_id,
+            created_at: record.created_at,
+        }
+    }
+}
+
+impl From<UserCredit> for UserCreditRecord {
+    fn from(credit: UserCredit) -> Self {
+        Self {
+            id: credit.id,
+            user_id: credit.user_id,
+            amount: credit.amount.to_major(),
+            currency: credit.amount.currency(),
+            reason: credit.reason,
+            job_id: credit.job_id,
+            created_at: credit.created_at,
+        }
+    }
+}
+
+/// Total of `credits`, None when there are none. A wallet holds a single currency, so
+/// credits in different currencies are a validation error rather than a sum.
+pub fn credit_total(credits: &[UserCredit]) -> Result<Option<Money>, AppError> {
+    let mut amounts = credits.iter().map(|credit| credit.amount);
+    let Some(first) = amounts.next() else {
+        return Ok(None);
+    };
+    amounts.try_fold(first, Money::checked_add).map(Some)
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreditBalance {
+    pub user_id: UserId,
+    pub balance: f64,
+    pub currency: Option<Currency>, // None until the first credit
+    pub credits: Vec<UserCredit>,
+}
+
+impl CreditBalance {
+    pub fn new(user_id: UserId, credits: Vec<UserCredit>) -> Result<Self, AppError> {
+        let total = credit_total(&credits)?;
+        Ok(Self {
+            user_id,
+            balance: total.map_or(0.0, |total| total.to_major()),
+            currency: total.map(|total| total.currency()),
+            credits,
+        })
+    }
+}
+
 // Loyalty and rewards
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LoyaltyProgram {
-    pub user_id: String,
+    pub user_id: UserId,
     pub points: u32,
     pub tier: LoyaltyTier,
     pub rides_this_month: u32,
@@ -0,0 +1,54 @@
+// src/models/voice.rs
+// Automated calls placed through the voice gateway, and the status callbacks it sends back
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::models::{ids::JobId, job::{CallLanguage, StopKind}};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CallStatus {
+    Queued,
+    Ringing,
+    Answered,
+    Completed,
+    NoAnswer,
+    Busy,
+    Failed,
+}
+
+impl CallStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CallStatus::Queued => "queued",
+            CallStatus::Ringing => "ringing",
+            CallStatus::Answered => "answered",
+            CallStatus::Completed => "completed",
+            CallStatus::NoAnswer => "no answer",
+            CallStatus::Busy => "busy",
+            CallStatus::Failed => "failed",
+        }
+    }
+}
+
+// Which job and stop a gateway call ID belongs to, kept until its callbacks stop coming
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ArrivalCall {
+    pub call_id: String,
+    pub job_id: JobId,
+    pub stop: StopKind,
+    pub language: CallLanguage,
+    pub to: String, // Masked
+    pub placed_at: DateTime<Utc>,
+}
+
+// POST /webhooks/voice/calls
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CallStatusCallback {
+    pub call_id: String,
+    pub status: CallStatus,
+    #[serde(default)]
+    pub duration_seconds: Option<u32>,
+    #[serde(default)]
+    pub digits: Option<String>, // Keys the contact pressed; "1" confirms they're coming
+}
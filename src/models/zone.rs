@@ -0,0 +1,63 @@
+// src/models/zone.rs
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::models::tenant::default_tenant_id;
+
+// Where the service operates, drawn by operations. Every edit of the boundary is kept as a
+// new version; only the active one is used, so a draft can be checked before it goes live.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceZone {
+    pub id: String,
+    #[serde(default = "default_tenant_id")]
+    pub tenant_id: String,
+    pub name: String,
+    pub versions: Vec<ZoneVersion>, // Oldest first
+    pub active_version: Option<u32>, // None until one is activated
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ServiceZone {
+    pub fn version(&self, version: u32) -> Option<&ZoneVersion> {
+        self.versions.iter().find(|candidate| candidate.version == version)
+    }
+
+    pub fn active(&self) -> Option<&ZoneVersion> {
+        self.active_version.and_then(|version| self.version(version))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ZoneVersion {
+    pub version: u32,
+    pub boundary: Vec<[f64; 2]>, // [longitude, latitude] as in GeoJSON; closed, first == last
+    pub created_at: DateTime<Utc>,
+}
+
+// `geometry` is a GeoJSON Polygon, or a Feature wrapping one
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateZoneRequest {
+    pub name: String,
+    pub geometry: serde_json::Value,
+}
+
+// A new geometry adds a version; it stays inactive until activated
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct UpdateZoneRequest {
+    pub name: Option<String>,
+    pub geometry: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActivateZoneRequest {
+    pub version: u32,
+}
+
+// The active zone a point falls in
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ZoneMatch {
+    pub zone_id: String,
+    pub name: String,
+    pub version: u32,
+}
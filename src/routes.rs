@@ -0,0 +1,279 @@
+// src/routes.rs
+// The full HTTP surface, shared by the server binary and the in-process test harness
+use axum::{
+    middleware,
+    routing::{delete, get, patch, post, put},
+    Router,
+};
+use std::sync::Arc;
+use tower_http::catch_panic::CatchPanicLayer;
+
+use crate::{
+    handlers::{
+        auth::{require_scope, RequiredScope},
+        business_handler, dispatch_handler, driver_handler, fallback, job_handler, merchant_handler, user_handler, webhook_handler,
+        request_id::assign_request_id,
+        language::resolve_language,
+        region::resolve_region,
+        request_log::log_requests,
+        tenant::resolve_tenant,
+    },
+    state::AppState,
+};
+#[cfg(feature = "admin")]
+use crate::handlers::admin_handler;
+#[cfg(feature = "realtime-ably")]
+use crate::handlers::realtime_handler;
+
+pub fn router(app_state: Arc<AppState>) -> Router {
+    // Each group's routes need the scope it's layered with; see `RequiredScope` for how a
+    // bare resource becomes read or write by method
+    let scoped = |scope| middleware::from_fn_with_state(RequiredScope::new(&app_state, scope), require_scope);
+
+    // Subsystems a build leaves out simply have no routes
+    #[cfg(feature = "admin")]
+    let admin = Router::new()
+        .route("/admin/dashboard", get(admin_handler::get_dashboard))
+        .route("/admin/users", get(admin_handler::search_users))
+        .route("/admin/drivers", get(admin_handler::search_drivers))
+        .route("/admin/users/:id/ban", post(admin_handler::ban_user).delete(admin_handler::unban_user))
+        .route("/admin/drivers/:id/ban", post(admin_handler::ban_driver).delete(admin_handler::unban_driver))
+        .route("/admin/drivers/:id/onboarding/advance", post(admin_handler::advance_onboarding))
+        .route("/admin/drivers/:id/onboarding/reject", post(admin_handler::reject_onboarding))
+        .route("/admin/write-behind", get(admin_handler::get_write_behind_metrics))
+        .route("/admin/presence", get(admin_handler::get_presence))
+        .route("/admin/send-queues", get(admin_handler::get_send_queue_metrics))
+        .route("/admin/canary", get(admin_handler::get_canary_status))
+        .route("/admin/exports/jobs", get(admin_handler::export_jobs))
+        .route("/admin/exports/drivers", get(admin_handler::export_drivers))
+        .route("/admin/exports/earnings", get(admin_handler::export_earnings))
+        .route("/admin/api-keys", get(admin_handler::list_api_keys).post(admin_handler::issue_api_key))
+        .route("/admin/api-keys/:id", delete(admin_handler::revoke_api_key))
+        .route("/admin/api-keys/:id/rotate", post(admin_handler::rotate_api_key))
+        .route("/admin/api-keys/:id/usage", get(admin_handler::get_api_key_usage))
+        .route("/admin/api-keys/:id/quotas", put(admin_handler::update_api_key_quotas))
+        .route("/admin/commissions", get(admin_handler::get_commissions).put(admin_handler::update_commissions))
+        .route("/admin/dispatch-settings", get(admin_handler::get_dispatch_settings).put(admin_handler::update_dispatch_settings))
+        .route("/admin/retention/policies", get(admin_handler::get_retention_policies).put(admin_handler::update_retention_policies))
+        .route("/admin/retention/report", get(admin_handler::get_retention_report))
+        .route("/admin/jobs/:id/delivery-code/override", post(admin_handler::override_delivery_code))
+        .route("/admin/jobs/:id/refund", post(admin_handler::refund_job))
+        .route("/admin/incidents", get(admin_handler::list_incidents))
+        .route("/admin/incidents/:id", get(admin_handler::get_incident))
+        .route("/admin/incidents/:id/updates", post(admin_handler::add_incident_update))
+        .route("/admin/consumers", get(admin_handler::list_consumers))
+        .route("/admin/consumers/:group/replay", post(admin_handler::replay_consumer))
+        .route("/admin/regions", get(admin_handler::list_regions))
+        .route("/admin/sagas", get(admin_handler::list_open_sagas))
+        .route("/admin/risk/drivers", get(admin_handler::list_flagged_drivers))
+        .route("/admin/risk/drivers/:id", get(admin_handler::get_driver_risk).delete(admin_handler::clear_driver_risk))
+        .route("/admin/retention/holds", get(admin_handler::list_legal_holds).post(admin_handler::place_legal_hold))
+        .route("/admin/retention/holds/:job_id", delete(admin_handler::release_legal_hold))
+        .route("/admin/zones", get(admin_handler::list_zones).post(admin_handler::create_zone))
+        .route("/admin/zones/lookup", get(admin_handler::lookup_zone))
+        .route(
+            "/admin/zones/:id",
+            get(admin_handler::get_zone).put(admin_handler::update_zone).delete(admin_handler::delete_zone),
+        )
+        .route("/admin/zones/:id/activate", post(admin_handler::activate_zone))
+        .route("/admin/taxes", get(admin_handler::list_tax_schedules).post(admin_handler::add_tax_schedule))
+        .route("/admin/calendar", get(admin_handler::list_calendar_periods).post(admin_handler::add_calendar_period))
+        .route("/admin/calendar/:id", delete(admin_handler::remove_calendar_period))
+        .route("/admin/ledger/entries", post(admin_handler::post_ledger_entry))
+        .route("/admin/ledger/accounts/:account", get(admin_handler::get_account_balance))
+        .route("/admin/ledger/accounts/:account/statement", get(admin_handler::get_account_statement))
+        .route("/admin/ledger/jobs/:id", get(admin_handler::get_job_ledger))
+        .route("/admin/billing/:user_id", get(admin_handler::get_billing_account).put(admin_handler::update_billing_account))
+        .route("/admin/invoices", get(admin_handler::list_invoices))
+        .route("/admin/invoices/:id", get(admin_handler::get_invoice))
+        .route("/admin/invoices/:id/payment", post(admin_handler::record_invoice_payment))
+        .route("/admin/currencies", get(admin_handler::list_currencies))
+        .route("/admin/notifications/broadcast", post(admin_handler::broadcast_notification))
+        .route("/admin/notification-templates", get(admin_handler::list_notification_templates))
+        .route("/admin/notification-templates/preview", post(admin_handler::preview_notification_template))
+        .route(
+            "/admin/notification-templates/:type/:language",
+            put(admin_handler::put_notification_template).delete(admin_handler::delete_notification_template),
+        )
+        .route("/admin/startup", get(admin_handler::get_startup_report))
+        .route("/admin/config", get(admin_handler::get_runtime_config))
+        .route("/admin/config/reload", post(admin_handler::reload_runtime_config))
+        .route("/admin/broadcasts", get(admin_handler::list_broadcasts).post(admin_handler::create_broadcast))
+        .route("/admin/exchange-rates", get(admin_handler::get_exchange_rates).put(admin_handler::update_exchange_rates))
+        .route("/admin/tenants", get(admin_handler::list_tenants).post(admin_handler::create_tenant))
+        .route_layer(scoped("admin"));
+    #[cfg(all(feature = "admin", feature = "payments"))]
+    let admin = admin.merge(
+        Router::new()
+            .route("/admin/reconciliation", get(admin_handler::list_discrepancies))
+            .route("/admin/reconciliation/run", post(admin_handler::run_reconciliation))
+            .route("/admin/reconciliation/:id/resolve", post(admin_handler::resolve_discrepancy))
+            .route("/admin/disputes", get(admin_handler::list_disputes))
+            .route("/admin/disputes/:id", get(admin_handler::get_dispute))
+            .route("/admin/disputes/:id/evidence", post(admin_handler::submit_dispute_evidence))
+            .route("/admin/disputes/:id/outcome", post(admin_handler::record_dispute_outcome))
+            .route_layer(scoped("admin")),
+    );
+    // Fault injection only exists in builds made for resilience testing
+    #[cfg(feature = "chaos")]
+    let admin = admin.merge(
+        Router::new()
+            .route("/admin/chaos/faults", get(admin_handler::get_faults).put(admin_handler::set_faults).delete(admin_handler::clear_faults))
+            .route_layer(scoped("admin")),
+    );
+
+    let dispatch = Router::new()
+        .route("/dispatch/jobs/unassigned", get(dispatch_handler::list_unassigned_jobs))
+        .route("/dispatch/jobs/:id/candidates", get(dispatch_handler::list_candidates))
+        .route("/dispatch/jobs/:id/assign", post(dispatch_handler::force_assign))
+        .route("/dispatch/jobs/:id/unassign", post(dispatch_handler::force_unassign))
+        .route("/dispatch/jobs/:id/broadcast", post(dispatch_handler::broadcast_job))
+        .route("/dispatch/pools", post(dispatch_handler::pool_jobs))
+        .route("/dispatch/audit", get(dispatch_handler::get_audit_log))
+        .route_layer(scoped("dispatch"));
+
+    // A route layer covers every route added before it, hence one router per scope
+    let merchant_jobs = Router::new()
+        .route("/merchant/jobs", get(merchant_handler::list_jobs).post(merchant_handler::create_job))
+        .route("/merchant/jobs/:id", get(merchant_handler::get_job))
+        .route("/merchant/feed", get(merchant_handler::get_feed_settings).put(merchant_handler::update_feed_settings))
+        .route("/merchant/addresses", get(merchant_handler::list_addresses))
+        .route_layer(scoped("jobs"));
+
+    let merchant_invoices = Router::new()
+        .route("/merchant/invoices", get(merchant_handler::list_invoices))
+        .route("/merchant/invoices/draft", get(merchant_handler::get_draft_invoices))
+        .route("/merchant/invoices/:id", get(merchant_handler::get_invoice))
+        .route_layer(scoped("invoices"));
+
+    #[cfg(feature = "realtime-ably")]
+    let realtime = Router::new()
+        .route("/realtime/token", post(realtime_handler::issue_token))
+        .route_layer(scoped("realtime:connect"));
+
+    // Signed-in users acting on their own account; handlers check the path names it
+    let users = Router::new()
+        .route("/users", get(user_handler::get_user))
+        .route("/users/credits", get(user_handler::get_credit_balance))
+        .route("/users/topics", put(user_handler::update_topic_subscriptions))
+        .route("/users/:id/jobs", get(user_handler::list_jobs))
+        .route("/users/:id/blocked-drivers", get(user_handler::list_blocked_drivers).post(user_handler::block_driver))
+        .route("/users/:id/blocked-drivers/:driver_id", delete(user_handler::unblock_driver))
+        .route("/users/:id/devices", get(user_handler::list_devices))
+        .route("/users/:id/devices/:device_id", delete(user_handler::revoke_device))
+        .route("/users/:id/sos", post(user_handler::customer_sos))
+        .route_layer(scoped("users"));
+
+    let drivers = Router::new()
+        .route("/drivers", get(driver_handler::get_driver).post(driver_handler::create_driver))
+        .route("/drivers/heatmap", get(driver_handler::get_heatmap))
+        .route("/drivers/:id/jobs", get(driver_handler::list_jobs))
+        .route("/drivers/:id/available-jobs", get(driver_handler::get_available_jobs))
+        .route("/drivers/:id/profile", get(driver_handler::get_driver_profile))
+        .route("/drivers/:id/onboarding", get(driver_handler::get_onboarding))
+        .route("/drivers/:id/onboarding/documents", post(driver_handler::submit_documents))
+        .route("/drivers/:id/blocked-customers", get(driver_handler::list_blocked_customers).post(driver_handler::block_customer))
+        .route("/drivers/:id/blocked-customers/:user_id", delete(driver_handler::unblock_customer))
+        .route("/drivers/:id/locations/batch", post(driver_handler::batch_update_locations))
+        .route("/drivers/:id/break/start", post(driver_handler::start_break))
+        .route("/drivers/:id/break/end", post(driver_handler::end_break))
+        .route("/drivers/:id/equipment", put(driver_handler::update_equipment))
+        .route("/drivers/:id/sos", post(driver_handler::driver_sos))
+        .route("/drivers/:id/distance", get(driver_handler::get_distance))
+        .route("/drivers/:id/vehicle/service", post(driver_handler::record_vehicle_service))
+        .route_layer(scoped("drivers"));
+
+    // Handlers check the caller is the job's customer or its driver
+    let jobs = Router::new()
+        .route("/jobs", get(job_handler::get_job).post(job_handler::create_job))
+        .route("/jobs/bulk", post(job_handler::create_jobs_bulk))
+        .route("/jobs/bulk/:batch_id", get(job_handler::get_job_batch))
+        .route("/jobs/:id/assign", post(job_handler::assign_driver))
+        .route("/jobs/:id/complete", post(job_handler::complete_job))
+        .route("/jobs/:id/status", post(job_handler::update_job_status))
+        .route("/jobs/:id/handoff-codes", get(job_handler::get_handoff_codes))
+        .route("/jobs/:id/scan", post(job_handler::scan_handoff))
+        .route("/jobs/:id/delivery-code", post(job_handler::confirm_delivery))
+        .route("/jobs/:id/dropoff", patch(job_handler::change_dropoff))
+        .route("/jobs/:id/dropoff/approve", post(job_handler::approve_dropoff_change))
+        .route("/jobs/:id/share", get(job_handler::list_trip_shares).post(job_handler::share_trip))
+        .route("/jobs/:id/share/:token", delete(job_handler::revoke_trip_share))
+        .route("/jobs/:id/route", get(job_handler::get_job_route))
+        .route("/jobs/:id/navigation", get(job_handler::get_job_navigation))
+        .route("/jobs/:id/pool", get(job_handler::get_job_pool))
+        .route_layer(scoped("jobs"));
+
+    let sockets = Router::new()
+        .route("/ws/users/:id", get(user_handler::user_socket))
+        .route("/ws/drivers/:id", get(driver_handler::driver_socket))
+        .route_layer(scoped("realtime:connect"));
+
+    // Signing up, logging in, quotes and public tracking need no session; business staff
+    // are checked against their own session in services::business_accounts
+    let app = Router::new()
+        .route("/users", post(user_handler::create_user))
+        .route("/users/login", post(user_handler::login))
+        .route("/businesses/:id/staff", get(business_handler::list_staff).post(business_handler::add_staff))
+        .route("/businesses/:id/staff/:user_id", delete(business_handler::remove_staff))
+        .route("/businesses/:id/addresses", get(business_handler::list_addresses).post(business_handler::add_address))
+        .route("/businesses/:id/addresses/:address_id", put(business_handler::update_address).delete(business_handler::remove_address))
+        .route("/jobs/estimate", post(job_handler::estimate_job))
+        .route("/track/:tracking_code", get(job_handler::track_delivery))
+        .route("/shared/:token", get(job_handler::get_shared_trip))
+        .route("/webhooks/voice/calls", post(webhook_handler::voice_call_status))
+        .route("/track/:tracking_code/preferences", put(job_handler::update_recipient_preferences))
+        .route("/merchant/usage", get(merchant_handler::get_usage))
+        .merge(users)
+        .merge(drivers)
+        .merge(jobs)
+        .merge(sockets)
+        .merge(dispatch)
+        .merge(merchant_jobs)
+        .merge(merchant_invoices);
+    #[cfg(feature = "admin")]
+    let app = app.merge(admin);
+    #[cfg(feature = "payments")]
+    let app = app.route("/webhooks/payments/chargebacks", post(webhook_handler::chargeback));
+    #[cfg(feature = "realtime-ably")]
+    let app = app.merge(realtime);
+
+    app
+        .fallback(fallback::not_found)
+        .layer(middleware::from_fn_with_state(app_state.clone(), resolve_tenant))
+        .layer(middleware::from_fn_with_state(app_state.clone(), resolve_region))
+        .layer(middleware::from_fn(resolve_language))
+        .layer(CatchPanicLayer::custom(fallback::handle_panic))
+        .layer(middleware::from_fn_with_state(app_state.clone(), log_requests))
+        // Outermost, so tenant resolution failures carry a request ID too
+        .layer(middleware::from_fn(assign_request_id))
+        .with_state(app_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Method;
+    use serde_json::json;
+
+    use crate::mocks::app::{json_request, TestApp, TestResponse};
+
+    // Anything but the fallback's answer means the route exists, whatever it made of the request
+    fn routed(response: &TestResponse) -> bool {
+        !String::from_utf8_lossy(&response.body).contains("No route for")
+    }
+
+    // Run with --no-default-features as well, so builds without the optional subsystems are checked
+    #[tokio::test]
+    async fn test_optional_subsystems_are_routed_only_when_built_in() {
+        let app = TestApp::new();
+
+        assert!(routed(&app.post_json("/jobs/estimate", &json!({})).await));
+        assert!(routed(&app.get("/track/SPR-UNKNOWN").await));
+
+        let admin = app.admin_get("/admin/dashboard").await;
+        assert_eq!(routed(&admin), cfg!(feature = "admin"));
+        let chargeback = app.send(json_request(Method::POST, "/webhooks/payments/chargebacks", &json!({}))).await;
+        assert_eq!(routed(&chargeback), cfg!(feature = "payments"));
+        let token = app.post_json("/realtime/token", &json!({})).await;
+        assert_eq!(routed(&token), cfg!(feature = "realtime-ably"));
+    }
+}
@@ -0,0 +1,206 @@
+// src/services/ably_auth.rs
+// Token requests for clients that subscribe to Ably directly. Capabilities come from who the
+// caller is, never from what they ask for: customers get their own channel and their open
+// jobs, drivers their own channel and the jobs assigned to them. Tokens are short-lived; the
+// SDK calls back for a fresh one before expiry, which also picks up newly assigned jobs.
+use base64::{engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD}, Engine};
+use chrono::Utc;
+use ring::{hmac, rand::{SecureRandom, SystemRandom}};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        ids::{DriverId, JobId, UserId},
+        realtime::AblyTokenRequest,
+        user::{User, UserType},
+    },
+    services::cache_service::CacheService,
+};
+
+const SUBSCRIBE: &[&str] = &["subscribe"];
+const SUBSCRIBE_AND_PRESENCE: &[&str] = &["subscribe", "presence"];
+
+#[derive(Debug, Clone)]
+pub struct AblyAuthConfig {
+    pub token_ttl_seconds: i64,
+    pub channel_prefix: String,
+}
+
+impl Default for AblyAuthConfig {
+    fn default() -> Self {
+        Self {
+            token_ttl_seconds: 900,
+            channel_prefix: "sparrow".to_string(),
+        }
+    }
+}
+
+// `keyName:keySecret`, as shown in the Ably dashboard
+struct AblyKey {
+    name: String,
+    signing_key: hmac::Key,
+}
+
+pub struct AblyAuthService {
+    cache_service: Arc<CacheService>,
+    key: Option<AblyKey>,
+    random: SystemRandom,
+    config: AblyAuthConfig,
+}
+
+impl AblyAuthService {
+    pub fn new(cache_service: Arc<CacheService>, api_key: &str, config: AblyAuthConfig) -> Self {
+        let key = api_key.split_once(':')
+            .filter(|(name, secret)| name.contains('.') && !secret.is_empty())
+            .map(|(name, secret)| AblyKey {
+                name: name.to_string(),
+                signing_key: hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()),
+            });
+        if key.is_none() {
+            tracing::warn!("No usable Ably API key configured; realtime tokens can't be issued");
+        }
+        Self {
+            cache_service,
+            key,
+            random: SystemRandom::new(),
+            config,
+        }
+    }
+
+    pub fn user_channel(&self, user_id: &UserId) -> String {
+        format!("{}:users:{}", self.config.channel_prefix, user_id)
+    }
+
+    pub fn driver_channel(&self, driver_id: &DriverId) -> String {
+        format!("{}:drivers:{}", self.config.channel_prefix, driver_id)
+    }
+
+    pub fn job_channel(&self, job_id: &JobId) -> String {
+        format!("{}:jobs:{}", self.config.channel_prefix, job_id)
+    }
+
+    /// A signed token request scoped to the user's own channels
+    pub async fn token_request(&self, user: &User) -> Result<AblyTokenRequest, AppError> {
+        let key = self.key.as_ref().ok_or_else(|| AppError::service_unavailable("ably"))?;
+        let capability = serde_json::to_string(&self.capabilities(user).await?)
+            .map_err(|e| AppError::internal_error(format!("Failed to encode capability: {}", e)))?;
+
+        let mut nonce = [0u8; 16];
+        self.random.fill(&mut nonce)
+            .map_err(|_| AppError::internal_error("System randomness unavailable"))?;
+        let mut request = AblyTokenRequest {
+            key_name: key.name.clone(),
+            ttl: self.config.token_ttl_seconds * 1000,
+            capability,
+            client_id: user.id.to_string(),
+            timestamp: Utc::now().timestamp_millis(),
+            nonce: URL_SAFE_NO_PAD.encode(nonce),
+            mac: String::new(),
+        };
+        request.mac = STANDARD.encode(hmac::sign(&key.signing_key, signing_input(&request).as_bytes()).as_ref());
+
+        tracing::debug!("Issued Ably token request for user {}", user.id);
+        Ok(request)
+    }
+
+    async fn capabilities(&self, user: &User) -> Result<BTreeMap<String, &'static [&'static str]>, AppError> {
+        let mut capabilities = BTreeMap::new();
+        match user.user_type {
+            UserType::Customer | UserType::Business => {
+                capabilities.insert(self.user_channel(&user.id), SUBSCRIBE);
+                for job_id in self.cache_service.get_customer_jobs(&user.id).await? {
+                    let open = self.cache_service.load_job(&job_id).await?
+                        .is_some_and(|job| !job.status.is_terminal());
+                    if open {
+                        capabilities.insert(self.job_channel(&job_id), SUBSCRIBE);
+                    }
+                }
+            }
+            UserType::Driver => {
+                let driver_id = self.cache_service.get_driver_id_by_user_id(&user.id).await?
+                    .ok_or_else(|| AppError::Forbidden("No driver profile for this account".to_string()))?;
+                capabilities.insert(self.driver_channel(&driver_id), SUBSCRIBE_AND_PRESENCE);
+                for job_id in self.cache_service.get_driver_jobs(&driver_id).await? {
+                    let assigned = self.cache_service.load_job(&job_id).await?
+                        .is_some_and(|job| job.driver_id.as_ref() == Some(&driver_id) && !job.status.is_terminal());
+                    if assigned {
+                        capabilities.insert(self.job_channel(&job_id), SUBSCRIBE);
+                    }
+                }
+            }
+            UserType::Admin | UserType::Dispatcher => {
+                capabilities.insert(format!("{}:jobs:*", self.config.channel_prefix), SUBSCRIBE);
+            }
+        }
+        Ok(capabilities)
+    }
+}
+
+// Ably's token request MAC input: each field followed by a newline
+fn signing_input(request: &AblyTokenRequest) -> String {
+    format!(
+        "{}\n{}\n{}\n{}\n{}\n{}\n",
+        request.key_name, request.ttl, request.capability, request.client_id, request.timestamp, request.nonce
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::user::UserLogin,
+        services::{job_service::JobOperations, user_service::UserOperations},
+    };
+
+    #[tokio::test]
+    async fn test_token_request_is_scoped_to_the_callers_jobs() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(47);
+
+        let registration = faker.user_registration(UserType::Customer);
+        let login = UserLogin {
+            email: Some(registration.email.clone()),
+            phone_number: None,
+            password: registration.password.clone(),
+            device_token: None,
+            device_platform: Default::default(),
+            scopes: None,
+            device: None,
+            city: None,
+            verification_code: None,
+        };
+        let customer = state.user_service.register_user(registration).await.unwrap();
+        let other = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let own_job = state.job_service.create_job(faker.job_request(&customer.id)).await.unwrap();
+        let other_job = state.job_service.create_job(faker.job_request(&other.id)).await.unwrap();
+
+        let (_, token) = state.user_service.login_user(login).await.unwrap();
+        let user = state.user_service.authenticate_session(&token).await.unwrap();
+        assert!(state.user_service.authenticate_session("token_nobody_1").await.is_err());
+
+        let unconfigured = AblyAuthService::new(state.cache_service.clone(), "", AblyAuthConfig::default());
+        assert!(matches!(unconfigured.token_request(&user).await, Err(AppError::ServiceUnavailable { .. })));
+
+        let ably = AblyAuthService::new(state.cache_service.clone(), "app.key:secret", AblyAuthConfig::default());
+        let request = ably.token_request(&user).await.unwrap();
+        assert_eq!(request.key_name, "app.key");
+        assert_eq!(request.client_id, customer.id.to_string());
+        assert_eq!(request.ttl, 900_000);
+
+        let capability: BTreeMap<String, Vec<String>> = serde_json::from_str(&request.capability).unwrap();
+        assert!(capability.contains_key(&ably.user_channel(&customer.id)));
+        assert!(capability.contains_key(&ably.job_channel(&own_job.id)));
+        assert!(!capability.contains_key(&ably.job_channel(&other_job.id)));
+        assert!(capability.values().all(|operations| operations == &vec!["subscribe".to_string()]));
+
+        let signing_key = hmac::Key::new(hmac::HMAC_SHA256, b"secret");
+        let mac = STANDARD.decode(&request.mac).unwrap();
+        assert!(hmac::verify(&signing_key, signing_input(&request).as_bytes(), &mac).is_ok());
+    }
+}
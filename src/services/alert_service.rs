@@ -0,0 +1,373 @@
+// src/services/alert_service.rs
+// Posts to the ops chat (Slack and/or Telegram) when a key signal crosses its threshold:
+// slow dispatch, failing pushes, Redis going away, or jobs piling up without a driver.
+// Each kind of alert is raised once per cooldown, by whichever instance gets there first;
+// while Redis is down that can't be agreed on, so each instance keeps its own cooldown.
+//
+// Runs as its own loop rather than a worker: workers list tenants through Redis before
+// every pass, so they stop exactly when the Redis alert is needed.
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::alert::{Alert, AlertKind},
+    services::{
+        cache_service::CacheService,
+        dashboard_service::DashboardService,
+        tenant_service::{with_tenant, TenantService},
+    },
+};
+
+#[async_trait]
+pub trait AlertChannel: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn send(&self, alert: &Alert) -> Result<(), AppError>;
+}
+
+// Incoming webhook URL from the Slack app
+pub struct SlackWebhook {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl SlackWebhook {
+    pub fn new(url: String) -> Self {
+        Self { url, client: reqwest::Client::new() }
+    }
+}
+
+#[async_trait]
+impl AlertChannel for SlackWebhook {
+    fn name(&self) -> &'static str {
+        "slack"
+    }
+
+    async fn send(&self, alert: &Alert) -> Result<(), AppError> {
+        let response = self.client.post(&self.url)
+            .json(&json!({ "text": alert.text() }))
+            .send()
+            .await
+            .map_err(|e| AppError::HttpClient(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(AppError::HttpClient(format!("Slack rejected alert: {}", response.status())));
+        }
+        Ok(())
+    }
+}
+
+// A bot that has been added to the ops group
+pub struct TelegramWebhook {
+    bot_token: String,
+    chat_id: String,
+    client: reqwest::Client,
+}
+
+impl TelegramWebhook {
+    pub fn new(bot_token: String, chat_id: String) -> Self {
+        Self { bot_token, chat_id, client: reqwest::Client::new() }
+    }
+}
+
+#[async_trait]
+impl AlertChannel for TelegramWebhook {
+    fn name(&self) -> &'static str {
+        "telegram"
+    }
+
+    async fn send(&self, alert: &Alert) -> Result<(), AppError> {
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
+        let response = self.client.post(url)
+            .json(&json!({ "chat_id": self.chat_id, "text": alert.text() }))
+            .send()
+            .await
+            .map_err(|e| AppError::HttpClient(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(AppError::HttpClient(format!("Telegram rejected alert: {}", response.status())));
+        }
+        Ok(())
+    }
+}
+
+/// Every channel configured through ALERT_SLACK_WEBHOOK_URL, or ALERT_TELEGRAM_BOT_TOKEN
+/// with ALERT_TELEGRAM_CHAT_ID
+pub fn channels_from_env() -> Vec<Arc<dyn AlertChannel>> {
+    let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+    let mut channels: Vec<Arc<dyn AlertChannel>> = Vec::new();
+    if let Some(url) = var("ALERT_SLACK_WEBHOOK_URL") {
+        channels.push(Arc::new(SlackWebhook::new(url)));
+    }
+    if let (Some(bot_token), Some(chat_id)) = (var("ALERT_TELEGRAM_BOT_TOKEN"), var("ALERT_TELEGRAM_CHAT_ID")) {
+        channels.push(Arc::new(TelegramWebhook::new(bot_token, chat_id)));
+    }
+    channels
+}
+
+#[derive(Debug, Clone)]
+pub struct AlertConfig {
+    pub check_interval_seconds: u64,
+    pub cooldown_seconds: u64,           // Between two alerts of the same kind
+    pub dispatch_latency_seconds: f64,   // Average wait from creation to acceptance
+    pub push_window_minutes: i64,
+    pub push_failure_rate: f64,          // Of the pushes sent in the window
+    pub min_push_attempts: i64,          // Fewer than this in the window is too few to judge
+    pub redis_failed_checks: u32,        // In a row
+    pub backlog_wait_minutes: i64,       // A job waiting longer than this counts towards the backlog
+    pub backlog_jobs: usize,
+    pub backlog_growth: usize,           // Since the previous check
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 60,
+            cooldown_seconds: 900,
+            dispatch_latency_seconds: 300.0,
+            push_window_minutes: 5,
+            push_failure_rate: 0.2,
+            min_push_attempts: 20,
+            redis_failed_checks: 3,
+            backlog_wait_minutes: 10,
+            backlog_jobs: 20,
+            backlog_growth: 5,
+        }
+    }
+}
+
+#[derive(Default)]
+struct CheckState {
+    redis_failures: u32,
+    backlog: HashMap<String, usize>,       // By tenant, as of the previous check
+    raised: HashMap<String, DateTime<Utc>>, // Local cooldowns, by tenant and kind
+}
+
+pub struct AlertService {
+    cache_service: Arc<CacheService>,
+    tenant_service: Arc<TenantService>,
+    dashboard_service: Arc<DashboardService>,
+    channels: Mutex<Vec<Arc<dyn AlertChannel>>>,
+    state: Mutex<CheckState>,
+    config: AlertConfig,
+}
+
+impl AlertService {
+    pub fn new(
+        cache_service: Arc<CacheService>,
+        tenant_service: Arc<TenantService>,
+        dashboard_service: Arc<DashboardService>,
+        config: AlertConfig,
+    ) -> Self {
+        Self {
+            cache_service,
+            tenant_service,
+            dashboard_service,
+            channels: Mutex::new(Vec::new()),
+            state: Mutex::new(CheckState::default()),
+            config,
+        }
+    }
+
+    pub fn add_channel(&self, channel: Arc<dyn AlertChannel>) {
+        self.channels.lock().unwrap().push(channel);
+    }
+
+    /// Check every signal for the life of the process
+    pub async fn run(self: Arc<Self>) {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(self.config.check_interval_seconds));
+        loop {
+            ticker.tick().await;
+            self.check(Utc::now()).await;
+        }
+    }
+
+    /// Checks every signal once; returns the alerts that went out
+    pub async fn check(&self, now: DateTime<Utc>) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        match self.tenant_service.tenant_ids().await {
+            Ok(tenant_ids) => {
+                self.state.lock().unwrap().redis_failures = 0;
+                for tenant_id in tenant_ids {
+                    match with_tenant(tenant_id.clone(), self.check_tenant(&tenant_id, now)).await {
+                        Ok(found) => alerts.extend(found),
+                        Err(e) => tracing::warn!("Alert checks failed for tenant {}: {}", tenant_id, e),
+                    }
+                }
+            }
+            Err(e) => {
+                let failures = {
+                    let mut state = self.state.lock().unwrap();
+                    state.redis_failures += 1;
+                    state.redis_failures
+                };
+                tracing::warn!("Alert checks could not reach Redis ({} in a row): {}", failures, e);
+                if failures >= self.config.redis_failed_checks {
+                    alerts.push(Alert {
+                        kind: AlertKind::RedisErrors,
+                        tenant_id: None,
+                        summary: format!("Redis unreachable: {}", e),
+                        value: failures as f64,
+                        threshold: self.config.redis_failed_checks as f64,
+                        raised_at: now,
+                    });
+                }
+            }
+        }
+
+        let mut raised = Vec::new();
+        for alert in alerts {
+            if self.raise(&alert).await {
+                raised.push(alert);
+            }
+        }
+        raised
+    }
+
+    /// Posts an alert found elsewhere, e.g. by the canary; false if it is cooling down
+    pub async fn raise(&self, alert: &Alert) -> bool {
+        if !self.claim(alert, alert.raised_at).await {
+            return false;
+        }
+        self.send(alert).await;
+        true
+    }
+
+    /// Posts an alert that no cooldown may hold back, such as an SOS
+    pub async fn page(&self, alert: &Alert) {
+        self.send(alert).await;
+    }
+
+    async fn check_tenant(&self, tenant_id: &str, now: DateTime<Utc>) -> Result<Vec<Alert>, AppError> {
+        let mut alerts = Vec::new();
+        let alert = |kind, summary: String, value: f64, threshold: f64| Alert {
+            kind,
+            tenant_id: Some(tenant_id.to_string()),
+            summary,
+            value,
+            threshold,
+            raised_at: now,
+        };
+
+        let dashboard = self.dashboard_service.get_dashboard(Some(self.config.backlog_wait_minutes)).await?;
+        if let Some(latency) = dashboard.average_assignment_latency_seconds
+            && latency >= self.config.dispatch_latency_seconds
+        {
+            alerts.push(alert(AlertKind::DispatchLatency, "Jobs are slow to get a driver".to_string(), latency, self.config.dispatch_latency_seconds));
+        }
+
+        let backlog = dashboard.stale_unassigned_jobs.len();
+        let previous = self.state.lock().unwrap().backlog.insert(tenant_id.to_string(), backlog).unwrap_or(0);
+        if backlog >= self.config.backlog_jobs && backlog >= previous + self.config.backlog_growth {
+            let summary = format!("{} jobs waiting over {} minutes for a driver, up from {}", backlog, self.config.backlog_wait_minutes, previous);
+            alerts.push(alert(AlertKind::UnassignedBacklog, summary, backlog as f64, self.config.backlog_jobs as f64));
+        }
+
+        let (delivered, failed) = self.cache_service.count_push_outcomes(now, self.config.push_window_minutes).await?;
+        let attempts = delivered + failed;
+        if attempts >= self.config.min_push_attempts {
+            let rate = failed as f64 / attempts as f64;
+            if rate >= self.config.push_failure_rate {
+                let summary = format!("{} of {} pushes failed in the last {} minutes", failed, attempts, self.config.push_window_minutes);
+                alerts.push(alert(AlertKind::PushFailureRate, summary, rate, self.config.push_failure_rate));
+            }
+        }
+        Ok(alerts)
+    }
+
+    // Whether this instance gets to raise the alert now
+    async fn claim(&self, alert: &Alert, now: DateTime<Utc>) -> bool {
+        let local_key = format!("{}:{}", alert.tenant_id.as_deref().unwrap_or("*"), alert.kind);
+        {
+            let state = self.state.lock().unwrap();
+            if state.raised.get(&local_key).is_some_and(|at| now < *at + Duration::seconds(self.config.cooldown_seconds as i64)) {
+                return false;
+            }
+        }
+        if let Some(tenant_id) = &alert.tenant_id {
+            match with_tenant(tenant_id.clone(), self.cache_service.claim_alert(alert.kind, self.config.cooldown_seconds)).await {
+                Ok(true) => {}
+                Ok(false) => return false,
+                Err(e) => tracing::warn!("Could not share the {} alert cooldown: {}", alert.kind, e),
+            }
+        }
+        self.state.lock().unwrap().raised.insert(local_key, now);
+        true
+    }
+
+    async fn send(&self, alert: &Alert) {
+        let channels = self.channels.lock().unwrap().clone();
+        if channels.is_empty() {
+            tracing::warn!("No alert channel configured: {}", alert.text());
+        }
+        for channel in channels {
+            if let Err(e) = channel.send(alert).await {
+                tracing::error!("Failed to post {} alert to {}: {}", alert.kind, channel.name(), e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::{job::JobStatus, tenant::DEFAULT_TENANT_ID, user::UserType},
+    };
+
+    #[derive(Default)]
+    struct RecordingChannel {
+        sent: Mutex<Vec<Alert>>,
+    }
+
+    #[async_trait]
+    impl AlertChannel for RecordingChannel {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        async fn send(&self, alert: &Alert) -> Result<(), AppError> {
+            self.sent.lock().unwrap().push(alert.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signals_over_threshold_alert_once_per_cooldown() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let cache = state.cache_service.clone();
+        let config = AlertConfig { backlog_jobs: 2, backlog_growth: 1, min_push_attempts: 4, ..AlertConfig::default() };
+        let alerts = AlertService::new(cache.clone(), state.tenant_service.clone(), state.dashboard_service.clone(), config);
+        let channel = Arc::new(RecordingChannel::default());
+        alerts.add_channel(channel.clone());
+
+        let mut faker = Faker::seeded(72);
+        let customer = faker.user(UserType::Customer);
+        for _ in 0..3 {
+            let mut job = faker.job(&customer.id);
+            job.status = JobStatus::Searching;
+            job.created_at = Utc::now() - Duration::minutes(30);
+            cache.cache_job(&job).await.unwrap();
+            cache.add_active_job(&job.id).await.unwrap();
+        }
+        for delivered in [true, false, false, true] {
+            cache.record_push_outcome(delivered).await.unwrap();
+        }
+
+        let now = Utc::now();
+        let raised = alerts.check(now).await;
+        let kinds: Vec<AlertKind> = raised.iter().map(|alert| alert.kind).collect();
+        assert_eq!(kinds, vec![AlertKind::UnassignedBacklog, AlertKind::PushFailureRate]);
+        assert_eq!(raised[0].tenant_id.as_deref(), Some(DEFAULT_TENANT_ID));
+        assert_eq!(channel.sent.lock().unwrap().len(), 2);
+
+        // Still over, but cooling down, and the backlog has stopped growing
+        assert!(alerts.check(now).await.is_empty());
+        assert_eq!(channel.sent.lock().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,326 @@
+// src/services/api_key_service.rs
+// Server-to-server credentials for business accounts and the dispatcher console
+use chrono::{Duration, Utc};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        api_key::{ApiKey, ApiKeyResponse, ApiScope, CreateApiKeyRequest, IssuedApiKey},
+        ids::UserId,
+        quota::{QuotaLimit, UpdateQuotasRequest},
+        user::User,
+    },
+    services::{cache_service::CacheService, tenant_service::current_tenant_id},
+    utils::id_generator::{IdGenerator, IdType},
+};
+
+const SECRET_PREFIX: &str = "sk_";
+const SECRET_LENGTH: usize = 40;
+const DISPLAY_PREFIX_LENGTH: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct ApiKeyConfig {
+    pub default_rate_limit_per_minute: u32,
+    pub max_rate_limit_per_minute: u32,
+    pub rotation_grace_minutes: i64, // Old key keeps working this long after rotation
+}
+
+impl Default for ApiKeyConfig {
+    fn default() -> Self {
+        Self {
+            default_rate_limit_per_minute: 120,
+            max_rate_limit_per_minute: 6000,
+            rotation_grace_minutes: 60,
+        }
+    }
+}
+
+pub struct ApiKeyService {
+    cache_service: Arc<CacheService>,
+    config: ApiKeyConfig,
+}
+
+impl ApiKeyService {
+    pub fn new(cache_service: Arc<CacheService>, config: ApiKeyConfig) -> Self {
+        Self {
+            cache_service,
+            config,
+        }
+    }
+
+    pub async fn issue_key(&self, request: CreateApiKeyRequest) -> Result<IssuedApiKey, AppError> {
+        if request.name.trim().is_empty() {
+            return Err(AppError::MissingRequiredField("name".to_string()));
+        }
+        if request.scopes.is_empty() {
+            return Err(AppError::validation_error("scopes", "At least one scope is required"));
+        }
+        let rate_limit = request.rate_limit_per_minute.unwrap_or(self.config.default_rate_limit_per_minute);
+        if rate_limit == 0 || rate_limit > self.config.max_rate_limit_per_minute {
+            return Err(AppError::validation_error(
+                "rate_limit_per_minute",
+                format!("Must be between 1 and {}", self.config.max_rate_limit_per_minute),
+            ));
+        }
+
+        let merchant: User = self.cache_service.load_user(&request.merchant_id).await?
+            .ok_or_else(|| AppError::user_not_found(request.merchant_id.as_str()))?;
+        if request.scopes.iter().any(|scope| scope.owner_type() != merchant.user_type) {
+            return Err(AppError::validation_error(
+                "scopes",
+                format!("Not every scope can be issued to a {:?} account", merchant.user_type),
+            ));
+        }
+
+        validate_quotas(&request.quotas)?;
+
+        let (mut api_key, secret) = self.new_key(request.merchant_id, request.name, request.scopes, rate_limit);
+        api_key.quotas = request.quotas;
+        self.cache_service.cache_api_key(&api_key).await?;
+
+        tracing::info!("Issued API key {} for merchant {}", api_key.id, api_key.merchant_id);
+
+        Ok(IssuedApiKey {
+            key: api_key.into(),
+            secret,
+        })
+    }
+
+    // Issue a replacement with the same scopes; the old key expires after a grace period
+    pub async fn rotate_key(&self, key_id: &str) -> Result<IssuedApiKey, AppError> {
+        let mut old_key = self.get_usable_key(key_id).await?;
+
+        let (mut new_key, secret) = self.new_key(
+            old_key.merchant_id.clone(),
+            old_key.name.clone(),
+            old_key.scopes.clone(),
+            old_key.rate_limit_per_minute,
+        );
+        new_key.tenant_id = old_key.tenant_id.clone();
+        new_key.quotas = old_key.quotas.clone();
+        new_key.usage_of = Some(old_key.quota_id().to_string());
+        self.cache_service.cache_api_key(&new_key).await?;
+
+        old_key.expires_at = Some(Utc::now() + Duration::minutes(self.config.rotation_grace_minutes));
+        old_key.rotated_to = Some(new_key.id.clone());
+        self.cache_service.cache_api_key(&old_key).await?;
+
+        tracing::info!("Rotated API key {} to {}", old_key.id, new_key.id);
+
+        Ok(IssuedApiKey {
+            key: new_key.into(),
+            secret,
+        })
+    }
+
+    pub async fn revoke_key(&self, key_id: &str) -> Result<ApiKeyResponse, AppError> {
+        let mut api_key = self.get_usable_key(key_id).await?;
+        api_key.revoked_at = Some(Utc::now());
+        self.cache_service.cache_api_key(&api_key).await?;
+
+        tracing::info!("Revoked API key {}", api_key.id);
+        Ok(api_key.into())
+    }
+
+    pub async fn update_quotas(&self, key_id: &str, request: UpdateQuotasRequest) -> Result<ApiKeyResponse, AppError> {
+        validate_quotas(&request.quotas)?;
+        let mut api_key = self.get_usable_key(key_id).await?;
+        api_key.quotas = request.quotas;
+        self.cache_service.cache_api_key(&api_key).await?;
+
+        tracing::info!("Set {} quotas on API key {}", api_key.quotas.len(), api_key.id);
+        Ok(api_key.into())
+    }
+
+    pub async fn list_keys(&self, merchant_id: &UserId) -> Result<Vec<ApiKeyResponse>, AppError> {
+        let tenant_id = current_tenant_id();
+        let mut keys = Vec::new();
+        for key_id in self.cache_service.get_merchant_api_keys(merchant_id).await? {
+            let api_key = self.cache_service.get_api_key(&key_id).await?
+                .filter(|api_key| api_key.tenant_id == tenant_id);
+            if let Some(api_key) = api_key {
+                keys.push(ApiKeyResponse::from(api_key));
+            }
+        }
+        keys.sort_by_key(|key| key.created_at);
+        Ok(keys)
+    }
+
+    // Resolve a presented secret to its key, enforcing validity and the per-key rate limit
+    pub async fn authenticate(&self, secret: &str) -> Result<ApiKey, AppError> {
+        if !secret.starts_with(SECRET_PREFIX) {
+            return Err(AppError::unauthorized("Malformed API key"));
+        }
+
+        let key_id = self.cache_service.get_api_key_id_by_hash(&hash_secret(secret)).await?
+            .ok_or_else(|| AppError::unauthorized("Unknown API key"))?;
+        let mut api_key = self.cache_service.get_api_key(&key_id).await?
+            .ok_or_else(|| AppError::unauthorized("Unknown API key"))?;
+
+        // Keys are stored globally; one brand's key must never act inside another
+        if api_key.tenant_id != current_tenant_id() {
+            return Err(AppError::unauthorized("Unknown API key"));
+        }
+
+        let now = Utc::now();
+        if !api_key.is_usable(now) {
+            return Err(AppError::unauthorized("API key has been revoked or has expired"));
+        }
+
+        let window = now.timestamp() / 60;
+        let count = self.cache_service.count_api_key_request(&api_key.id, window).await?;
+        if count > api_key.rate_limit_per_minute as i64 {
+            tracing::warn!("API key {} exceeded {} requests/minute", api_key.id, api_key.rate_limit_per_minute);
+            let retry_after_seconds = (60 - now.timestamp() % 60) as u64;
+            return Err(AppError::RateLimitExceeded { retry_after_seconds });
+        }
+
+        // Record usage once per window rather than on every request, and on its own so a
+        // revocation landing meanwhile isn't overwritten
+        if count == 1 {
+            api_key.last_used_at = Some(now);
+            if let Err(e) = self.cache_service.record_api_key_use(&api_key.id, now).await {
+                tracing::warn!("Failed to record API key usage for {}: {}", api_key.id, e);
+            }
+        }
+
+        Ok(api_key)
+    }
+
+    // Tenant a secret belongs to, without counting the lookup against its rate limit
+    pub async fn tenant_for_secret(&self, secret: &str) -> Result<Option<String>, AppError> {
+        if !secret.starts_with(SECRET_PREFIX) {
+            return Ok(None);
+        }
+        let Some(key_id) = self.cache_service.get_api_key_id_by_hash(&hash_secret(secret)).await? else {
+            return Ok(None);
+        };
+        Ok(self.cache_service.get_api_key(&key_id).await?.map(|api_key| api_key.tenant_id))
+    }
+
+    // Any key of the current tenant, revoked or not
+    pub async fn get_key(&self, key_id: &str) -> Result<ApiKey, AppError> {
+        if !IdGenerator::validate_id(key_id, Some(IdType::ApiKey)) {
+            return Err(AppError::validation_error("key_id", "Invalid API key ID format"));
+        }
+        self.cache_service.get_api_key(key_id).await?
+            .filter(|api_key| api_key.tenant_id == current_tenant_id())
+            .ok_or_else(|| AppError::NotFound("API key not found".to_string()))
+    }
+
+    async fn get_usable_key(&self, key_id: &str) -> Result<ApiKey, AppError> {
+        let api_key = self.get_key(key_id).await?;
+        if !api_key.is_usable(Utc::now()) {
+            return Err(AppError::Conflict("API key is already revoked or expired".to_string()));
+        }
+        Ok(api_key)
+    }
+
+    fn new_key(&self, merchant_id: UserId, name: String, scopes: Vec<ApiScope>, rate_limit_per_minute: u32) -> (ApiKey, String) {
+        let secret = generate_secret();
+        let api_key = ApiKey {
+            id: IdGenerator::generate(IdType::ApiKey),
+            tenant_id: current_tenant_id(),
+            merchant_id,
+            name,
+            prefix: secret[..DISPLAY_PREFIX_LENGTH].to_string(),
+            secret_hash: hash_secret(&secret),
+            scopes,
+            rate_limit_per_minute,
+            created_at: Utc::now(),
+            last_used_at: None,
+            expires_at: None,
+            revoked_at: None,
+            rotated_to: None,
+            quotas: Vec::new(),
+            usage_of: None,
+        };
+        (api_key, secret)
+    }
+}
+
+// One limit per metric and period
+fn validate_quotas(quotas: &[QuotaLimit]) -> Result<(), AppError> {
+    for (index, quota) in quotas.iter().enumerate() {
+        if quota.limit == 0 {
+            return Err(AppError::validation_error(format!("quotas[{}].limit", index), "Must be at least 1"));
+        }
+        if quotas[..index].iter().any(|earlier| earlier.metric == quota.metric && earlier.period == quota.period) {
+            return Err(AppError::validation_error(
+                format!("quotas[{}]", index),
+                format!("{} already has a {} quota", quota.metric, quota.period),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn generate_secret() -> String {
+    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    let mut rng = rand::rng();
+    let body: String = (0..SECRET_LENGTH)
+        .map(|_| CHARSET[rng.random_range(0..CHARSET.len())] as char)
+        .collect();
+    format!("{}{}", SECRET_PREFIX, body)
+}
+
+/// Hex-encoded SHA-256 of an API key secret
+pub fn hash_secret(secret: &str) -> String {
+    Sha256::digest(secret.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{mocks::{app::TestApp, fixtures::Faker}, models::user::UserType};
+
+    #[test]
+    fn test_hash_secret_is_stable_hex() {
+        let hash = hash_secret("sk_test");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_secret("sk_test"));
+        assert_ne!(hash, hash_secret("sk_test2"));
+    }
+
+    #[tokio::test]
+    async fn test_recording_use_leaves_a_revocation_in_place() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let merchant = Faker::seeded(74).user(UserType::Business);
+        state.cache_service.cache_user(&merchant).await.unwrap();
+        let issued = state.api_key_service.issue_key(CreateApiKeyRequest {
+            merchant_id: merchant.id.clone(),
+            name: "Storefront".to_string(),
+            scopes: vec![ApiScope::CreateJobs],
+            rate_limit_per_minute: None,
+            quotas: Vec::new(),
+        }).await.unwrap();
+
+        let used = state.api_key_service.authenticate(&issued.secret).await.unwrap();
+        assert!(used.last_used_at.is_some());
+        assert_eq!(state.api_key_service.get_key(&issued.key.id).await.unwrap().last_used_at, used.last_used_at);
+
+        // Revoked between an authentication's read and its usage write
+        state.api_key_service.revoke_key(&issued.key.id).await.unwrap();
+        state.cache_service.record_api_key_use(&issued.key.id, Utc::now()).await.unwrap();
+        let api_key = state.api_key_service.get_key(&issued.key.id).await.unwrap();
+        assert!(api_key.revoked_at.is_some());
+        assert!(state.api_key_service.authenticate(&issued.secret).await.is_err());
+    }
+
+    #[test]
+    fn test_generate_secret_format() {
+        let secret = generate_secret();
+        assert!(secret.starts_with(SECRET_PREFIX));
+        assert_eq!(secret.len(), SECRET_PREFIX.len() + SECRET_LENGTH);
+        assert_ne!(secret, generate_secret());
+    }
+}
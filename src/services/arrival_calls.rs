@@ -0,0 +1,213 @@
+// src/services/arrival_calls.rs
+// Automated calls telling a pickup or dropoff contact that the driver has arrived, in English
+// or Twi as chosen when the job was booked. Meant for contacts on basic phones, who can't
+// follow the tracking link. The gateway reports back how each call went, signed with the
+// shared secret, and every report lands in the job's event log.
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use ring::hmac;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{job::{CallLanguage, Job, JobEvent, JobEventType, StopKind}, voice::{ArrivalCall, CallStatusCallback}},
+    services::{cache_service::CacheService, sms::mask_phone, voice::VoiceGateway},
+};
+
+pub const VOICE_SIGNATURE_HEADER: &str = "x-voice-signature";
+
+#[derive(Clone, Default)]
+pub struct ArrivalCallConfig {
+    pub webhook_secret: Option<Vec<u8>>, // Shared with the gateway; callbacks are refused without one
+}
+
+impl ArrivalCallConfig {
+    pub fn from_env() -> Self {
+        Self {
+            webhook_secret: std::env::var("VOICE_WEBHOOK_SECRET").ok()
+                .filter(|value| !value.is_empty())
+                .map(String::into_bytes),
+        }
+    }
+}
+
+pub struct ArrivalCallService {
+    cache_service: Arc<CacheService>,
+    gateway: Arc<dyn VoiceGateway>,
+    webhook_key: Option<hmac::Key>,
+}
+
+impl ArrivalCallService {
+    pub fn new(cache_service: Arc<CacheService>, gateway: Arc<dyn VoiceGateway>, config: ArrivalCallConfig) -> Self {
+        Self {
+            cache_service,
+            gateway,
+            webhook_key: config.webhook_secret.map(|secret| hmac::Key::new(hmac::HMAC_SHA256, &secret)),
+        }
+    }
+
+    /// Call the contact at `stop` if the job asked for it. None when it didn't.
+    pub async fn announce(&self, job: &Job, stop: StopKind, driver_name: Option<&str>) -> Result<Option<ArrivalCall>, AppError> {
+        let Some(calls) = &job.arrival_calls else {
+            return Ok(None);
+        };
+        let (wanted, location) = match stop {
+            StopKind::Pickup => (calls.pickup, &job.pickup_location),
+            StopKind::Dropoff => (calls.dropoff, &job.dropoff_location),
+        };
+        if !wanted {
+            return Ok(None);
+        }
+        let phone = location.contact_phone.trim();
+        if phone.is_empty() {
+            return Err(AppError::validation_error("contact_phone", "An arrival call needs the contact's phone number"));
+        }
+
+        let message = script(calls.language, stop, &job.tracking_code, driver_name);
+        let call_id = self.gateway.call(phone, calls.language, &message).await?;
+        let call = ArrivalCall {
+            call_id,
+            job_id: job.id.clone(),
+            stop,
+            language: calls.language,
+            to: mask_phone(phone),
+            placed_at: Utc::now(),
+        };
+        self.cache_service.cache_arrival_call(&call).await?;
+        self.cache_service.append_job_event(&job.id, &JobEvent {
+            event_type: JobEventType::ArrivalCallPlaced,
+            timestamp: call.placed_at,
+            location: None,
+            actor: "system".to_string(),
+            notes: Some(format!("Called {} contact {} in {:?}", stop_name(stop), call.to, call.language)),
+        }).await?;
+        tracing::info!("Placed {} arrival call {} for job {}", stop_name(stop), call.call_id, job.id);
+        Ok(Some(call))
+    }
+
+    /// Check a callback body against its base64 HMAC-SHA256 signature
+    pub fn verify_webhook(&self, body: &[u8], signature: Option<&str>) -> Result<(), AppError> {
+        let key = self.webhook_key.as_ref()
+            .ok_or_else(|| AppError::unauthorized("Voice callbacks are not configured"))?;
+        let signature = signature
+            .and_then(|signature| STANDARD.decode(signature.trim()).ok())
+            .ok_or_else(|| AppError::unauthorized("Missing or malformed callback signature"))?;
+        hmac::verify(key, body, &signature).map_err(|_| AppError::unauthorized("Callback signature does not match"))
+    }
+
+    /// Record what the gateway reported about a call on its job
+    pub async fn record_status(&self, callback: CallStatusCallback) -> Result<JobEvent, AppError> {
+        let call = self.cache_service.get_arrival_call(&callback.call_id).await?
+            .ok_or_else(|| AppError::NotFound(format!("Call {} not found", callback.call_id)))?;
+        let mut notes = format!("{} call to {}: {}", stop_name(call.stop), call.to, callback.status.as_str());
+        if let Some(seconds) = callback.duration_seconds {
+            notes.push_str(&format!(", {}s", seconds));
+        }
+        if callback.digits.as_deref().is_some_and(|digits| digits.trim() == "1") {
+            notes.push_str(", contact confirmed they're coming");
+        }
+        let event = JobEvent {
+            event_type: JobEventType::ArrivalCallUpdated,
+            timestamp: Utc::now(),
+            location: None,
+            actor: "voice_gateway".to_string(),
+            notes: Some(notes),
+        };
+        self.cache_service.append_job_event(&call.job_id, &event).await?;
+        Ok(event)
+    }
+}
+
+fn stop_name(stop: StopKind) -> &'static str {
+    match stop {
+        StopKind::Pickup => "pickup",
+        StopKind::Dropoff => "dropoff",
+    }
+}
+
+// Read one character at a time, so "GH4F2" isn't read as a word
+fn spelled(tracking_code: &str) -> String {
+    tracking_code.chars().map(String::from).collect::<Vec<_>>().join(" ")
+}
+
+fn script(language: CallLanguage, stop: StopKind, tracking_code: &str, driver_name: Option<&str>) -> String {
+    let code = spelled(tracking_code);
+    let driver = match language {
+        CallLanguage::English => driver_name.map_or("Your driver".to_string(), |name| format!("Your driver {}", name)),
+        CallLanguage::Twi => driver_name.map_or("Wo drɔba".to_string(), |name| format!("Wo drɔba {}", name)),
+    };
+    match (language, stop) {
+        (CallLanguage::English, StopKind::Pickup) => format!(
+            "Hello, this is Sparrow. {} has arrived to collect delivery {}. Please bring the package out. Press 1 if you are coming.",
+            driver, code,
+        ),
+        (CallLanguage::English, StopKind::Dropoff) => format!(
+            "Hello, this is Sparrow. {} has arrived with delivery {} for you. Please come and receive it. Press 1 if you are coming.",
+            driver, code,
+        ),
+        (CallLanguage::Twi, StopKind::Pickup) => format!(
+            "Agoo, yɛfrɛ wo firi Sparrow. {} aba sɛ ɔrebɛfa nneɛma {}. Yɛsrɛ wo, fa nneɛma no bra. Mia baako sɛ woreba a.",
+            driver, code,
+        ),
+        (CallLanguage::Twi, StopKind::Dropoff) => format!(
+            "Agoo, yɛfrɛ wo firi Sparrow. {} de wo nneɛma {} aba. Yɛsrɛ wo, bra bɛgye. Mia baako sɛ woreba a.",
+            driver, code,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker, messaging::RecordingVoiceGateway},
+        models::{ids::UserId, job::ArrivalCalls, voice::CallStatus},
+    };
+
+    #[tokio::test]
+    async fn test_arrival_calls_follow_the_jobs_choice_and_log_signed_callbacks() {
+        let app = TestApp::new();
+        let gateway = Arc::new(RecordingVoiceGateway::new());
+        let secret = b"voice-secret".to_vec();
+        let calls = ArrivalCallService::new(
+            app.state.cache_service.clone(),
+            gateway.clone(),
+            ArrivalCallConfig { webhook_secret: Some(secret.clone()) },
+        );
+
+        let mut job = Faker::seeded(107).job(&UserId::generate());
+        assert!(calls.announce(&job, StopKind::Dropoff, None).await.unwrap().is_none());
+        job.arrival_calls = Some(ArrivalCalls { language: CallLanguage::Twi, pickup: false, dropoff: true });
+        job.tracking_code = "GH42".to_string();
+        assert!(calls.announce(&job, StopKind::Pickup, Some("Kofi")).await.unwrap().is_none());
+
+        let call = calls.announce(&job, StopKind::Dropoff, Some("Kofi")).await.unwrap().unwrap();
+        let placed = gateway.placed().pop().unwrap();
+        assert_eq!((placed.call_id.as_str(), placed.to.as_str()), (call.call_id.as_str(), job.dropoff_location.contact_phone.as_str()));
+        assert_eq!(placed.language, CallLanguage::Twi);
+        assert!(placed.message.contains("Wo drɔba Kofi") && placed.message.contains("G H 4 2"));
+
+        let body = serde_json::to_vec(&CallStatusCallback {
+            call_id: call.call_id.clone(),
+            status: CallStatus::Completed,
+            duration_seconds: Some(21),
+            digits: Some("1".to_string()),
+        }).unwrap();
+        let signature = STANDARD.encode(hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &secret), &body).as_ref());
+        assert!(calls.verify_webhook(&body, None).is_err());
+        assert!(calls.verify_webhook(&body, Some(&STANDARD.encode(b"forged"))).is_err());
+        calls.verify_webhook(&body, Some(&signature)).unwrap();
+        calls.record_status(serde_json::from_slice(&body).unwrap()).await.unwrap();
+
+        let events = app.state.cache_service.get_job_events(&job.id).await.unwrap();
+        let kinds: Vec<&JobEventType> = events.iter().map(|event| &event.event_type).collect();
+        assert_eq!(kinds, vec![&JobEventType::ArrivalCallPlaced, &JobEventType::ArrivalCallUpdated]);
+        let notes = events[1].notes.as_deref().unwrap();
+        assert!(notes.contains("completed, 21s") && notes.contains("confirmed"));
+
+        let unknown = CallStatusCallback { call_id: "call-unknown".to_string(), status: CallStatus::Busy, duration_seconds: None, digits: None };
+        assert!(matches!(calls.record_status(unknown).await, Err(AppError::NotFound(_))));
+    }
+}
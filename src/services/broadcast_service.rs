@@ -0,0 +1,179 @@
+// src/services/broadcast_service.rs
+// Zone topic subscriptions and promotional broadcasts to them. Scheduled broadcasts are
+// stored and picked up by the broadcast scheduler once they fall due.
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        broadcast::{language_tag, zone_slug, zone_topic, Broadcast, BroadcastStatus, CreateBroadcastRequest, LocalizedText, TopicSubscriptions, UpdateTopicsRequest},
+        device::fcm_tokens,
+        ids::UserId,
+        user::DEFAULT_LANGUAGE,
+    },
+    services::{
+        cache_service::CacheService,
+        messaging_service::{NotificationMessage, NotificationPriority, NotificationService},
+        tenant_service::current_tenant_id,
+    },
+    utils::id_generator::{IdGenerator, IdType},
+};
+
+pub struct BroadcastService {
+    cache_service: Arc<CacheService>,
+    notification_service: Arc<dyn NotificationService>,
+}
+
+impl BroadcastService {
+    pub fn new(cache_service: Arc<CacheService>, notification_service: Arc<dyn NotificationService>) -> Self {
+        Self {
+            cache_service,
+            notification_service,
+        }
+    }
+
+    /// Follow exactly `zones`: subscribe the user's devices to new zones and drop the rest
+    pub async fn update_subscriptions(&self, user_id: &UserId, request: UpdateTopicsRequest) -> Result<TopicSubscriptions, AppError> {
+        let user = self.cache_service.load_user(user_id).await?
+            .ok_or_else(|| AppError::user_not_found(user_id.clone()))?;
+        let mut zones = Vec::new();
+        for (index, zone) in request.zones.iter().enumerate() {
+            if zone_slug(zone).is_empty() {
+                return Err(AppError::validation_error(format!("zones[{}]", index), "Must name a zone"));
+            }
+            if !zones.iter().any(|seen: &String| zone_slug(seen) == zone_slug(zone)) {
+                zones.push(zone.trim().to_string());
+            }
+        }
+
+        let tenant_id = current_tenant_id();
+        let language = language_tag(&user.language);
+        // Everything subscribed before is dropped first, in case the language or devices changed since
+        if let Some(previous) = self.cache_service.get_topic_subscriptions(user_id).await? {
+            for zone in &previous.zones {
+                let topic = zone_topic(&tenant_id, zone, &previous.language);
+                self.notification_service.unsubscribe_from_topic(&topic, &previous.device_tokens).await?;
+            }
+        }
+        // Topics are FCM's, so devices on APNs are left out
+        let device_tokens = fcm_tokens(&user.device_tokens);
+        for zone in &zones {
+            let topic = zone_topic(&tenant_id, zone, &language);
+            self.notification_service.subscribe_to_topic(&topic, &device_tokens).await?;
+            self.cache_service.add_zone_language(&zone_slug(zone), &language).await?;
+        }
+
+        let subscriptions = TopicSubscriptions {
+            user_id: user_id.clone(),
+            zones,
+            language,
+            device_tokens,
+        };
+        self.cache_service.cache_topic_subscriptions(&subscriptions).await?;
+        Ok(subscriptions)
+    }
+
+    pub async fn list_broadcasts(&self) -> Result<Vec<Broadcast>, AppError> {
+        self.cache_service.get_broadcasts().await
+    }
+
+    pub async fn create_broadcast(&self, request: CreateBroadcastRequest) -> Result<Broadcast, AppError> {
+        if request.zones.is_empty() || request.zones.iter().any(|zone| zone_slug(zone).is_empty()) {
+            return Err(AppError::validation_error("zones", "Name at least one zone"));
+        }
+        let messages: BTreeMap<String, LocalizedText> = request.messages.into_iter()
+            .map(|(language, text)| (language_tag(&language), text))
+            .collect();
+        if !messages.contains_key(DEFAULT_LANGUAGE) {
+            return Err(AppError::validation_error("messages", "An English message is required as the fallback"));
+        }
+        for (language, text) in &messages {
+            if text.title.trim().is_empty() || text.body.trim().is_empty() {
+                return Err(AppError::validation_error(format!("messages.{}", language), "Title and body are required"));
+            }
+        }
+
+        let now = Utc::now();
+        let mut broadcast = Broadcast {
+            id: IdGenerator::generate(IdType::Broadcast),
+            zones: request.zones.iter().map(|zone| zone.trim().to_string()).collect(),
+            messages,
+            send_at: request.send_at.unwrap_or(now).max(now),
+            status: BroadcastStatus::Scheduled,
+            topics: Vec::new(),
+            error: None,
+            created_at: now,
+            sent_at: None,
+        };
+        if broadcast.send_at <= now {
+            self.send(&mut broadcast, now).await;
+        }
+
+        let mut broadcasts = self.cache_service.get_broadcasts().await?;
+        broadcasts.push(broadcast.clone());
+        self.cache_service.cache_broadcasts(&broadcasts).await?;
+
+        tracing::info!("Broadcast {} to {} zones: {:?}", broadcast.id, broadcast.zones.len(), broadcast.status);
+        Ok(broadcast)
+    }
+
+    /// Send every scheduled broadcast due by `now`; returns how many were attempted
+    pub async fn send_due(&self, now: DateTime<Utc>) -> Result<usize, AppError> {
+        let mut broadcasts = self.cache_service.get_broadcasts().await?;
+        let mut attempted = 0;
+        for broadcast in broadcasts.iter_mut() {
+            if broadcast.status == BroadcastStatus::Scheduled && broadcast.send_at <= now {
+                self.send(broadcast, now).await;
+                attempted += 1;
+            }
+        }
+        if attempted > 0 {
+            self.cache_service.cache_broadcasts(&broadcasts).await?;
+        }
+        Ok(attempted)
+    }
+
+    // Push to each language topic with subscribers in the broadcast's zones. Not retried:
+    // a promotion that fails is recorded as such rather than going out late.
+    async fn send(&self, broadcast: &mut Broadcast, now: DateTime<Utc>) {
+        let tenant_id = current_tenant_id();
+        let mut topics = BTreeSet::new();
+        let result: Result<(), AppError> = async {
+            for zone in &broadcast.zones {
+                for language in self.cache_service.get_zone_languages(&zone_slug(zone)).await? {
+                    let topic = zone_topic(&tenant_id, zone, &language);
+                    let Some(text) = broadcast.message_for(&language) else {
+                        continue;
+                    };
+                    if !topics.insert(topic.clone()) {
+                        continue;
+                    }
+                    let message = NotificationMessage::new(&text.title, &text.body)
+                        .with_data(json!({
+                            "type": "promotion",
+                            "broadcast_id": broadcast.id,
+                            "zone": zone,
+                        }))
+                        .with_priority(NotificationPriority::Normal);
+                    self.notification_service.send_to_topic(&topic, message).await?;
+                }
+            }
+            Ok(())
+        }.await;
+
+        broadcast.topics = topics.into_iter().collect();
+        broadcast.sent_at = Some(now);
+        match result {
+            Ok(()) => broadcast.status = BroadcastStatus::Sent,
+            Err(e) => {
+                tracing::error!("Broadcast {} failed: {}", broadcast.id, e);
+                broadcast.status = BroadcastStatus::Failed;
+                broadcast.error = Some(e.to_string());
+            }
+        }
+    }
+}
@@ -0,0 +1,256 @@
+// src/services/business_accounts.rs
+// Staff and the shared address book of business accounts. The business's own account acts
+// as an admin; staff are customers added with a role. Anyone on the staff, and the
+// business's own API keys, can book from the shared addresses; only admins and editors
+// change them.
+use chrono::Utc;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{business::{AddStaffRequest, SharedAddressRequest, StaffMember, StaffRole}, ids::UserId, user::{Address, User, UserType}},
+    services::cache_service::CacheService,
+    utils::id_generator::{IdGenerator, IdType},
+};
+
+pub struct BusinessAccountService {
+    cache: Arc<CacheService>,
+}
+
+impl BusinessAccountService {
+    pub fn new(cache: Arc<CacheService>) -> Self {
+        Self { cache }
+    }
+
+    async fn business(&self, business_id: &UserId) -> Result<User, AppError> {
+        self.cache.load_user(business_id).await?
+            .filter(|user| user.user_type == UserType::Business)
+            .ok_or_else(|| AppError::NotFound(format!("Business account {} not found", business_id)))
+    }
+
+    async fn role(&self, business_id: &UserId, user_id: &UserId) -> Result<StaffRole, AppError> {
+        self.business(business_id).await?;
+        if user_id == business_id {
+            return Ok(StaffRole::Admin);
+        }
+        self.cache.get_business_staff(business_id).await?
+            .into_iter()
+            .find(|member| member.user_id == *user_id)
+            .map(|member| member.role)
+            .ok_or_else(|| AppError::Forbidden(format!("Not on the staff of {}", business_id)))
+    }
+
+    pub async fn staff(&self, business_id: &UserId, actor: &UserId) -> Result<Vec<StaffMember>, AppError> {
+        self.role(business_id, actor).await?;
+        self.cache.get_business_staff(business_id).await
+    }
+
+    /// Adds a customer to the staff, or changes their role if they're already on it.
+    /// Someone books for one business at a time.
+    pub async fn add_staff(&self, business_id: &UserId, actor: &UserId, request: AddStaffRequest) -> Result<StaffMember, AppError> {
+        if !self.role(business_id, actor).await?.can_manage_staff() {
+            return Err(AppError::InsufficientPermissions);
+        }
+        let user = self.cache.load_user(&request.user_id).await?
+            .ok_or_else(|| AppError::user_not_found(request.user_id.as_str()))?;
+        if user.user_type != UserType::Customer {
+            return Err(AppError::validation_error("user_id", "Only customer accounts can join a business's staff"));
+        }
+        if let Some(other) = self.cache.get_staff_business(&user.id).await?
+            && other != *business_id
+        {
+            return Err(AppError::Conflict(format!("User {} is already on the staff of another business", user.id)));
+        }
+
+        let member = StaffMember {
+            user_id: user.id.clone(),
+            role: request.role,
+            added_by: actor.clone(),
+            added_at: Utc::now(),
+        };
+        let mut staff = self.cache.get_business_staff(business_id).await?;
+        staff.retain(|existing| existing.user_id != user.id);
+        staff.push(member.clone());
+        self.cache.cache_business_staff(business_id, &staff).await?;
+        self.cache.set_staff_business(&user.id, Some(business_id)).await?;
+        tracing::info!("{} added {} to the staff of {} as {:?}", actor, user.id, business_id, member.role);
+        Ok(member)
+    }
+
+    /// Returns the staff left
+    pub async fn remove_staff(&self, business_id: &UserId, actor: &UserId, user_id: &UserId) -> Result<Vec<StaffMember>, AppError> {
+        if !self.role(business_id, actor).await?.can_manage_staff() {
+            return Err(AppError::InsufficientPermissions);
+        }
+        let mut staff = self.cache.get_business_staff(business_id).await?;
+        let before = staff.len();
+        staff.retain(|member| member.user_id != *user_id);
+        if staff.len() == before {
+            return Err(AppError::NotFound(format!("User {} is not on the staff", user_id)));
+        }
+        self.cache.cache_business_staff(business_id, &staff).await?;
+        self.cache.set_staff_business(user_id, None).await?;
+        tracing::info!("{} removed {} from the staff of {}", actor, user_id, business_id);
+        Ok(staff)
+    }
+
+    pub async fn addresses(&self, business_id: &UserId, actor: &UserId) -> Result<Vec<Address>, AppError> {
+        self.role(business_id, actor).await?;
+        self.cache.get_business_addresses(business_id).await
+    }
+
+    pub async fn add_address(&self, business_id: &UserId, actor: &UserId, request: SharedAddressRequest) -> Result<Address, AppError> {
+        self.require_editor(business_id, actor).await?;
+        validate(&request)?;
+        let now = Utc::now();
+        let address = Address {
+            id: IdGenerator::generate(IdType::Address),
+            label: request.label.trim().to_string(),
+            street: request.street.trim().to_string(),
+            city: request.city,
+            region: request.region,
+            country: request.country,
+            postal_code: request.postal_code,
+            latitude: Some(request.latitude),
+            longitude: Some(request.longitude),
+            is_primary: false,
+            created_at: now,
+            updated_at: now,
+        };
+        let mut addresses = self.cache.get_business_addresses(business_id).await?;
+        addresses.push(address.clone());
+        self.cache.cache_business_addresses(business_id, &addresses).await?;
+        Ok(address)
+    }
+
+    pub async fn update_address(&self, business_id: &UserId, actor: &UserId, address_id: &str, request: SharedAddressRequest) -> Result<Address, AppError> {
+        self.require_editor(business_id, actor).await?;
+        validate(&request)?;
+        let mut addresses = self.cache.get_business_addresses(business_id).await?;
+        let address = addresses.iter_mut()
+            .find(|address| address.id == address_id)
+            .ok_or_else(|| AppError::NotFound("Address not found".to_string()))?;
+        address.label = request.label.trim().to_string();
+        address.street = request.street.trim().to_string();
+        address.city = request.city;
+        address.region = request.region;
+        address.country = request.country;
+        address.postal_code = request.postal_code;
+        address.latitude = Some(request.latitude);
+        address.longitude = Some(request.longitude);
+        address.updated_at = Utc::now();
+        let updated = address.clone();
+        self.cache.cache_business_addresses(business_id, &addresses).await?;
+        Ok(updated)
+    }
+
+    /// Returns the addresses left
+    pub async fn remove_address(&self, business_id: &UserId, actor: &UserId, address_id: &str) -> Result<Vec<Address>, AppError> {
+        self.require_editor(business_id, actor).await?;
+        let mut addresses = self.cache.get_business_addresses(business_id).await?;
+        let before = addresses.len();
+        addresses.retain(|address| address.id != address_id);
+        if addresses.len() == before {
+            return Err(AppError::NotFound("Address not found".to_string()));
+        }
+        self.cache.cache_business_addresses(business_id, &addresses).await?;
+        Ok(addresses)
+    }
+
+    /// A shared address `customer_id` may book from: their business's when they're on its
+    /// staff, or a business's own when it books through its API keys
+    pub async fn bookable_address(&self, customer_id: &UserId, address_id: &str) -> Result<Option<Address>, AppError> {
+        let business_id = match self.cache.get_staff_business(customer_id).await? {
+            Some(business_id) => business_id,
+            None => customer_id.clone(),
+        };
+        Ok(self.cache.get_business_addresses(&business_id).await?
+            .into_iter()
+            .find(|address| address.id == address_id))
+    }
+
+    async fn require_editor(&self, business_id: &UserId, actor: &UserId) -> Result<(), AppError> {
+        if self.role(business_id, actor).await?.can_edit_addresses() {
+            Ok(())
+        } else {
+            Err(AppError::InsufficientPermissions)
+        }
+    }
+}
+
+fn validate(request: &SharedAddressRequest) -> Result<(), AppError> {
+    if request.label.trim().is_empty() {
+        return Err(AppError::validation_error("label", "Must not be empty"));
+    }
+    if request.street.trim().is_empty() {
+        return Err(AppError::validation_error("street", "Must not be empty"));
+    }
+    if !(-90.0..=90.0).contains(&request.latitude) || !(-180.0..=180.0).contains(&request.longitude) {
+        return Err(AppError::validation_error("latitude", "Coordinates are out of range"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        services::{job_service::JobOperations, user_service::UserOperations},
+    };
+
+    #[tokio::test]
+    async fn test_staff_book_from_the_shared_address_book_editors_maintain() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let accounts = &state.business_accounts;
+        let mut faker = Faker::seeded(106);
+
+        let business = state.user_service.register_user(faker.user_registration(UserType::Business)).await.unwrap();
+        let mut register = || faker.user_registration(UserType::Customer);
+        let (editor, booker, outsider) = (register(), register(), register());
+        let editor = state.user_service.register_user(editor).await.unwrap();
+        let booker = state.user_service.register_user(booker).await.unwrap();
+        let outsider = state.user_service.register_user(outsider).await.unwrap();
+        accounts.add_staff(&business.id, &business.id, AddStaffRequest { user_id: editor.id.clone(), role: StaffRole::Editor }).await.unwrap();
+        accounts.add_staff(&business.id, &business.id, AddStaffRequest { user_id: booker.id.clone(), role: StaffRole::Booker }).await.unwrap();
+
+        let request = faker.job_request(&booker.id);
+        let pickup = request.pickup_location.clone().unwrap();
+        let warehouse = || SharedAddressRequest {
+            label: "Tema warehouse".to_string(),
+            street: pickup.address.clone(),
+            city: pickup.city.clone(),
+            region: pickup.region.clone(),
+            country: "Ghana".to_string(),
+            postal_code: None,
+            latitude: pickup.latitude,
+            longitude: pickup.longitude,
+        };
+        // Bookers only book; editors keep the book but don't manage staff
+        assert!(matches!(accounts.add_address(&business.id, &booker.id, warehouse()).await, Err(AppError::InsufficientPermissions)));
+        let invite = AddStaffRequest { user_id: outsider.id.clone(), role: StaffRole::Admin };
+        assert!(matches!(accounts.add_staff(&business.id, &editor.id, invite).await, Err(AppError::InsufficientPermissions)));
+        let address = accounts.add_address(&business.id, &editor.id, warehouse()).await.unwrap();
+        assert_eq!(accounts.addresses(&business.id, &booker.id).await.unwrap().len(), 1);
+        assert!(matches!(accounts.addresses(&business.id, &outsider.id).await, Err(AppError::Forbidden(_))));
+
+        let mut book = |customer_id: &UserId| {
+            let mut request = faker.job_request(customer_id);
+            request.pickup_location = None;
+            request.pickup_address_id = Some(address.id.clone());
+            request
+        };
+        let job = state.job_service.create_job(book(&booker.id)).await.unwrap();
+        assert!((job.pickup_location.latitude - pickup.latitude).abs() < 1e-9 && (job.pickup_location.longitude - pickup.longitude).abs() < 1e-9);
+        // The business's own API bookings can use it too, but nobody off the staff
+        state.job_service.create_job(book(&business.id)).await.unwrap();
+        assert!(matches!(state.job_service.create_job(book(&outsider.id)).await, Err(AppError::Forbidden(_))));
+
+        let staff = accounts.remove_staff(&business.id, &business.id, &booker.id).await.unwrap();
+        assert_eq!(staff.len(), 1);
+        assert!(matches!(state.job_service.create_job(book(&booker.id)).await, Err(AppError::Forbidden(_))));
+    }
+}
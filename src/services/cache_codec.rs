@@ -0,0 +1,123 @@
+// src/services/cache_codec.rs
+// Wire format of cached values. JSON is written bare, exactly as before, so older
+// binaries can still read it. Other formats are wrapped in a small versioned envelope:
+//
+//     [ENVELOPE_MAGIC, version, format tag, payload...]
+//
+// Reads never depend on the configured format: bare JSON and every known envelope
+// decode, so a rollout (or rollback) can switch formats without flushing Redis.
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::{errors::SparrowError as AppError, services::cache_service::CacheError};
+
+// Never the first byte of a JSON document (not even valid UTF-8)
+pub const ENVELOPE_MAGIC: u8 = 0xFF;
+pub const ENVELOPE_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheFormat {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+impl CacheFormat {
+    fn tag(self) -> u8 {
+        match self {
+            CacheFormat::Json => 0,
+            CacheFormat::MessagePack => 1,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(CacheFormat::Json),
+            1 => Some(CacheFormat::MessagePack),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for CacheFormat {
+    type Err = AppError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "json" => Ok(CacheFormat::Json),
+            "msgpack" | "messagepack" => Ok(CacheFormat::MessagePack),
+            _ => Err(AppError::validation_error("cache_format", "Expected json or msgpack")),
+        }
+    }
+}
+
+pub fn encode<T: Serialize + ?Sized>(format: CacheFormat, value: &T) -> Result<Vec<u8>, CacheError> {
+    match format {
+        CacheFormat::Json => Ok(serde_json::to_vec(value)?),
+        CacheFormat::MessagePack => {
+            let mut bytes = vec![ENVELOPE_MAGIC, ENVELOPE_VERSION, format.tag()];
+            // Named fields, so `#[serde(default)]` and added fields behave as they do in JSON
+            rmp_serde::encode::write_named(&mut bytes, value)?;
+            Ok(bytes)
+        }
+    }
+}
+
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CacheError> {
+    let [ENVELOPE_MAGIC, version, tag, payload @ ..] = bytes else {
+        // Bare JSON, as written by the JSON format and everything before envelopes
+        return Ok(serde_json::from_slice(bytes)?);
+    };
+    if *version != ENVELOPE_VERSION {
+        return Err(CacheError::UnsupportedEnvelope { version: *version, tag: *tag });
+    }
+    match CacheFormat::from_tag(*tag) {
+        Some(CacheFormat::Json) => Ok(serde_json::from_slice(payload)?),
+        Some(CacheFormat::MessagePack) => Ok(rmp_serde::from_slice(payload)?),
+        None => Err(CacheError::UnsupportedEnvelope { version: *version, tag: *tag }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ids::UserId, job::Job};
+    use crate::mocks::fixtures::Faker;
+
+    #[test]
+    fn test_round_trips_and_reads_legacy_json() {
+        let job = Faker::seeded(7).job(&UserId::generate());
+
+        for format in [CacheFormat::Json, CacheFormat::MessagePack] {
+            let decoded: Job = decode(&encode(format, &job).unwrap()).unwrap();
+            assert_eq!(decoded.id, job.id);
+            assert_eq!(decoded.tenant_id, job.tenant_id);
+            assert_eq!(decoded.pricing.total, job.pricing.total);
+        }
+
+        // Entries written before this module existed
+        let legacy = serde_json::to_string(&job).unwrap();
+        let decoded: Job = decode(legacy.as_bytes()).unwrap();
+        assert_eq!(decoded.id, job.id);
+    }
+
+    #[test]
+    fn test_message_pack_is_smaller() {
+        let job = Faker::seeded(7).job(&UserId::generate());
+        let json = encode(CacheFormat::Json, &job).unwrap();
+        let msgpack = encode(CacheFormat::MessagePack, &job).unwrap();
+        assert!(msgpack.len() < json.len(), "{} >= {}", msgpack.len(), json.len());
+    }
+
+    #[test]
+    fn test_unknown_envelope_is_an_error() {
+        let future = [ENVELOPE_MAGIC, ENVELOPE_VERSION + 1, 1, 0x80];
+        assert!(matches!(
+            decode::<Job>(&future),
+            Err(CacheError::UnsupportedEnvelope { version: 2, tag: 1 })
+        ));
+        assert!("msgpack".parse::<CacheFormat>().is_ok());
+        assert!("cbor".parse::<CacheFormat>().is_err());
+    }
+}
@@ -1,768 +1,3568 @@
-// // src/services/cache_service.rs
-// use async_trait::async_trait;
-// use redis::{Client};
-// use serde::{de::DeserializeOwned, Serialize};
-// use std::sync::Arc;
-// use tokio::sync::RwLock;
-// use chrono::{DateTime, Utc};
-// use tracing;
-
-// use crate::models::{user::User, job::Job};
-// use crate::errors::SparrowError as AppError;
-
-// // Cache configuration
-// #[derive(Debug, Clone)]
-// pub struct CacheConfig {
-//     pub default_ttl_seconds: u64,
-//     pub redis_url: String,
-//     pub enabled: bool,
-// }
-
-// impl Default for CacheConfig {
-//     fn default() -> Self {
-//         Self {
-//             default_ttl_seconds: 300, // 5 minutes
-//             redis_url: "redis://127.0.0.1:6379".to_string(),
-//             enabled: true,
-//         }
-//     }
-// }
-
-// // Cache key strategies
-// #[derive(Debug, Clone)]
-// pub enum CacheKey {
-//     Simple(String),
-//     Composite(Vec<String>),
-//     Pattern(String),
-// }
-
-// impl CacheKey {
-//     pub fn to_string(&self) -> String {
-//         match self {
-//             CacheKey::Simple(key) => key.clone(),
-//             CacheKey::Composite(parts) => parts.join(":"),
-//             CacheKey::Pattern(pattern) => pattern.clone(),
-//         }
-//     }
-// }
-
-// // ------------------------------
-// // Traits (split to avoid E0283)
-// // ------------------------------
-
-// #[async_trait]
-// pub trait CacheOperations<T>: Send + Sync
-// where
-//     T: Serialize + DeserializeOwned + Send + Sync + 'static,
-// {
-//     async fn get(&self, key: &CacheKey) -> Result<Option<T>, CacheError>;
-//     async fn set(&self, key: &CacheKey, value: &T, ttl: Option<u64>) -> Result<(), CacheError>;
-//     async fn get_or_set<F>(&self, key: &CacheKey, ttl: Option<u64>, factory: F) -> Result<T, CacheError>
-//     where
-//         F: Fn() -> futures::future::BoxFuture<'static, Result<T, CacheError>> + Send + Sync;
-// }
-
-// #[async_trait]
-// pub trait KeyOperations: Send + Sync {
-//     async fn delete(&self, key: &CacheKey) -> Result<(), CacheError>;
-//     async fn exists(&self, key: &CacheKey) -> Result<bool, CacheError>;
-// }
-
-// #[async_trait]
-// pub trait SetOperations: Send + Sync {
-//     async fn sadd(&self, key: &CacheKey, value: &str) -> Result<(), CacheError>;
-//     async fn smembers(&self, key: &CacheKey) -> Result<Vec<String>, CacheError>;
-//     async fn srem(&self, key: &CacheKey, value: &str) -> Result<(), CacheError>;
-// }
-
-// // Enum to wrap different cache implementations
-// pub enum Cache {
-//     Redis(RedisCache),
-//     Memory(MemoryCache),
-// }
-
-// // Redis-based cache implementation
-// pub struct RedisCache {
-//     client: Client,
-//     config: CacheConfig,
-//     connection: RwLock<Option<redis::aio::Connection>>,
-// }
-
-// impl RedisCache {
-//     pub async fn new(config: CacheConfig) -> Result<Self, CacheError> {
-//         let client = Client::open(config.redis_url.clone())
-//             .map_err(|e| CacheError::ConnectionError(e.to_string()))?;
-
-//         let instance = Self {
-//             client,
-//             config,
-//             connection: RwLock::new(None),
-//         };
-
-//         instance.connect().await?;
-//         Ok(instance)
-//     }
-
-//     async fn connect(&self) -> Result<(), CacheError> {
-//         let mut conn = self.connection.write().await;
-//         if conn.is_none() {
-//             *conn = Some(
-//                 self.client
-//                     .get_async_connection()
-//                     .await
-//                     .map_err(|e| CacheError::ConnectionError(e.to_string()))?,
-//             );
-//         }
-//         Ok(())
-//     }
-
-//     async fn get_connection(&self) -> Result<redis::aio::Connection, CacheError> {
-//         self.client
-//             .get_async_connection()
-//             .await
-//             .map_err(|e| CacheError::ConnectionError(e.to_string()))
-//     }
-// }
-
-// // -------- Redis impls --------
-
-// #[async_trait]
-// impl<T> CacheOperations<T> for RedisCache
-// where
-//     T: Serialize + DeserializeOwned + Send + Sync + 'static,
-// {
-//     async fn get(&self, key: &CacheKey) -> Result<Option<T>, CacheError> {
-//         if !self.config.enabled {
-//             return Err(CacheError::CacheDisabled);
-//         }
-
-//         let key_str = key.to_string();
-//         let mut conn = self.get_connection().await?;
-
-//         let data: Option<String> = redis::cmd("GET")
-//             .arg(&key_str)
-//             .query_async(&mut conn)
-//             .await
-//             .map_err(|e| CacheError::OperationError(e.to_string()))?;
-
-//         match data {
-//             Some(json) => {
-//                 let value: T = serde_json::from_str(&json)
-//                     .map_err(|e| CacheError::SerializationError(e.to_string()))?;
-//                 Ok(Some(value))
-//             }
-//             None => Ok(None),
-//         }
-//     }
-
-//     async fn set(&self, key: &CacheKey, value: &T, ttl: Option<u64>) -> Result<(), CacheError> {
-//         if !self.config.enabled {
-//             return Err(CacheError::CacheDisabled);
-//         }
-
-//         let key_str = key.to_string();
-//         let json = serde_json::to_string(value)
-//             .map_err(|e| CacheError::SerializationError(e.to_string()))?;
-
-//         let mut conn = self.get_connection().await?;
-//         let ttl = ttl.unwrap_or(self.config.default_ttl_seconds);
-
-//         if ttl > 0 {
-//             let _: () = redis::cmd("SET")
-//                 .arg(&key_str)
-//                 .arg(json)
-//                 .arg("EX")
-//                 .arg(ttl)
-//                 .query_async(&mut conn)
-//                 .await
-//                 .map_err(|e| CacheError::OperationError(e.to_string()))?;
-//         } else {
-//             let _: () = redis::cmd("SET")
-//                 .arg(&key_str)
-//                 .arg(json)
-//                 .query_async(&mut conn)
-//                 .await
-//                 .map_err(|e| CacheError::OperationError(e.to_string()))?;
-//         }
-
-//         Ok(())
-//     }
-
-//     async fn get_or_set<F>(&self, key: &CacheKey, ttl: Option<u64>, factory: F) -> Result<T, CacheError>
-//     where
-//         F: Fn() -> futures::future::BoxFuture<'static, Result<T, CacheError>> + Send + Sync,
-//     {
-//         if let Some(cached) = self.get(key).await? {
-//             tracing::debug!("Cache hit for key: {}", key.to_string());
-//             return Ok(cached);
-//         }
-
-//         tracing::debug!("Cache miss for key: {}, executing factory", key.to_string());
-//         let value = factory().await?;
-//         self.set(key, &value, ttl).await?;
-//         Ok(value)
-//     }
-// }
-
-// #[async_trait]
-// impl KeyOperations for RedisCache {
-//     async fn delete(&self, key: &CacheKey) -> Result<(), CacheError> {
-//         if !self.config.enabled {
-//             return Err(CacheError::CacheDisabled);
-//         }
-
-//         let key_str = key.to_string();
-//         let mut conn = self.get_connection().await?;
-
-//         let _: () = redis::cmd("DEL")
-//             .arg(&key_str)
-//             .query_async(&mut conn)
-//             .await
-//             .map_err(|e| CacheError::OperationError(e.to_string()))?;
-
-//         Ok(())
-//     }
-
-//     async fn exists(&self, key: &CacheKey) -> Result<bool, CacheError> {
-//         if !self.config.enabled {
-//             return Err(CacheError::CacheDisabled);
-//         }
-
-//         let key_str = key.to_string();
-//         let mut conn = self.get_connection().await?;
-
-//         let exists: bool = redis::cmd("EXISTS")
-//             .arg(&key_str)
-//             .query_async(&mut conn)
-//             .await
-//             .map_err(|e| CacheError::OperationError(e.to_string()))?;
-
-//         Ok(exists)
-//     }
-// }
-
-// #[async_trait]
-// impl SetOperations for RedisCache {
-//     async fn sadd(&self, key: &CacheKey, value: &str) -> Result<(), CacheError> {
-//         let mut conn = self.get_connection().await?;
-//         let key_str = key.to_string();
-//         let _: () = redis::cmd("SADD")
-//             .arg(&key_str)
-//             .arg(value)
-//             .query_async(&mut conn)
-//             .await
-//             .map_err(|e| CacheError::OperationError(e.to_string()))?;
-//         Ok(())
-//     }
-
-//     async fn smembers(&self, key: &CacheKey) -> Result<Vec<String>, CacheError> {
-//         let mut conn = self.get_connection().await?;
-//         let key_str = key.to_string();
-//         let members: Vec<String> = redis::cmd("SMEMBERS")
-//             .arg(&key_str)
-//             .query_async(&mut conn)
-//             .await
-//             .map_err(|e| CacheError::OperationError(e.to_string()))?;
-//         Ok(members)
-//     }
-
-//     async fn srem(&self, key: &CacheKey, value: &str) -> Result<(), CacheError> {
-//         let mut conn = self.get_connection().await?;
-//         let key_str = key.to_string();
-//         let _: () = redis::cmd("SREM")
-//             .arg(&key_str)
-//             .arg(value)
-//             .query_async(&mut conn)
-//             .await
-//             .map_err(|e| CacheError::OperationError(e.to_string()))?;
-//         Ok(())
-//     }
-// }
-
-// // Memory cache for development/testing
-// pub struct MemoryCache {
-//     store: RwLock<std::collections::HashMap<String, (String, Option<DateTime<Utc>>)>>,
-//     config: CacheConfig,
-// }
-
-// impl MemoryCache {
-//     pub fn new(config: CacheConfig) -> Self {
-//         Self {
-//             store: RwLock::new(std::collections::HashMap::new()),
-//             config,
-//         }
-//     }
-
-//     fn is_expired(&self, expires_at: Option<DateTime<Utc>>) -> bool {
-//         match expires_at {
-//             Some(expiry) => Utc::now() > expiry,
-//             None => false,
-//         }
-//     }
-// }
-
-// // -------- Memory impls --------
-
-// #[async_trait]
-// impl<T> CacheOperations<T> for MemoryCache
-// where
-//     T: Serialize + DeserializeOwned + Send + Sync + 'static,
-// {
-//     async fn get(&self, key: &CacheKey) -> Result<Option<T>, CacheError> {
-//         if !self.config.enabled {
-//             return Err(CacheError::CacheDisabled);
-//         }
-
-//         let key_str = key.to_string();
-//         let store = self.store.read().await;
-
-//         if let Some((json, expiry)) = store.get(&key_str) {
-//             if self.is_expired(*expiry) {
-//                 return Ok(None);
-//             }
-
-//             let value: T = serde_json::from_str(json)
-//                 .map_err(|e| CacheError::SerializationError(e.to_string()))?;
-//             Ok(Some(value))
-//         } else {
-//             Ok(None)
-//         }
-//     }
-
-//     async fn set(&self, key: &CacheKey, value: &T, ttl: Option<u64>) -> Result<(), CacheError> {
-//         if !self.config.enabled {
-//             return Err(CacheError::CacheDisabled);
-//         }
-
-//         let key_str = key.to_string();
-//         let json = serde_json::to_string(value)
-//             .map_err(|e| CacheError::SerializationError(e.to_string()))?;
-
-//         let expires_at = ttl.map(|seconds| Utc::now() + chrono::Duration::seconds(seconds as i64));
-
-//         let mut store = self.store.write().await;
-//         store.insert(key_str, (json, expires_at));
-
-//         Ok(())
-//     }
-
-//     async fn get_or_set<F>(&self, key: &CacheKey, ttl: Option<u64>, factory: F) -> Result<T, CacheError>
-//     where
-//         F: Fn() -> futures::future::BoxFuture<'static, Result<T, CacheError>> + Send + Sync,
-//     {
-//         if let Some(cached) = self.get(key).await? {
-//             return Ok(cached);
-//         }
-
-//         let value = factory().await?;
-//         self.set(key, &value, ttl).await?;
-//         Ok(value)
-//     }
-// }
-
-// #[async_trait]
-// impl KeyOperations for MemoryCache {
-//     async fn delete(&self, key: &CacheKey) -> Result<(), CacheError> {
-//         if !self.config.enabled {
-//             return Err(CacheError::CacheDisabled);
-//         }
-
-//         let key_str = key.to_string();
-//         let mut store = self.store.write().await;
-//         store.remove(&key_str);
-//         Ok(())
-//     }
-
-//     async fn exists(&self, key: &CacheKey) -> Result<bool, CacheError> {
-//         if !self.config.enabled {
-//             return Err(CacheError::CacheDisabled);
-//         }
-
-//         let key_str = key.to_string();
-//         let store = self.store.read().await;
-
-//         Ok(store.contains_key(&key_str))
-//     }
-// }
-
-// #[async_trait]
-// impl SetOperations for MemoryCache {
-//     async fn sadd(&self, _key: &CacheKey, _value: &str) -> Result<(), CacheError> {
-//         // Not implemented for memory cache
-//         Ok(())
-//     }
-
-//     async fn smembers(&self, _key: &CacheKey) -> Result<Vec<String>, CacheError> {
-//         // Not implemented for memory cache
-//         Ok(vec![])
-//     }
-
-//     async fn srem(&self, _key: &CacheKey, _value: &str) -> Result<(), CacheError> {
-//         // Not implemented for memory cache
-//         Ok(())
-//     }
-// }
-
-// // Error types
-// #[derive(Debug, thiserror::Error)]
-// pub enum CacheError {
-//     #[error("Connection error: {0}")]
-//     ConnectionError(String),
-
-//     #[error("Operation error: {0}")]
-//     OperationError(String),
-
-//     #[error("Serialization error: {0}")]
-//     SerializationError(String),
-
-//     #[error("Cache is disabled")]
-//     CacheDisabled,
-
-//     #[error("Cache miss")]
-//     CacheMiss,
-// }
-
-// // Cache key generators for different resources
-// pub struct CacheKeys;
-
-// impl CacheKeys {
-//     // User cache keys
-//     pub fn user_by_id(user_id: &str) -> CacheKey {
-//         CacheKey::Composite(vec!["user".to_string(), "id".to_string(), user_id.to_string()])
-//     }
-
-//     pub fn user_by_email(email: &str) -> CacheKey {
-//         CacheKey::Composite(vec!["user".to_string(), "email".to_string(), email.to_string()])
-//     }
-
-//     pub fn user_by_phone(phone: &str) -> CacheKey {
-//         CacheKey::Composite(vec!["user".to_string(), "phone".to_string(), phone.to_string()])
-//     }
-
-//     pub fn user_credentials(user_id: &str) -> CacheKey {
-//         CacheKey::Composite(vec![
-//             "user".to_string(),
-//             "credentials".to_string(),
-//             user_id.to_string(),
-//         ])
-//     }
-
-//     pub fn all_users() -> CacheKey {
-//         CacheKey::Simple("users:all".to_string())
-//     }
-
-//     // Driver cache keys
-//     pub fn driver_by_id(driver_id: &str) -> CacheKey {
-//         CacheKey::Composite(vec!["driver".to_string(), "id".to_string(), driver_id.to_string()])
-//     }
-
-//     pub fn driver_by_user_id(user_id: &str) -> CacheKey {
-//         CacheKey::Composite(vec![
-//             "driver".to_string(),
-//             "user_id".to_string(),
-//             user_id.to_string(),
-//         ])
-//     }
-
-//     pub fn online_drivers() -> CacheKey {
-//         CacheKey::Simple("drivers:online".to_string())
-//     }
-
-//     // Job cache keys
-//     pub fn job_by_id(job_id: &str) -> CacheKey {
-//         CacheKey::Composite(vec!["job".to_string(), "id".to_string(), job_id.to_string()])
-//     }
-
-//     pub fn jobs_by_customer(customer_id: &str) -> CacheKey {
-//         CacheKey::Composite(vec![
-//             "jobs".to_string(),
-//             "customer".to_string(),
-//             customer_id.to_string(),
-//         ])
-//     }
-
-//     pub fn jobs_by_driver(driver_id: &str) -> CacheKey {
-//         CacheKey::Composite(vec![
-//             "jobs".to_string(),
-//             "driver".to_string(),
-//             driver_id.to_string(),
-//         ])
-//     }
-
-//     pub fn active_jobs() -> CacheKey {
-//         CacheKey::Simple("jobs:active".to_string())
-//     }
-
-//     // Location cache keys
-//     pub fn driver_location(driver_id: &str) -> CacheKey {
-//         CacheKey::Composite(vec![
-//             "location".to_string(),
-//             "driver".to_string(),
-//             driver_id.to_string(),
-//         ])
-//     }
-
-//     // Pattern keys for bulk operations
-//     pub fn all_users_pattern() -> CacheKey {
-//         CacheKey::Pattern("user:*".to_string())
-//     }
-
-//     pub fn all_drivers_pattern() -> CacheKey {
-//         CacheKey::Pattern("driver:*".to_string())
-//     }
-// }
-
-// // Cache service wrapper
-// pub struct CacheService {
-//     user_cache: Arc<Cache>,
-//     job_cache: Arc<Cache>,
-//     config: CacheConfig,
-// }
-
-// impl CacheService {
-//     pub async fn new(redis_url: &str) -> Result<Self, CacheError> {
-//         let config = CacheConfig {
-//             redis_url: redis_url.to_string(),
-//             ..Default::default()
-//         };
-
-//         Ok(Self {
-//             user_cache: Arc::new(Cache::Redis(RedisCache::new(config.clone()).await?)),
-//             job_cache: Arc::new(Cache::Redis(RedisCache::new(config.clone()).await?)),
-//             config,
-//         })
-//     }
-
-//     pub fn new_memory(config: CacheConfig) -> Self {
-//         Self {
-//             user_cache: Arc::new(Cache::Memory(MemoryCache::new(config.clone()))),
-//             job_cache: Arc::new(Cache::Memory(MemoryCache::new(config.clone()))),
-//             config,
-//         }
-//     }
-
-//     pub async fn get_user(&self, key: &CacheKey) -> Result<Option<User>, AppError> {
-//         self.user_cache.get(key).await.map_err(|e| e.into())
-//     }
-
-//     pub async fn set_user(&self, key: &CacheKey, value: &User, ttl: Option<u64>) -> Result<(), AppError> {
-//         self.user_cache.set(key, value, ttl).await.map_err(|e| e.into())
-//     }
-
-//     pub async fn get_job(&self, key: &CacheKey) -> Result<Option<Job>, AppError> {
-//         self.job_cache.get(key).await.map_err(|e| e.into())
-//     }
-
-//     pub async fn set_job(&self, key: &CacheKey, value: &Job, ttl: Option<u64>) -> Result<(), AppError> {
-//         self.job_cache.set(key, value, ttl).await.map_err(|e| e.into())
-//     }
-
-//     // User caching methods
-//     pub async fn cache_user(&self, user: &User) -> Result<(), AppError> {
-//         let key = CacheKeys::user_by_id(&user.id);
-//         self.set_user(&key, user, Some(86400 * 7)).await?; // 7 days TTL
-
-//         // Update indices
-//         self.cache_user_by_phone(&user.phone_number, &user.id).await?;
-//         self.cache_user_by_email(&user.email, &user.id).await?;
-
-//         Ok(())
-//     }
-
-//     pub async fn get_user_credentials(&self, _user_id: &str) -> Result<Option<String>, AppError> {
-//         unimplemented!()
-//     }
-
-//     pub async fn cache_user_credentials(&self, _user_id: &str, _hashed_password: &str) -> Result<(), AppError> {
-//         unimplemented!()
-//     }
-
-//     pub async fn cache_user_by_email(&self, email: &str, user_id: &str) -> Result<(), AppError> {
-//         let key = CacheKeys::user_by_email(email);
-//         self.user_cache
-//             .set(&key, &user_id.to_string(), Some(86400 * 7))
-//             .await
-//             .map_err(|e| e.into())?;
-//         Ok(())
-//     }
-
-//     pub async fn get_user_id_by_email(&self, email: &str) -> Result<Option<String>, AppError> {
-//         let key = CacheKeys::user_by_email(email);
-//         self.user_cache.get(&key).await.map_err(|e| e.into())
-//     }
-
-//     pub async fn cache_user_by_phone(&self, phone: &str, user_id: &str) -> Result<(), AppError> {
-//         let key = CacheKeys::user_by_phone(phone);
-//         self.user_cache
-//             .set(&key, &user_id.to_string(), Some(86400 * 7))
-//             .await
-//             .map_err(|e| e.into())?;
-//         Ok(())
-//     }
-
-//     pub async fn get_user_id_by_phone(&self, phone: &str) -> Result<Option<String>, AppError> {
-//         let key = CacheKeys::user_by_phone(phone);
-//         self.user_cache.get(&key).await.map_err(|e| e.into())
-//     }
-
-//     pub async fn cache_user_index(&self, user: &User) -> Result<(), AppError> {
-//         // Add to all users set
-//         let all_users_key = CacheKeys::all_users();
-//         self.user_cache
-//             .sadd(&all_users_key, &user.id)
-//             .await
-//             .map_err(|e| e.into())?;
-//         Ok(())
-//     }
-
-//     // Job caching methods
-//     pub async fn cache_job(&self, job: &Job) -> Result<(), AppError> {
-//         let key = CacheKeys::job_by_id(&job.id);
-//         self.set_job(&key, job, Some(3600)).await?; // 1 hour TTL
-//         Ok(())
-//     }
-
-//     pub async fn get_customer_jobs(&self, customer_id: &str) -> Result<Vec<String>, AppError> {
-//         let key = CacheKeys::jobs_by_customer(customer_id);
-//         self.job_cache.smembers(&key).await.map_err(|e| e.into())
-//     }
-
-//     pub async fn cache_customer_job(&self, customer_id: &str, job_id: &str) -> Result<(), AppError> {
-//         let key = CacheKeys::jobs_by_customer(customer_id);
-//         self.job_cache.sadd(&key, job_id).await.map_err(|e| e.into())
-//     }
-
-//     pub async fn get_driver_jobs(&self, driver_id: &str) -> Result<Vec<String>, AppError> {
-//         let key = CacheKeys::jobs_by_driver(driver_id);
-//         self.job_cache.smembers(&key).await.map_err(|e| e.into())
-//     }
-
-//     pub async fn remove_driver_job(&self, driver_id: &str, job_id: &str) -> Result<(), AppError> {
-//         let key = CacheKeys::jobs_by_driver(driver_id);
-//         self.job_cache.srem(&key, job_id).await.map_err(|e| e.into())
-//     }
-
-//     pub async fn cache_driver_job(&self, driver_id: &str, job_id: &str) -> Result<(), AppError> {
-//         let key = CacheKeys::jobs_by_driver(driver_id);
-//         self.job_cache.sadd(&key, job_id).await.map_err(|e| e.into())
-//     }
-
-//     // Bulk operations / invalidation
-//     pub async fn invalidate_user(&self, user_id: &str) -> Result<(), AppError> {
-//         let key = CacheKeys::user_by_id(user_id);
-//         self.user_cache.delete(&key).await?;
-//         Ok(())
-//     }
-// }
-
-// // Health check
-// impl CacheService {
-//     pub async fn health_check(&self) -> Result<bool, AppError> {
-//         unimplemented!()
-//     }
-// }
-
-// impl From<CacheError> for AppError {
-//     fn from(error: CacheError) -> Self {
-//         AppError::ResourceExhausted(error.to_string())
-//     }
-// }
-
-// // ------------------------------
-// // Enum delegations (Cache)
-// // ------------------------------
-
-// #[async_trait]
-// impl<T> CacheOperations<T> for Cache
-// where
-//     T: Serialize + DeserializeOwned + Send + Sync + 'static,
-// {
-//     async fn get(&self, key: &CacheKey) -> Result<Option<T>, CacheError> {
-//         match self {
-//             Cache::Redis(cache) => cache.get(key).await,
-//             Cache::Memory(cache) => cache.get(key).await,
-//         }
-//     }
-
-//     async fn set(&self, key: &CacheKey, value: &T, ttl: Option<u64>) -> Result<(), CacheError> {
-//         match self {
-//             Cache::Redis(cache) => cache.set(key, value, ttl).await,
-//             Cache::Memory(cache) => cache.set(key, value, ttl).await,
-//         }
-//     }
-
-//     async fn get_or_set<F>(&self, key: &CacheKey, ttl: Option<u64>, factory: F) -> Result<T, CacheError>
-//     where
-//         F: Fn() -> futures::future::BoxFuture<'static, Result<T, CacheError>> + Send + Sync,
-//     {
-//         match self {
-//             Cache::Redis(cache) => cache.get_or_set(key, ttl, factory).await,
-//             Cache::Memory(cache) => cache.get_or_set(key, ttl, factory).await,
-//         }
-//     }
-// }
-
-// #[async_trait]
-// impl KeyOperations for Cache {
-//     async fn delete(&self, key: &CacheKey) -> Result<(), CacheError> {
-//         match self {
-//             Cache::Redis(cache) => cache.delete(key).await,
-//             Cache::Memory(cache) => cache.delete(key).await,
-//         }
-//     }
-
-//     async fn exists(&self, key: &CacheKey) -> Result<bool, CacheError> {
-//         match self {
-//             Cache::Redis(cache) => cache.exists(key).await,
-//             Cache::Memory(cache) => cache.exists(key).await,
-//         }
-//     }
-// }
-
-// #[async_trait]
-// impl SetOperations for Cache {
-//     async fn sadd(&self, key: &CacheKey, value: &str) -> Result<(), CacheError> {
-//         match self {
-//             Cache::Redis(cache) => cache.sadd(key, value).await,
-//             Cache::Memory(cache) => cache.sadd(key, value).await,
-//         }
-//     }
-
-//     async fn smembers(&self, key: &CacheKey) -> Result<Vec<String>, CacheError> {
-//         match self {
-//             Cache::Redis(cache) => cache.smembers(key).await,
-//             Cache::Memory(cache) => cache.smembers(key).await,
-//         }
-//     }
-
-//     async fn srem(&self, key: &CacheKey, value: &str) -> Result<(), CacheError> {
-//         match self {
-//             Cache::Redis(cache) => cache.srem(key, value).await,
-//             Cache::Memory(cache) => cache.srem(key, value).await,
-//         }
-//     }
-// }
-
-// // ------------------------------
-// // get_or_set helper in service
-// // ------------------------------
-
-// impl CacheService {
-//     // Get or set pattern with automatic caching
-//     pub async fn get_user_or_fetch<F>(&self, user_id: &str, fetch_fn: F) -> Result<User, AppError>
-//     where
-//         F: Fn() -> futures::future::BoxFuture<'static, Result<User, AppError>> + Send + Sync,
-//     {
-//         let key = CacheKeys::user_by_id(user_id);
-//         self.user_cache
-//             .get_or_set(&key, Some(3600), || {
-//                 Box::pin(async move {
-//                     fetch_fn()
-//                         .await
-//                         .map_err(|e| CacheError::OperationError(e.to_string()))
-//                 })
-//             })
-//             .await
-//             .map_err(|e| e.into())
-//     }
-// }
+// src/services/cache_service.rs
+use async_trait::async_trait;
+use redis::{Client};
+use serde::{de::DeserializeOwned, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use chrono::{DateTime, NaiveDate, Utc};
+use tracing;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+
+use crate::models::{admin::OperationsDashboard, alert::AlertKind, business::StaffMember, calendar::CalendarPeriod, chaos::FaultLayer, canary::{CanaryParticipants, CanaryRun}, broadcast::{Broadcast, TopicSubscriptions}, commission::CommissionConfig, consumer::{ConsumerCheckpoint, ParkedEvent}, saga::{SagaKind, SagaState, SagaStatus}, exchange_rate::ExchangeRates, messages::{DeferredNotification, DigestItem, NotificationTemplate}, tax::TaxSchedule, ids::{DriverId, JobId, UserId}, api_key::ApiKey, region::DEFAULT_REGION_ID, tenant::{Tenant, DEFAULT_TENANT_ID}, demand::{DemandForecast, DemandPoint}, dispatch::{DispatchAuditEntry, DispatchSettings}, driver::{DispatchOutcome, Driver, DriverReliability}, presence::{PresenceEntry, PresenceKind}, device::UserDevice, events_export::ExportedEvent, otp::StoredOtp, user::{Address, User, UserCredit}, job::{Job, JobBatch, JobCursor, JobEvent, JobPool, LocationUpdate, RouteSegment, TripShare}, ledger::{LedgerAccount, LedgerEntry}, reconciliation::Discrepancy, quota::QuotaMetric, retention::{DataClass, LegalHold, RetentionSettings}, status_feed::FeedSettings, voice::ArrivalCall, dispute::DisputeCase, incident::Incident, odometer::VehicleOdometer, risk::{QuarantinedLocation, RiskFlag}, invoice::{BillingAccount, Invoice, InvoiceLine}, zone::ServiceZone};
+use crate::errors::SparrowError as AppError;
+use crate::services::{cache_codec::{self, CacheFormat}, chaos, database::{NoRepository, Repository}, region::current_region_id, tenant_service::current_tenant_id};
+use crate::utils::{field_crypto::{FieldCipher, PiiFields}, geo::haversine_km};
+
+// How long exported events stay in the outbox, and so how far back a replay can go
+pub const EVENTS_OUTBOX_TTL_SECONDS: u64 = 86400 * 7;
+
+// Cache configuration
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    pub default_ttl_seconds: u64,
+    pub redis_url: String,
+    pub enabled: bool,
+    pub format: CacheFormat, // Encoding for new writes; reads accept every format
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            default_ttl_seconds: 300, // 5 minutes
+            redis_url: "redis://127.0.0.1:6379".to_string(),
+            enabled: true,
+            format: CacheFormat::Json,
+        }
+    }
+}
+
+// Cache key strategies
+#[derive(Debug, Clone)]
+pub enum CacheKey {
+    Simple(String),
+    Composite(Vec<String>),
+    Pattern(String),
+    Global(String), // Shared by all tenants and regions - never namespaced
+}
+
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheKey::Simple(key) => f.write_str(key),
+            CacheKey::Composite(parts) => f.write_str(&parts.join(":")),
+            CacheKey::Pattern(pattern) => f.write_str(pattern),
+            CacheKey::Global(key) => f.write_str(key),
+        }
+    }
+}
+
+impl CacheKey {
+    // Namespace a key under a tenant; the default tenant keeps un-prefixed keys
+    pub fn scoped(&self, tenant_id: &str) -> CacheKey {
+        match self {
+            CacheKey::Global(_) => self.clone(),
+            _ if tenant_id == DEFAULT_TENANT_ID => self.clone(),
+            CacheKey::Pattern(pattern) => CacheKey::Pattern(format!("t:{}:{}", tenant_id, pattern)),
+            _ => CacheKey::Simple(format!("t:{}:{}", tenant_id, self)),
+        }
+    }
+
+    // Namespace a key under a region, outside any tenant prefix; the default region keeps un-prefixed keys
+    pub fn regional(&self, region_id: &str) -> CacheKey {
+        match self {
+            CacheKey::Global(_) => self.clone(),
+            _ if region_id == DEFAULT_REGION_ID => self.clone(),
+            CacheKey::Pattern(pattern) => CacheKey::Pattern(format!("r:{}:{}", region_id, pattern)),
+            _ => CacheKey::Simple(format!("r:{}:{}", region_id, self)),
+        }
+    }
+}
+
+// ------------------------------
+// Traits (split to avoid E0283)
+// ------------------------------
+
+#[async_trait]
+pub trait CacheOperations<T>: Send + Sync
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    async fn get(&self, key: &CacheKey) -> Result<Option<T>, CacheError>;
+    async fn set(&self, key: &CacheKey, value: &T, ttl: Option<u64>) -> Result<(), CacheError>;
+    async fn get_or_set<F>(&self, key: &CacheKey, ttl: Option<u64>, factory: F) -> Result<T, CacheError>
+    where
+        F: Fn() -> futures::future::BoxFuture<'static, Result<T, CacheError>> + Send + Sync;
+}
+
+#[async_trait]
+pub trait KeyOperations: Send + Sync {
+    async fn delete(&self, key: &CacheKey) -> Result<(), CacheError>;
+    async fn exists(&self, key: &CacheKey) -> Result<bool, CacheError>;
+}
+
+#[async_trait]
+pub trait SetOperations: Send + Sync {
+    async fn sadd(&self, key: &CacheKey, value: &str) -> Result<(), CacheError>;
+    async fn smembers(&self, key: &CacheKey) -> Result<Vec<String>, CacheError>;
+    async fn srem(&self, key: &CacheKey, value: &str) -> Result<(), CacheError>;
+}
+
+#[async_trait]
+pub trait GeoOperations: Send + Sync {
+    // members are (member, longitude, latitude) - same order as GEOADD
+    async fn geoadd(&self, key: &CacheKey, members: &[(String, f64, f64)]) -> Result<(), CacheError>;
+    async fn geosearch(&self, key: &CacheKey, longitude: f64, latitude: f64, radius_km: f64, limit: usize) -> Result<Vec<String>, CacheError>;
+    async fn georem(&self, key: &CacheKey, member: &str) -> Result<(), CacheError>;
+}
+
+#[async_trait]
+pub trait ListOperations: Send + Sync {
+    async fn rpush(&self, key: &CacheKey, value: &str, ttl: Option<u64>) -> Result<(), CacheError>;
+    async fn lrange(&self, key: &CacheKey, start: isize, stop: isize) -> Result<Vec<String>, CacheError>;
+}
+
+#[async_trait]
+pub trait SortedSetOperations: Send + Sync {
+    async fn zadd(&self, key: &CacheKey, member: &str, score: f64) -> Result<(), CacheError>;
+    async fn zrem(&self, key: &CacheKey, member: &str) -> Result<(), CacheError>;
+    // Highest score first from `max` down, ties in reverse member order, as ZREVRANGEBYSCORE
+    async fn zrevrange_by_score(&self, key: &CacheKey, max: f64, offset: usize, count: usize) -> Result<Vec<(String, f64)>, CacheError>;
+}
+
+#[async_trait]
+pub trait SwapOperations: Send + Sync {
+    // The value as stored, to hand back to `swap` as what it expects to replace
+    async fn get_raw(&self, key: &CacheKey) -> Result<Option<Vec<u8>>, CacheError>;
+    // Write `value` only if the key still holds `expected`; false if someone wrote in between
+    async fn swap<T: Serialize + Send + Sync>(&self, key: &CacheKey, expected: &[u8], value: &T, ttl: Option<u64>) -> Result<bool, CacheError>;
+}
+
+#[async_trait]
+pub trait CounterOperations: Send + Sync {
+    // Increment and return the new value; the expiry is set when the counter is created
+    async fn incr(&self, key: &CacheKey, ttl: u64) -> Result<i64, CacheError>;
+}
+
+// Enum to wrap different cache implementations. Only a handful are ever built, so the
+// memory cache's size doesn't matter.
+#[allow(clippy::large_enum_variant)]
+pub enum Cache {
+    Redis(RedisCache),
+    Memory(MemoryCache),
+}
+
+// Redis-based cache implementation
+pub struct RedisCache {
+    client: Client,
+    config: CacheConfig,
+    connection: RwLock<Option<redis::aio::Connection>>,
+}
+
+impl RedisCache {
+    pub async fn new(config: CacheConfig) -> Result<Self, CacheError> {
+        let client = Client::open(config.redis_url.clone())?;
+
+        let instance = Self {
+            client,
+            config,
+            connection: RwLock::new(None),
+        };
+
+        instance.connect().await?;
+        Ok(instance)
+    }
+
+    async fn connect(&self) -> Result<(), CacheError> {
+        let mut conn = self.connection.write().await;
+        if conn.is_none() {
+            *conn = Some(self.client.get_async_connection().await?);
+        }
+        Ok(())
+    }
+
+    async fn get_connection(&self) -> Result<redis::aio::Connection, CacheError> {
+        Ok(self.client.get_async_connection().await?)
+    }
+}
+
+// -------- Redis impls --------
+
+#[async_trait]
+impl<T> CacheOperations<T> for RedisCache
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    async fn get(&self, key: &CacheKey) -> Result<Option<T>, CacheError> {
+        if !self.config.enabled {
+            return Err(CacheError::CacheDisabled);
+        }
+
+        let key_str = key.to_string();
+        let mut conn = self.get_connection().await?;
+
+        let data: Option<Vec<u8>> = redis::cmd("GET")
+            .arg(&key_str)
+            .query_async(&mut conn)
+            .await?;
+
+        match data {
+            Some(bytes) => Ok(Some(cache_codec::decode(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn set(&self, key: &CacheKey, value: &T, ttl: Option<u64>) -> Result<(), CacheError> {
+        if !self.config.enabled {
+            return Err(CacheError::CacheDisabled);
+        }
+
+        let key_str = key.to_string();
+        let bytes = cache_codec::encode(self.config.format, value)?;
+
+        let mut conn = self.get_connection().await?;
+        let ttl = ttl.unwrap_or(self.config.default_ttl_seconds);
+
+        if ttl > 0 {
+            let _: () = redis::cmd("SET")
+                .arg(&key_str)
+                .arg(&bytes)
+                .arg("EX")
+                .arg(ttl)
+                .query_async(&mut conn)
+                .await?;
+        } else {
+            let _: () = redis::cmd("SET")
+                .arg(&key_str)
+                .arg(&bytes)
+                .query_async(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_or_set<F>(&self, key: &CacheKey, ttl: Option<u64>, factory: F) -> Result<T, CacheError>
+    where
+        F: Fn() -> futures::future::BoxFuture<'static, Result<T, CacheError>> + Send + Sync,
+    {
+        if let Some(cached) = self.get(key).await? {
+            tracing::debug!("Cache hit for key: {}", key.to_string());
+            return Ok(cached);
+        }
+
+        tracing::debug!("Cache miss for key: {}, executing factory", key.to_string());
+        let value = factory().await?;
+        self.set(key, &value, ttl).await?;
+        Ok(value)
+    }
+}
+
+#[async_trait]
+impl KeyOperations for RedisCache {
+    async fn delete(&self, key: &CacheKey) -> Result<(), CacheError> {
+        if !self.config.enabled {
+            return Err(CacheError::CacheDisabled);
+        }
+
+        let key_str = key.to_string();
+        let mut conn = self.get_connection().await?;
+
+        let _: () = redis::cmd("DEL")
+            .arg(&key_str)
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn exists(&self, key: &CacheKey) -> Result<bool, CacheError> {
+        if !self.config.enabled {
+            return Err(CacheError::CacheDisabled);
+        }
+
+        let key_str = key.to_string();
+        let mut conn = self.get_connection().await?;
+
+        let exists: bool = redis::cmd("EXISTS")
+            .arg(&key_str)
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(exists)
+    }
+}
+
+#[async_trait]
+impl SetOperations for RedisCache {
+    async fn sadd(&self, key: &CacheKey, value: &str) -> Result<(), CacheError> {
+        let mut conn = self.get_connection().await?;
+        let key_str = key.to_string();
+        let _: () = redis::cmd("SADD")
+            .arg(&key_str)
+            .arg(value)
+            .query_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn smembers(&self, key: &CacheKey) -> Result<Vec<String>, CacheError> {
+        let mut conn = self.get_connection().await?;
+        let key_str = key.to_string();
+        let members: Vec<String> = redis::cmd("SMEMBERS")
+            .arg(&key_str)
+            .query_async(&mut conn)
+            .await?;
+        Ok(members)
+    }
+
+    async fn srem(&self, key: &CacheKey, value: &str) -> Result<(), CacheError> {
+        let mut conn = self.get_connection().await?;
+        let key_str = key.to_string();
+        let _: () = redis::cmd("SREM")
+            .arg(&key_str)
+            .arg(value)
+            .query_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl GeoOperations for RedisCache {
+    async fn geoadd(&self, key: &CacheKey, members: &[(String, f64, f64)]) -> Result<(), CacheError> {
+        if members.is_empty() {
+            return Ok(());
+        }
+
+        let mut conn = self.get_connection().await?;
+        let key_str = key.to_string();
+
+        // One GEOADD for the whole batch instead of a round trip per member
+        let mut cmd = redis::cmd("GEOADD");
+        cmd.arg(&key_str);
+        for (member, longitude, latitude) in members {
+            cmd.arg(*longitude).arg(*latitude).arg(member);
+        }
+
+        let _: () = cmd
+            .query_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn geosearch(&self, key: &CacheKey, longitude: f64, latitude: f64, radius_km: f64, limit: usize) -> Result<Vec<String>, CacheError> {
+        let mut conn = self.get_connection().await?;
+        let key_str = key.to_string();
+        let members: Vec<String> = redis::cmd("GEOSEARCH")
+            .arg(&key_str)
+            .arg("FROMLONLAT")
+            .arg(longitude)
+            .arg(latitude)
+            .arg("BYRADIUS")
+            .arg(radius_km)
+            .arg("km")
+            .arg("ASC")
+            .arg("COUNT")
+            .arg(limit)
+            .query_async(&mut conn)
+            .await?;
+        Ok(members)
+    }
+
+    async fn georem(&self, key: &CacheKey, member: &str) -> Result<(), CacheError> {
+        let mut conn = self.get_connection().await?;
+        let key_str = key.to_string();
+        // GEO sets are plain sorted sets underneath
+        let _: () = redis::cmd("ZREM")
+            .arg(&key_str)
+            .arg(member)
+            .query_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ListOperations for RedisCache {
+    async fn rpush(&self, key: &CacheKey, value: &str, ttl: Option<u64>) -> Result<(), CacheError> {
+        let mut conn = self.get_connection().await?;
+        let key_str = key.to_string();
+        let _: () = redis::cmd("RPUSH")
+            .arg(&key_str)
+            .arg(value)
+            .query_async(&mut conn)
+            .await?;
+
+        // Refresh the expiry so the list lives as long as it keeps growing
+        if let Some(ttl) = ttl {
+            let _: () = redis::cmd("EXPIRE")
+                .arg(&key_str)
+                .arg(ttl)
+                .query_async(&mut conn)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn lrange(&self, key: &CacheKey, start: isize, stop: isize) -> Result<Vec<String>, CacheError> {
+        let mut conn = self.get_connection().await?;
+        let key_str = key.to_string();
+        let values: Vec<String> = redis::cmd("LRANGE")
+            .arg(&key_str)
+            .arg(start)
+            .arg(stop)
+            .query_async(&mut conn)
+            .await?;
+        Ok(values)
+    }
+}
+
+#[async_trait]
+impl SortedSetOperations for RedisCache {
+    async fn zadd(&self, key: &CacheKey, member: &str, score: f64) -> Result<(), CacheError> {
+        let mut conn = self.get_connection().await?;
+        let key_str = key.to_string();
+        let _: () = redis::cmd("ZADD")
+            .arg(&key_str)
+            .arg(score)
+            .arg(member)
+            .query_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn zrem(&self, key: &CacheKey, member: &str) -> Result<(), CacheError> {
+        let mut conn = self.get_connection().await?;
+        let key_str = key.to_string();
+        let _: () = redis::cmd("ZREM")
+            .arg(&key_str)
+            .arg(member)
+            .query_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn zrevrange_by_score(&self, key: &CacheKey, max: f64, offset: usize, count: usize) -> Result<Vec<(String, f64)>, CacheError> {
+        let mut conn = self.get_connection().await?;
+        let key_str = key.to_string();
+        let max = if max == f64::INFINITY { "+inf".to_string() } else { max.to_string() };
+        let members: Vec<(String, f64)> = redis::cmd("ZREVRANGEBYSCORE")
+            .arg(&key_str)
+            .arg(max)
+            .arg("-inf")
+            .arg("WITHSCORES")
+            .arg("LIMIT")
+            .arg(offset)
+            .arg(count)
+            .query_async(&mut conn)
+            .await?;
+        Ok(members)
+    }
+}
+
+// Compare and set in one step on the server
+const SWAP_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) ~= ARGV[1] then
+    return 0
+end
+if tonumber(ARGV[3]) > 0 then
+    redis.call('SET', KEYS[1], ARGV[2], 'EX', ARGV[3])
+else
+    redis.call('SET', KEYS[1], ARGV[2])
+end
+return 1
+"#;
+
+#[async_trait]
+impl SwapOperations for RedisCache {
+    async fn get_raw(&self, key: &CacheKey) -> Result<Option<Vec<u8>>, CacheError> {
+        if !self.config.enabled {
+            return Err(CacheError::CacheDisabled);
+        }
+
+        let mut conn = self.get_connection().await?;
+        let data: Option<Vec<u8>> = redis::cmd("GET")
+            .arg(key.to_string())
+            .query_async(&mut conn)
+            .await?;
+        Ok(data)
+    }
+
+    async fn swap<T: Serialize + Send + Sync>(&self, key: &CacheKey, expected: &[u8], value: &T, ttl: Option<u64>) -> Result<bool, CacheError> {
+        if !self.config.enabled {
+            return Err(CacheError::CacheDisabled);
+        }
+
+        let bytes = cache_codec::encode(self.config.format, value)?;
+        let ttl = ttl.unwrap_or(self.config.default_ttl_seconds);
+        let mut conn = self.get_connection().await?;
+        let swapped: i64 = redis::cmd("EVAL")
+            .arg(SWAP_SCRIPT)
+            .arg(1)
+            .arg(key.to_string())
+            .arg(expected)
+            .arg(&bytes)
+            .arg(ttl)
+            .query_async(&mut conn)
+            .await?;
+        Ok(swapped == 1)
+    }
+}
+
+#[async_trait]
+impl CounterOperations for RedisCache {
+    async fn incr(&self, key: &CacheKey, ttl: u64) -> Result<i64, CacheError> {
+        let mut conn = self.get_connection().await?;
+        let key_str = key.to_string();
+        let count: i64 = redis::cmd("INCR")
+            .arg(&key_str)
+            .query_async(&mut conn)
+            .await?;
+
+        if count == 1 {
+            let _: () = redis::cmd("EXPIRE")
+                .arg(&key_str)
+                .arg(ttl)
+                .query_async(&mut conn)
+                .await?;
+        }
+        Ok(count)
+    }
+}
+
+// Value plus optional expiry, per key
+type Expiring<T> = RwLock<HashMap<String, (T, Option<DateTime<Utc>>)>>;
+
+// member -> (longitude, latitude)
+type GeoMembers = HashMap<String, (f64, f64)>;
+
+// Memory cache for development/testing
+pub struct MemoryCache {
+    store: Expiring<Vec<u8>>,
+    sets: RwLock<HashMap<String, BTreeSet<String>>>,
+    geo: RwLock<HashMap<String, GeoMembers>>,
+    lists: Expiring<Vec<String>>,
+    sorted: RwLock<HashMap<String, HashMap<String, f64>>>, // member -> score
+    config: CacheConfig,
+}
+
+impl MemoryCache {
+    pub fn new(config: CacheConfig) -> Self {
+        Self {
+            store: RwLock::new(HashMap::new()),
+            sets: RwLock::new(HashMap::new()),
+            geo: RwLock::new(HashMap::new()),
+            lists: RwLock::new(HashMap::new()),
+            sorted: RwLock::new(HashMap::new()),
+            config,
+        }
+    }
+
+    fn check_enabled(&self) -> Result<(), CacheError> {
+        if self.config.enabled {
+            Ok(())
+        } else {
+            Err(CacheError::CacheDisabled)
+        }
+    }
+
+    fn is_expired(&self, expires_at: Option<DateTime<Utc>>) -> bool {
+        match expires_at {
+            Some(expiry) => Utc::now() > expiry,
+            None => false,
+        }
+    }
+}
+
+// -------- Memory impls --------
+
+#[async_trait]
+impl<T> CacheOperations<T> for MemoryCache
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    async fn get(&self, key: &CacheKey) -> Result<Option<T>, CacheError> {
+        if !self.config.enabled {
+            return Err(CacheError::CacheDisabled);
+        }
+
+        let key_str = key.to_string();
+        let store = self.store.read().await;
+
+        if let Some((bytes, expiry)) = store.get(&key_str) {
+            if self.is_expired(*expiry) {
+                return Ok(None);
+            }
+
+            Ok(Some(cache_codec::decode(bytes)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn set(&self, key: &CacheKey, value: &T, ttl: Option<u64>) -> Result<(), CacheError> {
+        if !self.config.enabled {
+            return Err(CacheError::CacheDisabled);
+        }
+
+        let key_str = key.to_string();
+        let bytes = cache_codec::encode(self.config.format, value)?;
+
+        let expires_at = ttl.map(|seconds| Utc::now() + chrono::Duration::seconds(seconds as i64));
+
+        let mut store = self.store.write().await;
+        store.insert(key_str, (bytes, expires_at));
+
+        Ok(())
+    }
+
+    async fn get_or_set<F>(&self, key: &CacheKey, ttl: Option<u64>, factory: F) -> Result<T, CacheError>
+    where
+        F: Fn() -> futures::future::BoxFuture<'static, Result<T, CacheError>> + Send + Sync,
+    {
+        if let Some(cached) = self.get(key).await? {
+            return Ok(cached);
+        }
+
+        let value = factory().await?;
+        self.set(key, &value, ttl).await?;
+        Ok(value)
+    }
+}
+
+#[async_trait]
+impl KeyOperations for MemoryCache {
+    async fn delete(&self, key: &CacheKey) -> Result<(), CacheError> {
+        if !self.config.enabled {
+            return Err(CacheError::CacheDisabled);
+        }
+
+        let key_str = key.to_string();
+        self.store.write().await.remove(&key_str);
+        self.sets.write().await.remove(&key_str);
+        self.geo.write().await.remove(&key_str);
+        self.lists.write().await.remove(&key_str);
+        Ok(())
+    }
+
+    async fn exists(&self, key: &CacheKey) -> Result<bool, CacheError> {
+        if !self.config.enabled {
+            return Err(CacheError::CacheDisabled);
+        }
+
+        let key_str = key.to_string();
+        let store = self.store.read().await;
+
+        Ok(store.contains_key(&key_str))
+    }
+}
+
+#[async_trait]
+impl SetOperations for MemoryCache {
+    async fn sadd(&self, key: &CacheKey, value: &str) -> Result<(), CacheError> {
+        self.check_enabled()?;
+        self.sets.write().await
+            .entry(key.to_string())
+            .or_default()
+            .insert(value.to_string());
+        Ok(())
+    }
+
+    async fn smembers(&self, key: &CacheKey) -> Result<Vec<String>, CacheError> {
+        self.check_enabled()?;
+        let sets = self.sets.read().await;
+        Ok(sets.get(&key.to_string()).map(|set| set.iter().cloned().collect()).unwrap_or_default())
+    }
+
+    async fn srem(&self, key: &CacheKey, value: &str) -> Result<(), CacheError> {
+        self.check_enabled()?;
+        if let Some(set) = self.sets.write().await.get_mut(&key.to_string()) {
+            set.remove(value);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl GeoOperations for MemoryCache {
+    async fn geoadd(&self, key: &CacheKey, members: &[(String, f64, f64)]) -> Result<(), CacheError> {
+        self.check_enabled()?;
+        let mut geo = self.geo.write().await;
+        let positions = geo.entry(key.to_string()).or_default();
+        for (member, longitude, latitude) in members {
+            positions.insert(member.clone(), (*longitude, *latitude));
+        }
+        Ok(())
+    }
+
+    async fn geosearch(&self, key: &CacheKey, longitude: f64, latitude: f64, radius_km: f64, limit: usize) -> Result<Vec<String>, CacheError> {
+        self.check_enabled()?;
+        let geo = self.geo.read().await;
+        let Some(positions) = geo.get(&key.to_string()) else {
+            return Ok(vec![]);
+        };
+
+        // Nearest first, like GEOSEARCH ... ASC COUNT
+        let mut in_range: Vec<(f64, &String)> = positions
+            .iter()
+            .map(|(member, (lon, lat))| (haversine_km((latitude, longitude), (*lat, *lon)), member))
+            .filter(|(distance, _)| *distance <= radius_km)
+            .collect();
+        in_range.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(in_range.into_iter().take(limit).map(|(_, member)| member.clone()).collect())
+    }
+
+    async fn georem(&self, key: &CacheKey, member: &str) -> Result<(), CacheError> {
+        self.check_enabled()?;
+        if let Some(positions) = self.geo.write().await.get_mut(&key.to_string()) {
+            positions.remove(member);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ListOperations for MemoryCache {
+    async fn rpush(&self, key: &CacheKey, value: &str, ttl: Option<u64>) -> Result<(), CacheError> {
+        self.check_enabled()?;
+        let mut lists = self.lists.write().await;
+        let key_str = key.to_string();
+        let expired = lists.get(&key_str).is_some_and(|(_, expiry)| self.is_expired(*expiry));
+        if expired {
+            lists.remove(&key_str);
+        }
+
+        let (values, expires_at) = lists.entry(key_str).or_insert_with(|| (Vec::new(), None));
+        values.push(value.to_string());
+        // Refresh the expiry so the list lives as long as it keeps growing
+        if let Some(ttl) = ttl {
+            *expires_at = Some(Utc::now() + chrono::Duration::seconds(ttl as i64));
+        }
+        Ok(())
+    }
+
+    async fn lrange(&self, key: &CacheKey, start: isize, stop: isize) -> Result<Vec<String>, CacheError> {
+        self.check_enabled()?;
+        let lists = self.lists.read().await;
+        let values = match lists.get(&key.to_string()) {
+            Some((values, expiry)) if !self.is_expired(*expiry) => values,
+            _ => return Ok(vec![]),
+        };
+
+        // Same index rules as LRANGE: negatives count from the end, stop is inclusive
+        let len = values.len() as isize;
+        let start = if start < 0 { (len + start).max(0) } else { start };
+        let stop = if stop < 0 { len + stop } else { stop.min(len - 1) };
+        if start > stop || start >= len {
+            return Ok(vec![]);
+        }
+        Ok(values[start as usize..=stop as usize].to_vec())
+    }
+}
+
+#[async_trait]
+impl SortedSetOperations for MemoryCache {
+    async fn zadd(&self, key: &CacheKey, member: &str, score: f64) -> Result<(), CacheError> {
+        self.check_enabled()?;
+        self.sorted.write().await
+            .entry(key.to_string())
+            .or_default()
+            .insert(member.to_string(), score);
+        Ok(())
+    }
+
+    async fn zrem(&self, key: &CacheKey, member: &str) -> Result<(), CacheError> {
+        self.check_enabled()?;
+        if let Some(members) = self.sorted.write().await.get_mut(&key.to_string()) {
+            members.remove(member);
+        }
+        Ok(())
+    }
+
+    async fn zrevrange_by_score(&self, key: &CacheKey, max: f64, offset: usize, count: usize) -> Result<Vec<(String, f64)>, CacheError> {
+        self.check_enabled()?;
+        let sorted = self.sorted.read().await;
+        let Some(members) = sorted.get(&key.to_string()) else {
+            return Ok(vec![]);
+        };
+        let mut members: Vec<(String, f64)> = members.iter()
+            .filter(|(_, score)| **score <= max)
+            .map(|(member, score)| (member.clone(), *score))
+            .collect();
+        members.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| b.0.cmp(&a.0)));
+        Ok(members.into_iter().skip(offset).take(count).collect())
+    }
+}
+
+#[async_trait]
+impl SwapOperations for MemoryCache {
+    async fn get_raw(&self, key: &CacheKey) -> Result<Option<Vec<u8>>, CacheError> {
+        self.check_enabled()?;
+        let store = self.store.read().await;
+        Ok(store.get(&key.to_string())
+            .filter(|(_, expiry)| !self.is_expired(*expiry))
+            .map(|(bytes, _)| bytes.clone()))
+    }
+
+    async fn swap<T: Serialize + Send + Sync>(&self, key: &CacheKey, expected: &[u8], value: &T, ttl: Option<u64>) -> Result<bool, CacheError> {
+        self.check_enabled()?;
+        let bytes = cache_codec::encode(self.config.format, value)?;
+        let mut store = self.store.write().await;
+        let key_str = key.to_string();
+        let current = store.get(&key_str).filter(|(_, expiry)| !self.is_expired(*expiry));
+        if current.map(|(bytes, _)| bytes.as_slice()) != Some(expected) {
+            return Ok(false);
+        }
+        let expires_at = ttl.map(|seconds| Utc::now() + chrono::Duration::seconds(seconds as i64));
+        store.insert(key_str, (bytes, expires_at));
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl CounterOperations for MemoryCache {
+    async fn incr(&self, key: &CacheKey, ttl: u64) -> Result<i64, CacheError> {
+        if !self.config.enabled {
+            return Err(CacheError::CacheDisabled);
+        }
+
+        let mut store = self.store.write().await;
+        let entry = store.get(&key.to_string()).filter(|(_, expiry)| !self.is_expired(*expiry));
+        let (count, expires_at) = match entry {
+            Some((bytes, expiry)) => {
+                let count = std::str::from_utf8(bytes).ok().and_then(|s| s.parse::<i64>().ok());
+                (count.unwrap_or(0) + 1, *expiry)
+            }
+            None => (1, Some(Utc::now() + chrono::Duration::seconds(ttl as i64))),
+        };
+        store.insert(key.to_string(), (count.to_string().into_bytes(), expires_at));
+        Ok(count)
+    }
+}
+
+// Error types
+#[derive(Debug, thiserror::Error)]
+pub enum CacheError {
+    // Keeps the redis error so callers can tell timeouts from refused connections
+    // from auth failures
+    #[error("Redis error: {0}")]
+    Redis(#[from] redis::RedisError),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("MessagePack encode error: {0}")]
+    MessagePackEncode(#[from] rmp_serde::encode::Error),
+
+    #[error("MessagePack decode error: {0}")]
+    MessagePackDecode(#[from] rmp_serde::decode::Error),
+
+    // Written by a newer binary, or not ours at all
+    #[error("Unsupported cache envelope (version {version}, format {tag})")]
+    UnsupportedEnvelope { version: u8, tag: u8 },
+
+    // A `get_or_set` loader failed; handed back unchanged
+    #[error("Loader failed: {0}")]
+    Loader(Box<AppError>),
+
+    #[error("Cache is disabled")]
+    CacheDisabled,
+
+    #[error("Cache miss")]
+    CacheMiss,
+}
+
+// Cache key generators for different resources
+pub struct CacheKeys;
+
+impl CacheKeys {
+    // User cache keys
+    pub fn user_by_id(user_id: &UserId) -> CacheKey {
+        CacheKey::Composite(vec!["user".to_string(), "id".to_string(), user_id.to_string()])
+    }
+
+    pub fn user_by_email(email: &str) -> CacheKey {
+        CacheKey::Composite(vec!["user".to_string(), "email".to_string(), email.to_string()])
+    }
+
+    pub fn user_by_phone(phone: &str) -> CacheKey {
+        CacheKey::Composite(vec!["user".to_string(), "phone".to_string(), phone.to_string()])
+    }
+
+    pub fn user_credentials(user_id: &UserId) -> CacheKey {
+        CacheKey::Composite(vec![
+            "user".to_string(),
+            "credentials".to_string(),
+            user_id.to_string(),
+        ])
+    }
+
+    pub fn user_addresses(user_id: &UserId) -> CacheKey {
+        CacheKey::Composite(vec![
+            "user".to_string(),
+            "addresses".to_string(),
+            user_id.to_string(),
+        ])
+    }
+
+    pub fn user_credits(user_id: &UserId) -> CacheKey {
+        CacheKey::Composite(vec!["user".to_string(), "credits".to_string(), user_id.to_string()])
+    }
+
+    pub fn user_devices(user_id: &UserId) -> CacheKey {
+        CacheKey::Composite(vec!["user".to_string(), "devices".to_string(), user_id.to_string()])
+    }
+
+    // A business account's staff and shared address book, and which business a staff member books for
+    pub fn business_staff(business_id: &UserId) -> CacheKey {
+        CacheKey::Composite(vec!["business".to_string(), "staff".to_string(), business_id.to_string()])
+    }
+
+    pub fn business_addresses(business_id: &UserId) -> CacheKey {
+        CacheKey::Composite(vec!["business".to_string(), "addresses".to_string(), business_id.to_string()])
+    }
+
+    pub fn staff_business(user_id: &UserId) -> CacheKey {
+        CacheKey::Composite(vec!["user".to_string(), "business".to_string(), user_id.to_string()])
+    }
+
+    // One-time codes, e.g. ("device", "<user id>:<device id>"), and every one still outstanding
+    pub fn otp(purpose: &str, identifier: &str) -> CacheKey {
+        CacheKey::Composite(vec!["auth".to_string(), "otp".to_string(), purpose.to_string(), identifier.to_string()])
+    }
+
+    pub fn pending_otps() -> CacheKey {
+        CacheKey::Simple("auth:otp:pending".to_string())
+    }
+
+    // Failed attempts at a code or password, and the lockout once there are too many
+    pub fn auth_failures(purpose: &str, identifier: &str) -> CacheKey {
+        CacheKey::Composite(vec!["auth".to_string(), "failures".to_string(), purpose.to_string(), identifier.to_string()])
+    }
+
+    pub fn auth_lockout(purpose: &str, identifier: &str) -> CacheKey {
+        CacheKey::Composite(vec!["auth".to_string(), "lockout".to_string(), purpose.to_string(), identifier.to_string()])
+    }
+
+    // Notification digests waiting to go out, and the users who have one
+    pub fn notification_digest(user_id: &UserId) -> CacheKey {
+        CacheKey::Composite(vec!["notify".to_string(), "digest".to_string(), user_id.to_string()])
+    }
+
+    pub fn pending_digests() -> CacheKey {
+        CacheKey::Simple("notify:digest:pending".to_string())
+    }
+
+    // Notifications held back by quiet hours, and the users who have some
+    pub fn deferred_notifications(user_id: &UserId) -> CacheKey {
+        CacheKey::Composite(vec!["notify".to_string(), "deferred".to_string(), user_id.to_string()])
+    }
+
+    pub fn pending_deferred() -> CacheKey {
+        CacheKey::Simple("notify:deferred:pending".to_string())
+    }
+
+    pub fn topic_subscriptions(user_id: &UserId) -> CacheKey {
+        CacheKey::Composite(vec!["notify".to_string(), "topics".to_string(), user_id.to_string()])
+    }
+
+    // Languages with subscribers in a zone, by zone slug
+    pub fn zone_languages(zone_slug: &str) -> CacheKey {
+        CacheKey::Simple(format!("notify:zone:{}:languages", zone_slug))
+    }
+
+    pub fn broadcasts() -> CacheKey {
+        CacheKey::Simple("notify:broadcasts".to_string())
+    }
+
+    // Fixed window per type and recipient, e.g. notify:cap:promotion:usr-...:480012
+    pub fn notification_cap_window(kind: &str, recipient: &str, window: i64) -> CacheKey {
+        CacheKey::Simple(format!("notify:cap:{}:{}:{}", kind, recipient, window))
+    }
+
+    // API key cache keys
+    // Global so a key can be resolved before its tenant is known
+    pub fn api_key_by_id(key_id: &str) -> CacheKey {
+        CacheKey::Global(format!("apikey:id:{}", key_id))
+    }
+
+    pub fn api_key_by_hash(secret_hash: &str) -> CacheKey {
+        CacheKey::Global(format!("apikey:hash:{}", secret_hash))
+    }
+
+    // Kept apart from the key record so recording use never writes the record back
+    pub fn api_key_last_used(key_id: &str) -> CacheKey {
+        CacheKey::Global(format!("apikey:id:{}:last_used", key_id))
+    }
+
+    pub fn api_keys_by_merchant(merchant_id: &UserId) -> CacheKey {
+        CacheKey::Global(format!("apikeys:merchant:{}", merchant_id))
+    }
+
+    // Fixed one-minute window, e.g. ratelimit:apikey:key_...:29301520
+    pub fn api_key_rate_window(key_id: &str, window: i64) -> CacheKey {
+        CacheKey::Global(format!("ratelimit:apikey:{}:{}", key_id, window))
+    }
+
+    // Usage of one quota in one period, e.g. quota:apikey:key_...:jobs_created:202510
+    pub fn api_key_quota(key_id: &str, metric: QuotaMetric, window: &str) -> CacheKey {
+        CacheKey::Global(format!("quota:apikey:{}:{}:{}", key_id, metric, window))
+    }
+
+    pub fn api_key_quota_warning(key_id: &str, metric: QuotaMetric, window: &str) -> CacheKey {
+        CacheKey::Global(format!("quota:apikey:{}:{}:{}:warned", key_id, metric, window))
+    }
+
+    // Tenant registry keys
+    pub fn tenant_by_id(tenant_id: &str) -> CacheKey {
+        CacheKey::Global(format!("tenant:id:{}", tenant_id))
+    }
+
+    pub fn tenant_by_host(host: &str) -> CacheKey {
+        CacheKey::Global(format!("tenant:host:{}", host))
+    }
+
+    pub fn all_tenants() -> CacheKey {
+        CacheKey::Global("tenants:all".to_string())
+    }
+
+    pub fn all_users() -> CacheKey {
+        CacheKey::Simple("users:all".to_string())
+    }
+
+    // Driver cache keys
+    pub fn driver_by_id(driver_id: &DriverId) -> CacheKey {
+        CacheKey::Composite(vec!["driver".to_string(), "id".to_string(), driver_id.to_string()])
+    }
+
+    pub fn driver_by_user_id(user_id: &UserId) -> CacheKey {
+        CacheKey::Composite(vec![
+            "driver".to_string(),
+            "user_id".to_string(),
+            user_id.to_string(),
+        ])
+    }
+
+    pub fn all_drivers() -> CacheKey {
+        CacheKey::Simple("drivers:all".to_string())
+    }
+
+    pub fn online_drivers() -> CacheKey {
+        CacheKey::Simple("drivers:online".to_string())
+    }
+
+    pub fn online_customers() -> CacheKey {
+        CacheKey::Simple("users:online".to_string())
+    }
+
+    pub fn online(kind: PresenceKind) -> CacheKey {
+        match kind {
+            PresenceKind::Driver => Self::online_drivers(),
+            PresenceKind::Customer => Self::online_customers(),
+        }
+    }
+
+    // Offers made over a driver's socket, open until answered, expired or withdrawn
+    pub fn driver_offer(driver_id: &DriverId, job_id: &JobId) -> CacheKey {
+        CacheKey::Composite(vec!["offer".to_string(), driver_id.to_string(), job_id.to_string()])
+    }
+
+    pub fn offers_by_driver(driver_id: &DriverId) -> CacheKey {
+        CacheKey::Composite(vec!["offers".to_string(), "driver".to_string(), driver_id.to_string()])
+    }
+
+    pub fn offers_by_job(job_id: &JobId) -> CacheKey {
+        CacheKey::Composite(vec!["offers".to_string(), "job".to_string(), job_id.to_string()])
+    }
+
+    // Blocks run both ways and either side's is enough to keep a pair apart
+    pub fn drivers_blocked_by(user_id: &UserId) -> CacheKey {
+        CacheKey::Composite(vec!["blocks".to_string(), "user".to_string(), user_id.to_string()])
+    }
+
+    pub fn customers_blocked_by(driver_id: &DriverId) -> CacheKey {
+        CacheKey::Composite(vec!["blocks".to_string(), "driver".to_string(), driver_id.to_string()])
+    }
+
+    pub fn presence_connection(kind: PresenceKind, id: &str) -> CacheKey {
+        CacheKey::Composite(vec![
+            "presence".to_string(),
+            "connection".to_string(),
+            kind.as_str().to_string(),
+            id.to_string(),
+        ])
+    }
+
+    // Job cache keys
+    pub fn job_by_id(job_id: &JobId) -> CacheKey {
+        CacheKey::Composite(vec!["job".to_string(), "id".to_string(), job_id.to_string()])
+    }
+
+    pub fn jobs_by_customer(customer_id: &UserId) -> CacheKey {
+        CacheKey::Composite(vec![
+            "jobs".to_string(),
+            "customer".to_string(),
+            customer_id.to_string(),
+        ])
+    }
+
+    pub fn jobs_by_driver(driver_id: &DriverId) -> CacheKey {
+        CacheKey::Composite(vec![
+            "jobs".to_string(),
+            "driver".to_string(),
+            driver_id.to_string(),
+        ])
+    }
+
+    // The same jobs as a sorted set scored by creation time, for paging through history
+    pub fn job_history_by_customer(customer_id: &UserId) -> CacheKey {
+        CacheKey::Composite(vec!["jobs".to_string(), "customer".to_string(), customer_id.to_string(), "history".to_string()])
+    }
+
+    pub fn job_history_by_driver(driver_id: &DriverId) -> CacheKey {
+        CacheKey::Composite(vec!["jobs".to_string(), "driver".to_string(), driver_id.to_string(), "history".to_string()])
+    }
+
+    // Jobs created on a UTC day, e.g. jobs:day:20250901
+    pub fn jobs_by_day(day: &NaiveDate) -> CacheKey {
+        CacheKey::Simple(format!("jobs:day:{}", day.format("%Y%m%d")))
+    }
+
+    // Every day with a jobs:day set, so the retention purge can walk them
+    pub fn job_days() -> CacheKey {
+        CacheKey::Simple("jobs:days".to_string())
+    }
+
+    // Public tracking code to job ID
+    pub fn job_by_tracking_code(tracking_code: &str) -> CacheKey {
+        CacheKey::Composite(vec!["jobs".to_string(), "tracking".to_string(), tracking_code.to_string()])
+    }
+
+    // Share link token to the share, expiring with it
+    pub fn trip_share(token: &str) -> CacheKey {
+        CacheKey::Composite(vec!["jobs".to_string(), "share".to_string(), token.to_string()])
+    }
+
+    // A job's share tokens, including ones since expired
+    pub fn job_trip_shares(job_id: &JobId) -> CacheKey {
+        CacheKey::Composite(vec!["jobs".to_string(), "shares".to_string(), job_id.to_string()])
+    }
+
+    pub fn job_batch(batch_id: &str) -> CacheKey {
+        CacheKey::Composite(vec!["jobs".to_string(), "batch".to_string(), batch_id.to_string()])
+    }
+
+    pub fn job_pool(pool_id: &str) -> CacheKey {
+        CacheKey::Composite(vec!["jobs".to_string(), "pool".to_string(), pool_id.to_string()])
+    }
+
+    // Voice gateway call ID -> the job and stop it announced
+    pub fn arrival_call(call_id: &str) -> CacheKey {
+        CacheKey::Composite(vec!["jobs".to_string(), "arrival_call".to_string(), call_id.to_string()])
+    }
+
+    pub fn active_jobs() -> CacheKey {
+        CacheKey::Simple("jobs:active".to_string())
+    }
+
+    // Guaranteed jobs delivered since the SLA monitor last looked, so a late one is still credited
+    pub fn completed_sla_jobs() -> CacheKey {
+        CacheKey::Simple("jobs:sla:completed".to_string())
+    }
+
+    pub fn surge_multipliers() -> CacheKey {
+        CacheKey::Simple("pricing:surge".to_string())
+    }
+
+    pub fn commission_config() -> CacheKey {
+        CacheKey::Simple("pricing:commissions".to_string())
+    }
+
+    pub fn dispatch_settings() -> CacheKey {
+        CacheKey::Simple("dispatch:settings".to_string())
+    }
+
+    pub fn retention_settings() -> CacheKey {
+        CacheKey::Simple("retention:settings".to_string())
+    }
+
+    // Last day a data class was purged through, e.g. retention:cursor:jobs
+    pub fn retention_cursor(class: DataClass) -> CacheKey {
+        CacheKey::Composite(vec!["retention".to_string(), "cursor".to_string(), class.to_string()])
+    }
+
+    pub fn legal_hold(job_id: &JobId) -> CacheKey {
+        CacheKey::Composite(vec!["retention".to_string(), "hold".to_string(), job_id.to_string()])
+    }
+
+    pub fn legal_holds() -> CacheKey {
+        CacheKey::Simple("retention:holds".to_string())
+    }
+
+    pub fn service_zone(zone_id: &str) -> CacheKey {
+        CacheKey::Composite(vec!["zones".to_string(), "id".to_string(), zone_id.to_string()])
+    }
+
+    pub fn service_zones() -> CacheKey {
+        CacheKey::Simple("zones:all".to_string())
+    }
+
+    // Changes on every zone edit, so each instance knows when to reload its copy
+    pub fn zones_generation() -> CacheKey {
+        CacheKey::Simple("zones:generation".to_string())
+    }
+
+    pub fn calendar_periods() -> CacheKey {
+        CacheKey::Simple("pricing:calendar".to_string())
+    }
+
+    pub fn tax_schedules() -> CacheKey {
+        CacheKey::Simple("pricing:taxes".to_string())
+    }
+
+    pub fn exchange_rates() -> CacheKey {
+        CacheKey::Simple("pricing:exchange-rates".to_string())
+    }
+
+    pub fn notification_templates() -> CacheKey {
+        CacheKey::Simple("notifications:templates".to_string())
+    }
+
+    // Pickups bucketed by UTC hour, e.g. demand:pickups:2025090108
+    pub fn demand_pickups(hour: &DateTime<Utc>) -> CacheKey {
+        CacheKey::Simple(format!("demand:pickups:{}", hour.format("%Y%m%d%H")))
+    }
+
+    pub fn demand_forecast() -> CacheKey {
+        CacheKey::Simple("demand:forecast".to_string())
+    }
+
+    pub fn admin_dashboard(stale_after_minutes: i64) -> CacheKey {
+        CacheKey::Composite(vec![
+            "admin".to_string(),
+            "dashboard".to_string(),
+            stale_after_minutes.to_string(),
+        ])
+    }
+
+    pub fn job_route(job_id: &JobId) -> CacheKey {
+        CacheKey::Composite(vec!["route".to_string(), "job".to_string(), job_id.to_string()])
+    }
+
+    pub fn job_events(job_id: &JobId) -> CacheKey {
+        CacheKey::Composite(vec!["events".to_string(), "job".to_string(), job_id.to_string()])
+    }
+
+    // Push sends by outcome ("delivered" or "failed") per UTC minute, e.g. alerts:push:failed:202509011405
+    pub fn push_outcomes(outcome: &str, minute: &DateTime<Utc>) -> CacheKey {
+        CacheKey::Simple(format!("alerts:push:{}:{}", outcome, minute.format("%Y%m%d%H%M")))
+    }
+
+    // Held while an alert of this kind is cooling down, so instances don't repeat it
+    pub fn alert_cooldown(kind: AlertKind) -> CacheKey {
+        CacheKey::Composite(vec!["alerts".to_string(), "cooldown".to_string(), kind.to_string()])
+    }
+
+    // Events waiting for the warehouse export, by the UTC day they happened
+    pub fn events_outbox(day: &NaiveDate) -> CacheKey {
+        CacheKey::Simple(format!("events:outbox:{}", day.format("%Y%m%d")))
+    }
+
+    pub fn events_outbox_days() -> CacheKey {
+        CacheKey::Simple("events:outbox:days".to_string())
+    }
+
+    // How many of a day's outbox events have been exported
+    pub fn events_export_cursor(day: &NaiveDate) -> CacheKey {
+        CacheKey::Simple(format!("events:export:cursor:{}", day.format("%Y%m%d")))
+    }
+
+    // Consumers read the log across every tenant and region, so their bookkeeping is global
+    pub fn consumer_checkpoint(group: &str, consumer: &str) -> CacheKey {
+        CacheKey::Global(format!("events:consumer:{}:checkpoint:{}", group, consumer))
+    }
+
+    pub fn consumer_names(group: &str) -> CacheKey {
+        CacheKey::Global(format!("events:consumer:{}:consumers", group))
+    }
+
+    pub fn consumer_handled(group: &str, offset: &str) -> CacheKey {
+        CacheKey::Global(format!("events:consumer:{}:handled:{}", group, offset))
+    }
+
+    pub fn consumer_parked(group: &str) -> CacheKey {
+        CacheKey::Global(format!("events:consumer:{}:parked", group))
+    }
+
+    pub fn saga(kind: SagaKind, key: &str) -> CacheKey {
+        CacheKey::Simple(format!("saga:{}:{}", kind.as_str(), key))
+    }
+
+    // "kind:key" of every saga not yet completed or undone
+    pub fn saga_claim(kind: SagaKind, key: &str) -> CacheKey {
+        CacheKey::Simple(format!("saga:{}:{}:claim", kind.as_str(), key))
+    }
+
+    pub fn open_sagas() -> CacheKey {
+        CacheKey::Simple("sagas:open".to_string())
+    }
+
+    pub fn canary_participants() -> CacheKey {
+        CacheKey::Simple("canary:participants".to_string())
+    }
+
+    pub fn canary_runs() -> CacheKey {
+        CacheKey::Simple("canary:runs".to_string())
+    }
+
+    // Taken by the one instance that runs the canary in a given interval
+    pub fn canary_slot(slot: i64) -> CacheKey {
+        CacheKey::Simple(format!("canary:slot:{}", slot))
+    }
+
+    // Location cache keys
+    pub fn driver_location(driver_id: &DriverId) -> CacheKey {
+        CacheKey::Composite(vec![
+            "location".to_string(),
+            "driver".to_string(),
+            driver_id.to_string(),
+        ])
+    }
+
+    pub fn driver_last_seen(driver_id: &DriverId) -> CacheKey {
+        CacheKey::Composite(vec![
+            "presence".to_string(),
+            "driver".to_string(),
+            driver_id.to_string(),
+        ])
+    }
+
+    pub fn driver_outcomes(driver_id: &DriverId) -> CacheKey {
+        CacheKey::Composite(vec![
+            "outcomes".to_string(),
+            "driver".to_string(),
+            driver_id.to_string(),
+        ])
+    }
+
+    pub fn driver_reliability(driver_id: &DriverId) -> CacheKey {
+        CacheKey::Composite(vec![
+            "reliability".to_string(),
+            "driver".to_string(),
+            driver_id.to_string(),
+        ])
+    }
+
+    // Last point counted towards the driver's distance
+    pub fn odometer_anchor(driver_id: &DriverId) -> CacheKey {
+        CacheKey::Composite(vec!["odometer".to_string(), "anchor".to_string(), driver_id.to_string()])
+    }
+
+    pub fn driver_distance(driver_id: &DriverId, date: &NaiveDate) -> CacheKey {
+        CacheKey::Composite(vec!["odometer".to_string(), "driver".to_string(), driver_id.to_string(), date.to_string()])
+    }
+
+    pub fn vehicle_odometer(vehicle_id: &str) -> CacheKey {
+        CacheKey::Composite(vec!["odometer".to_string(), "vehicle".to_string(), vehicle_id.to_string()])
+    }
+
+    pub fn spoof_strikes(driver_id: &DriverId) -> CacheKey {
+        CacheKey::Composite(vec!["risk".to_string(), "strikes".to_string(), driver_id.to_string()])
+    }
+
+    pub fn quarantined_locations(driver_id: &DriverId) -> CacheKey {
+        CacheKey::Composite(vec!["risk".to_string(), "quarantine".to_string(), driver_id.to_string()])
+    }
+
+    pub fn risk_flag(driver_id: &DriverId) -> CacheKey {
+        CacheKey::Composite(vec!["risk".to_string(), "flag".to_string(), driver_id.to_string()])
+    }
+
+    pub fn risk_flags() -> CacheKey {
+        CacheKey::Simple("risk:flagged".to_string())
+    }
+
+    pub fn dispatch_audit() -> CacheKey {
+        CacheKey::Simple("audit:dispatch".to_string())
+    }
+
+    // Ledger keys; the journal holds every entry, each account list the ones touching it
+    pub fn ledger_journal() -> CacheKey {
+        CacheKey::Simple("ledger:journal".to_string())
+    }
+
+    pub fn ledger_account(account: &LedgerAccount) -> CacheKey {
+        CacheKey::Simple(format!("ledger:account:{}", account))
+    }
+
+    pub fn ledger_posting(posting_key: &str) -> CacheKey {
+        CacheKey::Simple(format!("ledger:posting:{}", posting_key))
+    }
+
+    pub fn ledger_posting_claim(posting_key: &str) -> CacheKey {
+        CacheKey::Simple(format!("ledger:claim:{}", posting_key))
+    }
+
+    pub fn reconciliation_discrepancies() -> CacheKey {
+        CacheKey::Simple("reconciliation:discrepancies".to_string())
+    }
+
+    pub fn last_reconciled_day() -> CacheKey {
+        CacheKey::Simple("reconciliation:last-day".to_string())
+    }
+
+    pub fn dispute(case_id: &str) -> CacheKey {
+        CacheKey::Composite(vec!["disputes".to_string(), "id".to_string(), case_id.to_string()])
+    }
+
+    pub fn disputes() -> CacheKey {
+        CacheKey::Simple("disputes:all".to_string())
+    }
+
+    // Provider chargeback notice to the case it opened, e.g. disputes:event:paystack:evt_1
+    pub fn chargeback_event(provider: &str, event_id: &str) -> CacheKey {
+        CacheKey::Simple(format!("disputes:event:{}:{}", provider, event_id))
+    }
+
+    pub fn chargeback_event_claim(provider: &str, event_id: &str) -> CacheKey {
+        CacheKey::Simple(format!("disputes:claim:{}:{}", provider, event_id))
+    }
+
+    pub fn incident(incident_id: &str) -> CacheKey {
+        CacheKey::Composite(vec!["incidents".to_string(), "id".to_string(), incident_id.to_string()])
+    }
+
+    pub fn incidents() -> CacheKey {
+        CacheKey::Simple("incidents:all".to_string())
+    }
+
+    pub fn feed_settings(consumer_id: &UserId) -> CacheKey {
+        CacheKey::Composite(vec!["feed".to_string(), "settings".to_string(), consumer_id.to_string()])
+    }
+
+    pub fn billing_account(merchant_id: &UserId) -> CacheKey {
+        CacheKey::Composite(vec!["billing".to_string(), "account".to_string(), merchant_id.to_string()])
+    }
+
+    // Merchants with jobs waiting to be invoiced, then per merchant the months and their lines
+    pub fn invoice_draft_merchants() -> CacheKey {
+        CacheKey::Simple("invoices:drafts".to_string())
+    }
+
+    pub fn invoice_draft_periods(merchant_id: &UserId) -> CacheKey {
+        CacheKey::Simple(format!("invoices:drafts:{}", merchant_id))
+    }
+
+    pub fn invoice_draft_lines(merchant_id: &UserId, period: &str) -> CacheKey {
+        CacheKey::Simple(format!("invoices:drafts:{}:{}", merchant_id, period))
+    }
+
+    pub fn invoice_issue_claim(merchant_id: &UserId, period: &str) -> CacheKey {
+        CacheKey::Simple(format!("invoices:claim:{}:{}", merchant_id, period))
+    }
+
+    pub fn invoice(invoice_id: &str) -> CacheKey {
+        CacheKey::Composite(vec!["invoices".to_string(), "id".to_string(), invoice_id.to_string()])
+    }
+
+    pub fn invoices() -> CacheKey {
+        CacheKey::Simple("invoices:all".to_string())
+    }
+
+    pub fn merchant_invoices(merchant_id: &UserId) -> CacheKey {
+        CacheKey::Simple(format!("invoices:merchant:{}", merchant_id))
+    }
+
+    pub fn driver_locations_geo() -> CacheKey {
+        CacheKey::Simple("drivers:geo".to_string())
+    }
+
+    // Pattern keys for bulk operations
+    pub fn all_users_pattern() -> CacheKey {
+        CacheKey::Pattern("user:*".to_string())
+    }
+
+    pub fn all_drivers_pattern() -> CacheKey {
+        CacheKey::Pattern("driver:*".to_string())
+    }
+}
+
+// Cache service wrapper
+pub struct CacheService {
+    user_cache: Arc<Cache>,
+    job_cache: Arc<Cache>,
+    driver_cache: Arc<Cache>,
+    repository: Arc<dyn Repository>,
+    cipher: Option<Arc<FieldCipher>>,
+}
+
+impl CacheService {
+    pub async fn new(redis_url: &str) -> Result<Self, CacheError> {
+        Self::with_config(CacheConfig {
+            redis_url: redis_url.to_string(),
+            ..Default::default()
+        })
+        .await
+    }
+
+    pub async fn with_config(config: CacheConfig) -> Result<Self, CacheError> {
+        Ok(Self {
+            user_cache: Arc::new(Cache::Redis(RedisCache::new(config.clone()).await?)),
+            job_cache: Arc::new(Cache::Redis(RedisCache::new(config.clone()).await?)),
+            driver_cache: Arc::new(Cache::Redis(RedisCache::new(config.clone()).await?)),
+            repository: Arc::new(NoRepository),
+            cipher: None,
+        })
+    }
+
+    pub fn new_memory(config: CacheConfig) -> Self {
+        // One store behind all three, as with a single Redis server, so indexes written
+        // through one cache are visible through the others
+        let cache = Arc::new(Cache::Memory(MemoryCache::new(config.clone())));
+        Self {
+            user_cache: cache.clone(),
+            job_cache: cache.clone(),
+            driver_cache: cache,
+            repository: Arc::new(NoRepository),
+            cipher: None,
+        }
+    }
+
+    // Users and jobs are written through to `repository` and read back from it on a miss
+    pub fn with_repository(mut self, repository: Arc<dyn Repository>) -> Self {
+        self.repository = repository;
+        self
+    }
+
+    // Personal fields of users, drivers and jobs are sealed on their way into Redis and the
+    // repository, and opened on the way back out
+    pub fn with_cipher(mut self, cipher: Arc<FieldCipher>) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    fn seal<'a, T: PiiFields + Clone>(&self, record: &'a T) -> Cow<'a, T> {
+        match &self.cipher {
+            Some(cipher) => {
+                let mut sealed = record.clone();
+                cipher.seal_fields(&mut sealed);
+                Cow::Owned(sealed)
+            }
+            None => Cow::Borrowed(record),
+        }
+    }
+
+    fn open<T: PiiFields>(&self, record: Option<T>) -> Result<Option<T>, AppError> {
+        match (&self.cipher, record) {
+            (Some(cipher), Some(mut record)) => {
+                cipher.open_fields(&mut record)?;
+                Ok(Some(record))
+            }
+            (_, record) => Ok(record),
+        }
+    }
+
+    // What an email or phone number is indexed under
+    fn lookup_value(&self, value: &str) -> String {
+        match &self.cipher {
+            Some(cipher) => cipher.blind_index(value),
+            None => value.to_string(),
+        }
+    }
+
+    pub async fn get_user(&self, key: &CacheKey) -> Result<Option<User>, AppError> {
+        self.open(self.user_cache.get(key).await?)
+    }
+
+    pub async fn set_user(&self, key: &CacheKey, value: &User, ttl: Option<u64>) -> Result<(), AppError> {
+        self.user_cache.set(key, self.seal(value).as_ref(), ttl).await.map_err(|e| e.into())
+    }
+
+    pub async fn get_job(&self, key: &CacheKey) -> Result<Option<Job>, AppError> {
+        self.open(self.job_cache.get(key).await?)
+    }
+
+    pub async fn set_job(&self, key: &CacheKey, value: &Job, ttl: Option<u64>) -> Result<(), AppError> {
+        self.job_cache.set(key, self.seal(value).as_ref(), ttl).await.map_err(|e| e.into())
+    }
+
+    // Read-through: on a miss, load from the repository and repopulate the cache
+    pub async fn load_user(&self, user_id: &UserId) -> Result<Option<User>, AppError> {
+        if let Some(user) = self.get_user(&CacheKeys::user_by_id(user_id)).await? {
+            return Ok(Some(user));
+        }
+        let Some(user) = self.open(self.repository.find_user(user_id).await?)? else {
+            return Ok(None);
+        };
+        tracing::debug!("User {} missed the cache, reloaded from repository", user_id);
+        self.put_user(&user).await?;
+        Ok(Some(user))
+    }
+
+    pub async fn load_job(&self, job_id: &JobId) -> Result<Option<Job>, AppError> {
+        if let Some(job) = self.get_job(&CacheKeys::job_by_id(job_id)).await? {
+            return Ok(Some(job));
+        }
+        let Some(job) = self.open(self.repository.find_job(job_id).await?)? else {
+            return Ok(None);
+        };
+        tracing::debug!("Job {} missed the cache, reloaded from repository", job_id);
+        self.put_job(&job).await?;
+        Ok(Some(job))
+    }
+
+    // User caching methods
+    pub async fn cache_user(&self, user: &User) -> Result<(), AppError> {
+        self.repository.save_user(self.seal(user).as_ref()).await?;
+        self.put_user(user).await
+    }
+
+    async fn put_user(&self, user: &User) -> Result<(), AppError> {
+        let key = CacheKeys::user_by_id(&user.id);
+        self.set_user(&key, user, Some(86400 * 7)).await?; // 7 days TTL
+
+        // Update indices
+        self.cache_user_by_phone(&user.phone_number, &user.id).await?;
+        self.cache_user_by_email(&user.email, &user.id).await?;
+
+        Ok(())
+    }
+
+    pub async fn get_user_credentials(&self, user_id: &UserId) -> Result<Option<String>, AppError> {
+        let key = CacheKeys::user_credentials(user_id);
+        self.user_cache.get(&key).await.map_err(|e| e.into())
+    }
+
+    pub async fn cache_user_credentials(&self, user_id: &UserId, hashed_password: &str) -> Result<(), AppError> {
+        let key = CacheKeys::user_credentials(user_id);
+        self.user_cache
+            .set(&key, &hashed_password.to_string(), None)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn cache_user_by_email(&self, email: &str, user_id: &UserId) -> Result<(), AppError> {
+        let key = CacheKeys::user_by_email(&self.lookup_value(email));
+        self.user_cache
+            .set(&key, user_id, Some(86400 * 7))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_user_id_by_email(&self, email: &str) -> Result<Option<UserId>, AppError> {
+        let key = CacheKeys::user_by_email(&self.lookup_value(email));
+        if let Some(user_id) = self.user_cache.get(&key).await? {
+            return Ok(Some(user_id));
+        }
+        self.legacy_lookup(CacheKeys::user_by_email(email)).await
+    }
+
+    pub async fn cache_user_by_phone(&self, phone: &str, user_id: &UserId) -> Result<(), AppError> {
+        let key = CacheKeys::user_by_phone(&self.lookup_value(phone));
+        self.user_cache
+            .set(&key, user_id, Some(86400 * 7))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_user_id_by_phone(&self, phone: &str) -> Result<Option<UserId>, AppError> {
+        let key = CacheKeys::user_by_phone(&self.lookup_value(phone));
+        if let Some(user_id) = self.user_cache.get(&key).await? {
+            return Ok(Some(user_id));
+        }
+        self.legacy_lookup(CacheKeys::user_by_phone(phone)).await
+    }
+
+    // Indexes written before encryption was turned on are keyed by the plaintext
+    async fn legacy_lookup(&self, key: CacheKey) -> Result<Option<UserId>, AppError> {
+        if self.cipher.is_none() {
+            return Ok(None);
+        }
+        Ok(self.user_cache.get(&key).await?)
+    }
+
+    pub async fn get_user_addresses(&self, user_id: &UserId) -> Result<Vec<Address>, AppError> {
+        let key = CacheKeys::user_addresses(user_id);
+        let addresses: Option<Vec<Address>> = self.user_cache.get(&key).await?;
+        Ok(addresses.unwrap_or_default())
+    }
+
+    pub async fn cache_user_addresses(&self, user_id: &UserId, addresses: &Vec<Address>) -> Result<(), AppError> {
+        let key = CacheKeys::user_addresses(user_id);
+        self.user_cache.set(&key, addresses, Some(86400 * 7)).await?; // 7 days TTL
+        Ok(())
+    }
+
+    pub async fn get_user_devices(&self, user_id: &UserId) -> Result<Vec<UserDevice>, AppError> {
+        let devices: Option<Vec<UserDevice>> = self.user_cache.get(&CacheKeys::user_devices(user_id)).await?;
+        Ok(devices.unwrap_or_default())
+    }
+
+    pub async fn cache_user_devices(&self, user_id: &UserId, devices: &Vec<UserDevice>) -> Result<(), AppError> {
+        self.user_cache.set(&CacheKeys::user_devices(user_id), devices, None).await?;
+        Ok(())
+    }
+
+    pub async fn get_business_staff(&self, business_id: &UserId) -> Result<Vec<StaffMember>, AppError> {
+        let staff: Option<Vec<StaffMember>> = self.user_cache.get(&CacheKeys::business_staff(business_id)).await?;
+        Ok(staff.unwrap_or_default())
+    }
+
+    pub async fn cache_business_staff(&self, business_id: &UserId, staff: &Vec<StaffMember>) -> Result<(), AppError> {
+        self.user_cache.set(&CacheKeys::business_staff(business_id), staff, None).await?;
+        Ok(())
+    }
+
+    pub async fn get_business_addresses(&self, business_id: &UserId) -> Result<Vec<Address>, AppError> {
+        let addresses: Option<Vec<Address>> = self.user_cache.get(&CacheKeys::business_addresses(business_id)).await?;
+        Ok(addresses.unwrap_or_default())
+    }
+
+    pub async fn cache_business_addresses(&self, business_id: &UserId, addresses: &Vec<Address>) -> Result<(), AppError> {
+        self.user_cache.set(&CacheKeys::business_addresses(business_id), addresses, None).await?;
+        Ok(())
+    }
+
+    pub async fn get_staff_business(&self, user_id: &UserId) -> Result<Option<UserId>, AppError> {
+        Ok(self.user_cache.get(&CacheKeys::staff_business(user_id)).await?)
+    }
+
+    pub async fn set_staff_business(&self, user_id: &UserId, business_id: Option<&UserId>) -> Result<(), AppError> {
+        let key = CacheKeys::staff_business(user_id);
+        match business_id {
+            Some(business_id) => self.user_cache.set(&key, business_id, None).await?,
+            None => self.user_cache.delete(&key).await?,
+        }
+        Ok(())
+    }
+
+    pub async fn get_otp(&self, purpose: &str, identifier: &str) -> Result<Option<StoredOtp>, AppError> {
+        Ok(self.user_cache.get(&CacheKeys::otp(purpose, identifier)).await?)
+    }
+
+    pub async fn cache_otp(&self, purpose: &str, identifier: &str, otp: &StoredOtp, ttl_seconds: u64) -> Result<(), AppError> {
+        self.user_cache.set(&CacheKeys::otp(purpose, identifier), otp, Some(ttl_seconds)).await?;
+        self.user_cache.sadd(&CacheKeys::pending_otps(), &format!("{}:{}", purpose, identifier)).await?;
+        Ok(())
+    }
+
+    pub async fn delete_otp(&self, purpose: &str, identifier: &str) -> Result<(), AppError> {
+        self.user_cache.delete(&CacheKeys::otp(purpose, identifier)).await?;
+        self.user_cache.srem(&CacheKeys::pending_otps(), &format!("{}:{}", purpose, identifier)).await?;
+        Ok(())
+    }
+
+    // (purpose, identifier) of every code issued and not yet used or purged
+    pub async fn get_pending_otps(&self) -> Result<Vec<(String, String)>, AppError> {
+        let members = self.user_cache.smembers(&CacheKeys::pending_otps()).await?;
+        Ok(members.iter()
+            .filter_map(|member| member.split_once(':'))
+            .map(|(purpose, identifier)| (purpose.to_string(), identifier.to_string()))
+            .collect())
+    }
+
+    // Failures so far in the window that starts with the first one
+    pub async fn record_auth_failure(&self, purpose: &str, identifier: &str, window_seconds: u64) -> Result<i64, AppError> {
+        Ok(self.user_cache.incr(&CacheKeys::auth_failures(purpose, identifier), window_seconds).await?)
+    }
+
+    pub async fn clear_auth_failures(&self, purpose: &str, identifier: &str) -> Result<(), AppError> {
+        self.user_cache.delete(&CacheKeys::auth_failures(purpose, identifier)).await?;
+        Ok(())
+    }
+
+    pub async fn get_auth_lockout(&self, purpose: &str, identifier: &str) -> Result<Option<DateTime<Utc>>, AppError> {
+        Ok(self.user_cache.get(&CacheKeys::auth_lockout(purpose, identifier)).await?)
+    }
+
+    pub async fn set_auth_lockout(&self, purpose: &str, identifier: &str, until: DateTime<Utc>) -> Result<(), AppError> {
+        let ttl = (until - Utc::now()).num_seconds().max(1) as u64;
+        self.user_cache.set(&CacheKeys::auth_lockout(purpose, identifier), &until, Some(ttl)).await?;
+        Ok(())
+    }
+
+    pub async fn get_all_user_ids(&self) -> Result<Vec<UserId>, AppError> {
+        let key = CacheKeys::all_users();
+        Ok(parse_members(self.user_cache.smembers(&key).await?))
+    }
+
+    pub async fn cache_user_index(&self, user: &User) -> Result<(), AppError> {
+        // Add to all users set
+        let all_users_key = CacheKeys::all_users();
+        self.user_cache
+            .sadd(&all_users_key, user.id.as_str())
+            .await?;
+        Ok(())
+    }
+
+    // Job caching methods
+    pub async fn cache_job(&self, job: &Job) -> Result<(), AppError> {
+        self.repository.save_job(self.seal(job).as_ref()).await?;
+        self.put_job(job).await
+    }
+
+    async fn put_job(&self, job: &Job) -> Result<(), AppError> {
+        let key = CacheKeys::job_by_id(&job.id);
+        self.set_job(&key, job, Some(3600)).await?; // 1 hour TTL
+        Ok(())
+    }
+
+    // Apply `change` to the job as it is now, and write it only if no one else has written
+    // it since; otherwise read it again and reapply. Background jobs that touch a few
+    // fields use this so they never undo a status change made while they were working.
+    // An error from `change` leaves the job as it was.
+    pub async fn update_job<F>(&self, job_id: &JobId, mut change: F) -> Result<Job, AppError>
+    where
+        F: FnMut(&mut Job) -> Result<(), AppError> + Send,
+    {
+        const ATTEMPTS: usize = 5;
+        let key = CacheKeys::job_by_id(job_id);
+        for _ in 0..ATTEMPTS {
+            let stored = match self.job_cache.get_raw(&key).await? {
+                Some(stored) => stored,
+                None => {
+                    // Read through so there is a cached copy to compare against
+                    self.load_job(job_id).await?.ok_or_else(|| AppError::job_not_found(job_id.as_str()))?;
+                    continue;
+                }
+            };
+            let mut job: Job = self.open(Some(cache_codec::decode(&stored)?))?
+                .ok_or_else(|| AppError::job_not_found(job_id.as_str()))?;
+            change(&mut job)?;
+            if self.job_cache.swap(&key, &stored, self.seal(&job).as_ref(), Some(3600)).await? {
+                self.repository.save_job(self.seal(&job).as_ref()).await?;
+                return Ok(job);
+            }
+            tracing::debug!("Job {} changed while being updated, retrying", job_id);
+        }
+        Err(AppError::Conflict(format!("Job {} kept changing while being updated", job_id)))
+    }
+
+    pub async fn get_jobs_for_day(&self, day: &NaiveDate) -> Result<Vec<JobId>, AppError> {
+        let key = CacheKeys::jobs_by_day(day);
+        Ok(parse_members(self.job_cache.smembers(&key).await?))
+    }
+
+    pub async fn add_job_to_day(&self, job: &Job) -> Result<(), AppError> {
+        let day = job.created_at.date_naive();
+        self.job_cache.sadd(&CacheKeys::jobs_by_day(&day), job.id.as_str()).await?;
+        self.job_cache.sadd(&CacheKeys::job_days(), &day.format("%Y%m%d").to_string()).await?;
+        Ok(())
+    }
+
+    pub async fn get_job_days(&self) -> Result<Vec<NaiveDate>, AppError> {
+        Ok(parse_days(self.job_cache.smembers(&CacheKeys::job_days()).await?))
+    }
+
+    // Undo persist of a job that was never handed to anyone (bulk import rollback)
+    pub async fn discard_job(&self, job: &Job) -> Result<(), AppError> {
+        self.job_cache.delete(&CacheKeys::job_by_id(&job.id)).await?;
+        self.job_cache.srem(&CacheKeys::jobs_by_customer(&job.customer_id), job.id.as_str()).await?;
+        self.job_cache.zrem(&CacheKeys::job_history_by_customer(&job.customer_id), job.id.as_str()).await?;
+        self.job_cache.srem(&CacheKeys::active_jobs(), job.id.as_str()).await?;
+        self.job_cache.srem(&CacheKeys::jobs_by_day(&job.created_at.date_naive()), job.id.as_str()).await?;
+        self.job_cache.delete(&CacheKeys::job_by_tracking_code(&job.tracking_code)).await?;
+        Ok(())
+    }
+
+    pub async fn get_job_id_by_tracking_code(&self, tracking_code: &str) -> Result<Option<JobId>, AppError> {
+        let key = CacheKeys::job_by_tracking_code(tracking_code);
+        Ok(self.job_cache.get(&key).await?)
+    }
+
+    pub async fn cache_tracking_code(&self, job: &Job) -> Result<(), AppError> {
+        let key = CacheKeys::job_by_tracking_code(&job.tracking_code);
+        self.job_cache.set(&key, &job.id, Some(86400 * 30)).await?; // 30 days TTL, like the job's events
+        Ok(())
+    }
+
+    pub async fn get_trip_share(&self, token: &str) -> Result<Option<TripShare>, AppError> {
+        Ok(self.job_cache.get(&CacheKeys::trip_share(token)).await?)
+    }
+
+    pub async fn cache_trip_share(&self, share: &TripShare, ttl_seconds: u64) -> Result<(), AppError> {
+        self.job_cache.set(&CacheKeys::trip_share(&share.token), share, Some(ttl_seconds)).await?;
+        self.job_cache.sadd(&CacheKeys::job_trip_shares(&share.job_id), &share.token).await?;
+        Ok(())
+    }
+
+    pub async fn get_trip_share_tokens(&self, job_id: &JobId) -> Result<Vec<String>, AppError> {
+        Ok(self.job_cache.smembers(&CacheKeys::job_trip_shares(job_id)).await?)
+    }
+
+    pub async fn delete_trip_share(&self, job_id: &JobId, token: &str) -> Result<(), AppError> {
+        self.job_cache.delete(&CacheKeys::trip_share(token)).await?;
+        self.job_cache.srem(&CacheKeys::job_trip_shares(job_id), token).await?;
+        Ok(())
+    }
+
+    pub async fn get_job_pool(&self, pool_id: &str) -> Result<Option<JobPool>, AppError> {
+        let key = CacheKeys::job_pool(pool_id);
+        Ok(self.job_cache.get(&key).await?)
+    }
+
+    pub async fn cache_job_pool(&self, pool: &JobPool) -> Result<(), AppError> {
+        let key = CacheKeys::job_pool(&pool.id);
+        self.job_cache.set(&key, pool, Some(86400 * 7)).await?; // 7 days TTL, like batches
+        Ok(())
+    }
+
+    pub async fn cache_arrival_call(&self, call: &ArrivalCall) -> Result<(), AppError> {
+        self.job_cache.set(&CacheKeys::arrival_call(&call.call_id), call, Some(86400 * 2)).await?; // Callbacks stop within hours
+        Ok(())
+    }
+
+    pub async fn get_arrival_call(&self, call_id: &str) -> Result<Option<ArrivalCall>, AppError> {
+        Ok(self.job_cache.get(&CacheKeys::arrival_call(call_id)).await?)
+    }
+
+    pub async fn get_job_batch(&self, batch_id: &str) -> Result<Option<JobBatch>, AppError> {
+        let key = CacheKeys::job_batch(batch_id);
+        Ok(self.job_cache.get(&key).await?)
+    }
+
+    pub async fn cache_job_batch(&self, batch: &JobBatch) -> Result<(), AppError> {
+        let key = CacheKeys::job_batch(&batch.id);
+        self.job_cache.set(&key, batch, Some(86400 * 7)).await?; // 7 days TTL
+        Ok(())
+    }
+
+    pub async fn get_customer_jobs(&self, customer_id: &UserId) -> Result<Vec<JobId>, AppError> {
+        let key = CacheKeys::jobs_by_customer(customer_id);
+        Ok(parse_members(self.job_cache.smembers(&key).await?))
+    }
+
+    pub async fn cache_customer_job(&self, job: &Job) -> Result<(), AppError> {
+        self.job_cache.sadd(&CacheKeys::jobs_by_customer(&job.customer_id), job.id.as_str()).await?;
+        let history = CacheKeys::job_history_by_customer(&job.customer_id);
+        self.job_cache.zadd(&history, job.id.as_str(), history_score(job)).await.map_err(|e| e.into())
+    }
+
+    pub async fn get_driver_jobs(&self, driver_id: &DriverId) -> Result<Vec<JobId>, AppError> {
+        let key = CacheKeys::jobs_by_driver(driver_id);
+        Ok(parse_members(self.job_cache.smembers(&key).await?))
+    }
+
+    pub async fn remove_driver_job(&self, driver_id: &DriverId, job_id: &JobId) -> Result<(), AppError> {
+        self.job_cache.srem(&CacheKeys::jobs_by_driver(driver_id), job_id.as_str()).await?;
+        self.job_cache.zrem(&CacheKeys::job_history_by_driver(driver_id), job_id.as_str()).await.map_err(|e| e.into())
+    }
+
+    pub async fn cache_driver_job(&self, driver_id: &DriverId, job: &Job) -> Result<(), AppError> {
+        self.job_cache.sadd(&CacheKeys::jobs_by_driver(driver_id), job.id.as_str()).await?;
+        let history = CacheKeys::job_history_by_driver(driver_id);
+        self.job_cache.zadd(&history, job.id.as_str(), history_score(job)).await.map_err(|e| e.into())
+    }
+
+    /// One page of a customer's jobs, newest first, starting after `after`
+    pub async fn get_customer_job_page(&self, customer_id: &UserId, after: Option<&JobCursor>, limit: usize) -> Result<Vec<JobId>, AppError> {
+        let history = CacheKeys::job_history_by_customer(customer_id);
+        self.job_history_page(&history, &CacheKeys::jobs_by_customer(customer_id), after, limit).await
+    }
+
+    pub async fn get_driver_job_page(&self, driver_id: &DriverId, after: Option<&JobCursor>, limit: usize) -> Result<Vec<JobId>, AppError> {
+        let history = CacheKeys::job_history_by_driver(driver_id);
+        self.job_history_page(&history, &CacheKeys::jobs_by_driver(driver_id), after, limit).await
+    }
+
+    // Jobs tied at the cursor's timestamp are skipped up to and including the cursor's own,
+    // reading on past them in batches when there are many
+    async fn job_history_page(&self, history: &CacheKey, index: &CacheKey, after: Option<&JobCursor>, limit: usize) -> Result<Vec<JobId>, AppError> {
+        if after.is_none() {
+            self.backfill_job_history(history, index).await?;
+        }
+        let max = after.map_or(f64::INFINITY, |cursor| cursor.created_at_ms as f64);
+        let mut page = Vec::with_capacity(limit);
+        let mut offset = 0;
+        loop {
+            let batch = self.job_cache.zrevrange_by_score(history, max, offset, limit).await?;
+            let read = batch.len();
+            for (member, score) in batch {
+                let seen = after.is_some_and(|cursor| score as i64 == cursor.created_at_ms && member.as_str() >= cursor.job_id.as_str());
+                if seen {
+                    continue;
+                }
+                page.extend(parse_members::<JobId>(vec![member]));
+                if page.len() == limit {
+                    return Ok(page);
+                }
+            }
+            if read < limit {
+                return Ok(page);
+            }
+            offset += read;
+        }
+    }
+
+    // Jobs indexed before history was kept sorted are scored on the first read
+    async fn backfill_job_history(&self, history: &CacheKey, index: &CacheKey) -> Result<(), AppError> {
+        if !self.job_cache.zrevrange_by_score(history, f64::INFINITY, 0, 1).await?.is_empty() {
+            return Ok(());
+        }
+        for job_id in parse_members::<JobId>(self.job_cache.smembers(index).await?) {
+            if let Some(job) = self.load_job(&job_id).await? {
+                self.job_cache.zadd(history, job.id.as_str(), history_score(&job)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    // Tenant registry methods
+    pub async fn get_tenant(&self, tenant_id: &str) -> Result<Option<Tenant>, AppError> {
+        let key = CacheKeys::tenant_by_id(tenant_id);
+        Ok(self.user_cache.get(&key).await?)
+    }
+
+    pub async fn get_tenant_id_by_host(&self, host: &str) -> Result<Option<String>, AppError> {
+        let key = CacheKeys::tenant_by_host(host);
+        Ok(self.user_cache.get(&key).await?)
+    }
+
+    pub async fn cache_tenant(&self, tenant: &Tenant) -> Result<(), AppError> {
+        self.user_cache.set(&CacheKeys::tenant_by_id(&tenant.id), tenant, None).await?;
+        for host in &tenant.hosts {
+            self.user_cache.set(&CacheKeys::tenant_by_host(host), &tenant.id, None).await?;
+        }
+        self.user_cache.sadd(&CacheKeys::all_tenants(), &tenant.id).await?;
+        Ok(())
+    }
+
+    pub async fn get_tenant_ids(&self) -> Result<Vec<String>, AppError> {
+        let key = CacheKeys::all_tenants();
+        self.user_cache.smembers(&key).await.map_err(|e| e.into())
+    }
+
+    // API key methods
+    pub async fn get_api_key(&self, key_id: &str) -> Result<Option<ApiKey>, AppError> {
+        let key = CacheKeys::api_key_by_id(key_id);
+        let Some(mut api_key): Option<ApiKey> = self.user_cache.get(&key).await? else {
+            return Ok(None);
+        };
+        if let Some(last_used_at) = self.user_cache.get(&CacheKeys::api_key_last_used(key_id)).await? {
+            api_key.last_used_at = Some(last_used_at);
+        }
+        Ok(Some(api_key))
+    }
+
+    pub async fn record_api_key_use(&self, key_id: &str, at: DateTime<Utc>) -> Result<(), AppError> {
+        self.user_cache.set(&CacheKeys::api_key_last_used(key_id), &at, None).await?;
+        Ok(())
+    }
+
+    pub async fn get_api_key_id_by_hash(&self, secret_hash: &str) -> Result<Option<String>, AppError> {
+        let key = CacheKeys::api_key_by_hash(secret_hash);
+        Ok(self.user_cache.get(&key).await?)
+    }
+
+    // Keys do not expire from the cache; revocation is explicit
+    pub async fn cache_api_key(&self, api_key: &ApiKey) -> Result<(), AppError> {
+        self.user_cache.set(&CacheKeys::api_key_by_id(&api_key.id), api_key, None).await?;
+        self.user_cache.set(&CacheKeys::api_key_by_hash(&api_key.secret_hash), &api_key.id, None).await?;
+        self.user_cache.sadd(&CacheKeys::api_keys_by_merchant(&api_key.merchant_id), &api_key.id).await?;
+        Ok(())
+    }
+
+    pub async fn get_merchant_api_keys(&self, merchant_id: &UserId) -> Result<Vec<String>, AppError> {
+        let key = CacheKeys::api_keys_by_merchant(merchant_id);
+        self.user_cache.smembers(&key).await.map_err(|e| e.into())
+    }
+
+    // Requests made with a key in the current minute, including this one
+    pub async fn count_api_key_request(&self, key_id: &str, window: i64) -> Result<i64, AppError> {
+        let key = CacheKeys::api_key_rate_window(key_id, window);
+        Ok(self.user_cache.incr(&key, 60).await?)
+    }
+
+    pub async fn get_api_key_quota_usage(&self, key_id: &str, metric: QuotaMetric, window: &str) -> Result<u64, AppError> {
+        let key = CacheKeys::api_key_quota(key_id, metric, window);
+        let used: Option<i64> = self.user_cache.get(&key).await?;
+        Ok(used.unwrap_or(0).max(0) as u64)
+    }
+
+    /// Counts one use; `ttl_seconds` should outlast the period
+    pub async fn count_api_key_quota_usage(&self, key_id: &str, metric: QuotaMetric, window: &str, ttl_seconds: u64) -> Result<u64, AppError> {
+        let key = CacheKeys::api_key_quota(key_id, metric, window);
+        Ok(self.user_cache.incr(&key, ttl_seconds).await?.max(0) as u64)
+    }
+
+    pub async fn claim_api_key_quota_warning(&self, key_id: &str, metric: QuotaMetric, window: &str, ttl_seconds: u64) -> Result<bool, AppError> {
+        let key = CacheKeys::api_key_quota_warning(key_id, metric, window);
+        Ok(self.user_cache.incr(&key, ttl_seconds).await? == 1)
+    }
+
+    // Driver caching methods
+    pub async fn get_driver(&self, driver_id: &DriverId) -> Result<Option<Driver>, AppError> {
+        let key = CacheKeys::driver_by_id(driver_id);
+        self.open(self.driver_cache.get(&key).await?)
+    }
+
+    pub async fn cache_driver(&self, driver: &Driver) -> Result<(), AppError> {
+        let key = CacheKeys::driver_by_id(&driver.id);
+        self.driver_cache.set(&key, self.seal(driver).as_ref(), Some(86400 * 7)).await?; // 7 days TTL
+        self.driver_cache.set(&CacheKeys::driver_by_user_id(&driver.user_id), &driver.id, Some(86400 * 7)).await?;
+        self.driver_cache.sadd(&CacheKeys::all_drivers(), driver.id.as_str()).await?;
+        Ok(())
+    }
+
+    pub async fn get_driver_id_by_user_id(&self, user_id: &UserId) -> Result<Option<DriverId>, AppError> {
+        let key = CacheKeys::driver_by_user_id(user_id);
+        Ok(self.driver_cache.get(&key).await?)
+    }
+
+    pub async fn get_all_driver_ids(&self) -> Result<Vec<DriverId>, AppError> {
+        let key = CacheKeys::all_drivers();
+        Ok(parse_members(self.driver_cache.smembers(&key).await?))
+    }
+
+    // Open (non-terminal) jobs, scanned by the background workers
+    pub async fn get_active_jobs(&self) -> Result<Vec<JobId>, AppError> {
+        let key = CacheKeys::active_jobs();
+        Ok(parse_members(self.job_cache.smembers(&key).await?))
+    }
+
+    pub async fn add_active_job(&self, job_id: &JobId) -> Result<(), AppError> {
+        let key = CacheKeys::active_jobs();
+        self.job_cache.sadd(&key, job_id.as_str()).await.map_err(|e| e.into())
+    }
+
+    pub async fn remove_active_job(&self, job_id: &JobId) -> Result<(), AppError> {
+        let key = CacheKeys::active_jobs();
+        self.job_cache.srem(&key, job_id.as_str()).await.map_err(|e| e.into())
+    }
+
+    pub async fn get_completed_sla_jobs(&self) -> Result<Vec<JobId>, AppError> {
+        let key = CacheKeys::completed_sla_jobs();
+        Ok(parse_members(self.job_cache.smembers(&key).await?))
+    }
+
+    pub async fn add_completed_sla_job(&self, job_id: &JobId) -> Result<(), AppError> {
+        let key = CacheKeys::completed_sla_jobs();
+        self.job_cache.sadd(&key, job_id.as_str()).await.map_err(|e| e.into())
+    }
+
+    pub async fn remove_completed_sla_job(&self, job_id: &JobId) -> Result<(), AppError> {
+        let key = CacheKeys::completed_sla_jobs();
+        self.job_cache.srem(&key, job_id.as_str()).await.map_err(|e| e.into())
+    }
+
+    pub async fn count_online_drivers(&self) -> Result<usize, AppError> {
+        let key = CacheKeys::online_drivers();
+        Ok(self.driver_cache.smembers(&key).await?.len())
+    }
+
+    // Drivers with a live WebSocket open
+    pub async fn get_online_driver_ids(&self) -> Result<Vec<DriverId>, AppError> {
+        let key = CacheKeys::online_drivers();
+        Ok(parse_members(self.driver_cache.smembers(&key).await?))
+    }
+
+    // Everyone of `kind` with a live connection open, on any instance
+    pub async fn get_present_ids(&self, kind: PresenceKind) -> Result<Vec<String>, AppError> {
+        Ok(self.driver_cache.smembers(&CacheKeys::online(kind)).await?)
+    }
+
+    pub async fn get_presence(&self, kind: PresenceKind, id: &str) -> Result<Option<PresenceEntry>, AppError> {
+        let key = CacheKeys::presence_connection(kind, id);
+        Ok(self.driver_cache.get(&key).await?)
+    }
+
+    // The entry expires unless refreshed, so a crashed instance's connections fade out
+    pub async fn cache_presence(&self, entry: &PresenceEntry, ttl_seconds: u64) -> Result<(), AppError> {
+        let key = CacheKeys::presence_connection(entry.kind, &entry.id);
+        self.driver_cache.set(&key, entry, Some(ttl_seconds)).await?;
+        self.driver_cache.sadd(&CacheKeys::online(entry.kind), &entry.id).await?;
+        Ok(())
+    }
+
+    pub async fn remove_presence(&self, kind: PresenceKind, id: &str) -> Result<(), AppError> {
+        self.driver_cache.delete(&CacheKeys::presence_connection(kind, id)).await?;
+        self.driver_cache.srem(&CacheKeys::online(kind), id).await?;
+        Ok(())
+    }
+
+    // Shared by every instance, so an offer made on one can be answered on another
+    pub async fn cache_offer(&self, driver_id: &DriverId, job_id: &JobId, expires_at: DateTime<Utc>, ttl_seconds: u64) -> Result<(), AppError> {
+        self.driver_cache.set(&CacheKeys::driver_offer(driver_id, job_id), &expires_at, Some(ttl_seconds)).await?;
+        self.driver_cache.sadd(&CacheKeys::offers_by_driver(driver_id), job_id.as_str()).await?;
+        self.driver_cache.sadd(&CacheKeys::offers_by_job(job_id), driver_id.as_str()).await?;
+        Ok(())
+    }
+
+    // Close an offer, returning its deadline; None when it was never made or already closed
+    pub async fn take_offer(&self, driver_id: &DriverId, job_id: &JobId) -> Result<Option<DateTime<Utc>>, AppError> {
+        let key = CacheKeys::driver_offer(driver_id, job_id);
+        let expires_at = self.driver_cache.get(&key).await?;
+        self.driver_cache.delete(&key).await?;
+        self.driver_cache.srem(&CacheKeys::offers_by_driver(driver_id), job_id.as_str()).await?;
+        self.driver_cache.srem(&CacheKeys::offers_by_job(job_id), driver_id.as_str()).await?;
+        Ok(expires_at)
+    }
+
+    pub async fn get_offer_deadline(&self, driver_id: &DriverId, job_id: &JobId) -> Result<Option<DateTime<Utc>>, AppError> {
+        Ok(self.driver_cache.get(&CacheKeys::driver_offer(driver_id, job_id)).await?)
+    }
+
+    pub async fn get_driver_offer_ids(&self, driver_id: &DriverId) -> Result<Vec<JobId>, AppError> {
+        Ok(parse_members(self.driver_cache.smembers(&CacheKeys::offers_by_driver(driver_id)).await?))
+    }
+
+    pub async fn get_job_offer_driver_ids(&self, job_id: &JobId) -> Result<Vec<DriverId>, AppError> {
+        Ok(parse_members(self.driver_cache.smembers(&CacheKeys::offers_by_job(job_id)).await?))
+    }
+
+    pub async fn cache_driver_block(&self, user_id: &UserId, driver_id: &DriverId) -> Result<(), AppError> {
+        Ok(self.user_cache.sadd(&CacheKeys::drivers_blocked_by(user_id), driver_id.as_str()).await?)
+    }
+
+    pub async fn remove_driver_block(&self, user_id: &UserId, driver_id: &DriverId) -> Result<(), AppError> {
+        Ok(self.user_cache.srem(&CacheKeys::drivers_blocked_by(user_id), driver_id.as_str()).await?)
+    }
+
+    pub async fn get_drivers_blocked_by(&self, user_id: &UserId) -> Result<Vec<DriverId>, AppError> {
+        Ok(parse_members(self.user_cache.smembers(&CacheKeys::drivers_blocked_by(user_id)).await?))
+    }
+
+    pub async fn cache_customer_block(&self, driver_id: &DriverId, user_id: &UserId) -> Result<(), AppError> {
+        Ok(self.driver_cache.sadd(&CacheKeys::customers_blocked_by(driver_id), user_id.as_str()).await?)
+    }
+
+    pub async fn remove_customer_block(&self, driver_id: &DriverId, user_id: &UserId) -> Result<(), AppError> {
+        Ok(self.driver_cache.srem(&CacheKeys::customers_blocked_by(driver_id), user_id.as_str()).await?)
+    }
+
+    pub async fn get_customers_blocked_by(&self, driver_id: &DriverId) -> Result<Vec<UserId>, AppError> {
+        Ok(parse_members(self.driver_cache.smembers(&CacheKeys::customers_blocked_by(driver_id)).await?))
+    }
+
+    // Whether either of the two has blocked the other
+    pub async fn is_pair_blocked(&self, driver_id: &DriverId, customer_id: &UserId) -> Result<bool, AppError> {
+        Ok(self.get_drivers_blocked_by(customer_id).await?.contains(driver_id)
+            || self.get_customers_blocked_by(driver_id).await?.contains(customer_id))
+    }
+
+    pub async fn get_surge_multipliers(&self) -> Result<BTreeMap<String, f64>, AppError> {
+        let key = CacheKeys::surge_multipliers();
+        let multipliers: Option<BTreeMap<String, f64>> = self.job_cache.get(&key).await?;
+        Ok(multipliers.unwrap_or_default())
+    }
+
+    pub async fn cache_surge_multipliers(&self, multipliers: &BTreeMap<String, f64>, ttl_seconds: u64) -> Result<(), AppError> {
+        let key = CacheKeys::surge_multipliers();
+        self.job_cache.set(&key, multipliers, Some(ttl_seconds)).await?;
+        Ok(())
+    }
+
+    // Admin-managed, so kept until replaced
+    pub async fn get_commission_config(&self) -> Result<Option<CommissionConfig>, AppError> {
+        let key = CacheKeys::commission_config();
+        Ok(self.job_cache.get(&key).await?)
+    }
+
+    pub async fn cache_commission_config(&self, config: &CommissionConfig) -> Result<(), AppError> {
+        let key = CacheKeys::commission_config();
+        self.job_cache.set(&key, config, None).await?;
+        Ok(())
+    }
+
+    // Admin-managed, so kept until replaced
+    pub async fn get_dispatch_settings(&self) -> Result<Option<DispatchSettings>, AppError> {
+        let key = CacheKeys::dispatch_settings();
+        Ok(self.job_cache.get(&key).await?)
+    }
+
+    pub async fn cache_dispatch_settings(&self, settings: &DispatchSettings) -> Result<(), AppError> {
+        let key = CacheKeys::dispatch_settings();
+        self.job_cache.set(&key, settings, None).await?;
+        Ok(())
+    }
+
+    // Admin-managed, so kept until replaced
+    pub async fn get_retention_settings(&self) -> Result<Option<RetentionSettings>, AppError> {
+        let key = CacheKeys::retention_settings();
+        Ok(self.job_cache.get(&key).await?)
+    }
+
+    pub async fn cache_retention_settings(&self, settings: &RetentionSettings) -> Result<(), AppError> {
+        let key = CacheKeys::retention_settings();
+        self.job_cache.set(&key, settings, None).await?;
+        Ok(())
+    }
+
+    pub async fn get_retention_cursor(&self, class: DataClass) -> Result<Option<NaiveDate>, AppError> {
+        let key = CacheKeys::retention_cursor(class);
+        Ok(self.job_cache.get(&key).await?)
+    }
+
+    pub async fn set_retention_cursor(&self, class: DataClass, day: NaiveDate) -> Result<(), AppError> {
+        let key = CacheKeys::retention_cursor(class);
+        self.job_cache.set(&key, &day, None).await?;
+        Ok(())
+    }
+
+    pub async fn get_legal_hold(&self, job_id: &JobId) -> Result<Option<LegalHold>, AppError> {
+        let key = CacheKeys::legal_hold(job_id);
+        Ok(self.job_cache.get(&key).await?)
+    }
+
+    pub async fn get_legal_holds(&self) -> Result<Vec<JobId>, AppError> {
+        Ok(parse_members(self.job_cache.smembers(&CacheKeys::legal_holds()).await?))
+    }
+
+    // Holds last until released
+    pub async fn cache_legal_hold(&self, hold: &LegalHold) -> Result<(), AppError> {
+        self.job_cache.set(&CacheKeys::legal_hold(&hold.job_id), hold, None).await?;
+        self.job_cache.sadd(&CacheKeys::legal_holds(), hold.job_id.as_str()).await?;
+        Ok(())
+    }
+
+    pub async fn delete_legal_hold(&self, job_id: &JobId) -> Result<(), AppError> {
+        self.job_cache.delete(&CacheKeys::legal_hold(job_id)).await?;
+        self.job_cache.srem(&CacheKeys::legal_holds(), job_id.as_str()).await?;
+        Ok(())
+    }
+
+    pub async fn get_service_zone(&self, zone_id: &str) -> Result<Option<ServiceZone>, AppError> {
+        let key = CacheKeys::service_zone(zone_id);
+        Ok(self.job_cache.get(&key).await?)
+    }
+
+    pub async fn get_service_zone_ids(&self) -> Result<Vec<String>, AppError> {
+        Ok(self.job_cache.smembers(&CacheKeys::service_zones()).await?)
+    }
+
+    pub async fn cache_service_zone(&self, zone: &ServiceZone) -> Result<(), AppError> {
+        self.job_cache.set(&CacheKeys::service_zone(&zone.id), zone, None).await?;
+        self.job_cache.sadd(&CacheKeys::service_zones(), &zone.id).await?;
+        Ok(())
+    }
+
+    pub async fn delete_service_zone(&self, zone_id: &str) -> Result<(), AppError> {
+        self.job_cache.delete(&CacheKeys::service_zone(zone_id)).await?;
+        self.job_cache.srem(&CacheKeys::service_zones(), zone_id).await?;
+        Ok(())
+    }
+
+    pub async fn get_zones_generation(&self) -> Result<Option<i64>, AppError> {
+        Ok(self.job_cache.get(&CacheKeys::zones_generation()).await?)
+    }
+
+    pub async fn set_zones_generation(&self, generation: i64) -> Result<(), AppError> {
+        self.job_cache.set(&CacheKeys::zones_generation(), &generation, None).await?;
+        Ok(())
+    }
+
+    pub async fn get_calendar_periods(&self) -> Result<Vec<CalendarPeriod>, AppError> {
+        Ok(self.job_cache.get(&CacheKeys::calendar_periods()).await?.unwrap_or_default())
+    }
+
+    pub async fn cache_calendar_periods(&self, periods: &Vec<CalendarPeriod>) -> Result<(), AppError> {
+        self.job_cache.set(&CacheKeys::calendar_periods(), periods, None).await?;
+        Ok(())
+    }
+
+    pub async fn get_tax_schedules(&self) -> Result<Option<Vec<TaxSchedule>>, AppError> {
+        let key = CacheKeys::tax_schedules();
+        Ok(self.job_cache.get(&key).await?)
+    }
+
+    pub async fn cache_tax_schedules(&self, schedules: &Vec<TaxSchedule>) -> Result<(), AppError> {
+        let key = CacheKeys::tax_schedules();
+        self.job_cache.set(&key, schedules, None).await?;
+        Ok(())
+    }
+
+    pub async fn get_exchange_rates(&self) -> Result<Option<ExchangeRates>, AppError> {
+        let key = CacheKeys::exchange_rates();
+        Ok(self.job_cache.get(&key).await?)
+    }
+
+    pub async fn cache_exchange_rates(&self, rates: &ExchangeRates) -> Result<(), AppError> {
+        let key = CacheKeys::exchange_rates();
+        self.job_cache.set(&key, rates, None).await?;
+        Ok(())
+    }
+
+    pub async fn get_notification_templates(&self) -> Result<Vec<NotificationTemplate>, AppError> {
+        let key = CacheKeys::notification_templates();
+        let templates: Option<Vec<NotificationTemplate>> = self.user_cache.get(&key).await?;
+        Ok(templates.unwrap_or_default())
+    }
+
+    pub async fn cache_notification_templates(&self, templates: &Vec<NotificationTemplate>) -> Result<(), AppError> {
+        let key = CacheKeys::notification_templates();
+        self.user_cache.set(&key, templates, None).await?;
+        Ok(())
+    }
+
+    // Admin dashboard snapshot - short TTL, dashboards poll it
+    pub async fn get_dashboard(&self, stale_after_minutes: i64) -> Result<Option<OperationsDashboard>, AppError> {
+        let key = CacheKeys::admin_dashboard(stale_after_minutes);
+        Ok(self.job_cache.get(&key).await?)
+    }
+
+    pub async fn cache_dashboard(&self, dashboard: &OperationsDashboard, ttl_seconds: u64) -> Result<(), AppError> {
+        let key = CacheKeys::admin_dashboard(dashboard.stale_after_minutes);
+        self.job_cache.set(&key, dashboard, Some(ttl_seconds)).await?;
+        Ok(())
+    }
+
+    // Demand analytics
+    pub async fn record_pickup(&self, point: &DemandPoint) -> Result<(), AppError> {
+        let key = CacheKeys::demand_pickups(&point.created_at);
+        let json = serde_json::to_string(point)?;
+        self.job_cache.rpush(&key, &json, Some(86400 * 35)).await?; // Five weeks feeds the forecast
+        Ok(())
+    }
+
+    pub async fn get_pickups_for_hour(&self, hour: &DateTime<Utc>) -> Result<Vec<DemandPoint>, AppError> {
+        let key = CacheKeys::demand_pickups(hour);
+        let raw = self.job_cache.lrange(&key, 0, -1).await?;
+        raw.iter()
+            .map(|json| serde_json::from_str(json).map_err(AppError::from))
+            .collect()
+    }
+
+    pub async fn get_demand_forecast(&self) -> Result<Option<DemandForecast>, AppError> {
+        let key = CacheKeys::demand_forecast();
+        Ok(self.job_cache.get(&key).await?)
+    }
+
+    pub async fn cache_demand_forecast(&self, forecast: &DemandForecast) -> Result<(), AppError> {
+        let key = CacheKeys::demand_forecast();
+        self.job_cache.set(&key, forecast, Some(86400 * 7)).await?;
+        Ok(())
+    }
+
+    // Goodwill and promotional credits
+    pub async fn append_user_credit(&self, credit: &UserCredit) -> Result<(), AppError> {
+        let key = CacheKeys::user_credits(&credit.user_id);
+        let json = serde_json::to_string(credit)?;
+        self.user_cache.rpush(&key, &json, None).await?;
+        Ok(())
+    }
+
+    pub async fn get_user_credits(&self, user_id: &UserId) -> Result<Vec<UserCredit>, AppError> {
+        let key = CacheKeys::user_credits(user_id);
+        let raw = self.user_cache.lrange(&key, 0, -1).await?;
+        raw.iter()
+            .map(|json| serde_json::from_str(json).map_err(AppError::from))
+            .collect()
+    }
+
+    pub async fn queue_digest_item(&self, user_id: &UserId, item: &DigestItem) -> Result<(), AppError> {
+        let key = CacheKeys::notification_digest(user_id);
+        let json = serde_json::to_string(item)?;
+        self.user_cache.rpush(&key, &json, Some(86400 * 7)).await?;
+        self.user_cache.sadd(&CacheKeys::pending_digests(), user_id.as_str()).await?;
+        Ok(())
+    }
+
+    pub async fn get_pending_digest_users(&self) -> Result<Vec<UserId>, AppError> {
+        let key = CacheKeys::pending_digests();
+        Ok(parse_members(self.user_cache.smembers(&key).await?))
+    }
+
+    // Everything queued for the user, oldest first, leaving the digest empty
+    pub async fn take_digest_items(&self, user_id: &UserId) -> Result<Vec<DigestItem>, AppError> {
+        let key = CacheKeys::notification_digest(user_id);
+        let raw = self.user_cache.lrange(&key, 0, -1).await?;
+        self.user_cache.delete(&key).await?;
+        self.user_cache.srem(&CacheKeys::pending_digests(), user_id.as_str()).await?;
+        raw.iter()
+            .map(|json| serde_json::from_str(json).map_err(AppError::from))
+            .collect()
+    }
+
+    pub async fn queue_deferred_notification(&self, user_id: &UserId, deferred: &DeferredNotification) -> Result<(), AppError> {
+        let key = CacheKeys::deferred_notifications(user_id);
+        let json = serde_json::to_string(deferred)?;
+        self.user_cache.rpush(&key, &json, Some(86400 * 2)).await?;
+        self.user_cache.sadd(&CacheKeys::pending_deferred(), user_id.as_str()).await?;
+        Ok(())
+    }
+
+    pub async fn get_deferred_users(&self) -> Result<Vec<UserId>, AppError> {
+        let key = CacheKeys::pending_deferred();
+        Ok(parse_members(self.user_cache.smembers(&key).await?))
+    }
+
+    // Everything held for the user, oldest first, leaving nothing held
+    pub async fn take_deferred_notifications(&self, user_id: &UserId) -> Result<Vec<DeferredNotification>, AppError> {
+        let key = CacheKeys::deferred_notifications(user_id);
+        let raw = self.user_cache.lrange(&key, 0, -1).await?;
+        self.user_cache.delete(&key).await?;
+        self.user_cache.srem(&CacheKeys::pending_deferred(), user_id.as_str()).await?;
+        raw.iter()
+            .map(|json| serde_json::from_str(json).map_err(AppError::from))
+            .collect()
+    }
+
+    pub async fn get_topic_subscriptions(&self, user_id: &UserId) -> Result<Option<TopicSubscriptions>, AppError> {
+        let key = CacheKeys::topic_subscriptions(user_id);
+        Ok(self.user_cache.get(&key).await?)
+    }
+
+    pub async fn cache_topic_subscriptions(&self, subscriptions: &TopicSubscriptions) -> Result<(), AppError> {
+        let key = CacheKeys::topic_subscriptions(&subscriptions.user_id);
+        self.user_cache.set(&key, subscriptions, None).await?;
+        Ok(())
+    }
+
+    pub async fn add_zone_language(&self, zone_slug: &str, language: &str) -> Result<(), AppError> {
+        let key = CacheKeys::zone_languages(zone_slug);
+        self.user_cache.sadd(&key, language).await.map_err(|e| e.into())
+    }
+
+    pub async fn get_zone_languages(&self, zone_slug: &str) -> Result<Vec<String>, AppError> {
+        let key = CacheKeys::zone_languages(zone_slug);
+        self.user_cache.smembers(&key).await.map_err(|e| e.into())
+    }
+
+    // Admin-managed; scheduled and sent broadcasts, oldest first
+    pub async fn get_broadcasts(&self) -> Result<Vec<Broadcast>, AppError> {
+        let key = CacheKeys::broadcasts();
+        let broadcasts: Option<Vec<Broadcast>> = self.job_cache.get(&key).await?;
+        Ok(broadcasts.unwrap_or_default())
+    }
+
+    pub async fn cache_broadcasts(&self, broadcasts: &Vec<Broadcast>) -> Result<(), AppError> {
+        let key = CacheKeys::broadcasts();
+        self.job_cache.set(&key, broadcasts, None).await?;
+        Ok(())
+    }
+
+    // Notifications of `kind` sent to `recipient` in the current window, including this one
+    pub async fn count_notification(&self, kind: &str, recipient: &str, window_seconds: i64) -> Result<i64, AppError> {
+        let window = Utc::now().timestamp() / window_seconds;
+        let key = CacheKeys::notification_cap_window(kind, recipient, window);
+        Ok(self.user_cache.incr(&key, window_seconds as u64).await?)
+    }
+
+    // Location caching methods
+    pub async fn cache_driver_locations(&self, locations: &[(DriverId, LocationUpdate)]) -> Result<(), AppError> {
+        let members: Vec<(String, f64, f64)> = locations
+            .iter()
+            .map(|(driver_id, location)| (driver_id.to_string(), location.longitude, location.latitude))
+            .collect();
+        self.driver_cache
+            .geoadd(&CacheKeys::driver_locations_geo(), &members)
+            .await?;
+
+        // Keep the full point (heading, speed, accuracy) next to the GEO index
+        for (driver_id, location) in locations {
+            let key = CacheKeys::driver_location(driver_id);
+            self.driver_cache.set(&key, location, Some(300)).await?; // 5 minutes TTL
+        }
+        Ok(())
+    }
+
+    pub async fn get_driver_location(&self, driver_id: &DriverId) -> Result<Option<LocationUpdate>, AppError> {
+        let key = CacheKeys::driver_location(driver_id);
+        Ok(self.driver_cache.get(&key).await?)
+    }
+
+    // When each driver was last heard from
+    pub async fn cache_driver_last_seen(&self, last_seen: &[(DriverId, DateTime<Utc>)]) -> Result<(), AppError> {
+        for (driver_id, at) in last_seen {
+            let key = CacheKeys::driver_last_seen(driver_id);
+            self.driver_cache.set(&key, at, Some(86400)).await?; // 24 hours TTL
+        }
+        Ok(())
+    }
+
+    pub async fn get_driver_last_seen(&self, driver_id: &DriverId) -> Result<Option<DateTime<Utc>>, AppError> {
+        let key = CacheKeys::driver_last_seen(driver_id);
+        Ok(self.driver_cache.get(&key).await?)
+    }
+
+    // Append-only dispatch history per driver, oldest first; feeds the reliability score
+    pub async fn append_dispatch_outcome(&self, driver_id: &DriverId, outcome: &DispatchOutcome) -> Result<(), AppError> {
+        let key = CacheKeys::driver_outcomes(driver_id);
+        let json = serde_json::to_string(outcome)?;
+        self.driver_cache.rpush(&key, &json, Some(86400 * 30)).await?; // 30 days TTL
+        Ok(())
+    }
+
+    pub async fn get_dispatch_outcomes(&self, driver_id: &DriverId) -> Result<Vec<DispatchOutcome>, AppError> {
+        let key = CacheKeys::driver_outcomes(driver_id);
+        let raw = self.driver_cache.lrange(&key, 0, -1).await?;
+        raw.iter()
+            .map(|json| serde_json::from_str(json).map_err(AppError::from))
+            .collect()
+    }
+
+    pub async fn get_driver_reliability(&self, driver_id: &DriverId) -> Result<Option<DriverReliability>, AppError> {
+        let key = CacheKeys::driver_reliability(driver_id);
+        Ok(self.driver_cache.get(&key).await?)
+    }
+
+    pub async fn cache_driver_reliability(&self, driver_id: &DriverId, reliability: &DriverReliability) -> Result<(), AppError> {
+        let key = CacheKeys::driver_reliability(driver_id);
+        self.driver_cache.set(&key, reliability, Some(86400 * 7)).await?; // 7 days TTL, refreshed by the analytics worker
+        Ok(())
+    }
+
+    pub async fn get_odometer_anchor(&self, driver_id: &DriverId) -> Result<Option<LocationUpdate>, AppError> {
+        Ok(self.driver_cache.get(&CacheKeys::odometer_anchor(driver_id)).await?)
+    }
+
+    pub async fn set_odometer_anchor(&self, driver_id: &DriverId, anchor: &LocationUpdate) -> Result<(), AppError> {
+        // Gone by the next day; the first point then starts afresh
+        self.driver_cache.set(&CacheKeys::odometer_anchor(driver_id), anchor, Some(86400)).await?;
+        Ok(())
+    }
+
+    pub async fn get_driver_distance(&self, driver_id: &DriverId, date: &NaiveDate) -> Result<f64, AppError> {
+        Ok(self.driver_cache.get(&CacheKeys::driver_distance(driver_id, date)).await?.unwrap_or(0.0))
+    }
+
+    pub async fn add_driver_distance(&self, driver_id: &DriverId, date: &NaiveDate, km: f64) -> Result<f64, AppError> {
+        let key = CacheKeys::driver_distance(driver_id, date);
+        let current: Option<f64> = self.driver_cache.get(&key).await?;
+        let total = current.unwrap_or(0.0) + km;
+        self.driver_cache.set(&key, &total, Some(86400 * 120)).await?; // 120 days TTL
+        Ok(total)
+    }
+
+    pub async fn get_vehicle_odometer(&self, vehicle_id: &str) -> Result<Option<VehicleOdometer>, AppError> {
+        Ok(self.driver_cache.get(&CacheKeys::vehicle_odometer(vehicle_id)).await?)
+    }
+
+    pub async fn cache_vehicle_odometer(&self, odometer: &VehicleOdometer) -> Result<(), AppError> {
+        self.driver_cache.set(&CacheKeys::vehicle_odometer(&odometer.vehicle_id), odometer, None).await?;
+        Ok(())
+    }
+
+    // Counted over a window that starts with the driver's first strike
+    pub async fn add_spoof_strike(&self, driver_id: &DriverId, window_seconds: u64) -> Result<u32, AppError> {
+        Ok(self.driver_cache.incr(&CacheKeys::spoof_strikes(driver_id), window_seconds).await? as u32)
+    }
+
+    pub async fn get_spoof_strikes(&self, driver_id: &DriverId) -> Result<u32, AppError> {
+        let strikes: Option<i64> = self.driver_cache.get(&CacheKeys::spoof_strikes(driver_id)).await?;
+        Ok(strikes.unwrap_or(0) as u32)
+    }
+
+    pub async fn quarantine_location(&self, driver_id: &DriverId, point: &QuarantinedLocation, ttl_seconds: u64) -> Result<(), AppError> {
+        let json = serde_json::to_string(point)?;
+        self.driver_cache.rpush(&CacheKeys::quarantined_locations(driver_id), &json, Some(ttl_seconds)).await?;
+        Ok(())
+    }
+
+    // The last `limit` points, oldest first
+    pub async fn get_quarantined_locations(&self, driver_id: &DriverId, limit: usize) -> Result<Vec<QuarantinedLocation>, AppError> {
+        let raw = self.driver_cache.lrange(&CacheKeys::quarantined_locations(driver_id), -(limit as isize), -1).await?;
+        Ok(raw.iter().filter_map(|json| serde_json::from_str(json).ok()).collect())
+    }
+
+    pub async fn get_risk_flag(&self, driver_id: &DriverId) -> Result<Option<RiskFlag>, AppError> {
+        Ok(self.driver_cache.get(&CacheKeys::risk_flag(driver_id)).await?)
+    }
+
+    pub async fn get_flagged_driver_ids(&self) -> Result<Vec<DriverId>, AppError> {
+        let ids = self.driver_cache.smembers(&CacheKeys::risk_flags()).await?;
+        Ok(ids.into_iter().filter_map(|id| DriverId::parse(&id).ok()).collect())
+    }
+
+    pub async fn cache_risk_flag(&self, flag: &RiskFlag) -> Result<(), AppError> {
+        self.driver_cache.set(&CacheKeys::risk_flag(&flag.driver_id), flag, None).await?;
+        self.driver_cache.sadd(&CacheKeys::risk_flags(), flag.driver_id.as_str()).await?;
+        Ok(())
+    }
+
+    // Also starts the driver's strike count afresh
+    pub async fn clear_risk_flag(&self, driver_id: &DriverId) -> Result<(), AppError> {
+        self.driver_cache.delete(&CacheKeys::risk_flag(driver_id)).await?;
+        self.driver_cache.delete(&CacheKeys::spoof_strikes(driver_id)).await?;
+        self.driver_cache.srem(&CacheKeys::risk_flags(), driver_id.as_str()).await?;
+        Ok(())
+    }
+
+    // Manual dispatch actions, oldest first
+    pub async fn append_dispatch_audit(&self, entry: &DispatchAuditEntry) -> Result<(), AppError> {
+        let json = serde_json::to_string(entry)?;
+        self.job_cache.rpush(&CacheKeys::dispatch_audit(), &json, Some(86400 * 90)).await?; // 90 days TTL
+        Ok(())
+    }
+
+    pub async fn get_dispatch_audit(&self) -> Result<Vec<DispatchAuditEntry>, AppError> {
+        let raw = self.job_cache.lrange(&CacheKeys::dispatch_audit(), 0, -1).await?;
+        raw.iter()
+            .map(|json| serde_json::from_str(json).map_err(AppError::from))
+            .collect()
+    }
+
+    // The entry already recorded under `posting_key`, if any
+    pub async fn get_ledger_entry_by_key(&self, posting_key: &str) -> Result<Option<LedgerEntry>, AppError> {
+        Ok(self.job_cache.get(&CacheKeys::ledger_posting(posting_key)).await?)
+    }
+
+    // True for the first caller to claim `posting_key`; the claim lapses after `ttl_seconds`
+    // so a poster that died midway doesn't hold the key forever
+    pub async fn claim_ledger_posting(&self, posting_key: &str, ttl_seconds: u64) -> Result<bool, AppError> {
+        let key = CacheKeys::ledger_posting_claim(posting_key);
+        Ok(self.job_cache.incr(&key, ttl_seconds).await? == 1)
+    }
+
+    // Entries are never expired; the ledger is the record of money owed
+    pub async fn append_ledger_entry(&self, entry: &LedgerEntry) -> Result<(), AppError> {
+        let json = serde_json::to_string(entry)?;
+        self.job_cache.rpush(&CacheKeys::ledger_journal(), &json, None).await?;
+        let mut accounts: Vec<&LedgerAccount> = Vec::new();
+        for posting in &entry.postings {
+            if !accounts.contains(&&posting.account) {
+                accounts.push(&posting.account);
+            }
+        }
+        for account in accounts {
+            self.job_cache.rpush(&CacheKeys::ledger_account(account), &json, None).await?;
+        }
+        // Written last, so a key only resolves once the entry is fully recorded
+        self.job_cache.set(&CacheKeys::ledger_posting(&entry.posting_key), entry, None).await?;
+        Ok(())
+    }
+
+    // Every entry, oldest first
+    pub async fn get_ledger_journal(&self) -> Result<Vec<LedgerEntry>, AppError> {
+        let raw = self.job_cache.lrange(&CacheKeys::ledger_journal(), 0, -1).await?;
+        raw.iter()
+            .map(|json| serde_json::from_str(json).map_err(AppError::from))
+            .collect()
+    }
+
+    // Entries touching `account`, oldest first
+    pub async fn get_ledger_entries(&self, account: &LedgerAccount) -> Result<Vec<LedgerEntry>, AppError> {
+        let raw = self.job_cache.lrange(&CacheKeys::ledger_account(account), 0, -1).await?;
+        raw.iter()
+            .map(|json| serde_json::from_str(json).map_err(AppError::from))
+            .collect()
+    }
+
+    // Reconciliation queue, oldest first; resolved discrepancies stay as the audit trail
+    pub async fn get_discrepancies(&self) -> Result<Vec<Discrepancy>, AppError> {
+        Ok(self.job_cache.get(&CacheKeys::reconciliation_discrepancies()).await?.unwrap_or_default())
+    }
+
+    pub async fn cache_discrepancies(&self, discrepancies: &Vec<Discrepancy>) -> Result<(), AppError> {
+        self.job_cache.set(&CacheKeys::reconciliation_discrepancies(), discrepancies, None).await?;
+        Ok(())
+    }
+
+    pub async fn get_last_reconciled_day(&self) -> Result<Option<NaiveDate>, AppError> {
+        Ok(self.job_cache.get(&CacheKeys::last_reconciled_day()).await?)
+    }
+
+    pub async fn set_last_reconciled_day(&self, day: NaiveDate) -> Result<(), AppError> {
+        self.job_cache.set(&CacheKeys::last_reconciled_day(), &day, None).await?;
+        Ok(())
+    }
+
+    pub async fn get_dispute(&self, case_id: &str) -> Result<Option<DisputeCase>, AppError> {
+        Ok(self.job_cache.get(&CacheKeys::dispute(case_id)).await?)
+    }
+
+    pub async fn get_dispute_ids(&self) -> Result<Vec<String>, AppError> {
+        Ok(self.job_cache.smembers(&CacheKeys::disputes()).await?)
+    }
+
+    pub async fn cache_dispute(&self, case: &DisputeCase) -> Result<(), AppError> {
+        self.job_cache.set(&CacheKeys::dispute(&case.id), case, None).await?;
+        self.job_cache.sadd(&CacheKeys::disputes(), &case.id).await?;
+        Ok(())
+    }
+
+    pub async fn get_incident(&self, incident_id: &str) -> Result<Option<Incident>, AppError> {
+        Ok(self.job_cache.get(&CacheKeys::incident(incident_id)).await?)
+    }
+
+    pub async fn get_incident_ids(&self) -> Result<Vec<String>, AppError> {
+        Ok(self.job_cache.smembers(&CacheKeys::incidents()).await?)
+    }
+
+    pub async fn cache_incident(&self, incident: &Incident) -> Result<(), AppError> {
+        self.job_cache.set(&CacheKeys::incident(&incident.id), incident, None).await?;
+        self.job_cache.sadd(&CacheKeys::incidents(), &incident.id).await?;
+        Ok(())
+    }
+
+    // True for the first delivery of a provider's notice; providers give up retrying well within a month
+    pub async fn claim_chargeback_event(&self, provider: &str, event_id: &str) -> Result<bool, AppError> {
+        let key = CacheKeys::chargeback_event_claim(provider, event_id);
+        Ok(self.job_cache.incr(&key, 86400 * 30).await? == 1)
+    }
+
+    pub async fn get_chargeback_event_case(&self, provider: &str, event_id: &str) -> Result<Option<String>, AppError> {
+        Ok(self.job_cache.get(&CacheKeys::chargeback_event(provider, event_id)).await?)
+    }
+
+    pub async fn cache_chargeback_event(&self, provider: &str, event_id: &str, case_id: &str) -> Result<(), AppError> {
+        let key = CacheKeys::chargeback_event(provider, event_id);
+        self.job_cache.set(&key, &case_id.to_string(), None).await?;
+        Ok(())
+    }
+
+    pub async fn get_feed_settings(&self, consumer_id: &UserId) -> Result<Option<FeedSettings>, AppError> {
+        Ok(self.job_cache.get(&CacheKeys::feed_settings(consumer_id)).await?)
+    }
+
+    pub async fn cache_feed_settings(&self, settings: &FeedSettings) -> Result<(), AppError> {
+        self.job_cache.set(&CacheKeys::feed_settings(&settings.consumer_id), settings, None).await?;
+        Ok(())
+    }
+
+    pub async fn get_billing_account(&self, merchant_id: &UserId) -> Result<Option<BillingAccount>, AppError> {
+        Ok(self.job_cache.get(&CacheKeys::billing_account(merchant_id)).await?)
+    }
+
+    pub async fn cache_billing_account(&self, account: &BillingAccount) -> Result<(), AppError> {
+        self.job_cache.set(&CacheKeys::billing_account(&account.merchant_id), account, None).await?;
+        Ok(())
+    }
+
+    pub async fn append_invoice_line(&self, merchant_id: &UserId, period: &str, line: &InvoiceLine) -> Result<(), AppError> {
+        let json = serde_json::to_string(line)?;
+        self.job_cache.rpush(&CacheKeys::invoice_draft_lines(merchant_id, period), &json, None).await?;
+        self.job_cache.sadd(&CacheKeys::invoice_draft_periods(merchant_id), period).await?;
+        self.job_cache.sadd(&CacheKeys::invoice_draft_merchants(), merchant_id.as_str()).await?;
+        Ok(())
+    }
+
+    pub async fn get_invoice_draft_merchants(&self) -> Result<Vec<UserId>, AppError> {
+        Ok(parse_members(self.job_cache.smembers(&CacheKeys::invoice_draft_merchants()).await?))
+    }
+
+    pub async fn get_invoice_draft_periods(&self, merchant_id: &UserId) -> Result<Vec<String>, AppError> {
+        Ok(self.job_cache.smembers(&CacheKeys::invoice_draft_periods(merchant_id)).await?)
+    }
+
+    pub async fn get_invoice_draft_lines(&self, merchant_id: &UserId, period: &str) -> Result<Vec<InvoiceLine>, AppError> {
+        let raw = self.job_cache.lrange(&CacheKeys::invoice_draft_lines(merchant_id, period), 0, -1).await?;
+        raw.iter()
+            .map(|json| serde_json::from_str(json).map_err(AppError::from))
+            .collect()
+    }
+
+    // True for whoever gets to issue the month; expires so a crashed run is retried
+    pub async fn claim_invoice_issue(&self, merchant_id: &UserId, period: &str) -> Result<bool, AppError> {
+        let key = CacheKeys::invoice_issue_claim(merchant_id, period);
+        Ok(self.job_cache.incr(&key, 3600).await? == 1)
+    }
+
+    // The month is off the drafts list before its lines go, so a retry never sees it half-cleared
+    pub async fn clear_invoice_draft(&self, merchant_id: &UserId, period: &str) -> Result<(), AppError> {
+        self.job_cache.srem(&CacheKeys::invoice_draft_periods(merchant_id), period).await?;
+        self.job_cache.delete(&CacheKeys::invoice_draft_lines(merchant_id, period)).await?;
+        Ok(())
+    }
+
+    pub async fn get_invoice(&self, invoice_id: &str) -> Result<Option<Invoice>, AppError> {
+        Ok(self.job_cache.get(&CacheKeys::invoice(invoice_id)).await?)
+    }
+
+    pub async fn get_invoice_ids(&self) -> Result<Vec<String>, AppError> {
+        Ok(self.job_cache.smembers(&CacheKeys::invoices()).await?)
+    }
+
+    pub async fn get_merchant_invoice_ids(&self, merchant_id: &UserId) -> Result<Vec<String>, AppError> {
+        Ok(self.job_cache.smembers(&CacheKeys::merchant_invoices(merchant_id)).await?)
+    }
+
+    pub async fn cache_invoice(&self, invoice: &Invoice) -> Result<(), AppError> {
+        self.job_cache.set(&CacheKeys::invoice(&invoice.id), invoice, None).await?;
+        self.job_cache.sadd(&CacheKeys::invoices(), &invoice.id).await?;
+        self.job_cache.sadd(&CacheKeys::merchant_invoices(&invoice.merchant_id), &invoice.id).await?;
+        Ok(())
+    }
+
+    pub async fn find_driver_ids_near(&self, latitude: f64, longitude: f64, radius_km: f64, limit: usize) -> Result<Vec<DriverId>, AppError> {
+        let key = CacheKeys::driver_locations_geo();
+        Ok(parse_members(self.driver_cache.geosearch(&key, longitude, latitude, radius_km, limit).await?))
+    }
+
+    // Route history methods
+    pub async fn append_route_segment(&self, job_id: &JobId, segment: &RouteSegment) -> Result<(), AppError> {
+        let key = CacheKeys::job_route(job_id);
+        let json = serde_json::to_string(segment)?;
+        self.job_cache.rpush(&key, &json, None).await?; // Kept until the retention purge
+        Ok(())
+    }
+
+    // Append-only history of a job's lifecycle, oldest first
+    pub async fn append_job_event(&self, job_id: &JobId, event: &JobEvent) -> Result<(), AppError> {
+        let key = CacheKeys::job_events(job_id);
+        let json = serde_json::to_string(event)?;
+        self.job_cache.rpush(&key, &json, None).await?; // Kept until the retention purge
+
+        // Written with the event itself, so the export can't miss one
+        let exported = ExportedEvent::from_job_event(&current_tenant_id(), job_id, event);
+        let day = exported.occurred_at.date_naive();
+        self.job_cache.rpush(&CacheKeys::events_outbox(&day), &serde_json::to_string(&exported)?, Some(EVENTS_OUTBOX_TTL_SECONDS)).await?;
+        self.job_cache.sadd(&CacheKeys::events_outbox_days(), &day.format("%Y%m%d").to_string()).await?;
+        Ok(())
+    }
+
+    pub async fn record_push_outcome(&self, delivered: bool) -> Result<(), AppError> {
+        let key = CacheKeys::push_outcomes(if delivered { "delivered" } else { "failed" }, &Utc::now());
+        self.job_cache.incr(&key, 3600).await?;
+        Ok(())
+    }
+
+    // (delivered, failed) over the `minutes` up to and including `now`'s
+    pub async fn count_push_outcomes(&self, now: DateTime<Utc>, minutes: i64) -> Result<(i64, i64), AppError> {
+        let (mut delivered, mut failed) = (0, 0);
+        for offset in 0..minutes {
+            let minute = now - chrono::Duration::minutes(offset);
+            delivered += self.job_cache.get(&CacheKeys::push_outcomes("delivered", &minute)).await?.unwrap_or(0i64);
+            failed += self.job_cache.get(&CacheKeys::push_outcomes("failed", &minute)).await?.unwrap_or(0i64);
+        }
+        Ok((delivered, failed))
+    }
+
+    // True for the first instance to raise `kind` in a cooldown
+    pub async fn claim_alert(&self, kind: AlertKind, cooldown_seconds: u64) -> Result<bool, AppError> {
+        let key = CacheKeys::alert_cooldown(kind);
+        Ok(self.job_cache.incr(&key, cooldown_seconds).await? == 1)
+    }
+
+    pub async fn get_canary_participants(&self) -> Result<Option<CanaryParticipants>, AppError> {
+        Ok(self.job_cache.get(&CacheKeys::canary_participants()).await?)
+    }
+
+    pub async fn cache_canary_participants(&self, participants: &CanaryParticipants) -> Result<(), AppError> {
+        self.job_cache.set(&CacheKeys::canary_participants(), participants, None).await?;
+        Ok(())
+    }
+
+    // Most recent first
+    pub async fn get_canary_runs(&self) -> Result<Vec<CanaryRun>, AppError> {
+        Ok(self.job_cache.get(&CacheKeys::canary_runs()).await?.unwrap_or_default())
+    }
+
+    /// Keeps the latest `keep` runs
+    pub async fn record_canary_run(&self, run: &CanaryRun, keep: usize) -> Result<(), AppError> {
+        let mut runs = self.get_canary_runs().await?;
+        runs.insert(0, run.clone());
+        runs.truncate(keep);
+        self.job_cache.set(&CacheKeys::canary_runs(), &runs, None).await?;
+        Ok(())
+    }
+
+    pub async fn claim_canary_slot(&self, slot: i64, ttl_seconds: u64) -> Result<bool, AppError> {
+        let key = CacheKeys::canary_slot(slot);
+        Ok(self.job_cache.incr(&key, ttl_seconds).await? == 1)
+    }
+
+    pub async fn get_events_outbox_days(&self) -> Result<Vec<NaiveDate>, AppError> {
+        Ok(parse_days(self.job_cache.smembers(&CacheKeys::events_outbox_days()).await?))
+    }
+
+    pub async fn add_events_outbox_day(&self, day: &NaiveDate) -> Result<(), AppError> {
+        let key = CacheKeys::events_outbox_days();
+        self.job_cache.sadd(&key, &day.format("%Y%m%d").to_string()).await.map_err(|e| e.into())
+    }
+
+    pub async fn remove_events_outbox_day(&self, day: &NaiveDate) -> Result<(), AppError> {
+        let key = CacheKeys::events_outbox_days();
+        self.job_cache.srem(&key, &day.format("%Y%m%d").to_string()).await.map_err(|e| e.into())
+    }
+
+    // Raw NDJSON lines, `start` to `stop` inclusive
+    pub async fn get_events_outbox(&self, day: &NaiveDate, start: isize, stop: isize) -> Result<Vec<String>, AppError> {
+        let key = CacheKeys::events_outbox(day);
+        Ok(self.job_cache.lrange(&key, start, stop).await?)
+    }
+
+    pub async fn get_events_export_cursor(&self, day: &NaiveDate) -> Result<usize, AppError> {
+        let key = CacheKeys::events_export_cursor(day);
+        Ok(self.job_cache.get(&key).await?.unwrap_or(0))
+    }
+
+    pub async fn set_events_export_cursor(&self, day: &NaiveDate, exported: usize) -> Result<(), AppError> {
+        let key = CacheKeys::events_export_cursor(day);
+        self.job_cache.set(&key, &exported, Some(EVENTS_OUTBOX_TTL_SECONDS)).await?;
+        Ok(())
+    }
+
+    pub async fn get_consumer_checkpoint(&self, group: &str, consumer: &str) -> Result<Option<ConsumerCheckpoint>, AppError> {
+        Ok(self.job_cache.get(&CacheKeys::consumer_checkpoint(group, consumer)).await?)
+    }
+
+    // Every consumer that has ever checkpointed in the group, including ones since restarted
+    pub async fn get_consumer_checkpoints(&self, group: &str) -> Result<Vec<ConsumerCheckpoint>, AppError> {
+        let mut checkpoints = Vec::new();
+        for consumer in self.job_cache.smembers(&CacheKeys::consumer_names(group)).await? {
+            if let Some(checkpoint) = self.get_consumer_checkpoint(group, &consumer).await? {
+                checkpoints.push(checkpoint);
+            }
+        }
+        Ok(checkpoints)
+    }
+
+    pub async fn set_consumer_checkpoint(&self, checkpoint: &ConsumerCheckpoint) -> Result<(), AppError> {
+        let consumer = checkpoint.consumer.as_deref()
+            .ok_or_else(|| AppError::internal_error("Only a single consumer's checkpoint can be saved"))?;
+        let key = CacheKeys::consumer_checkpoint(&checkpoint.group, consumer);
+        self.job_cache.set(&key, checkpoint, None).await?;
+        self.job_cache.sadd(&CacheKeys::consumer_names(&checkpoint.group), consumer).await?;
+        Ok(())
+    }
+
+    // Marks outlive the log's redelivery window, after which an entry is parked instead
+    pub async fn mark_consumer_handled(&self, group: &str, offset: &str, ttl_seconds: u64) -> Result<(), AppError> {
+        let key = CacheKeys::consumer_handled(group, offset);
+        self.job_cache.set(&key, &true, Some(ttl_seconds)).await?;
+        Ok(())
+    }
+
+    pub async fn was_consumer_handled(&self, group: &str, offset: &str) -> Result<bool, AppError> {
+        Ok(self.job_cache.exists(&CacheKeys::consumer_handled(group, offset)).await?)
+    }
+
+    pub async fn park_consumer_event(&self, parked: &ParkedEvent) -> Result<(), AppError> {
+        let key = CacheKeys::consumer_parked(&parked.group);
+        self.job_cache.rpush(&key, &serde_json::to_string(parked)?, None).await?;
+        Ok(())
+    }
+
+    pub async fn get_parked_consumer_events(&self, group: &str) -> Result<Vec<ParkedEvent>, AppError> {
+        let lines = self.job_cache.lrange(&CacheKeys::consumer_parked(group), 0, -1).await?;
+        Ok(lines.iter().filter_map(|line| serde_json::from_str(line).ok()).collect())
+    }
+
+    pub async fn get_saga(&self, kind: SagaKind, key: &str) -> Result<Option<SagaState>, AppError> {
+        Ok(self.job_cache.get(&CacheKeys::saga(kind, key)).await?)
+    }
+
+    // True for whoever gets to run the saga for `key`; expires so a crashed runner doesn't hold it
+    pub async fn claim_saga(&self, kind: SagaKind, key: &str, ttl_seconds: u64) -> Result<bool, AppError> {
+        Ok(self.job_cache.incr(&CacheKeys::saga_claim(kind, key), ttl_seconds).await? == 1)
+    }
+
+    pub async fn release_saga(&self, kind: SagaKind, key: &str) -> Result<(), AppError> {
+        self.job_cache.delete(&CacheKeys::saga_claim(kind, key)).await?;
+        Ok(())
+    }
+
+    // Completed and undone sagas stay readable but drop out of the open set; failed ones
+    // stay in it until someone deals with them
+    pub async fn cache_saga(&self, saga: &SagaState) -> Result<(), AppError> {
+        self.job_cache.set(&CacheKeys::saga(saga.kind, &saga.key), saga, None).await?;
+        let member = format!("{}:{}", saga.kind.as_str(), saga.key);
+        if matches!(saga.status, SagaStatus::Completed | SagaStatus::Compensated) {
+            self.job_cache.srem(&CacheKeys::open_sagas(), &member).await?;
+        } else {
+            self.job_cache.sadd(&CacheKeys::open_sagas(), &member).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get_open_sagas(&self) -> Result<Vec<SagaState>, AppError> {
+        let mut sagas = Vec::new();
+        for member in self.job_cache.smembers(&CacheKeys::open_sagas()).await? {
+            let Some((kind, key)) = member.split_once(':') else {
+                continue;
+            };
+            let Some(kind) = SagaKind::parse(kind) else {
+                continue;
+            };
+            if let Some(saga) = self.get_saga(kind, key).await? {
+                sagas.push(saga);
+            }
+        }
+        Ok(sagas)
+    }
+
+    pub async fn get_job_events(&self, job_id: &JobId) -> Result<Vec<JobEvent>, AppError> {
+        let key = CacheKeys::job_events(job_id);
+        let raw = self.job_cache.lrange(&key, 0, -1).await?;
+        raw.iter()
+            .map(|json| serde_json::from_str(json).map_err(AppError::from))
+            .collect()
+    }
+
+    pub async fn get_route_segments(&self, job_id: &JobId) -> Result<Vec<RouteSegment>, AppError> {
+        let key = CacheKeys::job_route(job_id);
+        let raw = self.job_cache.lrange(&key, 0, -1).await?;
+        raw.iter()
+            .map(|json| serde_json::from_str(json).map_err(AppError::from))
+            .collect()
+    }
+
+    pub async fn delete_route_segments(&self, job_id: &JobId) -> Result<(), AppError> {
+        self.job_cache.delete(&CacheKeys::job_route(job_id)).await.map_err(|e| e.into())
+    }
+
+    pub async fn delete_job_events(&self, job_id: &JobId) -> Result<(), AppError> {
+        self.job_cache.delete(&CacheKeys::job_events(job_id)).await.map_err(|e| e.into())
+    }
+
+    // Bulk operations / invalidation
+    pub async fn invalidate_user(&self, user_id: &UserId) -> Result<(), AppError> {
+        let key = CacheKeys::user_by_id(user_id);
+        self.user_cache.delete(&key).await?;
+        Ok(())
+    }
+
+    pub async fn invalidate_job(&self, job_id: &JobId) -> Result<(), AppError> {
+        let key = CacheKeys::job_by_id(job_id);
+        self.job_cache.delete(&key).await?;
+        Ok(())
+    }
+}
+
+// Health check
+impl CacheService {
+    // Whether every cache answers; a failure is logged and reported as unhealthy
+    pub async fn health_check(&self) -> Result<bool, AppError> {
+        for cache in [&self.user_cache, &self.job_cache, &self.driver_cache] {
+            if let Err(e) = cache.ping().await {
+                tracing::warn!("Cache health check failed: {}", e);
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl Cache {
+    async fn ping(&self) -> Result<(), CacheError> {
+        match self {
+            Cache::Redis(cache) => {
+                let mut conn = cache.get_connection().await?;
+                let _: String = redis::cmd("PING").query_async(&mut conn).await?;
+                Ok(())
+            }
+            Cache::Memory(cache) => cache.check_enabled(),
+        }
+    }
+}
+
+// Set and GEO members are plain strings; entries that no longer parse as an ID are skipped
+// Day-set members as written by add_job_to_day and append_job_event, oldest first
+fn parse_days(members: Vec<String>) -> Vec<NaiveDate> {
+    let mut days: Vec<NaiveDate> = members.iter()
+        .filter_map(|day| NaiveDate::parse_from_str(day, "%Y%m%d").ok())
+        .collect();
+    days.sort();
+    days
+}
+
+// Creation time in milliseconds; exact in a sorted-set score
+fn history_score(job: &Job) -> f64 {
+    job.created_at.timestamp_millis() as f64
+}
+
+fn parse_members<T: FromStr>(members: Vec<String>) -> Vec<T> {
+    members
+        .into_iter()
+        .filter_map(|member| match member.parse() {
+            Ok(id) => Some(id),
+            Err(_) => {
+                tracing::warn!("Skipping malformed ID in cache index: {}", member);
+                None
+            }
+        })
+        .collect()
+}
+
+impl From<CacheError> for AppError {
+    fn from(error: CacheError) -> Self {
+        match error {
+            CacheError::Redis(e) => e.into(),
+            CacheError::Serialization(e) => AppError::RedisSerialization(e.to_string()),
+            CacheError::MessagePackEncode(e) => AppError::RedisSerialization(e.to_string()),
+            CacheError::MessagePackDecode(e) => AppError::RedisSerialization(e.to_string()),
+            e @ CacheError::UnsupportedEnvelope { .. } => AppError::RedisSerialization(e.to_string()),
+            CacheError::Loader(e) => *e,
+            CacheError::CacheDisabled => AppError::service_unavailable("cache"),
+            CacheError::CacheMiss => AppError::NotFound("Cache miss".to_string()),
+        }
+    }
+}
+
+// ------------------------------
+// Enum delegations (Cache)
+// ------------------------------
+// Every key is namespaced by the current tenant and region here, so nothing above
+// this layer can read or write another tenant's or region's data by accident.
+
+#[async_trait]
+impl<T> CacheOperations<T> for Cache
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    async fn get(&self, key: &CacheKey) -> Result<Option<T>, CacheError> {
+        chaos::inject(FaultLayer::Cache, "get").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.get(key).await,
+            Cache::Memory(cache) => cache.get(key).await,
+        }
+    }
+
+    async fn set(&self, key: &CacheKey, value: &T, ttl: Option<u64>) -> Result<(), CacheError> {
+        chaos::inject(FaultLayer::Cache, "set").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.set(key, value, ttl).await,
+            Cache::Memory(cache) => cache.set(key, value, ttl).await,
+        }
+    }
+
+    async fn get_or_set<F>(&self, key: &CacheKey, ttl: Option<u64>, factory: F) -> Result<T, CacheError>
+    where
+        F: Fn() -> futures::future::BoxFuture<'static, Result<T, CacheError>> + Send + Sync,
+    {
+        chaos::inject(FaultLayer::Cache, "get_or_set").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.get_or_set(key, ttl, factory).await,
+            Cache::Memory(cache) => cache.get_or_set(key, ttl, factory).await,
+        }
+    }
+}
+
+#[async_trait]
+impl KeyOperations for Cache {
+    async fn delete(&self, key: &CacheKey) -> Result<(), CacheError> {
+        chaos::inject(FaultLayer::Cache, "delete").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.delete(key).await,
+            Cache::Memory(cache) => cache.delete(key).await,
+        }
+    }
+
+    async fn exists(&self, key: &CacheKey) -> Result<bool, CacheError> {
+        chaos::inject(FaultLayer::Cache, "exists").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.exists(key).await,
+            Cache::Memory(cache) => cache.exists(key).await,
+        }
+    }
+}
+
+#[async_trait]
+impl SetOperations for Cache {
+    async fn sadd(&self, key: &CacheKey, value: &str) -> Result<(), CacheError> {
+        chaos::inject(FaultLayer::Cache, "sadd").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.sadd(key, value).await,
+            Cache::Memory(cache) => cache.sadd(key, value).await,
+        }
+    }
+
+    async fn smembers(&self, key: &CacheKey) -> Result<Vec<String>, CacheError> {
+        chaos::inject(FaultLayer::Cache, "smembers").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.smembers(key).await,
+            Cache::Memory(cache) => cache.smembers(key).await,
+        }
+    }
+
+    async fn srem(&self, key: &CacheKey, value: &str) -> Result<(), CacheError> {
+        chaos::inject(FaultLayer::Cache, "srem").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.srem(key, value).await,
+            Cache::Memory(cache) => cache.srem(key, value).await,
+        }
+    }
+}
+
+#[async_trait]
+impl GeoOperations for Cache {
+    async fn geoadd(&self, key: &CacheKey, members: &[(String, f64, f64)]) -> Result<(), CacheError> {
+        chaos::inject(FaultLayer::Cache, "geoadd").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.geoadd(key, members).await,
+            Cache::Memory(cache) => cache.geoadd(key, members).await,
+        }
+    }
+
+    async fn geosearch(&self, key: &CacheKey, longitude: f64, latitude: f64, radius_km: f64, limit: usize) -> Result<Vec<String>, CacheError> {
+        chaos::inject(FaultLayer::Cache, "geosearch").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.geosearch(key, longitude, latitude, radius_km, limit).await,
+            Cache::Memory(cache) => cache.geosearch(key, longitude, latitude, radius_km, limit).await,
+        }
+    }
+
+    async fn georem(&self, key: &CacheKey, member: &str) -> Result<(), CacheError> {
+        chaos::inject(FaultLayer::Cache, "georem").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.georem(key, member).await,
+            Cache::Memory(cache) => cache.georem(key, member).await,
+        }
+    }
+}
+
+#[async_trait]
+impl ListOperations for Cache {
+    async fn rpush(&self, key: &CacheKey, value: &str, ttl: Option<u64>) -> Result<(), CacheError> {
+        chaos::inject(FaultLayer::Cache, "rpush").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.rpush(key, value, ttl).await,
+            Cache::Memory(cache) => cache.rpush(key, value, ttl).await,
+        }
+    }
+
+    async fn lrange(&self, key: &CacheKey, start: isize, stop: isize) -> Result<Vec<String>, CacheError> {
+        chaos::inject(FaultLayer::Cache, "lrange").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.lrange(key, start, stop).await,
+            Cache::Memory(cache) => cache.lrange(key, start, stop).await,
+        }
+    }
+}
+
+#[async_trait]
+impl SortedSetOperations for Cache {
+    async fn zadd(&self, key: &CacheKey, member: &str, score: f64) -> Result<(), CacheError> {
+        chaos::inject(FaultLayer::Cache, "zadd").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.zadd(key, member, score).await,
+            Cache::Memory(cache) => cache.zadd(key, member, score).await,
+        }
+    }
+
+    async fn zrem(&self, key: &CacheKey, member: &str) -> Result<(), CacheError> {
+        chaos::inject(FaultLayer::Cache, "zrem").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.zrem(key, member).await,
+            Cache::Memory(cache) => cache.zrem(key, member).await,
+        }
+    }
+
+    async fn zrevrange_by_score(&self, key: &CacheKey, max: f64, offset: usize, count: usize) -> Result<Vec<(String, f64)>, CacheError> {
+        chaos::inject(FaultLayer::Cache, "zrevrange_by_score").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.zrevrange_by_score(key, max, offset, count).await,
+            Cache::Memory(cache) => cache.zrevrange_by_score(key, max, offset, count).await,
+        }
+    }
+}
+
+#[async_trait]
+impl SwapOperations for Cache {
+    async fn get_raw(&self, key: &CacheKey) -> Result<Option<Vec<u8>>, CacheError> {
+        chaos::inject(FaultLayer::Cache, "get_raw").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.get_raw(key).await,
+            Cache::Memory(cache) => cache.get_raw(key).await,
+        }
+    }
+
+    async fn swap<T: Serialize + Send + Sync>(&self, key: &CacheKey, expected: &[u8], value: &T, ttl: Option<u64>) -> Result<bool, CacheError> {
+        chaos::inject(FaultLayer::Cache, "swap").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.swap(key, expected, value, ttl).await,
+            Cache::Memory(cache) => cache.swap(key, expected, value, ttl).await,
+        }
+    }
+}
+
+#[async_trait]
+impl CounterOperations for Cache {
+    async fn incr(&self, key: &CacheKey, ttl: u64) -> Result<i64, CacheError> {
+        chaos::inject(FaultLayer::Cache, "incr").await?;
+        let key = &key.scoped(&current_tenant_id()).regional(&current_region_id());
+        match self {
+            Cache::Redis(cache) => cache.incr(key, ttl).await,
+            Cache::Memory(cache) => cache.incr(key, ttl).await,
+        }
+    }
+}
+
+// ------------------------------
+// get_or_set helper in service
+// ------------------------------
+
+impl CacheService {
+    // Get or set pattern with automatic caching
+    pub async fn get_user_or_fetch<F>(&self, user_id: &UserId, fetch_fn: F) -> Result<User, AppError>
+    where
+        F: Fn() -> futures::future::BoxFuture<'static, Result<User, AppError>> + Send + Sync + 'static,
+    {
+        // Not `get_or_set`, which would store the user without sealing it
+        let key = CacheKeys::user_by_id(user_id);
+        if let Some(user) = self.get_user(&key).await? {
+            return Ok(user);
+        }
+        let user = fetch_fn().await?;
+        self.set_user(&key, &user, Some(3600)).await?;
+        Ok(user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_errors_keep_their_kind() {
+        let timeout = redis::RedisError::from(std::io::Error::new(std::io::ErrorKind::TimedOut, "read timed out"));
+        assert!(matches!(AppError::from(CacheError::from(timeout)), AppError::RedisTimeout));
+
+        let refused = redis::RedisError::from(std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "refused"));
+        assert!(matches!(AppError::from(CacheError::from(refused)), AppError::RedisConnection(_)));
+
+        let auth = redis::RedisError::from((redis::ErrorKind::AuthenticationFailed, "WRONGPASS"));
+        assert!(matches!(AppError::from(CacheError::from(auth)), AppError::ConfigurationError(_)));
+
+        let bad_json = serde_json::from_str::<User>("{").unwrap_err();
+        assert!(matches!(AppError::from(CacheError::from(bad_json)), AppError::RedisSerialization(_)));
+
+        let loader = CacheError::Loader(Box::new(AppError::user_not_found("usr-1")));
+        assert!(matches!(AppError::from(loader), AppError::UserNotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn test_memory_lrange_matches_redis_indexing() {
+        let cache = MemoryCache::new(CacheConfig::default());
+        let key = CacheKey::Simple("list".to_string());
+        for value in ["a", "b", "c", "d"] {
+            cache.rpush(&key, value, None).await.unwrap();
+        }
+
+        assert_eq!(cache.lrange(&key, 0, -1).await.unwrap(), vec!["a", "b", "c", "d"]);
+        assert_eq!(cache.lrange(&key, 1, 2).await.unwrap(), vec!["b", "c"]);
+        assert_eq!(cache.lrange(&key, -2, 10).await.unwrap(), vec!["c", "d"]);
+        assert!(cache.lrange(&key, 3, 1).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_message_pack_values_and_counters_share_the_store() {
+        let cache = MemoryCache::new(CacheConfig { format: CacheFormat::MessagePack, ..Default::default() });
+        let key = CacheKey::Simple("driver".to_string());
+        let location = LocationUpdate {
+            latitude: 5.556,
+            longitude: -0.183,
+            timestamp: Utc::now(),
+            accuracy: Some(8.0),
+            heading: None,
+            speed: None,
+            is_mock: false,
+        };
+        cache.set(&key, &location, None).await.unwrap();
+        let stored: LocationUpdate = cache.get(&key).await.unwrap().unwrap();
+        assert_eq!(stored.timestamp, location.timestamp);
+
+        let counter = CacheKey::Simple("counter".to_string());
+        cache.incr(&counter, 60).await.unwrap();
+        assert_eq!(cache.incr(&counter, 60).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_memory_geosearch_orders_by_distance() {
+        let cache = MemoryCache::new(CacheConfig::default());
+        let key = CacheKey::Simple("geo".to_string());
+        cache.geoadd(&key, &[
+            ("kumasi".to_string(), -1.6244, 6.6885),
+            ("osu".to_string(), -0.1830, 5.5560),
+            ("legon".to_string(), -0.1610, 5.6370),
+        ]).await.unwrap();
+
+        let nearby = cache.geosearch(&key, -0.1870, 5.5600, 20.0, 10).await.unwrap();
+        assert_eq!(nearby, vec!["osu", "legon"]);
+
+        cache.georem(&key, "osu").await.unwrap();
+        assert_eq!(cache.geosearch(&key, -0.1870, 5.5600, 20.0, 1).await.unwrap(), vec!["legon"]);
+    }
+
+    #[tokio::test]
+    async fn test_personal_fields_are_sealed_at_rest() {
+        let repository = Arc::new(crate::services::database::MemoryRepository::new());
+        let cipher = FieldCipher::new(vec![("k1".to_string(), vec![7; 32])], b"index").unwrap();
+        let cache = CacheService::new_memory(CacheConfig::default())
+            .with_repository(repository.clone())
+            .with_cipher(Arc::new(cipher));
+        let user = crate::mocks::fixtures::Faker::seeded(70).user(crate::models::user::UserType::Customer);
+        cache.cache_user(&user).await.unwrap();
+
+        let raw: Option<User> = cache.user_cache.get(&CacheKeys::user_by_id(&user.id)).await.unwrap();
+        let persisted = repository.find_user(&user.id).await.unwrap().unwrap();
+        for stored in [raw.unwrap(), persisted] {
+            assert!(stored.email.starts_with("enc:") && stored.phone_number.starts_with("enc:"));
+        }
+        assert_eq!(cache.load_user(&user.id).await.unwrap().unwrap().email, user.email);
+        assert_eq!(cache.get_user_id_by_email(&user.email.to_uppercase()).await.unwrap(), Some(user.id.clone()));
+        assert_eq!(cache.get_user_id_by_phone(&user.phone_number).await.unwrap(), Some(user.id));
+    }
+
+    #[tokio::test]
+    async fn test_jobs_reload_from_the_repository_only_in_their_own_region() {
+        use crate::services::region::with_region;
+        let repository = Arc::new(crate::services::database::MemoryRepository::new());
+        let cache = CacheService::new_memory(CacheConfig::default()).with_repository(repository);
+        let mut faker = crate::mocks::fixtures::Faker::seeded(4714);
+        let customer = faker.user(crate::models::user::UserType::Customer);
+        let mut job = faker.job(&customer.id);
+        job.region = "ng".to_string();
+        with_region("ng".to_string(), cache.cache_job(&job)).await.unwrap();
+        with_region("ng".to_string(), cache.job_cache.delete(&CacheKeys::job_by_id(&job.id))).await.unwrap();
+
+        // Missing from Accra's cache, and not to be found in the repository from there either
+        assert!(with_region("gh".to_string(), cache.load_job(&job.id)).await.unwrap().is_none());
+        assert!(cache.load_job(&job.id).await.unwrap().is_none());
+        let reloaded = with_region("ng".to_string(), cache.load_job(&job.id)).await.unwrap();
+        assert_eq!(reloaded.map(|reloaded| reloaded.id), Some(job.id));
+    }
+
+    #[tokio::test]
+    async fn test_health_check_reports_a_disabled_cache() {
+        assert!(CacheService::new_memory(CacheConfig::default()).health_check().await.unwrap());
+        let disabled = CacheService::new_memory(CacheConfig { enabled: false, ..Default::default() });
+        assert!(!disabled.health_check().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_job_updates_never_overwrite_a_newer_write() {
+        use crate::models::job::JobStatus;
+        let cache = CacheService::new_memory(CacheConfig::default());
+        let mut faker = crate::mocks::fixtures::Faker::seeded(4607);
+        let customer = faker.user(crate::models::user::UserType::Customer);
+        let job = faker.job(&customer.id);
+        cache.cache_job(&job).await.unwrap();
+        let key = CacheKeys::job_by_id(&job.id);
+        let read = cache.job_cache.get_raw(&key).await.unwrap().unwrap();
+
+        // Delivered after we read it, so a write based on what we read is refused
+        let mut delivered = job.clone();
+        delivered.status = JobStatus::DeliveryCompleted;
+        cache.cache_job(&delivered).await.unwrap();
+        assert!(!cache.job_cache.swap(&key, &read, &job, None).await.unwrap());
+
+        let updated = cache.update_job(&job.id, |job| {
+            job.sla = None;
+            Ok(())
+        }).await.unwrap();
+        assert_eq!(updated.status, JobStatus::DeliveryCompleted);
+        assert_eq!(cache.load_job(&job.id).await.unwrap().unwrap().status, JobStatus::DeliveryCompleted);
+
+        // A change that refuses leaves the job alone
+        let refused = cache.update_job(&job.id, |job| {
+            job.status = JobStatus::Cancelled;
+            Err(AppError::Conflict("delivered".to_string()))
+        }).await;
+        assert!(matches!(refused, Err(AppError::Conflict(_))));
+        assert_eq!(cache.load_job(&job.id).await.unwrap().unwrap().status, JobStatus::DeliveryCompleted);
+    }
+}
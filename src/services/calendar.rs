@@ -0,0 +1,233 @@
+// src/services/calendar.rs
+// Public holidays and peak periods. Each region's own fixed-date holidays are built in, from
+// its country's rules; moveable ones (Easter, the Eids) and busy spells are added by operations. While one is in force, fares
+// carry a holiday surcharge and delivery promises are pushed back, since fewer drivers work
+// and the roads are busier.
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::calendar::{CalendarAdjustment, CalendarPeriod, CreateCalendarPeriodRequest, PeriodKind},
+    services::{cache_service::CacheService, region::RegionRegistry},
+    utils::id_generator::{IdGenerator, IdType},
+};
+
+#[derive(Debug, Clone)]
+pub struct CalendarConfig {
+    pub holiday_price_multiplier: f64,
+    pub peak_price_multiplier: f64,
+    pub holiday_sla_extension_hours: i64,
+    pub peak_sla_extension_hours: i64,
+    pub max_price_multiplier: f64,
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        Self {
+            holiday_price_multiplier: 1.25,
+            peak_price_multiplier: 1.1,
+            holiday_sla_extension_hours: 4,
+            peak_sla_extension_hours: 2,
+            max_price_multiplier: 3.0,
+        }
+    }
+}
+
+pub struct CalendarService {
+    cache_service: Arc<CacheService>,
+    regions: Arc<RegionRegistry>,
+    config: CalendarConfig,
+}
+
+impl CalendarService {
+    pub fn new(cache_service: Arc<CacheService>, regions: Arc<RegionRegistry>, config: CalendarConfig) -> Self {
+        Self { cache_service, regions, config }
+    }
+
+    /// Configured periods, earliest first
+    pub async fn periods(&self) -> Result<Vec<CalendarPeriod>, AppError> {
+        self.cache_service.get_calendar_periods().await
+    }
+
+    pub async fn add_period(&self, request: CreateCalendarPeriodRequest) -> Result<CalendarPeriod, AppError> {
+        let name = request.name.trim();
+        if name.is_empty() {
+            return Err(AppError::validation_error("name", "Must not be empty"));
+        }
+        if request.ends_at <= request.starts_at {
+            return Err(AppError::validation_error("ends_at", "Must be after starts_at"));
+        }
+        let (default_multiplier, default_extension) = self.defaults(request.kind);
+        let price_multiplier = request.price_multiplier.unwrap_or(default_multiplier);
+        if !(1.0..=self.config.max_price_multiplier).contains(&price_multiplier) {
+            return Err(AppError::validation_error(
+                "price_multiplier",
+                format!("Must be between 1 and {}", self.config.max_price_multiplier),
+            ));
+        }
+        let sla_extension_hours = request.sla_extension_hours.unwrap_or(default_extension);
+        if !(0..=48).contains(&sla_extension_hours) {
+            return Err(AppError::validation_error("sla_extension_hours", "Must be between 0 and 48"));
+        }
+
+        let period = CalendarPeriod {
+            id: IdGenerator::generate(IdType::Period),
+            name: name.to_string(),
+            kind: request.kind,
+            starts_at: request.starts_at,
+            ends_at: request.ends_at,
+            price_multiplier,
+            sla_extension_hours,
+        };
+        let mut periods = self.periods().await?;
+        periods.push(period.clone());
+        periods.sort_by_key(|period| period.starts_at);
+        self.cache_service.cache_calendar_periods(&periods).await?;
+
+        tracing::info!("Calendar period {} ({}) added from {} to {}", period.id, period.name, period.starts_at, period.ends_at);
+        Ok(period)
+    }
+
+    pub async fn remove_period(&self, period_id: &str) -> Result<CalendarPeriod, AppError> {
+        let mut periods = self.periods().await?;
+        let index = periods.iter().position(|period| period.id == period_id)
+            .ok_or_else(|| AppError::NotFound(format!("Calendar period {} not found", period_id)))?;
+        let removed = periods.remove(index);
+        self.cache_service.cache_calendar_periods(&periods).await?;
+        Ok(removed)
+    }
+
+    /// The surcharge and SLA relaxation in force at `at` in the current region, if any
+    pub async fn adjustment_at(&self, at: DateTime<Utc>) -> Result<Option<CalendarAdjustment>, AppError> {
+        let (holiday_multiplier, holiday_extension) = self.defaults(PeriodKind::Holiday);
+        let mut adjustments: Vec<CalendarAdjustment> = self.regions.current().public_holiday_at(at)
+            .map(|name| CalendarAdjustment {
+                name: name.to_string(),
+                kind: PeriodKind::Holiday,
+                price_multiplier: holiday_multiplier,
+                sla_extension_hours: holiday_extension,
+            })
+            .into_iter()
+            .collect();
+        for period in self.periods().await? {
+            if period.covers(at) {
+                adjustments.push(CalendarAdjustment {
+                    name: period.name,
+                    kind: period.kind,
+                    price_multiplier: period.price_multiplier,
+                    sla_extension_hours: period.sla_extension_hours,
+                });
+            }
+        }
+
+        // The dearest period names the surcharge; the promise gets the longest extension
+        let sla_extension_hours = adjustments.iter().map(|adjustment| adjustment.sla_extension_hours).max();
+        Ok(adjustments.into_iter()
+            .max_by(|a, b| a.price_multiplier.total_cmp(&b.price_multiplier))
+            .map(|strongest| CalendarAdjustment {
+                sla_extension_hours: sla_extension_hours.unwrap_or(strongest.sla_extension_hours),
+                ..strongest
+            }))
+    }
+
+    fn defaults(&self, kind: PeriodKind) -> (f64, i64) {
+        match kind {
+            PeriodKind::Holiday => (self.config.holiday_price_multiplier, self.config.holiday_sla_extension_hours),
+            PeriodKind::Peak => (self.config.peak_price_multiplier, self.config.peak_sla_extension_hours),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, NaiveDate};
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::{job::JobPriority, user::UserType},
+        services::{job_service::JobOperations, region::with_region, user_service::UserOperations},
+    };
+
+    #[tokio::test]
+    async fn test_holidays_surcharge_quotes_and_relax_delivery_promises() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let calendar = &state.calendar_service;
+        let mut faker = Faker::seeded(59);
+
+        let christmas = NaiveDate::from_ymd_opt(2025, 12, 25).unwrap().and_hms_opt(10, 0, 0).unwrap().and_utc();
+        let christmas_day = calendar.adjustment_at(christmas).await.unwrap().unwrap();
+        assert_eq!((christmas_day.name.as_str(), christmas_day.price_multiplier), ("Christmas Day", 1.25));
+        let ghana = state.regions.current();
+        assert_eq!(ghana.public_holiday_at(christmas - Duration::days(20)), Some("Farmers' Day"));
+        assert_eq!(ghana.public_holiday_at(christmas - Duration::days(13)), None);
+
+        let invalid = CreateCalendarPeriodRequest {
+            name: "Backwards".to_string(),
+            kind: PeriodKind::Peak,
+            starts_at: christmas,
+            ends_at: christmas - Duration::days(1),
+            price_multiplier: None,
+            sla_extension_hours: None,
+        };
+        assert!(matches!(calendar.add_period(invalid).await, Err(AppError::ValidationFailed(_))));
+
+        // A normal day quotes without a surcharge, then operations declare a peak over it
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let mut request = faker.job_request(&customer.id);
+        request.priority = JobPriority::Express;
+        let estimate_request = || crate::models::job::JobEstimateRequest {
+            pickup_location: request.pickup_location.clone().unwrap(),
+            dropoff_location: request.dropoff_location.clone().unwrap(),
+            package: request.package.clone(),
+            priority: request.priority.clone(),
+            currency: None,
+        };
+        let normal = state.job_service.calculate_estimate(estimate_request()).await.unwrap();
+
+        let now = Utc::now();
+        let rush = calendar.add_period(CreateCalendarPeriodRequest {
+            name: "Homowo rush".to_string(),
+            kind: PeriodKind::Peak,
+            starts_at: now - Duration::hours(1),
+            ends_at: now + Duration::hours(1),
+            price_multiplier: Some(1.5),
+            sla_extension_hours: None,
+        }).await.unwrap();
+        assert_eq!(rush.sla_extension_hours, 2);
+
+        let peak = state.job_service.calculate_estimate(estimate_request()).await.unwrap();
+        assert!(peak.pricing.holiday_surcharge.to_major() > normal.pricing.holiday_surcharge.to_major());
+        assert!(peak.pricing.total.to_major() > normal.pricing.total.to_major());
+        assert_eq!(peak.occasion.map(|occasion| occasion.name).as_deref(), Some("Homowo rush"));
+
+        let job = state.job_service.create_job(request).await.unwrap();
+        let promised_by = job.promised_by.unwrap();
+        assert!(promised_by >= job.created_at + Duration::hours(6) - Duration::seconds(1));
+        assert_eq!(job.pricing.holiday_surcharge, peak.pricing.holiday_surcharge);
+
+        calendar.remove_period(&rush.id).await.unwrap();
+        assert!(calendar.periods().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_each_region_keeps_its_own_public_holidays() {
+        let app = TestApp::builder().regions(&["ng", "ke"]).build();
+        let calendar = &app.state.calendar_service;
+        let noon = |month, day| NaiveDate::from_ymd_opt(2026, month, day).unwrap().and_hms_opt(12, 0, 0).unwrap().and_utc();
+        let holiday_in = |region: &str, at| with_region(region.to_string(), calendar.adjustment_at(at));
+
+        // Ghana's Independence Day is an ordinary day in Lagos, and Nigeria's in Accra
+        assert_eq!(holiday_in("gh", noon(3, 6)).await.unwrap().unwrap().name, "Independence Day");
+        assert!(holiday_in("ng", noon(3, 6)).await.unwrap().is_none());
+        assert_eq!(holiday_in("ng", noon(10, 1)).await.unwrap().unwrap().name, "Independence Day");
+        assert!(holiday_in("gh", noon(10, 1)).await.unwrap().is_none());
+
+        // Jamhuri Day starts at midnight in Nairobi, 21:00 UTC the evening before
+        let eve = NaiveDate::from_ymd_opt(2026, 12, 11).unwrap().and_hms_opt(21, 30, 0).unwrap().and_utc();
+        assert_eq!(holiday_in("ke", eve).await.unwrap().unwrap().name, "Jamhuri Day");
+    }
+}
@@ -0,0 +1,276 @@
+// src/services/canary_service.rs
+// A synthetic job walked through its whole lifecycle against the real services: created
+// for a mock customer, assigned to a mock driver, picked up, carried and completed. Each
+// step is timed; the latest runs are kept for `/admin/canary`, and a failed or slow run is
+// raised through the ops alerts.
+//
+// Everything happens inside a tenant of its own, registered on the first run, so canary
+// jobs never show up in a real tenant's dashboards, ledger or exports.
+use chrono::{DateTime, Utc};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    mocks::fixtures::Faker,
+    models::{
+        alert::{Alert, AlertKind},
+        canary::{CanaryParticipants, CanaryRun, CanaryStatus, CanaryStep, CanaryStepTiming},
+        driver::DriverEquipment,
+        ids::JobId,
+        job::{JobStatus, JobStatusUpdate},
+        tenant::CreateTenantRequest,
+        user::UserType,
+    },
+    services::{
+        alert_service::AlertService,
+        cache_service::CacheService,
+        driver_service::{DriverOperations, DriverService},
+        job_service::{JobOperations, JobService},
+        tenant_service::{with_tenant, TenantService},
+        user_service::{UserOperations, UserService},
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct CanaryConfig {
+    pub tenant_id: String,
+    pub slow_after_ms: u64, // Whole lifecycle, from creation to completion
+    pub keep_runs: usize,
+}
+
+impl Default for CanaryConfig {
+    fn default() -> Self {
+        Self {
+            tenant_id: "canary".to_string(),
+            slow_after_ms: 5000,
+            keep_runs: 50,
+        }
+    }
+}
+
+pub struct CanaryService {
+    cache_service: Arc<CacheService>,
+    tenant_service: Arc<TenantService>,
+    user_service: Arc<UserService>,
+    driver_service: Arc<DriverService>,
+    job_service: Arc<JobService>,
+    alert_service: Arc<AlertService>,
+    config: CanaryConfig,
+}
+
+impl CanaryService {
+    pub fn new(
+        cache_service: Arc<CacheService>,
+        tenant_service: Arc<TenantService>,
+        user_service: Arc<UserService>,
+        driver_service: Arc<DriverService>,
+        job_service: Arc<JobService>,
+        alert_service: Arc<AlertService>,
+        config: CanaryConfig,
+    ) -> Self {
+        Self {
+            cache_service,
+            tenant_service,
+            user_service,
+            driver_service,
+            job_service,
+            alert_service,
+            config,
+        }
+    }
+
+    pub async fn status(&self) -> Result<CanaryStatus, AppError> {
+        let runs = with_tenant(self.config.tenant_id.clone(), self.cache_service.get_canary_runs()).await?;
+        let failures = runs.iter().filter(|run| !run.succeeded()).count();
+        let succeeded: Vec<u64> = runs.iter().filter(|run| run.succeeded()).map(|run| run.total_ms).collect();
+        let average_total_ms = (!succeeded.is_empty())
+            .then(|| succeeded.iter().sum::<u64>() as f64 / succeeded.len() as f64);
+        Ok(CanaryStatus { runs, failures, average_total_ms })
+    }
+
+    /// Walks one synthetic job through its lifecycle, records the run and alerts on it
+    pub async fn run(&self, now: DateTime<Utc>) -> Result<CanaryRun, AppError> {
+        self.ensure_tenant().await?;
+        let run = with_tenant(self.config.tenant_id.clone(), self.walk(now)).await;
+        with_tenant(self.config.tenant_id.clone(), self.cache_service.record_canary_run(&run, self.config.keep_runs)).await?;
+
+        match (&run.failed_step, &run.error) {
+            (Some(step), Some(error)) => {
+                tracing::error!("Canary failed at {} after {} ms: {}", step, run.total_ms, error);
+                self.alert(AlertKind::CanaryFailed, format!("Canary job failed at {}: {}", step, error), 1.0, 0.0, now).await;
+            }
+            _ if run.total_ms > self.config.slow_after_ms => {
+                tracing::warn!("Canary took {} ms", run.total_ms);
+                let slowest = run.steps.iter().max_by_key(|timing| timing.elapsed_ms)
+                    .map(|timing| format!(", slowest step {} at {} ms", timing.step, timing.elapsed_ms))
+                    .unwrap_or_default();
+                let summary = format!("Canary job took {} ms end to end{}", run.total_ms, slowest);
+                self.alert(AlertKind::CanaryLatency, summary, run.total_ms as f64, self.config.slow_after_ms as f64, now).await;
+            }
+            _ => tracing::debug!("Canary completed in {} ms", run.total_ms),
+        }
+        Ok(run)
+    }
+
+    async fn ensure_tenant(&self) -> Result<(), AppError> {
+        if self.tenant_service.get_tenant(&self.config.tenant_id).await?.is_some() {
+            return Ok(());
+        }
+        self.tenant_service.create_tenant(CreateTenantRequest {
+            id: self.config.tenant_id.clone(),
+            name: "Synthetic canary".to_string(),
+            hosts: Vec::new(),
+            pricing: None,
+            currency_pricing: Vec::new(),
+            service_regions: Vec::new(),
+            mqtt_enabled: false,
+            mqtt_format: Default::default(),
+        }).await?;
+        Ok(())
+    }
+
+    async fn walk(&self, now: DateTime<Utc>) -> CanaryRun {
+        let started = Instant::now();
+        let mut run = CanaryRun {
+            job_id: None,
+            started_at: now,
+            steps: Vec::new(),
+            total_ms: 0,
+            failed_step: None,
+            error: None,
+        };
+        if let Err((step, e)) = self.steps(&mut run).await {
+            run.failed_step = Some(step);
+            run.error = Some(e.to_string());
+            // Don't leave the mock driver holding a job the next run would queue behind
+            if let Some(job_id) = &run.job_id {
+                self.abandon(job_id).await;
+            }
+        }
+        run.total_ms = started.elapsed().as_millis() as u64;
+        run
+    }
+
+    async fn steps(&self, run: &mut CanaryRun) -> Result<(), (CanaryStep, AppError)> {
+        let participants = timed(run, CanaryStep::Setup, self.participants()).await?;
+        let mut faker = Faker::new();
+
+        let job = timed(run, CanaryStep::Create, self.job_service.create_job(faker.job_request(&participants.customer_id))).await?;
+        run.job_id = Some(job.id.clone());
+        timed(run, CanaryStep::Assign, self.job_service.assign_driver_to_job(&job.id, &participants.driver_id)).await?;
+        for (step, status) in [(CanaryStep::PickUp, JobStatus::PackagePickedUp), (CanaryStep::Transit, JobStatus::InTransit)] {
+            let update = JobStatusUpdate {
+                job_id: job.id.clone(),
+                status,
+                driver_id: Some(participants.driver_id.clone()),
+                notes: None,
+            };
+            timed(run, step, self.job_service.update_job_status(update)).await?;
+        }
+        let completed = timed(run, CanaryStep::Complete, self.job_service.complete_job(&job.id)).await?;
+        if completed.status != JobStatus::DeliveryCompleted {
+            let error = AppError::InternalServer(format!("Job ended as {:?}", completed.status));
+            return Err((CanaryStep::Complete, error));
+        }
+        Ok(())
+    }
+
+    // The mock customer and driver, registered the first time through
+    async fn participants(&self) -> Result<CanaryParticipants, AppError> {
+        if let Some(participants) = self.cache_service.get_canary_participants().await? {
+            return Ok(participants);
+        }
+        let mut faker = Faker::new();
+        let customer = self.user_service.register_user(faker.user_registration(UserType::Customer)).await?;
+        let driver_user = faker.user(UserType::Driver);
+        let mut registration = faker.driver_registration(&driver_user);
+        // Fixture packages vary; the driver can carry any of them
+        registration.equipment = DriverEquipment { insulated_bag: true, upright_carrier: true };
+        let driver = self.driver_service.register_driver(registration).await?;
+
+        let participants = CanaryParticipants { customer_id: customer.id, driver_id: driver.id };
+        self.cache_service.cache_canary_participants(&participants).await?;
+        tracing::info!("Registered canary customer {} and driver {}", participants.customer_id, participants.driver_id);
+        Ok(participants)
+    }
+
+    async fn abandon(&self, job_id: &JobId) {
+        let open = matches!(self.cache_service.load_job(job_id).await, Ok(Some(job)) if !job.status.is_terminal());
+        if open && let Err(e) = self.job_service.cancel_job(job_id, Some("Canary run failed".to_string())).await {
+            tracing::warn!("Failed to cancel canary job {}: {}", job_id, e);
+        }
+    }
+
+    async fn alert(&self, kind: AlertKind, summary: String, value: f64, threshold: f64, now: DateTime<Utc>) {
+        self.alert_service.raise(&Alert {
+            kind,
+            tenant_id: Some(self.config.tenant_id.clone()),
+            summary,
+            value,
+            threshold,
+            raised_at: now,
+        }).await;
+    }
+}
+
+// Runs one step, noting how long it took or which step it failed at
+async fn timed<T, F>(run: &mut CanaryRun, step: CanaryStep, future: F) -> Result<T, (CanaryStep, AppError)>
+where
+    F: Future<Output = Result<T, AppError>>,
+{
+    let started = Instant::now();
+    let value = future.await.map_err(|e| (step, e))?;
+    run.steps.push(CanaryStepTiming { step, elapsed_ms: started.elapsed().as_millis() as u64 });
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{mocks::app::TestApp, models::tenant::DEFAULT_TENANT_ID, services::alert_service::AlertConfig};
+
+    #[tokio::test]
+    async fn test_canary_walks_a_job_to_completion_in_its_own_tenant() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let alerts = Arc::new(AlertService::new(
+            state.cache_service.clone(),
+            state.tenant_service.clone(),
+            state.dashboard_service.clone(),
+            AlertConfig::default(),
+        ));
+        let canary = CanaryService::new(
+            state.cache_service.clone(),
+            state.tenant_service.clone(),
+            state.user_service.clone(),
+            state.driver_service.clone(),
+            state.job_service.clone(),
+            alerts,
+            CanaryConfig::default(),
+        );
+
+        let first = canary.run(Utc::now()).await.unwrap();
+        assert!(first.succeeded(), "{:?}", first.error);
+        let steps: Vec<CanaryStep> = first.steps.iter().map(|timing| timing.step).collect();
+        assert_eq!(steps, vec![CanaryStep::Setup, CanaryStep::Create, CanaryStep::Assign, CanaryStep::PickUp, CanaryStep::Transit, CanaryStep::Complete]);
+
+        // The job lives in the canary tenant, not the default one
+        let job_id = first.job_id.clone().unwrap();
+        let job = with_tenant("canary".to_string(), state.cache_service.load_job(&job_id)).await.unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::DeliveryCompleted);
+        let elsewhere = with_tenant(DEFAULT_TENANT_ID.to_string(), state.cache_service.load_job(&job_id)).await.unwrap();
+        assert!(elsewhere.is_none());
+
+        // Later runs reuse the same mock accounts
+        let second = canary.run(Utc::now()).await.unwrap();
+        assert!(second.succeeded(), "{:?}", second.error);
+        let status = canary.status().await.unwrap();
+        assert_eq!((status.runs.len(), status.failures), (2, 0));
+        assert_eq!(status.runs[0].job_id, second.job_id);
+        assert!(status.average_total_ms.is_some());
+    }
+}
@@ -0,0 +1,198 @@
+// src/services/chaos.rs
+// Fault injection for resilience testing: artificial latency and failures in the cache,
+// notification and payment layers, so staging can check that retries, fallbacks and
+// alerts actually kick in. Rules are set through `/admin/chaos/faults`, per layer and
+// operation, as a percentage of matching calls, and lapse on their own.
+//
+// Only builds made with `--features chaos` can inject anything; elsewhere `inject` is an
+// empty function and the admin routes don't exist. Rules live in the memory of the
+// instance that received them, as the cache itself may be what is failing.
+use crate::{
+    errors::SparrowError as AppError,
+    models::chaos::FaultLayer,
+    services::cache_service::CacheError,
+};
+
+#[cfg(feature = "chaos")]
+pub use injector::{faults, FaultInjector};
+
+#[derive(Debug, Clone, Copy)]
+pub struct InjectedFault {
+    pub layer: FaultLayer,
+    pub operation: &'static str,
+}
+
+// Surfaces as a refused connection, the same as Redis going away
+impl From<InjectedFault> for CacheError {
+    fn from(fault: InjectedFault) -> Self {
+        let message = format!("injected fault in {}", fault.operation);
+        CacheError::Redis(redis::RedisError::from(std::io::Error::new(std::io::ErrorKind::ConnectionRefused, message)))
+    }
+}
+
+impl From<InjectedFault> for AppError {
+    fn from(fault: InjectedFault) -> Self {
+        tracing::debug!("Injected fault in {} {}", fault.layer, fault.operation);
+        AppError::service_unavailable(fault.layer.to_string())
+    }
+}
+
+/// Delays or fails the call as the rules say; always passes without the `chaos` feature
+#[cfg(feature = "chaos")]
+pub async fn inject(layer: FaultLayer, operation: &'static str) -> Result<(), InjectedFault> {
+    faults().inject(layer, operation).await
+}
+
+#[cfg(not(feature = "chaos"))]
+#[inline(always)]
+pub async fn inject(_layer: FaultLayer, _operation: &'static str) -> Result<(), InjectedFault> {
+    Ok(())
+}
+
+#[cfg(feature = "chaos")]
+mod injector {
+    use chrono::{Duration, Utc};
+    use rand::Rng;
+    use std::sync::{LazyLock, Mutex};
+    use tracing;
+
+    use super::InjectedFault;
+    use crate::{
+        errors::SparrowError as AppError,
+        models::chaos::{FaultLayer, FaultRule, SetFaultsRequest, ANY_OPERATION},
+    };
+
+    // Longest a rule may stay in force
+    const MAX_DURATION_SECONDS: u64 = 3600;
+
+    static FAULTS: LazyLock<FaultInjector> = LazyLock::new(FaultInjector::default);
+
+    /// The rules every `inject` call in this process checks
+    pub fn faults() -> &'static FaultInjector {
+        &FAULTS
+    }
+
+    #[derive(Default)]
+    pub struct FaultInjector {
+        rules: Mutex<Vec<FaultRule>>,
+    }
+
+    impl FaultInjector {
+        // Rules still in force
+        pub fn rules(&self) -> Vec<FaultRule> {
+            let now = Utc::now();
+            let mut rules = self.rules.lock().unwrap();
+            rules.retain(|rule| rule.expires_at > now);
+            rules.clone()
+        }
+
+        pub fn set_rules(&self, request: SetFaultsRequest) -> Result<Vec<FaultRule>, AppError> {
+            let now = Utc::now();
+            let mut rules = Vec::with_capacity(request.rules.len());
+            for (index, rule) in request.rules.into_iter().enumerate() {
+                let field = |name: &str| format!("rules[{}].{}", index, name);
+                if rule.operation != ANY_OPERATION && !rule.layer.operations().contains(&rule.operation.as_str()) {
+                    let known = rule.layer.operations().join(", ");
+                    return Err(AppError::validation_error(field("operation"), format!("Use \"*\" or one of {}", known)));
+                }
+                for (name, percent) in [("failure_percent", rule.failure_percent), ("latency_percent", rule.latency_percent)] {
+                    if !(0.0..=100.0).contains(&percent) {
+                        return Err(AppError::validation_error(field(name), "Must be between 0 and 100"));
+                    }
+                }
+                if rule.duration_seconds == 0 || rule.duration_seconds > MAX_DURATION_SECONDS {
+                    return Err(AppError::validation_error(field("duration_seconds"), format!("Must be between 1 and {}", MAX_DURATION_SECONDS)));
+                }
+                rules.push(FaultRule {
+                    layer: rule.layer,
+                    operation: rule.operation,
+                    failure_percent: rule.failure_percent,
+                    latency_ms: rule.latency_ms,
+                    latency_percent: rule.latency_percent,
+                    expires_at: now + Duration::seconds(rule.duration_seconds as i64),
+                });
+            }
+
+            for rule in &rules {
+                tracing::warn!(
+                    "Injecting faults into {} {}: {}% failures, {}% delayed {} ms, until {}",
+                    rule.layer, rule.operation, rule.failure_percent, rule.latency_percent, rule.latency_ms, rule.expires_at,
+                );
+            }
+            *self.rules.lock().unwrap() = rules.clone();
+            Ok(rules)
+        }
+
+        pub fn clear(&self) {
+            self.rules.lock().unwrap().clear();
+            tracing::info!("Cleared injected faults");
+        }
+
+        pub async fn inject(&self, layer: FaultLayer, operation: &'static str) -> Result<(), InjectedFault> {
+            let (delay, fail) = self.roll(layer, operation);
+            if let Some(delay) = delay {
+                tokio::time::sleep(delay).await;
+            }
+            if fail {
+                return Err(InjectedFault { layer, operation });
+            }
+            Ok(())
+        }
+
+        // What happens to this call, under the first rule that matches it
+        fn roll(&self, layer: FaultLayer, operation: &str) -> (Option<std::time::Duration>, bool) {
+            let now = Utc::now();
+            let rules = self.rules.lock().unwrap();
+            let Some(rule) = rules.iter().find(|rule| {
+                rule.layer == layer && (rule.operation == ANY_OPERATION || rule.operation == operation) && rule.expires_at > now
+            }) else {
+                return (None, false);
+            };
+            let mut rng = rand::rng();
+            let delay = (rule.latency_ms > 0 && rng.random_range(0.0..100.0) < rule.latency_percent)
+                .then(|| std::time::Duration::from_millis(rule.latency_ms));
+            let fail = rng.random_range(0.0..100.0) < rule.failure_percent;
+            (delay, fail)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        use crate::{models::chaos::FaultRuleRequest, services::cache_service::CacheError};
+
+        fn rule(layer: FaultLayer, operation: &str, failure_percent: f64) -> FaultRuleRequest {
+            FaultRuleRequest {
+                layer,
+                operation: operation.to_string(),
+                failure_percent,
+                latency_ms: 0,
+                latency_percent: 0.0,
+                duration_seconds: 60,
+            }
+        }
+
+        #[tokio::test]
+        async fn test_rules_fail_matching_operations_until_cleared() {
+            let faults = FaultInjector::default();
+            assert!(faults.inject(FaultLayer::Cache, "get").await.is_ok());
+
+            faults.set_rules(SetFaultsRequest {
+                rules: vec![rule(FaultLayer::Cache, "get", 100.0), rule(FaultLayer::Payment, "*", 0.0)],
+            }).unwrap();
+            let fault = faults.inject(FaultLayer::Cache, "get").await.unwrap_err();
+            assert_eq!(fault.operation, "get");
+            assert!(matches!(AppError::from(CacheError::from(fault)), AppError::RedisConnection(_)));
+            assert!(faults.inject(FaultLayer::Cache, "set").await.is_ok());
+            assert!(faults.inject(FaultLayer::Payment, "post_entry").await.is_ok());
+
+            let unknown = faults.set_rules(SetFaultsRequest { rules: vec![rule(FaultLayer::Notification, "charge", 50.0)] });
+            assert!(unknown.is_err());
+            assert_eq!(faults.rules().len(), 2);
+
+            faults.clear();
+            assert!(faults.inject(FaultLayer::Cache, "get").await.is_ok());
+        }
+    }
+}
@@ -0,0 +1,575 @@
+// src/services/consumers.rs
+// Runs event subscribers off the event log as consumer groups, one group per subscriber,
+// so each subscriber sees every event once across all instances. The framework does the
+// bookkeeping and a subscriber only implements `handle`:
+//
+// - an entry is acknowledged once handled, and the group's checkpoint moves past it
+// - an entry that failed, or whose consumer died, is claimed again once it has sat idle
+// - a redelivered entry that was already handled (the ack was lost) is skipped, which is
+//   as close to exactly once as an at-least-once log gets
+// - an entry that keeps failing is parked after `max_deliveries` so the rest can go on
+// - a group can be rewound to any offset to replay what came after it
+use async_trait::async_trait;
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing;
+use uuid::Uuid;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        consumer::{ConsumerCheckpoint, ConsumerStatus, ParkedEvent},
+        domain_event::EventEnvelope,
+    },
+    services::{
+        cache_service::CacheService,
+        event_bus::EventSubscriber,
+        region::with_region,
+        tenant_service::with_tenant,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub offset: String,
+    pub payload: String,
+    pub deliveries: u64, // Including this one
+}
+
+// An append-only log read through consumer groups, as Redis Streams are
+#[async_trait]
+pub trait EventLog: Send + Sync {
+    /// Returns the new entry's offset
+    async fn append(&self, payload: &str) -> Result<String, AppError>;
+    /// Start `group` at the end of the log; a group that exists keeps its place
+    async fn create_group(&self, group: &str) -> Result<(), AppError>;
+    /// Entries no one in `group` has been given yet, waiting a while for some if need be
+    async fn read_new(&self, group: &str, consumer: &str, count: usize) -> Result<Vec<LogEntry>, AppError>;
+    /// Take over entries given out at least `min_idle` ago and never acknowledged
+    async fn claim_stale(&self, group: &str, consumer: &str, min_idle: Duration, count: usize) -> Result<Vec<LogEntry>, AppError>;
+    async fn ack(&self, group: &str, offset: &str) -> Result<(), AppError>;
+    /// Deliver the entries after `offset` to `group` again
+    async fn rewind(&self, group: &str, offset: &str) -> Result<(), AppError>;
+}
+
+// (milliseconds, sequence) of an offset like "1718000000000-3"; "0" is the start of the log
+fn position(offset: &str) -> Option<(u64, u64)> {
+    let (ms, sequence) = offset.split_once('-').unwrap_or((offset, "0"));
+    Some((ms.parse().ok()?, sequence.parse().ok()?))
+}
+
+#[derive(Default)]
+struct MemoryGroup {
+    delivered: usize,                            // Entries before this index have been given out
+    pending: BTreeMap<usize, (u64, Instant)>,    // Index -> (deliveries, given out at)
+}
+
+#[derive(Default)]
+struct MemoryLog {
+    entries: Vec<String>,
+    groups: HashMap<String, MemoryGroup>,
+}
+
+/// The log within one process, for tests and for running without Redis. Reads don't wait.
+#[derive(Default)]
+pub struct MemoryEventLog {
+    inner: Mutex<MemoryLog>,
+}
+
+impl MemoryEventLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn offset(index: usize) -> String {
+        format!("{}-0", index + 1)
+    }
+
+    fn index(offset: &str) -> Result<usize, AppError> {
+        let (ms, _) = position(offset).ok_or_else(|| AppError::validation_error("from", format!("Invalid offset {}", offset)))?;
+        Ok(ms as usize)
+    }
+}
+
+#[async_trait]
+impl EventLog for MemoryEventLog {
+    async fn append(&self, payload: &str) -> Result<String, AppError> {
+        let mut log = self.inner.lock().unwrap();
+        log.entries.push(payload.to_string());
+        Ok(Self::offset(log.entries.len() - 1))
+    }
+
+    async fn create_group(&self, group: &str) -> Result<(), AppError> {
+        let mut log = self.inner.lock().unwrap();
+        let end = log.entries.len();
+        log.groups.entry(group.to_string()).or_insert_with(|| MemoryGroup { delivered: end, ..Default::default() });
+        Ok(())
+    }
+
+    async fn read_new(&self, group: &str, _consumer: &str, count: usize) -> Result<Vec<LogEntry>, AppError> {
+        let mut log = self.inner.lock().unwrap();
+        let MemoryLog { entries, groups } = &mut *log;
+        let state = groups.get_mut(group).ok_or_else(|| AppError::not_found(format!("Consumer group {}", group)))?;
+        let end = entries.len().min(state.delivered + count);
+        let read: Vec<LogEntry> = (state.delivered..end)
+            .map(|index| {
+                state.pending.insert(index, (1, Instant::now()));
+                LogEntry { offset: Self::offset(index), payload: entries[index].clone(), deliveries: 1 }
+            })
+            .collect();
+        state.delivered = end.max(state.delivered);
+        Ok(read)
+    }
+
+    async fn claim_stale(&self, group: &str, _consumer: &str, min_idle: Duration, count: usize) -> Result<Vec<LogEntry>, AppError> {
+        let mut log = self.inner.lock().unwrap();
+        let MemoryLog { entries, groups } = &mut *log;
+        let state = groups.get_mut(group).ok_or_else(|| AppError::not_found(format!("Consumer group {}", group)))?;
+        Ok(state.pending.iter_mut()
+            .filter(|(_, (_, given_at))| given_at.elapsed() >= min_idle)
+            .take(count)
+            .map(|(index, (deliveries, given_at))| {
+                *deliveries += 1;
+                *given_at = Instant::now();
+                LogEntry { offset: Self::offset(*index), payload: entries[*index].clone(), deliveries: *deliveries }
+            })
+            .collect())
+    }
+
+    async fn ack(&self, group: &str, offset: &str) -> Result<(), AppError> {
+        let index = Self::index(offset)?.saturating_sub(1);
+        if let Some(state) = self.inner.lock().unwrap().groups.get_mut(group) {
+            state.pending.remove(&index);
+        }
+        Ok(())
+    }
+
+    async fn rewind(&self, group: &str, offset: &str) -> Result<(), AppError> {
+        let index = Self::index(offset)?;
+        let mut log = self.inner.lock().unwrap();
+        let end = log.entries.len();
+        let state = log.groups.get_mut(group).ok_or_else(|| AppError::not_found(format!("Consumer group {}", group)))?;
+        state.delivered = index.min(end);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RedisStreamConfig {
+    pub stream_key: String,
+    pub max_len: usize,         // Approximate; older entries are trimmed as new ones arrive
+    pub block_milliseconds: u64, // How long one read waits for new entries
+}
+
+impl Default for RedisStreamConfig {
+    fn default() -> Self {
+        Self {
+            stream_key: "events:stream".to_string(),
+            max_len: 100_000,
+            block_milliseconds: 5000,
+        }
+    }
+}
+
+/// The log as a Redis stream, shared by every instance
+pub struct RedisStreamLog {
+    client: redis::Client,
+    config: RedisStreamConfig,
+}
+
+impl RedisStreamLog {
+    pub fn new(client: redis::Client, config: RedisStreamConfig) -> Self {
+        Self { client, config }
+    }
+
+    fn entries(ids: Vec<redis::streams::StreamId>, deliveries: &HashMap<String, u64>) -> Vec<LogEntry> {
+        ids.into_iter()
+            .map(|entry| LogEntry {
+                payload: entry.get::<String>("payload").unwrap_or_default(),
+                deliveries: deliveries.get(&entry.id).copied().unwrap_or(1),
+                offset: entry.id,
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl EventLog for RedisStreamLog {
+    async fn append(&self, payload: &str) -> Result<String, AppError> {
+        let mut connection = self.client.get_async_connection().await?;
+        Ok(redis::cmd("XADD")
+            .arg(&self.config.stream_key)
+            .arg("MAXLEN").arg("~").arg(self.config.max_len)
+            .arg("*")
+            .arg("payload").arg(payload)
+            .query_async(&mut connection)
+            .await?)
+    }
+
+    async fn create_group(&self, group: &str) -> Result<(), AppError> {
+        let mut connection = self.client.get_async_connection().await?;
+        let created: Result<(), redis::RedisError> = redis::cmd("XGROUP")
+            .arg("CREATE").arg(&self.config.stream_key).arg(group).arg("$").arg("MKSTREAM")
+            .query_async(&mut connection)
+            .await;
+        match created {
+            Err(e) if e.code() != Some("BUSYGROUP") => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn read_new(&self, group: &str, consumer: &str, count: usize) -> Result<Vec<LogEntry>, AppError> {
+        let mut connection = self.client.get_async_connection().await?;
+        let reply: Option<redis::streams::StreamReadReply> = redis::cmd("XREADGROUP")
+            .arg("GROUP").arg(group).arg(consumer)
+            .arg("COUNT").arg(count)
+            .arg("BLOCK").arg(self.config.block_milliseconds)
+            .arg("STREAMS").arg(&self.config.stream_key).arg(">")
+            .query_async(&mut connection)
+            .await?;
+        let ids = reply.into_iter().flat_map(|reply| reply.keys).flat_map(|key| key.ids).collect();
+        Ok(Self::entries(ids, &HashMap::new()))
+    }
+
+    async fn claim_stale(&self, group: &str, consumer: &str, min_idle: Duration, count: usize) -> Result<Vec<LogEntry>, AppError> {
+        let mut connection = self.client.get_async_connection().await?;
+        let idle = min_idle.as_millis() as u64;
+        let pending: redis::streams::StreamPendingCountReply = redis::cmd("XPENDING")
+            .arg(&self.config.stream_key).arg(group)
+            .arg("IDLE").arg(idle)
+            .arg("-").arg("+").arg(count)
+            .query_async(&mut connection)
+            .await?;
+        if pending.ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        // Claiming counts as another delivery
+        let deliveries: HashMap<String, u64> = pending.ids.iter()
+            .map(|pending| (pending.id.clone(), pending.times_delivered as u64 + 1))
+            .collect();
+        let mut claim = redis::cmd("XCLAIM");
+        claim.arg(&self.config.stream_key).arg(group).arg(consumer).arg(idle);
+        for pending in &pending.ids {
+            claim.arg(&pending.id);
+        }
+        let claimed: redis::streams::StreamClaimReply = claim.query_async(&mut connection).await?;
+        // Entries trimmed from the stream can't be claimed; all that's left is to let them go
+        for pending in &pending.ids {
+            if !claimed.ids.iter().any(|entry| entry.id == pending.id) {
+                self.ack(group, &pending.id).await?;
+            }
+        }
+        Ok(Self::entries(claimed.ids, &deliveries))
+    }
+
+    async fn ack(&self, group: &str, offset: &str) -> Result<(), AppError> {
+        let mut connection = self.client.get_async_connection().await?;
+        let _: i64 = redis::cmd("XACK")
+            .arg(&self.config.stream_key).arg(group).arg(offset)
+            .query_async(&mut connection)
+            .await?;
+        Ok(())
+    }
+
+    async fn rewind(&self, group: &str, offset: &str) -> Result<(), AppError> {
+        let mut connection = self.client.get_async_connection().await?;
+        let _: () = redis::cmd("XGROUP")
+            .arg("SETID").arg(&self.config.stream_key).arg(group).arg(offset)
+            .query_async(&mut connection)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConsumerConfig {
+    pub batch_size: usize,
+    pub claim_idle_seconds: u64,  // How long an entry sits unacknowledged before it is retried
+    pub max_deliveries: u64,      // Attempts before an entry is parked
+    pub handled_ttl_seconds: u64, // How long a handled entry is remembered against redelivery
+    pub retry_delay_seconds: u64, // Pause after the log itself fails
+}
+
+impl Default for ConsumerConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 50,
+            claim_idle_seconds: 30,
+            max_deliveries: 5,
+            handled_ttl_seconds: 86400,
+            retry_delay_seconds: 5,
+        }
+    }
+}
+
+impl ConsumerConfig {
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.parse().ok());
+        let defaults = Self::default();
+        Self {
+            claim_idle_seconds: var("EVENT_CONSUMER_CLAIM_IDLE_SECONDS").unwrap_or(defaults.claim_idle_seconds),
+            max_deliveries: var("EVENT_CONSUMER_MAX_DELIVERIES").unwrap_or(defaults.max_deliveries),
+            ..defaults
+        }
+    }
+}
+
+/// One subscriber reading the log as its own consumer group
+pub struct Consumer {
+    subscriber: Arc<dyn EventSubscriber>,
+    log: Arc<dyn EventLog>,
+    cache_service: Arc<CacheService>,
+    config: ConsumerConfig,
+    name: String, // This instance, within the group
+}
+
+impl Consumer {
+    pub fn group(&self) -> &'static str {
+        self.subscriber.name()
+    }
+
+    /// Handle what is waiting: first entries due a retry, then new ones. Returns how many
+    /// entries were looked at.
+    pub async fn poll(&self) -> Result<usize, AppError> {
+        let idle = Duration::from_secs(self.config.claim_idle_seconds);
+        let mut entries = self.log.claim_stale(self.group(), &self.name, idle, self.config.batch_size).await?;
+        entries.extend(self.log.read_new(self.group(), &self.name, self.config.batch_size).await?);
+        for entry in &entries {
+            self.process(entry).await?;
+        }
+        Ok(entries.len())
+    }
+
+    async fn process(&self, entry: &LogEntry) -> Result<(), AppError> {
+        let group = self.group();
+        if entry.deliveries > 1 && self.cache_service.was_consumer_handled(group, &entry.offset).await? {
+            self.log.ack(group, &entry.offset).await?;
+            return self.checkpoint(&entry.offset, |checkpoint| checkpoint.skipped += 1).await;
+        }
+        let handled = match serde_json::from_str::<EventEnvelope>(&entry.payload) {
+            Ok(envelope) => {
+                let handling = self.subscriber.handle(&envelope);
+                with_region(envelope.region_id.clone(), with_tenant(envelope.tenant_id.clone(), handling)).await
+            }
+            // Nothing will make it readable, so there's no point trying again
+            Err(e) => return self.park(entry, format!("Unreadable event: {}", e)).await,
+        };
+        match handled {
+            Ok(()) => {
+                self.cache_service.mark_consumer_handled(group, &entry.offset, self.config.handled_ttl_seconds).await?;
+                self.log.ack(group, &entry.offset).await?;
+                self.checkpoint(&entry.offset, |checkpoint| checkpoint.handled += 1).await
+            }
+            Err(e) if entry.deliveries >= self.config.max_deliveries => self.park(entry, e.to_string()).await,
+            Err(e) => {
+                tracing::warn!("Consumer {} failed on {} (attempt {}), will retry: {}", group, entry.offset, entry.deliveries, e);
+                Ok(())
+            }
+        }
+    }
+
+    async fn park(&self, entry: &LogEntry, error: String) -> Result<(), AppError> {
+        let group = self.group();
+        tracing::error!("Consumer {} parked {} after {} attempts: {}", group, entry.offset, entry.deliveries, error);
+        self.cache_service.park_consumer_event(&ParkedEvent {
+            group: group.to_string(),
+            offset: entry.offset.clone(),
+            payload: entry.payload.clone(),
+            deliveries: entry.deliveries,
+            error,
+            parked_at: Utc::now(),
+        }).await?;
+        self.log.ack(group, &entry.offset).await?;
+        self.checkpoint(&entry.offset, |checkpoint| checkpoint.parked += 1).await
+    }
+
+    // Retries can finish behind newer entries, so the offset only ever moves forward. Only
+    // this consumer writes its checkpoint, and it handles one entry at a time.
+    async fn checkpoint(&self, offset: &str, count: impl FnOnce(&mut ConsumerCheckpoint)) -> Result<(), AppError> {
+        let group = self.group();
+        let mut checkpoint = self.cache_service.get_consumer_checkpoint(group, &self.name).await?
+            .unwrap_or_else(|| ConsumerCheckpoint::new(group, Some(&self.name)));
+        if position(offset) > position(&checkpoint.offset) {
+            checkpoint.offset = offset.to_string();
+        }
+        count(&mut checkpoint);
+        checkpoint.updated_at = Utc::now();
+        self.cache_service.set_consumer_checkpoint(&checkpoint).await
+    }
+
+    /// Consume for the life of the process
+    pub async fn run(self: Arc<Self>) {
+        let retry_delay = Duration::from_secs(self.config.retry_delay_seconds);
+        while let Err(e) = self.log.create_group(self.group()).await {
+            tracing::warn!("Failed to set up consumer group {}: {}", self.group(), e);
+            tokio::time::sleep(retry_delay).await;
+        }
+        tracing::info!("Consumer {} reading the event log as {}", self.group(), self.name);
+        loop {
+            if let Err(e) = self.poll().await {
+                tracing::warn!("Consumer {} lost the event log: {}", self.group(), e);
+                tokio::time::sleep(retry_delay).await;
+            }
+        }
+    }
+}
+
+/// Builds consumers over a log and answers for their groups
+pub struct Consumers {
+    log: Arc<dyn EventLog>,
+    cache_service: Arc<CacheService>,
+    config: ConsumerConfig,
+    name: String,
+}
+
+impl Consumers {
+    pub fn new(log: Arc<dyn EventLog>, cache_service: Arc<CacheService>, config: ConsumerConfig) -> Self {
+        Self { log, cache_service, config, name: Uuid::new_v4().to_string() }
+    }
+
+    pub fn log(&self) -> &Arc<dyn EventLog> {
+        &self.log
+    }
+
+    pub fn consumer(&self, subscriber: Arc<dyn EventSubscriber>) -> Consumer {
+        Consumer {
+            subscriber,
+            log: self.log.clone(),
+            cache_service: self.cache_service.clone(),
+            config: self.config.clone(),
+            name: self.name.clone(),
+        }
+    }
+
+    pub async fn status(&self, group: &str) -> Result<ConsumerStatus, AppError> {
+        let consumers = self.cache_service.get_consumer_checkpoints(group).await?;
+        let mut checkpoint = ConsumerCheckpoint::new(group, None);
+        for consumer in &consumers {
+            if position(&consumer.offset) > position(&checkpoint.offset) {
+                checkpoint.offset = consumer.offset.clone();
+            }
+            checkpoint.handled += consumer.handled;
+            checkpoint.skipped += consumer.skipped;
+            checkpoint.parked += consumer.parked;
+        }
+        if let Some(updated_at) = consumers.iter().map(|consumer| consumer.updated_at).max() {
+            checkpoint.updated_at = updated_at;
+        }
+        Ok(ConsumerStatus {
+            group: group.to_string(),
+            checkpoint,
+            consumers,
+            parked: self.cache_service.get_parked_consumer_events(group).await?,
+        })
+    }
+
+    /// Deliver everything after `offset` to `group` again. Redeliveries that were handled
+    /// are skipped, but a replay reads the entries afresh, so they are handled again.
+    pub async fn replay(&self, group: &str, offset: &str) -> Result<(), AppError> {
+        if position(offset).is_none() {
+            return Err(AppError::validation_error("from", format!("Invalid offset {}", offset)));
+        }
+        self.log.rewind(group, offset).await?;
+        tracing::info!("Consumer group {} rewound to {}", group, offset);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{consumer::LOG_START, domain_event::DomainEvent, ids::{DriverId, JobId}},
+        services::cache_service::CacheConfig,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Fails on drivers it's told to, and counts what it handled
+    struct Flaky {
+        broken: DriverId,
+        handled: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl EventSubscriber for Flaky {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        async fn handle(&self, envelope: &EventEnvelope) -> Result<(), AppError> {
+            match &envelope.event {
+                DomainEvent::JobAssigned { driver_id, .. } if *driver_id == self.broken => Err(AppError::internal_error("always fails")),
+                _ => {
+                    self.handled.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    fn payload(driver_id: &DriverId) -> String {
+        serde_json::to_string(&EventEnvelope {
+            event_id: Uuid::new_v4().to_string(),
+            tenant_id: "default".to_string(),
+            region_id: "gh".to_string(),
+            occurred_at: Utc::now(),
+            event: DomainEvent::JobAssigned { job_id: JobId::generate(), driver_id: driver_id.clone(), offered: true },
+        }).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_consumers_checkpoint_park_poison_and_replay() {
+        let cache_service = Arc::new(CacheService::new_memory(CacheConfig::default()));
+        let log = Arc::new(MemoryEventLog::new());
+        let config = ConsumerConfig { claim_idle_seconds: 0, max_deliveries: 3, ..Default::default() };
+        let consumers = Consumers::new(log.clone(), cache_service.clone(), config.clone());
+        let subscriber = Arc::new(Flaky { broken: DriverId::generate(), handled: AtomicUsize::new(0) });
+        let consumer = consumers.consumer(subscriber.clone());
+        log.create_group("flaky").await.unwrap();
+
+        let (good, poison) = (DriverId::generate(), subscriber.broken.clone());
+        log.append(&payload(&good)).await.unwrap();
+        let poison_offset = log.append(&payload(&poison)).await.unwrap();
+        log.append("not an event").await.unwrap();
+        let last = log.append(&payload(&good)).await.unwrap();
+
+        // The poison entry is retried until it runs out of attempts; the rest go through
+        for _ in 0..3 {
+            consumer.poll().await.unwrap();
+        }
+        assert_eq!(subscriber.handled.load(Ordering::SeqCst), 2);
+        let status = consumers.status("flaky").await.unwrap();
+        assert_eq!((status.checkpoint.handled, status.checkpoint.parked), (2, 2));
+        assert_eq!(status.checkpoint.offset, last);
+        assert_eq!(status.parked.iter().map(|parked| parked.offset.as_str()).collect::<Vec<_>>(), vec!["3-0", poison_offset.as_str()]);
+        assert_eq!(consumer.poll().await.unwrap(), 0);
+
+        // A handled entry whose ack went missing comes back, and is skipped
+        log.rewind("flaky", LOG_START).await.unwrap();
+        let redelivered = log.read_new("flaky", "other", 1).await.unwrap();
+        assert_eq!(redelivered.len(), 1);
+        assert_eq!(consumer.poll().await.unwrap(), 4);
+        let status = consumers.status("flaky").await.unwrap();
+        assert_eq!(status.checkpoint.skipped, 1);
+
+        // A replay hands over everything after the offset afresh
+        let handled = subscriber.handled.load(Ordering::SeqCst);
+        consumers.replay("flaky", &poison_offset).await.unwrap();
+        consumer.poll().await.unwrap();
+        assert_eq!(subscriber.handled.load(Ordering::SeqCst), handled + 1);
+        assert!(consumers.replay("flaky", "yesterday").await.is_err());
+
+        // Another instance in the group counts on its own checkpoint; the group's adds them up
+        let other = Consumers::new(log.clone(), cache_service.clone(), config).consumer(subscriber.clone());
+        let before = consumers.status("flaky").await.unwrap().checkpoint.handled;
+        let newest = log.append(&payload(&good)).await.unwrap();
+        other.poll().await.unwrap();
+        let status = consumers.status("flaky").await.unwrap();
+        assert_eq!(status.consumers.len(), 2);
+        assert_eq!(status.checkpoint.handled, before + 1);
+        assert_eq!(status.checkpoint.handled, status.consumers.iter().map(|consumer| consumer.handled).sum::<u64>());
+        assert_eq!(status.checkpoint.offset, newest);
+    }
+}
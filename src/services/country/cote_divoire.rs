@@ -0,0 +1,86 @@
+// src/services/country/cote_divoire.rs
+// Côte d'Ivoire prices in CFA francs, which have no minor unit, and charges a single TVA.
+use chrono::DateTime;
+
+use crate::{
+    models::{money::Currency, tax::{TaxLevy, TaxSchedule}, tenant::{PricingConfig, Surcharges}},
+    services::country::{registered_currency, CountryRules},
+};
+
+pub struct CoteDivoire;
+
+impl CountryRules for CoteDivoire {
+    fn region_id(&self) -> &'static str {
+        "ci"
+    }
+
+    fn name(&self) -> &'static str {
+        "Côte d'Ivoire"
+    }
+
+    fn countries(&self) -> &'static [&'static str] {
+        &["CI", "Côte d'Ivoire", "Cote d'Ivoire", "Ivory Coast"]
+    }
+
+    fn currency(&self) -> Currency {
+        registered_currency("XOF")
+    }
+
+    fn dialing_code(&self) -> &'static str {
+        "+225"
+    }
+
+    fn utc_offset_minutes(&self) -> i32 {
+        0
+    }
+
+    fn national_number_digits(&self) -> usize {
+        10
+    }
+
+    fn base_fares(&self) -> PricingConfig {
+        PricingConfig {
+            currency: self.currency(),
+            base_fare_standard: 600.0,
+            base_fare_express: 1000.0,
+            base_fare_same_day: 1600.0,
+            base_fare_emergency: 2400.0,
+            per_km: 100.0,
+            per_minute: 8.0,
+            service_fee_rate: 0.1,
+            surcharges: Some(Surcharges {
+                small_package: 200.0,
+                medium_package: 400.0,
+                large_package: 800.0,
+                extra_large: 1600.0,
+                food: 320.0,
+                grocery: 600.0,
+                pharmacy: 200.0,
+                electronics: 600.0,
+                fragile: 480.0,
+                express: 400.0,
+                same_day: 1000.0,
+                emergency: 2000.0,
+            }),
+        }
+    }
+
+    fn tax_schedule(&self) -> TaxSchedule {
+        TaxSchedule {
+            effective_from: DateTime::UNIX_EPOCH,
+            levies: vec![TaxLevy::new("TVA", "Taxe sur la valeur ajoutée", 0.18, false)],
+        }
+    }
+
+    fn public_holidays(&self) -> &'static [(u32, u32, &'static str)] {
+        &[
+            (1, 1, "Jour de l'an"),
+            (5, 1, "Fête du travail"),
+            (8, 7, "Fête de l'indépendance"),
+            (8, 15, "Assomption"),
+            (11, 1, "Toussaint"),
+            (11, 15, "Journée nationale de la paix"),
+            (12, 25, "Noël"),
+        ]
+    }
+}
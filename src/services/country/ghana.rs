@@ -0,0 +1,92 @@
+// src/services/country/ghana.rs
+// Where the service launched. VAT is charged on top of the NHIL, GETFund and COVID-19
+// levies rather than on the fare alone.
+use chrono::{DateTime, Datelike, NaiveDate, Weekday};
+
+use crate::{
+    models::{money::Currency, tax::{TaxLevy, TaxSchedule}, tenant::{PricingConfig, Surcharges}},
+    services::country::{fixed_holiday, CountryRules},
+};
+
+pub struct Ghana;
+
+impl CountryRules for Ghana {
+    fn region_id(&self) -> &'static str {
+        "gh"
+    }
+
+    fn name(&self) -> &'static str {
+        "Ghana"
+    }
+
+    fn countries(&self) -> &'static [&'static str] {
+        &["GH", "Ghana"]
+    }
+
+    fn currency(&self) -> Currency {
+        Currency::GHS
+    }
+
+    fn dialing_code(&self) -> &'static str {
+        "+233"
+    }
+
+    fn utc_offset_minutes(&self) -> i32 {
+        0
+    }
+
+    fn national_number_digits(&self) -> usize {
+        9
+    }
+
+    fn base_fares(&self) -> PricingConfig {
+        PricingConfig {
+            surcharges: Some(Surcharges {
+                small_package: 5.0,
+                medium_package: 10.0,
+                large_package: 20.0,
+                extra_large: 40.0,
+                food: 8.0,
+                grocery: 15.0,
+                pharmacy: 5.0,
+                electronics: 15.0,
+                fragile: 12.0,
+                express: 10.0,
+                same_day: 25.0,
+                emergency: 50.0,
+            }),
+            ..PricingConfig::default()
+        }
+    }
+
+    fn tax_schedule(&self) -> TaxSchedule {
+        TaxSchedule {
+            effective_from: DateTime::UNIX_EPOCH,
+            levies: vec![
+                TaxLevy::new("NHIL", "National Health Insurance Levy", 0.025, false),
+                TaxLevy::new("GETFUND", "GETFund Levy", 0.025, false),
+                TaxLevy::new("COVID19", "COVID-19 Health Recovery Levy", 0.01, false),
+                TaxLevy::new("VAT", "Value Added Tax", 0.15, true),
+            ],
+        }
+    }
+
+    fn public_holidays(&self) -> &'static [(u32, u32, &'static str)] {
+        &[
+            (1, 1, "New Year's Day"),
+            (1, 7, "Constitution Day"),
+            (3, 6, "Independence Day"),
+            (5, 1, "May Day"),
+            (8, 4, "Founders' Day"),
+            (9, 21, "Kwame Nkrumah Memorial Day"),
+            (12, 25, "Christmas Day"),
+            (12, 26, "Boxing Day"),
+        ]
+    }
+
+    fn public_holiday(&self, date: NaiveDate) -> Option<&'static str> {
+        // Farmers' Day falls on the first Friday of December
+        let farmers_day = date.month() == 12 && date.weekday() == Weekday::Fri && date.day() <= 7;
+        fixed_holiday(self.public_holidays(), date).or(farmers_day.then_some("Farmers' Day"))
+    }
+}
@@ -0,0 +1,87 @@
+// src/services/country/kenya.rs
+// Kenya charges a single VAT on the fare.
+use chrono::DateTime;
+
+use crate::{
+    models::{money::Currency, tax::{TaxLevy, TaxSchedule}, tenant::{PricingConfig, Surcharges}},
+    services::country::{registered_currency, CountryRules},
+};
+
+pub struct Kenya;
+
+impl CountryRules for Kenya {
+    fn region_id(&self) -> &'static str {
+        "ke"
+    }
+
+    fn name(&self) -> &'static str {
+        "Kenya"
+    }
+
+    fn countries(&self) -> &'static [&'static str] {
+        &["KE", "Kenya"]
+    }
+
+    fn currency(&self) -> Currency {
+        registered_currency("KES")
+    }
+
+    fn dialing_code(&self) -> &'static str {
+        "+254"
+    }
+
+    fn utc_offset_minutes(&self) -> i32 {
+        180
+    }
+
+    fn national_number_digits(&self) -> usize {
+        9
+    }
+
+    fn base_fares(&self) -> PricingConfig {
+        PricingConfig {
+            currency: self.currency(),
+            base_fare_standard: 150.0,
+            base_fare_express: 250.0,
+            base_fare_same_day: 400.0,
+            base_fare_emergency: 600.0,
+            per_km: 25.0,
+            per_minute: 2.0,
+            service_fee_rate: 0.1,
+            surcharges: Some(Surcharges {
+                small_package: 50.0,
+                medium_package: 100.0,
+                large_package: 200.0,
+                extra_large: 400.0,
+                food: 80.0,
+                grocery: 150.0,
+                pharmacy: 50.0,
+                electronics: 150.0,
+                fragile: 120.0,
+                express: 100.0,
+                same_day: 250.0,
+                emergency: 500.0,
+            }),
+        }
+    }
+
+    fn tax_schedule(&self) -> TaxSchedule {
+        TaxSchedule {
+            effective_from: DateTime::UNIX_EPOCH,
+            levies: vec![TaxLevy::new("VAT", "Value Added Tax", 0.16, false)],
+        }
+    }
+
+    fn public_holidays(&self) -> &'static [(u32, u32, &'static str)] {
+        &[
+            (1, 1, "New Year's Day"),
+            (5, 1, "Labour Day"),
+            (6, 1, "Madaraka Day"),
+            (10, 10, "Mazingira Day"),
+            (10, 20, "Mashujaa Day"),
+            (12, 12, "Jamhuri Day"),
+            (12, 25, "Christmas Day"),
+            (12, 26, "Boxing Day"),
+        ]
+    }
+}
@@ -0,0 +1,110 @@
+// src/services/country/mod.rs
+// Everything that changes from one country to the next: currency, time zone, phone
+// numbering, public holidays, the fares a tenant starts with and the taxes on them. Each country is a plugin implementing
+// `CountryRules`; a region is built from one, so opening a new market means adding a
+// file here and a line to `builtin`, not edits across the services.
+use chrono::{Datelike, NaiveDate};
+use std::sync::Arc;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{money::Currency, tax::TaxSchedule, tenant::PricingConfig},
+};
+
+pub mod cote_divoire;
+pub mod ghana;
+pub mod kenya;
+pub mod nigeria;
+
+pub trait CountryRules: Send + Sync {
+    /// Slug of the region the country is run as, e.g. "gh"
+    fn region_id(&self) -> &'static str;
+    fn name(&self) -> &'static str;
+    /// ISO 3166 codes and names a pickup's `country` is matched against
+    fn countries(&self) -> &'static [&'static str];
+    fn currency(&self) -> Currency;
+    /// e.g. "+233"
+    fn dialing_code(&self) -> &'static str;
+    /// Local time against UTC; none of the markets keeps daylight saving
+    fn utc_offset_minutes(&self) -> i32;
+    /// Length of a national number without the trunk 0
+    fn national_number_digits(&self) -> usize;
+    /// Rates and surcharges for tenants that haven't set their own in the country's currency
+    fn base_fares(&self) -> PricingConfig;
+    /// Levies in force until a tenant sets its own schedule
+    fn tax_schedule(&self) -> TaxSchedule;
+    /// Fixed-date public holidays as (month, day, name); moveable ones are added by operations
+    fn public_holidays(&self) -> &'static [(u32, u32, &'static str)];
+
+    /// The public holiday on the local `date`, if any. Countries with holidays that fall by
+    /// rule rather than date add them here.
+    fn public_holiday(&self, date: NaiveDate) -> Option<&'static str> {
+        fixed_holiday(self.public_holidays(), date)
+    }
+
+    /// The number in international form, e.g. "+233241234567". Takes it with or without
+    /// the trunk 0 or the dialing code.
+    fn normalize_phone(&self, phone: &str) -> Result<String, AppError> {
+        let digits: String = phone.chars().filter(|c| !c.is_whitespace() && *c != '-').collect();
+        let code = self.dialing_code().trim_start_matches('+');
+        let national = digits.strip_prefix('+').and_then(|rest| rest.strip_prefix(code))
+            .or_else(|| digits.strip_prefix('0'))
+            .unwrap_or(&digits);
+        if national.len() != self.national_number_digits() || !national.chars().all(|c| c.is_ascii_digit()) {
+            return Err(AppError::validation_error(
+                "phone_number",
+                format!("Expected a {}-digit {} number", self.national_number_digits(), self.name()),
+            ));
+        }
+        Ok(format!("{}{}", self.dialing_code(), national))
+    }
+}
+
+/// The rules for a region the service knows how to run in
+pub fn builtin(region_id: &str) -> Option<Arc<dyn CountryRules>> {
+    match region_id {
+        "gh" => Some(Arc::new(ghana::Ghana)),
+        "ng" => Some(Arc::new(nigeria::Nigeria)),
+        "ci" => Some(Arc::new(cote_divoire::CoteDivoire)),
+        "ke" => Some(Arc::new(kenya::Kenya)),
+        _ => None,
+    }
+}
+
+pub(crate) fn fixed_holiday(holidays: &'static [(u32, u32, &'static str)], date: NaiveDate) -> Option<&'static str> {
+    holidays.iter()
+        .find(|(month, day, _)| date.month() == *month && date.day() == *day)
+        .map(|(_, _, name)| *name)
+}
+
+pub(crate) fn registered_currency(code: &str) -> Currency {
+    Currency::parse(code).expect("built-in countries use registered currencies")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::money::Money;
+
+    #[test]
+    fn test_every_builtin_country_is_self_consistent() {
+        for region_id in ["gh", "ng", "ci", "ke"] {
+            let rules = builtin(region_id).unwrap();
+            assert_eq!(rules.region_id(), region_id);
+            assert_eq!(rules.base_fares().currency, rules.currency());
+            assert!(rules.base_fares().surcharges.is_some());
+            assert!(!rules.public_holidays().is_empty());
+            assert!(!rules.tax_schedule().levies.is_empty());
+
+            let national = "7".repeat(rules.national_number_digits());
+            let international = format!("{}{}", rules.dialing_code(), national);
+            assert_eq!(rules.normalize_phone(&format!("0{}", national)).unwrap(), international);
+            assert_eq!(rules.normalize_phone(&international).unwrap(), international);
+            assert!(rules.normalize_phone(&national[1..]).is_err());
+        }
+        assert!(builtin("zz").is_none());
+
+        let lines = builtin("ng").unwrap().tax_schedule().apply(Money::from_major(1000.0, registered_currency("NGN")));
+        assert_eq!(lines.iter().map(|line| (line.code.as_str(), line.amount.minor())).collect::<Vec<_>>(), vec![("VAT", 7500)]);
+    }
+}
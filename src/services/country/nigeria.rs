@@ -0,0 +1,85 @@
+// src/services/country/nigeria.rs
+// Nigeria charges a single VAT on the fare.
+use chrono::DateTime;
+
+use crate::{
+    models::{money::Currency, tax::{TaxLevy, TaxSchedule}, tenant::{PricingConfig, Surcharges}},
+    services::country::{registered_currency, CountryRules},
+};
+
+pub struct Nigeria;
+
+impl CountryRules for Nigeria {
+    fn region_id(&self) -> &'static str {
+        "ng"
+    }
+
+    fn name(&self) -> &'static str {
+        "Nigeria"
+    }
+
+    fn countries(&self) -> &'static [&'static str] {
+        &["NG", "Nigeria"]
+    }
+
+    fn currency(&self) -> Currency {
+        registered_currency("NGN")
+    }
+
+    fn dialing_code(&self) -> &'static str {
+        "+234"
+    }
+
+    fn utc_offset_minutes(&self) -> i32 {
+        60
+    }
+
+    fn national_number_digits(&self) -> usize {
+        10
+    }
+
+    fn base_fares(&self) -> PricingConfig {
+        PricingConfig {
+            currency: self.currency(),
+            base_fare_standard: 1500.0,
+            base_fare_express: 2500.0,
+            base_fare_same_day: 4000.0,
+            base_fare_emergency: 6000.0,
+            per_km: 250.0,
+            per_minute: 20.0,
+            service_fee_rate: 0.1,
+            surcharges: Some(Surcharges {
+                small_package: 500.0,
+                medium_package: 1000.0,
+                large_package: 2000.0,
+                extra_large: 4000.0,
+                food: 800.0,
+                grocery: 1500.0,
+                pharmacy: 500.0,
+                electronics: 1500.0,
+                fragile: 1200.0,
+                express: 1000.0,
+                same_day: 2500.0,
+                emergency: 5000.0,
+            }),
+        }
+    }
+
+    fn tax_schedule(&self) -> TaxSchedule {
+        TaxSchedule {
+            effective_from: DateTime::UNIX_EPOCH,
+            levies: vec![TaxLevy::new("VAT", "Value Added Tax", 0.075, false)],
+        }
+    }
+
+    fn public_holidays(&self) -> &'static [(u32, u32, &'static str)] {
+        &[
+            (1, 1, "New Year's Day"),
+            (5, 1, "Workers' Day"),
+            (6, 12, "Democracy Day"),
+            (10, 1, "Independence Day"),
+            (12, 25, "Christmas Day"),
+            (12, 26, "Boxing Day"),
+        ]
+    }
+}
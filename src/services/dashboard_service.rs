@@ -0,0 +1,175 @@
+// src/services/dashboard_service.rs
+// Aggregates live operational numbers for the admin dashboard
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{admin::{OperationsDashboard, StaleJob}, job::{Job, JobStatus}},
+    services::cache_service::CacheService,
+};
+
+#[derive(Debug, Clone)]
+pub struct DashboardConfig {
+    pub snapshot_ttl_seconds: u64,       // Dashboards poll; recompute at most this often
+    pub default_stale_after_minutes: i64, // Unassigned jobs older than this are flagged
+}
+
+impl Default for DashboardConfig {
+    fn default() -> Self {
+        Self {
+            snapshot_ttl_seconds: 10,
+            default_stale_after_minutes: 10,
+        }
+    }
+}
+
+pub struct DashboardService {
+    cache_service: Arc<CacheService>,
+    config: DashboardConfig,
+}
+
+impl DashboardService {
+    pub fn new(cache_service: Arc<CacheService>, config: DashboardConfig) -> Self {
+        Self {
+            cache_service,
+            config,
+        }
+    }
+
+    pub async fn get_dashboard(&self, stale_after_minutes: Option<i64>) -> Result<OperationsDashboard, AppError> {
+        let stale_after_minutes = stale_after_minutes.unwrap_or(self.config.default_stale_after_minutes);
+        if stale_after_minutes < 0 {
+            return Err(AppError::validation_error("stale_after_minutes", "Must not be negative"));
+        }
+
+        if let Some(snapshot) = self.cache_service.get_dashboard(stale_after_minutes).await? {
+            return Ok(snapshot);
+        }
+
+        let mut jobs = Vec::new();
+        for job_id in self.cache_service.get_active_jobs().await? {
+            if let Some(job) = self.cache_service.load_job(&job_id).await? {
+                jobs.push(job);
+            }
+        }
+
+        let online_driver_count = self.cache_service.count_online_drivers().await? as u32;
+        let surge_multipliers = self.cache_service.get_surge_multipliers().await?;
+
+        let dashboard = build_dashboard(jobs, online_driver_count, surge_multipliers, stale_after_minutes, Utc::now());
+
+        // A failed snapshot write only costs us a recompute on the next poll
+        if let Err(e) = self.cache_service.cache_dashboard(&dashboard, self.config.snapshot_ttl_seconds).await {
+            tracing::warn!("Failed to cache dashboard snapshot: {}", e);
+        }
+
+        Ok(dashboard)
+    }
+}
+
+fn is_unassigned(status: &JobStatus) -> bool {
+    matches!(status, JobStatus::Pending | JobStatus::Searching)
+}
+
+pub fn build_dashboard(
+    jobs: Vec<Job>,
+    online_driver_count: u32,
+    surge_multipliers: BTreeMap<String, f64>,
+    stale_after_minutes: i64,
+    now: DateTime<Utc>,
+) -> OperationsDashboard {
+    let mut active_jobs_by_status: BTreeMap<String, u32> = BTreeMap::new();
+    let mut latencies: Vec<i64> = Vec::new();
+    let mut stale_unassigned_jobs = Vec::new();
+    let mut active_job_count = 0;
+
+    for job in jobs.into_iter().filter(|job| !job.status.is_terminal()) {
+        active_job_count += 1;
+        *active_jobs_by_status.entry(format!("{:?}", job.status)).or_insert(0) += 1;
+
+        if let Some(accepted_at) = job.accepted_at {
+            latencies.push((accepted_at - job.created_at).num_seconds());
+        }
+
+        let waiting_minutes = (now - job.created_at).num_minutes();
+        if is_unassigned(&job.status) && waiting_minutes >= stale_after_minutes {
+            stale_unassigned_jobs.push(StaleJob {
+                job_id: job.id,
+                status: job.status,
+                priority: job.priority,
+                pickup_city: job.pickup_location.city,
+                created_at: job.created_at,
+                waiting_minutes,
+            });
+        }
+    }
+
+    // Longest waiting first - that's what dispatchers act on
+    stale_unassigned_jobs.sort_by_key(|job| std::cmp::Reverse(job.waiting_minutes));
+
+    let average_assignment_latency_seconds = if latencies.is_empty() {
+        None
+    } else {
+        Some(latencies.iter().sum::<i64>() as f64 / latencies.len() as f64)
+    };
+
+    OperationsDashboard {
+        generated_at: now,
+        active_job_count,
+        active_jobs_by_status,
+        online_driver_count,
+        average_assignment_latency_seconds,
+        stale_after_minutes,
+        stale_unassigned_jobs,
+        surge_multipliers,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use crate::{mocks::fixtures::Faker, models::user::UserType};
+
+    #[test]
+    fn test_build_dashboard_counts_active_jobs_and_flags_stale_ones() {
+        let now = Utc::now();
+        let mut faker = Faker::seeded(29);
+        let customer = faker.user(UserType::Customer);
+        let job = |faker: &mut Faker, status: JobStatus, age_minutes: i64, accepted_after_seconds: Option<i64>| {
+            let mut job = faker.job(&customer.id);
+            job.status = status;
+            job.created_at = now - Duration::minutes(age_minutes);
+            job.accepted_at = accepted_after_seconds.map(|seconds| job.created_at + Duration::seconds(seconds));
+            job
+        };
+        let waiting_longest = job(&mut faker, JobStatus::Searching, 45, None);
+        let waiting = job(&mut faker, JobStatus::Pending, 10, None);
+        let jobs = vec![
+            job(&mut faker, JobStatus::Pending, 9, None),
+            waiting.clone(),
+            waiting_longest.clone(),
+            job(&mut faker, JobStatus::DriverAssigned, 30, Some(60)),
+            job(&mut faker, JobStatus::InTransit, 60, Some(180)),
+            // Finished jobs count for nothing, not even latency or staleness
+            job(&mut faker, JobStatus::DeliveryCompleted, 90, Some(3600)),
+            job(&mut faker, JobStatus::Cancelled, 90, None),
+        ];
+
+        let dashboard = build_dashboard(jobs, 4, BTreeMap::new(), 10, now);
+        assert_eq!(dashboard.active_job_count, 5);
+        assert_eq!(dashboard.active_jobs_by_status.get("Pending"), Some(&2));
+        assert_eq!(dashboard.active_jobs_by_status.get("DeliveryCompleted"), None);
+        assert_eq!(dashboard.online_driver_count, 4);
+        assert_eq!(dashboard.average_assignment_latency_seconds, Some(120.0));
+        // Waiting exactly the threshold counts as stale; longest waiting first
+        let stale: Vec<_> = dashboard.stale_unassigned_jobs.iter().map(|job| job.job_id.clone()).collect();
+        assert_eq!(stale, vec![waiting_longest.id, waiting.id]);
+        assert_eq!(dashboard.stale_unassigned_jobs[0].waiting_minutes, 45);
+
+        assert_eq!(build_dashboard(Vec::new(), 0, BTreeMap::new(), 10, now).average_assignment_latency_seconds, None);
+    }
+}
@@ -0,0 +1,86 @@
+// src/services/database/mod.rs
+// Durable storage behind the cache. Redis only holds a TTL'd copy of users and jobs;
+// `CacheService` writes through to a `Repository` and reads back from it on a miss.
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{ids::{JobId, UserId}, job::Job, user::User},
+    services::{region::current_region_id, tenant_service::current_tenant_id},
+};
+
+/// Source of truth for users and jobs. Lookups are scoped to the current tenant and region,
+/// like the cache in front of them, so a miss never pulls in another market's records.
+#[async_trait]
+pub trait Repository: Send + Sync {
+    async fn find_user(&self, user_id: &UserId) -> Result<Option<User>, AppError>;
+    async fn save_user(&self, user: &User) -> Result<(), AppError>;
+    async fn find_job(&self, job_id: &JobId) -> Result<Option<Job>, AppError>;
+    async fn save_job(&self, job: &Job) -> Result<(), AppError>;
+}
+
+/// No database configured: writes are dropped and every cache miss is a real miss
+pub struct NoRepository;
+
+#[async_trait]
+impl Repository for NoRepository {
+    async fn find_user(&self, _user_id: &UserId) -> Result<Option<User>, AppError> {
+        Ok(None)
+    }
+
+    async fn save_user(&self, _user: &User) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    async fn find_job(&self, _job_id: &JobId) -> Result<Option<Job>, AppError> {
+        Ok(None)
+    }
+
+    async fn save_job(&self, _job: &Job) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+// (tenant, region, id)
+type Scoped<Id> = (String, String, Id);
+
+/// Process-local repository for tests and local runs; survives cache flushes, not restarts
+#[derive(Default)]
+pub struct MemoryRepository {
+    users: RwLock<HashMap<Scoped<UserId>, User>>,
+    jobs: RwLock<HashMap<Scoped<JobId>, Job>>,
+}
+
+impl MemoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Repository for MemoryRepository {
+    async fn find_user(&self, user_id: &UserId) -> Result<Option<User>, AppError> {
+        let users = self.users.read().await;
+        Ok(users.get(&(current_tenant_id(), current_region_id(), user_id.clone())).cloned())
+    }
+
+    async fn save_user(&self, user: &User) -> Result<(), AppError> {
+        let mut users = self.users.write().await;
+        // Users carry no region of their own; they live in the one they were saved in
+        users.insert((user.tenant_id.clone(), current_region_id(), user.id.clone()), user.clone());
+        Ok(())
+    }
+
+    async fn find_job(&self, job_id: &JobId) -> Result<Option<Job>, AppError> {
+        let jobs = self.jobs.read().await;
+        Ok(jobs.get(&(current_tenant_id(), current_region_id(), job_id.clone())).cloned())
+    }
+
+    async fn save_job(&self, job: &Job) -> Result<(), AppError> {
+        let mut jobs = self.jobs.write().await;
+        jobs.insert((job.tenant_id.clone(), job.region.clone(), job.id.clone()), job.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+// src/services/delivery_code.rs
+// Proof that valuable or signed-for packages reached the right person. When the driver
+// arrives at the dropoff a short code is texted to the dropoff contact; the driver enters
+// what the recipient reads out, and the job can't complete until it matches. A few wrong
+// guesses throw the code away, after which only support can complete the delivery.
+use chrono::Utc;
+use std::{collections::BTreeMap, sync::Arc};
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{job::{DeliveryCode, Job}, money::{Currency, Money}},
+    services::{
+        cache_service::CacheService,
+        otp_service::{OtpConfig, OtpService},
+        sms::{mask_phone, SmsSender},
+    },
+};
+
+const OTP_PURPOSE: &str = "delivery";
+// Roughly the same worth in each currency jobs are priced in
+const DEFAULT_HIGH_VALUE: &str = "GHS:1000,NGN:100000,XOF:50000,KES:10000,USD:100";
+
+#[derive(Debug, Clone)]
+pub struct DeliveryCodeConfig {
+    pub high_value: BTreeMap<Currency, Money>, // Packages declared at this value or more, in the job's currency, need a code
+    pub code_length: usize,
+    pub code_ttl_seconds: u64, // Long enough for a delivery rescheduled to later in the day
+    pub max_attempts: i64,
+}
+
+impl Default for DeliveryCodeConfig {
+    fn default() -> Self {
+        Self {
+            high_value: parse_thresholds(DEFAULT_HIGH_VALUE).into_iter().collect(),
+            code_length: 4,
+            code_ttl_seconds: 24 * 60 * 60,
+            max_attempts: 3,
+        }
+    }
+}
+
+impl DeliveryCodeConfig {
+    /// `DELIVERY_CODE_HIGH_VALUE` (per currency, e.g. "GHS:1500,NGN:200000") and
+    /// `DELIVERY_CODE_MAX_ATTEMPTS` override the defaults
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let mut defaults = Self::default();
+        if let Some(spec) = var("DELIVERY_CODE_HIGH_VALUE") {
+            defaults.high_value.extend(parse_thresholds(&spec));
+        }
+        Self {
+            max_attempts: var("DELIVERY_CODE_MAX_ATTEMPTS").and_then(|v| v.parse().ok()).unwrap_or(defaults.max_attempts),
+            ..defaults
+        }
+    }
+}
+
+// "GHS:1500,NGN:200000"; entries that don't parse are skipped
+fn parse_thresholds(spec: &str) -> Vec<(Currency, Money)> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let parsed = entry.split_once(':').and_then(|(code, amount)| {
+                let currency = Currency::parse(code.trim()).ok()?;
+                let amount: f64 = amount.trim().parse().ok()?;
+                Some((currency, Money::from_major(amount, currency)))
+            });
+            if parsed.is_none() {
+                tracing::warn!("Ignoring delivery code threshold {:?}", entry);
+            }
+            parsed
+        })
+        .collect()
+}
+
+pub struct DeliveryCodeService {
+    otp: OtpService,
+    sms: Arc<dyn SmsSender>,
+    config: DeliveryCodeConfig,
+}
+
+impl DeliveryCodeService {
+    pub fn new(cache_service: Arc<CacheService>, sms: Arc<dyn SmsSender>, config: DeliveryCodeConfig) -> Self {
+        // Its own codes and attempt counting; running out of attempts throws the code away
+        // for good, as it outlives the lockout
+        let otp = OtpService::new(cache_service, OtpConfig {
+            code_length: config.code_length,
+            code_ttl_seconds: config.code_ttl_seconds,
+            max_failures: config.max_attempts,
+            failure_window_seconds: config.code_ttl_seconds,
+            lockout_seconds: config.code_ttl_seconds,
+        });
+        Self { otp, sms, config }
+    }
+
+    /// Declared values are in the job's currency; one with no threshold never needs a code
+    /// for its value alone
+    pub fn required(&self, job: &Job) -> bool {
+        let currency = job.pricing.currency;
+        job.package.requires_signature || job.package.estimated_value.is_some_and(|value| {
+            self.config.high_value.get(&currency)
+                .is_some_and(|threshold| Money::from_major(value, currency).minor() >= threshold.minor())
+        })
+    }
+
+    /// Text a fresh code to the job's dropoff contact
+    pub async fn send(&self, job: &Job) -> Result<DeliveryCode, AppError> {
+        let phone = job.dropoff_location.contact_phone.trim();
+        if phone.is_empty() {
+            return Err(AppError::validation_error("dropoff_location.contact_phone", "A delivery code needs the recipient's phone number"));
+        }
+        let code = self.otp.issue(OTP_PURPOSE, job.id.as_str()).await?;
+        let body = format!(
+            "Your Sparrow delivery {} has arrived. Give the driver this code to receive it: {}",
+            job.tracking_code, code,
+        );
+        self.sms.send(phone, &body).await?;
+        tracing::info!("Sent delivery code for job {} to {}", job.id, mask_phone(phone));
+        Ok(DeliveryCode {
+            sent_to: mask_phone(phone),
+            sent_at: Some(Utc::now()),
+            confirmed_at: None,
+            overridden_by: None,
+            override_reason: None,
+        })
+    }
+
+    /// Uses up the code if it matches. Once the attempts run out every try is refused and
+    /// the delivery needs a support override.
+    pub async fn verify(&self, job: &Job, code: &str) -> Result<(), AppError> {
+        self.otp.verify(OTP_PURPOSE, job.id.as_str(), code).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker, messaging::RecordingSmsSender},
+        models::{job::{ConfirmDeliveryRequest, JobStatus, JobStatusUpdate, OverrideDeliveryCodeRequest}, user::UserType},
+        services::{job_service::JobOperations, user_service::UserOperations},
+    };
+
+    #[tokio::test]
+    async fn test_signed_for_delivery_needs_the_texted_code_or_an_override() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(103);
+        let sms = Arc::new(RecordingSmsSender::new());
+        let codes = DeliveryCodeService::new(state.cache_service.clone(), sms.clone(), DeliveryCodeConfig::default());
+
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let mut request = faker.job_request(&customer.id);
+        request.package.requires_signature = true;
+        let created = state.job_service.create_job(request).await.unwrap();
+        let mut job = state.cache_service.load_job(&created.id).await.unwrap().unwrap();
+        assert!(codes.required(&job));
+        job.package.requires_signature = false;
+        job.package.estimated_value = Some(10.0);
+        assert!(!codes.required(&job));
+        // The same declared value is valuable in cedis but not in naira
+        job.package.estimated_value = Some(5_000.0);
+        assert!(codes.required(&job));
+        let naira = Currency::parse("NGN").unwrap();
+        job.pricing.currency = naira;
+        assert!(!codes.required(&job));
+        job.pricing.currency = Currency::GHS;
+        assert_eq!(parse_thresholds("ngn:200000, bogus, USD:x"), vec![(naira, Money::from_major(200_000.0, naira))]);
+
+        // Through the job service: arriving texts the recipient, and completing waits for the code
+        let driver = faker.driver();
+        state.cache_service.cache_driver(&driver).await.unwrap();
+        state.job_service.assign_driver_to_job(&created.id, &driver.id).await.unwrap();
+        let arrived = state.job_service.update_job_status(JobStatusUpdate {
+            job_id: created.id.clone(),
+            status: JobStatus::ArrivedAtDropoff,
+            driver_id: Some(driver.id.clone()),
+            notes: None,
+        }).await.unwrap();
+        let sent = arrived.delivery_code.unwrap();
+        assert!(sent.sent_to.ends_with(&created.dropoff_location.contact_phone[created.dropoff_location.contact_phone.len() - 3..]));
+        assert!(matches!(state.job_service.complete_job(&created.id).await, Err(AppError::Conflict(_))));
+
+        let wrong = ConfirmDeliveryRequest { code: "not-it".to_string() };
+        assert!(state.job_service.confirm_delivery(&created.id, &driver.id, wrong).await.is_err());
+        // Running out of tries throws the code away; only support can finish the job now
+        for _ in 0..3 {
+            let wrong = ConfirmDeliveryRequest { code: "0000x".to_string() };
+            assert!(state.job_service.confirm_delivery(&created.id, &driver.id, wrong).await.is_err());
+        }
+
+        let overridden = state.job_service.override_delivery_code(&created.id, OverrideDeliveryCodeRequest {
+            agent: "support@sparrow".to_string(),
+            reason: "Recipient showed ID on a video call".to_string(),
+        }).await.unwrap();
+        assert_eq!(overridden.status, JobStatus::DeliveryCompleted);
+        assert_eq!(overridden.delivery_code.unwrap().overridden_by.as_deref(), Some("support@sparrow"));
+
+        // No new code for it either, but a code for another job that matches works once
+        let job = state.cache_service.load_job(&created.id).await.unwrap().unwrap();
+        assert!(codes.send(&job).await.is_err());
+        let job = faker.job(&customer.id);
+        codes.send(&job).await.unwrap();
+        let texted = sms.sent().pop().unwrap();
+        assert_eq!(texted.to, job.dropoff_location.contact_phone);
+        let code = texted.body.rsplit(' ').next().unwrap().to_string();
+        assert_eq!(code.len(), 4);
+        codes.verify(&job, &code).await.unwrap();
+        assert!(codes.verify(&job, &code).await.is_err());
+    }
+}
@@ -0,0 +1,287 @@
+// src/services/demand_service.rs
+// Pickup heatmap for driver apps, the weekly demand forecast behind it, and the surge
+// multipliers set where pickups are running ahead of that forecast
+use chrono::{DateTime, Datelike, Duration, DurationRound, Timelike, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::demand::{DemandForecast, DemandPoint, DriverHeatmap, HeatmapCell, ZoneForecast},
+    services::cache_service::CacheService,
+    utils::geohash,
+};
+
+#[derive(Debug, Clone)]
+pub struct DemandConfig {
+    pub default_window_minutes: i64,
+    pub max_window_minutes: i64,
+    pub default_precision: usize, // 6 chars ~ 1.2km x 0.6km cells
+    pub zone_precision: usize,    // 5 chars ~ 5km x 5km forecast zones
+    pub history_weeks: u32,
+    pub surge_window_minutes: i64, // Recent pickups compared against the forecast
+    pub surge_min_pickups: u32,    // Fewer than this in the window is noise, not a surge
+    pub max_surge_multiplier: f64,
+    pub surge_ttl_seconds: u64,    // Surge lapses if it stops being refreshed
+}
+
+impl Default for DemandConfig {
+    fn default() -> Self {
+        Self {
+            default_window_minutes: 60,
+            max_window_minutes: 24 * 60,
+            default_precision: 6,
+            zone_precision: 5,
+            history_weeks: 4,
+            surge_window_minutes: 30,
+            surge_min_pickups: 3,
+            max_surge_multiplier: 2.0,
+            surge_ttl_seconds: 15 * 60,
+        }
+    }
+}
+
+pub struct DemandService {
+    cache_service: Arc<CacheService>,
+    config: DemandConfig,
+}
+
+impl DemandService {
+    pub fn new(cache_service: Arc<CacheService>, config: DemandConfig) -> Self {
+        Self {
+            cache_service,
+            config,
+        }
+    }
+
+    pub async fn get_heatmap(&self, window_minutes: Option<i64>, precision: Option<usize>) -> Result<DriverHeatmap, AppError> {
+        let window_minutes = window_minutes.unwrap_or(self.config.default_window_minutes);
+        if !(1..=self.config.max_window_minutes).contains(&window_minutes) {
+            return Err(AppError::validation_error(
+                "window_minutes",
+                format!("Must be between 1 and {}", self.config.max_window_minutes),
+            ));
+        }
+        let precision = precision.unwrap_or(self.config.default_precision);
+        if !(1..=8).contains(&precision) {
+            return Err(AppError::validation_error("precision", "Must be between 1 and 8"));
+        }
+
+        let now = Utc::now();
+        let since = now - Duration::minutes(window_minutes);
+        let points: Vec<DemandPoint> = self.load_pickups(since, now).await?
+            .into_iter()
+            .filter(|point| point.created_at >= since)
+            .collect();
+
+        let next_hour_forecast = match self.cache_service.get_demand_forecast().await? {
+            Some(forecast) => forecast_for_slot(&forecast, week_slot(&(now + Duration::hours(1)))),
+            None => Vec::new(),
+        };
+
+        Ok(DriverHeatmap {
+            generated_at: now,
+            window_minutes,
+            precision,
+            cells: bucket_points(&points, precision),
+            next_hour_forecast,
+        })
+    }
+
+    // Rebuild the forecast from the last few weeks of pickups
+    pub async fn refresh_forecast(&self) -> Result<DemandForecast, AppError> {
+        let now = Utc::now();
+        let since = now - Duration::weeks(self.config.history_weeks as i64);
+        let points = self.load_pickups(since, now).await?;
+
+        let forecast = build_forecast(&points, self.config.zone_precision, self.config.history_weeks, now);
+        self.cache_service.cache_demand_forecast(&forecast).await?;
+
+        tracing::info!(
+            "Demand forecast rebuilt from {} pickups across {} zones",
+            points.len(),
+            forecast.zones.len()
+        );
+        Ok(forecast)
+    }
+
+    /// Set a multiplier on each zone where pickups in the last window are running ahead of
+    /// the forecast for this hour. Nothing surges until there is a forecast to compare with.
+    pub async fn refresh_surge(&self, now: DateTime<Utc>) -> Result<BTreeMap<String, f64>, AppError> {
+        let multipliers = match self.cache_service.get_demand_forecast().await? {
+            Some(forecast) => {
+                let since = now - Duration::minutes(self.config.surge_window_minutes);
+                let points: Vec<DemandPoint> = self.load_pickups(since, now).await?
+                    .into_iter()
+                    .filter(|point| point.created_at >= since && point.created_at <= now)
+                    .collect();
+                build_surge(&points, &forecast, week_slot(&now), &self.config)
+            }
+            None => BTreeMap::new(),
+        };
+        self.cache_service.cache_surge_multipliers(&multipliers, self.config.surge_ttl_seconds).await?;
+
+        if !multipliers.is_empty() {
+            tracing::info!("Surge set on {} zones", multipliers.len());
+        }
+        Ok(multipliers)
+    }
+
+    async fn load_pickups(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<DemandPoint>, AppError> {
+        let mut hour = since.duration_trunc(Duration::hours(1)).unwrap_or(since);
+        let mut points = Vec::new();
+        while hour <= until {
+            points.extend(self.cache_service.get_pickups_for_hour(&hour).await?);
+            hour += Duration::hours(1);
+        }
+        Ok(points)
+    }
+}
+
+// Hour of the week, Monday 00:00 UTC = 0
+pub fn week_slot(time: &DateTime<Utc>) -> usize {
+    time.weekday().num_days_from_monday() as usize * 24 + time.hour() as usize
+}
+
+pub fn bucket_points(points: &[DemandPoint], precision: usize) -> Vec<HeatmapCell> {
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for point in points {
+        *counts.entry(geohash::encode(point.latitude, point.longitude, precision)).or_insert(0) += 1;
+    }
+
+    let mut cells: Vec<HeatmapCell> = counts
+        .into_iter()
+        .filter_map(|(hash, pickups)| {
+            let (latitude, longitude) = geohash::decode_center(&hash)?;
+            Some(HeatmapCell { geohash: hash, latitude, longitude, pickups })
+        })
+        .collect();
+    cells.sort_by(|a, b| b.pickups.cmp(&a.pickups).then_with(|| a.geohash.cmp(&b.geohash)));
+    cells
+}
+
+pub fn build_forecast(points: &[DemandPoint], zone_precision: usize, weeks: u32, now: DateTime<Utc>) -> DemandForecast {
+    let mut zones: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    for point in points {
+        let zone = geohash::encode(point.latitude, point.longitude, zone_precision);
+        let slots = zones.entry(zone).or_insert_with(|| vec![0.0; DemandForecast::SLOTS_PER_WEEK]);
+        slots[week_slot(&point.created_at)] += 1.0;
+    }
+
+    // Each slot has been observed once per sampled week
+    let weeks = weeks.max(1);
+    for slots in zones.values_mut() {
+        slots.iter_mut().for_each(|count| *count /= weeks as f64);
+    }
+
+    DemandForecast {
+        generated_at: now,
+        weeks_sampled: weeks,
+        zones,
+    }
+}
+
+// Pickups in the window over what the forecast expects for it, in tenths, capped
+pub fn build_surge(points: &[DemandPoint], forecast: &DemandForecast, slot: usize, config: &DemandConfig) -> BTreeMap<String, f64> {
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for point in points {
+        *counts.entry(geohash::encode(point.latitude, point.longitude, config.zone_precision)).or_default() += 1;
+    }
+
+    let window_hours = config.surge_window_minutes as f64 / 60.0;
+    counts.into_iter()
+        .filter(|(_, pickups)| *pickups >= config.surge_min_pickups)
+        .filter_map(|(zone, pickups)| {
+            let expected = forecast.zones.get(&zone)?.get(slot)? * window_hours;
+            // A zone with no history for this hour has nothing to run ahead of
+            if expected <= 0.0 {
+                return None;
+            }
+            let multiplier = ((pickups as f64 / expected * 10.0).floor() / 10.0).min(config.max_surge_multiplier);
+            (multiplier > 1.0).then_some((zone, multiplier))
+        })
+        .collect()
+}
+
+fn forecast_for_slot(forecast: &DemandForecast, slot: usize) -> Vec<ZoneForecast> {
+    let mut zones: Vec<ZoneForecast> = forecast.zones
+        .iter()
+        .filter_map(|(zone, slots)| {
+            let expected_pickups = *slots.get(slot)?;
+            (expected_pickups > 0.0).then(|| ZoneForecast { zone: zone.clone(), expected_pickups })
+        })
+        .collect();
+    zones.sort_by(|a, b| b.expected_pickups.total_cmp(&a.expected_pickups));
+    zones
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::models::ids::JobId;
+
+    fn pickup(latitude: f64, longitude: f64, created_at: DateTime<Utc>) -> DemandPoint {
+        DemandPoint {
+            job_id: JobId::generate(),
+            latitude,
+            longitude,
+            created_at,
+        }
+    }
+
+    #[test]
+    fn test_bucket_points_busiest_first() {
+        let at = Utc.with_ymd_and_hms(2025, 9, 1, 8, 0, 0).unwrap();
+        let points = vec![
+            pickup(5.5560, -0.1820, at), // Osu x2
+            pickup(5.5561, -0.1821, at),
+            pickup(6.6885, -1.6244, at), // Kumasi
+        ];
+        let cells = bucket_points(&points, 6);
+        assert_eq!(cells.len(), 2);
+        assert_eq!(cells[0].pickups, 2);
+    }
+
+    #[test]
+    fn test_build_forecast_averages_per_week() {
+        // Monday 2025-09-01 08:xx UTC, same slot four weeks running
+        let points: Vec<DemandPoint> = (0..4)
+            .map(|week| pickup(5.5560, -0.1820, Utc.with_ymd_and_hms(2025, 9, 1, 8, 15, 0).unwrap() + Duration::weeks(week)))
+            .chain(std::iter::once(pickup(5.5560, -0.1820, Utc.with_ymd_and_hms(2025, 9, 1, 8, 45, 0).unwrap())))
+            .collect();
+        let forecast = build_forecast(&points, 5, 4, Utc::now());
+
+        let slots = forecast.zones.values().next().unwrap();
+        assert_eq!(slots[8], 1.25);
+        assert_eq!(slots.iter().filter(|count| **count > 0.0).count(), 1);
+    }
+
+    #[test]
+    fn test_build_surge_where_pickups_outrun_the_forecast() {
+        let config = DemandConfig::default();
+        let at = Utc.with_ymd_and_hms(2025, 9, 1, 8, 10, 0).unwrap();
+        let slot = week_slot(&at);
+        let osu = geohash::encode(5.5560, -0.1820, config.zone_precision);
+        let kumasi = geohash::encode(6.6885, -1.6244, config.zone_precision);
+        let mut forecast = DemandForecast { generated_at: at, weeks_sampled: 4, zones: BTreeMap::new() };
+        for zone in [&osu, &kumasi] {
+            let mut slots = vec![0.0; DemandForecast::SLOTS_PER_WEEK];
+            slots[slot] = 8.0; // 4 expected in a half-hour window
+            forecast.zones.insert(zone.clone(), slots);
+        }
+
+        // Osu at 1.5x what's expected, Kumasi on forecast, Tema with no history
+        let points: Vec<DemandPoint> = std::iter::repeat_with(|| pickup(5.5560, -0.1820, at)).take(6)
+            .chain(std::iter::repeat_with(|| pickup(6.6885, -1.6244, at)).take(4))
+            .chain(std::iter::repeat_with(|| pickup(5.6698, -0.0166, at)).take(5))
+            .collect();
+        let surge = build_surge(&points, &forecast, slot, &config);
+        assert_eq!(surge, BTreeMap::from([(osu.clone(), 1.5)]));
+
+        // Capped, however far ahead
+        let rush: Vec<DemandPoint> = std::iter::repeat_with(|| pickup(5.5560, -0.1820, at)).take(40).collect();
+        assert_eq!(build_surge(&rush, &forecast, slot, &config)[&osu], config.max_surge_multiplier);
+    }
+}
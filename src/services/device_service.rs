@@ -0,0 +1,253 @@
+// src/services/device_service.rs
+// Devices users log in from. A login from a device the user has not used before, in a
+// city none of their devices has logged in from, has to be confirmed with a one-time code
+// pushed to the devices they already have. A login that doesn't say which device it is
+// comes from an unseen one, and one whose city isn't known from a new city.
+use chrono::Utc;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{device::{DeviceInfo, DevicePlatform, DeviceToken, UserDevice}, ids::UserId},
+    services::{cache_service::CacheService, messaging_service::{NotificationMessage, NotificationPriority, NotificationService}, otp_service::OtpService},
+};
+
+const OTP_PURPOSE: &str = "device";
+
+// Stands in for the device id of logins that don't name their device
+const UNIDENTIFIED_DEVICE: &str = "unidentified";
+
+// Where a login comes from, as far as device checks are concerned
+pub struct LoginDevice<'a> {
+    pub info: Option<&'a DeviceInfo>,
+    pub platform: DevicePlatform,
+    pub push_token: Option<&'a str>,
+    pub city: Option<&'a str>,
+    pub verification_code: Option<&'a str>,
+}
+
+pub struct DeviceService {
+    cache: Arc<CacheService>,
+    notifications: Arc<dyn NotificationService>,
+    otp: Arc<OtpService>,
+}
+
+impl DeviceService {
+    pub fn new(cache: Arc<CacheService>, notifications: Arc<dyn NotificationService>, otp: Arc<OtpService>) -> Self {
+        Self { cache, notifications, otp }
+    }
+
+    pub async fn devices(&self, user_id: &UserId) -> Result<Vec<UserDevice>, AppError> {
+        self.cache.get_user_devices(user_id).await
+    }
+
+    /// Records the device a login comes from and returns its id, or refuses the login
+    /// until it is confirmed with the code sent to the user's other devices. A login that
+    /// doesn't name its device leaves nothing to record.
+    pub async fn check_login(&self, user_id: &UserId, login: LoginDevice<'_>) -> Result<Option<String>, AppError> {
+        if login.info.is_some_and(|info| info.device_id.trim().is_empty()) {
+            return Err(AppError::validation_error("device.device_id", "Device id is required"));
+        }
+        let device_id = login.info.map(|info| fingerprint(login.platform, &info.device_id));
+        let mut devices = self.cache.get_user_devices(user_id).await?;
+        let active: Vec<&UserDevice> = devices.iter().filter(|device| device.is_active()).collect();
+
+        let seen = device_id.as_ref().is_some_and(|device_id| active.iter().any(|device| &device.id == device_id));
+        let new_city = login.city.is_none_or(|city| {
+            !active.iter().any(|device| device.last_city.as_deref().is_some_and(|last| last.eq_ignore_ascii_case(city.trim())))
+        });
+        // With nothing to confirm from, the first device is trusted as it is
+        if !seen && new_city && !active.is_empty() {
+            let device_id = device_id.as_deref().unwrap_or(UNIDENTIFIED_DEVICE);
+            let identifier = otp_identifier(user_id, device_id);
+            match login.verification_code {
+                Some(code) => self.otp.verify(OTP_PURPOSE, &identifier, code).await?,
+                None => {
+                    let push_tokens: Vec<DeviceToken> = active.iter()
+                        .filter_map(|device| device.push_token.as_ref().map(|token| DeviceToken::new(token.clone(), device.platform)))
+                        .collect();
+                    self.send_code(user_id, device_id, &login, &push_tokens).await?;
+                    return Err(AppError::DeviceVerificationRequired(
+                        "Enter the code sent to your other devices to log in from this one".to_string(),
+                    ));
+                }
+            }
+        }
+
+        let (Some(info), Some(device_id)) = (login.info, device_id) else {
+            return Ok(None);
+        };
+        let now = Utc::now();
+        let index = match devices.iter().position(|device| device.id == device_id) {
+            Some(index) => index,
+            None => {
+                devices.push(UserDevice {
+                    id: device_id.clone(),
+                    platform: login.platform,
+                    model: None,
+                    os_version: None,
+                    app_version: None,
+                    push_token: None,
+                    last_city: None,
+                    first_seen_at: now,
+                    last_seen_at: now,
+                    revoked_at: None,
+                });
+                devices.len() - 1
+            }
+        };
+        let device = &mut devices[index];
+        device.model = info.model.clone().or(device.model.take());
+        device.os_version = info.os_version.clone().or(device.os_version.take());
+        device.app_version = info.app_version.clone().or(device.app_version.take());
+        if let Some(token) = login.push_token {
+            device.push_token = Some(token.to_string());
+        }
+        if let Some(city) = login.city {
+            device.last_city = Some(city.trim().to_string());
+        }
+        device.last_seen_at = now;
+        device.revoked_at = None;
+        self.cache.cache_user_devices(user_id, &devices).await?;
+        Ok(Some(device_id))
+    }
+
+    /// Stops pushes to the device and ends its session if it is the current one
+    pub async fn revoke(&self, user_id: &UserId, device_id: &str) -> Result<UserDevice, AppError> {
+        let mut devices = self.cache.get_user_devices(user_id).await?;
+        let device = devices.iter_mut()
+            .find(|device| device.id == device_id)
+            .ok_or_else(|| AppError::NotFound(format!("Device {} not found", device_id)))?;
+        if device.is_active() {
+            device.revoked_at = Some(Utc::now());
+        }
+        let revoked = device.clone();
+        self.cache.cache_user_devices(user_id, &devices).await?;
+
+        if let Some(mut user) = self.cache.load_user(user_id).await? {
+            if let Some(token) = &revoked.push_token {
+                user.device_tokens.retain(|device| &device.token != token);
+            }
+            if user.session_device.as_deref() == Some(device_id) {
+                user.current_session = None;
+                user.session_scopes = None;
+                user.session_device = None;
+            }
+            user.updated_at = Utc::now();
+            self.cache.cache_user(&user).await?;
+        }
+        tracing::info!("Revoked device {} for user {}", device_id, user_id);
+        Ok(revoked)
+    }
+
+    async fn send_code(&self, user_id: &UserId, device_id: &str, login: &LoginDevice<'_>, push_tokens: &[DeviceToken]) -> Result<(), AppError> {
+        let code = self.otp.issue(OTP_PURPOSE, &otp_identifier(user_id, device_id)).await?;
+
+        let model = login.info.and_then(|info| info.model.as_deref()).unwrap_or("a new device");
+        let city = login.city.unwrap_or("an unfamiliar location");
+        let message = NotificationMessage::new(
+            "New login attempt",
+            &format!("Someone is logging in on {} in {}. If it's you, enter {} to continue.", model, city, code),
+        )
+        .with_data(json!({
+            "type": "device_verification",
+            "device_id": device_id,
+            "city": login.city,
+        }))
+        .with_priority(NotificationPriority::High);
+        // The login still has to fail either way, so a push that can't go out is only logged
+        if let Err(e) = self.notifications.send_to_devices(push_tokens, message).await {
+            tracing::warn!("Failed to send device verification code to user {}: {}", user_id, e);
+        }
+        Ok(())
+    }
+}
+
+// Same device, same id, however many times the app is reinstalled with the same vendor id
+fn fingerprint(platform: DevicePlatform, device_id: &str) -> String {
+    let digest = Sha256::digest(format!("{}:{}", platform, device_id.trim()).as_bytes());
+    digest.iter().take(12).map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn otp_identifier(user_id: &UserId, device_id: &str) -> String {
+    format!("{}:{}", user_id, device_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::user::UserType,
+        services::user_service::UserOperations,
+    };
+
+    fn device(device_id: &str) -> DeviceInfo {
+        DeviceInfo { device_id: device_id.to_string(), model: None, os_version: None, app_version: None }
+    }
+
+    fn login<'a>(info: &'a DeviceInfo, city: &'a str) -> LoginDevice<'a> {
+        LoginDevice {
+            info: Some(info),
+            platform: DevicePlatform::Android,
+            push_token: Some(&info.device_id),
+            city: Some(city),
+            verification_code: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unseen_device_in_a_new_city_needs_the_code() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let devices = DeviceService::new(state.cache_service.clone(), state.notification_service.clone(), state.otp_service.clone());
+        let user = state.user_service
+            .register_user(Faker::seeded(68).user_registration(UserType::Customer))
+            .await
+            .unwrap();
+        let phone = DeviceInfo { model: Some("Pixel 8".to_string()), ..device("phone") };
+        let (tablet, stranger) = (device("tablet"), device("stranger"));
+
+        // The first device is trusted, and a second one in a city already seen goes through
+        let phone_id = devices.check_login(&user.id, login(&phone, "Accra")).await.unwrap().unwrap();
+        assert!(devices.check_login(&user.id, login(&tablet, "accra")).await.is_ok());
+
+        let error = devices.check_login(&user.id, login(&stranger, "Kumasi")).await.unwrap_err();
+        assert!(matches!(error, AppError::DeviceVerificationRequired(_)));
+        let wrong = LoginDevice { verification_code: Some("not-a-code"), ..login(&stranger, "Kumasi") };
+        assert!(devices.check_login(&user.id, wrong).await.is_err());
+        // Leaving out the device or the city doesn't get round it
+        let unnamed = LoginDevice { info: None, ..login(&phone, "Kumasi") };
+        assert!(matches!(devices.check_login(&user.id, unnamed).await, Err(AppError::DeviceVerificationRequired(_))));
+        let unnamed_at_home = LoginDevice { info: None, ..login(&phone, "Accra") };
+        assert_eq!(devices.check_login(&user.id, unnamed_at_home).await.unwrap(), None);
+        let nowhere = LoginDevice { city: None, ..login(&stranger, "Kumasi") };
+        assert!(matches!(devices.check_login(&user.id, nowhere).await, Err(AppError::DeviceVerificationRequired(_))));
+        let seen_anywhere = LoginDevice { city: None, ..login(&tablet, "Accra") };
+        assert!(devices.check_login(&user.id, seen_anywhere).await.is_ok());
+
+        // The code itself only reaches the other devices, so issue a known one in its place
+        let stranger_id = fingerprint(DevicePlatform::Android, "stranger");
+        let code = state.otp_service.issue(OTP_PURPOSE, &otp_identifier(&user.id, &stranger_id)).await.unwrap();
+        let confirmed = LoginDevice { verification_code: Some(&code), ..login(&stranger, "Kumasi") };
+        assert_eq!(devices.check_login(&user.id, confirmed).await.unwrap(), Some(stranger_id));
+        assert_eq!(devices.devices(&user.id).await.unwrap().len(), 3);
+
+        // Revoking the phone ends the session it logged in with and stops its pushes
+        let mut stored = state.cache_service.load_user(&user.id).await.unwrap().unwrap();
+        stored.current_session = Some("token".to_string());
+        stored.session_device = Some(phone_id.clone());
+        stored.device_tokens = vec![DeviceToken::new("phone", DevicePlatform::Android)];
+        state.cache_service.cache_user(&stored).await.unwrap();
+        let revoked = devices.revoke(&user.id, &phone_id).await.unwrap();
+        assert!(!revoked.is_active());
+        assert_eq!(revoked.model.as_deref(), Some("Pixel 8"));
+        let stored = state.cache_service.load_user(&user.id).await.unwrap().unwrap();
+        assert!(stored.current_session.is_none() && stored.device_tokens.is_empty());
+        assert!(devices.revoke(&user.id, "unknown").await.is_err());
+    }
+}
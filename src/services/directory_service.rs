@@ -0,0 +1,221 @@
+// src/services/directory_service.rs
+// Admin listing and search over users and drivers. Both live in Redis behind an ID set,
+// so a search walks the IDs in order from the page cursor, loading and filtering records
+// until a page is full. Cost grows with how far the matches are spread, which is fine
+// for back-office lookups but not for anything on a hot path.
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        admin::{DriverSearchQuery, SearchPage, UserSearchQuery},
+        driver::{Driver, DriverResponse},
+        user::{User, UserResponse},
+    },
+    services::cache_service::CacheService,
+};
+
+#[derive(Debug, Clone)]
+pub struct DirectoryConfig {
+    pub default_page_size: usize,
+    pub max_page_size: usize,
+}
+
+impl Default for DirectoryConfig {
+    fn default() -> Self {
+        Self {
+            default_page_size: 50,
+            max_page_size: 200,
+        }
+    }
+}
+
+pub struct DirectoryService {
+    cache_service: Arc<CacheService>,
+    config: DirectoryConfig,
+}
+
+impl DirectoryService {
+    pub fn new(cache_service: Arc<CacheService>, config: DirectoryConfig) -> Self {
+        Self {
+            cache_service,
+            config,
+        }
+    }
+
+    pub async fn search_users(&self, query: UserSearchQuery) -> Result<SearchPage<UserResponse>, AppError> {
+        let limit = self.page_size(query.limit)?;
+        let needle = needle(query.q.as_deref());
+        let (needle, query) = (needle.as_deref(), &query);
+        let ids = self.cache_service.get_all_user_ids().await?;
+
+        let page = paginate(ids, query.page.as_deref(), limit, |id| async move {
+            let Some(user) = self.cache_service.load_user(&id).await? else {
+                return Ok(None);
+            };
+            let matches = query.status.as_ref().is_none_or(|status| user.status == *status)
+                && query.user_type.as_ref().is_none_or(|user_type| user.user_type == *user_type)
+                && needle.is_none_or(|needle| user_matches(&user, needle));
+            Ok(matches.then_some(user))
+        }).await?;
+        Ok(SearchPage {
+            items: page.items.into_iter().map(UserResponse::from).collect(),
+            next_page: page.next_page,
+        })
+    }
+
+    pub async fn search_drivers(&self, query: DriverSearchQuery) -> Result<SearchPage<DriverResponse>, AppError> {
+        let limit = self.page_size(query.limit)?;
+        let needle = needle(query.q.as_deref());
+        let (needle, query) = (needle.as_deref(), &query);
+        let ids = self.cache_service.get_all_driver_ids().await?;
+
+        let page = paginate(ids, query.page.as_deref(), limit, |id| async move {
+            let Some(driver) = self.cache_service.get_driver(&id).await? else {
+                return Ok(None);
+            };
+            let matches = query.status.as_ref().is_none_or(|status| driver.status == *status)
+                && query.is_verified.is_none_or(|is_verified| driver.is_verified == is_verified)
+                && query.onboarding_state.is_none_or(|state| driver.onboarding_state == state)
+                && needle.is_none_or(|needle| driver_matches(&driver, needle));
+            Ok(matches.then_some(driver))
+        }).await?;
+        Ok(SearchPage {
+            items: page.items.into_iter().map(DriverResponse::from).collect(),
+            next_page: page.next_page,
+        })
+    }
+
+    fn page_size(&self, limit: Option<usize>) -> Result<usize, AppError> {
+        match limit {
+            None => Ok(self.config.default_page_size),
+            Some(limit) if (1..=self.config.max_page_size).contains(&limit) => Ok(limit),
+            Some(_) => Err(AppError::validation_error("limit", format!("Must be between 1 and {}", self.config.max_page_size))),
+        }
+    }
+}
+
+// Lowercased; blank searches match everything
+fn needle(q: Option<&str>) -> Option<String> {
+    q.map(str::trim).filter(|q| !q.is_empty()).map(str::to_lowercase)
+}
+
+fn contains(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(needle)
+}
+
+// Phone numbers match with or without the country code and spacing
+fn phone_matches(country_code: &str, phone_number: &str, needle: &str) -> bool {
+    let digits = |text: &str| text.chars().filter(char::is_ascii_digit).collect::<String>();
+    let wanted = digits(needle);
+    !wanted.is_empty() && format!("{}{}", digits(country_code), digits(phone_number)).contains(&wanted)
+}
+
+fn user_matches(user: &User, needle: &str) -> bool {
+    contains(&format!("{} {}", user.first_name, user.last_name), needle)
+        || user.display_name.as_deref().is_some_and(|name| contains(name, needle))
+        || contains(&user.email, needle)
+        || phone_matches(&user.country_code, &user.phone_number, needle)
+}
+
+fn driver_matches(driver: &Driver, needle: &str) -> bool {
+    contains(&format!("{} {}", driver.first_name, driver.last_name), needle)
+        || contains(&driver.email, needle)
+        || contains(&driver.vehicle.license_plate, needle)
+        || phone_matches("", &driver.phone_number, needle)
+}
+
+struct Page<T> {
+    items: Vec<T>,
+    next_page: Option<String>,
+}
+
+// Walk `ids` in order from just after `after`, keeping what `load` returns, until `limit`
+// are kept; the last one kept is the cursor for the next page
+async fn paginate<K, T, F, Fut>(mut ids: Vec<K>, after: Option<&str>, limit: usize, load: F) -> Result<Page<T>, AppError>
+where
+    K: Ord + ToString,
+    F: Fn(K) -> Fut,
+    Fut: Future<Output = Result<Option<T>, AppError>>,
+{
+    ids.sort();
+    let start = after.map_or(0, |after| ids.partition_point(|id| id.to_string().as_str() <= after));
+    let mut items = Vec::new();
+    let mut last_id = None;
+    let mut remaining = ids.into_iter().skip(start);
+    for id in remaining.by_ref() {
+        let cursor = id.to_string();
+        if let Some(item) = load(id).await? {
+            items.push(item);
+            last_id = Some(cursor);
+            if items.len() == limit {
+                break;
+            }
+        }
+    }
+    // A full page only has a next one if there are IDs left to look at
+    let next_page = if items.len() == limit && remaining.next().is_some() { last_id } else { None };
+    Ok(Page { items, next_page })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mocks::fixtures::Faker,
+        models::{driver::DriverStatus, user::{UserStatus, UserType}},
+        services::cache_service::CacheConfig,
+    };
+
+    #[tokio::test]
+    async fn test_search_filters_and_pages_in_id_order() {
+        let cache_service = Arc::new(CacheService::new_memory(CacheConfig::default()));
+        let directory = DirectoryService::new(cache_service.clone(), DirectoryConfig::default());
+        let mut faker = Faker::new();
+
+        let mut customers = Vec::new();
+        for _ in 0..5 {
+            let user = faker.user(UserType::Customer);
+            cache_service.cache_user(&user).await.unwrap();
+            cache_service.cache_user_index(&user).await.unwrap();
+            customers.push(user);
+        }
+        let mut suspended = faker.user(UserType::Business);
+        suspended.status = UserStatus::Suspended;
+        suspended.email = "Dispatch@Kumasi-Traders.example".to_string();
+        cache_service.cache_user(&suspended).await.unwrap();
+        cache_service.cache_user_index(&suspended).await.unwrap();
+
+        // Two pages of two and one of one, covering every customer once
+        let mut seen = Vec::new();
+        let mut page = None;
+        loop {
+            let query = UserSearchQuery { status: None, user_type: Some(UserType::Customer), q: None, page: page.clone(), limit: Some(2) };
+            let result = directory.search_users(query).await.unwrap();
+            seen.extend(result.items.into_iter().map(|user| user.id));
+            match result.next_page {
+                Some(next) => page = Some(next),
+                None => break,
+            }
+        }
+        let mut expected: Vec<_> = customers.iter().map(|user| user.id.clone()).collect();
+        expected.sort();
+        assert_eq!(seen, expected);
+
+        let query = UserSearchQuery { status: Some(UserStatus::Suspended), user_type: None, q: Some("kumasi-traders".to_string()), page: None, limit: None };
+        let found = directory.search_users(query).await.unwrap();
+        assert_eq!(found.items.iter().map(|user| &user.id).collect::<Vec<_>>(), vec![&suspended.id]);
+
+        let mut driver = faker.driver();
+        driver.status = DriverStatus::Online;
+        cache_service.cache_driver(&driver).await.unwrap();
+        cache_service.cache_driver(&faker.driver()).await.unwrap();
+        let plate = driver.vehicle.license_plate.to_lowercase();
+        let query = DriverSearchQuery { status: Some(DriverStatus::Online), is_verified: None, onboarding_state: None, q: Some(plate), page: None, limit: None };
+        let found = directory.search_drivers(query).await.unwrap();
+        assert_eq!(found.items.len(), 1);
+        assert_eq!(found.items[0].id, driver.id);
+        assert!(directory.search_drivers(DriverSearchQuery { status: None, is_verified: None, onboarding_state: None, q: None, page: None, limit: Some(0) }).await.is_err());
+    }
+}
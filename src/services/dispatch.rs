@@ -0,0 +1,98 @@
+// src/services/dispatch.rs
+// Orders the drivers a job is offered to. Distance alone favours whoever happens to be
+// closest; blending in the reliability score moves dependable drivers up the list.
+use std::cmp::Ordering;
+
+use crate::models::{dispatch::DispatchOverride, driver::MAX_RELIABILITY_SCORE, ids::DriverId, job::Job};
+
+#[derive(Debug, Clone)]
+pub struct DispatchConfig {
+    pub search_radius_km: f64,
+    pub max_candidates: usize,
+    pub reliability_weight: f64, // 0 ranks by distance only, 1 by reliability only
+    pub offer_timeout_seconds: i64, // How long a driver has to accept or reject
+}
+
+impl Default for DispatchConfig {
+    fn default() -> Self {
+        Self {
+            search_radius_km: 10.0,
+            max_candidates: 10,
+            reliability_weight: 0.3,
+            offer_timeout_seconds: 20,
+        }
+    }
+}
+
+impl DispatchConfig {
+    pub fn with_override(&self, settings: &DispatchOverride) -> Self {
+        Self {
+            search_radius_km: settings.search_radius_km.unwrap_or(self.search_radius_km),
+            max_candidates: settings.max_candidates.unwrap_or(self.max_candidates),
+            offer_timeout_seconds: settings.offer_timeout_seconds.unwrap_or(self.offer_timeout_seconds),
+            ..self.clone()
+        }
+    }
+
+    // Escalated jobs look further out for a driver
+    pub fn search_radius_for(&self, job: &Job) -> f64 {
+        let multiplier = job.escalation.as_ref().map_or(1.0, |escalation| escalation.radius_multiplier);
+        self.search_radius_km * multiplier
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DispatchCandidate {
+    pub driver_id: DriverId,
+    pub distance_km: f64,
+    pub reliability_score: f32,
+}
+
+impl DispatchCandidate {
+    // Lower is better: both terms are scaled to 0-1 before weighting
+    fn cost(&self, config: &DispatchConfig) -> f64 {
+        let distance = (self.distance_km / config.search_radius_km).clamp(0.0, 1.0);
+        let unreliability = 1.0 - (self.reliability_score / MAX_RELIABILITY_SCORE).clamp(0.0, 1.0) as f64;
+        (1.0 - config.reliability_weight) * distance + config.reliability_weight * unreliability
+    }
+}
+
+/// Best candidate first, at most `max_candidates` of them
+pub fn rank_candidates(mut candidates: Vec<DispatchCandidate>, config: &DispatchConfig) -> Vec<DispatchCandidate> {
+    candidates.sort_by(|a, b| {
+        a.cost(config)
+            .partial_cmp(&b.cost(config))
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.distance_km.partial_cmp(&b.distance_km).unwrap_or(Ordering::Equal))
+    });
+    candidates.truncate(config.max_candidates);
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(distance_km: f64, reliability_score: f32) -> DispatchCandidate {
+        DispatchCandidate {
+            driver_id: DriverId::generate(),
+            distance_km,
+            reliability_score,
+        }
+    }
+
+    #[test]
+    fn test_reliable_driver_beats_slightly_closer_one() {
+        let flaky = candidate(1.0, 40.0);
+        let steady = candidate(1.5, 95.0);
+        let config = DispatchConfig::default();
+
+        let ranked = rank_candidates(vec![flaky.clone(), steady.clone()], &config);
+        assert_eq!(ranked[0].driver_id, steady.driver_id);
+
+        // Distance only
+        let config = DispatchConfig { reliability_weight: 0.0, ..config };
+        let ranked = rank_candidates(vec![steady, flaky.clone()], &config);
+        assert_eq!(ranked[0].driver_id, flaky.driver_id);
+    }
+}
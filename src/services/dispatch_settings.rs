@@ -0,0 +1,183 @@
+// src/services/dispatch_settings.rs
+// How far dispatch looks for drivers, how many it considers and how long an offer stays open,
+// tuned per pickup region with time-of-day overrides: dense Accra at rush hour wants a tight
+// search, a rural region a wide one. Administered through `/admin/dispatch-settings`; the
+// platform defaults underneath can be tuned in the runtime config.
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        dispatch::{DispatchOverride, DispatchSettings, UpdateDispatchSettingsRequest},
+        job::Job,
+    },
+    services::{cache_service::CacheService, dispatch::DispatchConfig, region::RegionRegistry, runtime_config::RuntimeConfigService},
+};
+
+pub struct DispatchSettingsService {
+    cache_service: Arc<CacheService>,
+    runtime_config: Arc<RuntimeConfigService>,
+    regions: Arc<RegionRegistry>,
+    defaults: DispatchConfig,
+}
+
+impl DispatchSettingsService {
+    pub fn new(cache_service: Arc<CacheService>, runtime_config: Arc<RuntimeConfigService>, regions: Arc<RegionRegistry>, defaults: DispatchConfig) -> Self {
+        Self {
+            cache_service,
+            runtime_config,
+            regions,
+            defaults,
+        }
+    }
+
+    /// The built-in defaults with the runtime config's dispatch settings over them
+    pub fn defaults(&self) -> DispatchConfig {
+        self.defaults.with_override(&self.runtime_config.current().config.dispatch)
+    }
+
+    /// The tenant's zone settings; none until an admin sets them
+    pub async fn settings(&self) -> Result<DispatchSettings, AppError> {
+        Ok(self.cache_service.get_dispatch_settings().await?.unwrap_or_default())
+    }
+
+    pub async fn update_settings(&self, request: UpdateDispatchSettingsRequest) -> Result<DispatchSettings, AppError> {
+        let mut zones = request.zones;
+        for index in 0..zones.len() {
+            let field = format!("zones[{}]", index);
+            let zone = &mut zones[index];
+            zone.zone = zone.zone.trim().to_string();
+            if zone.zone.is_empty() {
+                return Err(AppError::validation_error(format!("{}.zone", field), "Must not be empty"));
+            }
+            // Time-of-day windows are in the region's local time unless the zone says otherwise
+            let utc_offset_minutes = *zone.utc_offset_minutes.get_or_insert(self.regions.current().utc_offset_minutes);
+            if utc_offset_minutes.abs() > 14 * 60 {
+                return Err(AppError::validation_error(format!("{}.utc_offset_minutes", field), "Must be within 14 hours of UTC"));
+            }
+            validate_override(&field, &zone.settings)?;
+            for (window_index, window) in zone.time_of_day.iter().enumerate() {
+                let field = format!("{}.time_of_day[{}]", field, window_index);
+                if window.start == window.end {
+                    return Err(AppError::validation_error(format!("{}.end", field), "Must differ from start"));
+                }
+                validate_override(&field, &window.settings)?;
+            }
+            let name = &zones[index].zone;
+            if zones[..index].iter().any(|earlier| earlier.zone.eq_ignore_ascii_case(name)) {
+                return Err(AppError::validation_error(format!("{}.zone", field), "Zone is listed twice"));
+            }
+        }
+
+        let settings = DispatchSettings {
+            zones,
+            updated_at: Some(Utc::now()),
+        };
+        self.cache_service.cache_dispatch_settings(&settings).await?;
+
+        tracing::info!("Updated dispatch settings ({} zones)", settings.zones.len());
+        Ok(settings)
+    }
+
+    /// Dispatch parameters for `job` at `at`, from its pickup region's settings over the defaults
+    pub async fn config_for(&self, job: &Job, at: DateTime<Utc>) -> DispatchConfig {
+        self.config_in(&job.pickup_location.region, at).await
+    }
+
+    /// Dispatch parameters for a pickup in `region`, before there is a job
+    pub async fn config_in(&self, region: &str, at: DateTime<Utc>) -> DispatchConfig {
+        let settings = match self.settings().await {
+            Ok(settings) => settings,
+            Err(e) => {
+                // Dispatch carries on with the defaults rather than stall
+                tracing::warn!("Failed to load dispatch settings for {}: {}", region, e);
+                return self.defaults();
+            }
+        };
+        match settings.zone(region) {
+            Some(zone) => zone.overrides_at(at)
+                .into_iter()
+                .fold(self.defaults(), |config, settings| config.with_override(settings)),
+            None => self.defaults(),
+        }
+    }
+}
+
+pub fn validate_override(field: &str, settings: &DispatchOverride) -> Result<(), AppError> {
+    if settings.search_radius_km.is_some_and(|radius| !radius.is_finite() || radius <= 0.0) {
+        return Err(AppError::validation_error(format!("{}.search_radius_km", field), "Must be positive"));
+    }
+    if settings.max_candidates == Some(0) {
+        return Err(AppError::validation_error(format!("{}.max_candidates", field), "Must be at least 1"));
+    }
+    if settings.offer_timeout_seconds.is_some_and(|seconds| seconds <= 0) {
+        return Err(AppError::validation_error(format!("{}.offer_timeout_seconds", field), "Must be positive"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveTime, TimeZone};
+    use serde_json::json;
+
+    use crate::{mocks::{app::TestApp, fixtures::Faker}, models::ids::UserId, services::region::with_region};
+
+    #[tokio::test]
+    async fn test_zone_and_rush_hour_settings_override_defaults() {
+        let app = TestApp::new();
+        let service = DispatchSettingsService::new(app.state.cache_service.clone(), app.state.runtime_config.clone(), app.state.regions.clone(), DispatchConfig::default());
+
+        let request: UpdateDispatchSettingsRequest = serde_json::from_value(json!({
+            "zones": [{
+                "zone": "Greater Accra",
+                "search_radius_km": 5.0,
+                "time_of_day": [{ "start": "17:00:00", "end": "20:00:00", "max_candidates": 20, "offer_timeout_seconds": 15 }]
+            }]
+        })).unwrap();
+        let settings = service.update_settings(request).await.unwrap();
+        assert_eq!(settings.zones[0].time_of_day[0].start, NaiveTime::from_hms_opt(17, 0, 0).unwrap());
+
+        let mut job = Faker::seeded(3).job(&UserId::generate());
+        job.pickup_location.region = "greater accra".to_string();
+        let morning = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let config = service.config_for(&job, morning).await;
+        assert_eq!((config.search_radius_km, config.max_candidates, config.offer_timeout_seconds), (5.0, 10, 20));
+        let rush_hour = Utc.with_ymd_and_hms(2026, 3, 2, 18, 30, 0).unwrap();
+        let config = service.config_for(&job, rush_hour).await;
+        assert_eq!((config.search_radius_km, config.max_candidates, config.offer_timeout_seconds), (5.0, 20, 15));
+
+        // Anywhere else gets the defaults
+        job.pickup_location.region = "Upper East".to_string();
+        assert_eq!(service.config_for(&job, rush_hour).await.search_radius_km, 10.0);
+
+        let invalid: UpdateDispatchSettingsRequest = serde_json::from_value(json!({
+            "zones": [{ "zone": "Ashanti", "max_candidates": 0 }]
+        })).unwrap();
+        assert!(service.update_settings(invalid).await.is_err());
+        let duplicate: UpdateDispatchSettingsRequest = serde_json::from_value(json!({
+            "zones": [{ "zone": "Ashanti" }, { "zone": "ashanti" }]
+        })).unwrap();
+        assert!(service.update_settings(duplicate).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rush_hour_windows_are_in_the_regions_local_time() {
+        let app = TestApp::builder().regions(&["ke"]).build();
+        let service = DispatchSettingsService::new(app.state.cache_service.clone(), app.state.runtime_config.clone(), app.state.regions.clone(), DispatchConfig::default());
+        let request: UpdateDispatchSettingsRequest = serde_json::from_value(json!({
+            "zones": [{ "zone": "Nairobi", "time_of_day": [{ "start": "17:00:00", "end": "20:00:00", "max_candidates": 20 }] }]
+        })).unwrap();
+        let settings = with_region("ke".to_string(), service.update_settings(request)).await.unwrap();
+        assert_eq!(settings.zones[0].utc_offset_minutes, Some(180));
+
+        // 15:30 UTC is 18:30 in Nairobi; 18:30 UTC is already past the window there
+        let rush_hour = Utc.with_ymd_and_hms(2026, 3, 2, 15, 30, 0).unwrap();
+        let evening = Utc.with_ymd_and_hms(2026, 3, 2, 18, 30, 0).unwrap();
+        assert_eq!(with_region("ke".to_string(), service.config_in("Nairobi", rush_hour)).await.max_candidates, 20);
+        assert_eq!(with_region("ke".to_string(), service.config_in("Nairobi", evening)).await.max_candidates, 10);
+    }
+}
@@ -0,0 +1,432 @@
+// src/services/dispatcher_service.rs
+// Dispatcher console: what automatic dispatch left behind, and manual overrides of it.
+// Every override is written to the tenant's dispatch audit log.
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        admin::StaleJob,
+        dispatch::{BroadcastResponse, CandidateDriver, DispatchAction, DispatchAuditEntry, DriverSocketEvent},
+        driver::{DispatchOutcomeKind, DriverStatus, VehicleType},
+        ids::{DriverId, JobId, UserId},
+        job::{Job, JobResponse, JobStatus},
+    },
+    services::{
+        cache_service::CacheService,
+        dispatch::rank_candidates,
+        dispatch_settings::DispatchSettingsService,
+        driver_channel::DriverChannel,
+        driver_service::{DriverOperations, DriverService},
+        earnings::EarningsCalculator,
+        job_service::{JobOperations, JobService},
+        messaging_service::{NotificationMessage, NotificationService},
+    },
+    utils::{geo::haversine_km, geohash},
+};
+
+#[derive(Debug, Clone)]
+pub struct DispatcherConfig {
+    pub default_older_than_minutes: i64,
+    pub zone_precision: usize,        // Default broadcast zone: the pickup's geohash cell
+    pub max_broadcast_drivers: usize,
+    pub chain_radius_km: f64,         // How near a dropoff the next job's pickup must be
+}
+
+impl Default for DispatcherConfig {
+    fn default() -> Self {
+        Self {
+            default_older_than_minutes: 5,
+            zone_precision: 5,
+            max_broadcast_drivers: 200,
+            chain_radius_km: 2.0,
+        }
+    }
+}
+
+// Who is acting, as recorded in the audit log
+pub struct Dispatcher<'a> {
+    pub user_id: &'a UserId,
+    pub api_key_id: &'a str,
+}
+
+pub struct DispatcherService {
+    cache_service: Arc<CacheService>,
+    driver_service: Arc<DriverService>,
+    job_service: Arc<JobService>,
+    notification_service: Arc<dyn NotificationService>,
+    driver_channel: Arc<DriverChannel>,
+    earnings: Arc<EarningsCalculator>,
+    dispatch_settings: Arc<DispatchSettingsService>,
+    config: DispatcherConfig,
+}
+
+impl DispatcherService {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        cache_service: Arc<CacheService>,
+        driver_service: Arc<DriverService>,
+        job_service: Arc<JobService>,
+        notification_service: Arc<dyn NotificationService>,
+        driver_channel: Arc<DriverChannel>,
+        earnings: Arc<EarningsCalculator>,
+        dispatch_settings: Arc<DispatchSettingsService>,
+        config: DispatcherConfig,
+    ) -> Self {
+        Self {
+            cache_service,
+            driver_service,
+            job_service,
+            notification_service,
+            driver_channel,
+            earnings,
+            dispatch_settings,
+            config,
+        }
+    }
+
+    async fn load_job(&self, job_id: &JobId) -> Result<Job, AppError> {
+        self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::job_not_found(job_id.as_str()))
+    }
+
+    /// Jobs still waiting for a driver after `older_than_minutes`, longest waiting first
+    pub async fn unassigned_jobs(&self, older_than_minutes: Option<i64>) -> Result<Vec<StaleJob>, AppError> {
+        let older_than_minutes = older_than_minutes.unwrap_or(self.config.default_older_than_minutes);
+        if older_than_minutes < 0 {
+            return Err(AppError::validation_error("older_than_minutes", "Must not be negative"));
+        }
+
+        let now = Utc::now();
+        let mut jobs = Vec::new();
+        for job_id in self.cache_service.get_active_jobs().await? {
+            let Some(job) = self.cache_service.load_job(&job_id).await? else {
+                continue;
+            };
+            let waiting_minutes = (now - job.created_at).num_minutes();
+            let unassigned = job.driver_id.is_none() && matches!(job.status, JobStatus::Pending | JobStatus::Searching);
+            if unassigned && waiting_minutes >= older_than_minutes {
+                jobs.push(StaleJob {
+                    job_id: job.id,
+                    status: job.status,
+                    priority: job.priority,
+                    pickup_city: job.pickup_location.city,
+                    created_at: job.created_at,
+                    waiting_minutes,
+                });
+            }
+        }
+        jobs.sort_by_key(|job| std::cmp::Reverse(job.waiting_minutes));
+        Ok(jobs)
+    }
+
+    /// Drivers automatic dispatch would offer the job to, best first
+    pub async fn candidate_drivers(&self, job_id: &JobId) -> Result<Vec<CandidateDriver>, AppError> {
+        let job = self.load_job(job_id).await?;
+        let config = self.dispatch_settings.config_for(&job, Utc::now()).await;
+        let candidates = self.driver_service.find_dispatch_candidates(
+            job.pickup_location.latitude,
+            job.pickup_location.longitude,
+            config.search_radius_for(&job),
+            config.max_candidates * 2,
+            &job.package.handling(),
+        ).await?;
+
+        let mut drivers = Vec::new();
+        for candidate in rank_candidates(candidates, &config) {
+            if let Some(driver) = self.driver_service.get_driver(&candidate.driver_id).await? {
+                drivers.push(CandidateDriver {
+                    driver,
+                    distance_km: candidate.distance_km,
+                    reliability_score: candidate.reliability_score,
+                });
+            }
+        }
+        Ok(drivers)
+    }
+
+    /// Give the job to `driver_id`, taking it off whoever holds it now
+    pub async fn force_assign(
+        &self,
+        dispatcher: &Dispatcher<'_>,
+        job_id: &JobId,
+        driver_id: &DriverId,
+        reason: Option<String>,
+    ) -> Result<JobResponse, AppError> {
+        let job = self.load_job(job_id).await?;
+        if job.status.is_terminal() || job.status.is_carrying_package() {
+            return Err(AppError::Conflict(format!(
+                "Job {} cannot be reassigned once {:?}",
+                job_id, job.status
+            )));
+        }
+        if job.driver_id.as_ref() == Some(driver_id) {
+            return Err(AppError::Conflict(format!("Job {} is already assigned to driver {}", job_id, driver_id)));
+        }
+
+        let previous_driver_id = job.driver_id.clone();
+        if previous_driver_id.is_some() {
+            self.job_service.unassign_driver(job_id, reason.clone()).await?;
+        }
+        let response = self.job_service.assign_driver_to_job(job_id, driver_id).await?;
+
+        self.audit(dispatcher, DispatchAuditEntry {
+            action: DispatchAction::ForceAssign,
+            dispatcher_id: dispatcher.user_id.clone(),
+            api_key_id: dispatcher.api_key_id.to_string(),
+            job_id: job_id.clone(),
+            driver_id: Some(driver_id.clone()),
+            previous_driver_id,
+            zone: None,
+            drivers_offered: None,
+            reason,
+            at: Utc::now(),
+        }).await;
+        Ok(response)
+    }
+
+    /// Take the job off its driver and put it back up for dispatch
+    pub async fn force_unassign(
+        &self,
+        dispatcher: &Dispatcher<'_>,
+        job_id: &JobId,
+        reason: Option<String>,
+    ) -> Result<JobResponse, AppError> {
+        let previous_driver_id = self.load_job(job_id).await?.driver_id;
+        let response = self.job_service.unassign_driver(job_id, reason.clone()).await?;
+
+        self.audit(dispatcher, DispatchAuditEntry {
+            action: DispatchAction::ForceUnassign,
+            dispatcher_id: dispatcher.user_id.clone(),
+            api_key_id: dispatcher.api_key_id.to_string(),
+            job_id: job_id.clone(),
+            driver_id: None,
+            previous_driver_id,
+            zone: None,
+            drivers_offered: None,
+            reason,
+            at: Utc::now(),
+        }).await;
+        Ok(response)
+    }
+
+    /// Offer an unassigned job to every online driver whose live position is in `zone`
+    pub async fn broadcast(
+        &self,
+        dispatcher: &Dispatcher<'_>,
+        job_id: &JobId,
+        zone: Option<String>,
+    ) -> Result<BroadcastResponse, AppError> {
+        let mut job = self.load_job(job_id).await?;
+        if job.driver_id.is_some() || !matches!(job.status, JobStatus::Pending | JobStatus::Searching) {
+            return Err(AppError::Conflict(format!("Job {} is not waiting for a driver", job_id)));
+        }
+
+        let zone = match zone {
+            Some(zone) => {
+                let zone = zone.trim().to_ascii_lowercase();
+                if zone.is_empty() || zone.len() > geohash::MAX_PRECISION || geohash::decode_center(&zone).is_none() {
+                    return Err(AppError::validation_error("zone", "Expected a geohash cell"));
+                }
+                zone
+            }
+            None => geohash::encode(job.pickup_location.latitude, job.pickup_location.longitude, self.config.zone_precision),
+        };
+
+        let handling = job.package.handling();
+        let mut offered_to = Vec::new();
+        for driver_id in self.cache_service.get_all_driver_ids().await? {
+            if offered_to.len() >= self.config.max_broadcast_drivers {
+                break;
+            }
+            let Some(driver) = self.cache_service.get_driver(&driver_id).await? else {
+                continue;
+            };
+            if driver.status != DriverStatus::Online || !driver.can_take_work() || !driver.equipment.covers(&handling) {
+                continue;
+            }
+            if self.cache_service.is_pair_blocked(&driver_id, &job.customer_id).await? {
+                continue;
+            }
+            let position = match self.cache_service.get_driver_location(&driver_id).await? {
+                Some(live) => Some((live.latitude, live.longitude)),
+                None => driver.current_location.as_ref().map(|location| (location.latitude, location.longitude)),
+            };
+            let in_zone = position.is_some_and(|(latitude, longitude)| {
+                geohash::encode(latitude, longitude, zone.len()) == zone
+            });
+            if in_zone {
+                offered_to.push((driver_id, driver.vehicle.vehicle_type));
+            }
+        }
+        let (offered_to, vehicle_types): (Vec<DriverId>, Vec<VehicleType>) = offered_to.into_iter().unzip();
+
+        for driver_id in &offered_to {
+            if !job.offered_to_drivers.contains(driver_id) {
+                job.offered_to_drivers.push(driver_id.clone());
+            }
+        }
+        job.status = JobStatus::Searching;
+        job.updated_at = Utc::now();
+        self.cache_service.cache_job(&job).await?;
+
+        let offer_timeout_seconds = self.dispatch_settings.config_for(&job, job.updated_at).await.offer_timeout_seconds;
+        for (driver_id, vehicle_type) in offered_to.iter().zip(&vehicle_types) {
+            let earnings = self.earnings.preview(&job, vehicle_type).await;
+            self.driver_service.record_outcome(driver_id, DispatchOutcomeKind::Offered, job_id, None).await;
+            // Drivers with the app open get the offer over their socket; the rest by push
+            if self.driver_channel.send_offer(driver_id, &job, &earnings, offer_timeout_seconds).await {
+                continue;
+            }
+            // Best-effort: one unreachable device must not stop the rest
+            if let Err(e) = self.notification_service.send_to_driver(driver_id, NotificationMessage::job_offer(&job, &earnings)).await {
+                tracing::warn!("Failed to offer job {} to driver {}: {}", job_id, driver_id, e);
+            }
+        }
+
+        self.audit(dispatcher, DispatchAuditEntry {
+            action: DispatchAction::Broadcast,
+            dispatcher_id: dispatcher.user_id.clone(),
+            api_key_id: dispatcher.api_key_id.to_string(),
+            job_id: job_id.clone(),
+            driver_id: None,
+            previous_driver_id: None,
+            zone: Some(zone.clone()),
+            drivers_offered: Some(offered_to.len()),
+            reason: None,
+            at: Utc::now(),
+        }).await;
+
+        Ok(BroadcastResponse {
+            job_id: job_id.clone(),
+            zone,
+            offered_to,
+        })
+    }
+
+    /// Offer the driver delivering `current` the closest open job picking up near its dropoff,
+    /// to take straight after it. None when there is nothing close enough, or the driver
+    /// already has a next job or an offer of one.
+    pub async fn offer_next_job(&self, current: &Job) -> Result<Option<JobId>, AppError> {
+        let Some(driver_id) = current.driver_id.clone() else {
+            return Ok(None);
+        };
+        let Some(driver) = self.cache_service.get_driver(&driver_id).await? else {
+            return Ok(None);
+        };
+        if driver.queued_job_id.is_some() || !driver.can_take_work() {
+            return Ok(None);
+        }
+
+        let dropoff = (current.dropoff_location.latitude, current.dropoff_location.longitude);
+        let mut closest: Option<(f64, Job)> = None;
+        for job_id in self.cache_service.get_active_jobs().await? {
+            let Some(job) = self.cache_service.load_job(&job_id).await? else {
+                continue;
+            };
+            if job.driver_id.is_some() || !matches!(job.status, JobStatus::Pending | JobStatus::Searching) {
+                continue;
+            }
+            if job.offered_to_drivers.contains(&driver_id) && !job.rejected_by_drivers.contains(&driver_id) {
+                // Still waiting on their answer to an earlier offer
+                return Ok(None);
+            }
+            if job.rejected_by_drivers.contains(&driver_id) || !driver.can_carry(&job.package) {
+                continue;
+            }
+            let distance_km = haversine_km(dropoff, (job.pickup_location.latitude, job.pickup_location.longitude));
+            let closer = closest.as_ref().is_none_or(|(closest_km, _)| distance_km < *closest_km);
+            if distance_km > self.config.chain_radius_km || !closer {
+                continue;
+            }
+            if self.cache_service.is_pair_blocked(&driver_id, &job.customer_id).await? {
+                continue;
+            }
+            closest = Some((distance_km, job));
+        }
+        let Some((_, mut job)) = closest else {
+            return Ok(None);
+        };
+
+        job.offered_to_drivers.push(driver_id.clone());
+        job.status = JobStatus::Searching;
+        job.updated_at = Utc::now();
+        self.cache_service.cache_job(&job).await?;
+
+        let offer_timeout_seconds = self.dispatch_settings.config_for(&job, job.updated_at).await.offer_timeout_seconds;
+        let earnings = self.earnings.preview(&job, &driver.vehicle.vehicle_type).await;
+        self.driver_service.record_outcome(&driver_id, DispatchOutcomeKind::Offered, &job.id, None).await;
+        // Over their socket if the app is open, otherwise by push
+        if !self.driver_channel.send_offer(&driver_id, &job, &earnings, offer_timeout_seconds).await
+            && let Err(e) = self.notification_service.send_to_driver(&driver_id, NotificationMessage::job_offer(&job, &earnings)).await
+        {
+            tracing::warn!("Failed to offer next job {} to driver {}: {}", job.id, driver_id, e);
+        }
+
+        tracing::info!("Offered job {} to driver {} after job {}", job.id, driver_id, current.id);
+        Ok(Some(job.id))
+    }
+
+    /// A driver's answer to an offer made over their socket, and the event to send back.
+    /// Acceptance assigns the job and withdraws everyone else's offer of it.
+    pub async fn answer_offer(&self, driver_id: &DriverId, job_id: JobId, accepted: bool) -> DriverSocketEvent {
+        let answered = if accepted {
+            self.accept_offer(driver_id, &job_id).await
+                .map(|_| DriverSocketEvent::OfferAccepted { job_id: job_id.clone() })
+        } else {
+            self.reject_offer(driver_id, &job_id).await
+                .map(|_| DriverSocketEvent::OfferRejected { job_id: job_id.clone() })
+        };
+        answered.unwrap_or_else(|e| DriverSocketEvent::Error { job_id: Some(job_id), message: e.to_string() })
+    }
+
+    async fn accept_offer(&self, driver_id: &DriverId, job_id: &JobId) -> Result<JobResponse, AppError> {
+        self.driver_channel.take_offer(driver_id, job_id, Utc::now()).await?;
+        let job = self.load_job(job_id).await?;
+        if job.driver_id.is_some() || !matches!(job.status, JobStatus::Pending | JobStatus::Searching) {
+            return Err(AppError::Conflict(format!("Job {} has already been taken", job_id)));
+        }
+        let response = self.job_service.assign_driver_to_job(job_id, driver_id).await?;
+        // The job is taken either way; others' offers still lapse on their own
+        if let Err(e) = self.driver_channel.withdraw_offers(job_id, driver_id).await {
+            tracing::warn!("Failed to withdraw other offers of job {}: {}", job_id, e);
+        }
+        Ok(response)
+    }
+
+    // Rejected jobs stay out of the driver's available list
+    async fn reject_offer(&self, driver_id: &DriverId, job_id: &JobId) -> Result<(), AppError> {
+        self.driver_channel.take_offer(driver_id, job_id, Utc::now()).await?;
+        let mut job = self.load_job(job_id).await?;
+        if !job.rejected_by_drivers.contains(driver_id) {
+            job.rejected_by_drivers.push(driver_id.clone());
+            job.updated_at = Utc::now();
+            self.cache_service.cache_job(&job).await?;
+        }
+        Ok(())
+    }
+
+    /// Most recent first, optionally since `since_hours` ago
+    pub async fn audit_log(&self, since_hours: Option<i64>) -> Result<Vec<DispatchAuditEntry>, AppError> {
+        let since = since_hours.map(|hours| Utc::now() - Duration::hours(hours));
+        let mut entries: Vec<_> = self.cache_service.get_dispatch_audit().await?
+            .into_iter()
+            .filter(|entry| since.is_none_or(|since| entry.at >= since))
+            .collect();
+        entries.reverse();
+        Ok(entries)
+    }
+
+    // The override already happened; a lost audit write is logged loudly rather than undone
+    async fn audit(&self, dispatcher: &Dispatcher<'_>, entry: DispatchAuditEntry) {
+        tracing::info!(
+            "Dispatcher {} {:?} on job {} (driver {:?}, was {:?})",
+            dispatcher.user_id, entry.action, entry.job_id, entry.driver_id, entry.previous_driver_id
+        );
+        if let Err(e) = self.cache_service.append_dispatch_audit(&entry).await {
+            tracing::error!("Failed to write dispatch audit entry for job {}: {}", entry.job_id, e);
+        }
+    }
+}
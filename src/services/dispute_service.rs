@@ -0,0 +1,320 @@
+// src/services/dispute_service.rs
+// Chargebacks. When a provider tells us a customer disputed a payment, the driver's
+// earnings from that job are frozen in the ledger and a case is opened. Finance adds
+// evidence and records the outcome: a win releases the earnings, a loss refunds the
+// customer out of them and books any shortfall as a chargeback loss.
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use ring::hmac;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        dispute::{
+            ChargebackNotification, DisputeCase, DisputeEvidence, DisputeOutcome, DisputeOutcomeRequest, DisputeStatus,
+            SubmitEvidenceRequest,
+        },
+        ids::JobId,
+        job::{JobEvent, JobEventType, PaymentStatus},
+        ledger::{CreateLedgerEntryRequest, LedgerAccount, PlatformAccount, Posting},
+        money::Money,
+    },
+    services::{cache_service::CacheService, ledger::LedgerService},
+    utils::id_generator::{IdGenerator, IdType},
+};
+
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "x-webhook-signature";
+
+#[derive(Clone, Default)]
+pub struct DisputeConfig {
+    pub webhook_secret: Option<Vec<u8>>, // Shared with the providers; webhooks are refused without one
+}
+
+impl DisputeConfig {
+    pub fn from_env() -> Self {
+        Self {
+            webhook_secret: std::env::var("PAYMENT_WEBHOOK_SECRET").ok()
+                .filter(|value| !value.is_empty())
+                .map(String::into_bytes),
+        }
+    }
+}
+
+pub struct DisputeService {
+    cache_service: Arc<CacheService>,
+    ledger: Arc<LedgerService>,
+    webhook_key: Option<hmac::Key>,
+}
+
+impl DisputeService {
+    pub fn new(cache_service: Arc<CacheService>, ledger: Arc<LedgerService>, config: DisputeConfig) -> Self {
+        Self {
+            cache_service,
+            ledger,
+            webhook_key: config.webhook_secret.map(|secret| hmac::Key::new(hmac::HMAC_SHA256, &secret)),
+        }
+    }
+
+    /// Check a webhook body against its base64 HMAC-SHA256 signature
+    pub fn verify_webhook(&self, body: &[u8], signature: Option<&str>) -> Result<(), AppError> {
+        let key = self.webhook_key.as_ref()
+            .ok_or_else(|| AppError::unauthorized("Payment webhooks are not configured"))?;
+        let signature = signature
+            .and_then(|signature| STANDARD.decode(signature.trim()).ok())
+            .ok_or_else(|| AppError::unauthorized("Missing or malformed webhook signature"))?;
+        hmac::verify(key, body, &signature).map_err(|_| AppError::unauthorized("Webhook signature does not match"))
+    }
+
+    /// Open a case for a chargeback, freezing the driver's earnings from the job.
+    /// A repeated notice returns the case it opened the first time.
+    pub async fn open_case(&self, notification: ChargebackNotification) -> Result<DisputeCase, AppError> {
+        if notification.amount.is_negative() || notification.amount.is_zero() {
+            return Err(AppError::validation_error("amount", "Must be positive"));
+        }
+        let job_id = JobId::parse(notification.reference.trim())?;
+        if let Some(existing) = self.case_for_event(&notification.provider, &notification.event_id).await? {
+            return Ok(existing);
+        }
+        let mut job = self.cache_service.load_job(&job_id).await?
+            .ok_or_else(|| AppError::NotFound(format!("Job {} not found", job_id)))?;
+        if !self.cache_service.claim_chargeback_event(&notification.provider, &notification.event_id).await? {
+            return match self.case_for_event(&notification.provider, &notification.event_id).await? {
+                Some(existing) => Ok(existing),
+                None => Err(AppError::Conflict(format!("Chargeback {} is already being opened", notification.event_id))),
+            };
+        }
+
+        let case_id = IdGenerator::generate(IdType::Dispute);
+        let currency = notification.amount.currency();
+
+        // Freeze what the driver was credited for the job, up to the disputed amount
+        let mut frozen_earnings = Money::zero(currency);
+        let mut ledger_entry_ids = Vec::new();
+        if let Some(driver_id) = &job.driver_id {
+            let driver_account = LedgerAccount::Driver(driver_id.clone());
+            let credited = self.ledger.job_entries(&job.id).await?
+                .iter()
+                .filter_map(|entry| entry.net_for(&driver_account))
+                .filter(|amount| amount.currency() == currency)
+                .fold(Money::zero(currency), |total, amount| total + amount);
+            frozen_earnings = Money::from_minor(credited.minor().clamp(0, notification.amount.minor()), currency);
+            if !frozen_earnings.is_zero() {
+                let entry = self.ledger.post(CreateLedgerEntryRequest {
+                    posting_key: format!("dispute:{}:freeze", case_id),
+                    description: format!("Earnings frozen for chargeback on job {}", job.id),
+                    job_id: Some(job.id.clone()),
+                    provider_reference: None,
+                    postings: vec![
+                        Posting::debit(driver_account, frozen_earnings),
+                        Posting::credit(LedgerAccount::Platform(PlatformAccount::Disputes), frozen_earnings),
+                    ],
+                }).await?;
+                ledger_entry_ids.push(entry.id);
+            }
+        }
+
+        let case = DisputeCase {
+            id: case_id,
+            job_id: job.id.clone(),
+            driver_id: job.driver_id.clone(),
+            provider: notification.provider,
+            provider_event_id: notification.event_id,
+            amount: notification.amount,
+            reason: notification.reason,
+            respond_by: notification.respond_by,
+            status: DisputeStatus::Open,
+            frozen_earnings,
+            ledger_entry_ids,
+            evidence: Vec::new(),
+            outcome_note: None,
+            opened_at: Utc::now(),
+            closed_at: None,
+        };
+        self.cache_service.cache_dispute(&case).await?;
+        self.cache_service.cache_chargeback_event(&case.provider, &case.provider_event_id, &case.id).await?;
+
+        job.payment_status = PaymentStatus::Disputed;
+        job.updated_at = Utc::now();
+        self.cache_service.cache_job(&job).await?;
+        self.cache_service.append_job_event(&job.id, &JobEvent {
+            event_type: JobEventType::PaymentDisputed,
+            timestamp: Utc::now(),
+            location: None,
+            actor: case.provider.clone(),
+            notes: Some(format!("Chargeback of {}: {}", case.amount, case.reason)),
+        }).await?;
+
+        tracing::warn!("Opened dispute {} for job {} ({} frozen)", case.id, case.job_id, case.frozen_earnings);
+        Ok(case)
+    }
+
+    pub async fn get_case(&self, case_id: &str) -> Result<DisputeCase, AppError> {
+        self.cache_service.get_dispute(case_id).await?
+            .ok_or_else(|| AppError::NotFound(format!("Dispute {} not found", case_id)))
+    }
+
+    /// Cases, newest first, optionally only those with `status`
+    pub async fn cases(&self, status: Option<DisputeStatus>) -> Result<Vec<DisputeCase>, AppError> {
+        let mut cases = Vec::new();
+        for case_id in self.cache_service.get_dispute_ids().await? {
+            if let Some(case) = self.cache_service.get_dispute(&case_id).await?
+                && status.is_none_or(|status| case.status == status)
+            {
+                cases.push(case);
+            }
+        }
+        cases.sort_by_key(|case| std::cmp::Reverse(case.opened_at));
+        Ok(cases)
+    }
+
+    pub async fn submit_evidence(&self, case_id: &str, request: SubmitEvidenceRequest) -> Result<DisputeCase, AppError> {
+        let description = request.description.trim();
+        if description.is_empty() {
+            return Err(AppError::validation_error("description", "Must not be empty"));
+        }
+        let mut case = self.get_case(case_id).await?;
+        if case.status.is_closed() {
+            return Err(AppError::Conflict(format!("Dispute {} is already closed", case_id)));
+        }
+        case.evidence.push(DisputeEvidence {
+            description: description.to_string(),
+            url: request.url.map(|url| url.trim().to_string()).filter(|url| !url.is_empty()),
+            submitted_at: Utc::now(),
+        });
+        case.status = DisputeStatus::EvidenceSubmitted;
+        self.cache_service.cache_dispute(&case).await?;
+        Ok(case)
+    }
+
+    pub async fn record_outcome(&self, case_id: &str, request: DisputeOutcomeRequest) -> Result<DisputeCase, AppError> {
+        let mut case = self.get_case(case_id).await?;
+        if case.status.is_closed() {
+            return Err(AppError::Conflict(format!("Dispute {} is already closed", case_id)));
+        }
+        let disputes = LedgerAccount::Platform(PlatformAccount::Disputes);
+        let postings = match (request.outcome, &case.driver_id) {
+            (DisputeOutcome::Won, Some(driver_id)) => vec![
+                Posting::debit(disputes, case.frozen_earnings),
+                Posting::credit(LedgerAccount::Driver(driver_id.clone()), case.frozen_earnings),
+            ],
+            (DisputeOutcome::Won, None) => Vec::new(),
+            // The refund leaves our cash; the frozen earnings cover what they can
+            (DisputeOutcome::Lost, _) => vec![
+                Posting::debit(disputes, case.frozen_earnings),
+                Posting::debit(LedgerAccount::Platform(PlatformAccount::Chargebacks), case.amount - case.frozen_earnings),
+                Posting::credit(LedgerAccount::Platform(PlatformAccount::Cash), case.amount),
+            ],
+        };
+        // A won case with nothing frozen has nothing to move
+        if postings.iter().any(|posting| !posting.amount.is_zero()) {
+            let entry = self.ledger.post(CreateLedgerEntryRequest {
+                posting_key: format!("dispute:{}:outcome", case.id),
+                description: format!("Chargeback on job {} {}", case.job_id, if request.outcome == DisputeOutcome::Won { "won" } else { "lost" }),
+                job_id: Some(case.job_id.clone()),
+                provider_reference: None,
+                postings,
+            }).await?;
+            case.ledger_entry_ids.push(entry.id);
+        }
+
+        case.status = match request.outcome {
+            DisputeOutcome::Won => DisputeStatus::Won,
+            DisputeOutcome::Lost => DisputeStatus::Lost,
+        };
+        case.outcome_note = request.note.map(|note| note.trim().to_string()).filter(|note| !note.is_empty());
+        case.closed_at = Some(Utc::now());
+        self.cache_service.cache_dispute(&case).await?;
+
+        if let Some(mut job) = self.cache_service.load_job(&case.job_id).await? {
+            job.payment_status = match request.outcome {
+                DisputeOutcome::Won => PaymentStatus::Paid,
+                DisputeOutcome::Lost => PaymentStatus::Refunded,
+            };
+            job.updated_at = Utc::now();
+            self.cache_service.cache_job(&job).await?;
+        }
+
+        tracing::info!("Dispute {} for job {} closed as {:?}", case.id, case.job_id, case.status);
+        Ok(case)
+    }
+
+    async fn case_for_event(&self, provider: &str, event_id: &str) -> Result<Option<DisputeCase>, AppError> {
+        match self.cache_service.get_chargeback_event_case(provider, event_id).await? {
+            Some(case_id) => self.cache_service.get_dispute(&case_id).await,
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::{ids::{DriverId, UserId}, money::Currency},
+    };
+
+    #[tokio::test]
+    async fn test_chargeback_freezes_earnings_until_the_outcome() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let secret = b"whsec".to_vec();
+        let disputes = DisputeService::new(
+            state.cache_service.clone(),
+            state.ledger_service.clone(),
+            DisputeConfig { webhook_secret: Some(secret.clone()) },
+        );
+        let cedis = |amount: f64| Money::from_major(amount, Currency::GHS);
+
+        let driver_id = DriverId::generate();
+        let mut job = Faker::seeded(63).job(&UserId::generate());
+        job.driver_id = Some(driver_id.clone());
+        job.pricing.total = cedis(50.0);
+        job.pricing.tax = cedis(0.0);
+        state.cache_service.cache_job(&job).await.unwrap();
+        let mut earnings = state.earnings_calculator.calculate(&job.pricing, 0.2, 1.0);
+        earnings.total = cedis(40.0);
+        state.ledger_service.post_job_settlement(&job, Some(&earnings)).await.unwrap();
+
+        let notification = serde_json::json!({
+            "provider": "paystack", "event_id": "evt_1", "reference": job.id.to_string(),
+            "amount": { "minor": 5000, "currency": "GHS" }, "reason": "fraudulent"
+        });
+        let body = serde_json::to_vec(&notification).unwrap();
+        let signature = STANDARD.encode(hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &secret), &body).as_ref());
+        assert!(disputes.verify_webhook(&body, Some(&signature)).is_ok());
+        assert!(disputes.verify_webhook(b"{}", Some(&signature)).is_err());
+        assert!(disputes.verify_webhook(&body, None).is_err());
+
+        let notification: ChargebackNotification = serde_json::from_slice(&body).unwrap();
+        let case = disputes.open_case(notification.clone()).await.unwrap();
+        assert_eq!(case.frozen_earnings, cedis(40.0));
+        assert_eq!(disputes.open_case(notification).await.unwrap().id, case.id);
+        let driver_balance = || async {
+            state.ledger_service.balance(&LedgerAccount::Driver(driver_id.clone()), Currency::GHS).await.unwrap().balance
+        };
+        assert!(driver_balance().await.is_zero());
+        let disputed = state.cache_service.load_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(disputed.payment_status, PaymentStatus::Disputed);
+
+        let case = disputes.submit_evidence(&case.id, SubmitEvidenceRequest {
+            description: "Signed dropoff handoff".to_string(),
+            url: Some("https://files.example/handoff.jpg".to_string()),
+        }).await.unwrap();
+        assert_eq!(case.status, DisputeStatus::EvidenceSubmitted);
+
+        let lost = disputes.record_outcome(&case.id, DisputeOutcomeRequest { outcome: DisputeOutcome::Lost, note: None }).await.unwrap();
+        assert_eq!(lost.ledger_entry_ids.len(), 2);
+        let chargebacks = state.ledger_service.balance(&LedgerAccount::Platform(PlatformAccount::Chargebacks), Currency::GHS).await.unwrap();
+        assert_eq!(chargebacks.balance, cedis(10.0));
+        assert!(driver_balance().await.is_zero());
+        assert!(matches!(
+            disputes.record_outcome(&case.id, DisputeOutcomeRequest { outcome: DisputeOutcome::Won, note: None }).await,
+            Err(AppError::Conflict(_))
+        ));
+        assert_eq!(disputes.cases(Some(DisputeStatus::Lost)).await.unwrap().len(), 1);
+    }
+}
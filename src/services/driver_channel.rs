@@ -0,0 +1,316 @@
+// src/services/driver_channel.rs
+// Live WebSockets to driver apps. Offers go down the socket when one is open, which beats a
+// push inside the accept window; each carries a deadline after which its answer is refused.
+// An open socket is what puts a driver in the tenant's online-drivers set, through presence.
+// Events travel over the realtime bus and open offers live in Redis, so the instance making
+// an offer need not be the one holding the driver's socket.
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+#[cfg(feature = "mqtt")]
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::{mpsc, Mutex};
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        dispatch::DriverSocketEvent,
+        domain_event::DomainEvent,
+        ids::{DriverId, JobId},
+        job::{DriverEarnings, Job},
+        presence::{PresenceEntry, PresenceKind},
+    },
+    services::{
+        cache_service::CacheService,
+        event_bus::EventBus,
+        messaging_service::NotificationMessage,
+        presence_service::PresenceService,
+        realtime_bus::{driver_topic, RealtimeBus},
+        tenant_service::current_tenant_id,
+    },
+};
+#[cfg(feature = "mqtt")]
+use crate::services::mqtt_bridge::MqttBridge;
+
+#[derive(Debug, Clone)]
+struct Connection {
+    id: u64,
+    subscription_id: u64,
+    presence: PresenceEntry,
+}
+
+/// Events for one socket, as the bus delivers them; ends when a newer socket replaces it
+pub struct DriverEvents {
+    receiver: mpsc::UnboundedReceiver<Vec<u8>>,
+}
+
+impl DriverEvents {
+    pub async fn recv(&mut self) -> Option<DriverSocketEvent> {
+        loop {
+            let payload = self.receiver.recv().await?;
+            if let Some(event) = decode_event(&payload) {
+                return Some(event);
+            }
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Option<DriverSocketEvent> {
+        loop {
+            let payload = self.receiver.try_recv().ok()?;
+            if let Some(event) = decode_event(&payload) {
+                return Some(event);
+            }
+        }
+    }
+}
+
+fn decode_event(payload: &[u8]) -> Option<DriverSocketEvent> {
+    serde_json::from_slice(payload)
+        .map_err(|e| tracing::warn!("Dropped undecodable driver event: {}", e))
+        .ok()
+}
+
+pub struct DriverChannel {
+    cache_service: Arc<CacheService>,
+    presence_service: Arc<PresenceService>,
+    bus: Arc<RealtimeBus>,
+    events: Arc<dyn EventBus>,
+    connections: Mutex<HashMap<(String, DriverId), Connection>>, // Sockets held by this instance
+    next_connection_id: AtomicU64,
+    #[cfg(feature = "mqtt")]
+    mqtt: OnceLock<Arc<MqttBridge>>, // Attached once the broker is configured
+}
+
+impl DriverChannel {
+    pub fn new(
+        cache_service: Arc<CacheService>,
+        presence_service: Arc<PresenceService>,
+        bus: Arc<RealtimeBus>,
+        events: Arc<dyn EventBus>,
+    ) -> Self {
+        Self {
+            cache_service,
+            presence_service,
+            bus,
+            events,
+            connections: Mutex::new(HashMap::new()),
+            next_connection_id: AtomicU64::new(1),
+            #[cfg(feature = "mqtt")]
+            mqtt: OnceLock::new(),
+        }
+    }
+
+    // The bridge needs services built after this channel, so it is attached afterwards
+    #[cfg(feature = "mqtt")]
+    pub fn attach_mqtt(&self, bridge: Arc<MqttBridge>) {
+        if self.mqtt.set(bridge).is_err() {
+            tracing::warn!("MQTT bridge already attached");
+        }
+    }
+
+    /// Register a driver's socket; a newer one replaces any left open here. Returns the
+    /// connection's ID, needed to disconnect it, and the events to write to it.
+    pub async fn connect(&self, driver_id: &DriverId) -> Result<(u64, DriverEvents), AppError> {
+        let presence = self.presence_service.connected(PresenceKind::Driver, driver_id.as_str()).await?;
+        let topic = driver_topic(driver_id);
+        let (subscription_id, receiver) = self.bus.subscribe(&topic);
+        let id = self.next_connection_id.fetch_add(1, Ordering::Relaxed);
+        let replaced = self.connections.lock().await
+            .insert((current_tenant_id(), driver_id.clone()), Connection { id, subscription_id, presence });
+        match replaced {
+            Some(replaced) => self.bus.unsubscribe(&topic, replaced.subscription_id),
+            None => self.events.publish(DomainEvent::DriverWentOnline { driver_id: driver_id.clone() }).await,
+        }
+        tracing::info!("Driver {} connected (connection {})", driver_id, id);
+        Ok((id, DriverEvents { receiver }))
+    }
+
+    // Offers left open die with the socket; the job can still be accepted over HTTP
+    pub async fn disconnect(&self, driver_id: &DriverId, connection_id: u64) {
+        let key = (current_tenant_id(), driver_id.clone());
+        let connection = {
+            let mut connections = self.connections.lock().await;
+            // A reconnect may already have replaced this socket
+            match connections.get(&key) {
+                Some(connection) if connection.id == connection_id => connections.remove(&key),
+                _ => None,
+            }
+        };
+        let Some(connection) = connection else {
+            return;
+        };
+        self.bus.unsubscribe(&driver_topic(driver_id), connection.subscription_id);
+        if let Err(e) = self.close_offers(driver_id).await {
+            tracing::warn!("Failed to close offers of driver {}: {}", driver_id, e);
+        }
+        if let Err(e) = self.presence_service.disconnected(&connection.presence).await {
+            tracing::warn!("Failed to mark driver {} offline: {}", driver_id, e);
+        }
+        self.events.publish(DomainEvent::DriverWentOffline { driver_id: driver_id.clone() }).await;
+        tracing::info!("Driver {} disconnected (connection {})", driver_id, connection_id);
+    }
+
+    async fn close_offers(&self, driver_id: &DriverId) -> Result<(), AppError> {
+        for job_id in self.cache_service.get_driver_offer_ids(driver_id).await? {
+            self.cache_service.take_offer(driver_id, &job_id).await?;
+        }
+        Ok(())
+    }
+
+    // Called on the presence heartbeat while the socket stays open
+    pub async fn heartbeat(&self, driver_id: &DriverId, connection_id: u64) -> Result<(), AppError> {
+        let presence = self.connections.lock().await
+            .get(&(current_tenant_id(), driver_id.clone()))
+            .filter(|connection| connection.id == connection_id)
+            .map(|connection| connection.presence.clone());
+        match presence {
+            Some(presence) => self.presence_service.heartbeat(&presence).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the driver holds a socket on any instance
+    pub async fn is_connected(&self, driver_id: &DriverId) -> bool {
+        match self.cache_service.get_presence(PresenceKind::Driver, driver_id.as_str()).await {
+            Ok(presence) => presence.is_some(),
+            Err(e) => {
+                tracing::warn!("Failed to look up presence of driver {}: {}", driver_id, e);
+                false
+            }
+        }
+    }
+
+    // False when the driver has no open socket, or the event could not be published
+    pub async fn send(&self, driver_id: &DriverId, event: DriverSocketEvent) -> bool {
+        if !self.is_connected(driver_id).await {
+            return false;
+        }
+        self.publish(driver_id, &event).await
+    }
+
+    async fn publish(&self, driver_id: &DriverId, event: &DriverSocketEvent) -> bool {
+        let published = match serde_json::to_vec(event) {
+            Ok(payload) => self.bus.publish(&driver_topic(driver_id), payload).await,
+            Err(e) => Err(AppError::JsonSerialization(e.to_string())),
+        };
+        published
+            .map_err(|e| tracing::warn!("Failed to publish event for driver {}: {}", driver_id, e))
+            .is_ok()
+    }
+
+    /// Offer `job` over the driver's socket, open for `offer_timeout_seconds`; false when they
+    /// have none and need a push instead. Fleets on MQTT get a copy there either way.
+    pub async fn send_offer(&self, driver_id: &DriverId, job: &Job, earnings: &DriverEarnings, offer_timeout_seconds: i64) -> bool {
+        let expires_at = Utc::now() + Duration::seconds(offer_timeout_seconds);
+        let offer = NotificationMessage::job_offer(job, earnings).data.unwrap_or_default();
+        let event = DriverSocketEvent::JobOffer { job_id: job.id.clone(), expires_at, offer };
+        #[cfg(feature = "mqtt")]
+        if let Some(mqtt) = self.mqtt.get()
+            && let Err(e) = mqtt.publish_offer(driver_id, &event).await
+        {
+            tracing::warn!("Failed to mirror offer of job {} to driver {} over MQTT: {}", job.id, driver_id, e);
+        }
+        if !self.is_connected(driver_id).await {
+            return false;
+        }
+        // Recorded first, so an answer arriving straight away finds it open
+        let ttl = (offer_timeout_seconds + 60) as u64;
+        if let Err(e) = self.cache_service.cache_offer(driver_id, &job.id, expires_at, ttl).await {
+            tracing::warn!("Failed to record offer of job {} to driver {}: {}", job.id, driver_id, e);
+            return false;
+        }
+        self.publish(driver_id, &event).await
+    }
+
+    /// Close the driver's open offer of `job_id` to answer it
+    pub async fn take_offer(&self, driver_id: &DriverId, job_id: &JobId, now: DateTime<Utc>) -> Result<(), AppError> {
+        match self.cache_service.take_offer(driver_id, job_id).await? {
+            Some(expires_at) if expires_at >= now => Ok(()),
+            Some(_) => Err(AppError::Conflict(format!("The offer of job {} has expired", job_id))),
+            None => Err(AppError::Conflict(format!("Job {} has no open offer for this driver", job_id))),
+        }
+    }
+
+    /// Offers of this driver's that ran out by `now`, closed
+    pub async fn expire_offers(&self, driver_id: &DriverId, now: DateTime<Utc>) -> Result<Vec<JobId>, AppError> {
+        let mut expired = Vec::new();
+        for job_id in self.cache_service.get_driver_offer_ids(driver_id).await? {
+            // A deadline that is gone from Redis lapsed with its key
+            let deadline = self.cache_service.get_offer_deadline(driver_id, &job_id).await?;
+            if deadline.is_none_or(|expires_at| expires_at < now) {
+                self.cache_service.take_offer(driver_id, &job_id).await?;
+                expired.push(job_id);
+            }
+        }
+        Ok(expired)
+    }
+
+    /// Close every other open offer of a job that has been taken, telling those drivers
+    pub async fn withdraw_offers(&self, job_id: &JobId, taken_by: &DriverId) -> Result<(), AppError> {
+        for driver_id in self.cache_service.get_job_offer_driver_ids(job_id).await? {
+            if &driver_id == taken_by {
+                continue;
+            }
+            self.cache_service.take_offer(&driver_id, job_id).await?;
+            self.send(&driver_id, DriverSocketEvent::OfferWithdrawn { job_id: job_id.clone() }).await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mocks::fixtures::Faker,
+        models::ids::UserId,
+        services::{
+            cache_service::CacheConfig,
+            earnings::{EarningsCalculator, EarningsConfig},
+            event_bus::InProcessEventBus,
+            presence_service::PresenceConfig,
+            realtime_bus::RealtimeBusConfig,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_offers_reach_sockets_held_by_another_instance() {
+        // Two instances sharing Redis: one cache, one bus
+        let cache_service = Arc::new(CacheService::new_memory(CacheConfig::default()));
+        let bus = Arc::new(RealtimeBus::new(RealtimeBusConfig::default()));
+        let instance = || DriverChannel::new(
+            cache_service.clone(),
+            Arc::new(PresenceService::new(cache_service.clone(), PresenceConfig::default())),
+            bus.clone(),
+            Arc::new(InProcessEventBus::new()),
+        );
+        let (dispatching, holding) = (instance(), instance());
+
+        let mut faker = Faker::seeded(5);
+        let job = faker.job(&UserId::generate());
+        let earnings = EarningsCalculator::new(cache_service.clone(), EarningsConfig::default()).calculate(&job.pricing, 0.2, 1.0);
+        let (taker, other) = (DriverId::generate(), DriverId::generate());
+        let (_, mut taker_events) = holding.connect(&taker).await.unwrap();
+        let (_, mut other_events) = dispatching.connect(&other).await.unwrap();
+
+        assert!(dispatching.send_offer(&taker, &job, &earnings, 20).await);
+        assert!(holding.send_offer(&other, &job, &earnings, 20).await);
+        assert!(!dispatching.send_offer(&DriverId::generate(), &job, &earnings, 20).await);
+        assert!(matches!(taker_events.try_recv(), Some(DriverSocketEvent::JobOffer { job_id, .. }) if job_id == job.id));
+        assert!(matches!(other_events.try_recv(), Some(DriverSocketEvent::JobOffer { .. })));
+
+        // Answered on the instance holding the socket, withdrawn on the other
+        holding.take_offer(&taker, &job.id, Utc::now()).await.unwrap();
+        holding.withdraw_offers(&job.id, &taker).await.unwrap();
+        assert_eq!(other_events.try_recv(), Some(DriverSocketEvent::OfferWithdrawn { job_id: job.id.clone() }));
+        assert!(dispatching.take_offer(&other, &job.id, Utc::now()).await.is_err());
+
+        // A newer socket closes the events of the one it replaced
+        let (_, mut replacement) = holding.connect(&taker).await.unwrap();
+        assert!(taker_events.recv().await.is_none());
+        assert!(dispatching.send(&taker, DriverSocketEvent::OfferExpired { job_id: job.id.clone() }).await);
+        assert!(replacement.try_recv().is_some());
+    }
+}
@@ -1,61 +1,220 @@
 // src/services/driver_service.rs
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use serde_json::json;
 use std::sync::Arc;
 use tracing;
 
 use crate::{
     errors::SparrowError as AppError,
+    models::ids::{DriverId, JobId, UserId},
     models::driver::{
-        Driver, DriverRegistration, DriverStatus, DriverStatusUpdate, DriverLocationUpdate,
-        DriverResponse, Vehicle,
+        Driver, DriverDocument, DriverEquipment, DriverRegistration, DriverStatus, DriverStatusUpdate, DriverLocationUpdate,
+        DispatchOutcome, DispatchOutcomeKind, DriverReliability, DriverResponse, OnboardingState, Vehicle,
+        MAX_RELIABILITY_SCORE,
     },
+    models::job::HandlingRequirements,
     models::user::User,
-    services::cache_service::{CacheService, CacheKeys},
-    services::messaging_service::NotificationService,
-    utils::id_generator::{IdGenerator, IdType, WithGeneratedId},
+    services::cache_service::CacheService,
+    services::dispatch::DispatchCandidate,
+    services::messaging_service::{NotificationMessage, NotificationService},
+    services::tenant_service::current_tenant_id,
+    utils::{geo::haversine_km, id_generator::{IdGenerator, IdType}},
 };
 
 #[async_trait]
 pub trait DriverOperations: Send + Sync {
     async fn register_driver(&self, registration: DriverRegistration) -> Result<DriverResponse, AppError>;
-    async fn get_driver(&self, driver_id: &str) -> Result<Option<DriverResponse>, AppError>;
-    async fn get_driver_by_user_id(&self, user_id: &str) -> Result<Option<DriverResponse>, AppError>;
+    async fn get_driver(&self, driver_id: &DriverId) -> Result<Option<DriverResponse>, AppError>;
+    async fn get_driver_by_user_id(&self, user_id: &UserId) -> Result<Option<DriverResponse>, AppError>;
+    async fn get_driver_profile(&self, driver_id: &DriverId) -> Result<DriverResponse, AppError>;
     async fn update_driver_status(&self, update: DriverStatusUpdate) -> Result<DriverResponse, AppError>;
     async fn update_driver_location(&self, update: DriverLocationUpdate) -> Result<DriverResponse, AppError>;
     async fn find_nearby_drivers(&self, latitude: f64, longitude: f64, radius_km: f64, limit: usize) -> Result<Vec<DriverResponse>, AppError>;
     async fn get_online_drivers(&self) -> Result<Vec<DriverResponse>, AppError>;
-    async fn get_driver_stats(&self, driver_id: &str) -> Result<User, AppError>;
-    async fn delete_driver(&self, driver_id: &str) -> Result<(), AppError>;
+    async fn get_driver_stats(&self, driver_id: &DriverId) -> Result<User, AppError>;
+    async fn delete_driver(&self, driver_id: &DriverId) -> Result<(), AppError>;
+    async fn start_break(&self, driver_id: &DriverId, minutes: Option<u32>) -> Result<DriverResponse, AppError>;
+    async fn end_break(&self, driver_id: &DriverId) -> Result<DriverResponse, AppError>;
+    async fn update_equipment(&self, driver_id: &DriverId, equipment: DriverEquipment) -> Result<DriverResponse, AppError>;
+}
+
+#[derive(Debug, Clone)]
+pub struct DriverConfig {
+    pub max_break_minutes: u32,
+    pub unresponsive_penalty: f32,    // Reliability points lost per abandoned assignment
+    pub on_time_pickup_minutes: i64,  // Acceptance to pickup within this counts as on time
+    pub reliability_window_days: i64, // Dispatch history considered by the score
+}
+
+impl Default for DriverConfig {
+    fn default() -> Self {
+        Self {
+            max_break_minutes: 60,
+            unresponsive_penalty: 10.0,
+            on_time_pickup_minutes: 30,
+            reliability_window_days: 30,
+        }
+    }
 }
 
 pub struct DriverService {
     notification_service: Arc<dyn NotificationService>,
     cache_service: Arc<CacheService>,
+    config: DriverConfig,
 }
 
 impl DriverService {
     pub fn new(
         cache_service: Arc<CacheService>,
-        notification_service: Arc<dyn NotificationService>
+        notification_service: Arc<dyn NotificationService>,
+        config: DriverConfig,
     ) -> Self {
-        Self { cache_service, notification_service }
+        Self { cache_service, notification_service, config }
+    }
+
+    async fn load_driver(&self, driver_id: &DriverId) -> Result<Driver, AppError> {
+        self.cache_service.get_driver(driver_id).await?
+            .ok_or_else(|| AppError::driver_not_found(driver_id.as_str()))
+    }
+
+    // Dispatch history is best-effort: losing one outcome must not fail the job update
+    pub async fn record_outcome(&self, driver_id: &DriverId, kind: DispatchOutcomeKind, job_id: &JobId, minutes_to_pickup: Option<i64>) {
+        let outcome = DispatchOutcome {
+            kind,
+            job_id: job_id.clone(),
+            minutes_to_pickup,
+            at: Utc::now(),
+        };
+        if let Err(e) = self.cache_service.append_dispatch_outcome(driver_id, &outcome).await {
+            tracing::warn!("Failed to record {:?} for driver {}: {}", kind, driver_id, e);
+        }
+    }
+
+    // The driver went silent on `job_id` before pickup: release them from the job and
+    // rescore them now rather than at the next analytics pass. Returns the new score.
+    pub async fn record_unresponsive(&self, driver_id: &DriverId, job_id: &JobId) -> Result<f32, AppError> {
+        let mut driver = self.load_driver(driver_id).await?;
+        if driver.current_ride_id.as_ref() == Some(job_id) {
+            driver.current_ride_id = None;
+            driver.updated_at = Utc::now();
+            self.cache_service.cache_driver(&driver).await?;
+        }
+        self.record_outcome(driver_id, DispatchOutcomeKind::Unresponsive, job_id, None).await;
+
+        let reliability = self.refresh_reliability(driver_id, Utc::now()).await?;
+        tracing::info!("Driver {} reliability lowered to {} after abandoning job {}", driver_id, reliability.score, job_id);
+        Ok(reliability.score)
+    }
+
+    // Recompute the composite score from the driver's recent dispatch history, cache the
+    // breakdown and copy the score onto the driver record used by dispatch
+    pub async fn refresh_reliability(&self, driver_id: &DriverId, now: DateTime<Utc>) -> Result<DriverReliability, AppError> {
+        let since = now - chrono::Duration::days(self.config.reliability_window_days);
+        let outcomes: Vec<_> = self.cache_service.get_dispatch_outcomes(driver_id).await?
+            .into_iter()
+            .filter(|outcome| outcome.at >= since)
+            .collect();
+        let reliability = DriverReliability::from_outcomes(
+            &outcomes,
+            self.config.on_time_pickup_minutes,
+            self.config.unresponsive_penalty,
+            now,
+        );
+        self.cache_service.cache_driver_reliability(driver_id, &reliability).await?;
+
+        let mut driver = self.load_driver(driver_id).await?;
+        if driver.reliability_score != reliability.score {
+            driver.reliability_score = reliability.score;
+            driver.updated_at = now;
+            self.cache_service.cache_driver(&driver).await?;
+        }
+        Ok(reliability)
+    }
+
+    // Nearby drivers who can take work, with what dispatch needs to rank them
+    pub async fn find_dispatch_candidates(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        radius_km: f64,
+        limit: usize,
+        handling: &HandlingRequirements,
+    ) -> Result<Vec<DispatchCandidate>, AppError> {
+        let mut candidates = Vec::new();
+        for driver_id in self.cache_service.find_driver_ids_near(latitude, longitude, radius_km, limit).await? {
+            let Some(driver) = self.cache_service.get_driver(&driver_id).await? else {
+                continue;
+            };
+            if !driver.can_take_work() || !driver.equipment.covers(handling) {
+                continue;
+            }
+            let position = match self.cache_service.get_driver_location(&driver_id).await? {
+                Some(live) => Some((live.latitude, live.longitude)),
+                None => driver.current_location.as_ref().map(|location| (location.latitude, location.longitude)),
+            };
+            let Some(position) = position else {
+                continue;
+            };
+            candidates.push(DispatchCandidate {
+                driver_id,
+                distance_km: haversine_km(position, (latitude, longitude)),
+                reliability_score: driver.reliability_score,
+            });
+        }
+        Ok(candidates)
+    }
+
+    // Breaks left running past `break_ends_at` put the driver Offline; they have to
+    // come back online themselves. Returns how many drivers were moved.
+    pub async fn end_overdue_breaks(&self, now: DateTime<Utc>) -> Result<usize, AppError> {
+        let mut ended = 0;
+        for driver_id in self.cache_service.get_all_driver_ids().await? {
+            let Some(mut driver) = self.cache_service.get_driver(&driver_id).await? else {
+                continue;
+            };
+            let overdue = driver.is_on_break() && driver.break_ends_at.is_some_and(|ends_at| ends_at <= now);
+            if !overdue {
+                continue;
+            }
+
+            driver.status = DriverStatus::Offline;
+            driver.break_started_at = None;
+            driver.break_ends_at = None;
+            driver.updated_at = now;
+            self.cache_service.cache_driver(&driver).await?;
+            ended += 1;
+
+            let message = NotificationMessage::new(
+                "☕ Break over",
+                "Your break ran past its limit, so you've been set offline. Go online when you're ready.",
+            )
+            .with_data(json!({ "type": "break_overdue", "driver_id": driver.id }));
+            if let Err(e) = self.notification_service.send_to_driver(&driver.id, message).await {
+                tracing::warn!("Failed to notify driver {} of overdue break: {}", driver.id, e);
+            }
+        }
+        Ok(ended)
+    }
+
+    // Silently asks every driver app to re-fetch `section`; returns how many devices were reached
+    pub async fn push_config_update(&self, section: &str) -> Result<usize, AppError> {
+        let mut device_tokens = Vec::new();
+        for driver_id in self.cache_service.get_all_driver_ids().await? {
+            if let Some(token) = self.cache_service.get_driver(&driver_id).await?.and_then(|driver| driver.device_token) {
+                device_tokens.push(token);
+            }
+        }
+        if !device_tokens.is_empty() {
+            self.notification_service
+                .send_to_devices(&device_tokens, NotificationMessage::config_updated(section))
+                .await?;
+        }
+        Ok(device_tokens.len())
     }
     
     fn to_response(&self, driver: Driver) -> DriverResponse {
-        DriverResponse {
-            id: driver.id,
-            first_name: driver.first_name,
-            last_name: driver.last_name,
-            phone_number: driver.phone_number,
-            status: driver.status,
-            current_location: driver.current_location,
-            vehicle: driver.vehicle,
-            rating: driver.rating,
-            total_rides: driver.total_rides,
-            is_verified: driver.is_verified,
-            current_ride_id: driver.current_ride_id,
-        }
+        DriverResponse::from(driver)
     }
 }
 
@@ -65,7 +224,7 @@ impl DriverOperations for DriverService {
         tracing::info!("Registering driver for user: {}", registration.user_id);
         
         // Check if driver already exists for this user
-        if let Some(existing) = self.get_driver_by_user_id(&registration.user_id).await? {
+        if self.get_driver_by_user_id(&registration.user_id).await?.is_some() {
             return Err(AppError::validation_error("user_id", "Driver already exists for this user"));
         }
         
@@ -82,8 +241,9 @@ impl DriverOperations for DriverService {
         };
         
         // Create driver with our ID generator
-        let mut driver = Driver {
-            id: String::new(), // Will be set by with_generated_id
+        let driver = Driver {
+            id: DriverId::generate(),
+            tenant_id: current_tenant_id(),
             user_id: registration.user_id,
             first_name: registration.first_name,
             last_name: registration.last_name,
@@ -92,56 +252,74 @@ impl DriverOperations for DriverService {
             status: DriverStatus::Offline,
             current_location: None,
             vehicle,
+            equipment: registration.equipment,
             rating: 0.0,
             total_rides: 0,
+            reliability_score: MAX_RELIABILITY_SCORE,
             is_verified: false,
+            onboarding_state: OnboardingState::DocumentsSubmitted,
+            documents: registration.documents.into_iter().map(|upload| DriverDocument {
+                kind: upload.kind,
+                url: upload.url,
+                submitted_at: Utc::now(),
+                expires_at: upload.expires_at,
+                reminded_days_before: None,
+            }).collect(),
+            onboarding_history: Vec::new(),
             is_active: true,
+            suspended_reason: None,
+            ban: None,
             current_ride_id: None,
+            queued_job_id: None,
             device_token: None,
+            break_started_at: None,
+            break_ends_at: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
         
-        // Use our WithGeneratedId trait to set the ID
-        driver.set_generated_id(IdType::Driver);
-        
-        // Cache the driver - note: we need to implement driver caching separately
-        // from user caching since Driver and User are different models
-        // For now, we'll skip the caching and return the response
-        // TODO: Implement proper driver caching
+        self.cache_service.cache_driver(&driver).await?;
         
         tracing::info!("Driver registered successfully: {}", driver.id);
         
         Ok(self.to_response(driver))
     }
     
-    async fn get_driver(&self, driver_id: &str) -> Result<Option<DriverResponse>, AppError> {
-        // Validate ID format first
-        if !IdGenerator::validate_id(driver_id, Some(IdType::Driver)) {
-            tracing::warn!("Invalid driver ID format: {}", driver_id);
-            return Ok(None);
-        }
-        
+    async fn get_driver(&self, driver_id: &DriverId) -> Result<Option<DriverResponse>, AppError> {
         tracing::debug!("Getting driver: {}", driver_id);
         
-        // Try cache first - TODO: implement proper driver caching
-        // For now, return None since we can't convert User to Driver
-        
-        Ok(None)
+        let Some(driver) = self.cache_service.get_driver(driver_id).await? else {
+            return Ok(None);
+        };
+        Ok(Some(DriverResponse {
+            last_seen_at: self.cache_service.get_driver_last_seen(driver_id).await?,
+            ..self.to_response(driver)
+        }))
     }
 
-    async fn get_driver_by_user_id(&self, user_id: &str) -> Result<Option<DriverResponse>, AppError> {
-        // Implementation needed
-        Ok(None)
+    async fn get_driver_by_user_id(&self, user_id: &UserId) -> Result<Option<DriverResponse>, AppError> {
+        match self.cache_service.get_driver_id_by_user_id(user_id).await? {
+            Some(driver_id) => self.get_driver(&driver_id).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn get_driver_profile(&self, driver_id: &DriverId) -> Result<DriverResponse, AppError> {
+        let driver = self.load_driver(driver_id).await?;
+        // Scored by the analytics worker; new drivers may not have been reached yet
+        let reliability = match self.cache_service.get_driver_reliability(driver_id).await? {
+            Some(reliability) => reliability,
+            None => self.refresh_reliability(driver_id, Utc::now()).await?,
+        };
+        Ok(DriverResponse {
+            reliability: Some(reliability),
+            last_seen_at: self.cache_service.get_driver_last_seen(driver_id).await?,
+            ..self.to_response(driver)
+        })
     }
     
     // ... rest of the methods remain the same but with ID validation
     async fn update_driver_status(&self, update: DriverStatusUpdate) -> Result<DriverResponse, AppError> {
-        // Validate driver ID format
-        if !IdGenerator::validate_id(&update.driver_id, Some(IdType::Driver)) {
-            return Err(AppError::validation_error("driver_id", "Invalid driver ID format"));
-        }
-        
         tracing::info!("Updating driver status: {} to {:?}", update.driver_id, update.status);
         
         // TODO: implement proper driver retrieval from cache
@@ -150,11 +328,6 @@ impl DriverOperations for DriverService {
     }
     
     async fn update_driver_location(&self, update: DriverLocationUpdate) -> Result<DriverResponse, AppError> {
-        // Validate driver ID format
-        if !IdGenerator::validate_id(&update.driver_id, Some(IdType::Driver)) {
-            return Err(AppError::validation_error("driver_id", "Invalid driver ID format"));
-        }
-        
         tracing::debug!("Updating driver location: {}", update.driver_id);
         
         // TODO: implement proper driver location updates
@@ -162,19 +335,97 @@ impl DriverOperations for DriverService {
         return Err(AppError::NotFound("Driver location update not fully implemented".to_string()));
     }
     
-    async fn find_nearby_drivers(&self, _: f64, _: f64, _: f64, _: usize) -> Result<Vec<DriverResponse>, AppError> {
-        Ok(vec![])
+    async fn find_nearby_drivers(&self, latitude: f64, longitude: f64, radius_km: f64, limit: usize) -> Result<Vec<DriverResponse>, AppError> {
+        let mut drivers = Vec::new();
+        for driver_id in self.cache_service.find_driver_ids_near(latitude, longitude, radius_km, limit).await? {
+            match self.cache_service.get_driver(&driver_id).await? {
+                // Drivers on a break, suspended or still in review are never offered work
+                Some(driver) if driver.can_take_work() => drivers.push(self.to_response(driver)),
+                _ => {}
+            }
+        }
+        Ok(drivers)
     }
 
+    // Drivers with the app open on a live socket
     async fn get_online_drivers(&self) -> Result<Vec<DriverResponse>, AppError> {
-        Ok(vec![])
+        let mut drivers = Vec::new();
+        for driver_id in self.cache_service.get_online_driver_ids().await? {
+            if let Some(driver) = self.cache_service.get_driver(&driver_id).await? {
+                drivers.push(self.to_response(driver));
+            }
+        }
+        Ok(drivers)
     }
 
-    async fn get_driver_stats(&self, _: &str) -> Result<User, AppError> {
+    async fn get_driver_stats(&self, _: &DriverId) -> Result<User, AppError> {
         unimplemented!()
     }
 
-    async fn delete_driver(&self, _: &str) -> Result<(), AppError> {
+    async fn delete_driver(&self, _: &DriverId) -> Result<(), AppError> {
         unimplemented!()
     }
+
+    async fn start_break(&self, driver_id: &DriverId, minutes: Option<u32>) -> Result<DriverResponse, AppError> {
+        let mut driver = self.load_driver(driver_id).await?;
+        if driver.is_on_break() {
+            return Err(AppError::Conflict("Driver is already on a break".to_string()));
+        }
+
+        let max = self.config.max_break_minutes;
+        let minutes = minutes.unwrap_or(max);
+        if minutes == 0 || minutes > max {
+            return Err(AppError::validation_error(
+                "minutes",
+                format!("Break must be between 1 and {} minutes", max),
+            ));
+        }
+
+        // A break can wait until the package in the car has been delivered
+        for job_id in self.cache_service.get_driver_jobs(driver_id).await? {
+            let job = self.cache_service.load_job(&job_id).await?;
+            if job.is_some_and(|job| job.status.is_carrying_package()) {
+                return Err(AppError::Conflict(format!(
+                    "Driver cannot take a break while delivering job {}",
+                    job_id
+                )));
+            }
+        }
+
+        let now = Utc::now();
+        driver.status = DriverStatus::OnBreak;
+        driver.break_started_at = Some(now);
+        driver.break_ends_at = Some(now + chrono::Duration::minutes(minutes as i64));
+        driver.updated_at = now;
+        self.cache_service.cache_driver(&driver).await?;
+
+        tracing::info!("Driver {} started a {} minute break", driver_id, minutes);
+        Ok(self.to_response(driver))
+    }
+
+    async fn end_break(&self, driver_id: &DriverId) -> Result<DriverResponse, AppError> {
+        let mut driver = self.load_driver(driver_id).await?;
+        if !driver.is_on_break() {
+            return Err(AppError::Conflict("Driver is not on a break".to_string()));
+        }
+
+        driver.status = DriverStatus::Online;
+        driver.break_started_at = None;
+        driver.break_ends_at = None;
+        driver.updated_at = Utc::now();
+        self.cache_service.cache_driver(&driver).await?;
+
+        tracing::info!("Driver {} ended their break", driver_id);
+        Ok(self.to_response(driver))
+    }
+
+    async fn update_equipment(&self, driver_id: &DriverId, equipment: DriverEquipment) -> Result<DriverResponse, AppError> {
+        let mut driver = self.load_driver(driver_id).await?;
+        driver.equipment = equipment;
+        driver.updated_at = Utc::now();
+        self.cache_service.cache_driver(&driver).await?;
+
+        tracing::info!("Driver {} updated their equipment: {:?}", driver_id, driver.equipment);
+        Ok(self.to_response(driver))
+    }
 }
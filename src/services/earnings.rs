@@ -0,0 +1,175 @@
+// src/services/earnings.rs
+// What a driver takes home from a job: the fare less our commission, plus the tip and a
+// share of any surge at the pickup. Shown on offers so drivers can judge a job up front.
+// Commission rates are the tenant's, administered through `/admin/commissions`.
+use chrono::Utc;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        commission::{CommissionConfig, UpdateCommissionsRequest},
+        driver::VehicleType,
+        job::{DriverEarnings, Job, Pricing},
+        money::Money,
+    },
+    services::cache_service::CacheService,
+    utils::geohash,
+};
+
+#[derive(Debug, Clone)]
+pub struct EarningsConfig {
+    pub surge_share: f64,          // Fraction of the surge uplift passed on to the driver
+    pub surge_zone_precision: usize,
+}
+
+impl Default for EarningsConfig {
+    fn default() -> Self {
+        Self {
+            surge_share: 0.5,
+            surge_zone_precision: 5,
+        }
+    }
+}
+
+pub struct EarningsCalculator {
+    cache_service: Arc<CacheService>,
+    config: EarningsConfig,
+}
+
+impl EarningsCalculator {
+    pub fn new(cache_service: Arc<CacheService>, config: EarningsConfig) -> Self {
+        Self {
+            cache_service,
+            config,
+        }
+    }
+
+    /// The tenant's commission rates; the defaults until an admin sets them
+    pub async fn commissions(&self) -> Result<CommissionConfig, AppError> {
+        Ok(self.cache_service.get_commission_config().await?.unwrap_or_default())
+    }
+
+    pub async fn update_commissions(&self, request: UpdateCommissionsRequest) -> Result<CommissionConfig, AppError> {
+        let valid_rate = |rate: f64| (0.0..=1.0).contains(&rate);
+        if !valid_rate(request.default_rate) {
+            return Err(AppError::validation_error("default_rate", "Must be between 0 and 1"));
+        }
+        let mut rules = request.rules;
+        for (index, rule) in rules.iter_mut().enumerate() {
+            if !valid_rate(rule.rate) {
+                return Err(AppError::validation_error(format!("rules[{}].rate", index), "Must be between 0 and 1"));
+            }
+            if let Some(zone) = rule.zone.as_mut() {
+                *zone = zone.trim().to_string();
+                if zone.is_empty() {
+                    return Err(AppError::validation_error(format!("rules[{}].zone", index), "Must not be empty"));
+                }
+            }
+        }
+
+        let config = CommissionConfig {
+            default_rate: request.default_rate,
+            rules,
+            updated_at: Some(Utc::now()),
+        };
+        self.cache_service.cache_commission_config(&config).await?;
+
+        tracing::info!("Updated commission rates ({} rules)", config.rules.len());
+        Ok(config)
+    }
+
+    pub fn calculate(&self, pricing: &Pricing, commission_rate: f64, surge_multiplier: f64) -> DriverEarnings {
+        let fare = pricing.driver_fare();
+        let commission = fare.times(commission_rate);
+        let surge_bonus = fare.times((surge_multiplier - 1.0).max(0.0) * self.config.surge_share);
+
+        DriverEarnings {
+            fare,
+            commission_rate,
+            commission,
+            tip: pricing.tip,
+            surge_bonus,
+            escalation_bonus: Money::zero(pricing.currency),
+            total: fare - commission + pricing.tip + surge_bonus,
+            currency: pricing.currency,
+        }
+    }
+
+    /// Earnings for `job` driven in `vehicle_type`, at the surge currently set for its pickup
+    /// zone, or failing that its region, plus any escalation bonus
+    pub async fn preview(&self, job: &Job, vehicle_type: &VehicleType) -> DriverEarnings {
+        let commission_rate = match self.commissions().await {
+            Ok(commissions) => commissions.rate_for(Some(vehicle_type), &job.pickup_location.region, job.effective_priority()),
+            Err(e) => {
+                tracing::warn!("Failed to load commission rates for job {}: {}", job.id, e);
+                CommissionConfig::default().default_rate
+            }
+        };
+        self.at_rate(job, commission_rate).await
+    }
+
+    /// Earnings for `job` at a commission rate already settled on, such as the one fixed
+    /// when it was assigned
+    pub async fn at_rate(&self, job: &Job, commission_rate: f64) -> DriverEarnings {
+        let surge_multiplier = match self.cache_service.get_surge_multipliers().await {
+            Ok(multipliers) => {
+                let zone = geohash::encode(
+                    job.pickup_location.latitude,
+                    job.pickup_location.longitude,
+                    self.config.surge_zone_precision,
+                );
+                multipliers.get(&zone)
+                    .or_else(|| multipliers.get(&job.pickup_location.region))
+                    .copied()
+                    .unwrap_or(1.0)
+            }
+            Err(e) => {
+                // An offer without the surge bonus beats no offer
+                tracing::warn!("Failed to load surge multipliers for job {}: {}", job.id, e);
+                1.0
+            }
+        };
+        let mut earnings = self.calculate(&job.pricing, commission_rate, surge_multiplier);
+        if let Some(escalation) = &job.escalation {
+            earnings.escalation_bonus = escalation.payout_bonus;
+            earnings.total += escalation.payout_bonus;
+        }
+        earnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::{ids::UserId, money::Money},
+    };
+
+    #[tokio::test]
+    async fn test_driver_cut_includes_tip_and_surge_share() {
+        let app = TestApp::new();
+        let calculator = EarningsCalculator::new(app.state.cache_service.clone(), EarningsConfig::default());
+
+        let mut job = Faker::seeded(5).job(&UserId::generate());
+        let cedis = |amount: f64| Money::from_major(amount, job.pricing.currency);
+        job.pricing.service_fee = cedis(10.0);
+        job.pricing.tax = cedis(5.0);
+        job.pricing.tip = cedis(20.0);
+        job.pricing.total = cedis(135.0); // A 100.0 fare
+
+        let earnings = calculator.calculate(&job.pricing, 0.15, 1.0);
+        assert_eq!(earnings.fare, cedis(100.0));
+        assert_eq!(earnings.commission, cedis(15.0));
+        assert!(earnings.surge_bonus.is_zero());
+        assert_eq!(earnings.total, cedis(105.0));
+
+        // Half of a 1.4x surge on the fare
+        let earnings = calculator.calculate(&job.pricing, 0.15, 1.4);
+        assert_eq!(earnings.surge_bonus, cedis(20.0));
+        assert_eq!(earnings.total, cedis(125.0));
+    }
+}
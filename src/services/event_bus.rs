@@ -0,0 +1,238 @@
+// src/services/event_bus.rs
+// Publish/subscribe for `DomainEvent`s. Publishing never fails the caller: the change the
+// event reports is already saved, so a subscriber that errors is logged and the rest
+// still run.
+//
+// `InProcessEventBus` hands each event to this instance's subscribers before `publish`
+// returns. `RedisEventBus` appends events to a Redis stream that each subscriber reads as
+// its own consumer group (see `consumers`), so every subscriber handles each event once,
+// on whichever instance reads it; until it is attached to Redis it behaves like the
+// in-process bus.
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::{Arc, OnceLock, RwLock};
+use tracing;
+use uuid::Uuid;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{consumer::ConsumerStatus, domain_event::{DomainEvent, EventEnvelope}},
+    services::{
+        cache_service::CacheService,
+        consumers::{ConsumerConfig, Consumers, EventLog, RedisStreamConfig, RedisStreamLog},
+        region::current_region_id,
+        tenant_service::current_tenant_id,
+    },
+};
+
+#[async_trait]
+pub trait EventSubscriber: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// Called for every event; ignore the ones that don't concern you
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<(), AppError>;
+}
+
+#[async_trait]
+pub trait EventBus: Send + Sync {
+    /// Announce `event` in the current tenant and region
+    async fn publish(&self, event: DomainEvent);
+    fn subscribe(&self, subscriber: Arc<dyn EventSubscriber>);
+}
+
+fn envelope(event: DomainEvent) -> EventEnvelope {
+    EventEnvelope {
+        event_id: Uuid::new_v4().to_string(),
+        tenant_id: current_tenant_id(),
+        region_id: current_region_id(),
+        occurred_at: Utc::now(),
+        event,
+    }
+}
+
+#[derive(Default)]
+pub struct InProcessEventBus {
+    subscribers: RwLock<Vec<Arc<dyn EventSubscriber>>>,
+}
+
+impl InProcessEventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribers(&self) -> Vec<Arc<dyn EventSubscriber>> {
+        self.subscribers.read().unwrap().clone()
+    }
+
+    /// Run every subscriber on `envelope`, in the order they subscribed
+    pub async fn dispatch(&self, envelope: &EventEnvelope) {
+        for subscriber in self.subscribers() {
+            if let Err(e) = subscriber.handle(envelope).await {
+                tracing::warn!("Subscriber {} failed on {} {}: {}", subscriber.name(), envelope.event.kind(), envelope.event_id, e);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl EventBus for InProcessEventBus {
+    async fn publish(&self, event: DomainEvent) {
+        self.dispatch(&envelope(event)).await;
+    }
+
+    fn subscribe(&self, subscriber: Arc<dyn EventSubscriber>) {
+        self.subscribers.write().unwrap().push(subscriber);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RedisEventBusConfig {
+    pub stream: RedisStreamConfig,
+    pub consumer: ConsumerConfig,
+}
+
+impl RedisEventBusConfig {
+    pub fn from_env() -> Self {
+        Self {
+            consumer: ConsumerConfig::from_env(),
+            ..Default::default()
+        }
+    }
+}
+
+pub struct RedisEventBus {
+    cache_service: Arc<CacheService>,
+    consumers: OnceLock<Consumers>, // Attached by the server; tests and tools run without it
+    local: InProcessEventBus,
+    config: RedisEventBusConfig,
+}
+
+impl RedisEventBus {
+    pub fn new(cache_service: Arc<CacheService>, config: RedisEventBusConfig) -> Self {
+        Self {
+            cache_service,
+            consumers: OnceLock::new(),
+            local: InProcessEventBus::new(),
+            config,
+        }
+    }
+
+    pub fn attach_redis(&self, client: redis::Client) {
+        self.attach_log(Arc::new(RedisStreamLog::new(client, self.config.stream.clone())));
+    }
+
+    pub fn attach_log(&self, log: Arc<dyn EventLog>) {
+        let consumers = Consumers::new(log, self.cache_service.clone(), self.config.consumer.clone());
+        if self.consumers.set(consumers).is_err() {
+            tracing::warn!("Event bus already attached to a log");
+        }
+    }
+
+    fn attached(&self) -> Result<&Consumers, AppError> {
+        self.consumers.get().ok_or_else(|| AppError::service_unavailable("event log"))
+    }
+
+    /// Start a consumer for each subscriber; each keeps going for the life of the process
+    pub async fn run(self: Arc<Self>) {
+        let Some(consumers) = self.consumers.get() else {
+            return;
+        };
+        for subscriber in self.local.subscribers() {
+            tokio::spawn(Arc::new(consumers.consumer(subscriber)).run());
+        }
+    }
+
+    pub async fn consumer_status(&self) -> Result<Vec<ConsumerStatus>, AppError> {
+        let consumers = self.attached()?;
+        let mut statuses = Vec::new();
+        for subscriber in self.local.subscribers() {
+            statuses.push(consumers.status(subscriber.name()).await?);
+        }
+        Ok(statuses)
+    }
+
+    pub async fn replay(&self, group: &str, from: &str) -> Result<ConsumerStatus, AppError> {
+        let consumers = self.attached()?;
+        if !self.local.subscribers().iter().any(|subscriber| subscriber.name() == group) {
+            return Err(AppError::not_found(format!("Consumer group {}", group)));
+        }
+        consumers.replay(group, from).await?;
+        consumers.status(group).await
+    }
+}
+
+#[async_trait]
+impl EventBus for RedisEventBus {
+    async fn publish(&self, event: DomainEvent) {
+        let envelope = envelope(event);
+        let Some(consumers) = self.consumers.get() else {
+            self.local.dispatch(&envelope).await;
+            return;
+        };
+        let appended = match serde_json::to_string(&envelope) {
+            Ok(payload) => consumers.log().append(&payload).await.map(|_| ()),
+            Err(e) => Err(e.into()),
+        };
+        // Better handled here than not at all
+        if let Err(e) = appended {
+            tracing::warn!("Failed to log {} {}, handling it here: {}", envelope.event.kind(), envelope.event_id, e);
+            self.local.dispatch(&envelope).await;
+        }
+    }
+
+    fn subscribe(&self, subscriber: Arc<dyn EventSubscriber>) {
+        self.local.subscribe(subscriber);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use crate::{
+        models::ids::{DriverId, JobId},
+        services::{region::with_region, tenant_service::with_tenant},
+    };
+
+    struct Recorder {
+        seen: Mutex<Vec<EventEnvelope>>,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl EventSubscriber for Recorder {
+        fn name(&self) -> &'static str {
+            "recorder"
+        }
+
+        async fn handle(&self, envelope: &EventEnvelope) -> Result<(), AppError> {
+            self.seen.lock().unwrap().push(envelope.clone());
+            if self.fail {
+                return Err(AppError::internal_error("subscriber broke"));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_every_subscriber_hears_events_in_the_publishers_scope() {
+        let bus = InProcessEventBus::new();
+        let failing = Arc::new(Recorder { seen: Mutex::new(Vec::new()), fail: true });
+        let recorder = Arc::new(Recorder { seen: Mutex::new(Vec::new()), fail: false });
+        bus.subscribe(failing.clone());
+        bus.subscribe(recorder.clone());
+
+        let event = DomainEvent::JobAssigned { job_id: JobId::generate(), driver_id: DriverId::generate(), offered: true };
+        with_region("ng".to_string(), with_tenant("kwik".to_string(), bus.publish(event.clone()))).await;
+
+        let seen = recorder.seen.lock().unwrap().clone();
+        assert_eq!(seen.len(), 1);
+        assert_eq!((seen[0].tenant_id.as_str(), seen[0].region_id.as_str()), ("kwik", "ng"));
+        assert_eq!(seen[0].event, event);
+        assert_eq!(failing.seen.lock().unwrap().len(), 1);
+
+        // The envelope carries its scope and type on the wire
+        let wire = serde_json::to_value(&seen[0]).unwrap();
+        assert_eq!(wire["type"], "job_assigned");
+        assert_eq!(serde_json::from_value::<EventEnvelope>(wire).unwrap(), seen[0]);
+    }
+}
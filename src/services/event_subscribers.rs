@@ -0,0 +1,263 @@
+// src/services/event_subscribers.rs
+// Reactions to domain events that used to be called from the middle of `JobService`.
+// Each loads what it needs from the cache, so it works the same on whichever instance
+// the event is handled. One-off pushes nothing else reacts to (expiry, reassignment, a
+// changed dropoff, a queued job starting) are still sent by `JobService` itself.
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{demand::DemandPoint, domain_event::{DomainEvent, EventEnvelope}, driver::{DispatchOutcomeKind, Driver}, job::Job},
+    services::{
+        cache_service::CacheService,
+        driver_service::DriverService,
+        earnings::EarningsCalculator,
+        event_bus::EventSubscriber,
+        messaging_service::{JobNotificationDetails, NotificationMessage, NotificationService},
+        route_service::RouteService,
+    },
+};
+
+// Who's on the other end and how far off the driver is, for the pushes about `job`.
+// Best-effort: a lookup that fails leaves its detail out rather than holding up the push.
+async fn notification_details(cache_service: &CacheService, route_service: &RouteService, job: &Job, driver: &Driver) -> JobNotificationDetails {
+    let customer_name = match cache_service.load_user(&job.customer_id).await {
+        Ok(customer) => customer.and_then(|customer| customer.name_for_drivers()),
+        Err(e) => {
+            tracing::warn!("Failed to load customer {} for job {}: {}", job.customer_id, job.id, e);
+            None
+        }
+    };
+    // Next stop: the pickup until the package is collected, then the dropoff
+    let destination = if job.pickup_time.is_some() { &job.dropoff_location } else { &job.pickup_location };
+    let eta_minutes = match route_service.eta_minutes(&driver.id, destination).await {
+        Ok(eta) => eta,
+        Err(e) => {
+            tracing::warn!("Failed to estimate arrival of driver {} for job {}: {}", driver.id, job.id, e);
+            None
+        }
+    };
+    JobNotificationDetails {
+        customer_name,
+        driver_name: Some(driver.first_name.clone()),
+        eta_minutes,
+        navigation: None,
+    }
+}
+
+// Pickups feed the demand heatmap and forecast
+pub struct DemandAnalytics {
+    cache_service: Arc<CacheService>,
+}
+
+impl DemandAnalytics {
+    pub fn new(cache_service: Arc<CacheService>) -> Self {
+        Self { cache_service }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for DemandAnalytics {
+    fn name(&self) -> &'static str {
+        "demand_analytics"
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<(), AppError> {
+        let DomainEvent::JobCreated { job_id, .. } = &envelope.event else {
+            return Ok(());
+        };
+        let job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::job_not_found(job_id.as_str()))?;
+        self.cache_service.record_pickup(&DemandPoint::from_job(&job)).await
+    }
+}
+
+// Tells the customer their delivery is done
+pub struct DeliveryNotifications {
+    cache_service: Arc<CacheService>,
+    notification_service: Arc<dyn NotificationService>,
+}
+
+impl DeliveryNotifications {
+    pub fn new(cache_service: Arc<CacheService>, notification_service: Arc<dyn NotificationService>) -> Self {
+        Self { cache_service, notification_service }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for DeliveryNotifications {
+    fn name(&self) -> &'static str {
+        "delivery_notifications"
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<(), AppError> {
+        let DomainEvent::JobCompleted { job_id, .. } = &envelope.event else {
+            return Ok(());
+        };
+        let job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::job_not_found(job_id.as_str()))?;
+        self.notification_service.notify_delivery_completed(&job).await
+    }
+}
+
+// Tells the customer their money is on its way back
+pub struct RefundNotifications {
+    cache_service: Arc<CacheService>,
+    notification_service: Arc<dyn NotificationService>,
+}
+
+impl RefundNotifications {
+    pub fn new(cache_service: Arc<CacheService>, notification_service: Arc<dyn NotificationService>) -> Self {
+        Self { cache_service, notification_service }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for RefundNotifications {
+    fn name(&self) -> &'static str {
+        "refund_notifications"
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<(), AppError> {
+        let DomainEvent::PaymentRefunded { job_id, customer_id, .. } = &envelope.event else {
+            return Ok(());
+        };
+        let job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::job_not_found(job_id.as_str()))?;
+        self.notification_service.send_to_user(customer_id, NotificationMessage::payment_refunded(&job)).await
+    }
+}
+
+// Feeds each driver's reliability score from the jobs they're given, collect and drop
+pub struct DriverOutcomes {
+    cache_service: Arc<CacheService>,
+    driver_service: Arc<DriverService>,
+}
+
+impl DriverOutcomes {
+    pub fn new(cache_service: Arc<CacheService>, driver_service: Arc<DriverService>) -> Self {
+        Self { cache_service, driver_service }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for DriverOutcomes {
+    fn name(&self) -> &'static str {
+        "driver_outcomes"
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<(), AppError> {
+        match &envelope.event {
+            DomainEvent::JobAssigned { job_id, driver_id, offered } => {
+                if !offered {
+                    self.driver_service.record_outcome(driver_id, DispatchOutcomeKind::Offered, job_id, None).await;
+                }
+                self.driver_service.record_outcome(driver_id, DispatchOutcomeKind::Accepted, job_id, None).await;
+            }
+            DomainEvent::JobPickedUp { job_id, driver_id: Some(driver_id) } => {
+                let job = self.cache_service.load_job(job_id).await?
+                    .ok_or_else(|| AppError::job_not_found(job_id.as_str()))?;
+                let minutes_to_pickup = job.accepted_at
+                    .zip(job.pickup_time)
+                    .map(|(accepted, picked_up)| (picked_up - accepted).num_minutes());
+                self.driver_service.record_outcome(driver_id, DispatchOutcomeKind::PickedUp, job_id, minutes_to_pickup).await;
+            }
+            DomainEvent::JobCancelled { job_id, driver_id: Some(driver_id), by_driver: true, .. } => {
+                self.driver_service.record_outcome(driver_id, DispatchOutcomeKind::Cancelled, job_id, None).await;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+// Sends the driver the job they've been given, with their cut and directions to the pickup
+pub struct AssignmentNotifications {
+    cache_service: Arc<CacheService>,
+    route_service: Arc<RouteService>,
+    earnings: Arc<EarningsCalculator>,
+    notification_service: Arc<dyn NotificationService>,
+}
+
+impl AssignmentNotifications {
+    pub fn new(
+        cache_service: Arc<CacheService>,
+        route_service: Arc<RouteService>,
+        earnings: Arc<EarningsCalculator>,
+        notification_service: Arc<dyn NotificationService>,
+    ) -> Self {
+        Self { cache_service, route_service, earnings, notification_service }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for AssignmentNotifications {
+    fn name(&self) -> &'static str {
+        "assignment_notifications"
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<(), AppError> {
+        let DomainEvent::JobAssigned { job_id, driver_id, .. } = &envelope.event else {
+            return Ok(());
+        };
+        let job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::job_not_found(job_id.as_str()))?;
+        let driver = self.cache_service.get_driver(driver_id).await?
+            .ok_or_else(|| AppError::driver_not_found(driver_id.as_str()))?;
+        // At the rate stored on assignment, which is what they're paid at
+        let earnings = match job.commission_rate {
+            Some(commission_rate) => self.earnings.at_rate(&job, commission_rate).await,
+            None => self.earnings.preview(&job, &driver.vehicle.vehicle_type).await,
+        };
+        let mut details = notification_details(&self.cache_service, &self.route_service, &job, &driver).await;
+        details.navigation = match self.route_service.navigation(&job).await {
+            Ok(plan) => Some(plan),
+            Err(e) => {
+                tracing::warn!("Failed to plan navigation for job {}: {}", job_id, e);
+                None
+            }
+        };
+        self.notification_service.notify_driver_assigned(&job, &driver, &earnings, &details).await
+    }
+}
+
+// Tells the customer their package is on its way
+pub struct PickupNotifications {
+    cache_service: Arc<CacheService>,
+    route_service: Arc<RouteService>,
+    notification_service: Arc<dyn NotificationService>,
+}
+
+impl PickupNotifications {
+    pub fn new(cache_service: Arc<CacheService>, route_service: Arc<RouteService>, notification_service: Arc<dyn NotificationService>) -> Self {
+        Self { cache_service, route_service, notification_service }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for PickupNotifications {
+    fn name(&self) -> &'static str {
+        "pickup_notifications"
+    }
+
+    async fn handle(&self, envelope: &EventEnvelope) -> Result<(), AppError> {
+        let DomainEvent::JobPickedUp { job_id, driver_id } = &envelope.event else {
+            return Ok(());
+        };
+        let job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::job_not_found(job_id.as_str()))?;
+        let driver = match driver_id {
+            Some(driver_id) => self.cache_service.get_driver(driver_id).await.unwrap_or_else(|e| {
+                tracing::warn!("Failed to load driver {} for job {}: {}", driver_id, job_id, e);
+                None
+            }),
+            None => None,
+        };
+        let details = match &driver {
+            Some(driver) => notification_details(&self.cache_service, &self.route_service, &job, driver).await,
+            None => JobNotificationDetails::default(),
+        };
+        self.notification_service.notify_package_picked_up(&job, &details).await
+    }
+}
@@ -0,0 +1,66 @@
+// src/services/exchange_rates.rs
+// The tenant's exchange rates, published by admins through `/admin/exchange-rates`.
+// Pricing doesn't use them, as every currency's fares and surcharges are set in that
+// currency; nothing converts silently when a rate is missing.
+use chrono::Utc;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        exchange_rate::{ExchangeRates, UpdateExchangeRatesRequest},
+        money::{Currency, Money},
+    },
+    services::cache_service::CacheService,
+};
+
+pub struct ExchangeRateService {
+    cache_service: Arc<CacheService>,
+}
+
+impl ExchangeRateService {
+    pub fn new(cache_service: Arc<CacheService>) -> Self {
+        Self { cache_service }
+    }
+
+    /// Published rates; none until an admin sets them
+    pub async fn rates(&self) -> Result<ExchangeRates, AppError> {
+        Ok(self.cache_service.get_exchange_rates().await?.unwrap_or_default())
+    }
+
+    pub async fn update_rates(&self, request: UpdateExchangeRatesRequest) -> Result<ExchangeRates, AppError> {
+        for (currency, rate) in &request.rates {
+            let field = format!("rates.{}", currency);
+            if *currency == request.base {
+                return Err(AppError::validation_error(field, "The base currency is always 1"));
+            }
+            if !rate.is_finite() || *rate <= 0.0 {
+                return Err(AppError::validation_error(field, "Must be a positive number"));
+            }
+        }
+
+        let rates = ExchangeRates {
+            base: request.base,
+            rates: request.rates,
+            updated_at: Some(Utc::now()),
+        };
+        self.cache_service.cache_exchange_rates(&rates).await?;
+
+        tracing::info!("Updated exchange rates against {} ({} currencies)", rates.base, rates.rates.len());
+        Ok(rates)
+    }
+
+    /// Units of `to` per unit of `from`
+    pub async fn rate(&self, from: Currency, to: Currency) -> Result<f64, AppError> {
+        self.rates().await?.rate(from, to).ok_or_else(|| missing_rate(from, to))
+    }
+
+    pub async fn convert(&self, amount: Money, to: Currency) -> Result<Money, AppError> {
+        self.rates().await?.convert(amount, to).ok_or_else(|| missing_rate(amount.currency(), to))
+    }
+}
+
+fn missing_rate(from: Currency, to: Currency) -> AppError {
+    AppError::validation_error("currency", format!("No exchange rate from {} to {} has been published", from, to))
+}
@@ -0,0 +1,264 @@
+// src/services/export_service.rs
+// Streams admin reports as CSV, one chunk per day (or per batch of drivers),
+// so large ranges never sit in memory as a whole.
+//
+// The response body is polled after the tenant middleware has returned, so each
+// stream captures the request's tenant up front and re-enters it for every load.
+use axum::body::Bytes;
+use chrono::NaiveDate;
+use futures::stream::{self, Stream, StreamExt};
+use serde::Serialize;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        admin::{DriverExportRow, EarningsExportRow, JobExportRow},
+        ids::DriverId,
+        job::Job,
+    },
+    services::{cache_service::CacheService, region::{current_region_id, with_region}, tenant_service::{current_tenant_id, with_tenant}},
+};
+
+pub type ExportStream = Pin<Box<dyn Stream<Item = Result<Bytes, AppError>> + Send>>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn parse(format: Option<&str>) -> Result<Self, AppError> {
+        match format.unwrap_or("csv").to_ascii_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            other => Err(AppError::InvalidFieldValue {
+                field: "format".to_string(),
+                value: other.to_string(),
+                reason: "Supported formats: csv".to_string(),
+            }),
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportConfig {
+    pub max_range_days: i64,
+    pub driver_chunk_size: usize,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            max_range_days: 366,
+            driver_chunk_size: 200,
+        }
+    }
+}
+
+pub struct ExportService {
+    cache_service: Arc<CacheService>,
+    config: ExportConfig,
+}
+
+// Position of a day-by-day export
+struct DayCursor {
+    next_day: Option<NaiveDate>,
+    header_written: bool,
+}
+
+impl ExportService {
+    pub fn new(cache_service: Arc<CacheService>, config: ExportConfig) -> Self {
+        Self {
+            cache_service,
+            config,
+        }
+    }
+
+    pub fn export_jobs(&self, from: NaiveDate, to: NaiveDate) -> Result<ExportStream, AppError> {
+        self.validate_range(from, to)?;
+        Ok(self.stream_jobs_by_day(from, to, |job| Some(JobExportRow::from(job))))
+    }
+
+    pub async fn export_earnings(&self, from: NaiveDate, to: NaiveDate) -> Result<ExportStream, AppError> {
+        self.validate_range(from, to)?;
+        let commissions = self.cache_service.get_commission_config().await?.unwrap_or_default();
+        Ok(self.stream_jobs_by_day(from, to, move |job| EarningsExportRow::from_job(job, &commissions)))
+    }
+
+    // Drivers registered between `from` and `to`
+    pub async fn export_drivers(&self, from: NaiveDate, to: NaiveDate) -> Result<ExportStream, AppError> {
+        self.validate_range(from, to)?;
+
+        let mut driver_ids = self.cache_service.get_all_driver_ids().await?;
+        driver_ids.sort();
+        let batches: Vec<Vec<DriverId>> = driver_ids
+            .chunks(self.config.driver_chunk_size)
+            .map(|chunk| chunk.to_vec())
+            .collect();
+
+        let cache_service = self.cache_service.clone();
+        let tenant_id = current_tenant_id();
+        let region_id = current_region_id();
+        let chunks = stream::iter(batches)
+            .then(move |batch| {
+                let cache_service = cache_service.clone();
+                with_region(region_id.clone(), with_tenant(tenant_id.clone(), async move {
+                    let mut rows = Vec::with_capacity(batch.len());
+                    for driver_id in batch {
+                        if let Some(driver) = cache_service.get_driver(&driver_id).await? {
+                            let registered = driver.created_at.date_naive();
+                            if registered >= from && registered <= to {
+                                rows.push(DriverExportRow::from(driver));
+                            }
+                        }
+                    }
+                    Ok::<_, AppError>(rows)
+                }))
+            })
+            .scan(false, |header_written, rows| {
+                let chunk = rows.and_then(|rows| {
+                    let chunk = write_csv_chunk(&rows, !*header_written)?;
+                    *header_written |= !rows.is_empty();
+                    Ok(chunk)
+                });
+                futures::future::ready(Some(chunk))
+            });
+
+        Ok(Box::pin(chunks))
+    }
+
+    fn validate_range(&self, from: NaiveDate, to: NaiveDate) -> Result<(), AppError> {
+        if to < from {
+            return Err(AppError::validation_error("to", "Must not be before `from`"));
+        }
+        if (to - from).num_days() >= self.config.max_range_days {
+            return Err(AppError::validation_error(
+                "to",
+                format!("Range must be at most {} days", self.config.max_range_days),
+            ));
+        }
+        Ok(())
+    }
+
+    fn stream_jobs_by_day<T, F>(&self, from: NaiveDate, to: NaiveDate, to_row: F) -> ExportStream
+    where
+        T: Serialize + Send + 'static,
+        F: Fn(Job) -> Option<T> + Send + Sync + 'static,
+    {
+        let cache_service = self.cache_service.clone();
+        let tenant_id = current_tenant_id();
+        let region_id = current_region_id();
+        let to_row = Arc::new(to_row);
+        let cursor = DayCursor {
+            next_day: Some(from),
+            header_written: false,
+        };
+
+        Box::pin(stream::unfold(cursor, move |mut cursor| {
+            let cache_service = cache_service.clone();
+            let to_row = to_row.clone();
+            let tenant_id = tenant_id.clone();
+            let region_id = region_id.clone();
+            async move {
+                // Skip empty days so every chunk carries data
+                loop {
+                    let day = cursor.next_day?;
+                    cursor.next_day = day.succ_opt().filter(|next| *next <= to);
+
+                    let jobs = match with_region(region_id.clone(), with_tenant(tenant_id.clone(), load_jobs_for_day(&cache_service, &day))).await {
+                        Ok(jobs) => jobs,
+                        Err(e) => {
+                            cursor.next_day = None;
+                            return Some((Err(e), cursor));
+                        }
+                    };
+                    let rows: Vec<T> = jobs.into_iter().filter_map(|job| to_row(job)).collect();
+                    if rows.is_empty() {
+                        continue;
+                    }
+
+                    let chunk = write_csv_chunk(&rows, !cursor.header_written);
+                    cursor.header_written = true;
+                    return Some((chunk, cursor));
+                }
+            }
+        }))
+    }
+}
+
+async fn load_jobs_for_day(cache_service: &CacheService, day: &NaiveDate) -> Result<Vec<Job>, AppError> {
+    let mut jobs = Vec::new();
+    for job_id in cache_service.get_jobs_for_day(day).await? {
+        // Jobs evicted from the cache since creation are skipped
+        if let Some(job) = cache_service.load_job(&job_id).await? {
+            jobs.push(job);
+        }
+    }
+    jobs.sort_by_key(|job| job.created_at);
+    Ok(jobs)
+}
+
+/// Serialize one chunk of rows; the header row is only written for the first chunk
+pub fn write_csv_chunk<T: Serialize>(rows: &[T], with_header: bool) -> Result<Bytes, AppError> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(with_header)
+        .from_writer(Vec::new());
+    for row in rows {
+        writer
+            .serialize(row)
+            .map_err(|e| AppError::internal_error(format!("CSV serialization failed: {}", e)))?;
+    }
+    let buffer = writer
+        .into_inner()
+        .map_err(|e| AppError::internal_error(format!("CSV flush failed: {}", e)))?;
+    Ok(Bytes::from(buffer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Row {
+        id: &'static str,
+        total: f64,
+        note: Option<&'static str>,
+    }
+
+    #[test]
+    fn test_write_csv_chunk_header_only_once() {
+        let rows = [Row { id: "job_a", total: 25.5, note: None }];
+        let first = write_csv_chunk(&rows, true).unwrap();
+        let next = write_csv_chunk(&rows, false).unwrap();
+
+        assert_eq!(first, "id,total,note\njob_a,25.5,\n");
+        assert_eq!(next, "job_a,25.5,\n");
+    }
+
+    #[test]
+    fn test_write_csv_chunk_quotes_commas() {
+        let rows = [Row { id: "job_b", total: 10.0, note: Some("Gate 2, blue door") }];
+        let chunk = write_csv_chunk(&rows, false).unwrap();
+        assert_eq!(chunk, "job_b,10.0,\"Gate 2, blue door\"\n");
+    }
+
+    #[test]
+    fn test_export_format_parse() {
+        assert_eq!(ExportFormat::parse(None).unwrap(), ExportFormat::Csv);
+        assert_eq!(ExportFormat::parse(Some("CSV")).unwrap(), ExportFormat::Csv);
+        assert!(ExportFormat::parse(Some("xlsx")).is_err());
+    }
+}
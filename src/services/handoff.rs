@@ -0,0 +1,184 @@
+// src/services/handoff.rs
+// Proof of handoff at both ends of a delivery. Each job has two QR codes, one the sender shows
+// at pickup and one the recipient shows at dropoff; the driver app scans it and posts it to
+// `/jobs/:id/scan`, and only a code signed for that job and stage moves the job on.
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::Utc;
+use ring::{hmac, rand::{SecureRandom, SystemRandom}};
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        ids::{DriverId, JobId, UserId},
+        job::{HandoffCodes, HandoffScanRequest, HandoffStage, Job, JobEvent, JobEventType, JobResponse, JobStatus, JobStatusUpdate},
+    },
+    services::{cache_service::CacheService, job_service::{JobOperations, JobService}},
+};
+
+#[derive(Clone)]
+pub struct HandoffConfig {
+    pub signing_key: Vec<u8>, // Shared by every instance, or codes only verify where they were made
+}
+
+impl HandoffConfig {
+    pub fn from_env() -> Option<Self> {
+        let key = std::env::var("HANDOFF_SIGNING_KEY").ok().filter(|value| !value.is_empty())?;
+        Some(Self { signing_key: key.into_bytes() })
+    }
+
+    // A random key for this process only
+    pub fn ephemeral() -> Self {
+        let mut signing_key = vec![0u8; 32];
+        SystemRandom::new().fill(&mut signing_key).expect("system randomness unavailable");
+        Self { signing_key }
+    }
+}
+
+pub struct HandoffService {
+    cache_service: Arc<CacheService>,
+    job_service: Arc<JobService>,
+    key: hmac::Key,
+}
+
+impl HandoffService {
+    pub fn new(cache_service: Arc<CacheService>, job_service: Arc<JobService>, config: HandoffConfig) -> Self {
+        Self {
+            cache_service,
+            job_service,
+            key: hmac::Key::new(hmac::HMAC_SHA256, &config.signing_key),
+        }
+    }
+
+    /// Both QR payloads for the customer app to render
+    pub async fn codes(&self, job_id: &JobId, customer_id: &UserId) -> Result<HandoffCodes, AppError> {
+        let job = self.load_job(job_id).await?;
+        if &job.customer_id != customer_id {
+            return Err(AppError::Forbidden("Not your job".to_string()));
+        }
+        Ok(HandoffCodes {
+            job_id: job.id.clone(),
+            pickup: self.payload(&job, HandoffStage::Pickup),
+            dropoff: self.payload(&job, HandoffStage::Dropoff),
+        })
+    }
+
+    /// Checks a scanned code and, when it holds, picks the package up or completes the delivery
+    pub async fn scan(&self, job_id: &JobId, driver_id: &DriverId, request: HandoffScanRequest) -> Result<JobResponse, AppError> {
+        let job = self.load_job(job_id).await?;
+        if job.driver_id.as_ref() != Some(driver_id) {
+            return Err(AppError::Forbidden("Job is not assigned to this driver".to_string()));
+        }
+
+        let stage = self.verify(&job, request.payload.trim())?;
+        let in_order = match stage {
+            HandoffStage::Pickup => job.status.is_awaiting_pickup(),
+            HandoffStage::Dropoff => job.status.is_carrying_package(),
+        };
+        if !in_order {
+            return Err(AppError::Conflict(format!("A {} code can't be scanned while the job is {:?}", stage, job.status)));
+        }
+
+        self.cache_service.append_job_event(job_id, &JobEvent {
+            event_type: JobEventType::HandoffScanned,
+            timestamp: Utc::now(),
+            location: None,
+            actor: driver_id.to_string(),
+            notes: Some(format!("Verified {} code", stage)),
+        }).await?;
+
+        tracing::info!("Verified {} handoff for job {} by driver {}", stage, job_id, driver_id);
+        match stage {
+            HandoffStage::Pickup => self.job_service.update_job_status(JobStatusUpdate {
+                job_id: job_id.clone(),
+                status: JobStatus::PackagePickedUp,
+                driver_id: Some(driver_id.clone()),
+                notes: None,
+            }).await,
+            HandoffStage::Dropoff => self.job_service.complete_job(job_id).await,
+        }
+    }
+
+    async fn load_job(&self, job_id: &JobId) -> Result<Job, AppError> {
+        self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::NotFound("Job not found".to_string()))
+    }
+
+    // `{tracking_code}.{stage}.{mac}`, the MAC also covering the job ID so a code can't be
+    // replayed against another job
+    fn payload(&self, job: &Job, stage: HandoffStage) -> String {
+        let tag = hmac::sign(&self.key, signing_input(job, stage).as_bytes());
+        format!("{}.{}.{}", job.tracking_code, stage, URL_SAFE_NO_PAD.encode(tag.as_ref()))
+    }
+
+    fn verify(&self, job: &Job, payload: &str) -> Result<HandoffStage, AppError> {
+        let invalid = || AppError::validation_error("payload", "Not a valid handoff code for this job");
+        let mut parts = payload.split('.');
+        let (Some(tracking_code), Some(stage), Some(mac), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+        if tracking_code != job.tracking_code {
+            return Err(invalid());
+        }
+        let stage = match stage {
+            "pickup" => HandoffStage::Pickup,
+            "dropoff" => HandoffStage::Dropoff,
+            _ => return Err(invalid()),
+        };
+        let mac = URL_SAFE_NO_PAD.decode(mac).map_err(|_| invalid())?;
+        hmac::verify(&self.key, signing_input(job, stage).as_bytes(), &mac).map_err(|_| invalid())?;
+        Ok(stage)
+    }
+}
+
+fn signing_input(job: &Job, stage: HandoffStage) -> String {
+    format!("{}:{}:{}", job.id, stage, job.tracking_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::{driver::DriverStatus, user::UserType},
+        services::user_service::UserOperations,
+    };
+
+    #[tokio::test]
+    async fn test_scanned_codes_drive_pickup_and_dropoff() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(31);
+
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let created = state.job_service.create_job(faker.job_request(&customer.id)).await.unwrap();
+        let other = state.job_service.create_job(faker.job_request(&customer.id)).await.unwrap();
+        let mut driver = faker.driver();
+        driver.status = DriverStatus::Online;
+        state.cache_service.cache_driver(&driver).await.unwrap();
+        state.job_service.assign_driver_to_job(&created.id, &driver.id).await.unwrap();
+
+        assert!(matches!(state.handoff_service.codes(&created.id, &faker.user(UserType::Customer).id).await, Err(AppError::Forbidden(_))));
+        let codes = state.handoff_service.codes(&created.id, &customer.id).await.unwrap();
+        let scan = |payload: &str| HandoffScanRequest { payload: payload.to_string() };
+
+        // Out of order, tampered with, for another job or from another driver
+        assert!(matches!(state.handoff_service.scan(&created.id, &driver.id, scan(&codes.dropoff)).await, Err(AppError::Conflict(_))));
+        let tampered = format!("{}x", &codes.pickup[..codes.pickup.len() - 1]);
+        assert!(state.handoff_service.scan(&created.id, &driver.id, scan(&tampered)).await.is_err());
+        let other_codes = state.handoff_service.codes(&other.id, &customer.id).await.unwrap();
+        assert!(state.handoff_service.scan(&created.id, &driver.id, scan(&other_codes.pickup)).await.is_err());
+        let stranger = faker.driver().id;
+        assert!(matches!(state.handoff_service.scan(&created.id, &stranger, scan(&codes.pickup)).await, Err(AppError::Forbidden(_))));
+
+        let picked_up = state.handoff_service.scan(&created.id, &driver.id, scan(&codes.pickup)).await.unwrap();
+        assert_eq!(picked_up.status, JobStatus::PackagePickedUp);
+        let delivered = state.handoff_service.scan(&created.id, &driver.id, scan(&codes.dropoff)).await.unwrap();
+        assert_eq!(delivered.status, JobStatus::DeliveryCompleted);
+
+        let events = state.cache_service.get_job_events(&created.id).await.unwrap();
+        assert_eq!(events.iter().filter(|event| event.event_type == JobEventType::HandoffScanned).count(), 2);
+    }
+}
@@ -0,0 +1,202 @@
+// src/services/i18n.rs
+// Translations of the text we show people: error messages, validation messages and the
+// built-in notification copy. Catalogs are keyed by the English text, as gettext's are,
+// with `{}` standing for the values formatted into it, so call sites keep writing English
+// and an untranslated string simply goes out in English.
+//
+// API responses follow the request's `Accept-Language`, which the `resolve_language`
+// middleware puts in a task-local; notifications follow the recipient's own language.
+use std::future::Future;
+
+use crate::{models::user::DEFAULT_LANGUAGE, services::messaging_service::NotificationMessage};
+
+tokio::task_local! {
+    static CURRENT_LANGUAGE: String;
+}
+
+/// Language of the running request; the default language outside any scope
+pub fn current_language() -> String {
+    CURRENT_LANGUAGE
+        .try_with(|language| language.clone())
+        .unwrap_or_else(|_| DEFAULT_LANGUAGE.to_string())
+}
+
+/// Run `future` with `language` as the current language
+pub async fn with_language<F: Future>(language: String, future: F) -> F::Output {
+    CURRENT_LANGUAGE.scope(language, future).await
+}
+
+// Languages with a catalog, besides English
+const CATALOGS: &[(&str, &[(&str, &str)])] = &[("fr", FRENCH)];
+
+const FRENCH: &[(&str, &str)] = &[
+    // Errors
+    ("Validation errors occurred", "Des erreurs de validation sont survenues"),
+    ("Missing required field: {}", "Champ obligatoire manquant : {}"),
+    ("Invalid value for {}: {}", "Valeur invalide pour {} : {}"),
+    ("Job is already assigned", "La course est déjà attribuée"),
+    ("Insufficient permissions", "Permissions insuffisantes"),
+    ("User not found: {}", "Utilisateur introuvable : {}"),
+    ("Driver not found: {}", "Livreur introuvable : {}"),
+    ("Job not found: {}", "Course introuvable : {}"),
+    ("Invalid or expired session", "Session invalide ou expirée"),
+    ("Region {} is not served here", "La région {} n'est pas desservie ici"),
+    // Validation
+    ("Must not be empty", "Ne doit pas être vide"),
+    ("Must not be negative", "Ne doit pas être négatif"),
+    ("Must be positive", "Doit être positif"),
+    ("Must be a positive number", "Doit être un nombre positif"),
+    ("Must not be in the past", "Ne doit pas être dans le passé"),
+    ("Must be between {} and {}", "Doit être compris entre {} et {}"),
+    ("Must be an email address", "Doit être une adresse e-mail"),
+    ("Invalid email format", "Format d'adresse e-mail invalide"),
+    ("Coordinates are out of range", "Les coordonnées sont hors limites"),
+    ("Send both coordinates or neither", "Envoyez les deux coordonnées ou aucune"),
+    ("User already exists with this phone number", "Un compte existe déjà avec ce numéro de téléphone"),
+    ("User already exists with this email", "Un compte existe déjà avec cette adresse e-mail"),
+    ("Expected a {}-digit {} number", "Numéro à {} chiffres attendu ({})"),
+    ("We don't operate in {} yet", "Nous ne sommes pas encore présents ici : {}"),
+    ("We don't operate under {} yet", "Nous ne sommes pas encore présents sous l'indicatif {}"),
+    // Notifications and receipts
+    ("👋 Welcome to Ghana Delivery!", "👋 Bienvenue sur Ghana Delivery !"),
+    ("Thank you for joining our delivery platform. Start shipping today!", "Merci d'avoir rejoint notre plateforme de livraison. Commencez à expédier dès aujourd'hui !"),
+    ("📦 Package Picked Up", "📦 Colis récupéré"),
+    ("Your package has been collected and should arrive in about {} minutes.", "Votre colis a été récupéré et devrait arriver dans environ {} minutes."),
+    ("Your package has been collected and is on the way!", "Votre colis a été récupéré et est en route !"),
+    ("✅ Delivery Completed", "✅ Livraison effectuée"),
+    ("Your package has been delivered successfully!", "Votre colis a bien été livré !"),
+    ("💸 Refund Issued", "💸 Remboursement effectué"),
+    ("We've refunded {} for your delivery.", "Nous vous avons remboursé {} pour votre livraison."),
+    ("🔄 Finding You A New Driver", "🔄 Recherche d'un nouveau livreur"),
+    ("Your driver stopped responding, so we're matching your delivery with another driver.", "Votre livreur ne répond plus, nous confions votre livraison à un autre livreur."),
+    ("🚗 Driver On The Way", "🚗 Livreur en route"),
+    ("Your driver is coming to pickup location", "Votre livreur se rend au point de retrait"),
+    ("📍 Driver Arrived", "📍 Livreur arrivé"),
+    ("Your driver has arrived at pickup location", "Votre livreur est arrivé au point de retrait"),
+    ("📦 Package In Transit", "📦 Colis en transit"),
+    ("Your package is on the way to destination", "Votre colis est en route vers sa destination"),
+    ("📋 Status Updated", "📋 Statut mis à jour"),
+    ("Delivery status: {}", "Statut de la livraison : {}"),
+    ("⏱️ Your delivery may be late", "⏱️ Votre livraison risque d'être en retard"),
+    ("We're doing our best to get your package there on time. We'll keep you posted.", "Nous faisons tout pour livrer votre colis à l'heure. Nous vous tiendrons informé."),
+    ("🙏 Sorry, we're running late", "🙏 Désolés, nous sommes en retard"),
+    ("We missed our delivery promise. {} has been added to your account.", "Nous n'avons pas tenu notre délai de livraison. {} a été crédité sur votre compte."),
+    ("⚠️ API quota almost used up", "⚠️ Quota d'API presque épuisé"),
+    ("Your key \"{}\" has used {} of its {} {} quota of {}. It resets on {}.", "Votre clé « {} » a utilisé {} de son quota {} {} de {}. Il sera réinitialisé le {}."),
+];
+
+/// Primary subtag, lowercased, of the best language in an `Accept-Language` header that
+/// we have a catalog for; the default language when there is none
+pub fn negotiate(accept_language: &str) -> String {
+    let mut ranges: Vec<(&str, f32)> = accept_language.split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            (!tag.is_empty() && quality > 0.0).then_some((tag, quality))
+        })
+        .collect();
+    // Stable, so equally preferred languages keep the client's order
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranges.into_iter()
+        .map(|(tag, _)| primary_subtag(tag))
+        .find(|language| is_supported(language))
+        .unwrap_or_else(|| DEFAULT_LANGUAGE.to_string())
+}
+
+pub fn is_supported(language: &str) -> bool {
+    language == DEFAULT_LANGUAGE || CATALOGS.iter().any(|(code, _)| *code == language)
+}
+
+// "fr-CI" -> "fr"
+fn primary_subtag(tag: &str) -> String {
+    tag.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase()
+}
+
+/// `text` in `language`, or as it is when the catalog has no entry for it
+pub fn translate(text: &str, language: &str) -> String {
+    let language = primary_subtag(language);
+    let Some((_, catalog)) = CATALOGS.iter().find(|(code, _)| *code == language) else {
+        return text.to_string();
+    };
+    catalog.iter()
+        .find_map(|(source, target)| fill(source, target, text))
+        .unwrap_or_else(|| text.to_string())
+}
+
+/// `text` in the current request's language
+pub fn localize(text: &str) -> String {
+    translate(text, &current_language())
+}
+
+/// A message's built-in title and body in `language`
+pub fn localize_message(message: NotificationMessage, language: &str) -> NotificationMessage {
+    NotificationMessage {
+        title: translate(&message.title, language),
+        body: translate(&message.body, language),
+        ..message
+    }
+}
+
+// `target` with the values `text` has in place of the `{}`s in `source`; None unless
+// `text` is `source` with some value in each slot
+fn fill(source: &str, target: &str, text: &str) -> Option<String> {
+    let mut parts = source.split("{}");
+    let mut rest = text.strip_prefix(parts.next()?)?;
+    let mut values = Vec::new();
+    let parts: Vec<&str> = parts.collect();
+    for (index, part) in parts.iter().enumerate() {
+        let end = if index + 1 == parts.len() {
+            rest.strip_suffix(part)?.len()
+        } else {
+            rest.find(part).filter(|_| !part.is_empty())?
+        };
+        values.push(&rest[..end]);
+        rest = &rest[end + part.len()..];
+    }
+    if !rest.is_empty() {
+        return None;
+    }
+
+    let mut values = values.into_iter();
+    let mut filled = String::with_capacity(target.len());
+    for (index, piece) in target.split("{}").enumerate() {
+        if index > 0 {
+            filled.push_str(values.next()?);
+        }
+        filled.push_str(piece);
+    }
+    Some(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiates_the_preferred_supported_language() {
+        assert_eq!(negotiate("fr-CI,fr;q=0.9,en;q=0.8"), "fr");
+        assert_eq!(negotiate("de-DE, en;q=0.5, fr;q=0.7"), "fr");
+        assert_eq!(negotiate("fr;q=0, en"), "en");
+        assert_eq!(negotiate("tw"), DEFAULT_LANGUAGE);
+        assert_eq!(negotiate(""), DEFAULT_LANGUAGE);
+    }
+
+    #[test]
+    fn test_translates_with_values_and_falls_back_to_english() {
+        assert_eq!(translate("Must not be empty", "fr-CI"), "Ne doit pas être vide");
+        assert_eq!(translate("Must be between 0 and 48", "fr"), "Doit être compris entre 0 et 48");
+        assert_eq!(translate("Expected a 9-digit Ghana number", "fr"), "Numéro à 9 chiffres attendu (Ghana)");
+        assert_eq!(translate("Delivery status: {}", "fr"), "Statut de la livraison : {}");
+        assert_eq!(
+            translate("Your key \"Storefront v2\" has used 4 of its daily jobs_created quota of 5. It resets on 2025-10-02.", "fr"),
+            "Votre clé « Storefront v2 » a utilisé 4 de son quota daily jobs_created de 5. Il sera réinitialisé le 2025-10-02.",
+        );
+        assert_eq!(translate("Must not be empty", "en"), "Must not be empty");
+        assert_eq!(translate("Must not be empty", "tw"), "Must not be empty");
+        assert_eq!(translate("Something we never translated", "fr"), "Something we never translated");
+        assert_eq!(translate("Must be between 0 and", "fr"), "Must be between 0 and");
+    }
+}
@@ -0,0 +1,330 @@
+// src/services/incident_service.rs
+// SOS from a driver or customer. Pressing it opens a high-priority incident with where they
+// were, pages on-call ops past any alert cooldown, and freezes the job it concerns so
+// nothing moves it on while ops find out what happened. Ops keep the incident's timeline
+// as they follow up; resolving it releases the job.
+use chrono::Utc;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        alert::{Alert, AlertKind},
+        ids::{DriverId, JobId, UserId},
+        incident::{Incident, IncidentLocation, IncidentPriority, IncidentReporter, IncidentStatus, IncidentUpdate, IncidentUpdateRequest, SosRequest},
+        job::{Job, JobEvent, JobEventType},
+    },
+    services::{alert_service::AlertService, cache_service::CacheService, tenant_service::current_tenant_id},
+    utils::id_generator::{IdGenerator, IdType},
+};
+
+pub struct IncidentService {
+    cache_service: Arc<CacheService>,
+    alert_service: Arc<AlertService>,
+}
+
+impl IncidentService {
+    pub fn new(cache_service: Arc<CacheService>, alert_service: Arc<AlertService>) -> Self {
+        Self { cache_service, alert_service }
+    }
+
+    /// SOS from a driver, about the job they name or else the one they're on
+    pub async fn driver_sos(&self, driver_id: &DriverId, request: SosRequest) -> Result<Incident, AppError> {
+        self.cache_service.get_driver(driver_id).await?
+            .ok_or_else(|| AppError::NotFound(format!("Driver {} not found", driver_id)))?;
+        let job = match &request.job_id {
+            Some(job_id) => {
+                let job = self.load_job(job_id).await?;
+                if job.driver_id.as_ref() != Some(driver_id) {
+                    return Err(AppError::Forbidden(format!("Job {} is not assigned to you", job_id)));
+                }
+                Some(job)
+            }
+            None => self.current_job(driver_id).await?,
+        };
+        let location = match sent_location(&request)? {
+            Some(location) => Some(location),
+            None => self.cache_service.get_driver_location(driver_id).await?.map(|last| IncidentLocation {
+                latitude: last.latitude,
+                longitude: last.longitude,
+                accuracy: last.accuracy,
+                captured_at: last.timestamp,
+            }),
+        };
+        self.open(IncidentReporter::Driver(driver_id.clone()), job, location, request.message).await
+    }
+
+    /// SOS from a customer, about one of their jobs if they name it
+    pub async fn customer_sos(&self, customer_id: &UserId, request: SosRequest) -> Result<Incident, AppError> {
+        let job = match &request.job_id {
+            Some(job_id) => {
+                let job = self.load_job(job_id).await?;
+                if job.customer_id != *customer_id {
+                    return Err(AppError::Forbidden(format!("Job {} is not yours", job_id)));
+                }
+                Some(job)
+            }
+            None => None,
+        };
+        let location = sent_location(&request)?;
+        self.open(IncidentReporter::Customer(customer_id.clone()), job, location, request.message).await
+    }
+
+    pub async fn get_incident(&self, incident_id: &str) -> Result<Incident, AppError> {
+        self.cache_service.get_incident(incident_id).await?
+            .ok_or_else(|| AppError::NotFound(format!("Incident {} not found", incident_id)))
+    }
+
+    pub async fn incidents(&self, status: Option<IncidentStatus>) -> Result<Vec<Incident>, AppError> {
+        let mut incidents = Vec::new();
+        for incident_id in self.cache_service.get_incident_ids().await? {
+            if let Some(incident) = self.cache_service.get_incident(&incident_id).await?
+                && status.is_none_or(|status| incident.status == status)
+            {
+                incidents.push(incident);
+            }
+        }
+        incidents.sort_by_key(|incident| std::cmp::Reverse(incident.opened_at));
+        Ok(incidents)
+    }
+
+    /// Adds to the timeline, moving the incident on if the update says so. Notes can still be
+    /// added once it's resolved, but it can't be reopened.
+    pub async fn add_update(&self, incident_id: &str, request: IncidentUpdateRequest) -> Result<Incident, AppError> {
+        let (actor, note) = (request.actor.trim(), request.note.trim());
+        if actor.is_empty() {
+            return Err(AppError::validation_error("actor", "Must not be empty"));
+        }
+        if note.is_empty() {
+            return Err(AppError::validation_error("note", "Must not be empty"));
+        }
+        let mut incident = self.get_incident(incident_id).await?;
+        if request.status.is_some() && incident.status == IncidentStatus::Resolved {
+            return Err(AppError::Conflict(format!("Incident {} is already resolved", incident_id)));
+        }
+        if request.status == Some(IncidentStatus::Open) {
+            return Err(AppError::validation_error("status", "An incident can only be acknowledged or resolved"));
+        }
+
+        let now = Utc::now();
+        incident.timeline.push(IncidentUpdate {
+            at: now,
+            actor: actor.to_string(),
+            status: request.status,
+            note: note.to_string(),
+        });
+        if let Some(status) = request.status {
+            incident.status = status;
+        }
+        if incident.status == IncidentStatus::Resolved && incident.resolved_at.is_none() {
+            incident.resolved_at = Some(now);
+            if let Some(job_id) = &incident.job_id {
+                self.release_job(job_id, &incident.id, actor, note).await?;
+            }
+            tracing::info!("Incident {} resolved by {}", incident.id, actor);
+        }
+        self.cache_service.cache_incident(&incident).await?;
+        Ok(incident)
+    }
+
+    async fn open(
+        &self,
+        reporter: IncidentReporter,
+        job: Option<Job>,
+        location: Option<IncidentLocation>,
+        message: Option<String>,
+    ) -> Result<Incident, AppError> {
+        let now = Utc::now();
+        let message = message.map(|message| message.trim().to_string()).filter(|message| !message.is_empty());
+        let who = match &reporter {
+            IncidentReporter::Driver(driver_id) => format!("Driver {}", driver_id),
+            IncidentReporter::Customer(customer_id) => format!("Customer {}", customer_id),
+        };
+        let incident = Incident {
+            id: IdGenerator::generate(IdType::Incident),
+            reporter,
+            priority: IncidentPriority::High,
+            status: IncidentStatus::Open,
+            job_id: job.as_ref().map(|job| job.id.clone()),
+            location,
+            message,
+            timeline: vec![IncidentUpdate {
+                at: now,
+                actor: "system".to_string(),
+                status: Some(IncidentStatus::Open),
+                note: format!("{} pressed SOS", who),
+            }],
+            opened_at: now,
+            resolved_at: None,
+        };
+        // Kept before anything else, so the SOS isn't lost if the rest fails
+        self.cache_service.cache_incident(&incident).await?;
+        // Ops are paged whether or not the job could be frozen
+        if let Some(job) = job
+            && let Err(e) = self.freeze_job(job, &incident.id).await
+        {
+            tracing::error!("Failed to freeze the job of incident {}: {}", incident.id, e);
+        }
+
+        let mut summary = format!("{} pressed SOS, incident {}", who, incident.id);
+        if let Some(job_id) = &incident.job_id {
+            summary.push_str(&format!(" on job {} (frozen)", job_id));
+        }
+        if let Some(location) = &incident.location {
+            summary.push_str(&format!(" at https://www.google.com/maps?q={},{}", location.latitude, location.longitude));
+        }
+        if let Some(message) = &incident.message {
+            summary.push_str(&format!(": \"{}\"", message));
+        }
+        self.alert_service.page(&Alert {
+            kind: AlertKind::Sos,
+            tenant_id: Some(current_tenant_id()),
+            summary,
+            value: 1.0,
+            threshold: 0.0,
+            raised_at: now,
+        }).await;
+        tracing::error!("SOS: {} opened incident {}", who, incident.id);
+        Ok(incident)
+    }
+
+    // A job already finished has nothing left to hold
+    async fn freeze_job(&self, mut job: Job, incident_id: &str) -> Result<(), AppError> {
+        if job.status.is_terminal() || job.frozen_by.is_some() {
+            return Ok(());
+        }
+        job.frozen_by = Some(incident_id.to_string());
+        job.updated_at = Utc::now();
+        self.cache_service.cache_job(&job).await?;
+        self.cache_service.append_job_event(&job.id, &JobEvent {
+            event_type: JobEventType::IncidentOpened,
+            timestamp: job.updated_at,
+            location: None,
+            actor: "system".to_string(),
+            notes: Some(format!("Frozen by incident {}", incident_id)),
+        }).await?;
+        Ok(())
+    }
+
+    async fn release_job(&self, job_id: &JobId, incident_id: &str, actor: &str, note: &str) -> Result<(), AppError> {
+        let Some(mut job) = self.cache_service.load_job(job_id).await? else {
+            return Ok(());
+        };
+        if job.frozen_by.as_deref() != Some(incident_id) {
+            return Ok(());
+        }
+        job.frozen_by = None;
+        job.updated_at = Utc::now();
+        self.cache_service.cache_job(&job).await?;
+        self.cache_service.append_job_event(job_id, &JobEvent {
+            event_type: JobEventType::IncidentResolved,
+            timestamp: job.updated_at,
+            location: None,
+            actor: actor.to_string(),
+            notes: Some(format!("Incident {} resolved: {}", incident_id, note)),
+        }).await?;
+        Ok(())
+    }
+
+    async fn load_job(&self, job_id: &JobId) -> Result<Job, AppError> {
+        self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::job_not_found(job_id.as_str()))
+    }
+
+    async fn current_job(&self, driver_id: &DriverId) -> Result<Option<Job>, AppError> {
+        for job_id in self.cache_service.get_driver_jobs(driver_id).await? {
+            if let Some(job) = self.cache_service.load_job(&job_id).await?
+                && job.driver_id.as_ref() == Some(driver_id)
+                && !job.status.is_terminal()
+            {
+                return Ok(Some(job));
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn sent_location(request: &SosRequest) -> Result<Option<IncidentLocation>, AppError> {
+    match (request.latitude, request.longitude) {
+        (Some(latitude), Some(longitude)) => {
+            if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+                return Err(AppError::validation_error("latitude", "Coordinates are out of range"));
+            }
+            Ok(Some(IncidentLocation { latitude, longitude, accuracy: request.accuracy, captured_at: Utc::now() }))
+        }
+        (None, None) => Ok(None),
+        _ => Err(AppError::validation_error("longitude", "Send both coordinates or neither")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::{Faker, ACCRA}},
+        models::{driver::DriverStatus, job::{JobStatus, JobStatusUpdate}, user::UserType},
+        services::{job_service::JobOperations, user_service::UserOperations},
+    };
+
+    #[tokio::test]
+    async fn test_driver_sos_freezes_their_job_until_ops_resolve_it() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(108);
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let mut driver = faker.driver();
+        driver.is_active = true;
+        driver.status = DriverStatus::Online;
+        state.cache_service.cache_driver(&driver).await.unwrap();
+        let job = state.job_service.create_job(faker.job_request(&customer.id)).await.unwrap();
+        state.job_service.assign_driver_to_job(&job.id, &driver.id).await.unwrap();
+        state.cache_service.cache_driver_locations(&[(driver.id.clone(), faker.location_update(ACCRA))]).await.unwrap();
+
+        // No job named, so it's the one they're on, at their last known position
+        let incident = state.incident_service.driver_sos(&driver.id, SosRequest {
+            message: Some("  Being followed  ".to_string()),
+            ..SosRequest::default()
+        }).await.unwrap();
+        assert_eq!((incident.priority, incident.status), (IncidentPriority::High, IncidentStatus::Open));
+        assert_eq!(incident.job_id.as_ref(), Some(&job.id));
+        assert_eq!(incident.message.as_deref(), Some("Being followed"));
+        assert!(incident.location.is_some());
+
+        let frozen = state.cache_service.load_job(&job.id).await.unwrap().unwrap();
+        assert_eq!(frozen.frozen_by.as_deref(), Some(incident.id.as_str()));
+        let moved = state.job_service.update_job_status(JobStatusUpdate {
+            job_id: job.id.clone(),
+            status: JobStatus::DriverEnRoute,
+            driver_id: Some(driver.id.clone()),
+            notes: None,
+        }).await;
+        assert!(matches!(moved, Err(AppError::Conflict(_))));
+        assert!(matches!(state.job_service.complete_job(&job.id).await, Err(AppError::Conflict(_))));
+
+        // Someone else's job isn't theirs to freeze
+        let other = faker.job(&customer.id);
+        state.cache_service.cache_job(&other).await.unwrap();
+        let stranger = UserId::generate();
+        let request = SosRequest { job_id: Some(other.id.clone()), ..SosRequest::default() };
+        assert!(matches!(state.incident_service.customer_sos(&stranger, request).await, Err(AppError::Forbidden(_))));
+
+        let update = |status, note: &str| IncidentUpdateRequest { actor: "ops@sparrow".to_string(), note: note.to_string(), status };
+        state.incident_service.add_update(&incident.id, update(Some(IncidentStatus::Acknowledged), "Called the driver")).await.unwrap();
+        assert_eq!(state.incident_service.incidents(Some(IncidentStatus::Acknowledged)).await.unwrap().len(), 1);
+        let resolved = state.incident_service.add_update(&incident.id, update(Some(IncidentStatus::Resolved), "Driver safe at a station")).await.unwrap();
+        assert!(resolved.resolved_at.is_some());
+        assert_eq!(resolved.timeline.len(), 3);
+        assert!(matches!(
+            state.incident_service.add_update(&incident.id, update(Some(IncidentStatus::Acknowledged), "Again")).await,
+            Err(AppError::Conflict(_)),
+        ));
+
+        let released = state.cache_service.load_job(&job.id).await.unwrap().unwrap();
+        assert!(released.frozen_by.is_none());
+        let events = state.cache_service.get_job_events(&job.id).await.unwrap();
+        assert!(events.iter().any(|event| event.event_type == JobEventType::IncidentOpened));
+        assert!(events.iter().any(|event| event.event_type == JobEventType::IncidentResolved));
+    }
+}
@@ -0,0 +1,437 @@
+// src/services/invoice_service.rs
+// Consolidated billing for business accounts. A merchant on monthly invoicing isn't
+// charged per job: each completed job is booked to their ledger account and added to
+// the month's draft. Once the month is over the draft is issued as one invoice per
+// currency and emailed, and unpaid invoices get reminders after they fall due.
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        ids::UserId,
+        invoice::{
+            billing_period, BillingAccount, BillingMode, Invoice, InvoiceDraft, InvoiceLine, InvoiceStatus,
+            RecordInvoicePaymentRequest, UpdateBillingAccountRequest,
+        },
+        job::{Job, PaymentStatus},
+        ledger::{CreateLedgerEntryRequest, LedgerAccount, PlatformAccount, Posting},
+        money::{Currency, Money},
+        user::UserType,
+    },
+    services::{cache_service::CacheService, ledger::LedgerService},
+    utils::id_generator::{IdGenerator, IdType},
+};
+
+#[async_trait]
+pub trait InvoiceMailer: Send + Sync {
+    async fn send_invoice(&self, to: &str, invoice: &Invoice) -> Result<(), AppError>;
+    async fn send_reminder(&self, to: &str, invoice: &Invoice) -> Result<(), AppError>;
+}
+
+// Until a mail provider is configured: invoices are still issued and listed for the merchant
+pub struct NoInvoiceMailer;
+
+#[async_trait]
+impl InvoiceMailer for NoInvoiceMailer {
+    async fn send_invoice(&self, to: &str, invoice: &Invoice) -> Result<(), AppError> {
+        tracing::info!("No mailer configured, invoice {} for {} not sent", invoice.id, to);
+        Ok(())
+    }
+
+    async fn send_reminder(&self, to: &str, invoice: &Invoice) -> Result<(), AppError> {
+        tracing::info!("No mailer configured, reminder for invoice {} to {} not sent", invoice.id, to);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InvoiceConfig {
+    pub default_payment_terms_days: u32,
+    pub max_payment_terms_days: u32,
+    pub issue_grace_hours: i64,     // Wait this long into the new month for late completions
+    pub reminder_interval_days: i64,
+    pub max_reminders: u32,
+}
+
+impl Default for InvoiceConfig {
+    fn default() -> Self {
+        Self {
+            default_payment_terms_days: 30,
+            max_payment_terms_days: 90,
+            issue_grace_hours: 2,
+            reminder_interval_days: 7,
+            max_reminders: 3,
+        }
+    }
+}
+
+pub struct InvoiceService {
+    cache_service: Arc<CacheService>,
+    ledger: Arc<LedgerService>,
+    mailer: Arc<dyn InvoiceMailer>,
+    config: InvoiceConfig,
+}
+
+impl InvoiceService {
+    pub fn new(cache_service: Arc<CacheService>, ledger: Arc<LedgerService>, mailer: Arc<dyn InvoiceMailer>, config: InvoiceConfig) -> Self {
+        Self { cache_service, ledger, mailer, config }
+    }
+
+    /// The merchant's billing setup; accounts never configured are charged per job
+    pub async fn billing_account(&self, merchant_id: &UserId) -> Result<BillingAccount, AppError> {
+        Ok(self.cache_service.get_billing_account(merchant_id).await?.unwrap_or_else(|| BillingAccount {
+            merchant_id: merchant_id.clone(),
+            mode: BillingMode::PerJob,
+            payment_terms_days: self.config.default_payment_terms_days,
+            billing_email: None,
+            updated_at: Utc::now(),
+        }))
+    }
+
+    pub async fn update_billing_account(&self, merchant_id: &UserId, request: UpdateBillingAccountRequest) -> Result<BillingAccount, AppError> {
+        let user = self.cache_service.load_user(merchant_id).await?
+            .ok_or_else(|| AppError::NotFound(format!("User {} not found", merchant_id)))?;
+        if user.user_type != UserType::Business {
+            return Err(AppError::validation_error("merchant_id", "Only business accounts can be invoiced"));
+        }
+        let mut account = self.billing_account(merchant_id).await?;
+        if let Some(days) = request.payment_terms_days {
+            if days == 0 || days > self.config.max_payment_terms_days {
+                return Err(AppError::validation_error(
+                    "payment_terms_days",
+                    format!("Must be between 1 and {}", self.config.max_payment_terms_days),
+                ));
+            }
+            account.payment_terms_days = days;
+        }
+        if let Some(email) = request.billing_email {
+            let email = email.trim();
+            if !email.is_empty() && !email.contains('@') {
+                return Err(AppError::validation_error("billing_email", "Must be an email address"));
+            }
+            account.billing_email = Some(email.to_string()).filter(|email| !email.is_empty());
+        }
+        account.mode = request.mode;
+        account.updated_at = Utc::now();
+        self.cache_service.cache_billing_account(&account).await?;
+
+        tracing::info!("Billing for {} set to {:?}", merchant_id, account.mode);
+        Ok(account)
+    }
+
+    pub async fn bills_monthly(&self, customer_id: &UserId) -> Result<bool, AppError> {
+        Ok(self.cache_service.get_billing_account(customer_id).await?
+            .is_some_and(|account| account.mode == BillingMode::MonthlyInvoice))
+    }
+
+    /// Add a completed, invoiced job to its customer's draft for the current month
+    pub async fn add_job(&self, job: &Job) -> Result<(), AppError> {
+        let line = InvoiceLine {
+            job_id: job.id.clone(),
+            description: format!("Delivery {} to {}", job.tracking_code, job.dropoff_location.address),
+            completed_at: job.dropoff_time.unwrap_or_else(Utc::now),
+            amount: job.pricing.total,
+            tax: job.pricing.tax,
+        };
+        // Billed in the month it was completed in our books, not by its dropoff time, so a
+        // month that's already been issued is never added to
+        let period = billing_period(Utc::now());
+        self.cache_service.append_invoice_line(&job.customer_id, &period, &line).await
+    }
+
+    /// Months not yet issued, oldest first
+    pub async fn drafts(&self, merchant_id: &UserId) -> Result<Vec<InvoiceDraft>, AppError> {
+        let mut periods = self.cache_service.get_invoice_draft_periods(merchant_id).await?;
+        periods.sort();
+        let mut drafts = Vec::new();
+        for period in periods {
+            let lines = self.draft_lines(merchant_id, &period).await?;
+            let totals = group_by_currency(&lines).into_iter()
+                .map(|(currency, lines)| lines.iter().fold(Money::zero(currency), |total, line| total + line.amount))
+                .collect();
+            drafts.push(InvoiceDraft {
+                merchant_id: merchant_id.clone(),
+                period,
+                lines,
+                totals,
+            });
+        }
+        Ok(drafts)
+    }
+
+    /// Issue and send every draft for a month that ended before `now`
+    pub async fn issue_due(&self, now: DateTime<Utc>) -> Result<Vec<Invoice>, AppError> {
+        let current = billing_period(now - Duration::hours(self.config.issue_grace_hours));
+        let mut issued = Vec::new();
+        for merchant_id in self.cache_service.get_invoice_draft_merchants().await? {
+            for period in self.cache_service.get_invoice_draft_periods(&merchant_id).await? {
+                if period >= current || !self.cache_service.claim_invoice_issue(&merchant_id, &period).await? {
+                    continue;
+                }
+                issued.extend(self.issue(&merchant_id, &period, now).await?);
+            }
+        }
+        Ok(issued)
+    }
+
+    async fn issue(&self, merchant_id: &UserId, period: &str, now: DateTime<Utc>) -> Result<Vec<Invoice>, AppError> {
+        let account = self.billing_account(merchant_id).await?;
+        let lines = self.draft_lines(merchant_id, period).await?;
+        let mut invoices = Vec::new();
+        for (currency, lines) in group_by_currency(&lines) {
+            let total = lines.iter().fold(Money::zero(currency), |total, line| total + line.amount);
+            let tax = lines.iter().fold(Money::zero(currency), |tax, line| tax + line.tax);
+            let invoice = Invoice {
+                id: IdGenerator::generate(IdType::Invoice),
+                merchant_id: merchant_id.clone(),
+                period: period.to_string(),
+                currency,
+                lines,
+                subtotal: total - tax,
+                tax,
+                total,
+                status: InvoiceStatus::Issued,
+                issued_at: now,
+                due_at: now + Duration::days(account.payment_terms_days as i64),
+                emailed_to: None,
+                reminders_sent: 0,
+                last_reminded_at: None,
+                paid_at: None,
+                payment_reference: None,
+                ledger_entry_id: None,
+            };
+            self.cache_service.cache_invoice(&invoice).await?;
+            invoices.push(invoice);
+        }
+        self.cache_service.clear_invoice_draft(merchant_id, period).await?;
+
+        // Sending is best-effort: the invoice stands whether or not the email got out
+        let recipient = self.recipient(&account).await?;
+        for invoice in &mut invoices {
+            tracing::info!("Issued invoice {} to {} for {}: {}", invoice.id, merchant_id, period, invoice.total);
+            let Some(to) = &recipient else {
+                tracing::warn!("No email address for {}, invoice {} not sent", merchant_id, invoice.id);
+                continue;
+            };
+            match self.mailer.send_invoice(to, invoice).await {
+                Ok(()) => {
+                    invoice.emailed_to = Some(to.clone());
+                    self.cache_service.cache_invoice(invoice).await?;
+                }
+                Err(e) => tracing::warn!("Failed to email invoice {} to {}: {}", invoice.id, to, e),
+            }
+        }
+        Ok(invoices)
+    }
+
+    /// Mark unpaid invoices past their due date overdue and remind the merchant, at most
+    /// every `reminder_interval_days` and `max_reminders` times. Returns reminders sent.
+    pub async fn remind_overdue(&self, now: DateTime<Utc>) -> Result<usize, AppError> {
+        let mut sent = 0;
+        for invoice_id in self.cache_service.get_invoice_ids().await? {
+            let Some(mut invoice) = self.cache_service.get_invoice(&invoice_id).await? else {
+                continue;
+            };
+            if !invoice.status.is_open() || invoice.due_at > now {
+                continue;
+            }
+            invoice.status = InvoiceStatus::Overdue;
+            let reminder_due = invoice.reminders_sent < self.config.max_reminders
+                && invoice.last_reminded_at
+                    .is_none_or(|at| now - at >= Duration::days(self.config.reminder_interval_days));
+            if reminder_due {
+                let account = self.billing_account(&invoice.merchant_id).await?;
+                match self.recipient(&account).await? {
+                    Some(to) => match self.mailer.send_reminder(&to, &invoice).await {
+                        Ok(()) => {
+                            invoice.reminders_sent += 1;
+                            invoice.last_reminded_at = Some(now);
+                            sent += 1;
+                        }
+                        Err(e) => tracing::warn!("Failed to send reminder for invoice {} to {}: {}", invoice.id, to, e),
+                    },
+                    None => tracing::warn!("No email address for {}, overdue invoice {} not chased", invoice.merchant_id, invoice.id),
+                }
+            }
+            self.cache_service.cache_invoice(&invoice).await?;
+        }
+        Ok(sent)
+    }
+
+    /// Record the merchant's payment: cash in against their account, and their jobs paid
+    pub async fn record_payment(&self, invoice_id: &str, request: RecordInvoicePaymentRequest) -> Result<Invoice, AppError> {
+        let reference = request.reference.trim();
+        if reference.is_empty() {
+            return Err(AppError::validation_error("reference", "Must not be empty"));
+        }
+        let mut invoice = self.get_invoice(invoice_id).await?;
+        if !invoice.status.is_open() {
+            return Err(AppError::Conflict(format!("Invoice {} is already paid", invoice_id)));
+        }
+
+        let entry = self.ledger.post(CreateLedgerEntryRequest {
+            posting_key: format!("invoice:{}:payment", invoice.id),
+            description: format!("Payment of invoice {} for {}", invoice.id, invoice.period),
+            job_id: None,
+            // Merchants pay by transfer quoting the invoice number
+            provider_reference: Some(invoice.id.clone()),
+            postings: vec![
+                Posting::debit(LedgerAccount::Platform(PlatformAccount::Cash), invoice.total),
+                Posting::credit(LedgerAccount::Customer(invoice.merchant_id.clone()), invoice.total),
+            ],
+        }).await?;
+
+        let now = Utc::now();
+        invoice.status = InvoiceStatus::Paid;
+        invoice.paid_at = Some(now);
+        invoice.payment_reference = Some(reference.to_string());
+        invoice.ledger_entry_id = Some(entry.id);
+        self.cache_service.cache_invoice(&invoice).await?;
+
+        for line in &invoice.lines {
+            let Some(mut job) = self.cache_service.load_job(&line.job_id).await? else {
+                continue;
+            };
+            if job.payment_status == PaymentStatus::Invoiced {
+                job.payment_status = PaymentStatus::Paid;
+                job.updated_at = now;
+                self.cache_service.cache_job(&job).await?;
+            }
+        }
+
+        tracing::info!("Invoice {} paid by {} ({})", invoice.id, invoice.merchant_id, reference);
+        Ok(invoice)
+    }
+
+    pub async fn get_invoice(&self, invoice_id: &str) -> Result<Invoice, AppError> {
+        self.cache_service.get_invoice(invoice_id).await?
+            .ok_or_else(|| AppError::NotFound(format!("Invoice {} not found", invoice_id)))
+    }
+
+    /// Issued invoices, newest first, optionally only those with `status`
+    pub async fn invoices(&self, status: Option<InvoiceStatus>) -> Result<Vec<Invoice>, AppError> {
+        let ids = self.cache_service.get_invoice_ids().await?;
+        self.load_invoices(ids, status).await
+    }
+
+    pub async fn merchant_invoices(&self, merchant_id: &UserId) -> Result<Vec<Invoice>, AppError> {
+        let ids = self.cache_service.get_merchant_invoice_ids(merchant_id).await?;
+        self.load_invoices(ids, None).await
+    }
+
+    async fn load_invoices(&self, ids: Vec<String>, status: Option<InvoiceStatus>) -> Result<Vec<Invoice>, AppError> {
+        let mut invoices = Vec::new();
+        for id in ids {
+            if let Some(invoice) = self.cache_service.get_invoice(&id).await?
+                && status.is_none_or(|status| invoice.status == status)
+            {
+                invoices.push(invoice);
+            }
+        }
+        invoices.sort_by_key(|invoice| std::cmp::Reverse(invoice.issued_at));
+        Ok(invoices)
+    }
+
+    // A job completed twice is still only billed once
+    async fn draft_lines(&self, merchant_id: &UserId, period: &str) -> Result<Vec<InvoiceLine>, AppError> {
+        let mut seen = HashSet::new();
+        Ok(self.cache_service.get_invoice_draft_lines(merchant_id, period).await?
+            .into_iter()
+            .filter(|line| seen.insert(line.job_id.clone()))
+            .collect())
+    }
+
+    async fn recipient(&self, account: &BillingAccount) -> Result<Option<String>, AppError> {
+        if let Some(email) = &account.billing_email {
+            return Ok(Some(email.clone()));
+        }
+        Ok(self.cache_service.load_user(&account.merchant_id).await?
+            .map(|user| user.email)
+            .filter(|email| !email.is_empty()))
+    }
+}
+
+fn group_by_currency(lines: &[InvoiceLine]) -> BTreeMap<Currency, Vec<InvoiceLine>> {
+    let mut groups: BTreeMap<Currency, Vec<InvoiceLine>> = BTreeMap::new();
+    for line in lines {
+        groups.entry(line.amount.currency()).or_default().push(line.clone());
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::job::JobStatus,
+        services::job_service::JobOperations,
+    };
+
+    #[tokio::test]
+    async fn test_monthly_merchant_is_invoiced_reminded_and_paid() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let invoices = &state.invoice_service;
+        let mut faker = Faker::seeded(64);
+        let cedis = |amount: f64| Money::from_major(amount, Currency::GHS);
+
+        let customer = faker.user(UserType::Customer);
+        state.cache_service.cache_user(&customer).await.unwrap();
+        let monthly = UpdateBillingAccountRequest { mode: BillingMode::MonthlyInvoice, payment_terms_days: Some(14), billing_email: None };
+        assert!(invoices.update_billing_account(&customer.id, monthly).await.is_err());
+
+        let merchant = faker.user(UserType::Business);
+        state.cache_service.cache_user(&merchant).await.unwrap();
+        let monthly = UpdateBillingAccountRequest { mode: BillingMode::MonthlyInvoice, payment_terms_days: Some(14), billing_email: None };
+        invoices.update_billing_account(&merchant.id, monthly).await.unwrap();
+
+        for total in [30.0, 45.0] {
+            let mut job = faker.job(&merchant.id);
+            job.status = JobStatus::InTransit;
+            job.pricing.total = cedis(total);
+            job.pricing.tax = cedis(total / 10.0);
+            state.cache_service.cache_job(&job).await.unwrap();
+            let completed = state.job_service.complete_job(&job.id).await.unwrap();
+            assert_eq!(completed.payment_status, PaymentStatus::Invoiced);
+        }
+        let drafts = invoices.drafts(&merchant.id).await.unwrap();
+        assert_eq!((drafts.len(), drafts[0].lines.len()), (1, 2));
+        assert_eq!(drafts[0].totals, vec![cedis(75.0)]);
+        let owed = state.ledger_service.balance(&LedgerAccount::Customer(merchant.id.clone()), Currency::GHS).await.unwrap();
+        assert_eq!(owed.balance, cedis(-75.0));
+
+        // Nothing is issued until the month is over
+        let now = Utc::now();
+        assert!(invoices.issue_due(now).await.unwrap().is_empty());
+        let next_month = now + Duration::days(40);
+        let issued = invoices.issue_due(next_month).await.unwrap();
+        assert_eq!(issued.len(), 1);
+        let invoice = &issued[0];
+        assert_eq!((invoice.total, invoice.tax, invoice.subtotal), (cedis(75.0), cedis(7.5), cedis(67.5)));
+        assert_eq!(invoice.emailed_to.as_deref(), Some(merchant.email.as_str()));
+        assert!(invoices.drafts(&merchant.id).await.unwrap().is_empty());
+        assert!(invoices.issue_due(next_month).await.unwrap().is_empty());
+
+        let late = next_month + Duration::days(15);
+        assert_eq!(invoices.remind_overdue(late).await.unwrap(), 1);
+        assert_eq!(invoices.remind_overdue(late + Duration::days(1)).await.unwrap(), 0);
+        assert_eq!(invoices.invoices(Some(InvoiceStatus::Overdue)).await.unwrap().len(), 1);
+
+        let paid = invoices.record_payment(&invoice.id, RecordInvoicePaymentRequest { reference: "GCB-20261103".to_string() }).await.unwrap();
+        assert_eq!(paid.status, InvoiceStatus::Paid);
+        let owed = state.ledger_service.balance(&LedgerAccount::Customer(merchant.id.clone()), Currency::GHS).await.unwrap();
+        assert!(owed.balance.is_zero());
+        let job = state.cache_service.load_job(&invoice.lines[0].job_id).await.unwrap().unwrap();
+        assert_eq!(job.payment_status, PaymentStatus::Paid);
+        assert!(matches!(
+            invoices.record_payment(&invoice.id, RecordInvoicePaymentRequest { reference: "again".to_string() }).await,
+            Err(AppError::Conflict(_))
+        ));
+    }
+}
@@ -0,0 +1,356 @@
+// src/services/job_sagas.rs
+// The money side of a job as sagas. Completing a job settles it in the ledger (the
+// customer's payment, the driver's earnings, commission and tax) and bills it if the
+// merchant is invoiced monthly. The job is delivered and paid whatever happens here, so
+// that saga only moves forward: a step that keeps failing is left for an operator rather
+// than unwinding the accounts. Refunding one reverses the settlement, marks the payment
+// refunded and announces that, and is undone if it can't finish.
+// Each step reloads the job, so a saga resumed after a restart works from what is saved.
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{domain_event::DomainEvent, ids::JobId, job::{Job, PaymentStatus}, saga::SagaKind},
+    services::{
+        cache_service::CacheService,
+        earnings::EarningsCalculator,
+        event_bus::EventBus,
+        invoice_service::InvoiceService,
+        ledger::{settlement_key, LedgerService},
+        saga::{Saga, SagaStep},
+    },
+};
+
+// Each go at refunding a job posts under its own key, so one that was withdrawn doesn't
+// stand in for the next
+fn refund_key(job_id: &JobId, attempt: u32) -> String {
+    match attempt {
+        1 => format!("job:{}:refund", job_id),
+        n => format!("job:{}:refund:{}", job_id, n),
+    }
+}
+
+fn withdrawal_key(job_id: &JobId, attempt: u32) -> String {
+    format!("{}:reversal", refund_key(job_id, attempt))
+}
+
+// The first attempt that hasn't been withdrawn
+async fn refund_attempt(cache_service: &CacheService, job_id: &JobId) -> Result<u32, AppError> {
+    let mut attempt = 1;
+    while cache_service.get_ledger_entry_by_key(&withdrawal_key(job_id, attempt)).await?.is_some() {
+        attempt += 1;
+    }
+    Ok(attempt)
+}
+
+async fn load_job(cache_service: &CacheService, job_id: &JobId) -> Result<Job, AppError> {
+    cache_service.load_job(job_id).await?
+        .ok_or_else(|| AppError::job_not_found(job_id.as_str()))
+}
+
+// Split the fare between the driver, commission and tax
+struct PostSettlement {
+    cache_service: Arc<CacheService>,
+    ledger: Arc<LedgerService>,
+    earnings: Arc<EarningsCalculator>,
+}
+
+#[async_trait]
+impl SagaStep<JobId> for PostSettlement {
+    fn name(&self) -> &'static str {
+        "post_settlement"
+    }
+
+    // The posting key is stable, so settling again records it once
+    async fn execute(&self, job_id: &JobId) -> Result<(), AppError> {
+        let job = load_job(&self.cache_service, job_id).await?;
+        let earnings = match &job.driver_id {
+            Some(driver_id) => match self.cache_service.get_driver(driver_id).await? {
+                Some(driver) => Some(self.earnings.preview(&job, &driver.vehicle.vehicle_type).await),
+                None => None,
+            },
+            None => None,
+        };
+        self.ledger.post_job_settlement(&job, earnings.as_ref()).await?;
+        Ok(())
+    }
+}
+
+// Merchants on monthly invoicing pay for it with the rest of the month's jobs
+struct BillOnInvoice {
+    cache_service: Arc<CacheService>,
+    invoices: Arc<InvoiceService>,
+}
+
+#[async_trait]
+impl SagaStep<JobId> for BillOnInvoice {
+    fn name(&self) -> &'static str {
+        "bill_on_invoice"
+    }
+
+    async fn execute(&self, job_id: &JobId) -> Result<(), AppError> {
+        let job = load_job(&self.cache_service, job_id).await?;
+        if job.payment_status != PaymentStatus::Invoiced {
+            return Ok(());
+        }
+        let drafts = self.invoices.drafts(&job.customer_id).await?;
+        if drafts.iter().flat_map(|draft| &draft.lines).any(|line| line.job_id == job.id) {
+            return Ok(());
+        }
+        self.invoices.add_job(&job).await
+    }
+}
+
+// The driver's earnings, our commission and the tax all come back out
+struct ReverseSettlement {
+    cache_service: Arc<CacheService>,
+    ledger: Arc<LedgerService>,
+}
+
+#[async_trait]
+impl SagaStep<JobId> for ReverseSettlement {
+    fn name(&self) -> &'static str {
+        "reverse_settlement"
+    }
+
+    async fn execute(&self, job_id: &JobId) -> Result<(), AppError> {
+        let attempt = refund_attempt(&self.cache_service, job_id).await?;
+        let description = format!("Refund for job {}", job_id);
+        match self.ledger.reverse(&settlement_key(job_id), &refund_key(job_id, attempt), description).await? {
+            Some(_) => Ok(()),
+            None => Err(AppError::Conflict(format!("Job {} has no settlement to refund", job_id))),
+        }
+    }
+
+    async fn compensate(&self, job_id: &JobId) -> Result<(), AppError> {
+        let attempt = refund_attempt(&self.cache_service, job_id).await?;
+        let description = format!("Refund for job {} withdrawn", job_id);
+        self.ledger.reverse(&refund_key(job_id, attempt), &withdrawal_key(job_id, attempt), description).await?;
+        Ok(())
+    }
+}
+
+struct MarkRefunded {
+    cache_service: Arc<CacheService>,
+}
+
+impl MarkRefunded {
+    // Only the payment status, and only from `from`, so nothing else written meanwhile is lost
+    async fn set(&self, job_id: &JobId, from: PaymentStatus, to: PaymentStatus) -> Result<(), AppError> {
+        self.cache_service.update_job(job_id, |job| {
+            if job.payment_status == to {
+                return Ok(());
+            }
+            if job.payment_status != from {
+                return Err(AppError::Conflict(format!("Job {} payment is {:?}, not {:?}", job_id, job.payment_status, from)));
+            }
+            job.payment_status = to.clone();
+            job.updated_at = Utc::now();
+            Ok(())
+        }).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SagaStep<JobId> for MarkRefunded {
+    fn name(&self) -> &'static str {
+        "mark_refunded"
+    }
+
+    async fn execute(&self, job_id: &JobId) -> Result<(), AppError> {
+        self.set(job_id, PaymentStatus::Paid, PaymentStatus::Refunded).await
+    }
+
+    async fn compensate(&self, job_id: &JobId) -> Result<(), AppError> {
+        self.set(job_id, PaymentStatus::Refunded, PaymentStatus::Paid).await
+    }
+}
+
+struct AnnounceRefund {
+    cache_service: Arc<CacheService>,
+    events: Arc<dyn EventBus>,
+}
+
+#[async_trait]
+impl SagaStep<JobId> for AnnounceRefund {
+    fn name(&self) -> &'static str {
+        "announce_refund"
+    }
+
+    async fn execute(&self, job_id: &JobId) -> Result<(), AppError> {
+        let job = load_job(&self.cache_service, job_id).await?;
+        self.events.publish(DomainEvent::PaymentRefunded {
+            job_id: job.id.clone(),
+            customer_id: job.customer_id.clone(),
+            amount: job.pricing.total,
+        }).await;
+        Ok(())
+    }
+}
+
+pub fn completion_saga(
+    cache_service: Arc<CacheService>,
+    ledger: Arc<LedgerService>,
+    earnings: Arc<EarningsCalculator>,
+    invoices: Arc<InvoiceService>,
+) -> Saga<JobId> {
+    let billing = BillOnInvoice { cache_service: cache_service.clone(), invoices };
+    settle_then_bill(PostSettlement { cache_service, ledger, earnings }, billing)
+}
+
+fn settle_then_bill(settlement: PostSettlement, billing: impl SagaStep<JobId> + 'static) -> Saga<JobId> {
+    Saga::new(SagaKind::JobCompletion)
+        .forward_only()
+        .step(settlement)
+        .step(billing)
+}
+
+pub fn refund_saga(cache_service: Arc<CacheService>, ledger: Arc<LedgerService>, events: Arc<dyn EventBus>) -> Saga<JobId> {
+    Saga::new(SagaKind::JobRefund)
+        .step(ReverseSettlement { cache_service: cache_service.clone(), ledger })
+        .step(MarkRefunded { cache_service: cache_service.clone() })
+        .step(AnnounceRefund { cache_service, events })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mocks::{app::TestApp, events::RecordingSubscriber, fixtures::Faker},
+        models::{job::JobStatus, ledger::{LedgerAccount, PlatformAccount}, money::Currency, saga::SagaStatus, user::UserType},
+        services::{event_bus::EventBus, job_service::JobOperations, saga::{SagaConfig, SagaOrchestrator}},
+    };
+
+    // Billing that never comes back
+    struct InvoiceStoreDown;
+
+    #[async_trait]
+    impl SagaStep<JobId> for InvoiceStoreDown {
+        fn name(&self) -> &'static str {
+            "bill_on_invoice"
+        }
+
+        async fn execute(&self, _job_id: &JobId) -> Result<(), AppError> {
+            Err(AppError::service_unavailable("invoices"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_completion_settles_and_refund_reverses_it() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(83);
+        let customer = faker.user(UserType::Customer);
+        state.cache_service.cache_user(&customer).await.unwrap();
+        let driver = faker.driver();
+        state.cache_service.cache_driver(&driver).await.unwrap();
+        let mut job = faker.job(&customer.id);
+        job.driver_id = Some(driver.id.clone());
+        job.status = JobStatus::InTransit;
+        state.cache_service.cache_job(&job).await.unwrap();
+
+        let balance = |account| async move {
+            state.ledger_service.balance(&account, Currency::GHS).await.unwrap().balance
+        };
+        let completed = state.job_service.complete_job(&job.id).await.unwrap();
+        assert_eq!(completed.payment_status, PaymentStatus::Paid);
+        assert_eq!(balance(LedgerAccount::Platform(PlatformAccount::Cash)).await, job.pricing.total);
+        assert!(!balance(LedgerAccount::Driver(driver.id.clone())).await.is_zero());
+        let saga = state.cache_service.get_saga(SagaKind::JobCompletion, job.id.as_str()).await.unwrap().unwrap();
+        assert_eq!(saga.status, SagaStatus::Completed);
+
+        let refunded = state.job_service.refund_job(&job.id).await.unwrap();
+        assert_eq!(refunded.payment_status, PaymentStatus::Refunded);
+        assert!(balance(LedgerAccount::Platform(PlatformAccount::Cash)).await.is_zero());
+        assert!(balance(LedgerAccount::Driver(driver.id.clone())).await.is_zero());
+        assert!(state.job_service.open_sagas().await.unwrap().is_empty());
+        assert!(matches!(state.job_service.refund_job(&job.id).await, Err(AppError::Conflict(_))));
+
+        // Nothing to give back until the job has been settled
+        let mut unsettled = faker.job(&customer.id);
+        unsettled.status = JobStatus::DeliveryCompleted;
+        unsettled.payment_status = PaymentStatus::Paid;
+        state.cache_service.cache_job(&unsettled).await.unwrap();
+        assert!(matches!(state.job_service.refund_job(&unsettled.id).await, Err(AppError::Conflict(_))));
+    }
+
+    #[tokio::test]
+    async fn test_billing_that_keeps_failing_leaves_the_settlement_in_place() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(84);
+        let customer = faker.user(UserType::Customer);
+        state.cache_service.cache_user(&customer).await.unwrap();
+        let mut job = faker.job(&customer.id);
+        job.status = JobStatus::DeliveryCompleted;
+        job.payment_status = PaymentStatus::Paid;
+        state.cache_service.cache_job(&job).await.unwrap();
+
+        let settlement = PostSettlement {
+            cache_service: state.cache_service.clone(),
+            ledger: state.ledger_service.clone(),
+            earnings: state.earnings_calculator.clone(),
+        };
+        let saga = settle_then_bill(settlement, InvoiceStoreDown);
+        let orchestrator = SagaOrchestrator::new(state.cache_service.clone(), SagaConfig::default());
+        let mut saga_state = orchestrator.run(&saga, job.id.as_str(), &job.id).await.unwrap();
+        while !saga_state.status.is_finished() {
+            saga_state = orchestrator.run(&saga, job.id.as_str(), &job.id).await.unwrap();
+        }
+
+        // Failed for an operator, with the customer's payment still on the books
+        assert_eq!(saga_state.status, SagaStatus::Failed);
+        assert!(state.cache_service.get_ledger_entry_by_key(&settlement_key(&job.id)).await.unwrap().is_some());
+        let cash = state.ledger_service.balance(&LedgerAccount::Platform(PlatformAccount::Cash), Currency::GHS).await.unwrap();
+        assert_eq!(cash.balance, job.pricing.total);
+        assert_eq!(state.cache_service.load_job(&job.id).await.unwrap().unwrap().payment_status, PaymentStatus::Paid);
+        assert!(state.job_service.open_sagas().await.unwrap().iter().any(|open| open.key == job.id.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_a_withdrawn_refund_can_be_asked_for_again_and_only_runs_once_at_a_time() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let published = RecordingSubscriber::new();
+        state.event_bus.subscribe(Arc::new(published.clone()));
+        let mut faker = Faker::seeded(85);
+        let customer = faker.user(UserType::Customer);
+        state.cache_service.cache_user(&customer).await.unwrap();
+        let mut job = faker.job(&customer.id);
+        job.status = JobStatus::InTransit;
+        state.cache_service.cache_job(&job).await.unwrap();
+        state.job_service.complete_job(&job.id).await.unwrap();
+        let cash = || async {
+            state.ledger_service.balance(&LedgerAccount::Platform(PlatformAccount::Cash), Currency::GHS).await.unwrap().balance
+        };
+
+        // Marking it refunded keeps failing, so the money that went back is taken out again
+        let set_payment = |status: PaymentStatus| state.cache_service.update_job(&job.id, move |job| {
+            job.payment_status = status.clone();
+            Ok(())
+        });
+        set_payment(PaymentStatus::Invoiced).await.unwrap();
+        let saga = refund_saga(state.cache_service.clone(), state.ledger_service.clone(), state.event_bus.clone());
+        let orchestrator = SagaOrchestrator::new(state.cache_service.clone(), SagaConfig::default());
+        let mut withdrawn = orchestrator.run(&saga, job.id.as_str(), &job.id).await.unwrap();
+        while !withdrawn.status.is_finished() {
+            withdrawn = orchestrator.run(&saga, job.id.as_str(), &job.id).await.unwrap();
+        }
+        assert_eq!(withdrawn.status, SagaStatus::Compensated);
+        assert_eq!(cash().await, job.pricing.total);
+
+        // Asked for again it starts over, rather than reporting the old failure for good
+        set_payment(PaymentStatus::Paid).await.unwrap();
+        let (first, second) = tokio::join!(state.job_service.refund_job(&job.id), state.job_service.refund_job(&job.id));
+        assert_eq!([&first, &second].iter().filter(|result| result.is_ok()).count(), 1);
+        assert!([first, second].into_iter().any(|result| matches!(result, Err(AppError::Conflict(_)))));
+        let retried = state.cache_service.get_saga(SagaKind::JobRefund, job.id.as_str()).await.unwrap().unwrap();
+        assert_ne!(retried.id, withdrawn.id);
+        assert_eq!(retried.status, SagaStatus::Completed);
+        assert!(cash().await.is_zero());
+        assert_eq!(published.count("payment_refunded"), 1);
+    }
+}
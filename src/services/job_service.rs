@@ -1,51 +1,177 @@
 // src/services/job_service.rs
 use async_trait::async_trait;
-use chrono::{Utc};
+use chrono::{DateTime, Utc};
 use std::sync::Arc;
 use tracing;
 
 use crate::{
     errors::SparrowError as AppError,
-    models::{job::{
-        Job, JobEstimateRequest, JobPriority, JobRequest, JobResponse, JobStatus, JobStatusUpdate, Location, PackageType, Pricing
-    }, user::User},
-    services::{cache_service::{CacheKey, CacheKeys, CacheService}, driver_service::{DriverOperations, DriverService}, messaging_service::NotificationService},
-    utils::id_generator::{IdGenerator, IdType, WithGeneratedId}, ValidationError,
+    models::{calendar::CalendarAdjustment, domain_event::DomainEvent, saga::{SagaState, SagaStatus}, ids::{DriverId, JobId, UserId}, job::{
+        AvailableJob, ApproveDropoffChangeRequest, BulkJobResponse, ChangeDropoffRequest, ConfirmDeliveryRequest, DeliveryCode, DriverJobStatusRequest, DropoffChange, JobCursor, JobHistoryPage, JobHistoryQuery, DeliverySla, Job, JobBatch, JobBatchItem, JobBatchStatus, JobEscalation, JobEstimate, JobEstimateRequest, JobEvent, JobEventType, JobManifestRow, JobPriority, JobRequest, JobResponse, JobStatus, JobStatusUpdate, Location, NavigationPlan, OverrideDeliveryCodeRequest, PaymentStatus, Pricing, StopKind
+    }, money::Money, tax::TaxSchedule, tenant::{PricingConfig, Surcharges}, user::{default_language, User}},
+    services::{arrival_calls::ArrivalCallService, business_accounts::BusinessAccountService, cache_service::CacheService, calendar::CalendarService, dispatch::rank_candidates, invoice_service::InvoiceService, ledger::{settlement_key, LedgerService}, dispatch_settings::DispatchSettingsService, package_analysis::PackageAnalysisService, driver_service::DriverService, earnings::EarningsCalculator, event_bus::EventBus, job_sagas::{completion_saga, refund_saga}, saga::{Saga, SagaOrchestrator}, messaging_service::{NotificationMessage, NotificationService}, realtime_publisher::{RealtimeChannel, RealtimeMessage, RealtimePublisher}, region::{current_region_id, pricing_in, RegionRegistry}, route_service::RouteService, delivery_code::DeliveryCodeService, sms::mask_phone, status_feed::StatusFeedService, tax::TaxEngine, tenant_service::{current_tenant_id, TenantService}},
+    utils::{geo::haversine_km, id_generator::{IdGenerator, IdType}}, ValidationError,
 };
 
 #[async_trait]
 pub trait JobOperations: Send + Sync {
     async fn create_job(&self, request: JobRequest) -> Result<JobResponse, AppError>;
-    async fn get_job(&self, job_id: &str) -> Result<Option<JobResponse>, AppError>;
-    async fn get_jobs_by_customer(&self, customer_id: &str) -> Result<Vec<JobResponse>, AppError>;
-    async fn get_jobs_by_driver(&self, driver_id: &str) -> Result<Vec<JobResponse>, AppError>;
+    async fn get_job(&self, job_id: &JobId) -> Result<Option<JobResponse>, AppError>;
+    async fn get_jobs_by_customer(&self, customer_id: &UserId, query: JobHistoryQuery) -> Result<JobHistoryPage, AppError>;
+    async fn get_jobs_by_driver(&self, driver_id: &DriverId, query: JobHistoryQuery) -> Result<JobHistoryPage, AppError>;
+    async fn get_available_jobs(&self, driver_id: &DriverId) -> Result<Vec<AvailableJob>, AppError>;
     async fn update_job_status(&self, update: JobStatusUpdate) -> Result<JobResponse, AppError>;
-    async fn assign_driver_to_job(&self, job_id: &str, driver_id: &str) -> Result<JobResponse, AppError>;
-    async fn calculate_estimate(&self, request: JobEstimateRequest) -> Result<Pricing, AppError>;
-    async fn find_available_drivers(&self, job_id: &str) -> Result<Vec<String>, AppError>;
-    async fn cancel_job(&self, job_id: &str, reason: Option<String>) -> Result<JobResponse, AppError>;
-    async fn complete_job(&self, job_id: &str) -> Result<JobResponse, AppError>;
+    async fn assign_driver_to_job(&self, job_id: &JobId, driver_id: &DriverId) -> Result<JobResponse, AppError>;
+    async fn calculate_estimate(&self, request: JobEstimateRequest) -> Result<JobEstimate, AppError>;
+    async fn find_available_drivers(&self, job_id: &JobId) -> Result<Vec<DriverId>, AppError>;
+    async fn cancel_job(&self, job_id: &JobId, reason: Option<String>) -> Result<JobResponse, AppError>;
+    async fn complete_job(&self, job_id: &JobId) -> Result<JobResponse, AppError>;
+    async fn expire_job(&self, job_id: &JobId, reason: &str) -> Result<JobResponse, AppError>;
+    async fn reassign_unresponsive_job(&self, job_id: &JobId, driver_id: &DriverId) -> Result<JobResponse, AppError>;
+    async fn escalate_job(&self, job_id: &JobId, escalation: JobEscalation) -> Result<JobResponse, AppError>;
+    async fn unassign_driver(&self, job_id: &JobId, reason: Option<String>) -> Result<JobResponse, AppError>;
+    async fn create_jobs_bulk(&self, merchant_id: &UserId, requests: Vec<JobRequest>) -> Result<BulkJobResponse, AppError>;
+    async fn get_job_batch(&self, batch_id: &str, merchant_id: &UserId) -> Result<Option<JobBatchStatus>, AppError>;
+}
+
+// Upper bound on jobs per bulk import request
+pub const MAX_BULK_JOBS: usize = 200;
+
+// How long a re-priced dropoff waits for the customer's approval
+const DROPOFF_CHANGE_TTL_MINUTES: i64 = 15;
+
+#[derive(Debug, Clone)]
+pub struct JobHistoryConfig {
+    pub default_page_size: usize,
+    pub max_page_size: usize,
+}
+
+impl Default for JobHistoryConfig {
+    fn default() -> Self {
+        Self {
+            default_page_size: 20,
+            max_page_size: 100,
+        }
+    }
+}
+
+impl JobHistoryConfig {
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let defaults = Self::default();
+        let max_page_size = var("JOB_HISTORY_MAX_PAGE_SIZE").and_then(|v| v.parse().ok()).unwrap_or(defaults.max_page_size);
+        Self {
+            default_page_size: var("JOB_HISTORY_PAGE_SIZE").and_then(|v| v.parse().ok()).unwrap_or(defaults.default_page_size).min(max_page_size),
+            max_page_size,
+        }
+    }
+
+    fn page_size(&self, limit: Option<usize>) -> Result<usize, AppError> {
+        match limit {
+            None => Ok(self.default_page_size),
+            Some(limit) if (1..=self.max_page_size).contains(&limit) => Ok(limit),
+            Some(_) => Err(AppError::validation_error("limit", format!("Must be between 1 and {}", self.max_page_size))),
+        }
+    }
 }
 
 pub struct JobService {
     cache_service: Arc<CacheService>,
     driver_service: Arc<DriverService>,
+    route_service: Arc<RouteService>,
     notification_service: Arc<dyn NotificationService>,
+    tenant_service: Arc<TenantService>,
+    regions: Arc<RegionRegistry>,
+    earnings: Arc<EarningsCalculator>,
+    tax_engine: Arc<TaxEngine>,
+    dispatch_settings: Arc<DispatchSettingsService>,
+    package_analysis: Arc<PackageAnalysisService>,
+    realtime: Arc<dyn RealtimePublisher>,
+    status_feed: Arc<StatusFeedService>,
+    delivery_codes: Arc<DeliveryCodeService>,
+    business_accounts: Arc<BusinessAccountService>,
+    arrival_calls: Arc<ArrivalCallService>,
+    calendar: Arc<CalendarService>,
+    invoices: Arc<InvoiceService>,
+    events: Arc<dyn EventBus>,
+    sagas: Arc<SagaOrchestrator>,
+    completion: Saga<JobId>,
+    refund: Saga<JobId>,
+    history: JobHistoryConfig,
 }
 
 impl JobService {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         cache_service: Arc<CacheService>,
         driver_service: Arc<DriverService>,
+        route_service: Arc<RouteService>,
         notification_service: Arc<dyn NotificationService>,
+        tenant_service: Arc<TenantService>,
+        regions: Arc<RegionRegistry>,
+        earnings: Arc<EarningsCalculator>,
+        tax_engine: Arc<TaxEngine>,
+        dispatch_settings: Arc<DispatchSettingsService>,
+        package_analysis: Arc<PackageAnalysisService>,
+        realtime: Arc<dyn RealtimePublisher>,
+        status_feed: Arc<StatusFeedService>,
+        delivery_codes: Arc<DeliveryCodeService>,
+        business_accounts: Arc<BusinessAccountService>,
+        arrival_calls: Arc<ArrivalCallService>,
+        calendar: Arc<CalendarService>,
+        ledger: Arc<LedgerService>,
+        invoices: Arc<InvoiceService>,
+        events: Arc<dyn EventBus>,
+        sagas: Arc<SagaOrchestrator>,
+        history: JobHistoryConfig,
     ) -> Self {
+        let completion = completion_saga(cache_service.clone(), ledger.clone(), earnings.clone(), invoices.clone());
+        let refund = refund_saga(cache_service.clone(), ledger.clone(), events.clone());
         Self {
             cache_service,
             driver_service,
+            route_service,
             notification_service,
+            tenant_service,
+            regions,
+            earnings,
+            tax_engine,
+            dispatch_settings,
+            package_analysis,
+            realtime,
+            status_feed,
+            delivery_codes,
+            business_accounts,
+            arrival_calls,
+            calendar,
+            invoices,
+            events,
+            sagas,
+            completion,
+            refund,
+            history,
         }
     }
     
+    fn history_page(&self, query: &JobHistoryQuery) -> Result<(Option<JobCursor>, usize), AppError> {
+        let after = query.cursor.as_deref().map(JobCursor::parse).transpose()?;
+        Ok((after, self.history.page_size(query.limit)?))
+    }
+    
+    // `job_ids` holds one more than a page when there is a page after this one
+    async fn load_history_page(&self, mut job_ids: Vec<JobId>, limit: usize) -> Result<JobHistoryPage, AppError> {
+        let more = job_ids.len() > limit;
+        job_ids.truncate(limit);
+        let mut items = Vec::with_capacity(job_ids.len());
+        for job_id in job_ids {
+            if let Some(job) = self.get_job(&job_id).await? {
+                items.push(job);
+            }
+        }
+        let next_cursor = items.last().filter(|_| more).map(|job| JobCursor::after(job).encode());
+        Ok(JobHistoryPage { items, next_cursor })
+    }
+    
     fn to_response(&self, job: Job) -> JobResponse {
         JobResponse {
             id: job.id,
@@ -58,9 +184,19 @@ impl JobService {
             estimated_distance_km: job.estimated_distance_km,
             estimated_duration_min: job.estimated_duration_min,
             package: job.package,
+            package_photo: job.package_photo,
             created_at: job.created_at,
             pickup_time: job.pickup_time,
             dropoff_time: job.dropoff_time,
+            promised_by: job.sla.map(|sla| sla.promised_by),
+            escalation: job.escalation,
+            recipient_preferences: job.recipient_preferences,
+            pool_id: job.pool_id,
+            delivery_code: job.delivery_code,
+            dropoff_change: job.dropoff_change,
+            arrival_calls: job.arrival_calls,
+            frozen_by: job.frozen_by,
+            region: job.region,
             pricing: job.pricing,
             payment_status: job.payment_status,
             tracking_code: job.tracking_code,
@@ -69,127 +205,616 @@ impl JobService {
         }
     }
     
-    async fn calculate_distance_km(&self, loc1: &Location, loc2: &Location) -> f64 {
-        // Simple haversine formula implementation
-        // In production, you'd use a proper geocoding service
-        let earth_radius_km = 6371.0;
-        let lat1_rad = loc1.latitude.to_radians();
-        let lat2_rad = loc2.latitude.to_radians();
-        let delta_lat = (loc2.latitude - loc1.latitude).to_radians();
-        let delta_lon = (loc2.longitude - loc1.longitude).to_radians();
+    // Put a job back up for dispatch without `driver_id`, recording why in the event log
+    async fn release_driver(
+        &self,
+        job: &mut Job,
+        driver_id: &DriverId,
+        event_type: JobEventType,
+        reason: Option<String>,
+    ) -> Result<(), AppError> {
+        let now = Utc::now();
+        // Against the job as stored, in case the driver picked it up since it was read
+        *job = self.cache_service.update_job(&job.id, |current| {
+            if current.driver_id.as_ref() != Some(driver_id) || !current.status.is_awaiting_pickup() {
+                return Err(AppError::Conflict(format!("Job {} is no longer waiting on driver {}", current.id, driver_id)));
+            }
+            current.status = JobStatus::Searching;
+            current.driver_id = None;
+            current.commission_rate = None;
+            current.accepted_at = None;
+            current.updated_at = now;
+            Ok(())
+        }).await?;
+        
+        self.cache_service.remove_driver_job(driver_id, &job.id).await?;
+        self.forget_queued_job(driver_id, &job.id).await?;
+        
+        let notes = match reason {
+            Some(reason) => format!("Released from driver {}: {}", driver_id, reason),
+            None => format!("Released from driver {}", driver_id),
+        };
+        self.cache_service.append_job_event(&job.id, &JobEvent {
+            event_type,
+            timestamp: now,
+            location: None,
+            actor: "system".to_string(),
+            notes: Some(notes),
+        }).await?;
+        Ok(())
+    }
+    
+    // The job other than `except` that `driver_id` has a package on board for
+    async fn current_delivery(&self, driver_id: &DriverId, except: &JobId) -> Result<Option<Job>, AppError> {
+        for job_id in self.cache_service.get_driver_jobs(driver_id).await? {
+            if &job_id == except {
+                continue;
+            }
+            let Some(job) = self.cache_service.load_job(&job_id).await? else {
+                continue;
+            };
+            if job.driver_id.as_ref() == Some(driver_id) && job.status.is_carrying_package() {
+                return Ok(Some(job));
+            }
+        }
+        Ok(None)
+    }
+    
+    // Hands the rest of a pool to the driver who took one of its jobs. A sibling that can't
+    // be assigned stays open and is dispatched on its own.
+    async fn assign_pool_siblings(&self, pool_id: &str, job_id: &JobId, driver_id: &DriverId) -> Result<(), AppError> {
+        let Some(pool) = self.cache_service.get_job_pool(pool_id).await? else {
+            return Ok(());
+        };
+        for sibling_id in pool.job_ids.iter().filter(|sibling_id| *sibling_id != job_id) {
+            let Some(sibling) = self.cache_service.load_job(sibling_id).await? else {
+                continue;
+            };
+            if sibling.driver_id.is_some() || !matches!(sibling.status, JobStatus::Pending | JobStatus::Searching) {
+                continue;
+            }
+            if let Err(e) = self.assign_driver_to_job(sibling_id, driver_id).await {
+                tracing::warn!("Failed to assign pooled job {} to driver {}: {}", sibling_id, driver_id, e);
+            }
+        }
+        Ok(())
+    }
+    
+    // Valuable and signed-for packages complete only once the recipient's code was entered or
+    // support stepped in. If the code never went out it is sent now, for the driver to try again.
+    async fn require_delivery_code(&self, job: &mut Job) -> Result<(), AppError> {
+        if !self.delivery_codes.required(job) || job.delivery_code.as_ref().is_some_and(DeliveryCode::is_settled) {
+            return Ok(());
+        }
+        if job.delivery_code.as_ref().is_none_or(|code| code.sent_at.is_none()) {
+            let code = self.delivery_codes.send(job).await?;
+            job.delivery_code = Some(code);
+            job.updated_at = Utc::now();
+            self.cache_service.cache_job(job).await?;
+            self.record_delivery_code_sent(job).await;
+            return Err(AppError::Conflict("A delivery code was just texted to the recipient; enter it to complete the delivery".to_string()));
+        }
+        Err(AppError::Conflict("Enter the delivery code texted to the recipient to complete the delivery".to_string()))
+    }
+    
+    async fn record_delivery_code_sent(&self, job: &Job) {
+        let event = JobEvent {
+            event_type: JobEventType::DeliveryCodeSent,
+            timestamp: Utc::now(),
+            location: None,
+            actor: "system".to_string(),
+            notes: job.delivery_code.as_ref().map(|code| format!("Sent to {}", code.sent_to)),
+        };
+        if let Err(e) = self.cache_service.append_job_event(&job.id, &event).await {
+            tracing::warn!("Failed to record delivery code for job {}: {}", job.id, e);
+        }
+    }
+    
+    // Voice call to the contact at `stop`, if the job asked for one. Best-effort: the driver
+    // is there either way.
+    async fn call_on_arrival(&self, job: &Job, stop: StopKind) {
+        let driver_name = match &job.driver_id {
+            Some(driver_id) => self.cache_service.get_driver(driver_id).await.ok().flatten().map(|driver| driver.first_name),
+            None => None,
+        };
+        if let Err(e) = self.arrival_calls.announce(job, stop, driver_name.as_deref()).await {
+            tracing::warn!("Failed to place arrival call for job {}: {}", job.id, e);
+        }
+    }
+    
+    /// The assigned driver marking the package delivered
+    pub async fn complete_job_as_driver(&self, job_id: &JobId, driver_id: &DriverId) -> Result<JobResponse, AppError> {
+        let job: Job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::NotFound("Job not found".to_string()))?;
+        if job.driver_id.as_ref() != Some(driver_id) {
+            return Err(AppError::Forbidden("Job is not assigned to this driver".to_string()));
+        }
+        self.complete_job(job_id).await
+    }
+    
+    /// The assigned driver reporting their progress along the route. It only goes forward,
+    /// and stops at the dropoff: completing is left to `complete_job_as_driver`.
+    pub async fn advance_job_as_driver(&self, job_id: &JobId, driver_id: &DriverId, request: DriverJobStatusRequest) -> Result<JobResponse, AppError> {
+        let job: Job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::NotFound("Job not found".to_string()))?;
+        if job.driver_id.as_ref() != Some(driver_id) {
+            return Err(AppError::Forbidden("Job is not assigned to this driver".to_string()));
+        }
+        let Some(to) = request.status.route_step().filter(|step| *step > 0) else {
+            return Err(AppError::validation_error("status", format!("Drivers can't set a job to {:?}", request.status)));
+        };
+        if job.status.route_step().is_none_or(|from| from >= to) {
+            return Err(AppError::Conflict(format!("Job {} can't go from {:?} to {:?}", job_id, job.status, request.status)));
+        }
+        self.update_job_status(JobStatusUpdate {
+            job_id: job_id.clone(),
+            status: request.status,
+            driver_id: Some(driver_id.clone()),
+            notes: request.notes,
+        }).await
+    }
+    
+    /// The driver enters the code the recipient read out; a match completes the delivery
+    pub async fn confirm_delivery(&self, job_id: &JobId, driver_id: &DriverId, request: ConfirmDeliveryRequest) -> Result<JobResponse, AppError> {
+        let mut job: Job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::NotFound("Job not found".to_string()))?;
+        ensure_not_frozen(&job)?;
+        if job.driver_id.as_ref() != Some(driver_id) {
+            return Err(AppError::Forbidden("Job is not assigned to this driver".to_string()));
+        }
+        if !job.status.is_carrying_package() {
+            return Err(AppError::Conflict(format!("A delivery code can't be entered while the job is {:?}", job.status)));
+        }
+        if job.delivery_code.as_ref().is_none_or(DeliveryCode::is_settled) {
+            return Err(AppError::Conflict("This delivery has no code waiting to be entered".to_string()));
+        }
+        self.delivery_codes.verify(&job, &request.code).await?;
         
-        let a = (delta_lat / 2.0).sin().powi(2) +
-               lat1_rad.cos() * lat2_rad.cos() * (delta_lon / 2.0).sin().powi(2);
-        let c = 2.0 * a.sqrt().atan2((1.0 - a).sqrt());
+        let now = Utc::now();
+        if let Some(code) = job.delivery_code.as_mut() {
+            code.confirmed_at = Some(now);
+        }
+        job.updated_at = now;
+        self.cache_service.cache_job(&job).await?;
+        self.cache_service.append_job_event(job_id, &JobEvent {
+            event_type: JobEventType::DeliveryCodeConfirmed,
+            timestamp: now,
+            location: None,
+            actor: driver_id.to_string(),
+            notes: None,
+        }).await?;
+        self.complete_job(job_id).await
+    }
+    
+    /// Support completes a delivery whose code can't be used: never received, or the
+    /// attempts ran out
+    pub async fn override_delivery_code(&self, job_id: &JobId, request: OverrideDeliveryCodeRequest) -> Result<JobResponse, AppError> {
+        if request.agent.trim().is_empty() {
+            return Err(AppError::validation_error("agent", "Say who approved the override"));
+        }
+        if request.reason.trim().is_empty() {
+            return Err(AppError::validation_error("reason", "Give a reason for completing without the code"));
+        }
+        let mut job: Job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::NotFound("Job not found".to_string()))?;
+        if !job.status.is_carrying_package() {
+            return Err(AppError::Conflict(format!("A delivery can't be completed while the job is {:?}", job.status)));
+        }
         
-        earth_radius_km * c
+        let now = Utc::now();
+        let sent_to = mask_phone(&job.dropoff_location.contact_phone);
+        let code = job.delivery_code.get_or_insert(DeliveryCode {
+            sent_to,
+            sent_at: None,
+            confirmed_at: None,
+            overridden_by: None,
+            override_reason: None,
+        });
+        code.overridden_by = Some(request.agent.trim().to_string());
+        code.override_reason = Some(request.reason.trim().to_string());
+        job.updated_at = now;
+        self.cache_service.cache_job(&job).await?;
+        self.cache_service.append_job_event(job_id, &JobEvent {
+            event_type: JobEventType::DeliveryCodeOverridden,
+            timestamp: now,
+            location: None,
+            actor: request.agent.trim().to_string(),
+            notes: Some(request.reason.trim().to_string()),
+        }).await?;
+        tracing::info!("Delivery code for job {} overridden by {}", job_id, request.agent.trim());
+        self.complete_job(job_id).await
     }
     
-    async fn calculate_duration_min(&self, distance_km: f64) -> i32 {
-        // Estimate duration based on Ghana traffic patterns
-        // Average speed: 25 km/h in urban areas, 40 km/h rural
-        let average_speed_kmh = 30.0; // Conservative estimate for Ghana
-        ((distance_km / average_speed_kmh) * 60.0) as i32
+    /// Give a customer their money back for a delivered job, taking the driver's earnings,
+    /// commission and tax back out of the ledger
+    pub async fn refund_job(&self, job_id: &JobId) -> Result<JobResponse, AppError> {
+        let job: Job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::job_not_found(job_id.as_str()))?;
+        ensure_not_frozen(&job)?;
+        if job.status != JobStatus::DeliveryCompleted || job.payment_status != PaymentStatus::Paid {
+            return Err(AppError::Conflict(format!("Only paid, delivered jobs can be refunded; job {} is {:?} and {:?}", job_id, job.status, job.payment_status)));
+        }
+        if self.cache_service.get_ledger_entry_by_key(&settlement_key(job_id)).await?.is_none() {
+            return Err(AppError::Conflict(format!("Job {} hasn't been settled yet", job_id)));
+        }
+        // A refund that was rolled back leaves the job as it was, ready to be asked for again
+        let saga = self.sagas.run(&self.refund, job_id.as_str(), job_id).await?;
+        let error = saga.steps.iter().find_map(|step| step.last_error.clone()).unwrap_or_default();
+        match saga.status {
+            SagaStatus::Completed => {}
+            SagaStatus::Running | SagaStatus::Compensating => {
+                return Err(AppError::Conflict(format!("Refund of job {} is still in progress: {}", job_id, error)));
+            }
+            SagaStatus::Compensated | SagaStatus::Failed => {
+                return Err(AppError::Conflict(format!("Refund of job {} didn't go through: {}", job_id, error)));
+            }
+        }
+        let job: Job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::job_not_found(job_id.as_str()))?;
+        tracing::info!("Refund of job {} is {:?}", job_id, saga.status);
+        Ok(self.to_response(job))
     }
     
-    async fn calculate_pricing(&self, request: &JobEstimateRequest) -> Pricing {
-        let distance_km = self.calculate_distance_km(&request.pickup_location, &request.dropoff_location).await;
-        let duration_min = self.calculate_duration_min(distance_km).await;
-        
-        // Ghana-specific pricing model
-        let base_fare = match request.priority {
-            JobPriority::Standard => 15.0,  // 15 GHS base
-            JobPriority::Express => 25.0,   // 25 GHS base
-            JobPriority::SameDay => 40.0,   // 40 GHS base  
-            JobPriority::Emergency => 60.0, // 60 GHS base
+    /// Carry on with completions and refunds whose runner died partway
+    pub async fn resume_sagas(&self, now: DateTime<Utc>) -> Result<usize, AppError> {
+        let mut resumed = 0;
+        for saga in [&self.completion, &self.refund] {
+            for state in self.sagas.stalled(saga.kind(), now).await? {
+                tracing::info!("Resuming saga {} ({} {})", state.id, state.kind.as_str(), state.key);
+                self.sagas.resume(saga, state).await?;
+                resumed += 1;
+            }
+        }
+        Ok(resumed)
+    }
+    
+    pub async fn open_sagas(&self) -> Result<Vec<SagaState>, AppError> {
+        self.sagas.open().await
+    }
+    
+    // Live status for the customer's app, whichever realtime provider is configured, and
+    // their webhook if they have one. Best-effort: the change is saved either way.
+    async fn publish_status(&self, job: &Job) {
+        if let Err(e) = self.status_feed.record_status(job).await {
+            tracing::warn!("Failed to queue status webhook for job {}: {}", job.id, e);
+        }
+        let message = RealtimeMessage {
+            name: "job_status".to_string(),
+            data: serde_json::json!({
+                "job_id": job.id,
+                "status": job.status,
+                "driver_id": job.driver_id,
+                "updated_at": job.updated_at,
+            }),
         };
-        
-        let distance_fare = distance_km * 2.5; // 2.5 GHS per km
-        let time_fare = (duration_min as f64) * 0.2; // 0.2 GHS per minute
-        
-        let package_surcharge = match request.package.package_type {
-            PackageType::Document => 0.0,
-            PackageType::SmallPackage => 5.0,
-            PackageType::MediumPackage => 10.0,
-            PackageType::LargePackage => 20.0,
-            PackageType::ExtraLarge => 40.0,
-            PackageType::Food => 8.0,
-            PackageType::Grocery => 15.0,
-            PackageType::Pharmacy => 5.0,
-            PackageType::Electronics => 15.0,
-            PackageType::Fragile => 12.0,
+        if let Err(e) = self.realtime.publish(&RealtimeChannel::User(job.customer_id.clone()), message).await {
+            tracing::warn!("Failed to publish status of job {}: {}", job.id, e);
+        }
+    }
+    
+    /// Send the driver fresh directions after a stop on `job` moves. Best-effort, like the
+    /// assignment it updates; returns the plan that was sent.
+    pub async fn refresh_navigation(&self, job: &Job) -> Option<NavigationPlan> {
+        let driver_id = job.driver_id.as_ref()?;
+        let plan = match self.route_service.navigation(job).await {
+            Ok(plan) => plan,
+            Err(e) => {
+                tracing::warn!("Failed to plan navigation for job {}: {}", job.id, e);
+                return None;
+            }
         };
-        
-        let priority_surcharge = match request.priority {
-            JobPriority::Standard => 0.0,
-            JobPriority::Express => 10.0,
-            JobPriority::SameDay => 25.0,
-            JobPriority::Emergency => 50.0,
+        let message = RealtimeMessage {
+            name: "navigation_updated".to_string(),
+            data: serde_json::json!(plan),
+        };
+        if let Err(e) = self.realtime.publish(&RealtimeChannel::Driver(driver_id.clone()), message).await {
+            tracing::warn!("Failed to send updated navigation for job {} to driver {}: {}", job.id, driver_id, e);
+        }
+        Some(plan)
+    }
+
+    // The customer's own job, still open to a new dropoff
+    async fn load_redirectable_job(&self, job_id: &JobId, customer_id: &UserId) -> Result<Job, AppError> {
+        let job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::job_not_found(job_id.as_str()))?;
+        ensure_redirectable(&job, customer_id)?;
+        Ok(job)
+    }
+
+    /// Re-price the job for a new dropoff and hold it until the customer approves the new
+    /// total. Asking again replaces the previous change.
+    pub async fn request_dropoff_change(&self, job_id: &JobId, customer_id: &UserId, request: ChangeDropoffRequest) -> Result<DropoffChange, AppError> {
+        let mut job = self.load_redirectable_job(job_id, customer_id).await?;
+        let dropoff_location = self.resolve_location(
+            customer_id,
+            request.dropoff_location,
+            request.dropoff_address_id,
+            "dropoff_location",
+        ).await?;
+        let tenant = self.tenant_service.current_tenant().await?;
+        if !tenant.serves_region(&dropoff_location.region) {
+            return Err(AppError::validation_error(
+                "dropoff_location",
+                format!("{} does not serve region {}", tenant.name, dropoff_location.region),
+            ));
+        }
+
+        let (distance_km, duration_min) = self.route_service.trip_estimate(&job.pickup_location, &dropoff_location);
+        let estimate_request = JobEstimateRequest {
+            pickup_location: job.pickup_location.clone(),
+            dropoff_location: dropoff_location.clone(),
+            package: job.package.clone(),
+            priority: job.priority.clone(),
+            currency: Some(job.pricing.currency),
+        };
+        // Same occasion surcharge as the original booking; the tip stays as given
+        let occasion = self.calendar.adjustment_at(job.created_at).await?;
+        let mut pricing = self.price(&estimate_request, occasion.as_ref()).await?;
+        pricing.tip = job.pricing.tip;
+        pricing.total += pricing.tip;
+
+        let now = Utc::now();
+        let change = DropoffChange {
+            dropoff_location,
+            estimated_distance_km: distance_km,
+            estimated_duration_min: duration_min,
+            pricing,
+            previous_total: job.pricing.total,
+            requested_at: now,
+            expires_at: now + chrono::Duration::minutes(DROPOFF_CHANGE_TTL_MINUTES),
+        };
+        job.dropoff_change = Some(change.clone());
+        job.updated_at = now;
+        self.cache_service.cache_job(&job).await?;
+        tracing::info!("Dropoff change for job {} priced at {} (was {})", job_id, change.pricing.total, change.previous_total);
+        Ok(change)
+    }
+
+    /// Apply the pending dropoff change once the customer accepts its total, and send the
+    /// driver the new route
+    pub async fn approve_dropoff_change(&self, job_id: &JobId, customer_id: &UserId, request: ApproveDropoffChangeRequest) -> Result<JobResponse, AppError> {
+        let now = Utc::now();
+        let mut notes = String::new();
+        // Checked against the stored job as it is swapped, so a dropoff change requested
+        // or a status reached meanwhile isn't overwritten
+        let job = self.cache_service.update_job(job_id, |job| {
+            ensure_redirectable(job, customer_id)?;
+            let change = job.dropoff_change.take()
+                .ok_or_else(|| AppError::NotFound(format!("No dropoff change awaiting approval for job {}", job_id)))?;
+            if change.expires_at <= now {
+                return Err(AppError::Conflict("Dropoff change has expired; request it again".to_string()));
+            }
+            if Money::from_major(request.total, change.pricing.currency) != change.pricing.total {
+                return Err(AppError::Conflict(format!("New total is {}", change.pricing.total)));
+            }
+
+            notes = format!("{} -> {}, total {} -> {}", job.dropoff_location.address, change.dropoff_location.address, change.previous_total, change.pricing.total);
+            job.dropoff_location = change.dropoff_location;
+            job.estimated_distance_km = change.estimated_distance_km;
+            job.estimated_duration_min = change.estimated_duration_min;
+            job.pricing = change.pricing;
+            job.updated_at = now;
+            Ok(())
+        }).await?;
+        self.cache_service.append_job_event(job_id, &JobEvent {
+            event_type: JobEventType::DropoffChanged,
+            timestamp: now,
+            location: None,
+            actor: "customer".to_string(),
+            notes: Some(notes),
+        }).await?;
+
+        if let Some(driver_id) = job.driver_id.clone() {
+            let navigation = self.refresh_navigation(&job).await;
+            let message = NotificationMessage::dropoff_changed(&job, navigation.as_ref());
+            if let Err(e) = self.notification_service.send_to_driver(&driver_id, message).await {
+                tracing::warn!("Failed to notify driver {} of new dropoff for job {}: {}", driver_id, job_id, e);
+            }
+        }
+
+        tracing::info!("Dropoff of job {} changed, new total {}", job_id, job.pricing.total);
+        Ok(self.to_response(job))
+    }
+    
+    // Drop `job_id` from the driver's queue when it is taken off them or cancelled while waiting
+    async fn forget_queued_job(&self, driver_id: &DriverId, job_id: &JobId) -> Result<(), AppError> {
+        let Some(mut driver) = self.cache_service.get_driver(driver_id).await? else {
+            return Ok(());
+        };
+        if driver.queued_job_id.as_ref() == Some(job_id) {
+            driver.queued_job_id = None;
+            driver.updated_at = Utc::now();
+            self.cache_service.cache_driver(&driver).await?;
+        }
+        Ok(())
+    }
+    
+    // After a delivery, send its driver on to the job they queued behind it. Returns that job.
+    async fn start_queued_job(&self, driver_id: &DriverId) -> Result<Option<JobId>, AppError> {
+        let Some(mut driver) = self.cache_service.get_driver(driver_id).await? else {
+            return Ok(None);
+        };
+        let Some(next_id) = driver.queued_job_id.take() else {
+            return Ok(None);
+        };
+        let now = Utc::now();
+        let next = match self.cache_service.load_job(&next_id).await? {
+            Some(next) if next.driver_id.as_ref() == Some(driver_id) && next.status == JobStatus::DriverAssigned => Some(next),
+            _ => None,
+        };
+        driver.current_ride_id = next.as_ref().map(|next| next.id.clone());
+        driver.updated_at = now;
+        self.cache_service.cache_driver(&driver).await?;
+        let Some(mut next) = next else {
+            return Ok(None);
         };
         
-        let subtotal = base_fare + distance_fare + time_fare + package_surcharge + priority_surcharge;
-        let service_fee = subtotal * 0.1; // 10% service fee
-        let tax = subtotal * 0.03; // 3% VAT for Ghana
-        let total = subtotal + service_fee + tax;
+        next.status = JobStatus::DriverEnRoute;
+        next.updated_at = now;
+        self.cache_service.cache_job(&next).await?;
+        self.cache_service.append_job_event(&next.id, &JobEvent {
+            event_type: JobEventType::DriverEnRoute,
+            timestamp: now,
+            location: None,
+            actor: "system".to_string(),
+            notes: Some(format!("Driver {} finished their previous delivery", driver_id)),
+        }).await?;
+        if let Err(e) = self.notification_service.send_to_user(&next.customer_id, NotificationMessage::status_update(&next, "driver_en_route")).await {
+            tracing::warn!("Failed to tell customer driver is en route for job {}: {}", next.id, e);
+        }
+        self.publish_status(&next).await;
         
-        Pricing {
-            base_fare,
-            distance_fare,
-            time_fare,
-            package_surcharge,
-            priority_surcharge,
-            service_fee,
-            tax,
-            total,
-            currency: "GHS".to_string(),
-            estimated_cost: true,
+        tracing::info!("Driver {} started queued job {}", driver_id, next.id);
+        Ok(Some(next.id))
+    }
+    
+    // Use the full location if given, otherwise look up the customer's saved address
+    async fn resolve_location(
+        &self,
+        customer_id: &UserId,
+        location: Option<Location>,
+        address_id: Option<String>,
+        field: &str,
+    ) -> Result<Location, AppError> {
+        match (location, address_id) {
+            (Some(location), None) => Ok(location),
+            (Some(_), Some(_)) => Err(AppError::validation_error(
+                field,
+                "Provide either a location or a saved address ID, not both",
+            )),
+            (None, None) => Err(AppError::MissingRequiredField(field.to_string())),
+            (None, Some(address_id)) => {
+                let customer: User = self.cache_service.load_user(customer_id).await?
+                    .ok_or_else(|| AppError::user_not_found(customer_id.as_str()))?;
+                
+                // Ownership check: the address must be in this customer's own address book, or
+                // the shared one of the business they book for
+                let own = self.cache_service.get_user_addresses(customer_id).await?
+                    .into_iter()
+                    .find(|a| a.id == address_id);
+                let address = match own {
+                    Some(address) => address,
+                    None => self.business_accounts.bookable_address(customer_id, &address_id).await?
+                        .ok_or_else(|| AppError::Forbidden(format!("Address {} does not belong to customer", address_id)))?,
+                };
+                
+                let contact_name = format!("{} {}", customer.first_name, customer.last_name);
+                let contact_phone = format!("{}{}", customer.country_code, customer.phone_number);
+                
+                Location::from_saved_address(&address, contact_name, contact_phone)
+                    .ok_or_else(|| AppError::validation_error(field, "Saved address has no coordinates"))
+            }
         }
     }
-}
-
-#[async_trait]
-impl JobOperations for JobService {
-    async fn create_job(&self, request: JobRequest) -> Result<JobResponse, AppError> {
-        tracing::info!("Creating job for customer: {}", request.customer_id);
+    
+    // Validate the request and price the job without writing anything
+    async fn build_job(&self, request: JobRequest) -> Result<Job, AppError> {
+        let banned = self.cache_service.load_user(&request.customer_id).await?
+            .is_some_and(|customer| customer.ban.is_some());
+        if banned {
+            return Err(AppError::Forbidden("Account is banned".to_string()));
+        }
         
         // Validate customer exists (would come from user service)
         // if !self.user_service.user_exists(&request.customer_id).await? {
         //     return Err(AppError::ValidationError("Customer not found".to_string()));
         // }
         
+        // Resolve saved-address references into full locations
+        let pickup_location = self.resolve_location(
+            &request.customer_id,
+            request.pickup_location,
+            request.pickup_address_id,
+            "pickup_location",
+        ).await?;
+        let dropoff_location = self.resolve_location(
+            &request.customer_id,
+            request.dropoff_location,
+            request.dropoff_address_id,
+            "dropoff_location",
+        ).await?;
+        
+        // Each brand only takes bookings inside its own service zones
+        let tenant = self.tenant_service.current_tenant().await?;
+        for (field, location) in [("pickup_location", &pickup_location), ("dropoff_location", &dropoff_location)] {
+            if !tenant.serves_region(&location.region) {
+                return Err(AppError::validation_error(
+                    field,
+                    format!("{} does not serve region {}", tenant.name, location.region),
+                ));
+            }
+        }
+        
+        // The photo is checked against the declared package before it's priced
+        let package_photo = match request.package_photo_url {
+            Some(url) if url.trim().is_empty() => {
+                return Err(AppError::validation_error("package_photo_url", "Must not be empty"));
+            }
+            Some(url) => Some(self.package_analysis.inspect(url.trim().to_string(), &request.package).await),
+            None => None,
+        };
+        
         // Calculate estimate
         let estimate_request = JobEstimateRequest {
-            pickup_location: request.pickup_location.clone(),
-            dropoff_location: request.dropoff_location.clone(),
+            pickup_location: pickup_location.clone(),
+            dropoff_location: dropoff_location.clone(),
             package: request.package.clone(),
             priority: request.priority.clone(),
+            currency: request.currency,
         };
         
-        let pricing = self.calculate_pricing(&estimate_request).await;
+        let occasion = self.calendar.adjustment_at(Utc::now()).await?;
+        let mut pricing = self.price(&estimate_request, occasion.as_ref()).await?;
+        if let Some(tip) = request.tip {
+            if !tip.is_finite() || tip < 0.0 {
+                return Err(AppError::validation_error("tip", "Must not be negative"));
+            }
+            pricing.tip = Money::from_major(tip, pricing.currency);
+            pricing.total += pricing.tip;
+        }
         
         // Calculate distance and duration
-        let distance_km = self.calculate_distance_km(&request.pickup_location, &request.dropoff_location).await;
+        let distance_km = self.calculate_distance_km(&pickup_location, &dropoff_location).await;
         let duration_min = self.calculate_duration_min(distance_km).await;
         
+        // Express/SameDay jobs carry a delivery guarantee, relaxed on holidays and at peaks
+        let created_at = Utc::now();
+        let extension = occasion.as_ref().map_or_else(chrono::Duration::zero, CalendarAdjustment::sla_extension);
+        let utc_offset_minutes = self.regions.current().utc_offset_minutes;
+        let sla = request.priority.delivery_deadline(created_at, utc_offset_minutes).map(|deadline| DeliverySla::new(deadline + extension));
+        
         // Create job with our ID generator
-        let mut job = Job {
-            id: String::new(), // Will be set by with_generated_id
+        let job = Job {
+            id: JobId::generate(),
+            tenant_id: current_tenant_id(),
+            region: current_region_id(),
             customer_id: request.customer_id,
             driver_id: None,
             status: JobStatus::Pending,
             priority: request.priority,
-            pickup_location: request.pickup_location,
-            dropoff_location: request.dropoff_location,
+            pickup_location,
+            dropoff_location,
             estimated_distance_km: distance_km,
             estimated_duration_min: duration_min,
             package: request.package,
-            created_at: Utc::now(),
+            package_photo,
+            created_at,
             accepted_at: None,
             pickup_time: None,
             dropoff_time: None,
             cancelled_at: None,
-            expires_at: Utc::now() + chrono::Duration::hours(2),
+            expires_at: created_at + chrono::Duration::hours(2),
+            sla,
+            escalation: None,
+            recipient_preferences: None,
+            pool_id: None,
+            delivery_code: None,
+            dropoff_change: None,
+            arrival_calls: request.arrival_calls,
+            frozen_by: None,
+            anonymized_at: None,
             pricing,
+            commission_rate: None,
             payment_method_id: request.payment_method_id,
             payment_status: crate::models::job::PaymentStatus::Pending,
             tracking_code: IdGenerator::generate(IdType::Job).replace("job-", "GH"), // Clean tracking code
@@ -201,94 +826,238 @@ impl JobOperations for JobService {
             updated_at: Utc::now(),
         };
         
-        // Use our WithGeneratedId trait to set the ID
-        job.set_generated_id(IdType::Job);
-        
+        Ok(job)
+    }
+    
+    async fn persist_job(&self, job: &Job) -> Result<(), AppError> {
         // Cache the job
-        self.cache_service.cache_job(&job).await?;
+        self.cache_service.cache_job(job).await?;
         
         // Add to customer's job list
-        self.cache_service.cache_customer_job(&job.customer_id, &job.id).await?;
-        
-        tracing::info!("Job created successfully: {} - {} GHS", job.id, job.pricing.total);
+        self.cache_service.cache_customer_job(job).await?;
         
-        Ok(self.to_response(job))
+        // Track as open so background workers can find it
+        self.cache_service.add_active_job(&job.id).await?;
+        self.cache_service.add_job_to_day(job).await?;
+        self.cache_service.cache_tracking_code(job).await?;
+        Ok(())
     }
     
-    async fn get_job(&self, job_id: &str) -> Result<Option<JobResponse>, AppError> {
-        // Validate ID format first
-        if !IdGenerator::validate_id(job_id, Some(IdType::Job)) {
-            tracing::warn!("Invalid job ID format: {}", job_id);
-            return Ok(None);
+    // Roll back jobs written by a bulk import that could not finish
+    async fn discard_jobs(&self, jobs: &[Job]) {
+        for job in jobs {
+            if let Err(e) = self.cache_service.discard_job(job).await {
+                tracing::error!("Failed to roll back job {}: {}", job.id, e);
+            }
         }
+    }
+    
+    async fn announce_created(&self, job: &Job) {
+        self.events.publish(DomainEvent::JobCreated { job_id: job.id.clone(), customer_id: job.customer_id.clone() }).await;
+    }
+    
+    async fn calculate_distance_km(&self, loc1: &Location, loc2: &Location) -> f64 {
+        // Simple haversine formula implementation
+        // In production, you'd use a proper geocoding service
+        let earth_radius_km = 6371.0;
+        let lat1_rad = loc1.latitude.to_radians();
+        let lat2_rad = loc2.latitude.to_radians();
+        let delta_lat = (loc2.latitude - loc1.latitude).to_radians();
+        let delta_lon = (loc2.longitude - loc1.longitude).to_radians();
         
-        tracing::debug!("Getting job: {}", job_id);
-        let key = CacheKey::Simple(job_id.to_string());
-        // Try cache first
-        if let Some(job) = self.cache_service.get_job(&key).await? {
-            return Ok(Some(self.to_response(job)));
-        }
+        let a = (delta_lat / 2.0).sin().powi(2) +
+               lat1_rad.cos() * lat2_rad.cos() * (delta_lon / 2.0).sin().powi(2);
+        let c = 2.0 * a.sqrt().atan2((1.0 - a).sqrt());
         
-        Ok(None)
+        earth_radius_km * c
     }
     
-    async fn get_jobs_by_customer(&self, customer_id: &str) -> Result<Vec<JobResponse>, AppError> {
-        tracing::debug!("Getting jobs for customer: {}", customer_id);
+    async fn calculate_duration_min(&self, distance_km: f64) -> i32 {
+        // Estimate duration based on Ghana traffic patterns
+        // Average speed: 25 km/h in urban areas, 40 km/h rural
+        let average_speed_kmh = 30.0; // Conservative estimate for Ghana
+        ((distance_km / average_speed_kmh) * 60.0) as i32
+    }
+    
+    // Price with the tenant's rates in the requested currency, today's taxes and any
+    // holiday or peak surcharge
+    async fn price(&self, request: &JobEstimateRequest, occasion: Option<&CalendarAdjustment>) -> Result<Pricing, AppError> {
+        let tenant = self.tenant_service.current_tenant().await?;
+        let region = self.regions.current();
+        let rates = pricing_in(&tenant, region, request.currency)?;
+        let surcharges = region.surcharges_for(rates);
+        let taxes = self.tax_engine.schedule_at(Utc::now()).await?;
+        let price_multiplier = occasion.map_or(1.0, |occasion| occasion.price_multiplier);
+        Ok(self.calculate_pricing(request, rates, &surcharges, price_multiplier, &taxes).await)
+    }
+    
+    // `surcharges` are in the rates' currency; `price_multiplier` above 1 adds the difference
+    // as the holiday surcharge
+    async fn calculate_pricing(&self, request: &JobEstimateRequest, rates: &PricingConfig, surcharges: &Surcharges, price_multiplier: f64, taxes: &TaxSchedule) -> Pricing {
+        let distance_km = self.calculate_distance_km(&request.pickup_location, &request.dropoff_location).await;
+        let duration_min = self.calculate_duration_min(distance_km).await;
         
-        let job_ids = self.cache_service.get_customer_jobs(customer_id).await?;
-        let mut jobs = Vec::new();
+        // Base and metered rates come from the tenant's pricing config
+        let base_fare = match request.priority {
+            JobPriority::Standard => rates.base_fare_standard,
+            JobPriority::Express => rates.base_fare_express,
+            JobPriority::SameDay => rates.base_fare_same_day,
+            JobPriority::Emergency => rates.base_fare_emergency,
+        };
         
-        for job_id in job_ids {
-            if let Some(job) = self.get_job(&job_id).await? {
-                jobs.push(job);
-            }
+        let distance_fare = distance_km * rates.per_km;
+        let time_fare = (duration_min as f64) * rates.per_minute;
+        let package_surcharge = surcharges.for_package(&request.package.package_type);
+        let priority_surcharge = surcharges.for_priority(&request.priority);
+        
+        // Each component is rounded to the minor unit once, so the parts add up to the total
+        let money = |amount: f64| Money::from_major(amount, rates.currency);
+        let (base_fare, distance_fare, time_fare) = (money(base_fare), money(distance_fare), money(time_fare));
+        let (package_surcharge, priority_surcharge) = (money(package_surcharge), money(priority_surcharge));
+        
+        let fare = base_fare + distance_fare + time_fare + package_surcharge + priority_surcharge;
+        let holiday_surcharge = fare.times(price_multiplier - 1.0);
+        let subtotal = fare + holiday_surcharge;
+        let service_fee = subtotal.times(rates.service_fee_rate);
+        let tax_lines = taxes.apply(subtotal);
+        let tax = Money::sum(rates.currency, tax_lines.iter().map(|line| &line.amount));
+        let total = subtotal + service_fee + tax;
+        
+        Pricing {
+            base_fare,
+            distance_fare,
+            time_fare,
+            package_surcharge,
+            priority_surcharge,
+            holiday_surcharge,
+            service_fee,
+            tax,
+            tax_lines,
+            tip: Money::zero(rates.currency),
+            discount: Money::zero(rates.currency),
+            total,
+            currency: rates.currency,
+            estimated_cost: true,
         }
+    }
+}
+
+#[async_trait]
+impl JobOperations for JobService {
+    async fn create_job(&self, request: JobRequest) -> Result<JobResponse, AppError> {
+        tracing::info!("Creating job for customer: {}", request.customer_id);
+        
+        let job = self.build_job(request).await?;
+        self.persist_job(&job).await?;
+        self.announce_created(&job).await;
         
-        // Sort by creation date (newest first)
-        jobs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        tracing::info!("Job created successfully: {} - {}", job.id, job.pricing.total);
         
-        Ok(jobs)
+        Ok(self.to_response(job))
     }
     
-    async fn get_jobs_by_driver(&self, driver_id: &str) -> Result<Vec<JobResponse>, AppError> {
-        tracing::debug!("Getting jobs for driver: {}", driver_id);
+    async fn get_job(&self, job_id: &JobId) -> Result<Option<JobResponse>, AppError> {
+        tracing::debug!("Getting job: {}", job_id);
+        // Cache first, then the repository
+        if let Some(job) = self.cache_service.load_job(job_id).await? {
+            return Ok(Some(self.to_response(job)));
+        }
         
-        // Validate driver ID format
-        if !IdGenerator::validate_id(driver_id, Some(IdType::Driver)) {
-            return Err(AppError::ValidationFailed(vec![ValidationError {
-                field: "driver_id".to_string(),
-                message: "Invalid driver ID format".to_string(),
-            }]));
+        Ok(None)
+    }
+    
+    async fn get_jobs_by_customer(&self, customer_id: &UserId, query: JobHistoryQuery) -> Result<JobHistoryPage, AppError> {
+        tracing::debug!("Getting jobs for customer: {}", customer_id);
+        let (after, limit) = self.history_page(&query)?;
+        let job_ids = self.cache_service.get_customer_job_page(customer_id, after.as_ref(), limit + 1).await?;
+        self.load_history_page(job_ids, limit).await
+    }
+    
+    async fn get_available_jobs(&self, driver_id: &DriverId) -> Result<Vec<AvailableJob>, AppError> {
+        let driver = self.cache_service.get_driver(driver_id).await?
+            .ok_or_else(|| AppError::driver_not_found(driver_id.as_str()))?;
+        // Nothing is offered to a driver on a break, suspended or not yet approved
+        if !driver.can_take_work() {
+            return Ok(Vec::new());
         }
         
-        let job_ids = self.cache_service.get_driver_jobs(driver_id).await?;
-        let mut jobs = Vec::new();
+        let position = match self.cache_service.get_driver_location(driver_id).await? {
+            Some(live) => (live.latitude, live.longitude),
+            None => match &driver.current_location {
+                Some(location) => (location.latitude, location.longitude),
+                None => return Err(AppError::Conflict("Driver location is unknown".to_string())),
+            },
+        };
         
-        for job_id in job_ids {
-            if let Some(job) = self.get_job(&job_id).await? {
-                jobs.push(job);
+        let now = Utc::now();
+        let mut available = Vec::new();
+        for job_id in self.cache_service.get_active_jobs().await? {
+            let Some(job) = self.cache_service.load_job(&job_id).await? else {
+                continue;
+            };
+            let open = job.driver_id.is_none() && matches!(job.status, JobStatus::Pending | JobStatus::Searching);
+            if !open || !driver.can_carry(&job.package) || job.rejected_by_drivers.contains(driver_id) {
+                continue;
+            }
+            if self.cache_service.is_pair_blocked(driver_id, &job.customer_id).await? {
+                continue;
             }
+            
+            let offered = job.offered_to_drivers.contains(driver_id);
+            let distance_to_pickup_km = haversine_km(position, (job.pickup_location.latitude, job.pickup_location.longitude));
+            if !offered && distance_to_pickup_km > self.dispatch_settings.config_for(&job, now).await.search_radius_for(&job) {
+                continue;
+            }
+            
+            let earnings = self.earnings.preview(&job, &driver.vehicle.vehicle_type).await;
+            available.push(AvailableJob {
+                offered,
+                estimated_earnings: earnings.total.to_major(),
+                earnings,
+                distance_to_pickup_km,
+                time_to_pickup_min: self.calculate_duration_min(distance_to_pickup_km).await,
+                customer_rating: None, // Customers aren't rated yet
+                job: self.to_response(job),
+            });
         }
         
-        // Sort by creation date (newest first)
-        jobs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        // Offers first, then closest pickup
+        available.sort_by(|a, b| {
+            b.offered.cmp(&a.offered).then_with(|| {
+                a.distance_to_pickup_km
+                    .partial_cmp(&b.distance_to_pickup_km)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+        });
         
-        Ok(jobs)
+        Ok(available)
+    }
+    
+    async fn get_jobs_by_driver(&self, driver_id: &DriverId, query: JobHistoryQuery) -> Result<JobHistoryPage, AppError> {
+        tracing::debug!("Getting jobs for driver: {}", driver_id);
+        let (after, limit) = self.history_page(&query)?;
+        let job_ids = self.cache_service.get_driver_job_page(driver_id, after.as_ref(), limit + 1).await?;
+        self.load_history_page(job_ids, limit).await
     }
     
     async fn update_job_status(&self, update: JobStatusUpdate) -> Result<JobResponse, AppError> {
-        // Validate job ID format
-        if !IdGenerator::validate_id(&update.job_id, Some(IdType::Job)) {
-            return Err(AppError::ValidationFailed(vec![ValidationError {
-                field: "job_id".to_string(),
-                message: "Invalid job ID format".to_string(),
-            }]));
-        }
-        
         tracing::info!("Updating job status: {} to {:?}", update.job_id, update.status);
         
-        let mut job: Job = self.cache_service.get_job(&CacheKey::Simple(update.job_id.clone())).await?
+        // Expiry releases payment and tells the customer, however it is triggered
+        if update.status == JobStatus::Expired {
+            return self.expire_job(&update.job_id, "status_update").await;
+        }
+        // Likewise completion settles payment and announces the delivery
+        if update.status == JobStatus::DeliveryCompleted {
+            return self.complete_job(&update.job_id).await;
+        }
+        
+        let mut job: Job = self.cache_service.load_job(&update.job_id).await?
             .ok_or_else(|| AppError::NotFound("Job not found".to_string()))?;
+        ensure_not_frozen(&job)?;
+        let assigned_driver = job.driver_id.clone();
+        // Sent by the assigned driver themselves
+        let by_assigned_driver = update.driver_id.is_some() && update.driver_id == assigned_driver;
         
         // Update status and timestamp
         job.status = update.status;
@@ -302,58 +1071,113 @@ impl JobOperations for JobService {
             JobStatus::PackagePickedUp => {
                 job.pickup_time = Some(Utc::now());
             }
-            JobStatus::DeliveryCompleted => {
-                job.dropoff_time = Some(Utc::now());
-            }
             JobStatus::Cancelled => {
                 job.cancelled_at = Some(Utc::now());
             }
+            JobStatus::ArrivedAtDropoff if job.delivery_code.is_none() && self.delivery_codes.required(&job) => {
+                // Completing sends it again if this one doesn't go out
+                match self.delivery_codes.send(&job).await {
+                    Ok(code) => {
+                        job.delivery_code = Some(code);
+                        self.record_delivery_code_sent(&job).await;
+                    }
+                    Err(e) => tracing::warn!("Failed to send delivery code for job {}: {}", job.id, e),
+                }
+            }
             _ => {}
         }
         
+        if job.status.is_terminal() {
+            self.cache_service.remove_active_job(&job.id).await?;
+        }
+        
         // Update driver if provided
         if let Some(driver_id) = update.driver_id {
-            if !IdGenerator::validate_id(&driver_id, Some(IdType::Driver)) {
-                return Err(AppError::ValidationFailed(vec![ValidationError {
-                    field: "driver_id".to_string(),
-                    message: "Invalid driver ID format".to_string(),
-                }]));
-            }
             job.driver_id = Some(driver_id);
         }
         
         // Update cache
         self.cache_service.cache_job(&job).await?;
+        self.publish_status(&job).await;
+        match job.status {
+            JobStatus::ArrivedAtPickup => self.call_on_arrival(&job, StopKind::Pickup).await,
+            JobStatus::ArrivedAtDropoff => self.call_on_arrival(&job, StopKind::Dropoff).await,
+            _ => {}
+        }
+        
+        // The customer's push and the driver's reliability score follow from these
+        match job.status {
+            JobStatus::PackagePickedUp => {
+                self.events.publish(DomainEvent::JobPickedUp { job_id: job.id.clone(), driver_id: assigned_driver }).await;
+            }
+            JobStatus::Cancelled => {
+                self.events.publish(DomainEvent::JobCancelled {
+                    job_id: job.id.clone(),
+                    customer_id: job.customer_id.clone(),
+                    driver_id: assigned_driver,
+                    by_driver: by_assigned_driver,
+                }).await;
+            }
+            _ => {}
+        }
         
         tracing::debug!("Job status updated successfully: {}", job.id);
         
         Ok(self.to_response(job))
     }
     
-    async fn assign_driver_to_job(&self, job_id: &str, driver_id: &str) -> Result<JobResponse, AppError> {
-        // Validate IDs
-        if !IdGenerator::validate_id(job_id, Some(IdType::Job)) {
-            return Err(AppError::validation_error("job_id", "Invalid job ID format"));
-        }
-        if !IdGenerator::validate_id(driver_id, Some(IdType::Driver)) {
-            return Err(AppError::validation_error("driver_id", "Invalid driver ID format"));
-        }
-        
+    async fn assign_driver_to_job(&self, job_id: &JobId, driver_id: &DriverId) -> Result<JobResponse, AppError> {
         tracing::info!("Assigning driver {} to job {}", driver_id, job_id);
         
-        let mut job: Job = self.cache_service.get_job(&CacheKey::Simple(job_id.to_string())).await?
+        let mut job: Job = self.cache_service.load_job(job_id).await?
             .ok_or_else(|| AppError::NotFound("Job not found".to_string()))?;
+        ensure_not_frozen(&job)?;
         
-        let driver: User = self.cache_service.get_user(&CacheKeys::driver_by_id(driver_id)).await?
+        let mut driver = self.cache_service.get_driver(driver_id).await?
             .ok_or_else(|| AppError::NotFound("Driver not found".to_string()))?;
         
         // Check if driver is available
         // if driver.status != crate::models::driver::DriverStatus::Online {
         //     return Err(AppError::ValidationError("Driver is not available".to_string()));
         // }
+        if driver.is_on_break() || !driver.is_active {
+            return Err(AppError::DriverNotAvailable);
+        }
+        if self.cache_service.is_pair_blocked(driver_id, &job.customer_id).await? {
+            return Err(AppError::Conflict(format!("Driver {} can't be assigned to this customer's jobs", driver_id)));
+        }
+        if !driver.equipment.covers(&job.package.handling()) {
+            return Err(AppError::Conflict(format!("Driver {} doesn't have the equipment this package needs", driver_id)));
+        }
+        // A pooled job brings the rest of its pool, so the driver has to carry them all
+        if let Some(pool_id) = &job.pool_id
+            && let Some(pool) = self.cache_service.get_job_pool(pool_id).await?
+            && pool.combined_weight_kg > driver.vehicle.capacity_kg
+        {
+            return Err(AppError::Conflict(format!("Driver {} can't carry every package in pool {}", driver_id, pool_id)));
+        }
+        // A driver with a package on board takes this one next, starting once they drop off
+        let chained = self.current_delivery(driver_id, job_id).await?.is_some();
+        if chained && let Some(queued) = driver.queued_job_id.as_ref().filter(|queued| *queued != job_id) {
+            let waiting = self.cache_service.load_job(queued).await?
+                .is_some_and(|queued| queued.driver_id.as_ref() == Some(driver_id) && queued.status == JobStatus::DriverAssigned);
+            if waiting {
+                return Err(AppError::Conflict(format!("Driver {} already has a next job queued", driver_id)));
+            }
+        }
+        
+        // A manual assignment counts as an offer the driver took
+        let offered = job.offered_to_drivers.contains(driver_id);
+        if !offered {
+            job.offered_to_drivers.push(driver_id.clone());
+        }
+        
+        // The rate shown to the driver is the one they're paid at
+        let earnings = self.earnings.preview(&job, &driver.vehicle.vehicle_type).await;
         
         // Update job
-        job.driver_id = Some(driver_id.to_string());
+        job.driver_id = Some(driver_id.clone());
+        job.commission_rate = Some(earnings.commission_rate);
         job.status = JobStatus::DriverAssigned;
         job.accepted_at = Some(Utc::now());
         job.updated_at = Utc::now();
@@ -363,51 +1187,94 @@ impl JobOperations for JobService {
         
         // Update cache
         self.cache_service.cache_job(&job).await?;
-        self.cache_service.cache_driver_job(driver_id, job_id).await?;
+        self.cache_service.cache_driver_job(driver_id, &job).await?;
+        if chained {
+            driver.queued_job_id = Some(job_id.clone());
+            driver.updated_at = Utc::now();
+            self.cache_service.cache_driver(&driver).await?;
+        }
+        // The driver's push and their reliability score follow from this
+        self.events.publish(DomainEvent::JobAssigned { job_id: job_id.clone(), driver_id: driver_id.clone(), offered }).await;
+        
+        self.publish_status(&job).await;
+        if let Some(pool_id) = &job.pool_id {
+            self.assign_pool_siblings(pool_id, job_id, driver_id).await?;
+        }
         
         tracing::info!("Driver {} assigned to job {}", driver_id, job_id);
         
         Ok(self.to_response(job))
     }
     
-    async fn calculate_estimate(&self, request: JobEstimateRequest) -> Result<Pricing, AppError> {
+    async fn calculate_estimate(&self, request: JobEstimateRequest) -> Result<JobEstimate, AppError> {
         tracing::debug!("Calculating estimate for delivery request");
         
-        let pricing = self.calculate_pricing(&request).await;
+        let occasion = self.calendar.adjustment_at(Utc::now()).await?;
+        let pricing = self.price(&request, occasion.as_ref()).await?;
+        let handling = request.package.handling();
+        
+        // Quoted either way; the customer decides whether to book
+        let mut warnings = Vec::new();
+        if let Some(max_transit_minutes) = handling.max_transit_minutes {
+            let distance_km = self.calculate_distance_km(&request.pickup_location, &request.dropoff_location).await;
+            let duration_min = self.calculate_duration_min(distance_km).await;
+            if duration_min > max_transit_minutes {
+                warnings.push(format!(
+                    "The trip takes about {} minutes, longer than this package should spend in transit ({} minutes)",
+                    duration_min, max_transit_minutes
+                ));
+            }
+        }
+        if handling.insulated_bag || handling.upright_only {
+            let pickup = &request.pickup_location;
+            let config = self.dispatch_settings.config_in(&pickup.region, Utc::now()).await;
+            let equipped = self.driver_service.find_dispatch_candidates(
+                pickup.latitude,
+                pickup.longitude,
+                config.search_radius_km,
+                config.max_candidates * 2,
+                &handling,
+            ).await?;
+            if equipped.is_empty() {
+                warnings.push("No driver nearby has the equipment this package needs, so finding one may take longer".to_string());
+            }
+        }
         
-        Ok(pricing)
+        Ok(JobEstimate { pricing, handling, warnings, occasion })
     }
     
-    async fn find_available_drivers(&self, job_id: &str) -> Result<Vec<String>, AppError> {
+    async fn find_available_drivers(&self, job_id: &JobId) -> Result<Vec<DriverId>, AppError> {
         tracing::debug!("Finding available drivers for job: {}", job_id);
         
-        let job: Job = self.cache_service.get_job(&CacheKey::Simple(job_id.to_string())).await?
+        let job: Job = self.cache_service.load_job(job_id).await?
             .ok_or_else(|| AppError::NotFound("Job not found".to_string()))?;
         
-        // Find nearby online drivers
-        let nearby_drivers = self.driver_service.find_nearby_drivers(
+        // Look past the closest few so reliability has room to reorder them
+        let config = self.dispatch_settings.config_for(&job, Utc::now()).await;
+        let candidates = self.driver_service.find_dispatch_candidates(
             job.pickup_location.latitude,
             job.pickup_location.longitude,
-            10.0, // 10km radius
-            10,   // max 10 drivers
+            config.search_radius_for(&job),
+            config.max_candidates * 2,
+            &job.package.handling(),
         ).await?;
         
-        let driver_ids = nearby_drivers.into_iter()
-            .map(|driver| driver.id)
-            .collect();
+        let mut driver_ids = Vec::new();
+        for candidate in rank_candidates(candidates, &config) {
+            if !self.cache_service.is_pair_blocked(&candidate.driver_id, &job.customer_id).await? {
+                driver_ids.push(candidate.driver_id);
+            }
+        }
         
         Ok(driver_ids)
     }
     
-    async fn cancel_job(&self, job_id: &str, reason: Option<String>) -> Result<JobResponse, AppError> {
-        if !IdGenerator::validate_id(job_id, Some(IdType::Job)) {
-            return Err(AppError::validation_error("job_id", "Invalid job ID format"));
-        }
-        
+    async fn cancel_job(&self, job_id: &JobId, reason: Option<String>) -> Result<JobResponse, AppError> {
         tracing::info!("Cancelling job: {}", job_id);
         
-        let mut job: Job = self.cache_service.get_job(&CacheKey::Simple(job_id.to_string())).await?
+        let mut job: Job = self.cache_service.load_job(job_id).await?
             .ok_or_else(|| AppError::NotFound("Job not found".to_string()))?;
+        ensure_not_frozen(&job)?;
         
         job.status = JobStatus::Cancelled;
         job.cancelled_at = Some(Utc::now());
@@ -416,48 +1283,636 @@ impl JobOperations for JobService {
         
         // Update cache
         self.cache_service.cache_job(&job).await?;
+        self.cache_service.remove_active_job(job_id).await?;
+        self.publish_status(&job).await;
         
         // If job had a driver assigned, update driver status
         if let Some(driver_id) = &job.driver_id {
             self.cache_service.remove_driver_job(driver_id, job_id).await?;
+            self.forget_queued_job(driver_id, job_id).await?;
         }
+        self.events.publish(DomainEvent::JobCancelled {
+            job_id: job_id.clone(),
+            customer_id: job.customer_id.clone(),
+            driver_id: job.driver_id.clone(),
+            by_driver: false,
+        }).await;
         
         tracing::info!("Job cancelled: {}", job_id);
         
         Ok(self.to_response(job))
     }
     
-    async fn complete_job(&self, job_id: &str) -> Result<JobResponse, AppError> {
-        if !IdGenerator::validate_id(job_id, Some(IdType::Job)) {
-            return Err(AppError::ValidationFailed(vec![ValidationError {
-                field: "job_id".to_string(),
-                message: "Invalid job ID format".to_string(),
-            }]));
+    async fn complete_job(&self, job_id: &JobId) -> Result<JobResponse, AppError> {
+        tracing::info!("Completing job: {}", job_id);
+        
+        let mut job: Job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::NotFound("Job not found".to_string()))?;
+        ensure_not_frozen(&job)?;
+        let not_carrying = |job: &Job| AppError::Conflict(format!("Job {} can't be completed while it is {:?}", job_id, job.status));
+        if !job.status.is_carrying_package() {
+            return Err(not_carrying(&job));
         }
+        self.require_delivery_code(&mut job).await?;
         
-        tracing::info!("Completing job: {}", job_id);
+        // Merchants on monthly invoicing pay for it with the rest of the month's jobs
+        let payment_status = if self.invoices.bills_monthly(&job.customer_id).await? {
+            PaymentStatus::Invoiced
+        } else {
+            PaymentStatus::Paid
+        };
+        // Checked again as it is written, so of two completions racing only one settles
+        let job = self.cache_service.update_job(job_id, |current| {
+            if !current.status.is_carrying_package() {
+                return Err(not_carrying(current));
+            }
+            let now = Utc::now();
+            current.status = JobStatus::DeliveryCompleted;
+            current.dropoff_time = Some(now);
+            current.updated_at = now;
+            current.payment_status = payment_status.clone();
+            Ok(())
+        }).await?;
+        self.cache_service.remove_active_job(job_id).await?;
+        // The SLA monitor only scans active jobs; it checks this one for lateness next time round
+        if job.sla.is_some() {
+            self.cache_service.add_completed_sla_job(job_id).await?;
+        }
+        self.publish_status(&job).await;
+        
+        // Update driver stats
+        // if let Some(driver_id) = &job.driver_id {
+        //     if let Some(mut driver) = self.cache_service.get_driver(driver_id).await? {
+        //         driver.total_rides += 1;
+        //         self.cache_service.cache_driver(&driver).await?;
+        //     }
+        // }
         
-        let mut job: Job = self.cache_service.get_job(&CacheKey::Simple(job_id.to_string())).await?
+        // Announced straight away: the customer hears about the delivery whatever the books do
+        self.events.publish(DomainEvent::JobCompleted {
+            job_id: job.id.clone(),
+            customer_id: job.customer_id.clone(),
+            driver_id: job.driver_id.clone(),
+        }).await;
+        if job.payment_status == PaymentStatus::Paid {
+            self.events.publish(DomainEvent::PaymentCaptured {
+                job_id: job.id.clone(),
+                customer_id: job.customer_id.clone(),
+                amount: job.pricing.total,
+            }).await;
+        }
+        
+        // Settling and billing; a step that fails is retried by the saga recovery worker, and
+        // one that keeps failing is left for an operator
+        match self.sagas.run(&self.completion, job_id.as_str(), job_id).await {
+            Ok(saga) if saga.status != SagaStatus::Completed => {
+                let error = saga.steps.iter().find_map(|step| step.last_error.as_deref()).unwrap_or("no error");
+                tracing::warn!("Settlement of job {} is {:?} after {}", job_id, saga.status, error);
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Failed to run the settlement of job {}: {}", job_id, e),
+        }
+        if let Some(driver_id) = &job.driver_id
+            && let Err(e) = self.start_queued_job(driver_id).await
+        {
+            tracing::warn!("Failed to start driver {}'s next job after {}: {}", driver_id, job_id, e);
+        }
+        
+        tracing::info!("Job completed: {}", job_id);
+        
+        Ok(self.to_response(job))
+    }
+    
+    async fn expire_job(&self, job_id: &JobId, reason: &str) -> Result<JobResponse, AppError> {
+        let mut job: Job = self.cache_service.load_job(job_id).await?
             .ok_or_else(|| AppError::NotFound("Job not found".to_string()))?;
         
-        job.status = JobStatus::DeliveryCompleted;
-        job.dropoff_time = Some(Utc::now());
-        job.updated_at = Utc::now();
-        job.payment_status = crate::models::job::PaymentStatus::Paid;
+        if job.status == JobStatus::Expired {
+            return Ok(self.to_response(job));
+        }
+        if job.status.is_terminal() {
+            return Err(AppError::Conflict(format!("Job {} is already {:?}", job_id, job.status)));
+        }
+        
+        let now = Utc::now();
+        job.status = JobStatus::Expired;
+        job.updated_at = now;
+        // Nothing was delivered: drop the hold, or give the money back if it was captured
+        job.payment_status = match job.payment_status {
+            PaymentStatus::Authorized => PaymentStatus::Voided,
+            PaymentStatus::Paid => PaymentStatus::Refunded,
+            status => status,
+        };
         
-        // Update cache
         self.cache_service.cache_job(&job).await?;
+        self.cache_service.remove_active_job(job_id).await?;
+        self.cache_service.append_job_event(job_id, &JobEvent {
+            event_type: JobEventType::JobExpired,
+            timestamp: now,
+            location: None,
+            actor: "system".to_string(),
+            notes: Some(reason.to_string()),
+        }).await?;
+        self.publish_status(&job).await;
+        
+        // Best-effort, like the other lifecycle pushes
+        let language = match self.cache_service.load_user(&job.customer_id).await {
+            Ok(Some(customer)) => customer.language,
+            _ => default_language(),
+        };
+        let message = NotificationMessage::job_expired(&job, &language, reason);
+        if let Err(e) = self.notification_service.send_to_user(&job.customer_id, message).await {
+            tracing::warn!("Failed to notify customer of expired job {}: {}", job_id, e);
+        }
         
-        // Update driver stats
-        if let Some(driver_id) = &job.driver_id {
-            // if let Some(mut driver) = self.cache_service.get_driver(driver_id).await? {
-            //     driver.total_rides += 1;
-            //     self.cache_service.cache_driver(&driver).await?;
-            // }
+        tracing::info!("Job expired: {} ({})", job_id, reason);
+        
+        Ok(self.to_response(job))
+    }
+    
+    async fn reassign_unresponsive_job(&self, job_id: &JobId, driver_id: &DriverId) -> Result<JobResponse, AppError> {
+        let mut job: Job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::NotFound("Job not found".to_string()))?;
+        
+        // The driver may have been replaced, or picked up, since the watchdog looked
+        if job.driver_id.as_ref() != Some(driver_id) || !job.status.is_awaiting_pickup() {
+            return Err(AppError::Conflict(format!(
+                "Job {} is no longer waiting on driver {}",
+                job_id, driver_id
+            )));
         }
         
-        tracing::info!("Job completed: {}", job_id);
+        self.release_driver(&mut job, driver_id, JobEventType::DriverUnresponsive, None).await?;
+        self.driver_service.record_unresponsive(driver_id, job_id).await?;
+        
+        // Best-effort, like the other lifecycle pushes
+        let message = NotificationMessage::driver_reassigning(&job);
+        if let Err(e) = self.notification_service.send_to_user(&job.customer_id, message).await {
+            tracing::warn!("Failed to notify customer of reassignment for job {}: {}", job_id, e);
+        }
+        
+        tracing::info!("Job {} back to searching after driver {} went unresponsive", job_id, driver_id);
+        
+        Ok(self.to_response(job))
+    }
+    
+    async fn escalate_job(&self, job_id: &JobId, escalation: JobEscalation) -> Result<JobResponse, AppError> {
+        let notes = format!(
+            "Level {}: dispatched as {}, search radius x{}, driver bonus {}",
+            escalation.level, escalation.priority, escalation.radius_multiplier, escalation.payout_bonus,
+        );
+        let (new_level, now) = (escalation.level, escalation.escalated_at);
+        let job = self.cache_service.update_job(job_id, |job| {
+            // A driver may have taken the job since the worker looked
+            let unassigned = job.driver_id.is_none() && matches!(job.status, JobStatus::Pending | JobStatus::Searching);
+            if !unassigned {
+                return Err(AppError::Conflict(format!("Job {} is not waiting for a driver", job_id)));
+            }
+            let level = job.escalation.as_ref().map_or(0, |current| current.level);
+            if escalation.level <= level {
+                return Err(AppError::Conflict(format!("Job {} is already at escalation level {}", job_id, level)));
+            }
+            job.escalation = Some(escalation.clone());
+            job.updated_at = now;
+            Ok(())
+        }).await?;
+        
+        self.cache_service.append_job_event(job_id, &JobEvent {
+            event_type: JobEventType::PriorityEscalated,
+            timestamp: now,
+            location: None,
+            actor: "system".to_string(),
+            notes: Some(notes),
+        }).await?;
+        
+        tracing::info!("Job {} escalated to level {}", job_id, new_level);
+        
+        Ok(self.to_response(job))
+    }
+    
+    async fn unassign_driver(&self, job_id: &JobId, reason: Option<String>) -> Result<JobResponse, AppError> {
+        let mut job: Job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::NotFound("Job not found".to_string()))?;
+        ensure_not_frozen(&job)?;
+        
+        let Some(driver_id) = job.driver_id.clone() else {
+            return Err(AppError::Conflict(format!("Job {} has no driver assigned", job_id)));
+        };
+        if !job.status.is_awaiting_pickup() {
+            return Err(AppError::Conflict(format!(
+                "Job {} cannot be unassigned once {:?}",
+                job_id, job.status
+            )));
+        }
+        
+        self.release_driver(&mut job, &driver_id, JobEventType::DriverUnassigned, reason).await?;
+        
+        let message = NotificationMessage::job_unassigned(&job);
+        if let Err(e) = self.notification_service.send_to_driver(&driver_id, message).await {
+            tracing::warn!("Failed to notify driver {} of unassignment from job {}: {}", driver_id, job_id, e);
+        }
+        
+        tracing::info!("Driver {} unassigned from job {}", driver_id, job_id);
         
         Ok(self.to_response(job))
     }
+    
+    async fn create_jobs_bulk(&self, merchant_id: &UserId, requests: Vec<JobRequest>) -> Result<BulkJobResponse, AppError> {
+        if requests.is_empty() {
+            return Err(AppError::validation_error("jobs", "At least one job is required"));
+        }
+        if requests.len() > MAX_BULK_JOBS {
+            return Err(AppError::validation_error(
+                "jobs",
+                format!("At most {} jobs are allowed per import", MAX_BULK_JOBS),
+            ));
+        }
+        
+        tracing::info!("Bulk importing {} jobs", requests.len());
+        
+        // Validate and price every row before writing any of them
+        let mut jobs = Vec::with_capacity(requests.len());
+        let mut errors = Vec::new();
+        for (index, mut request) in requests.into_iter().enumerate() {
+            // Every row is booked on the importing merchant's account, whoever it names
+            request.customer_id = merchant_id.clone();
+            match self.build_job(request).await {
+                Ok(job) => jobs.push(job),
+                Err(e) => errors.push(ValidationError {
+                    field: format!("jobs[{}]", index),
+                    message: e.to_string(),
+                }),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(AppError::ValidationFailed(errors));
+        }
+        
+        // All or nothing: a failed write rolls back the rows already written
+        for (index, job) in jobs.iter().enumerate() {
+            if let Err(e) = self.persist_job(job).await {
+                tracing::error!("Bulk import failed at row {}: {}", index, e);
+                self.discard_jobs(&jobs[..=index]).await;
+                return Err(e);
+            }
+        }
+        
+        let batch = JobBatch {
+            id: IdGenerator::generate(IdType::Batch),
+            merchant_id: Some(merchant_id.clone()),
+            job_ids: jobs.iter().map(|job| job.id.clone()).collect(),
+            created_at: Utc::now(),
+        };
+        if let Err(e) = self.cache_service.cache_job_batch(&batch).await {
+            self.discard_jobs(&jobs).await;
+            return Err(e);
+        }
+        
+        for job in &jobs {
+            self.announce_created(job).await;
+        }
+        
+        tracing::info!("Bulk import {} created {} jobs", batch.id, jobs.len());
+        
+        Ok(BulkJobResponse {
+            batch_id: batch.id,
+            created: jobs.len(),
+            jobs: jobs.into_iter().map(|job| self.to_response(job)).collect(),
+        })
+    }
+    
+    async fn get_job_batch(&self, batch_id: &str, merchant_id: &UserId) -> Result<Option<JobBatchStatus>, AppError> {
+        if !IdGenerator::validate_id(batch_id, Some(IdType::Batch)) {
+            return Err(AppError::validation_error("batch_id", "Invalid batch ID format"));
+        }
+        
+        // Other merchants' batches are reported as missing
+        let Some(batch) = self.cache_service.get_job_batch(batch_id).await?
+            .filter(|batch| batch.merchant_id.as_ref() == Some(merchant_id)) else {
+            return Ok(None);
+        };
+        
+        let mut items = Vec::with_capacity(batch.job_ids.len());
+        for job_id in &batch.job_ids {
+            if let Some(job) = self.cache_service.load_job(job_id).await? {
+                items.push(JobBatchItem {
+                    job_id: job.id,
+                    tracking_code: job.tracking_code,
+                    status: job.status,
+                    driver_id: job.driver_id,
+                });
+            }
+        }
+        
+        Ok(Some(JobBatchStatus {
+            batch_id: batch.id,
+            created_at: batch.created_at,
+            total: batch.job_ids.len(),
+            assigned: items.iter().filter(|item| item.driver_id.is_some()).count(),
+            completed: items.iter().filter(|item| item.status.is_terminal()).count(),
+            jobs: items,
+        }))
+    }
+}
+
+// An SOS holds the job where it is until ops resolve the incident
+fn ensure_not_frozen(job: &Job) -> Result<(), AppError> {
+    match &job.frozen_by {
+        Some(incident_id) => Err(AppError::Conflict(format!("Job {} is frozen by incident {}", job.id, incident_id))),
+        None => Ok(()),
+    }
+}
+
+// Whether the customer may still move the job's dropoff
+fn ensure_redirectable(job: &Job, customer_id: &UserId) -> Result<(), AppError> {
+    if job.customer_id != *customer_id {
+        return Err(AppError::Forbidden(format!("Job {} belongs to another customer", job.id)));
+    }
+    ensure_not_frozen(job)?;
+    if !job.status.allows_dropoff_change() {
+        return Err(AppError::Conflict(format!("Dropoff of job {} cannot be changed once {:?}", job.id, job.status)));
+    }
+    // The pool's route and discounts were planned around every job's dropoff
+    if job.pool_id.is_some() {
+        return Err(AppError::Conflict(format!("Job {} shares a pool; its dropoff cannot be changed", job.id)));
+    }
+    Ok(())
+}
+
+/// Parse a CSV manifest into job requests, reporting every bad line at once
+pub fn parse_job_manifest(data: &[u8]) -> Result<Vec<JobRequest>, AppError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(data);
+    
+    let mut requests = Vec::new();
+    let mut errors = Vec::new();
+    for (index, row) in reader.deserialize::<JobManifestRow>().enumerate() {
+        let line = index + 2; // Line 1 is the header
+        match row.map_err(|e| e.to_string()).and_then(JobManifestRow::into_request) {
+            Ok(request) => requests.push(request),
+            Err(message) => errors.push(ValidationError {
+                field: format!("line {}", line),
+                message,
+            }),
+        }
+    }
+    
+    if !errors.is_empty() {
+        return Err(AppError::ValidationFailed(errors));
+    }
+    Ok(requests)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, events::RecordingSubscriber, fixtures::Faker},
+        models::{dispatch::DriverSocketEvent, driver::{DispatchOutcomeKind, DriverEquipment, DriverStatus}, job::{LocationUpdate, PackageType}, user::{Address, UserType}},
+        services::{driver_service::DriverOperations, realtime_bus::driver_topic, user_service::UserOperations},
+    };
+
+    #[tokio::test]
+    async fn test_food_is_only_dispatched_to_drivers_with_an_insulated_bag() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(37);
+
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let mut request = faker.job_request(&customer.id);
+        request.package.package_type = PackageType::Food;
+        request.dropoff_location = request.pickup_location.clone();
+        let pickup = request.pickup_location.clone().unwrap();
+        let (package, priority) = (request.package.clone(), request.priority.clone());
+        let estimate_request = |dropoff: Location| JobEstimateRequest {
+            pickup_location: pickup.clone(),
+            dropoff_location: dropoff,
+            package: package.clone(),
+            priority: priority.clone(),
+            currency: None,
+        };
+
+        let mut driver = faker.driver();
+        driver.status = DriverStatus::Online;
+        driver.equipment = DriverEquipment { insulated_bag: false, upright_carrier: true };
+        state.cache_service.cache_driver(&driver).await.unwrap();
+        state.cache_service.cache_driver_locations(&[(driver.id.clone(), LocationUpdate {
+            latitude: pickup.latitude,
+            longitude: pickup.longitude,
+            timestamp: Utc::now(),
+            accuracy: None,
+            heading: None,
+            speed: None,
+            is_mock: false,
+        })]).await.unwrap();
+
+        let estimate = state.job_service.calculate_estimate(estimate_request(pickup.clone())).await.unwrap();
+        assert!(estimate.handling.insulated_bag);
+        assert_eq!(estimate.warnings.len(), 1);
+
+        let job = state.job_service.create_job(request).await.unwrap();
+        assert!(state.job_service.find_available_drivers(&job.id).await.unwrap().is_empty());
+        assert!(state.job_service.get_available_jobs(&driver.id).await.unwrap().is_empty());
+        assert!(state.job_service.assign_driver_to_job(&job.id, &driver.id).await.is_err());
+
+        state.driver_service.update_equipment(&driver.id, DriverEquipment { insulated_bag: true, upright_carrier: true }).await.unwrap();
+        assert_eq!(state.job_service.find_available_drivers(&job.id).await.unwrap(), vec![driver.id.clone()]);
+        let estimate = state.job_service.calculate_estimate(estimate_request(pickup.clone())).await.unwrap();
+        assert!(estimate.warnings.is_empty());
+
+        // Too far for food to arrive warm
+        let mut far = pickup.clone();
+        far.latitude += 1.0;
+        let estimate = state.job_service.calculate_estimate(estimate_request(far)).await.unwrap();
+        assert_eq!(estimate.warnings.len(), 1);
+        assert!(estimate.warnings[0].contains("45 minutes"));
+    }
+
+    #[tokio::test]
+    async fn test_job_history_pages_newest_first_across_tied_timestamps() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(4701);
+        let customer = faker.user(UserType::Customer);
+
+        // Two pairs created in the same millisecond
+        let start = Utc::now();
+        let mut created = Vec::new();
+        for offset in [0, 1, 1, 2, 2] {
+            let mut job = faker.job(&customer.id);
+            job.created_at = start + chrono::Duration::milliseconds(offset);
+            state.cache_service.cache_job(&job).await.unwrap();
+            state.cache_service.cache_customer_job(&job).await.unwrap();
+            created.push(job);
+        }
+        created.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.id.as_str().cmp(a.id.as_str())));
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let query = JobHistoryQuery { cursor: cursor.take(), limit: Some(2) };
+            let page = state.job_service.get_jobs_by_customer(&customer.id, query).await.unwrap();
+            seen.extend(page.items.into_iter().map(|job| job.id));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(seen, created.iter().map(|job| job.id.clone()).collect::<Vec<_>>());
+
+        let query = JobHistoryQuery { cursor: Some("not-a-cursor".to_string()), limit: None };
+        assert!(state.job_service.get_jobs_by_customer(&customer.id, query).await.is_err());
+        let query = JobHistoryQuery { cursor: None, limit: Some(0) };
+        assert!(state.job_service.get_jobs_by_customer(&customer.id, query).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dropoff_change_applies_only_once_its_new_total_is_approved() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(105);
+
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let other = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let created = state.job_service.create_job(faker.job_request(&customer.id)).await.unwrap();
+        let mut driver = faker.driver();
+        driver.status = DriverStatus::Online;
+        state.cache_service.cache_driver(&driver).await.unwrap();
+        state.job_service.assign_driver_to_job(&created.id, &driver.id).await.unwrap();
+        let (_, mut driver_socket) = state.realtime_bus.subscribe(&driver_topic(&driver.id));
+
+        // Further out, so dearer
+        let mut dropoff = created.dropoff_location.clone();
+        dropoff.latitude += if dropoff.latitude > created.pickup_location.latitude { 0.05 } else { -0.05 };
+        dropoff.address = "12 New Road".to_string();
+        let request = || ChangeDropoffRequest {
+            dropoff_location: Some(dropoff.clone()),
+            dropoff_address_id: None,
+        };
+        assert!(matches!(state.job_service.request_dropoff_change(&created.id, &other.id, request()).await, Err(AppError::Forbidden(_))));
+        let change = state.job_service.request_dropoff_change(&created.id, &customer.id, request()).await.unwrap();
+        assert_eq!(change.previous_total, created.pricing.total);
+        assert!(change.pricing.total.minor() > created.pricing.total.minor());
+        assert!(change.estimated_distance_km > created.estimated_distance_km);
+
+        // Nothing moves until the customer accepts the total they were shown
+        let pending = state.job_service.get_job(&created.id).await.unwrap().unwrap();
+        assert_eq!(pending.dropoff_location.address, created.dropoff_location.address);
+        assert!(pending.dropoff_change.is_some());
+        let stale = ApproveDropoffChangeRequest { total: created.pricing.total.to_major() };
+        assert!(matches!(state.job_service.approve_dropoff_change(&created.id, &customer.id, stale).await, Err(AppError::Conflict(_))));
+
+        let approval = || ApproveDropoffChangeRequest { total: change.pricing.total.to_major() };
+        assert!(matches!(state.job_service.approve_dropoff_change(&created.id, &other.id, approval()).await, Err(AppError::Forbidden(_))));
+        let changed = state.job_service.approve_dropoff_change(&created.id, &customer.id, approval()).await.unwrap();
+        assert_eq!(changed.dropoff_location.address, "12 New Road");
+        assert_eq!(changed.pricing.total, change.pricing.total);
+        assert!(changed.dropoff_change.is_none());
+        let event: DriverSocketEvent = serde_json::from_slice(&driver_socket.try_recv().unwrap()).unwrap();
+        let DriverSocketEvent::Message { name, data } = event else { panic!("expected a message") };
+        assert_eq!(name, "navigation_updated");
+        assert_eq!(data["destination"]["address"], "12 New Road");
+
+        // Too late once the driver is at the door
+        state.job_service.update_job_status(JobStatusUpdate {
+            job_id: created.id.clone(),
+            status: JobStatus::ArrivedAtDropoff,
+            driver_id: Some(driver.id.clone()),
+            notes: None,
+        }).await.unwrap();
+        assert!(matches!(state.job_service.request_dropoff_change(&created.id, &customer.id, request()).await, Err(AppError::Conflict(_))));
+    }
+
+    #[tokio::test]
+    async fn test_saved_addresses_resolve_only_for_their_owner_and_with_coordinates() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(4606);
+        let customer = faker.user(UserType::Customer);
+        let stranger = faker.user(UserType::Customer);
+        state.cache_service.cache_user(&customer).await.unwrap();
+        state.cache_service.cache_user(&stranger).await.unwrap();
+        let address = |id: &str, coordinates: Option<(f64, f64)>| Address {
+            id: id.to_string(),
+            label: "Home".to_string(),
+            street: "12 Oxford Street".to_string(),
+            city: "Accra".to_string(),
+            region: "Greater Accra".to_string(),
+            country: "Ghana".to_string(),
+            postal_code: None,
+            latitude: coordinates.map(|(latitude, _)| latitude),
+            longitude: coordinates.map(|(_, longitude)| longitude),
+            is_primary: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let addresses = vec![address("addr-home", Some((5.556, -0.196))), address("addr-new", None)];
+        state.cache_service.cache_user_addresses(&customer.id, &addresses).await.unwrap();
+        let resolve = |user_id: UserId, location: Option<Location>, address_id: Option<&str>| {
+            let address_id = address_id.map(str::to_string);
+            async move { state.job_service.resolve_location(&user_id, location, address_id, "pickup_location").await }
+        };
+
+        let location = resolve(customer.id.clone(), None, Some("addr-home")).await.unwrap();
+        assert_eq!((location.latitude, location.longitude), (5.556, -0.196));
+        assert_eq!(location.contact_name, format!("{} {}", customer.first_name, customer.last_name));
+
+        // Someone else's address
+        let result = resolve(stranger.id.clone(), None, Some("addr-home")).await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+        // Both, or neither
+        let result = resolve(customer.id.clone(), Some(location.clone()), Some("addr-home")).await;
+        assert!(matches!(result, Err(AppError::ValidationFailed(_))));
+        let result = resolve(customer.id.clone(), None, None).await;
+        assert!(matches!(result, Err(AppError::MissingRequiredField(field)) if field == "pickup_location"));
+        // Saved but never geocoded
+        let result = resolve(customer.id.clone(), None, Some("addr-new")).await;
+        assert!(matches!(result, Err(AppError::ValidationFailed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_assigning_picking_up_and_completing_a_job_announce_it() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let published = RecordingSubscriber::new();
+        state.event_bus.subscribe(Arc::new(published.clone()));
+        let mut faker = Faker::seeded(4717);
+
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let created = state.job_service.create_job(faker.job_request(&customer.id)).await.unwrap();
+        let driver = faker.driver();
+        state.cache_service.cache_driver(&driver).await.unwrap();
+
+        state.job_service.assign_driver_to_job(&created.id, &driver.id).await.unwrap();
+        let assigned = DomainEvent::JobAssigned { job_id: created.id.clone(), driver_id: driver.id.clone(), offered: false };
+        assert_eq!(published.events().last(), Some(&assigned));
+
+        state.job_service.update_job_status(JobStatusUpdate {
+            job_id: created.id.clone(),
+            status: JobStatus::PackagePickedUp,
+            driver_id: Some(driver.id.clone()),
+            notes: None,
+        }).await.unwrap();
+        let picked_up = DomainEvent::JobPickedUp { job_id: created.id.clone(), driver_id: Some(driver.id.clone()) };
+        assert_eq!(published.events().last(), Some(&picked_up));
+
+        // A bare status change completes the job like the completion route does, and once only
+        let completed = state.job_service.update_job_status(JobStatusUpdate {
+            job_id: created.id.clone(),
+            status: JobStatus::DeliveryCompleted,
+            driver_id: Some(driver.id.clone()),
+            notes: None,
+        }).await.unwrap();
+        assert_eq!(completed.payment_status, PaymentStatus::Paid);
+        assert!(matches!(state.job_service.complete_job(&created.id).await, Err(AppError::Conflict(_))));
+        assert_eq!(published.count("job_completed"), 1);
+        assert_eq!(published.count("payment_captured"), 1);
+
+        // The driver's record is kept from those events
+        let outcomes = state.cache_service.get_dispatch_outcomes(&driver.id).await.unwrap();
+        let kinds: Vec<_> = outcomes.iter().map(|outcome| outcome.kind).collect();
+        assert_eq!(kinds, [DispatchOutcomeKind::Offered, DispatchOutcomeKind::Accepted, DispatchOutcomeKind::PickedUp]);
+    }
 }
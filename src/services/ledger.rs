@@ -0,0 +1,311 @@
+// src/services/ledger.rs
+// The record of every movement of money: fares collected, driver earnings, commission,
+// tax, bonuses and adjustments. Entries are double-entry and append-only; balances and
+// statements are derived from them rather than stored alongside.
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        chaos::FaultLayer,
+        ids::JobId,
+        job::{DriverEarnings, Job, PaymentStatus},
+        ledger::{
+            AccountBalance, AccountStatement, CreateLedgerEntryRequest, EntrySide, LedgerAccount, LedgerEntry, PlatformAccount,
+            Posting, StatementLine,
+        },
+        money::{Currency, Money},
+    },
+    services::{cache_service::CacheService, chaos},
+    utils::id_generator::{IdGenerator, IdType},
+};
+
+#[derive(Debug, Clone)]
+pub struct LedgerConfig {
+    pub posting_claim_seconds: u64, // How long a half-finished posting blocks retries of its key
+    pub max_postings: usize,
+}
+
+impl Default for LedgerConfig {
+    fn default() -> Self {
+        Self {
+            posting_claim_seconds: 60,
+            max_postings: 20,
+        }
+    }
+}
+
+pub fn settlement_key(job_id: &JobId) -> String {
+    format!("job:{}:settlement", job_id)
+}
+
+pub struct LedgerService {
+    cache_service: Arc<CacheService>,
+    config: LedgerConfig,
+}
+
+impl LedgerService {
+    pub fn new(cache_service: Arc<CacheService>, config: LedgerConfig) -> Self {
+        Self { cache_service, config }
+    }
+
+    /// Record `request`, or return the entry already recorded under its posting key
+    pub async fn post(&self, request: CreateLedgerEntryRequest) -> Result<LedgerEntry, AppError> {
+        let posting_key = request.posting_key.trim();
+        if posting_key.is_empty() {
+            return Err(AppError::validation_error("posting_key", "Must not be empty"));
+        }
+        let description = request.description.trim();
+        if description.is_empty() {
+            return Err(AppError::validation_error("description", "Must not be empty"));
+        }
+        // Zero legs say nothing; drop them rather than reject an otherwise good entry
+        let postings: Vec<Posting> = request.postings.into_iter().filter(|posting| !posting.amount.is_zero()).collect();
+        self.validate(&postings)?;
+
+        chaos::inject(FaultLayer::Payment, "post_entry").await?;
+        if let Some(existing) = self.cache_service.get_ledger_entry_by_key(posting_key).await? {
+            return Ok(existing);
+        }
+        if !self.cache_service.claim_ledger_posting(posting_key, self.config.posting_claim_seconds).await? {
+            // Another poster got there first; it has either finished or is still writing
+            return match self.cache_service.get_ledger_entry_by_key(posting_key).await? {
+                Some(existing) => Ok(existing),
+                None => Err(AppError::Conflict(format!("Ledger entry {} is already being posted", posting_key))),
+            };
+        }
+
+        let entry = LedgerEntry {
+            id: IdGenerator::generate(IdType::LedgerEntry),
+            posting_key: posting_key.to_string(),
+            description: description.to_string(),
+            job_id: request.job_id,
+            provider_reference: request.provider_reference,
+            postings,
+            posted_at: Utc::now(),
+        };
+        self.cache_service.append_ledger_entry(&entry).await?;
+
+        tracing::info!("Posted ledger entry {} ({}) with {} postings", entry.id, entry.posting_key, entry.postings.len());
+        Ok(entry)
+    }
+
+    /// Settle a completed job: the customer's payment is split between the driver, our
+    /// commission and tax. Bonuses paid beyond the commission are charged to promotions.
+    /// Invoiced jobs are charged to the merchant's account instead of cash, until they pay.
+    pub async fn post_job_settlement(&self, job: &Job, earnings: Option<&DriverEarnings>) -> Result<LedgerEntry, AppError> {
+        let pricing = &job.pricing;
+        let invoiced = job.payment_status == PaymentStatus::Invoiced;
+        let payer = if invoiced {
+            LedgerAccount::Customer(job.customer_id.clone())
+        } else {
+            LedgerAccount::Platform(PlatformAccount::Cash)
+        };
+        let mut postings = vec![
+            Posting::debit(payer, pricing.total),
+            Posting::credit(LedgerAccount::Platform(PlatformAccount::Tax), pricing.tax),
+        ];
+        let mut platform_share = pricing.total - pricing.tax;
+        if let (Some(driver_id), Some(earnings)) = (&job.driver_id, earnings) {
+            postings.push(Posting::credit(LedgerAccount::Driver(driver_id.clone()), earnings.total));
+            platform_share -= earnings.total;
+        }
+        if platform_share.is_negative() {
+            postings.push(Posting::debit(LedgerAccount::Platform(PlatformAccount::Promotions), -platform_share));
+        } else {
+            postings.push(Posting::credit(LedgerAccount::Platform(PlatformAccount::Commission), platform_share));
+        }
+
+        self.post(CreateLedgerEntryRequest {
+            posting_key: settlement_key(&job.id),
+            description: format!("Settlement for job {}", job.id),
+            job_id: Some(job.id.clone()),
+            // Jobs are charged with their ID as the merchant reference, which providers report back
+            provider_reference: (!invoiced).then(|| job.id.to_string()),
+            postings,
+        }).await
+    }
+
+    /// Post the mirror image of the entry under `posting_key`, under `reversal_key`. None
+    /// when there was no entry to reverse.
+    pub async fn reverse(&self, posting_key: &str, reversal_key: &str, description: String) -> Result<Option<LedgerEntry>, AppError> {
+        let Some(entry) = self.cache_service.get_ledger_entry_by_key(posting_key).await? else {
+            return Ok(None);
+        };
+        let postings = entry.postings.iter()
+            .map(|posting| Posting {
+                side: match posting.side {
+                    EntrySide::Debit => EntrySide::Credit,
+                    EntrySide::Credit => EntrySide::Debit,
+                },
+                ..posting.clone()
+            })
+            .collect();
+        let reversal = self.post(CreateLedgerEntryRequest {
+            posting_key: reversal_key.to_string(),
+            description,
+            job_id: entry.job_id.clone(),
+            provider_reference: None,
+            postings,
+        }).await?;
+        Ok(Some(reversal))
+    }
+
+    pub async fn balance(&self, account: &LedgerAccount, currency: Currency) -> Result<AccountBalance, AppError> {
+        let entries = self.cache_service.get_ledger_entries(account).await?;
+        let balance = entries.iter()
+            .flat_map(|entry| entry.postings.iter())
+            .filter(|posting| &posting.account == account && posting.amount.currency() == currency)
+            .fold(Money::zero(currency), |balance, posting| balance + posting.signed_amount());
+        Ok(AccountBalance {
+            account: account.clone(),
+            balance,
+            entries: entries.len(),
+        })
+    }
+
+    /// Movements on `account` in `currency` posted in [from, to), with running balances
+    pub async fn statement(
+        &self,
+        account: &LedgerAccount,
+        currency: Currency,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<AccountStatement, AppError> {
+        let mut opening_balance = Money::zero(currency);
+        let mut balance = opening_balance;
+        let mut lines = Vec::new();
+        for entry in self.cache_service.get_ledger_entries(account).await? {
+            if to.is_some_and(|to| entry.posted_at >= to) {
+                break;
+            }
+            for posting in entry.postings.iter().filter(|posting| &posting.account == account && posting.amount.currency() == currency) {
+                balance += posting.signed_amount();
+                if from.is_some_and(|from| entry.posted_at < from) {
+                    opening_balance = balance;
+                    continue;
+                }
+                lines.push(StatementLine {
+                    entry_id: entry.id.clone(),
+                    description: entry.description.clone(),
+                    job_id: entry.job_id.clone(),
+                    side: posting.side,
+                    amount: posting.amount,
+                    balance,
+                    posted_at: entry.posted_at,
+                });
+            }
+        }
+        Ok(AccountStatement {
+            account: account.clone(),
+            opening_balance,
+            closing_balance: balance,
+            lines,
+        })
+    }
+
+    /// Entries recorded against `job_id`, oldest first
+    pub async fn job_entries(&self, job_id: &JobId) -> Result<Vec<LedgerEntry>, AppError> {
+        Ok(self.cache_service.get_ledger_journal().await?
+            .into_iter()
+            .filter(|entry| entry.job_id.as_ref() == Some(job_id))
+            .collect())
+    }
+
+    // Debits must equal credits, in a single currency
+    fn validate(&self, postings: &[Posting]) -> Result<(), AppError> {
+        if postings.len() < 2 {
+            return Err(AppError::validation_error("postings", "An entry needs at least two non-zero postings"));
+        }
+        if postings.len() > self.config.max_postings {
+            return Err(AppError::validation_error("postings", format!("At most {} postings per entry", self.config.max_postings)));
+        }
+        let currency = postings[0].amount.currency();
+        let mut net = Money::zero(currency);
+        for (index, posting) in postings.iter().enumerate() {
+            if posting.amount.is_negative() {
+                return Err(AppError::validation_error(format!("postings[{}].amount", index), "Must not be negative"));
+            }
+            net = net.checked_add(match posting.side {
+                EntrySide::Debit => posting.amount,
+                EntrySide::Credit => -posting.amount,
+            })?;
+        }
+        if !net.is_zero() {
+            return Err(AppError::validation_error("postings", format!("Debits and credits differ by {}", net)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::ids::{DriverId, UserId},
+    };
+
+    #[tokio::test]
+    async fn test_settlements_balance_and_post_once() {
+        let app = TestApp::new();
+        let ledger = &app.state.ledger_service;
+        let cedis = |amount: f64| Money::from_major(amount, Currency::GHS);
+
+        let mut job = Faker::seeded(61).job(&UserId::generate());
+        let driver_id = DriverId::generate();
+        job.driver_id = Some(driver_id.clone());
+        job.pricing.total = cedis(135.0);
+        job.pricing.tax = cedis(5.0);
+        let earnings = DriverEarnings {
+            fare: cedis(110.0),
+            commission_rate: 0.15,
+            commission: cedis(16.5),
+            tip: cedis(10.0),
+            surge_bonus: Money::zero(Currency::GHS),
+            escalation_bonus: Money::zero(Currency::GHS),
+            total: cedis(103.5),
+            currency: Currency::GHS,
+        };
+
+        let entry = ledger.post_job_settlement(&job, Some(&earnings)).await.unwrap();
+        let again = ledger.post_job_settlement(&job, Some(&earnings)).await.unwrap();
+        assert_eq!(again.id, entry.id);
+        assert_eq!(ledger.job_entries(&job.id).await.unwrap().len(), 1);
+
+        let balance = |account| async move { ledger.balance(&account, Currency::GHS).await.unwrap().balance };
+        assert_eq!(balance(LedgerAccount::Driver(driver_id.clone())).await, cedis(103.5));
+        assert_eq!(balance(LedgerAccount::Platform(PlatformAccount::Commission)).await, cedis(26.5));
+        assert_eq!(balance(LedgerAccount::Platform(PlatformAccount::Cash)).await, cedis(135.0));
+
+        // A payout to the driver draws their balance down
+        ledger.post(CreateLedgerEntryRequest {
+            posting_key: "payout:1".to_string(),
+            description: "Weekly payout".to_string(),
+            job_id: None,
+            provider_reference: None,
+            postings: vec![
+                Posting::debit(LedgerAccount::Driver(driver_id.clone()), cedis(100.0)),
+                Posting::credit(LedgerAccount::Platform(PlatformAccount::Cash), cedis(100.0)),
+            ],
+        }).await.unwrap();
+        let statement = ledger.statement(&LedgerAccount::Driver(driver_id), Currency::GHS, None, None).await.unwrap();
+        assert_eq!(statement.lines.iter().map(|line| line.side).collect::<Vec<_>>(), [EntrySide::Credit, EntrySide::Debit]);
+        assert_eq!(statement.closing_balance, cedis(3.5));
+
+        let unbalanced = ledger.post(CreateLedgerEntryRequest {
+            posting_key: "bad".to_string(),
+            description: "Lopsided".to_string(),
+            job_id: None,
+            provider_reference: None,
+            postings: vec![
+                Posting::debit(LedgerAccount::Platform(PlatformAccount::Cash), cedis(10.0)),
+                Posting::credit(LedgerAccount::Platform(PlatformAccount::Commission), cedis(9.0)),
+            ],
+        }).await;
+        assert!(matches!(unbalanced, Err(AppError::ValidationFailed(_))));
+    }
+}
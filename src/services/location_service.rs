@@ -0,0 +1,348 @@
+// src/services/location_service.rs
+// Driver location batches. The app sends points as it records them, and while it has no
+// signal it keeps them and uploads the backlog once it's back online, oldest first and at
+// most `max_batch_size` at a time. Every point carries the time it was recorded, so points
+// may arrive late and out of order: they all go into the route history, but only fresh ones
+// move the live position dispatch works from or reach the customer's map.
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{driver::LocationBatchResponse, ids::DriverId, job::LocationUpdate, risk::{QuarantinedLocation, SpoofReason}},
+    services::{cache_service::CacheService, odometer::OdometerService, risk_service::RiskService, route_service::RouteService, status_feed::StatusFeedService, write_behind::{WriteBehindQueue, WriteOp}},
+    utils::geo,
+};
+
+#[derive(Debug, Clone)]
+pub struct LocationConfig {
+    pub min_interval_seconds: i64, // Points closer together than this are dropped...
+    pub min_distance_meters: f64,  // ...unless the driver moved at least this far
+    pub max_batch_size: usize,     // Upper bound on points per batch request
+    pub max_accuracy_meters: f64,  // Fixes less precise than this are rejected
+    pub max_speed_kmh: f64,        // A jump implying more than this from the previous point is quarantined...
+    pub min_jump_meters: f64,      // ...once it is at least this far, so GPS jitter isn't mistaken for one
+    pub max_live_age_seconds: i64, // Older points are history; they never become the live position
+    pub max_backlog_hours: i64,    // How long the app may hold points while offline
+    pub max_clock_skew_seconds: i64, // Points further ahead of our clock than this are rejected
+}
+
+impl Default for LocationConfig {
+    fn default() -> Self {
+        Self {
+            min_interval_seconds: 5,
+            min_distance_meters: 25.0,
+            max_batch_size: 500,
+            max_accuracy_meters: 100.0,
+            max_speed_kmh: 200.0,
+            min_jump_meters: 500.0,
+            max_live_age_seconds: 120,
+            max_backlog_hours: 24,
+            max_clock_skew_seconds: 60,
+        }
+    }
+}
+
+pub struct LocationService {
+    config: LocationConfig,
+    cache_service: Arc<CacheService>,
+    risk_service: Arc<RiskService>,
+    route_service: Arc<RouteService>,
+    status_feed: Arc<StatusFeedService>,
+    odometer: Arc<OdometerService>,
+    // Live position and presence go to Redis behind the request
+    write_behind: Arc<WriteBehindQueue>,
+}
+
+impl LocationService {
+    pub fn new(
+        cache_service: Arc<CacheService>,
+        risk_service: Arc<RiskService>,
+        route_service: Arc<RouteService>,
+        status_feed: Arc<StatusFeedService>,
+        odometer: Arc<OdometerService>,
+        write_behind: Arc<WriteBehindQueue>,
+        config: LocationConfig,
+    ) -> Self {
+        Self {
+            config,
+            cache_service,
+            risk_service,
+            route_service,
+            status_feed,
+            odometer,
+            write_behind,
+        }
+    }
+
+    pub async fn ingest_batch(&self, driver_id: &DriverId, locations: Vec<LocationUpdate>) -> Result<LocationBatchResponse, AppError> {
+        if locations.is_empty() {
+            return Err(AppError::validation_error("locations", "At least one location is required"));
+        }
+        if locations.len() > self.config.max_batch_size {
+            return Err(AppError::validation_error(
+                "locations",
+                format!("At most {} locations are allowed per batch", self.config.max_batch_size),
+            ));
+        }
+        if let Some(bad) = locations.iter().find(|l| !is_valid_coordinate(l)) {
+            return Err(AppError::InvalidFieldValue {
+                field: "locations".to_string(),
+                value: format!("{},{}", bad.latitude, bad.longitude),
+                reason: "Coordinates out of range".to_string(),
+            });
+        }
+
+        let received = locations.len();
+        let now = Utc::now();
+        let previous = self.cache_service.get_driver_location(driver_id).await?;
+        let screened = screen(locations, previous.as_ref(), &self.config, now);
+        if let Err(e) = self.risk_service.report_spoofing(driver_id, &screened.quarantined).await {
+            tracing::warn!("Failed to report spoofed locations of driver {}: {}", driver_id, e);
+        }
+        let kept = downsample(
+            screened.kept,
+            self.config.min_interval_seconds,
+            self.config.min_distance_meters,
+        );
+        let accepted = kept.len();
+        let live: Vec<LocationUpdate> = kept.iter()
+            .filter(|location| is_live(location, previous.as_ref(), self.config.max_live_age_seconds, now))
+            .cloned()
+            .collect();
+
+        // Route history and customer frames are best-effort - a failure must not drop the live position
+        match self.route_service.record_driver_points(driver_id, &kept).await {
+            Ok(jobs) => {
+                if let Err(e) = self.status_feed.record_locations(&jobs, &live).await {
+                    tracing::warn!("Failed to feed locations of driver {} to customers: {}", driver_id, e);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to record route points for driver {}: {}", driver_id, e),
+        }
+        if let Err(e) = self.odometer.record(driver_id, &kept).await {
+            tracing::warn!("Failed to add distance driven by driver {}: {}", driver_id, e);
+        }
+
+        // Only the most recent point matters for the live position
+        let historical = accepted - live.len();
+        if let Some(latest) = live.into_iter().last() {
+            self.write_behind.enqueue(WriteOp::DriverLocation { driver_id: driver_id.clone(), location: latest });
+        }
+        self.write_behind.enqueue(WriteOp::DriverLastSeen { driver_id: driver_id.clone(), at: now });
+
+        tracing::debug!("Buffered location batch for driver {}: {}/{} points kept, {} historical", driver_id, accepted, received, historical);
+
+        Ok(LocationBatchResponse {
+            driver_id: driver_id.clone(),
+            received,
+            accepted,
+            historical,
+            rejected: screened.rejected,
+            quarantined: screened.quarantined.len(),
+        })
+    }
+}
+
+// Recent, and newer than the position we already have
+fn is_live(location: &LocationUpdate, previous: Option<&LocationUpdate>, max_age_seconds: i64, now: DateTime<Utc>) -> bool {
+    now.signed_duration_since(location.timestamp).num_seconds() <= max_age_seconds
+        && previous.is_none_or(|previous| location.timestamp > previous.timestamp)
+}
+
+fn is_valid_coordinate(location: &LocationUpdate) -> bool {
+    (-90.0..=90.0).contains(&location.latitude) && (-180.0..=180.0).contains(&location.longitude)
+}
+
+pub struct Screened {
+    pub kept: Vec<LocationUpdate>, // Oldest first
+    pub rejected: usize,
+    pub quarantined: Vec<QuarantinedLocation>,
+}
+
+/// Sort out points that can't be trusted. Fixes too imprecise to place the driver, or
+/// stamped outside the window a backlog may cover, are rejected; fixes the phone marked as mocked, or that jump further from the previous
+/// trusted point than any vehicle could go, are quarantined as likely spoofed.
+/// `previous` is the driver's last known position, so a batch can't start with a jump.
+pub fn screen(mut locations: Vec<LocationUpdate>, previous: Option<&LocationUpdate>, config: &LocationConfig, now: DateTime<Utc>) -> Screened {
+    locations.sort_by_key(|location| location.timestamp);
+
+    let mut screened = Screened { kept: Vec::with_capacity(locations.len()), rejected: 0, quarantined: Vec::new() };
+    let mut anchor = previous.cloned();
+    let oldest = now - Duration::hours(config.max_backlog_hours);
+    let newest = now + Duration::seconds(config.max_clock_skew_seconds);
+    for location in locations {
+        if location.accuracy.is_some_and(|accuracy| accuracy > config.max_accuracy_meters)
+            || location.timestamp < oldest
+            || location.timestamp > newest
+        {
+            screened.rejected += 1;
+            continue;
+        }
+        let quarantine = |reason, speed_kmh| QuarantinedLocation { location: location.clone(), reason, speed_kmh, received_at: now };
+        if location.is_mock {
+            screened.quarantined.push(quarantine(SpoofReason::MockLocation, None));
+            continue;
+        }
+        if let Some(anchor) = &anchor {
+            let meters = distance_meters(anchor, &location);
+            let seconds = location.timestamp.signed_duration_since(anchor.timestamp).num_milliseconds() as f64 / 1000.0;
+            // A point no newer than the anchor can't be judged by speed; downsampling sorts it out
+            if meters >= config.min_jump_meters && seconds > 0.0 {
+                let speed_kmh = meters / seconds * 3.6;
+                if speed_kmh > config.max_speed_kmh {
+                    screened.quarantined.push(quarantine(SpoofReason::Teleport, Some(speed_kmh)));
+                    continue;
+                }
+            }
+        }
+        anchor = Some(location.clone());
+        screened.kept.push(location);
+    }
+    screened
+}
+
+/// Drop points that add no information: anything recorded within `min_interval_seconds`
+/// of the last kept point, unless the driver moved at least `min_distance_meters`.
+/// The newest point is always kept so the live position is never stale.
+pub fn downsample(mut locations: Vec<LocationUpdate>, min_interval_seconds: i64, min_distance_meters: f64) -> Vec<LocationUpdate> {
+    locations.sort_by_key(|location| location.timestamp);
+
+    let last_index = match locations.len() {
+        0 => return locations,
+        n => n - 1,
+    };
+
+    let mut kept: Vec<LocationUpdate> = Vec::with_capacity(locations.len());
+    for (index, location) in locations.into_iter().enumerate() {
+        let keep = match kept.last() {
+            None => true,
+            Some(previous) => {
+                let elapsed = location.timestamp.signed_duration_since(previous.timestamp).num_seconds();
+                index == last_index
+                    || elapsed >= min_interval_seconds
+                    || distance_meters(previous, &location) >= min_distance_meters
+            }
+        };
+        if keep {
+            kept.push(location);
+        }
+    }
+    kept
+}
+
+fn distance_meters(a: &LocationUpdate, b: &LocationUpdate) -> f64 {
+    geo::haversine_km((a.latitude, a.longitude), (b.latitude, b.longitude)) * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::{ids::UserId, job::JobStatus},
+    };
+
+    fn point(seconds: i64, latitude: f64, longitude: f64) -> LocationUpdate {
+        LocationUpdate {
+            latitude,
+            longitude,
+            timestamp: Utc.with_ymd_and_hms(2025, 9, 1, 8, 0, 0).unwrap() + chrono::Duration::seconds(seconds),
+            accuracy: None,
+            heading: None,
+            speed: None,
+            is_mock: false,
+        }
+    }
+
+    #[test]
+    fn test_screen_rejects_imprecise_fixes_and_quarantines_spoofed_ones() {
+        let previous = point(0, 5.5560, -0.1820);
+        let mut imprecise = point(10, 5.5570, -0.1820);
+        imprecise.accuracy = Some(800.0);
+        let mut mocked = point(20, 5.5580, -0.1820);
+        mocked.is_mock = true;
+        // ~5 km in half a minute, then carrying on from where the driver really was
+        let jump = point(30, 5.6000, -0.1820);
+        let honest = point(40, 5.5590, -0.1820);
+
+        let now = previous.timestamp + Duration::minutes(1);
+        let screened = screen(vec![honest, jump, mocked, imprecise], Some(&previous), &LocationConfig::default(), now);
+        assert_eq!(screened.rejected, 1);
+        let reasons: Vec<SpoofReason> = screened.quarantined.iter().map(|q| q.reason).collect();
+        assert_eq!(reasons, vec![SpoofReason::MockLocation, SpoofReason::Teleport]);
+        assert!(screened.quarantined[1].speed_kmh.unwrap() > 300.0);
+        assert_eq!(screened.kept.len(), 1);
+        assert_eq!(screened.kept[0].latitude, 5.5590);
+    }
+
+    #[test]
+    fn test_downsample_drops_close_points() {
+        // Stationary driver in Osu reporting every second
+        let points = (0..10).map(|s| point(s, 5.5560, -0.1820)).collect();
+        let kept = downsample(points, 5, 25.0);
+
+        let seconds: Vec<i64> = kept.iter().map(|p| p.timestamp.timestamp() % 60).collect();
+        assert_eq!(seconds, vec![0, 5, 9]);
+    }
+
+    #[test]
+    fn test_downsample_keeps_movement() {
+        // ~110m apart each second - all points carry information
+        let points = (0..4).map(|s| point(s, 5.5560 + 0.001 * s as f64, -0.1820)).collect();
+        assert_eq!(downsample(points, 5, 25.0).len(), 4);
+    }
+
+    #[test]
+    fn test_downsample_sorts_out_of_order_points() {
+        let points = vec![point(20, 5.6, -0.18), point(0, 5.5, -0.18), point(10, 5.55, -0.18)];
+        let kept = downsample(points, 5, 25.0);
+        assert!(kept.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+        assert_eq!(kept.last().unwrap().latitude, 5.6);
+    }
+
+    #[tokio::test]
+    async fn test_an_offline_backlog_joins_the_route_without_moving_the_live_position() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(113);
+        let driver = faker.driver();
+        let mut job = faker.job(&UserId::generate());
+        job.driver_id = Some(driver.id.clone());
+        job.status = JobStatus::InTransit;
+        state.cache_service.cache_job(&job).await.unwrap();
+        state.cache_service.cache_driver_job(&driver.id, &job).await.unwrap();
+
+        let now = Utc::now();
+        let at = |minutes_ago: i64, latitude: f64| LocationUpdate {
+            latitude,
+            longitude: -0.1820,
+            timestamp: now - Duration::minutes(minutes_ago),
+            accuracy: Some(10.0),
+            heading: None,
+            speed: None,
+            is_mock: false,
+        };
+        state.cache_service.cache_driver_locations(&[(driver.id.clone(), at(0, 5.5900))]).await.unwrap();
+
+        // Back online: what was recorded just before, then the older backlog, newest first
+        let recent = state.location_service.ingest_batch(&driver.id, vec![at(10, 5.5800), at(9, 5.5850)]).await.unwrap();
+        let backlog = state.location_service.ingest_batch(&driver.id, vec![at(20, 5.5400), at(25, 5.5200), at(30, 5.5000)]).await.unwrap();
+        assert_eq!((recent.accepted, recent.historical), (2, 2));
+        assert_eq!((backlog.accepted, backlog.historical), (3, 3));
+        let stale = state.location_service.ingest_batch(&driver.id, vec![at(60 * 25, 5.5000)]).await.unwrap();
+        assert_eq!((stale.accepted, stale.rejected), (0, 1));
+
+        let route = state.route_service.get_route(&job.id).await.unwrap();
+        assert_eq!(route.point_count, 5);
+        assert_eq!(route.started_at, Some(at(30, 0.0).timestamp));
+        assert_eq!(route.ended_at, Some(at(9, 0.0).timestamp));
+
+        state.write_behind.shutdown().await;
+        let position = state.cache_service.get_driver_location(&driver.id).await.unwrap().unwrap();
+        assert_eq!(position.latitude, 5.5900);
+    }
+}
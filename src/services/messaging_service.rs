@@ -1,5 +1,6 @@
 // src/services/messaging_service.rs
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::sync::Arc;
 use tracing;
@@ -8,8 +9,8 @@ use chrono::Utc;
 
 use crate::{
     errors::SparrowError as AppError,
-    models::{user::User, driver::Driver, job::Job},
-    services::cache_service::{CacheService, CacheKeys},
+    models::{messages::NotificationType, device::DeviceToken, driver::{DocumentKind, Driver, DriverDocument, OnboardingState, Vehicle}, ids::{DriverId, UserId}, job::{DriverEarnings, Job, JobPriority, NavigationPlan, PaymentStatus, RecipientPreferences}},
+    services::cache_service::CacheService,
 };
 
 #[derive(Debug, Error)]
@@ -28,6 +29,7 @@ pub enum NotificationError {
 pub struct FcmConfig {
     pub fcm_server_key: String,
     pub fcm_url: String,
+    pub iid_url: String,     // Instance ID API, which manages topic subscriptions
 }
 
 impl Default for FcmConfig {
@@ -36,41 +38,63 @@ impl Default for FcmConfig {
             fcm_server_key: std::env::var("FCM_SERVER_KEY")
                 .unwrap_or_else(|_| "".to_string()),
             fcm_url: "https://fcm.googleapis.com/fcm/send".to_string(),
+            iid_url: "https://iid.googleapis.com/iid/v1".to_string(),
         }
     }
 }
 
+// Looked up by the sender before notifying; whatever couldn't be found is left out
+// and the message falls back to generic wording
+#[derive(Debug, Clone, Default)]
+pub struct JobNotificationDetails {
+    pub customer_name: Option<String>, // Only as much as the customer lets drivers see
+    pub driver_name: Option<String>,
+    pub eta_minutes: Option<i64>,      // To the driver's next stop
+    pub navigation: Option<NavigationPlan>, // Only planned for the assignment
+}
+
 #[async_trait]
 pub trait NotificationService: Send + Sync {
     async fn send_to_device(&self, device_token: &str, message: NotificationMessage) -> Result<(), AppError>;
-    async fn send_to_driver(&self, driver_id: &str, message: NotificationMessage) -> Result<(), AppError>;
-    async fn send_to_user(&self, user_id: &str, message: NotificationMessage) -> Result<(), AppError>;
-    async fn notify_driver_assigned(&self, job: &Job, driver: &Driver) -> Result<(), AppError>;
-    async fn notify_package_picked_up(&self, job: &Job) -> Result<(), AppError>;
+    // One request for up to FCM_MULTICAST_LIMIT devices; the same limit applies to topic (un)subscribes
+    async fn send_multicast(&self, device_tokens: &[String], message: NotificationMessage) -> Result<(), AppError>;
+    // Devices on any platform; senders that only speak FCM treat every token as an FCM one
+    async fn send_to_devices(&self, devices: &[DeviceToken], message: NotificationMessage) -> Result<(), AppError> {
+        let device_tokens: Vec<String> = devices.iter().map(|device| device.token.clone()).collect();
+        self.send_multicast(&device_tokens, message).await
+    }
+    async fn send_to_topic(&self, topic: &str, message: NotificationMessage) -> Result<(), AppError>;
+    async fn subscribe_to_topic(&self, topic: &str, device_tokens: &[String]) -> Result<(), AppError>;
+    async fn unsubscribe_from_topic(&self, topic: &str, device_tokens: &[String]) -> Result<(), AppError>;
+    async fn send_to_driver(&self, driver_id: &DriverId, message: NotificationMessage) -> Result<(), AppError>;
+    async fn send_to_user(&self, user_id: &UserId, message: NotificationMessage) -> Result<(), AppError>;
+    async fn notify_driver_assigned(&self, job: &Job, driver: &Driver, earnings: &DriverEarnings, details: &JobNotificationDetails) -> Result<(), AppError>;
+    async fn notify_package_picked_up(&self, job: &Job, details: &JobNotificationDetails) -> Result<(), AppError>;
     async fn notify_delivery_completed(&self, job: &Job) -> Result<(), AppError>;
     async fn notify_ride_status_update(&self, job: &Job, status: &str) -> Result<(), AppError>;
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NotificationMessage {
     pub title: String,
     pub body: String,
     pub data: Option<serde_json::Value>,
     pub priority: NotificationPriority,
+    pub data_only: bool, // Silent: only `data` reaches the app, which handles it in the background
 }
 
-#[derive(Debug, Clone, PartialEq)]
+// Most device tokens FCM accepts in one multicast request
+pub const FCM_MULTICAST_LIMIT: usize = 500;
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum NotificationPriority {
+    Low,     // Promotional and other news; held for the recipient's next digest
     Normal,
+    #[default]
     High,    // Will wake sleeping devices
 }
 
-impl Default for NotificationPriority {
-    fn default() -> Self {
-        Self::High
-    }
-}
-
 pub struct FcmNotificationService {
     config: FcmConfig,
     client: reqwest::Client,
@@ -96,24 +120,44 @@ impl FcmNotificationService {
         )
     }
     
-    async fn get_driver_device_token(&self, driver_id: &str) -> Result<String, AppError> {
-        // TODO: implement proper driver token retrieval
-        // For now, try to get from user instead since we can't convert User to Driver
-        if let Some(user) = self.cache_service.get_user(&CacheKeys::user_by_id(driver_id)).await? {
-            user.device_tokens.first()
-                .cloned()
+    async fn get_driver_device_token(&self, driver_id: &DriverId) -> Result<String, AppError> {
+        if let Some(driver) = self.cache_service.get_driver(driver_id).await? {
+            driver.device_token
+                .map(|device| device.token)
                 .ok_or_else(|| AppError::FcmInvalidToken("Driver has no device token".to_string()))
         } else {
             Err(AppError::DriverNotFound(driver_id.to_string()))
         }
     }
     
-    async fn get_user_device_token(&self, user_id: &str) -> Result<String, AppError> {
+    async fn post(&self, fcm_message: &serde_json::Value) -> Result<(), AppError> {
+        self.post_to(&self.config.fcm_url, fcm_message).await
+    }
+    
+    async fn post_to(&self, url: &str, fcm_message: &serde_json::Value) -> Result<(), AppError> {
+        let response = self.client
+            .post(url)
+            .header("Authorization", format!("key={}", self.config.fcm_server_key))
+            .header("Content-Type", "application/json")
+            .json(fcm_message)
+            .send()
+            .await
+            .map_err(|e| AppError::NetworkConnection(e.to_string()))?;
+        
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            tracing::error!("FCM request failed: {}", error_text);
+            return Err(AppError::FcmDelivery(error_text));
+        }
+        Ok(())
+    }
+    
+    async fn get_user_device_token(&self, user_id: &UserId) -> Result<String, AppError> {
         // This would typically come from your user service
         // For now, we'll use a placeholder
-        if let Some(user) = self.cache_service.get_user(&CacheKeys::user_by_id(user_id)).await? {
+        if let Some(user) = self.cache_service.load_user(user_id).await? {
             user.device_tokens.first()
-                .cloned()
+                .map(|device| device.token.clone())
                 .ok_or_else(|| AppError::FcmInvalidToken("User has no device token".to_string()))
         } else {
             Err(AppError::UserNotFound(user_id.to_string()))
@@ -130,140 +174,75 @@ impl NotificationService for FcmNotificationService {
         
         tracing::info!("Sending FCM notification to device: {}", device_token);
         
-        let mut fcm_message = json!({
-            "to": device_token,
-            "notification": {
-                "title": message.title,
-                "body": message.body,
-                "sound": "default"
-            },
-            "priority": match message.priority {
-                NotificationPriority::High => "high",
-                NotificationPriority::Normal => "normal",
-            }
-        });
+        let mut fcm_message = fcm_payload(message);
+        fcm_message["to"] = json!(device_token);
         
-        if let Some(data) = message.data {
-            fcm_message["data"] = data;
+        self.post(&fcm_message).await?;
+        tracing::debug!("FCM notification sent successfully");
+        Ok(())
+    }
+    
+    async fn send_multicast(&self, device_tokens: &[String], message: NotificationMessage) -> Result<(), AppError> {
+        if device_tokens.is_empty() {
+            return Ok(());
+        }
+        if device_tokens.len() > FCM_MULTICAST_LIMIT {
+            return Err(AppError::FcmDelivery(format!(
+                "{} device tokens in one multicast; FCM takes at most {}", device_tokens.len(), FCM_MULTICAST_LIMIT
+            )));
         }
         
-        let response = self.client
-            .post(&self.config.fcm_url)
-            .header("Authorization", format!("key={}", self.config.fcm_server_key))
-            .header("Content-Type", "application/json")
-            .json(&fcm_message)
-            .send()
-            .await
-            .map_err(|e| AppError::NetworkConnection(e.to_string()))?;
+        tracing::info!("Sending FCM multicast to {} devices", device_tokens.len());
         
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            tracing::error!("FCM request failed: {}", error_text);
-            return Err(AppError::FcmDelivery(error_text));
-        }
+        let mut fcm_message = fcm_payload(message);
+        fcm_message["registration_ids"] = json!(device_tokens);
         
-        tracing::debug!("FCM notification sent successfully");
-        Ok(())
+        self.post(&fcm_message).await
     }
     
-    async fn send_to_driver(&self, driver_id: &str, message: NotificationMessage) -> Result<(), AppError> {
+    async fn send_to_topic(&self, topic: &str, message: NotificationMessage) -> Result<(), AppError> {
+        tracing::info!("Sending FCM notification to topic: {}", topic);
+        
+        let mut fcm_message = fcm_payload(message);
+        fcm_message["to"] = json!(format!("/topics/{}", topic));
+        
+        self.post(&fcm_message).await
+    }
+    
+    async fn subscribe_to_topic(&self, topic: &str, device_tokens: &[String]) -> Result<(), AppError> {
+        let url = format!("{}:batchAdd", self.config.iid_url);
+        self.post_to(&url, &json!({ "to": format!("/topics/{}", topic), "registration_tokens": device_tokens })).await
+    }
+    
+    async fn unsubscribe_from_topic(&self, topic: &str, device_tokens: &[String]) -> Result<(), AppError> {
+        let url = format!("{}:batchRemove", self.config.iid_url);
+        self.post_to(&url, &json!({ "to": format!("/topics/{}", topic), "registration_tokens": device_tokens })).await
+    }
+    
+    async fn send_to_driver(&self, driver_id: &DriverId, message: NotificationMessage) -> Result<(), AppError> {
         let device_token = self.get_driver_device_token(driver_id).await?;
         self.send_to_device(&device_token, message).await
     }
     
-    async fn send_to_user(&self, user_id: &str, message: NotificationMessage) -> Result<(), AppError> {
+    async fn send_to_user(&self, user_id: &UserId, message: NotificationMessage) -> Result<(), AppError> {
         let device_token = self.get_user_device_token(user_id).await?;
         self.send_to_device(&device_token, message).await
     }
     
-    async fn notify_driver_assigned(&self, job: &Job, driver: &Driver) -> Result<(), AppError> {
-        let message = NotificationMessage {
-            title: "🚗 New Delivery Assignment".to_string(),
-            body: format!("Delivery from {} to {} - {} GHS", 
-                job.pickup_location.city, 
-                job.dropoff_location.city,
-                job.pricing.total
-            ),
-            data: Some(json!({
-                "type": "driver_assigned",
-                "job_id": job.id,
-                "amount": job.pricing.total,
-                "pickup_address": job.pickup_location.address,
-                "dropoff_address": job.dropoff_location.address,
-                "customer_name": "Customer", // Would get from user service
-                "priority": job.priority.to_string(),
-            })),
-            priority: NotificationPriority::High,
-        };
-        
-        self.send_to_driver(&driver.id, message).await
+    async fn notify_driver_assigned(&self, job: &Job, driver: &Driver, earnings: &DriverEarnings, details: &JobNotificationDetails) -> Result<(), AppError> {
+        self.send_to_driver(&driver.id, NotificationMessage::driver_assigned(job, earnings, details)).await
     }
     
-    async fn notify_package_picked_up(&self, job: &Job) -> Result<(), AppError> {
-        let message = NotificationMessage {
-            title: "📦 Package Picked Up".to_string(),
-            body: format!("Your package has been collected and is on the way!"),
-            data: Some(json!({
-                "type": "package_picked_up",
-                "job_id": job.id,
-                "driver_name": "Driver", // Would get from driver service
-                "estimated_arrival": "30 minutes", // Would calculate ETA
-            })),
-            priority: NotificationPriority::Normal,
-        };
-        
-        self.send_to_user(&job.customer_id, message).await
+    async fn notify_package_picked_up(&self, job: &Job, details: &JobNotificationDetails) -> Result<(), AppError> {
+        self.send_to_user(&job.customer_id, NotificationMessage::package_picked_up(job, details)).await
     }
     
     async fn notify_delivery_completed(&self, job: &Job) -> Result<(), AppError> {
-        let message = NotificationMessage {
-            title: "✅ Delivery Completed".to_string(),
-            body: format!("Your package has been delivered successfully!"),
-            data: Some(json!({
-                "type": "delivery_completed",
-                "job_id": job.id,
-                "amount": job.pricing.total,
-                "completion_time": Utc::now().to_rfc3339(),
-            })),
-            priority: NotificationPriority::Normal,
-        };
-        
-        self.send_to_user(&job.customer_id, message).await
+        self.send_to_user(&job.customer_id, NotificationMessage::delivery_completed(job)).await
     }
     
     async fn notify_ride_status_update(&self, job: &Job, status: &str) -> Result<(), AppError> {
-        let (title, body) = match status {
-            "driver_en_route" => (
-                "🚗 Driver On The Way".to_string(),
-                "Your driver is coming to pickup location".to_string()
-            ),
-            "driver_arrived" => (
-                "📍 Driver Arrived".to_string(),
-                "Your driver has arrived at pickup location".to_string()
-            ),
-            "in_progress" => (
-                "📦 Package In Transit".to_string(),
-                "Your package is on the way to destination".to_string()
-            ),
-            _ => (
-                "📋 Status Updated".to_string(),
-                format!("Delivery status: {}", status)
-            ),
-        };
-        
-        let message = NotificationMessage {
-            title,
-            body,
-            data: Some(json!({
-                "type": "status_update",
-                "job_id": job.id,
-                "status": status,
-                "timestamp": Utc::now().to_rfc3339(),
-            })),
-            priority: NotificationPriority::Normal,
-        };
-        
-        self.send_to_user(&job.customer_id, message).await
+        self.send_to_user(&job.customer_id, NotificationMessage::status_update(job, status)).await
     }
 }
 
@@ -279,24 +258,46 @@ impl NotificationService for MockNotificationService {
         Ok(())
     }
     
-    async fn send_to_driver(&self, driver_id: &str, message: NotificationMessage) -> Result<(), AppError> {
+    async fn send_multicast(&self, device_tokens: &[String], message: NotificationMessage) -> Result<(), AppError> {
+        tracing::info!("[MOCK] Would multicast FCM to {} devices: {} - {}", 
+            device_tokens.len(), message.title, message.body);
+        Ok(())
+    }
+    
+    async fn send_to_topic(&self, topic: &str, message: NotificationMessage) -> Result<(), AppError> {
+        tracing::info!("[MOCK] Would send FCM to topic {}: {} - {}", 
+            topic, message.title, message.body);
+        Ok(())
+    }
+    
+    async fn subscribe_to_topic(&self, topic: &str, device_tokens: &[String]) -> Result<(), AppError> {
+        tracing::info!("[MOCK] Would subscribe {} devices to topic {}", device_tokens.len(), topic);
+        Ok(())
+    }
+    
+    async fn unsubscribe_from_topic(&self, topic: &str, device_tokens: &[String]) -> Result<(), AppError> {
+        tracing::info!("[MOCK] Would unsubscribe {} devices from topic {}", device_tokens.len(), topic);
+        Ok(())
+    }
+    
+    async fn send_to_driver(&self, driver_id: &DriverId, message: NotificationMessage) -> Result<(), AppError> {
         tracing::info!("[MOCK] Would send to driver {}: {} - {}", 
             driver_id, message.title, message.body);
         Ok(())
     }
     
-    async fn send_to_user(&self, user_id: &str, message: NotificationMessage) -> Result<(), AppError> {
+    async fn send_to_user(&self, user_id: &UserId, message: NotificationMessage) -> Result<(), AppError> {
         tracing::info!("[MOCK] Would send to user {}: {} - {}", 
             user_id, message.title, message.body);
         Ok(())
     }
     
-    async fn notify_driver_assigned(&self, job: &Job, driver: &Driver) -> Result<(), AppError> {
+    async fn notify_driver_assigned(&self, job: &Job, driver: &Driver, _earnings: &DriverEarnings, _details: &JobNotificationDetails) -> Result<(), AppError> {
         tracing::info!("[MOCK] Driver assigned: {} to job {}", driver.id, job.id);
         Ok(())
     }
     
-    async fn notify_package_picked_up(&self, job: &Job) -> Result<(), AppError> {
+    async fn notify_package_picked_up(&self, job: &Job, _details: &JobNotificationDetails) -> Result<(), AppError> {
         tracing::info!("[MOCK] Package picked up for job: {}", job.id);
         Ok(())
     }
@@ -320,6 +321,7 @@ impl NotificationMessage {
             body: body.to_string(),
             data: None,
             priority: NotificationPriority::default(),
+            data_only: false,
         }
     }
     
@@ -332,4 +334,457 @@ impl NotificationMessage {
         self.priority = priority;
         self
     }
+
+    // Send silently; the title and body stay for logs and in-app use
+    pub fn data_only(mut self) -> Self {
+        self.data_only = true;
+        self
+    }
+
+    // Sent to the driver when a job is assigned to them
+    pub fn driver_assigned(job: &Job, earnings: &DriverEarnings, details: &JobNotificationDetails) -> Self {
+        NotificationMessage {
+            title: "🚗 New Delivery Assignment".to_string(),
+            body: format!("Delivery from {} to {} - you earn {}", 
+                job.pickup_location.city, 
+                job.dropoff_location.city,
+                earnings.total
+            ),
+            data: Some(json!({
+                "type": "driver_assigned",
+                "job_id": job.id,
+                "amount": earnings.total.to_major(),
+                "earnings": earnings,
+                "pickup_address": job.pickup_location.address,
+                "dropoff_address": job.dropoff_location.address,
+                "customer_name": details.customer_name.as_deref().unwrap_or("Customer"),
+                "eta_to_pickup_minutes": details.eta_minutes,
+                "priority": job.priority.to_string(),
+                "package_photo_url": job.package_photo.as_ref().map(|photo| &photo.url),
+                "package_size_mismatch": job.package_photo.as_ref().is_some_and(|photo| photo.size_mismatch),
+                "navigation": details.navigation,
+            })),
+            priority: NotificationPriority::High,
+            data_only: false,
+        }
+    }
+    
+    pub fn package_picked_up(job: &Job, details: &JobNotificationDetails) -> Self {
+        let body = match details.eta_minutes {
+            Some(minutes) => format!("Your package has been collected and should arrive in about {} minutes.", minutes),
+            None => "Your package has been collected and is on the way!".to_string(),
+        };
+        NotificationMessage {
+            title: "📦 Package Picked Up".to_string(),
+            body,
+            data: Some(json!({
+                "type": "package_picked_up",
+                "job_id": job.id,
+                "driver_name": details.driver_name.as_deref().unwrap_or("Driver"),
+                "estimated_arrival": details.eta_minutes.map(|minutes| format!("{} minutes", minutes)),
+                "estimated_arrival_minutes": details.eta_minutes,
+            })),
+            priority: NotificationPriority::Normal,
+            data_only: false,
+        }
+    }
+    
+    pub fn delivery_completed(job: &Job) -> Self {
+        NotificationMessage {
+            title: "✅ Delivery Completed".to_string(),
+            body: "Your package has been delivered successfully!".to_string(),
+            data: Some(json!({
+                "type": "delivery_completed",
+                "job_id": job.id,
+                "amount": job.pricing.total.to_major(),
+                "completion_time": Utc::now().to_rfc3339(),
+            })),
+            priority: NotificationPriority::Normal,
+            data_only: false,
+        }
+    }
+    
+    pub fn payment_refunded(job: &Job) -> Self {
+        NotificationMessage {
+            title: "💸 Refund Issued".to_string(),
+            body: format!("We've refunded {} for your delivery.", job.pricing.total),
+            data: Some(json!({
+                "type": "payment_refunded",
+                "job_id": job.id,
+                "amount": job.pricing.total.to_major(),
+            })),
+            priority: NotificationPriority::Normal,
+            data_only: false,
+        }
+    }
+    
+    // Sent to the customer when no driver accepted their job. Copy follows the user's
+    // language (primary subtag, e.g. "fr-GH" -> "fr") and falls back to English.
+    pub fn job_expired(job: &Job, language: &str, reason: &str) -> Self {
+        let language = language.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+        let french = language == "fr";
+        let suggested = job.priority.next_tier();
+
+        let title = if french { "😔 Aucun livreur disponible" } else { "😔 No driver available" };
+        let mut body = if french {
+            "Aucun livreur n'a accepté votre demande de livraison.".to_string()
+        } else {
+            "No driver accepted your delivery request.".to_string()
+        };
+        if matches!(job.payment_status, PaymentStatus::Voided | PaymentStatus::Refunded) {
+            body.push_str(if french { " Vous n'avez pas été débité." } else { " You have not been charged." });
+        }
+        if let Some(tier) = &suggested {
+            let label = priority_label(tier, french);
+            body.push_str(&if french {
+                format!(" Réessayez en {} pour une prise en charge plus rapide.", label)
+            } else {
+                format!(" Try again as {} for a faster pickup.", label)
+            });
+        }
+
+        NotificationMessage {
+            title: title.to_string(),
+            body,
+            data: Some(json!({
+                "type": "job_expired",
+                "job_id": job.id,
+                "reason": reason,
+                "payment_status": job.payment_status,
+                "suggested_priority": suggested.map(|tier| tier.to_string()),
+                "language": if french { "fr" } else { "en" },
+            })),
+            priority: NotificationPriority::High,
+            data_only: false,
+        }
+    }
+
+    // Sent to the customer when their driver went silent before pickup
+    pub fn driver_reassigning(job: &Job) -> Self {
+        NotificationMessage {
+            title: "🔄 Finding You A New Driver".to_string(),
+            body: "Your driver stopped responding, so we're matching your delivery with another driver.".to_string(),
+            data: Some(json!({
+                "type": "driver_reassigning",
+                "job_id": job.id,
+                "status": job.status,
+            })),
+            priority: NotificationPriority::High,
+            data_only: false,
+        }
+    }
+
+    // Sent to every driver a dispatcher broadcasts an unassigned job to
+    pub fn job_offer(job: &Job, earnings: &DriverEarnings) -> Self {
+        NotificationMessage {
+            title: "📣 Delivery Available Nearby".to_string(),
+            body: format!("Pickup in {} - you earn {}", job.pickup_location.city, earnings.total),
+            data: Some(json!({
+                "type": "job_offer",
+                "job_id": job.id,
+                "amount": earnings.total.to_major(),
+                "earnings": earnings,
+                "pickup_address": job.pickup_location.address,
+                "dropoff_address": job.dropoff_location.address,
+                "priority": job.priority.to_string(),
+            })),
+            priority: NotificationPriority::High,
+            data_only: true, // The app refreshes its offers feed and shows the offer itself
+        }
+    }
+
+    // Tells driver apps to re-fetch a piece of configuration, e.g. "commissions"
+    pub fn config_updated(section: &str) -> Self {
+        NotificationMessage::new("Configuration updated", section)
+            .with_data(json!({
+                "type": "config_updated",
+                "section": section,
+                "timestamp": Utc::now().to_rfc3339(),
+            }))
+            .with_priority(NotificationPriority::Normal)
+            .data_only()
+    }
+
+    // Sent to a driver taken off a job by a dispatcher
+    pub fn job_unassigned(job: &Job) -> Self {
+        NotificationMessage {
+            title: "↩️ Delivery Reassigned".to_string(),
+            body: format!("Delivery {} has been taken off your schedule by dispatch.", job.tracking_code),
+            data: Some(json!({
+                "type": "job_unassigned",
+                "job_id": job.id,
+            })),
+            priority: NotificationPriority::High,
+            data_only: false,
+        }
+    }
+
+    // Sent to an applicant each time their application moves to another stage
+    pub fn onboarding_update(driver_id: &DriverId, state: OnboardingState, note: Option<&str>) -> Self {
+        let (title, body) = match state {
+            OnboardingState::DocumentsSubmitted => ("📄 Documents Received", "We've received your documents and will review them shortly."),
+            OnboardingState::BackgroundCheck => ("✅ Documents Approved", "Your documents are approved. Your background check is now under way."),
+            OnboardingState::VehicleInspection => ("🔍 Background Check Passed", "Next step: bring your vehicle in for inspection."),
+            OnboardingState::Approved => ("🎉 You're Approved!", "Welcome aboard! You can now go online and start taking deliveries."),
+            OnboardingState::Rejected => ("⚠️ Application Not Approved", "Your application needs attention. Please check the note and resubmit your documents."),
+        };
+        let body = match note {
+            Some(note) => format!("{} {}", body, note),
+            None => body.to_string(),
+        };
+        NotificationMessage {
+            title: title.to_string(),
+            body,
+            data: Some(json!({
+                "type": "onboarding_update",
+                "driver_id": driver_id,
+                "state": state,
+                "note": note,
+            })),
+            priority: NotificationPriority::Normal,
+            data_only: false,
+        }
+    }
+
+    pub fn document_expiring(driver_id: &DriverId, document: &DriverDocument, days_left: i64) -> Self {
+        let name = document_name(document.kind);
+        let when = match days_left {
+            0 => "today".to_string(),
+            1 => "tomorrow".to_string(),
+            days => format!("in {} days", days),
+        };
+        NotificationMessage {
+            title: "📅 Document Expiring".to_string(),
+            body: format!("Your {} expires {}. Upload the renewed one to keep driving.", name, when),
+            data: Some(json!({
+                "type": "document_expiring",
+                "driver_id": driver_id,
+                "document": name,
+                "expires_at": document.expires_at,
+                "days_left": days_left,
+            })),
+            priority: NotificationPriority::Normal,
+            data_only: false,
+        }
+    }
+
+    pub fn vehicle_service_due(driver_id: &DriverId, vehicle: &Vehicle, km_since_service: f64) -> Self {
+        NotificationMessage {
+            title: "🔧 Service Due".to_string(),
+            body: format!(
+                "Your {} {} ({}) has done {:.0} km since its last service. Please book it in.",
+                vehicle.make, vehicle.model, vehicle.license_plate, km_since_service,
+            ),
+            data: Some(json!({
+                "type": "vehicle_service_due",
+                "driver_id": driver_id,
+                "vehicle_id": vehicle.id,
+                "km_since_service": km_since_service,
+            })),
+            priority: NotificationPriority::Normal,
+            data_only: false,
+        }
+    }
+
+    pub fn driver_suspended(driver_id: &DriverId, reason: &str) -> Self {
+        NotificationMessage {
+            title: "⛔ Account Suspended".to_string(),
+            body: format!("{} You won't receive deliveries until this is fixed.", reason),
+            data: Some(json!({
+                "type": "driver_suspended",
+                "driver_id": driver_id,
+                "reason": reason,
+            })),
+            priority: NotificationPriority::High,
+            data_only: false,
+        }
+    }
+
+    pub fn driver_reinstated(driver_id: &DriverId) -> Self {
+        NotificationMessage {
+            title: "✅ Account Reinstated".to_string(),
+            body: "Thanks for renewing your documents. You can go online again.".to_string(),
+            data: Some(json!({
+                "type": "driver_reinstated",
+                "driver_id": driver_id,
+            })),
+            priority: NotificationPriority::Normal,
+            data_only: false,
+        }
+    }
+
+    // Sent to the assigned driver when the customer redirects the package
+    pub fn dropoff_changed(job: &Job, navigation: Option<&NavigationPlan>) -> Self {
+        NotificationMessage {
+            title: "📍 New Dropoff".to_string(),
+            body: format!("Delivery {} now goes to {}.", job.tracking_code, job.dropoff_location.address),
+            data: Some(json!({
+                "type": "dropoff_changed",
+                "job_id": job.id,
+                "tracking_code": job.tracking_code,
+                "dropoff_address": job.dropoff_location.address,
+                "navigation": navigation,
+            })),
+            priority: NotificationPriority::High,
+            data_only: false,
+        }
+    }
+
+    // Sent to the assigned driver when the recipient changes how they want the package delivered
+    pub fn delivery_preferences_updated(job: &Job, preferences: &RecipientPreferences) -> Self {
+        let body = match (&preferences.safe_drop, &preferences.deliver_after) {
+            (_, Some(deliver_after)) => format!("Deliver {} no earlier than {}.", job.tracking_code, deliver_after.format("%H:%M %d/%m")),
+            (Some(safe_drop), None) => format!("{} may be left at: {}", job.tracking_code, safe_drop),
+            (None, None) => format!("The recipient of {} left new instructions.", job.tracking_code),
+        };
+        NotificationMessage {
+            title: "📝 Delivery Instructions Updated".to_string(),
+            body,
+            data: Some(json!({
+                "type": "delivery_preferences_updated",
+                "job_id": job.id,
+                "tracking_code": job.tracking_code,
+                "instructions": preferences.instructions,
+                "safe_drop": preferences.safe_drop,
+                "deliver_after": preferences.deliver_after.map(|at| at.to_rfc3339()),
+            })),
+            priority: NotificationPriority::High,
+            data_only: false,
+        }
+    }
+
+    pub fn status_update(job: &Job, status: &str) -> Self {
+        let (title, body) = match status {
+            "driver_en_route" => (
+                "🚗 Driver On The Way".to_string(),
+                "Your driver is coming to pickup location".to_string()
+            ),
+            "driver_arrived" => (
+                "📍 Driver Arrived".to_string(),
+                "Your driver has arrived at pickup location".to_string()
+            ),
+            "in_progress" => (
+                "📦 Package In Transit".to_string(),
+                "Your package is on the way to destination".to_string()
+            ),
+            _ => (
+                "📋 Status Updated".to_string(),
+                format!("Delivery status: {}", status)
+            ),
+        };
+        
+        NotificationMessage {
+            title,
+            body,
+            data: Some(json!({
+                "type": "status_update",
+                "job_id": job.id,
+                "status": status,
+                "timestamp": Utc::now().to_rfc3339(),
+            })),
+            priority: NotificationPriority::Normal,
+            data_only: false,
+        }
+    }
+    
+    // The `type` tag in `data`, e.g. "driver_assigned"
+    pub fn kind(&self) -> Option<&str> {
+        self.data.as_ref()?.get("type")?.as_str()
+    }
+
+    // Goes out even in the recipient's quiet hours. Silent messages disturb no one.
+    pub fn is_critical(&self) -> bool {
+        self.data_only
+            || self.priority == NotificationPriority::High
+            || self.kind().and_then(NotificationType::from_kind).is_some_and(|kind| kind.is_critical())
+    }
+}
+
+// How a document is named to its holder
+pub fn document_name(kind: DocumentKind) -> &'static str {
+    match kind {
+        DocumentKind::DriversLicense => "driver's licence",
+        DocumentKind::NationalId => "national ID",
+        DocumentKind::VehicleRegistration => "vehicle registration",
+        DocumentKind::Insurance => "insurance certificate",
+        DocumentKind::RoadworthyCertificate => "roadworthy certificate",
+    }
+}
+
+// Message body without its addressee. Data-only messages carry no `notification`, so
+// nothing is shown; `content_available` wakes iOS apps in the background as well.
+fn fcm_payload(message: NotificationMessage) -> serde_json::Value {
+    let mut fcm_message = json!({ "priority": fcm_priority(&message.priority) });
+    if message.data_only {
+        fcm_message["content_available"] = json!(true);
+    } else {
+        fcm_message["notification"] = json!({
+            "title": message.title,
+            "body": message.body,
+            "sound": "default"
+        });
+    }
+    if let Some(data) = message.data {
+        fcm_message["data"] = data;
+    }
+    fcm_message
+}
+
+// FCM only knows two priorities; low-priority messages reaching it go out as normal
+fn fcm_priority(priority: &NotificationPriority) -> &'static str {
+    match priority {
+        NotificationPriority::High => "high",
+        NotificationPriority::Normal | NotificationPriority::Low => "normal",
+    }
+}
+
+fn priority_label(priority: &JobPriority, french: bool) -> &'static str {
+    match (priority, french) {
+        (JobPriority::Standard, _) => "Standard",
+        (JobPriority::SameDay, false) => "Same-day",
+        (JobPriority::SameDay, true) => "Jour même",
+        (JobPriority::Express, _) => "Express",
+        (JobPriority::Emergency, false) => "Emergency",
+        (JobPriority::Emergency, true) => "Urgence",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mocks::fixtures::Faker, models::user::{NameVisibility, UserType}};
+
+    #[test]
+    fn test_data_only_messages_carry_no_notification() {
+        let alert = fcm_payload(NotificationMessage::new("Delivered", "At the door"));
+        assert_eq!(alert["notification"]["title"], "Delivered");
+        assert!(alert.get("content_available").is_none());
+
+        let silent = fcm_payload(NotificationMessage::config_updated("commissions"));
+        assert!(silent.get("notification").is_none());
+        assert_eq!(silent["content_available"], true);
+        assert_eq!(silent["data"]["section"], "commissions");
+    }
+
+    #[test]
+    fn test_pickup_details_fall_back_when_unknown() {
+        let mut faker = Faker::new();
+        let mut customer = faker.user(UserType::Customer);
+        let job = faker.job(&customer.id);
+
+        let generic = NotificationMessage::package_picked_up(&job, &JobNotificationDetails::default());
+        let data = generic.data.unwrap();
+        assert_eq!(data["driver_name"], "Driver");
+        assert!(data["estimated_arrival"].is_null());
+
+        let details = JobNotificationDetails { driver_name: Some("Kofi".to_string()), eta_minutes: Some(12), ..Default::default() };
+        let enriched = NotificationMessage::package_picked_up(&job, &details);
+        assert!(enriched.body.contains("12 minutes"));
+        assert_eq!(enriched.data.unwrap()["driver_name"], "Kofi");
+
+        // Drivers see no more of the customer's name than they allow
+        assert_eq!(customer.name_for_drivers(), Some(customer.first_name.clone()));
+        customer.name_visibility = NameVisibility::Hidden;
+        assert_eq!(customer.name_for_drivers(), None);
+    }
 }
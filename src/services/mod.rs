@@ -1,6 +1,79 @@
+pub mod cache_codec;
 pub mod cache_service;
+pub mod database;
+pub mod dispatch;
+pub mod dispatch_settings;
+pub mod package_analysis;
+pub mod handoff;
+pub mod delivery_code;
+pub mod arrival_calls;
+pub mod business_accounts;
+pub mod tracking_service;
+pub mod dispatcher_service;
+pub mod pooling;
+pub mod driver_channel;
+pub mod presence_service;
+#[cfg(feature = "mqtt")]
+pub mod mqtt_bridge;
+pub mod earnings;
 pub mod driver_service;
+pub mod onboarding_service;
 pub mod job_service;
 pub mod user_service;
+pub mod device_service;
+pub mod otp_service;
+pub mod retention_service;
+pub mod events_export;
+pub mod alert_service;
+pub mod incident_service;
+pub mod risk_service;
+pub mod canary_service;
+pub mod chaos;
+pub mod quota_service;
+pub mod runtime_config;
+pub mod startup;
+pub mod simulator;
+pub mod status_feed;
 pub mod messaging_service;
+pub mod sms;
+pub mod voice;
+pub mod apns;
+pub mod multi_channel;
+pub mod notification_batching;
+pub mod notification_templates;
+pub mod broadcast_service;
+pub mod location_service;
+pub mod odometer;
+pub mod route_service;
+pub mod dashboard_service;
+pub mod directory_service;
+pub mod moderation_service;
+pub mod demand_service;
+pub mod export_service;
+pub mod api_key_service;
+pub mod tenant_service;
+pub mod region;
+pub mod tax;
+pub mod calendar;
+pub mod country;
+pub mod i18n;
+pub mod event_bus;
+pub mod consumers;
+pub mod saga;
+pub mod job_sagas;
+pub mod event_subscribers;
+pub mod ledger;
+#[cfg(feature = "payments")]
+pub mod reconciliation;
+#[cfg(feature = "payments")]
+pub mod dispute_service;
+pub mod invoice_service;
+pub mod exchange_rates;
 pub mod realtime;
+#[cfg(feature = "realtime-ably")]
+pub mod ably_auth;
+pub mod realtime_publisher;
+pub mod zone_service;
+pub mod realtime_bus;
+pub mod send_queue;
+pub mod write_behind;
@@ -0,0 +1,242 @@
+// src/services/moderation_service.rs
+// Customers and drivers can block each other; dispatch never pairs a blocked pair again
+// (see `CacheService::is_pair_blocked`). Admins can ban an account from the platform,
+// which also ends its sessions: the app login, any API keys and a driver's live socket.
+use chrono::Utc;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        dispatch::DriverSocketEvent,
+        driver::{Driver, DriverResponse, DriverStatus},
+        ids::{DriverId, UserId},
+        moderation::AccountBan,
+        user::{User, UserResponse, UserStatus},
+    },
+    services::{api_key_service::ApiKeyService, cache_service::CacheService, driver_channel::DriverChannel},
+};
+
+pub struct ModerationService {
+    cache_service: Arc<CacheService>,
+    api_key_service: Arc<ApiKeyService>,
+    driver_channel: Arc<DriverChannel>,
+}
+
+impl ModerationService {
+    pub fn new(
+        cache_service: Arc<CacheService>,
+        api_key_service: Arc<ApiKeyService>,
+        driver_channel: Arc<DriverChannel>,
+    ) -> Self {
+        Self { cache_service, api_key_service, driver_channel }
+    }
+
+    async fn load_user(&self, user_id: &UserId) -> Result<User, AppError> {
+        self.cache_service.load_user(user_id).await?
+            .ok_or_else(|| AppError::user_not_found(user_id.as_str()))
+    }
+
+    async fn load_driver(&self, driver_id: &DriverId) -> Result<Driver, AppError> {
+        self.cache_service.get_driver(driver_id).await?
+            .ok_or_else(|| AppError::driver_not_found(driver_id.as_str()))
+    }
+
+    pub async fn blocked_drivers(&self, user_id: &UserId) -> Result<Vec<DriverId>, AppError> {
+        let mut driver_ids = self.cache_service.get_drivers_blocked_by(user_id).await?;
+        driver_ids.sort();
+        Ok(driver_ids)
+    }
+
+    /// Returns the customer's block list as it now stands
+    pub async fn block_driver(&self, user_id: &UserId, driver_id: &DriverId) -> Result<Vec<DriverId>, AppError> {
+        self.load_user(user_id).await?;
+        self.load_driver(driver_id).await?;
+        self.cache_service.cache_driver_block(user_id, driver_id).await?;
+        tracing::info!("User {} blocked driver {}", user_id, driver_id);
+        self.blocked_drivers(user_id).await
+    }
+
+    pub async fn unblock_driver(&self, user_id: &UserId, driver_id: &DriverId) -> Result<Vec<DriverId>, AppError> {
+        self.cache_service.remove_driver_block(user_id, driver_id).await?;
+        self.blocked_drivers(user_id).await
+    }
+
+    pub async fn blocked_customers(&self, driver_id: &DriverId) -> Result<Vec<UserId>, AppError> {
+        let mut user_ids = self.cache_service.get_customers_blocked_by(driver_id).await?;
+        user_ids.sort();
+        Ok(user_ids)
+    }
+
+    /// Returns the driver's block list as it now stands
+    pub async fn block_customer(&self, driver_id: &DriverId, user_id: &UserId) -> Result<Vec<UserId>, AppError> {
+        self.load_driver(driver_id).await?;
+        self.load_user(user_id).await?;
+        self.cache_service.cache_customer_block(driver_id, user_id).await?;
+        tracing::info!("Driver {} blocked customer {}", driver_id, user_id);
+        self.blocked_customers(driver_id).await
+    }
+
+    pub async fn unblock_customer(&self, driver_id: &DriverId, user_id: &UserId) -> Result<Vec<UserId>, AppError> {
+        self.cache_service.remove_customer_block(driver_id, user_id).await?;
+        self.blocked_customers(driver_id).await
+    }
+
+    pub async fn ban_user(&self, user_id: &UserId, reason: &str) -> Result<UserResponse, AppError> {
+        let ban = new_ban(reason)?;
+        let mut user = self.load_user(user_id).await?;
+        self.apply_user_ban(&mut user, ban).await?;
+        Ok(UserResponse::from(user))
+    }
+
+    pub async fn unban_user(&self, user_id: &UserId) -> Result<UserResponse, AppError> {
+        let mut user = self.load_user(user_id).await?;
+        if user.ban.take().is_some() {
+            user.status = UserStatus::Active;
+            user.updated_at = Utc::now();
+            self.cache_service.cache_user(&user).await?;
+            tracing::info!("Lifted ban on user {}", user.id);
+        }
+        Ok(UserResponse::from(user))
+    }
+
+    /// Bans the driver and the account they log in with. A delivery already under way
+    /// can be finished; nothing new is offered.
+    pub async fn ban_driver(&self, driver_id: &DriverId, reason: &str) -> Result<DriverResponse, AppError> {
+        let ban = new_ban(reason)?;
+        let mut driver = self.load_driver(driver_id).await?;
+        driver.ban = Some(ban.clone());
+        driver.is_active = false;
+        if driver.status != DriverStatus::OnRide {
+            driver.status = DriverStatus::Offline;
+            driver.break_started_at = None;
+            driver.break_ends_at = None;
+        }
+        driver.updated_at = Utc::now();
+        self.cache_service.cache_driver(&driver).await?;
+        tracing::info!("Banned driver {}: {}", driver.id, ban.reason);
+
+        if let Some(mut user) = self.cache_service.load_user(&driver.user_id).await? {
+            self.apply_user_ban(&mut user, ban.clone()).await?;
+        }
+        // The socket closes once the driver has been told why
+        self.driver_channel.send(&driver.id, DriverSocketEvent::SessionRevoked { reason: ban.reason }).await;
+        Ok(DriverResponse::from(driver))
+    }
+
+    pub async fn unban_driver(&self, driver_id: &DriverId) -> Result<DriverResponse, AppError> {
+        let mut driver = self.load_driver(driver_id).await?;
+        if driver.ban.take().is_some() {
+            // A suspension for lapsed documents outlasts the ban
+            driver.is_active = driver.suspended_reason.is_none();
+            driver.updated_at = Utc::now();
+            self.cache_service.cache_driver(&driver).await?;
+            tracing::info!("Lifted ban on driver {}", driver.id);
+            if self.cache_service.load_user(&driver.user_id).await?.is_some_and(|user| user.ban.is_some()) {
+                self.unban_user(&driver.user_id).await?;
+            }
+        }
+        Ok(DriverResponse::from(driver))
+    }
+
+    async fn apply_user_ban(&self, user: &mut User, ban: AccountBan) -> Result<(), AppError> {
+        user.status = UserStatus::Banned;
+        user.ban = Some(ban);
+        user.current_session = None;
+        user.updated_at = Utc::now();
+        self.cache_service.cache_user(user).await?;
+
+        let now = Utc::now();
+        for key in self.api_key_service.list_keys(&user.id).await? {
+            let usable = key.revoked_at.is_none() && key.expires_at.is_none_or(|expires_at| expires_at > now);
+            if usable {
+                self.api_key_service.revoke_key(&key.id).await?;
+            }
+        }
+        tracing::info!("Banned user {}", user.id);
+        Ok(())
+    }
+}
+
+fn new_ban(reason: &str) -> Result<AccountBan, AppError> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::validation_error("reason", "A ban needs a reason"));
+    }
+    Ok(AccountBan { reason: reason.to_string(), banned_at: Utc::now() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::{api_key::{ApiScope, CreateApiKeyRequest}, job::LocationUpdate, user::UserType},
+        services::job_service::JobOperations,
+    };
+
+    #[tokio::test]
+    async fn test_blocked_pairs_are_never_dispatched_and_bans_end_sessions() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(21);
+
+        let mut customer = faker.user(UserType::Business);
+        customer.current_session = Some("token_customer".to_string());
+        state.cache_service.cache_user(&customer).await.unwrap();
+        let job = faker.job(&customer.id);
+        state.cache_service.cache_job(&job).await.unwrap();
+
+        let (blocked_by_customer, blocking_customer, free) = (faker.driver(), faker.driver(), faker.driver());
+        let mut locations = Vec::new();
+        for driver in [&blocked_by_customer, &blocking_customer, &free] {
+            let mut driver = driver.clone();
+            driver.status = DriverStatus::Online;
+            state.cache_service.cache_driver(&driver).await.unwrap();
+            locations.push((driver.id.clone(), LocationUpdate {
+                latitude: job.pickup_location.latitude,
+                longitude: job.pickup_location.longitude,
+                timestamp: Utc::now(),
+                accuracy: None,
+                heading: None,
+                speed: None,
+                is_mock: false,
+            }));
+        }
+        state.cache_service.cache_driver_locations(&locations).await.unwrap();
+
+        let moderation = &state.moderation_service;
+        moderation.block_driver(&customer.id, &blocked_by_customer.id).await.unwrap();
+        moderation.block_customer(&blocking_customer.id, &customer.id).await.unwrap();
+        assert_eq!(state.job_service.find_available_drivers(&job.id).await.unwrap(), vec![free.id.clone()]);
+        assert!(state.job_service.assign_driver_to_job(&job.id, &blocking_customer.id).await.is_err());
+
+        let unblocked = moderation.unblock_customer(&blocking_customer.id, &customer.id).await.unwrap();
+        assert!(unblocked.is_empty());
+        assert_eq!(state.job_service.find_available_drivers(&job.id).await.unwrap().len(), 2);
+
+        // A ban ends the login session and revokes the merchant's API keys
+        state.api_key_service.issue_key(CreateApiKeyRequest {
+            merchant_id: customer.id.clone(),
+            name: "Storefront".to_string(),
+            scopes: vec![ApiScope::CreateJobs],
+            rate_limit_per_minute: None,
+            quotas: Vec::new(),
+        }).await.unwrap();
+        assert!(moderation.ban_user(&customer.id, " ").await.is_err());
+        let banned = moderation.ban_user(&customer.id, "Abusive towards drivers").await.unwrap();
+        assert_eq!(banned.status, UserStatus::Banned);
+        let stored = state.cache_service.load_user(&customer.id).await.unwrap().unwrap();
+        assert!(stored.current_session.is_none());
+        let keys = state.api_key_service.list_keys(&customer.id).await.unwrap();
+        assert!(keys.iter().all(|key| key.revoked_at.is_some()));
+        assert!(state.job_service.create_job(faker.job_request(&customer.id)).await.is_err());
+
+        let driver = moderation.ban_driver(&free.id, "Fraudulent deliveries").await.unwrap();
+        assert!(driver.is_banned);
+        assert_eq!(state.job_service.find_available_drivers(&job.id).await.unwrap(), vec![blocking_customer.id.clone()]);
+        let driver = moderation.unban_driver(&free.id).await.unwrap();
+        assert!(!driver.is_banned);
+    }
+}
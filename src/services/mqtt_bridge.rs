@@ -0,0 +1,166 @@
+// src/services/mqtt_bridge.rs
+// MQTT for fleets whose drivers run cheap phones on 2G, where WebSockets keep dropping.
+// Offers are mirrored to `<prefix>/<tenant>/drivers/<driver>/offers` and drivers publish
+// location batches to `.../location`. Only tenants with `mqtt_enabled` take part. Offers are
+// encoded in the tenant's `mqtt_format`; incoming batches may be JSON or CBOR either way.
+use rumqttc::{AsyncClient, Event, EventLoop, MqttOptions, Packet, QoS};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{dispatch::DriverSocketEvent, driver::DriverLocationBatch, ids::DriverId},
+    services::{
+        location_service::LocationService,
+        realtime::FrameFormat,
+        tenant_service::{current_tenant_id, with_tenant, TenantService},
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct MqttConfig {
+    pub broker_host: String,
+    pub broker_port: u16,
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub topic_prefix: String,
+    pub keep_alive_seconds: u64, // Long, so flaky links aren't dropped between packets
+    pub reconnect_delay_seconds: u64,
+}
+
+impl MqttConfig {
+    // Set up only when a broker is configured
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        Some(Self {
+            broker_host: var("MQTT_BROKER_HOST")?,
+            broker_port: var("MQTT_BROKER_PORT").and_then(|port| port.parse().ok()).unwrap_or(1883),
+            client_id: var("MQTT_CLIENT_ID").unwrap_or_else(|| "sparrow-realtime".to_string()),
+            username: var("MQTT_USERNAME"),
+            password: var("MQTT_PASSWORD"),
+            topic_prefix: var("MQTT_TOPIC_PREFIX").unwrap_or_else(|| "sparrow".to_string()),
+            keep_alive_seconds: 120,
+            reconnect_delay_seconds: 5,
+        })
+    }
+}
+
+pub struct MqttBridge {
+    client: AsyncClient,
+    tenant_service: Arc<TenantService>,
+    location_service: Arc<LocationService>,
+    config: MqttConfig,
+}
+
+impl MqttBridge {
+    /// The bridge, and the event loop `run` has to drive for anything to move
+    pub fn new(
+        config: MqttConfig,
+        tenant_service: Arc<TenantService>,
+        location_service: Arc<LocationService>,
+    ) -> (Self, EventLoop) {
+        let mut options = MqttOptions::new(&config.client_id, &config.broker_host, config.broker_port);
+        options.set_keep_alive(Duration::from_secs(config.keep_alive_seconds));
+        if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            options.set_credentials(username, password);
+        }
+        let (client, event_loop) = AsyncClient::new(options, 100);
+        (Self { client, tenant_service, location_service, config }, event_loop)
+    }
+
+    // The fleet's offer encoding; None for fleets that haven't opted in, or can't be looked up
+    async fn format_for(&self, tenant_id: &str) -> Option<FrameFormat> {
+        match self.tenant_service.get_tenant(tenant_id).await {
+            Ok(tenant) => tenant.filter(|tenant| tenant.mqtt_enabled).map(|tenant| tenant.mqtt_format),
+            Err(e) => {
+                tracing::warn!("Failed to load tenant {} for MQTT: {}", tenant_id, e);
+                None
+            }
+        }
+    }
+
+    /// Copy an offer to the driver's MQTT topic, if their fleet uses MQTT
+    pub async fn publish_offer(&self, driver_id: &DriverId, event: &DriverSocketEvent) -> Result<(), AppError> {
+        let tenant_id = current_tenant_id();
+        let Some(format) = self.format_for(&tenant_id).await else {
+            return Ok(());
+        };
+        let payload = format.encode(event)?;
+        let topic = driver_topic(&self.config.topic_prefix, &tenant_id, driver_id, "offers");
+        self.client
+            .publish(topic, QoS::AtLeastOnce, false, payload)
+            .await
+            .map_err(|e| AppError::MessageDeliveryFailed(e.to_string()))
+    }
+
+    /// Drive the connection for the life of the process, resubscribing after every reconnect
+    pub async fn run(self: Arc<Self>, mut event_loop: EventLoop) {
+        let locations = format!("{}/+/drivers/+/location", self.config.topic_prefix);
+        loop {
+            match event_loop.poll().await {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    tracing::info!("Connected to MQTT broker {}", self.config.broker_host);
+                    if let Err(e) = self.client.subscribe(&locations, QoS::AtLeastOnce).await {
+                        tracing::error!("Failed to subscribe to {}: {}", locations, e);
+                    }
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    if let Err(e) = self.receive_locations(&publish.topic, &publish.payload).await {
+                        tracing::warn!("Dropped MQTT location update on {}: {}", publish.topic, e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!("MQTT connection lost: {}", e);
+                    tokio::time::sleep(Duration::from_secs(self.config.reconnect_delay_seconds)).await;
+                }
+            }
+        }
+    }
+
+    async fn receive_locations(&self, topic: &str, payload: &[u8]) -> Result<(), AppError> {
+        let (tenant_id, driver_id) = parse_location_topic(&self.config.topic_prefix, topic)
+            .ok_or_else(|| AppError::InvalidFormat(format!("Not a location topic: {}", topic)))?;
+        if self.format_for(&tenant_id).await.is_none() {
+            return Err(AppError::Forbidden(format!("Tenant {} does not use MQTT", tenant_id)));
+        }
+        let batch: DriverLocationBatch = FrameFormat::sniff(payload).decode(payload)?;
+        with_tenant(tenant_id, self.location_service.ingest_batch(&driver_id, batch.locations)).await?;
+        Ok(())
+    }
+}
+
+fn driver_topic(prefix: &str, tenant_id: &str, driver_id: &DriverId, channel: &str) -> String {
+    format!("{}/{}/drivers/{}/{}", prefix, tenant_id, driver_id, channel)
+}
+
+// `<prefix>/<tenant>/drivers/<driver>/location`
+fn parse_location_topic(prefix: &str, topic: &str) -> Option<(String, DriverId)> {
+    let rest = topic.strip_prefix(prefix)?.strip_prefix('/')?;
+    match rest.split('/').collect::<Vec<_>>()[..] {
+        [tenant_id, "drivers", driver_id, "location"] if !tenant_id.is_empty() => {
+            Some((tenant_id.to_string(), DriverId::parse(driver_id).ok()?))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location_topics() {
+        let driver_id = DriverId::generate();
+        let offers = driver_topic("sparrow", "kwik-gh", &driver_id, "offers");
+        assert_eq!(offers, format!("sparrow/kwik-gh/drivers/{}/offers", driver_id));
+
+        let location = driver_topic("sparrow", "kwik-gh", &driver_id, "location");
+        assert_eq!(parse_location_topic("sparrow", &location), Some(("kwik-gh".to_string(), driver_id.clone())));
+        assert_eq!(parse_location_topic("sparrow", &offers), None);
+        assert_eq!(parse_location_topic("other", &location), None);
+        assert_eq!(parse_location_topic("sparrow", "sparrow/kwik-gh/drivers/not-an-id/location"), None);
+    }
+}
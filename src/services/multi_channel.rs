@@ -0,0 +1,157 @@
+// src/services/multi_channel.rs
+// Picks the push channel per device: iOS tokens go to APNs directly, everything else,
+// topics included, to FCM. Without APNs configured, iOS devices are skipped with a warning.
+use async_trait::async_trait;
+use std::future::Future;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{chaos::FaultLayer, device::DeviceToken, driver::Driver, ids::{DriverId, UserId}, job::{DriverEarnings, Job}},
+    services::{
+        cache_service::CacheService,
+        chaos,
+        messaging_service::{JobNotificationDetails, NotificationMessage, NotificationService},
+    },
+};
+
+pub struct MultiChannelNotificationService {
+    cache_service: Arc<CacheService>,
+    fcm: Arc<dyn NotificationService>,
+    apns: Option<Arc<dyn NotificationService>>,
+}
+
+impl MultiChannelNotificationService {
+    pub fn new(
+        cache_service: Arc<CacheService>,
+        fcm: Arc<dyn NotificationService>,
+        apns: Option<Arc<dyn NotificationService>>,
+    ) -> Self {
+        Self { cache_service, fcm, apns }
+    }
+
+    // Every send is counted for the push failure-rate alert; a count that can't be written
+    // doesn't fail the send
+    async fn counted(&self, operation: &'static str, send: impl Future<Output = Result<(), AppError>>) -> Result<(), AppError> {
+        let result = match chaos::inject(FaultLayer::Notification, operation).await {
+            Ok(()) => send.await,
+            Err(fault) => Err(fault.into()),
+        };
+        if let Err(e) = self.cache_service.record_push_outcome(result.is_ok()).await {
+            tracing::debug!("Failed to count push outcome: {}", e);
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl NotificationService for MultiChannelNotificationService {
+    // Bare tokens predate platform tracking, so they are all FCM's
+    async fn send_to_device(&self, device_token: &str, message: NotificationMessage) -> Result<(), AppError> {
+        self.counted("send_to_device", self.fcm.send_to_device(device_token, message)).await
+    }
+
+    async fn send_multicast(&self, device_tokens: &[String], message: NotificationMessage) -> Result<(), AppError> {
+        self.counted("send_multicast", self.fcm.send_multicast(device_tokens, message)).await
+    }
+
+    async fn send_to_devices(&self, devices: &[DeviceToken], message: NotificationMessage) -> Result<(), AppError> {
+        let (ios, others): (Vec<&DeviceToken>, Vec<&DeviceToken>) = devices.iter().partition(|device| device.is_apns());
+        let tokens = |devices: Vec<&DeviceToken>| devices.into_iter().map(|device| device.token.clone()).collect::<Vec<_>>();
+
+        if !others.is_empty() {
+            self.counted("send_multicast", self.fcm.send_multicast(&tokens(others), message.clone())).await?;
+        }
+        if !ios.is_empty() {
+            match &self.apns {
+                Some(apns) => self.counted("send_multicast", apns.send_multicast(&tokens(ios), message)).await?,
+                None => tracing::warn!("APNs is not configured; {} iOS devices skipped", ios.len()),
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_to_topic(&self, topic: &str, message: NotificationMessage) -> Result<(), AppError> {
+        self.counted("send_to_topic", self.fcm.send_to_topic(topic, message)).await
+    }
+
+    async fn subscribe_to_topic(&self, topic: &str, device_tokens: &[String]) -> Result<(), AppError> {
+        self.fcm.subscribe_to_topic(topic, device_tokens).await
+    }
+
+    async fn unsubscribe_from_topic(&self, topic: &str, device_tokens: &[String]) -> Result<(), AppError> {
+        self.fcm.unsubscribe_from_topic(topic, device_tokens).await
+    }
+
+    async fn send_to_driver(&self, driver_id: &DriverId, message: NotificationMessage) -> Result<(), AppError> {
+        let driver = self.cache_service.get_driver(driver_id).await?
+            .ok_or_else(|| AppError::DriverNotFound(driver_id.to_string()))?;
+        let device = driver.device_token
+            .ok_or_else(|| AppError::FcmInvalidToken("Driver has no device token".to_string()))?;
+        self.send_to_devices(&[device], message).await
+    }
+
+    async fn send_to_user(&self, user_id: &UserId, message: NotificationMessage) -> Result<(), AppError> {
+        let user = self.cache_service.load_user(user_id).await?
+            .ok_or_else(|| AppError::UserNotFound(user_id.to_string()))?;
+        let device = user.device_tokens.into_iter().next()
+            .ok_or_else(|| AppError::FcmInvalidToken("User has no device token".to_string()))?;
+        self.send_to_devices(&[device], message).await
+    }
+
+    async fn notify_driver_assigned(&self, job: &Job, driver: &Driver, earnings: &DriverEarnings, details: &JobNotificationDetails) -> Result<(), AppError> {
+        self.send_to_driver(&driver.id, NotificationMessage::driver_assigned(job, earnings, details)).await
+    }
+
+    async fn notify_package_picked_up(&self, job: &Job, details: &JobNotificationDetails) -> Result<(), AppError> {
+        self.send_to_user(&job.customer_id, NotificationMessage::package_picked_up(job, details)).await
+    }
+
+    async fn notify_delivery_completed(&self, job: &Job) -> Result<(), AppError> {
+        self.send_to_user(&job.customer_id, NotificationMessage::delivery_completed(job)).await
+    }
+
+    async fn notify_ride_status_update(&self, job: &Job, status: &str) -> Result<(), AppError> {
+        self.send_to_user(&job.customer_id, NotificationMessage::status_update(job, status)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{fixtures::Faker, messaging::{Recipient, RecordingNotificationService}},
+        models::device::DevicePlatform,
+        services::cache_service::CacheConfig,
+    };
+
+    #[tokio::test]
+    async fn test_ios_devices_go_through_apns() {
+        let cache_service = Arc::new(CacheService::new_memory(CacheConfig::default()));
+        let (fcm, apns) = (RecordingNotificationService::new(), RecordingNotificationService::new());
+        let router = MultiChannelNotificationService::new(
+            cache_service.clone(),
+            Arc::new(fcm.clone()),
+            Some(Arc::new(apns.clone())),
+        );
+
+        let mut driver = Faker::seeded(3).driver();
+        driver.device_token = Some(DeviceToken::new("apns-driver", DevicePlatform::Ios));
+        cache_service.cache_driver(&driver).await.unwrap();
+        router.send_to_driver(&driver.id, NotificationMessage::new("Offer", "New job nearby")).await.unwrap();
+        assert!(fcm.is_empty());
+        assert_eq!(apns.sent()[0].recipient, Recipient::Devices(vec!["apns-driver".to_string()]));
+
+        apns.clear();
+        let devices = vec![
+            DeviceToken::new("fcm-1", DevicePlatform::Android),
+            DeviceToken::new("apns-1", DevicePlatform::Ios),
+            DeviceToken::new("fcm-2", DevicePlatform::Web),
+        ];
+        router.send_to_devices(&devices, NotificationMessage::config_updated("commissions")).await.unwrap();
+        assert_eq!(fcm.sent()[0].recipient, Recipient::Devices(vec!["fcm-1".to_string(), "fcm-2".to_string()]));
+        assert_eq!(apns.sent()[0].recipient, Recipient::Devices(vec!["apns-1".to_string()]));
+    }
+}
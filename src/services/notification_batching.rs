@@ -0,0 +1,470 @@
+// src/services/notification_batching.rs
+// Sits in front of the real notifier. Swaps in admin-managed copy where a template
+// exists, caps how often each type of message reaches a recipient, holds non-critical
+// messages back through a user's quiet hours and low-priority ones for a periodic
+// per-user digest, and sends broadcasts as FCM multicasts rather than one request per
+// device.
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        device::{fcm_tokens, DeviceToken},
+        driver::Driver,
+        ids::{DriverId, UserId},
+        job::{DriverEarnings, Job},
+        messages::{DeferredNotification, DigestItem, NotificationBroadcastRequest, NotificationBroadcastResponse},
+        user::{User, DEFAULT_LANGUAGE},
+    },
+    services::{
+        cache_service::CacheService,
+        i18n::localize_message,
+        notification_templates::NotificationTemplateService,
+        messaging_service::{JobNotificationDetails, NotificationMessage, NotificationPriority, NotificationService, FCM_MULTICAST_LIMIT},
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct NotificationBatchConfig {
+    pub multicast_batch_size: usize,
+    pub digest_preview_items: usize,   // Titles listed in a digest before "and N more"
+    pub rate_cap_window_seconds: i64,
+    pub rate_caps: HashMap<String, i64>, // Most messages of a type per recipient per window
+}
+
+impl Default for NotificationBatchConfig {
+    fn default() -> Self {
+        Self {
+            multicast_batch_size: FCM_MULTICAST_LIMIT,
+            digest_preview_items: 3,
+            rate_cap_window_seconds: 24 * 60 * 60,
+            rate_caps: HashMap::from([
+                ("promotion".to_string(), 2),
+                ("status_update".to_string(), 50),
+            ]),
+        }
+    }
+}
+
+pub struct BatchingNotificationService {
+    cache_service: Arc<CacheService>,
+    inner: Arc<dyn NotificationService>,
+    templates: Arc<NotificationTemplateService>,
+    config: NotificationBatchConfig,
+}
+
+impl BatchingNotificationService {
+    pub fn new(
+        cache_service: Arc<CacheService>,
+        inner: Arc<dyn NotificationService>,
+        templates: Arc<NotificationTemplateService>,
+        config: NotificationBatchConfig,
+    ) -> Self {
+        Self {
+            cache_service,
+            inner,
+            templates,
+            config,
+        }
+    }
+
+    // For their language and quiet hours; without it, messages go out as if they had neither
+    async fn recipient(&self, user_id: &UserId) -> Option<User> {
+        self.cache_service.load_user(user_id).await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to load notification settings of user {}: {}", user_id, e);
+            None
+        })
+    }
+
+    // Held until the user's quiet hours are over
+    async fn defer(&self, user_id: &UserId, message: NotificationMessage) -> Result<(), AppError> {
+        tracing::debug!("Deferring {} notification to user {}: quiet hours", message.kind().unwrap_or("untyped"), user_id);
+        let deferred = DeferredNotification { message, deferred_at: Utc::now() };
+        self.cache_service.queue_deferred_notification(user_id, &deferred).await
+    }
+
+    // False once `recipient` has had its fill of this kind of message for the window
+    async fn within_cap(&self, recipient: &str, message: &NotificationMessage) -> bool {
+        let Some(kind) = message.kind() else {
+            return true;
+        };
+        let Some(cap) = self.config.rate_caps.get(kind) else {
+            return true;
+        };
+        match self.cache_service.count_notification(kind, recipient, self.config.rate_cap_window_seconds).await {
+            Ok(count) if count > *cap => {
+                tracing::debug!("Dropping {} notification to {}: over its cap of {}", kind, recipient, cap);
+                false
+            }
+            Ok(_) => true,
+            Err(e) => {
+                // One message too many beats a missing one
+                tracing::warn!("Failed to count {} notifications to {}: {}", kind, recipient, e);
+                true
+            }
+        }
+    }
+
+    /// Send to every device of `user_ids`, in as few multicast requests as FCM allows
+    pub async fn broadcast(&self, request: NotificationBroadcastRequest) -> Result<NotificationBroadcastResponse, AppError> {
+        let message = NotificationMessage::new(&request.title, &request.body)
+            .with_data(json!({ "type": request.kind }))
+            .with_priority(NotificationPriority::Normal);
+
+        let mut response = NotificationBroadcastResponse { recipients: 0, capped: 0, deferred: 0, devices: 0, requests: 0 };
+        let mut device_tokens = Vec::new();
+        let now = Utc::now();
+        for user_id in &request.user_ids {
+            let Some(user) = self.cache_service.load_user(user_id).await? else {
+                continue;
+            };
+            if user.device_tokens.is_empty() {
+                continue;
+            }
+            if !self.within_cap(user_id.as_str(), &message).await {
+                response.capped += 1;
+                continue;
+            }
+            if !message.is_critical() && user.quiet_hours.is_some_and(|quiet_hours| quiet_hours.contains(now)) {
+                self.defer(user_id, message.clone()).await?;
+                response.deferred += 1;
+                continue;
+            }
+            response.recipients += 1;
+            device_tokens.extend(user.device_tokens);
+        }
+
+        // FCM devices go in multicast batches, iOS ones to APNs one at a time
+        let multicast_devices = fcm_tokens(&device_tokens).len();
+        response.devices = device_tokens.len();
+        response.requests = multicast_devices.div_ceil(self.config.multicast_batch_size)
+            + (device_tokens.len() - multicast_devices);
+        self.send_to_devices(&device_tokens, message).await?;
+
+        tracing::info!(
+            "Broadcast {} to {} users on {} devices in {} requests",
+            request.kind, response.recipients, response.devices, response.requests
+        );
+        Ok(response)
+    }
+
+    /// Send each waiting digest as one summary notification; returns how many went out.
+    /// Digests of users in their quiet hours wait for a later flush.
+    pub async fn flush_digests(&self) -> Result<usize, AppError> {
+        let mut sent = 0;
+        let now = Utc::now();
+        for user_id in self.cache_service.get_pending_digest_users().await? {
+            if self.recipient(&user_id).await.and_then(|user| user.quiet_hours).is_some_and(|quiet_hours| quiet_hours.contains(now)) {
+                continue;
+            }
+            let items = self.cache_service.take_digest_items(&user_id).await?;
+            if items.is_empty() {
+                continue;
+            }
+            let message = digest_message(&items, self.config.digest_preview_items);
+            // A failed digest is dropped; its items were only ever low priority
+            match self.inner.send_to_user(&user_id, message).await {
+                Ok(()) => sent += 1,
+                Err(e) => tracing::warn!("Failed to send digest of {} items to user {}: {}", items.len(), user_id, e),
+            }
+        }
+        Ok(sent)
+    }
+
+    /// Send what quiet hours held back, for users whose quiet hours are over; returns how
+    /// many notifications went out. They were capped and templated when first sent.
+    pub async fn flush_deferred(&self) -> Result<usize, AppError> {
+        let mut sent = 0;
+        let now = Utc::now();
+        for user_id in self.cache_service.get_deferred_users().await? {
+            if self.recipient(&user_id).await.and_then(|user| user.quiet_hours).is_some_and(|quiet_hours| quiet_hours.contains(now)) {
+                continue;
+            }
+            for deferred in self.cache_service.take_deferred_notifications(&user_id).await? {
+                let message = deferred.message;
+                // Low-priority ones join the digest as they would have in the first place
+                if message.priority == NotificationPriority::Low && !message.data_only {
+                    self.queue_for_digest(&user_id, message).await?;
+                    continue;
+                }
+                match self.inner.send_to_user(&user_id, message).await {
+                    Ok(()) => sent += 1,
+                    Err(e) => tracing::warn!("Failed to send deferred notification to user {}: {}", user_id, e),
+                }
+            }
+        }
+        Ok(sent)
+    }
+
+    async fn queue_for_digest(&self, user_id: &UserId, message: NotificationMessage) -> Result<(), AppError> {
+        let item = DigestItem {
+            kind: message.kind().map(str::to_string),
+            title: message.title,
+            body: message.body,
+            queued_at: Utc::now(),
+        };
+        self.cache_service.queue_digest_item(user_id, &item).await
+    }
+}
+
+// One notification summing up `items`, oldest first
+fn digest_message(items: &[DigestItem], preview_items: usize) -> NotificationMessage {
+    let (title, body) = match items {
+        [item] => (item.title.clone(), item.body.clone()),
+        _ => {
+            let mut body = items.iter()
+                .take(preview_items)
+                .map(|item| item.title.as_str())
+                .collect::<Vec<_>>()
+                .join(" · ");
+            if items.len() > preview_items {
+                body.push_str(&format!(" and {} more", items.len() - preview_items));
+            }
+            (format!("{} updates", items.len()), body)
+        }
+    };
+    NotificationMessage::new(&title, &body)
+        .with_data(json!({
+            "type": "digest",
+            "count": items.len(),
+            "kinds": items.iter().filter_map(|item| item.kind.as_deref()).collect::<Vec<_>>(),
+        }))
+        .with_priority(NotificationPriority::Normal)
+}
+
+#[async_trait]
+impl NotificationService for BatchingNotificationService {
+    async fn send_to_device(&self, device_token: &str, message: NotificationMessage) -> Result<(), AppError> {
+        if !self.within_cap(device_token, &message).await {
+            return Ok(());
+        }
+        self.inner.send_to_device(device_token, message).await
+    }
+
+    async fn send_multicast(&self, device_tokens: &[String], message: NotificationMessage) -> Result<(), AppError> {
+        for batch in device_tokens.chunks(self.config.multicast_batch_size) {
+            self.inner.send_multicast(batch, message.clone()).await?;
+        }
+        Ok(())
+    }
+
+    async fn send_to_devices(&self, devices: &[DeviceToken], message: NotificationMessage) -> Result<(), AppError> {
+        for batch in devices.chunks(self.config.multicast_batch_size) {
+            self.inner.send_to_devices(batch, message.clone()).await?;
+        }
+        Ok(())
+    }
+
+    // Topics fan out inside FCM, so there is no one recipient to cap
+    async fn send_to_topic(&self, topic: &str, message: NotificationMessage) -> Result<(), AppError> {
+        self.inner.send_to_topic(topic, message).await
+    }
+
+    async fn subscribe_to_topic(&self, topic: &str, device_tokens: &[String]) -> Result<(), AppError> {
+        for batch in device_tokens.chunks(self.config.multicast_batch_size) {
+            self.inner.subscribe_to_topic(topic, batch).await?;
+        }
+        Ok(())
+    }
+
+    async fn unsubscribe_from_topic(&self, topic: &str, device_tokens: &[String]) -> Result<(), AppError> {
+        for batch in device_tokens.chunks(self.config.multicast_batch_size) {
+            self.inner.unsubscribe_from_topic(topic, batch).await?;
+        }
+        Ok(())
+    }
+
+    // Drivers get no digests; their low-priority messages go out straight away
+    async fn send_to_driver(&self, driver_id: &DriverId, message: NotificationMessage) -> Result<(), AppError> {
+        if !self.within_cap(driver_id.as_str(), &message).await {
+            return Ok(());
+        }
+        // Drivers have no language setting or quiet hours; they're told what they need when on shift
+        let message = self.templates.apply(message, DEFAULT_LANGUAGE).await;
+        self.inner.send_to_driver(driver_id, message).await
+    }
+
+    async fn send_to_user(&self, user_id: &UserId, message: NotificationMessage) -> Result<(), AppError> {
+        if !self.within_cap(user_id.as_str(), &message).await {
+            return Ok(());
+        }
+        let user = self.recipient(user_id).await;
+        let language = user.as_ref().map_or(DEFAULT_LANGUAGE, |user| user.language.as_str());
+        // An admin's template wins; otherwise the built-in copy, translated where we can
+        let message = match self.templates.apply(message.clone(), language).await {
+            templated if templated.title != message.title || templated.body != message.body => templated,
+            _ => localize_message(message, language),
+        };
+        // Silent messages are for the app, not the reader, so never wait for a digest
+        if message.priority != NotificationPriority::Low || message.data_only {
+            let quiet = user.and_then(|user| user.quiet_hours).is_some_and(|quiet_hours| quiet_hours.contains(Utc::now()));
+            if quiet && !message.is_critical() {
+                return self.defer(user_id, message).await;
+            }
+            return self.inner.send_to_user(user_id, message).await;
+        }
+        self.queue_for_digest(user_id, message).await
+    }
+
+    async fn notify_driver_assigned(&self, job: &Job, driver: &Driver, earnings: &DriverEarnings, details: &JobNotificationDetails) -> Result<(), AppError> {
+        self.send_to_driver(&driver.id, NotificationMessage::driver_assigned(job, earnings, details)).await
+    }
+
+    async fn notify_package_picked_up(&self, job: &Job, details: &JobNotificationDetails) -> Result<(), AppError> {
+        self.send_to_user(&job.customer_id, NotificationMessage::package_picked_up(job, details)).await
+    }
+
+    async fn notify_delivery_completed(&self, job: &Job) -> Result<(), AppError> {
+        self.send_to_user(&job.customer_id, NotificationMessage::delivery_completed(job)).await
+    }
+
+    async fn notify_ride_status_update(&self, job: &Job, status: &str) -> Result<(), AppError> {
+        self.send_to_user(&job.customer_id, NotificationMessage::status_update(job, status)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker, messaging::{Recipient, RecordingNotificationService}},
+        models::user::{QuietHours, UserPreferences, UserType},
+        services::{cache_service::CacheConfig, notification_templates::NotificationTemplateConfig, region::with_region, runtime_config::{RuntimeConfigService, RuntimeConfigWatch}, user_service::UserOperations},
+    };
+    use chrono::{Duration, TimeZone, Timelike};
+
+    fn batcher(recorder: &RecordingNotificationService, config: NotificationBatchConfig) -> BatchingNotificationService {
+        let cache_service = Arc::new(CacheService::new_memory(CacheConfig::default()));
+        let runtime_config = Arc::new(RuntimeConfigService::new(RuntimeConfigWatch::default()));
+        let templates = Arc::new(NotificationTemplateService::new(cache_service.clone(), runtime_config, NotificationTemplateConfig::default()));
+        BatchingNotificationService::new(cache_service, Arc::new(recorder.clone()), templates, config)
+    }
+
+    fn news(title: &str) -> NotificationMessage {
+        NotificationMessage::new(title, "Details inside")
+            .with_data(json!({ "type": "news" }))
+            .with_priority(NotificationPriority::Low)
+    }
+
+    #[tokio::test]
+    async fn test_low_priority_messages_wait_for_the_digest() {
+        let recorder = RecordingNotificationService::new();
+        let notifier = batcher(&recorder, NotificationBatchConfig { digest_preview_items: 2, ..Default::default() });
+        let user_id = UserId::generate();
+
+        for title in ["Weekend discount", "New in Kumasi", "Refer a friend"] {
+            notifier.send_to_user(&user_id, news(title)).await.unwrap();
+        }
+        notifier.send_to_user(&user_id, NotificationMessage::new("Delivered", "At the door")).await.unwrap();
+        assert_eq!(recorder.len(), 1);
+
+        assert_eq!(notifier.flush_digests().await.unwrap(), 1);
+        let digest = &recorder.of_kind("digest")[0];
+        assert_eq!(digest.recipient, Recipient::User(user_id.clone()));
+        assert_eq!(digest.message.title, "3 updates");
+        assert_eq!(digest.message.body, "Weekend discount · New in Kumasi and 1 more");
+        // Drained, so nothing goes out twice
+        assert_eq!(notifier.flush_digests().await.unwrap(), 0);
+
+        // Silent pushes are for the app and go straight out
+        recorder.clear();
+        notifier.send_to_user(&user_id, news("Sync").data_only()).await.unwrap();
+        assert!(recorder.sent()[0].message.data_only);
+        assert_eq!(notifier.flush_digests().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_caps_and_multicast_batches() {
+        let recorder = RecordingNotificationService::new();
+        let config = NotificationBatchConfig {
+            multicast_batch_size: 2,
+            rate_caps: HashMap::from([("news".to_string(), 1)]),
+            ..Default::default()
+        };
+        let notifier = batcher(&recorder, config);
+        let driver_id = DriverId::generate();
+
+        let capped = news("Fuel prices are down").with_priority(NotificationPriority::Normal);
+        notifier.send_to_driver(&driver_id, capped.clone()).await.unwrap();
+        notifier.send_to_driver(&driver_id, capped).await.unwrap();
+        assert_eq!(recorder.sent_to_driver(&driver_id).len(), 1);
+
+        recorder.clear();
+        let tokens: Vec<String> = (0..5).map(|n| format!("device-{}", n)).collect();
+        notifier.send_multicast(&tokens, NotificationMessage::new("Hi", "All")).await.unwrap();
+        let batches: Vec<usize> = recorder.sent().iter()
+            .map(|sent| match &sent.recipient {
+                Recipient::Devices(tokens) => tokens.len(),
+                other => panic!("expected a multicast, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(batches, vec![2, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_quiet_hours_hold_back_all_but_critical_messages() {
+        let recorder = RecordingNotificationService::new();
+        let notifier = batcher(&recorder, NotificationBatchConfig::default());
+        let mut user = Faker::new().user(UserType::Customer);
+        // An hour either side of now, whatever the time the test runs
+        let now = Utc::now();
+        let hour = |at: chrono::DateTime<Utc>| at.time().with_minute(0).unwrap().with_second(0).unwrap().with_nanosecond(0).unwrap();
+        user.quiet_hours = Some(QuietHours { start: hour(now - Duration::hours(1)), end: hour(now + Duration::hours(2)), utc_offset_minutes: Some(0) });
+        notifier.cache_service.cache_user(&user).await.unwrap();
+
+        let promotion = NotificationMessage::new("Weekend discount", "20% off")
+            .with_data(json!({ "type": "promotion" }))
+            .with_priority(NotificationPriority::Normal);
+        notifier.send_to_user(&user.id, promotion).await.unwrap();
+        notifier.send_to_user(&user.id, NotificationMessage::new("Driver arrived", "At pickup")
+            .with_data(json!({ "type": "status_update" }))
+            .with_priority(NotificationPriority::Normal)).await.unwrap();
+        assert_eq!(recorder.sent().iter().map(|sent| sent.message.kind()).collect::<Vec<_>>(), vec![Some("status_update")]);
+
+        // Held until the window ends
+        assert_eq!(notifier.flush_deferred().await.unwrap(), 0);
+        user.quiet_hours = None;
+        notifier.cache_service.cache_user(&user).await.unwrap();
+        assert_eq!(notifier.flush_deferred().await.unwrap(), 1);
+        assert_eq!(recorder.of_kind("promotion")[0].recipient, Recipient::User(user.id.clone()));
+        assert_eq!(notifier.flush_deferred().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_quiet_hours_saved_without_an_offset_keep_the_users_region_time() {
+        let app = TestApp::builder().regions(&["ng"]).build();
+        let user = Faker::seeded(7).user(UserType::Customer);
+        let preferences: UserPreferences = serde_json::from_value(json!({
+            "language": "en",
+            "currency": "NGN",
+            "theme": "system",
+            "search_history": [],
+            "notifications": {
+                "push_notifications": true,
+                "email_notifications": true,
+                "sms_notifications": false,
+                "ride_updates": true,
+                "promotional_offers": true,
+                "security_alerts": true,
+                "quiet_hours": { "start": "22:00:00", "end": "07:00:00" }
+            }
+        })).unwrap();
+        let saved = with_region("ng".to_string(), async {
+            app.state.cache_service.cache_user(&user).await.unwrap();
+            app.state.user_service.update_user_preferences(&user.id, preferences).await.unwrap();
+            app.state.cache_service.load_user(&user.id).await.unwrap().unwrap()
+        }).await;
+
+        // Lagos is an hour ahead: 21:30 UTC is quiet there, 06:30 UTC already isn't
+        let quiet_hours = saved.quiet_hours.unwrap();
+        assert_eq!(quiet_hours.utc_offset_minutes, Some(60));
+        assert!(quiet_hours.contains(Utc.with_ymd_and_hms(2026, 3, 2, 21, 30, 0).unwrap()));
+        assert!(!quiet_hours.contains(Utc.with_ymd_and_hms(2026, 3, 2, 6, 30, 0).unwrap()));
+    }
+}
@@ -0,0 +1,298 @@
+// src/services/notification_templates.rs
+// Notification copy that admins can change without a deploy. Templates are stored per
+// tenant, notification type and language; senders swap a message's built-in title and
+// body for the active template's, filled from the message's data. Each instance keeps
+// the active set in memory and reloads it every `reload_seconds`, or as soon as the runtime
+// config is reloaded, which an edit announces to the other instances.
+use chrono::Utc;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        messages::{NotificationTemplate, NotificationTemplateRequest, NotificationType, TemplatePreview, TemplatePreviewRequest},
+        user::DEFAULT_LANGUAGE,
+    },
+    services::{cache_service::CacheService, messaging_service::NotificationMessage, runtime_config::RuntimeConfigService, tenant_service::current_tenant_id},
+};
+
+#[derive(Debug, Clone)]
+pub struct NotificationTemplateConfig {
+    pub reload_seconds: u64, // How long an instance trusts its copy of the active set
+}
+
+impl Default for NotificationTemplateConfig {
+    fn default() -> Self {
+        Self {
+            reload_seconds: 60,
+        }
+    }
+}
+
+struct LoadedTemplates {
+    loaded_at: Instant,
+    config_version: u64, // Of the runtime config at the time
+    templates: Arc<Vec<NotificationTemplate>>, // Active ones only
+}
+
+pub struct NotificationTemplateService {
+    cache_service: Arc<CacheService>,
+    runtime_config: Arc<RuntimeConfigService>,
+    active: Mutex<HashMap<String, LoadedTemplates>>, // By tenant
+    config: NotificationTemplateConfig,
+}
+
+impl NotificationTemplateService {
+    pub fn new(cache_service: Arc<CacheService>, runtime_config: Arc<RuntimeConfigService>, config: NotificationTemplateConfig) -> Self {
+        Self {
+            cache_service,
+            runtime_config,
+            active: Mutex::new(HashMap::new()),
+            config,
+        }
+    }
+
+    /// Every template, active or not, by type then language
+    pub async fn list(&self) -> Result<Vec<NotificationTemplate>, AppError> {
+        self.cache_service.get_notification_templates().await
+    }
+
+    pub async fn upsert(
+        &self,
+        notification_type: NotificationType,
+        language: &str,
+        request: NotificationTemplateRequest,
+    ) -> Result<NotificationTemplate, AppError> {
+        let language = normalize_language(language)?;
+        validate(notification_type, &request.title, &request.body)?;
+
+        let template = NotificationTemplate {
+            notification_type,
+            language,
+            title: request.title,
+            body: request.body,
+            active: request.active,
+            updated_at: Utc::now(),
+        };
+        let mut templates = self.list().await?;
+        templates.retain(|existing| !same_slot(existing, notification_type, &template.language));
+        templates.push(template.clone());
+        templates.sort_by_key(|template| (template.notification_type.kind(), template.language.clone()));
+        self.save(templates).await?;
+
+        tracing::info!("Notification template {} ({}) saved", notification_type.kind(), template.language);
+        Ok(template)
+    }
+
+    pub async fn delete(&self, notification_type: NotificationType, language: &str) -> Result<NotificationTemplate, AppError> {
+        let language = normalize_language(language)?;
+        let mut templates = self.list().await?;
+        let index = templates.iter()
+            .position(|existing| same_slot(existing, notification_type, &language))
+            .ok_or_else(|| AppError::NotFound(format!("No {} template in {}", notification_type.kind(), language)))?;
+        let removed = templates.remove(index);
+        self.save(templates).await?;
+
+        tracing::info!("Notification template {} ({}) deleted", notification_type.kind(), language);
+        Ok(removed)
+    }
+
+    /// Render unsaved copy; placeholders without a value in the request are shown as written
+    pub fn preview(&self, request: TemplatePreviewRequest) -> Result<TemplatePreview, AppError> {
+        validate(request.notification_type, &request.title, &request.body)?;
+        let value_of = |name: &str| request.variables.get(name)
+            .and_then(display)
+            .or_else(|| Some(format!("{{{{{}}}}}", name)));
+        Ok(TemplatePreview {
+            title: render(&request.title, value_of).unwrap_or_default(),
+            body: render(&request.body, value_of).unwrap_or_default(),
+        })
+    }
+
+    /// Whether an active template could replace `message`'s copy, in any language
+    pub async fn covers(&self, message: &NotificationMessage) -> bool {
+        let Some(notification_type) = message.kind().and_then(NotificationType::from_kind) else {
+            return false;
+        };
+        self.active_templates().await.iter().any(|template| template.notification_type == notification_type)
+    }
+
+    /// `message` with its title and body taken from the active template for its type in
+    /// `language`, or in the default language failing that. Left as it is when there is
+    /// no such template or the message lacks a value the template needs.
+    pub async fn apply(&self, message: NotificationMessage, language: &str) -> NotificationMessage {
+        let Some(notification_type) = message.kind().and_then(NotificationType::from_kind) else {
+            return message;
+        };
+        let language = normalize_language(language).unwrap_or_else(|_| DEFAULT_LANGUAGE.to_string());
+        let templates = self.active_templates().await;
+        let Some(template) = [language.as_str(), DEFAULT_LANGUAGE].into_iter()
+            .find_map(|language| templates.iter().find(|template| same_slot(template, notification_type, language)))
+        else {
+            return message;
+        };
+
+        let data = message.data.clone().unwrap_or(Value::Null);
+        let value_of = |name: &str| data.get(name).and_then(display);
+        match (render(&template.title, value_of), render(&template.body, value_of)) {
+            (Some(title), Some(body)) => NotificationMessage { title, body, ..message },
+            _ => {
+                tracing::debug!("Template {} ({}) is missing a value; sending built-in copy", notification_type.kind(), template.language);
+                message
+            }
+        }
+    }
+
+    async fn save(&self, templates: Vec<NotificationTemplate>) -> Result<(), AppError> {
+        self.cache_service.cache_notification_templates(&templates).await?;
+        self.active.lock().unwrap().remove(&current_tenant_id());
+        Ok(())
+    }
+
+    // The current tenant's active templates, reloaded once the copy in memory is too old or
+    // predates a runtime config reload. A failed reload keeps the old copy; with none,
+    // messages go out with built-in copy.
+    async fn active_templates(&self) -> Arc<Vec<NotificationTemplate>> {
+        let tenant_id = current_tenant_id();
+        let config_version = self.runtime_config.version();
+        let stale = match self.active.lock().unwrap().get(&tenant_id) {
+            Some(loaded) if loaded.loaded_at.elapsed() < Duration::from_secs(self.config.reload_seconds)
+                && loaded.config_version == config_version => {
+                return loaded.templates.clone();
+            }
+            Some(loaded) => Some(loaded.templates.clone()),
+            None => None,
+        };
+
+        let templates = match self.cache_service.get_notification_templates().await {
+            Ok(templates) => Arc::new(templates.into_iter().filter(|template| template.active).collect::<Vec<_>>()),
+            Err(e) => {
+                tracing::warn!("Failed to load notification templates: {}", e);
+                return stale.unwrap_or_default();
+            }
+        };
+        self.active.lock().unwrap().insert(tenant_id, LoadedTemplates {
+            loaded_at: Instant::now(),
+            config_version,
+            templates: templates.clone(),
+        });
+        templates
+    }
+}
+
+fn same_slot(template: &NotificationTemplate, notification_type: NotificationType, language: &str) -> bool {
+    template.notification_type == notification_type && template.language == language
+}
+
+// Primary subtag, lowercased, as users' languages are matched: "fr-GH" -> "fr"
+fn normalize_language(language: &str) -> Result<String, AppError> {
+    let language = language.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+    if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(AppError::validation_error("language", "Must be a language code such as \"en\" or \"fr\""));
+    }
+    Ok(language)
+}
+
+fn validate(notification_type: NotificationType, title: &str, body: &str) -> Result<(), AppError> {
+    let allowed = notification_type.variables();
+    for (field, text) in [("title", title), ("body", body)] {
+        if text.trim().is_empty() {
+            return Err(AppError::validation_error(field, "Must not be empty"));
+        }
+        let names = placeholders(text).ok_or_else(|| AppError::validation_error(field, "Has a {{ without a closing }}"))?;
+        if let Some(unknown) = names.into_iter().find(|name| !allowed.contains(name)) {
+            return Err(AppError::validation_error(field, format!(
+                "Unknown placeholder {{{{{}}}}}; {} templates may use: {}",
+                unknown,
+                notification_type.kind(),
+                allowed.join(", "),
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Names of the `{{name}}` placeholders in `text`; None if one is left open
+fn placeholders(text: &str) -> Option<Vec<&str>> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let end = after.find("}}")?;
+        names.push(after[..end].trim());
+        rest = &after[end + 2..];
+    }
+    Some(names)
+}
+
+// None when a placeholder has no value
+fn render(text: &str, value_of: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find("}}")?;
+        rendered.push_str(&value_of(after[..end].trim())?);
+        rest = &after[end + 2..];
+    }
+    rendered.push_str(rest);
+    Some(rendered)
+}
+
+fn display(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(text) => Some(text.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{cache_service::CacheConfig, messaging_service::JobNotificationDetails, runtime_config::RuntimeConfigWatch};
+    use crate::{mocks::fixtures::Faker, models::user::UserType};
+
+    #[tokio::test]
+    async fn test_templates_replace_copy_in_the_recipients_language() {
+        let cache_service = Arc::new(CacheService::new_memory(CacheConfig::default()));
+        let runtime_config = Arc::new(RuntimeConfigService::new(RuntimeConfigWatch::default()));
+        let templates = NotificationTemplateService::new(cache_service, runtime_config, NotificationTemplateConfig::default());
+        let template = |title: &str, body: &str| NotificationTemplateRequest { title: title.to_string(), body: body.to_string(), active: true };
+
+        // Placeholders are checked against what the message carries
+        let unknown = templates.upsert(NotificationType::PackagePickedUp, "en", template("Hi {{customer_name}}", "On its way")).await;
+        assert!(matches!(unknown, Err(AppError::ValidationFailed(_))));
+        let open = templates.upsert(NotificationType::PackagePickedUp, "en", template("Picked up {{driver_name", "On its way")).await;
+        assert!(matches!(open, Err(AppError::ValidationFailed(_))));
+
+        templates.upsert(NotificationType::PackagePickedUp, "en", template("{{driver_name}} has your package", "Arriving in {{estimated_arrival}}")).await.unwrap();
+        templates.upsert(NotificationType::PackagePickedUp, "fr-GH", template("{{driver_name}} a votre colis", "Arrivée dans {{estimated_arrival}}")).await.unwrap();
+
+        let mut faker = Faker::new();
+        let customer = faker.user(UserType::Customer);
+        let job = faker.job(&customer.id);
+        let details = JobNotificationDetails { driver_name: Some("Kofi".to_string()), eta_minutes: Some(12), ..Default::default() };
+        let message = NotificationMessage::package_picked_up(&job, &details);
+        assert!(templates.covers(&message).await);
+
+        let french = templates.apply(message.clone(), "fr").await;
+        assert_eq!((french.title.as_str(), french.body.as_str()), ("Kofi a votre colis", "Arrivée dans 12 minutes"));
+        let fallback = templates.apply(message.clone(), "tw").await;
+        assert_eq!(fallback.title, "Kofi has your package");
+
+        // Without an ETA the template can't be filled, so the built-in copy goes out
+        let unknown_eta = NotificationMessage::package_picked_up(&job, &JobNotificationDetails::default());
+        let sent = templates.apply(unknown_eta.clone(), "en").await;
+        assert_eq!(sent.body, unknown_eta.body);
+
+        templates.delete(NotificationType::PackagePickedUp, "en").await.unwrap();
+        templates.delete(NotificationType::PackagePickedUp, "fr").await.unwrap();
+        assert_eq!(templates.apply(message.clone(), "fr").await.title, message.title);
+    }
+}
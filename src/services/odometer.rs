@@ -0,0 +1,284 @@
+// src/services/odometer.rs
+// Distance actually driven, added up from the location stream as it comes in. GPS noise
+// would otherwise pile up into phantom kilometres, so imprecise fixes, jitter around a stop
+// and jumps no vehicle could make are left out, and a gap in the stream (app closed, phone
+// off) isn't bridged with a straight line. Totals are kept per driver per day and per
+// vehicle; a vehicle that has gone its service interval since its last service gets its
+// driver a reminder.
+use chrono::{Duration, NaiveDate, Utc};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        driver::Driver,
+        ids::DriverId,
+        job::LocationUpdate,
+        odometer::{DailyDistance, DriverDistanceStats, VehicleMaintenance, VehicleOdometer},
+    },
+    services::{
+        cache_service::CacheService,
+        messaging_service::{NotificationMessage, NotificationService},
+    },
+    utils::geo::haversine_km,
+};
+
+#[derive(Debug, Clone)]
+pub struct OdometerConfig {
+    pub max_accuracy_meters: f64, // Fixes reported less precise than this are ignored
+    pub min_step_meters: f64,     // Movement under this is jitter; it counts once it adds up
+    pub max_speed_kmh: f64,       // Faster than this between two fixes is a jump
+    pub max_gap_minutes: i64,     // Longer without a fix and the distance in between is unknown
+    pub service_interval_km: f64,
+    pub max_stats_days: i64,
+}
+
+impl Default for OdometerConfig {
+    fn default() -> Self {
+        Self {
+            max_accuracy_meters: 50.0,
+            min_step_meters: 20.0,
+            max_speed_kmh: 160.0,
+            max_gap_minutes: 15,
+            service_interval_km: 5000.0,
+            max_stats_days: 90,
+        }
+    }
+}
+
+impl OdometerConfig {
+    /// `VEHICLE_SERVICE_INTERVAL_KM` overrides the default
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            service_interval_km: std::env::var("VEHICLE_SERVICE_INTERVAL_KM").ok()
+                .and_then(|value| value.parse().ok())
+                .filter(|km: &f64| *km > 0.0)
+                .unwrap_or(defaults.service_interval_km),
+            ..defaults
+        }
+    }
+}
+
+pub struct OdometerService {
+    cache_service: Arc<CacheService>,
+    notification_service: Arc<dyn NotificationService>,
+    config: OdometerConfig,
+}
+
+impl OdometerService {
+    pub fn new(cache_service: Arc<CacheService>, notification_service: Arc<dyn NotificationService>, config: OdometerConfig) -> Self {
+        Self { cache_service, notification_service, config }
+    }
+
+    /// Adds a batch of the driver's points, oldest first, to their distance. Returns the km added.
+    /// Points from before the last one counted, such as a backlog uploaded after the app was
+    /// offline, are measured among themselves, so they add their distance without moving the
+    /// live anchor back.
+    pub async fn record(&self, driver_id: &DriverId, points: &[LocationUpdate]) -> Result<f64, AppError> {
+        let previous = self.cache_service.get_odometer_anchor(driver_id).await?;
+        let (backlog, fresh): (Vec<LocationUpdate>, Vec<LocationUpdate>) = points.iter()
+            .cloned()
+            .partition(|point| previous.as_ref().is_some_and(|previous| point.timestamp <= previous.timestamp));
+
+        let mut by_day: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        self.measure(None, &backlog, &mut by_day);
+        let anchor = self.measure(previous.clone(), &fresh, &mut by_day);
+
+        if let Some(anchor) = &anchor
+            && previous.as_ref().is_none_or(|previous| previous.timestamp != anchor.timestamp)
+        {
+            self.cache_service.set_odometer_anchor(driver_id, anchor).await?;
+        }
+        let added: f64 = by_day.values().sum();
+        if added <= 0.0 {
+            return Ok(0.0);
+        }
+        for (date, km) in &by_day {
+            self.cache_service.add_driver_distance(driver_id, date, *km).await?;
+        }
+        if let Some(driver) = self.cache_service.get_driver(driver_id).await? {
+            self.add_vehicle_distance(&driver, added).await?;
+        }
+        Ok(added)
+    }
+
+    // Adds the distance along `points` to the days it was driven on, starting from `anchor`.
+    // Returns the last point counted from.
+    fn measure(&self, mut anchor: Option<LocationUpdate>, points: &[LocationUpdate], by_day: &mut BTreeMap<NaiveDate, f64>) -> Option<LocationUpdate> {
+        for point in points {
+            if point.accuracy.is_some_and(|accuracy| accuracy > self.config.max_accuracy_meters) {
+                continue;
+            }
+            let Some(last) = &anchor else {
+                anchor = Some(point.clone());
+                continue;
+            };
+            if point.timestamp <= last.timestamp {
+                continue;
+            }
+            if point.timestamp - last.timestamp > Duration::minutes(self.config.max_gap_minutes) {
+                anchor = Some(point.clone());
+                continue;
+            }
+            let km = haversine_km((last.latitude, last.longitude), (point.latitude, point.longitude));
+            if km * 1000.0 < self.config.min_step_meters {
+                continue;
+            }
+            let hours = (point.timestamp - last.timestamp).num_milliseconds() as f64 / 3_600_000.0;
+            if km / hours > self.config.max_speed_kmh {
+                continue;
+            }
+            *by_day.entry(point.timestamp.date_naive()).or_default() += km;
+            anchor = Some(point.clone());
+        }
+        anchor
+    }
+
+    /// Distance per day over the last `days`, today included, and the vehicle's odometer
+    pub async fn stats(&self, driver_id: &DriverId, days: Option<i64>) -> Result<DriverDistanceStats, AppError> {
+        let days = days.unwrap_or(7);
+        if days < 1 || days > self.config.max_stats_days {
+            return Err(AppError::validation_error("days", format!("Must be between 1 and {}", self.config.max_stats_days)));
+        }
+        let driver = self.load_driver(driver_id).await?;
+        let today = Utc::now().date_naive();
+        let mut daily = Vec::new();
+        for offset in (0..days).rev() {
+            let date = today - Duration::days(offset);
+            let km = self.cache_service.get_driver_distance(driver_id, &date).await?;
+            daily.push(DailyDistance { date, km: round_km(km) });
+        }
+        Ok(DriverDistanceStats {
+            driver_id: driver_id.clone(),
+            today_km: daily.last().map_or(0.0, |day| day.km),
+            period_km: round_km(daily.iter().map(|day| day.km).sum()),
+            days: daily,
+            vehicle: self.maintenance(&driver).await?,
+        })
+    }
+
+    /// Records that the driver's vehicle was serviced at its current reading
+    pub async fn record_service(&self, driver_id: &DriverId) -> Result<VehicleMaintenance, AppError> {
+        let driver = self.load_driver(driver_id).await?;
+        let mut odometer = self.odometer(&driver.vehicle.id).await?;
+        odometer.last_service_km = odometer.total_km;
+        odometer.last_serviced_at = Some(Utc::now());
+        odometer.reminded_for_km = None;
+        self.cache_service.cache_vehicle_odometer(&odometer).await?;
+        tracing::info!("Vehicle {} serviced at {:.0} km", odometer.vehicle_id, odometer.total_km);
+        self.maintenance(&driver).await
+    }
+
+    async fn add_vehicle_distance(&self, driver: &Driver, km: f64) -> Result<(), AppError> {
+        let mut odometer = self.odometer(&driver.vehicle.id).await?;
+        odometer.total_km += km;
+        let due_at = odometer.next_service_km(self.config.service_interval_km);
+        let remind = odometer.total_km >= due_at && odometer.reminded_for_km != Some(due_at);
+        if remind {
+            odometer.reminded_for_km = Some(due_at);
+        }
+        self.cache_service.cache_vehicle_odometer(&odometer).await?;
+
+        if remind {
+            let since_service = odometer.total_km - odometer.last_service_km;
+            let message = NotificationMessage::vehicle_service_due(&driver.id, &driver.vehicle, since_service);
+            if let Err(e) = self.notification_service.send_to_driver(&driver.id, message).await {
+                tracing::warn!("Failed to remind driver {} that vehicle {} is due a service: {}", driver.id, odometer.vehicle_id, e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn maintenance(&self, driver: &Driver) -> Result<VehicleMaintenance, AppError> {
+        let odometer = self.odometer(&driver.vehicle.id).await?;
+        let next_service_km = odometer.next_service_km(self.config.service_interval_km);
+        Ok(VehicleMaintenance {
+            vehicle_id: odometer.vehicle_id,
+            license_plate: driver.vehicle.license_plate.clone(),
+            total_km: round_km(odometer.total_km),
+            last_service_km: round_km(odometer.last_service_km),
+            last_serviced_at: odometer.last_serviced_at,
+            next_service_km: round_km(next_service_km),
+            service_due: odometer.total_km >= next_service_km,
+        })
+    }
+
+    async fn odometer(&self, vehicle_id: &str) -> Result<VehicleOdometer, AppError> {
+        Ok(self.cache_service.get_vehicle_odometer(vehicle_id).await?
+            .unwrap_or_else(|| VehicleOdometer::new(vehicle_id)))
+    }
+
+    async fn load_driver(&self, driver_id: &DriverId) -> Result<Driver, AppError> {
+        self.cache_service.get_driver(driver_id).await?
+            .ok_or_else(|| AppError::NotFound(format!("Driver {} not found", driver_id)))
+    }
+}
+
+// To the metre, which is all a phone's GPS is good for
+fn round_km(km: f64) -> f64 {
+    (km * 1000.0).round() / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::mocks::{app::TestApp, fixtures::Faker, messaging::RecordingNotificationService};
+
+    fn point(minutes_ago: f64, latitude: f64, accuracy: f64) -> LocationUpdate {
+        LocationUpdate {
+            latitude,
+            longitude: -0.1870,
+            timestamp: Utc::now() - Duration::milliseconds((minutes_ago * 60_000.0) as i64),
+            accuracy: Some(accuracy),
+            heading: None,
+            speed: None,
+            is_mock: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_noise_is_filtered_and_a_vehicle_past_its_interval_is_due_a_service() {
+        let app = TestApp::new();
+        let notifications = RecordingNotificationService::new();
+        let odometer = OdometerService::new(
+            app.state.cache_service.clone(),
+            Arc::new(notifications.clone()),
+            OdometerConfig { service_interval_km: 2.0, ..OdometerConfig::default() },
+        );
+        let driver = Faker::seeded(111).driver();
+        app.state.cache_service.cache_driver(&driver).await.unwrap();
+
+        // Heading north up Liberation Road, 0.01° (~1.1 km) a minute, with noise mixed in
+        let points = vec![
+            point(30.0, 5.600, 10.0),
+            point(29.9, 5.6001, 10.0), // ~11 m of jitter
+            point(29.5, 5.800, 10.0),  // 22 km in half a minute
+            point(29.0, 5.610, 200.0), // Too imprecise
+            point(29.0, 5.610, 10.0),
+            point(28.0, 5.620, 10.0),
+        ];
+        let added = odometer.record(&driver.id, &points).await.unwrap();
+        assert!((added - 2.224).abs() < 0.01, "added {}", added);
+        assert_eq!(notifications.of_kind("vehicle_service_due").len(), 1);
+
+        // After a gap the first fix only restarts counting; the reminder isn't repeated
+        let later = vec![point(2.0, 5.700, 10.0), point(1.0, 5.710, 10.0)];
+        let added = odometer.record(&driver.id, &later).await.unwrap();
+        assert!((added - 1.112).abs() < 0.01, "added {}", added);
+        assert_eq!(notifications.of_kind("vehicle_service_due").len(), 1);
+
+        let stats = odometer.stats(&driver.id, Some(3)).await.unwrap();
+        assert_eq!(stats.days.len(), 3);
+        assert!((stats.period_km - 3.336).abs() < 0.01 && stats.today_km <= stats.period_km);
+        assert!(stats.vehicle.service_due);
+        assert!(odometer.stats(&driver.id, Some(365)).await.is_err());
+
+        let serviced = odometer.record_service(&driver.id).await.unwrap();
+        assert!(!serviced.service_due);
+        assert_eq!(serviced.last_service_km, stats.vehicle.total_km);
+    }
+}
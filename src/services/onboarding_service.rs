@@ -0,0 +1,329 @@
+// src/services/onboarding_service.rs
+// Driver applications move through documents -> background check -> vehicle inspection
+// -> approved, one admin review at a time. Any stage can be failed, which sends the driver
+// back to resubmitting documents. The driver is notified of every move.
+//
+// Once approved, documents with an expiry date are watched: the driver is reminded as the
+// date approaches and suspended when it passes, until a renewed document is uploaded.
+use chrono::{NaiveDate, Utc};
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        driver::{
+            DocumentKind, Driver, DriverDocument, DriverDocumentUpload, OnboardingReview, OnboardingState,
+            DriverStatus, OnboardingStatus, OnboardingTransition,
+        },
+        ids::DriverId,
+    },
+    services::{
+        cache_service::CacheService,
+        messaging_service::{self, NotificationMessage, NotificationService},
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct OnboardingConfig {
+    pub required_documents: Vec<DocumentKind>, // Checked before documents can be accepted
+    pub expiry_reminder_days: Vec<i64>,        // Days before expiry to remind the driver
+}
+
+impl Default for OnboardingConfig {
+    fn default() -> Self {
+        Self {
+            required_documents: vec![
+                DocumentKind::DriversLicense,
+                DocumentKind::NationalId,
+                DocumentKind::VehicleRegistration,
+            ],
+            expiry_reminder_days: vec![30, 7, 1],
+        }
+    }
+}
+
+// What one expiry check did
+#[derive(Debug, Default, PartialEq)]
+pub struct ExpirySweep {
+    pub reminders_sent: usize,
+    pub drivers_suspended: usize,
+}
+
+pub struct OnboardingService {
+    cache_service: Arc<CacheService>,
+    notification_service: Arc<dyn NotificationService>,
+    config: OnboardingConfig,
+}
+
+impl OnboardingService {
+    pub fn new(
+        cache_service: Arc<CacheService>,
+        notification_service: Arc<dyn NotificationService>,
+        config: OnboardingConfig,
+    ) -> Self {
+        Self { cache_service, notification_service, config }
+    }
+
+    async fn load_driver(&self, driver_id: &DriverId) -> Result<Driver, AppError> {
+        self.cache_service.get_driver(driver_id).await?
+            .ok_or_else(|| AppError::driver_not_found(driver_id.as_str()))
+    }
+
+    pub async fn status(&self, driver_id: &DriverId) -> Result<OnboardingStatus, AppError> {
+        let driver = self.load_driver(driver_id).await?;
+        Ok(self.to_status(driver))
+    }
+
+    /// Add or replace documents while the application is waiting on them, or renew them
+    /// once approved. A rejected application goes back to the start of review; a driver
+    /// suspended for a lapsed document is reinstated once none are left.
+    pub async fn submit_documents(&self, driver_id: &DriverId, uploads: Vec<DriverDocumentUpload>) -> Result<OnboardingStatus, AppError> {
+        let now = Utc::now();
+        let today = now.date_naive();
+        if uploads.is_empty() {
+            return Err(AppError::validation_error("documents", "At least one document is required"));
+        }
+        if uploads.iter().any(|upload| upload.url.trim().is_empty()) {
+            return Err(AppError::validation_error("documents", "Every document needs a URL"));
+        }
+        if uploads.iter().any(|upload| upload.expires_at.is_some_and(|expires_at| expires_at < today)) {
+            return Err(AppError::validation_error("expires_at", "Document has already expired"));
+        }
+
+        let mut driver = self.load_driver(driver_id).await?;
+        if !matches!(driver.onboarding_state, OnboardingState::DocumentsSubmitted | OnboardingState::Rejected | OnboardingState::Approved) {
+            return Err(AppError::validation_error(
+                "documents",
+                format!("Documents can't be changed at {:?}", driver.onboarding_state),
+            ));
+        }
+
+        for upload in uploads {
+            driver.documents.retain(|document| document.kind != upload.kind);
+            driver.documents.push(DriverDocument {
+                kind: upload.kind,
+                url: upload.url,
+                submitted_at: now,
+                expires_at: upload.expires_at,
+                reminded_days_before: None,
+            });
+        }
+        if driver.onboarding_state == OnboardingState::Rejected {
+            self.transition(&mut driver, OnboardingState::DocumentsSubmitted, None).await?;
+            return Ok(self.to_status(driver));
+        }
+
+        let reinstated = driver.suspended_reason.is_some()
+            && driver.ban.is_none()
+            && !driver.documents.iter().any(|document| document.has_lapsed(today));
+        if reinstated {
+            driver.is_active = true;
+            driver.suspended_reason = None;
+            tracing::info!("Driver {} reinstated after renewing documents", driver.id);
+        }
+        driver.updated_at = now;
+        self.cache_service.cache_driver(&driver).await?;
+        if reinstated {
+            self.notify(&driver.id, NotificationMessage::driver_reinstated(&driver.id)).await;
+        }
+        Ok(self.to_status(driver))
+    }
+
+    /// Remind approved drivers of documents coming up for expiry, once per reminder day,
+    /// and suspend those holding one that has lapsed. A driver mid-delivery keeps the
+    /// job but gets no new ones.
+    pub async fn check_document_expiry(&self, today: NaiveDate) -> Result<ExpirySweep, AppError> {
+        let mut sweep = ExpirySweep::default();
+        for driver_id in self.cache_service.get_all_driver_ids().await? {
+            let Some(mut driver) = self.cache_service.get_driver(&driver_id).await? else {
+                continue;
+            };
+            if !driver.is_approved() {
+                continue;
+            }
+
+            let mut reminders = Vec::new();
+            for document in driver.documents.iter_mut() {
+                let Some(expires_at) = document.expires_at else {
+                    continue;
+                };
+                let days_left = (expires_at - today).num_days();
+                // The closest reminder day already reached, if not yet sent
+                let due = self.config.expiry_reminder_days.iter()
+                    .copied()
+                    .filter(|days| days_left >= 0 && days_left <= *days)
+                    .min()
+                    .filter(|days| document.reminded_days_before.is_none_or(|sent| *days < sent));
+                if let Some(days) = due {
+                    document.reminded_days_before = Some(days);
+                    reminders.push(NotificationMessage::document_expiring(&driver.id, document, days_left));
+                }
+            }
+
+            let lapsed = driver.documents.iter()
+                .filter(|document| document.has_lapsed(today))
+                .map(|document| messaging_service::document_name(document.kind))
+                .collect::<Vec<_>>();
+            let suspension = (!lapsed.is_empty() && driver.suspended_reason.is_none())
+                .then(|| format!("Your {} has expired.", lapsed.join(" and ")));
+            if reminders.is_empty() && suspension.is_none() {
+                continue;
+            }
+
+            if let Some(reason) = &suspension {
+                driver.is_active = false;
+                driver.suspended_reason = Some(reason.clone());
+                if driver.status != DriverStatus::OnRide {
+                    driver.status = DriverStatus::Offline;
+                    driver.break_started_at = None;
+                    driver.break_ends_at = None;
+                }
+                tracing::info!("Driver {} suspended: {}", driver.id, reason);
+            }
+            driver.updated_at = Utc::now();
+            self.cache_service.cache_driver(&driver).await?;
+
+            sweep.reminders_sent += reminders.len();
+            for message in reminders {
+                self.notify(&driver.id, message).await;
+            }
+            if let Some(reason) = suspension {
+                sweep.drivers_suspended += 1;
+                self.notify(&driver.id, NotificationMessage::driver_suspended(&driver.id, &reason)).await;
+            }
+        }
+        Ok(sweep)
+    }
+
+    /// Pass the stage the driver is at, moving them to the next; passing the vehicle
+    /// inspection approves them.
+    pub async fn advance(&self, driver_id: &DriverId, review: OnboardingReview) -> Result<OnboardingStatus, AppError> {
+        let mut driver = self.load_driver(driver_id).await?;
+        check_stage(&driver, review.stage)?;
+        let next = review.stage.next()
+            .ok_or_else(|| AppError::validation_error("stage", format!("{:?} is not a review stage", review.stage)))?;
+        if review.stage == OnboardingState::DocumentsSubmitted {
+            let missing = self.missing_documents(&driver);
+            if !missing.is_empty() {
+                return Err(AppError::validation_error("stage", format!("Documents still missing: {:?}", missing)));
+            }
+        }
+        self.transition(&mut driver, next, review.note).await?;
+        Ok(self.to_status(driver))
+    }
+
+    pub async fn reject(&self, driver_id: &DriverId, review: OnboardingReview) -> Result<OnboardingStatus, AppError> {
+        let note = review.note
+            .filter(|note| !note.trim().is_empty())
+            .ok_or_else(|| AppError::validation_error("note", "Tell the driver why"))?;
+        let mut driver = self.load_driver(driver_id).await?;
+        check_stage(&driver, review.stage)?;
+        if review.stage.next().is_none() {
+            return Err(AppError::validation_error("stage", format!("{:?} is not a review stage", review.stage)));
+        }
+        self.transition(&mut driver, OnboardingState::Rejected, Some(note)).await?;
+        Ok(self.to_status(driver))
+    }
+
+    async fn transition(&self, driver: &mut Driver, to: OnboardingState, note: Option<String>) -> Result<(), AppError> {
+        let now = Utc::now();
+        driver.onboarding_history.push(OnboardingTransition {
+            from: driver.onboarding_state,
+            to,
+            note: note.clone(),
+            at: now,
+        });
+        driver.onboarding_state = to;
+        driver.is_verified = to == OnboardingState::Approved;
+        driver.updated_at = now;
+        self.cache_service.cache_driver(driver).await?;
+        tracing::info!("Driver {} onboarding moved to {:?}", driver.id, to);
+
+        self.notify(&driver.id, NotificationMessage::onboarding_update(&driver.id, to, note.as_deref())).await;
+        Ok(())
+    }
+
+    // Changes stand even if the driver can't be reached
+    async fn notify(&self, driver_id: &DriverId, message: NotificationMessage) {
+        if let Err(e) = self.notification_service.send_to_driver(driver_id, message).await {
+            tracing::warn!("Failed to notify driver {}: {}", driver_id, e);
+        }
+    }
+
+    fn missing_documents(&self, driver: &Driver) -> Vec<DocumentKind> {
+        self.config.required_documents.iter()
+            .filter(|kind| !driver.documents.iter().any(|document| document.kind == **kind))
+            .copied()
+            .collect()
+    }
+
+    fn to_status(&self, driver: Driver) -> OnboardingStatus {
+        OnboardingStatus {
+            missing_documents: self.missing_documents(&driver),
+            driver_id: driver.id,
+            state: driver.onboarding_state,
+            documents: driver.documents,
+            history: driver.onboarding_history,
+        }
+    }
+}
+
+fn check_stage(driver: &Driver, stage: OnboardingState) -> Result<(), AppError> {
+    if driver.onboarding_state != stage {
+        return Err(AppError::validation_error(
+            "stage",
+            format!("Driver is at {:?}, not {:?}", driver.onboarding_state, stage),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mocks::{fixtures::Faker, messaging::RecordingNotificationService},
+        services::cache_service::CacheConfig,
+    };
+
+    #[tokio::test]
+    async fn test_application_moves_through_each_stage() {
+        let cache_service = Arc::new(CacheService::new_memory(CacheConfig::default()));
+        let notifications = Arc::new(RecordingNotificationService::new());
+        let onboarding = OnboardingService::new(cache_service.clone(), notifications.clone(), OnboardingConfig::default());
+
+        let mut driver = Faker::new().driver();
+        driver.onboarding_state = OnboardingState::DocumentsSubmitted;
+        driver.is_verified = false;
+        cache_service.cache_driver(&driver).await.unwrap();
+        let review = |stage, note: Option<&str>| OnboardingReview { stage, note: note.map(str::to_string) };
+        let upload = |kind| DriverDocumentUpload { kind, url: format!("https://files.example/{:?}", kind), expires_at: None };
+
+        // Documents can't be accepted while some are missing
+        onboarding.submit_documents(&driver.id, vec![upload(DocumentKind::DriversLicense)]).await.unwrap();
+        assert!(onboarding.advance(&driver.id, review(OnboardingState::DocumentsSubmitted, None)).await.is_err());
+        let status = onboarding.submit_documents(&driver.id, vec![upload(DocumentKind::NationalId), upload(DocumentKind::VehicleRegistration)]).await.unwrap();
+        assert!(status.missing_documents.is_empty());
+
+        onboarding.advance(&driver.id, review(OnboardingState::DocumentsSubmitted, None)).await.unwrap();
+        // A second reviewer acting on the stale stage is turned away
+        assert!(onboarding.advance(&driver.id, review(OnboardingState::DocumentsSubmitted, None)).await.is_err());
+        assert!(onboarding.reject(&driver.id, review(OnboardingState::BackgroundCheck, None)).await.is_err());
+        let status = onboarding.reject(&driver.id, review(OnboardingState::BackgroundCheck, Some("Police report unreadable."))).await.unwrap();
+        assert_eq!(status.state, OnboardingState::Rejected);
+
+        // Resubmitting starts review over
+        let status = onboarding.submit_documents(&driver.id, vec![upload(DocumentKind::NationalId)]).await.unwrap();
+        assert_eq!(status.state, OnboardingState::DocumentsSubmitted);
+        for stage in [OnboardingState::DocumentsSubmitted, OnboardingState::BackgroundCheck, OnboardingState::VehicleInspection] {
+            onboarding.advance(&driver.id, review(stage, None)).await.unwrap();
+        }
+
+        let approved = cache_service.get_driver(&driver.id).await.unwrap().unwrap();
+        assert!(approved.is_approved() && approved.is_verified);
+        assert_eq!(approved.onboarding_history.len(), 6);
+        assert_eq!(notifications.sent_to_driver(&driver.id).len(), 6);
+        assert_eq!(notifications.of_kind("onboarding_update").len(), 6);
+    }
+}
@@ -0,0 +1,159 @@
+// src/services/otp_service.rs
+// One-time codes and the lockouts that stop them being guessed. Codes are kept only as
+// salted hashes, work once, and every wrong guess counts towards locking the identifier
+// out; password logins share the same failure counting. A worker purges codes that were
+// never used, for stores that keep them past their TTL.
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::otp::StoredOtp,
+    services::cache_service::CacheService,
+    utils::secret_hash::{salted_hash, verify_salted},
+};
+
+pub const PASSWORD: &str = "password";
+
+#[derive(Debug, Clone)]
+pub struct OtpConfig {
+    pub code_length: usize,
+    pub code_ttl_seconds: u64,
+    pub max_failures: i64,        // Within one failure window, before the identifier is locked out
+    pub failure_window_seconds: u64,
+    pub lockout_seconds: u64,
+}
+
+impl Default for OtpConfig {
+    fn default() -> Self {
+        Self {
+            code_length: 6,
+            code_ttl_seconds: 600,
+            max_failures: 5,
+            failure_window_seconds: 900,
+            lockout_seconds: 900,
+        }
+    }
+}
+
+pub struct OtpService {
+    cache: Arc<CacheService>,
+    config: OtpConfig,
+}
+
+impl OtpService {
+    pub fn new(cache: Arc<CacheService>, config: OtpConfig) -> Self {
+        Self { cache, config }
+    }
+
+    /// A fresh code for `purpose` and `identifier`, replacing any outstanding one
+    pub async fn issue(&self, purpose: &str, identifier: &str) -> Result<String, AppError> {
+        self.ensure_unlocked(purpose, identifier).await?;
+        let code = generate_code(self.config.code_length);
+        let stored = StoredOtp {
+            hash: salted_hash(&code),
+            expires_at: Utc::now() + Duration::seconds(self.config.code_ttl_seconds as i64),
+        };
+        self.cache.cache_otp(purpose, identifier, &stored, self.config.code_ttl_seconds).await?;
+        Ok(code)
+    }
+
+    /// Uses up the code if it matches; a wrong one counts as a failure
+    pub async fn verify(&self, purpose: &str, identifier: &str, code: &str) -> Result<(), AppError> {
+        self.ensure_unlocked(purpose, identifier).await?;
+        let matches = self.cache.get_otp(purpose, identifier).await?
+            .is_some_and(|stored| stored.expires_at > Utc::now() && verify_salted(code.trim(), &stored.hash));
+        if !matches {
+            self.record_failure(purpose, identifier).await?;
+            return Err(AppError::validation_error("verification_code", "Code is incorrect or has expired"));
+        }
+        self.cache.delete_otp(purpose, identifier).await?;
+        self.cache.clear_auth_failures(purpose, identifier).await?;
+        Ok(())
+    }
+
+    pub async fn ensure_unlocked(&self, purpose: &str, identifier: &str) -> Result<(), AppError> {
+        if let Some(until) = self.cache.get_auth_lockout(purpose, identifier).await?
+            && until > Utc::now()
+        {
+            return Err(AppError::TooManyRequests {
+                message: "Too many failed attempts; try again later".to_string(),
+                retry_after_seconds: (until - Utc::now()).num_seconds().max(1) as u64,
+            });
+        }
+        Ok(())
+    }
+
+    /// Counts a failed attempt, locking the identifier out once there have been too many.
+    /// An outstanding code is thrown away with the lockout, so it can't be guessed after.
+    pub async fn record_failure(&self, purpose: &str, identifier: &str) -> Result<(), AppError> {
+        let failures = self.cache.record_auth_failure(purpose, identifier, self.config.failure_window_seconds).await?;
+        if failures >= self.config.max_failures {
+            let until = Utc::now() + Duration::seconds(self.config.lockout_seconds as i64);
+            self.cache.set_auth_lockout(purpose, identifier, until).await?;
+            self.cache.clear_auth_failures(purpose, identifier).await?;
+            self.cache.delete_otp(purpose, identifier).await?;
+            tracing::warn!("Locked out {} {} after {} failed attempts", purpose, identifier, failures);
+        }
+        Ok(())
+    }
+
+    pub async fn clear_failures(&self, purpose: &str, identifier: &str) -> Result<(), AppError> {
+        self.cache.clear_auth_failures(purpose, identifier).await
+    }
+
+    /// Drops codes that expired unused; returns how many went
+    pub async fn purge_expired(&self, now: DateTime<Utc>) -> Result<usize, AppError> {
+        let mut purged = 0;
+        for (purpose, identifier) in self.cache.get_pending_otps().await? {
+            let expired = self.cache.get_otp(&purpose, &identifier).await?
+                .is_none_or(|stored| stored.expires_at <= now);
+            if expired {
+                self.cache.delete_otp(&purpose, &identifier).await?;
+                purged += 1;
+            }
+        }
+        Ok(purged)
+    }
+}
+
+fn generate_code(length: usize) -> String {
+    let mut rng = rand::rng();
+    (0..length).map(|_| char::from(b'0' + rng.random_range(0..10u8))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::mocks::app::TestApp;
+
+    #[tokio::test]
+    async fn test_codes_work_once_and_guessing_locks_out() {
+        let app = TestApp::new();
+        let cache = app.state.cache_service.clone();
+        let otp = OtpService::new(cache.clone(), OtpConfig { max_failures: 3, ..OtpConfig::default() });
+
+        let code = otp.issue("device", "usr_1:abc").await.unwrap();
+        let stored = cache.get_otp("device", "usr_1:abc").await.unwrap().unwrap();
+        assert!(!stored.hash.contains(&code));
+        otp.verify("device", "usr_1:abc", &code).await.unwrap();
+        assert!(otp.verify("device", "usr_1:abc", &code).await.is_err());
+
+        // The failed reuse above counts too, so two more wrong guesses lock it out
+        let code = otp.issue("device", "usr_1:abc").await.unwrap();
+        assert!(otp.verify("device", "usr_1:abc", "000000x").await.is_err());
+        assert!(otp.verify("device", "usr_1:abc", "000000x").await.is_err());
+        let error = otp.verify("device", "usr_1:abc", &code).await.unwrap_err();
+        assert!(matches!(error, AppError::TooManyRequests { .. }));
+        assert!(otp.issue("device", "usr_1:abc").await.is_err());
+        assert!(otp.issue("device", "usr_2:abc").await.is_ok());
+
+        // Purging leaves live codes alone
+        assert_eq!(otp.purge_expired(Utc::now()).await.unwrap(), 0);
+        assert_eq!(otp.purge_expired(Utc::now() + Duration::hours(1)).await.unwrap(), 1);
+        assert!(cache.get_pending_otps().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+// src/services/package_analysis.rs
+// Sizing up a package from the customer's photo at booking. The image analysis is a
+// provider's behind `PackageImageAnalyzer`; this checks what it found against what the
+// customer declared, so an undersold package is flagged before a motorbike is sent for it.
+use async_trait::async_trait;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::job::{PackageDetails, PackageEstimate, PackagePhoto, PackageType},
+};
+
+#[async_trait]
+pub trait PackageImageAnalyzer: Send + Sync {
+    /// None when the provider can't make out a package in the photo
+    async fn analyze(&self, photo_url: &str) -> Result<Option<PackageEstimate>, AppError>;
+}
+
+// Until a provider is configured: photos are still kept and shown to drivers
+pub struct NoPackageAnalysis;
+
+#[async_trait]
+impl PackageImageAnalyzer for NoPackageAnalysis {
+    async fn analyze(&self, _photo_url: &str) -> Result<Option<PackageEstimate>, AppError> {
+        Ok(None)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PackageAnalysisConfig {
+    pub min_confidence: f32,   // Less certain estimates are kept but never flag a mismatch
+    pub volume_tolerance: f32, // How far past the declared volume a detected one may go, 0.5 = 50%
+}
+
+impl Default for PackageAnalysisConfig {
+    fn default() -> Self {
+        Self {
+            min_confidence: 0.6,
+            volume_tolerance: 0.5,
+        }
+    }
+}
+
+pub struct PackageAnalysisService {
+    analyzer: Arc<dyn PackageImageAnalyzer>,
+    config: PackageAnalysisConfig,
+}
+
+impl PackageAnalysisService {
+    pub fn new(analyzer: Arc<dyn PackageImageAnalyzer>, config: PackageAnalysisConfig) -> Self {
+        Self { analyzer, config }
+    }
+
+    /// The photo with whatever the provider detected. Best-effort: a failed analysis
+    /// leaves the estimate out rather than holding up the booking.
+    pub async fn inspect(&self, url: String, declared: &PackageDetails) -> PackagePhoto {
+        let estimate = match self.analyzer.analyze(&url).await {
+            Ok(estimate) => estimate,
+            Err(e) => {
+                tracing::warn!("Failed to analyse package photo {}: {}", url, e);
+                None
+            }
+        };
+        let size_mismatch = estimate.as_ref().is_some_and(|estimate| self.is_size_mismatch(declared, estimate));
+        PackagePhoto { url, estimate, size_mismatch }
+    }
+
+    // Only ever flags a package bigger than declared; a smaller one costs nobody anything
+    fn is_size_mismatch(&self, declared: &PackageDetails, estimate: &PackageEstimate) -> bool {
+        if estimate.confidence < self.config.min_confidence {
+            return false;
+        }
+        let larger_class = match (size_class(&declared.package_type), size_class(&estimate.package_type)) {
+            (Some(declared), Some(detected)) => detected > declared,
+            _ => false,
+        };
+        let declared_volume = declared.dimensions.volume_cm3();
+        let larger_volume = declared_volume > 0.0
+            && estimate.dimensions.volume_cm3() > declared_volume * (1.0 + self.config.volume_tolerance);
+        larger_class || larger_volume
+    }
+}
+
+// Package types that say how big the package is, smallest first; the rest say what's in it
+fn size_class(package_type: &PackageType) -> Option<u8> {
+    match package_type {
+        PackageType::Document => Some(0),
+        PackageType::SmallPackage => Some(1),
+        PackageType::MediumPackage => Some(2),
+        PackageType::LargePackage => Some(3),
+        PackageType::ExtraLarge => Some(4),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker, messaging::RecordingNotificationService},
+        models::{driver::DriverStatus, job::Dimensions, user::UserType},
+        services::{job_service::JobOperations, user_service::UserOperations},
+    };
+
+    struct FixedAnalyzer(PackageEstimate);
+
+    #[async_trait]
+    impl PackageImageAnalyzer for FixedAnalyzer {
+        async fn analyze(&self, _photo_url: &str) -> Result<Option<PackageEstimate>, AppError> {
+            Ok(Some(self.0.clone()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_undersold_package_is_flagged_and_photo_reaches_driver() {
+        let notifications = RecordingNotificationService::new();
+        let app = TestApp::builder()
+            .notification_service(Arc::new(notifications.clone()))
+            .package_analyzer(Arc::new(FixedAnalyzer(PackageEstimate {
+                package_type: PackageType::LargePackage,
+                dimensions: Dimensions { length_cm: 80.0, width_cm: 60.0, height_cm: 50.0 },
+                confidence: 0.9,
+            })))
+            .build();
+        let state = &app.state;
+        let mut faker = Faker::seeded(29);
+
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let mut request = faker.job_request(&customer.id);
+        request.package.package_type = PackageType::SmallPackage;
+        request.package_photo_url = Some("https://files.example/parcel.jpg".to_string());
+        let created = state.job_service.create_job(request).await.unwrap();
+        let photo = created.package_photo.unwrap();
+        assert!(photo.size_mismatch);
+        assert!(matches!(photo.estimate.unwrap().package_type, PackageType::LargePackage));
+
+        let mut driver = faker.driver();
+        driver.status = DriverStatus::Online;
+        state.cache_service.cache_driver(&driver).await.unwrap();
+        state.job_service.assign_driver_to_job(&created.id, &driver.id).await.unwrap();
+        let sent = notifications.of_kind("driver_assigned");
+        let data = sent[0].message.data.as_ref().unwrap();
+        assert_eq!(data["package_photo_url"], "https://files.example/parcel.jpg");
+        assert_eq!(data["package_size_mismatch"], true);
+
+        // Declared as large as it looks
+        let mut request = faker.job_request(&customer.id);
+        request.package.package_type = PackageType::LargePackage;
+        request.package.dimensions = Dimensions { length_cm: 80.0, width_cm: 60.0, height_cm: 45.0 };
+        request.package_photo_url = Some("https://files.example/crate.jpg".to_string());
+        let created = state.job_service.create_job(request).await.unwrap();
+        assert!(!created.package_photo.unwrap().size_mismatch);
+
+        let mut request = faker.job_request(&customer.id);
+        request.package_photo_url = Some(" ".to_string());
+        assert!(state.job_service.create_job(request).await.is_err());
+    }
+}
@@ -0,0 +1,241 @@
+// src/services/pooling.rs
+// Pooled deliveries: open jobs heading the same way are grouped so one driver carries them
+// together, collecting every package before dropping any off. Each customer gets a discount
+// for sharing, and each job keeps its own status, tracking code and handoff codes.
+use chrono::Utc;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::job::{Job, JobEvent, JobEventType, JobPool, JobStatus, Location, PoolStop, StopKind},
+    services::cache_service::CacheService,
+    utils::{
+        geo::{bearing_deg, haversine_km},
+        id_generator::{IdGenerator, IdType},
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct PoolingConfig {
+    pub max_pickup_gap_km: f64,          // Between any two pickups in a pool
+    pub max_dropoff_gap_km: f64,         // Between any two dropoffs in a pool
+    pub max_heading_difference_deg: f64, // Between the jobs' pickup-to-dropoff directions
+    pub max_jobs: usize,
+    pub max_combined_weight_kg: f32,     // Drivers still need the capacity for it at assignment
+    pub discount_rate: f64,              // Off each pooled job's fare, before tip
+}
+
+impl Default for PoolingConfig {
+    fn default() -> Self {
+        Self {
+            max_pickup_gap_km: 1.5,
+            max_dropoff_gap_km: 2.5,
+            max_heading_difference_deg: 30.0,
+            max_jobs: 3,
+            max_combined_weight_kg: 15.0,
+            discount_rate: 0.15,
+        }
+    }
+}
+
+pub struct PoolingService {
+    cache_service: Arc<CacheService>,
+    config: PoolingConfig,
+}
+
+impl PoolingService {
+    pub fn new(cache_service: Arc<CacheService>, config: PoolingConfig) -> Self {
+        Self { cache_service, config }
+    }
+
+    pub async fn get_pool(&self, pool_id: &str) -> Result<Option<JobPool>, AppError> {
+        self.cache_service.get_job_pool(pool_id).await
+    }
+
+    /// Groups open jobs no driver has taken yet into pools, oldest job first. Jobs already in
+    /// a pool are left alone; returns the pools formed by this pass.
+    pub async fn pool_pending_jobs(&self) -> Result<Vec<JobPool>, AppError> {
+        let mut open = Vec::new();
+        for job_id in self.cache_service.get_active_jobs().await? {
+            let Some(job) = self.cache_service.load_job(&job_id).await? else {
+                continue;
+            };
+            let waiting = job.driver_id.is_none() && matches!(job.status, JobStatus::Pending | JobStatus::Searching);
+            if waiting && job.pool_id.is_none() {
+                open.push(job);
+            }
+        }
+        open.sort_by_key(|job| job.created_at);
+
+        let mut taken = vec![false; open.len()];
+        let mut pools = Vec::new();
+        for lead in 0..open.len() {
+            if taken[lead] {
+                continue;
+            }
+            let mut group = vec![lead];
+            let mut weight_kg = open[lead].package.weight_kg;
+            for candidate in lead + 1..open.len() {
+                if group.len() >= self.config.max_jobs {
+                    break;
+                }
+                let job = &open[candidate];
+                let fits = weight_kg + job.package.weight_kg <= self.config.max_combined_weight_kg;
+                if !taken[candidate] && fits && group.iter().all(|&member| self.compatible(&open[member], job)) {
+                    group.push(candidate);
+                    weight_kg += job.package.weight_kg;
+                }
+            }
+            if group.len() < 2 {
+                continue;
+            }
+            for &member in &group {
+                taken[member] = true;
+            }
+            let jobs = group.iter().map(|&member| open[member].clone()).collect();
+            pools.push(self.form_pool(jobs, weight_kg).await?);
+        }
+        Ok(pools)
+    }
+
+    // Same corridor, same handling and priced in the same currency
+    fn compatible(&self, a: &Job, b: &Job) -> bool {
+        let point = |location: &Location| (location.latitude, location.longitude);
+        let heading = |job: &Job| bearing_deg(point(&job.pickup_location), point(&job.dropoff_location));
+        let difference = (heading(a) - heading(b)).abs() % 360.0;
+        a.pricing.currency == b.pricing.currency
+            && a.package.handling() == b.package.handling()
+            && haversine_km(point(&a.pickup_location), point(&b.pickup_location)) <= self.config.max_pickup_gap_km
+            && haversine_km(point(&a.dropoff_location), point(&b.dropoff_location)) <= self.config.max_dropoff_gap_km
+            && difference.min(360.0 - difference) <= self.config.max_heading_difference_deg
+    }
+
+    async fn form_pool(&self, mut jobs: Vec<Job>, combined_weight_kg: f32) -> Result<JobPool, AppError> {
+        let now = Utc::now();
+        let pool = JobPool {
+            id: IdGenerator::generate(IdType::Pool),
+            job_ids: jobs.iter().map(|job| job.id.clone()).collect(),
+            stops: plan_stops(&jobs),
+            discount_rate: self.config.discount_rate,
+            combined_weight_kg,
+            created_at: now,
+        };
+        self.cache_service.cache_job_pool(&pool).await?;
+
+        for job in &mut jobs {
+            let discount = (job.pricing.total - job.pricing.tip).times(self.config.discount_rate);
+            job.pricing.discount += discount;
+            job.pricing.total -= discount;
+            job.pool_id = Some(pool.id.clone());
+            job.updated_at = now;
+            self.cache_service.cache_job(job).await?;
+            self.cache_service.append_job_event(&job.id, &JobEvent {
+                event_type: JobEventType::Pooled,
+                timestamp: now,
+                location: None,
+                actor: "system".to_string(),
+                notes: Some(format!("Shares pool {} with {} other jobs, {} off", pool.id, pool.job_ids.len() - 1, discount)),
+            }).await?;
+        }
+
+        tracing::info!("Pooled {} jobs into {}", pool.job_ids.len(), pool.id);
+        Ok(pool)
+    }
+}
+
+// Every pickup, then every dropoff, each time heading for the nearest stop left
+fn plan_stops(jobs: &[Job]) -> Vec<PoolStop> {
+    let mut stops: Vec<PoolStop> = Vec::with_capacity(jobs.len() * 2);
+    for kind in [StopKind::Pickup, StopKind::Dropoff] {
+        let mut remaining: Vec<PoolStop> = jobs.iter().map(|job| PoolStop {
+            job_id: job.id.clone(),
+            kind,
+            location: match kind {
+                StopKind::Pickup => job.pickup_location.clone(),
+                StopKind::Dropoff => job.dropoff_location.clone(),
+            },
+        }).collect();
+        while !remaining.is_empty() {
+            let next = match stops.last() {
+                Some(last) => {
+                    let from = (last.location.latitude, last.location.longitude);
+                    let distance = |stop: &PoolStop| haversine_km(from, (stop.location.latitude, stop.location.longitude));
+                    (0..remaining.len())
+                        .min_by(|&a, &b| distance(&remaining[a]).total_cmp(&distance(&remaining[b])))
+                        .unwrap_or(0)
+                }
+                None => 0, // Start at the oldest job's pickup
+            };
+            stops.push(remaining.remove(next));
+        }
+    }
+    stops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::{driver::DriverStatus, job::PackageType, user::UserType},
+        services::{job_service::JobOperations, user_service::UserOperations},
+    };
+
+    #[tokio::test]
+    async fn test_pooled_jobs_are_discounted_and_assigned_together() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(43);
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+
+        // Two jobs along the same stretch of road, one heading the other way
+        let mut book = |pickup: (f64, f64), dropoff: (f64, f64)| {
+            let mut request = faker.job_request(&customer.id);
+            let (from, to) = (request.pickup_location.as_mut().unwrap(), request.dropoff_location.as_mut().unwrap());
+            (from.latitude, from.longitude) = pickup;
+            (to.latitude, to.longitude) = dropoff;
+            request.package.package_type = PackageType::SmallPackage;
+            request.package.is_fragile = false;
+            request.package.weight_kg = 3.0;
+            request
+        };
+        let requests = [
+            book((5.6037, -0.1870), (5.6500, -0.1700)),
+            book((5.6050, -0.1860), (5.6480, -0.1690)),
+            book((5.6040, -0.1865), (5.5600, -0.2000)),
+        ];
+        let mut jobs = Vec::new();
+        for request in requests {
+            jobs.push(state.job_service.create_job(request).await.unwrap());
+        }
+
+        let pools = state.pooling_service.pool_pending_jobs().await.unwrap();
+        assert_eq!(pools.len(), 1);
+        let pool = &pools[0];
+        assert_eq!(pool.job_ids, vec![jobs[0].id.clone(), jobs[1].id.clone()]);
+        let kinds: Vec<StopKind> = pool.stops.iter().map(|stop| stop.kind).collect();
+        assert_eq!(kinds, vec![StopKind::Pickup, StopKind::Pickup, StopKind::Dropoff, StopKind::Dropoff]);
+        // Nothing left to pool on a second pass
+        assert!(state.pooling_service.pool_pending_jobs().await.unwrap().is_empty());
+
+        let pooled = state.job_service.get_job(&jobs[0].id).await.unwrap().unwrap();
+        assert_eq!(pooled.pool_id.as_deref(), Some(pool.id.as_str()));
+        assert!(!pooled.pricing.discount.is_zero());
+        assert_eq!(pooled.pricing.total + pooled.pricing.discount, jobs[0].pricing.total);
+        assert_ne!(pooled.tracking_code, jobs[1].tracking_code);
+
+        // Taking one job of the pool takes the rest
+        let mut driver = faker.driver();
+        driver.status = DriverStatus::Online;
+        state.cache_service.cache_driver(&driver).await.unwrap();
+        state.job_service.assign_driver_to_job(&jobs[1].id, &driver.id).await.unwrap();
+        for job in &jobs[..2] {
+            let assigned = state.job_service.get_job(&job.id).await.unwrap().unwrap();
+            assert_eq!(assigned.driver_id.as_ref(), Some(&driver.id));
+        }
+        let unpooled = state.job_service.get_job(&jobs[2].id).await.unwrap().unwrap();
+        assert!(unpooled.driver_id.is_none() && unpooled.pool_id.is_none());
+    }
+}
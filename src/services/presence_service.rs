@@ -0,0 +1,159 @@
+// src/services/presence_service.rs
+// Who is connected right now, across every instance. Each open socket keeps an entry in
+// Redis that its instance refreshes on a heartbeat; entries of an instance that died
+// lapse on their own and are swept out the next time presence is read.
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+use uuid::Uuid;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        ids::DriverId,
+        presence::{PresenceEntry, PresenceKind, PresenceSnapshot},
+    },
+    services::cache_service::CacheService,
+};
+
+#[derive(Debug, Clone)]
+pub struct PresenceConfig {
+    pub heartbeat_seconds: u64, // How often an open connection refreshes its entry
+    pub entry_ttl_seconds: u64, // Entries not refreshed within this are taken as gone
+}
+
+impl Default for PresenceConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_seconds: 30,
+            entry_ttl_seconds: 90,
+        }
+    }
+}
+
+pub struct PresenceService {
+    cache_service: Arc<CacheService>,
+    config: PresenceConfig,
+}
+
+impl PresenceService {
+    pub fn new(cache_service: Arc<CacheService>, config: PresenceConfig) -> Self {
+        Self {
+            cache_service,
+            config,
+        }
+    }
+
+    pub fn heartbeat_interval(&self) -> Duration {
+        Duration::from_secs(self.config.heartbeat_seconds)
+    }
+
+    /// Record a new connection, replacing whatever entry an earlier one left
+    pub async fn connected(&self, kind: PresenceKind, id: &str) -> Result<PresenceEntry, AppError> {
+        let now = Utc::now();
+        let entry = PresenceEntry {
+            id: id.to_string(),
+            kind,
+            connection_id: Uuid::new_v4().to_string(),
+            connected_at: now,
+            last_seen_at: now,
+        };
+        self.cache_service.cache_presence(&entry, self.config.entry_ttl_seconds).await?;
+        self.record_last_seen(&entry, now).await?;
+        Ok(entry)
+    }
+
+    /// Keep a connection's entry alive; a no-op once a newer connection has replaced it
+    pub async fn heartbeat(&self, entry: &PresenceEntry) -> Result<(), AppError> {
+        let now = Utc::now();
+        if !self.is_current(entry).await? {
+            return Ok(());
+        }
+        let refreshed = PresenceEntry { last_seen_at: now, ..entry.clone() };
+        self.cache_service.cache_presence(&refreshed, self.config.entry_ttl_seconds).await?;
+        self.record_last_seen(entry, now).await
+    }
+
+    // A reconnect on another instance may already hold the entry; that one stays
+    pub async fn disconnected(&self, entry: &PresenceEntry) -> Result<(), AppError> {
+        if self.is_current(entry).await? {
+            self.cache_service.remove_presence(entry.kind, &entry.id).await?;
+        }
+        self.record_last_seen(entry, Utc::now()).await
+    }
+
+    pub async fn snapshot(&self) -> Result<PresenceSnapshot, AppError> {
+        Ok(PresenceSnapshot {
+            drivers: self.present(PresenceKind::Driver).await?,
+            customers: self.present(PresenceKind::Customer).await?,
+            generated_at: Utc::now(),
+        })
+    }
+
+    /// Connected entries of `kind`, most recently connected first
+    pub async fn present(&self, kind: PresenceKind) -> Result<Vec<PresenceEntry>, AppError> {
+        let mut entries = Vec::new();
+        for id in self.cache_service.get_present_ids(kind).await? {
+            match self.cache_service.get_presence(kind, &id).await? {
+                Some(entry) => entries.push(entry),
+                None => {
+                    tracing::debug!("Presence of {} {} lapsed", kind.as_str(), id);
+                    self.cache_service.remove_presence(kind, &id).await?;
+                }
+            }
+        }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.connected_at));
+        Ok(entries)
+    }
+
+    async fn is_current(&self, entry: &PresenceEntry) -> Result<bool, AppError> {
+        let stored = self.cache_service.get_presence(entry.kind, &entry.id).await?;
+        Ok(stored.is_none_or(|stored| stored.connection_id == entry.connection_id))
+    }
+
+    // Drivers share the last-seen record location updates keep
+    async fn record_last_seen(&self, entry: &PresenceEntry, at: DateTime<Utc>) -> Result<(), AppError> {
+        if entry.kind != PresenceKind::Driver {
+            return Ok(());
+        }
+        let driver_id = DriverId::parse(&entry.id)?;
+        self.cache_service.cache_driver_last_seen(&[(driver_id, at)]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::cache_service::CacheConfig;
+
+    #[tokio::test]
+    async fn test_replaced_connections_do_not_clear_presence() {
+        let cache_service = Arc::new(CacheService::new_memory(CacheConfig::default()));
+        let presence = PresenceService::new(cache_service.clone(), PresenceConfig::default());
+        let driver_id = DriverId::generate();
+
+        let first = presence.connected(PresenceKind::Driver, driver_id.as_str()).await.unwrap();
+        let second = presence.connected(PresenceKind::Driver, driver_id.as_str()).await.unwrap();
+        presence.connected(PresenceKind::Customer, "customer-1").await.unwrap();
+
+        // The first socket closing late must not take the driver offline
+        presence.disconnected(&first).await.unwrap();
+        let snapshot = presence.snapshot().await.unwrap();
+        assert_eq!(snapshot.drivers, vec![second.clone()]);
+        assert_eq!(snapshot.customers.len(), 1);
+        assert!(cache_service.get_driver_last_seen(&driver_id).await.unwrap().is_some());
+
+        presence.disconnected(&second).await.unwrap();
+        assert!(presence.present(PresenceKind::Driver).await.unwrap().is_empty());
+        assert!(cache_service.get_online_driver_ids().await.unwrap().is_empty());
+
+        // Entries whose instance stopped refreshing them are swept from the index
+        let lapsing = PresenceService::new(cache_service.clone(), PresenceConfig { entry_ttl_seconds: 0, ..Default::default() });
+        lapsing.connected(PresenceKind::Customer, "customer-2").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let customers = presence.present(PresenceKind::Customer).await.unwrap();
+        assert_eq!(customers.iter().map(|entry| entry.id.as_str()).collect::<Vec<_>>(), vec!["customer-1"]);
+        assert_eq!(cache_service.get_present_ids(PresenceKind::Customer).await.unwrap(), vec!["customer-1".to_string()]);
+    }
+}
@@ -0,0 +1,168 @@
+// src/services/quota_service.rs
+// Daily and monthly quotas on merchant API keys. An action is checked against every quota
+// on its metric before it happens and counted once it has, so a failed booking doesn't use
+// up the allowance. The merchant is warned once per period when a quota passes 80%; at the
+// limit, requests are refused with `quota_exceeded` until the period resets.
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        api_key::ApiKey,
+        quota::{ApiKeyUsage, QuotaLimit, QuotaMetric, QuotaUsage, QUOTA_WARNING_RATIO},
+    },
+    services::{
+        cache_service::CacheService,
+        messaging_service::{NotificationMessage, NotificationService},
+    },
+};
+
+// Counters outlive their period by a day, so usage can still be read just after a reset
+const COUNTER_GRACE_SECONDS: i64 = 86400;
+
+pub struct QuotaService {
+    cache_service: Arc<CacheService>,
+    notification_service: Arc<dyn NotificationService>,
+}
+
+impl QuotaService {
+    pub fn new(cache_service: Arc<CacheService>, notification_service: Arc<dyn NotificationService>) -> Self {
+        Self { cache_service, notification_service }
+    }
+
+    /// Refuses the action if any quota on `metric` is already used up
+    pub async fn check(&self, api_key: &ApiKey, metric: QuotaMetric) -> Result<(), AppError> {
+        self.check_room(api_key, metric, 1).await
+    }
+
+    /// Refuses a batch of `count` actions unless every quota on `metric` has room for all of them
+    pub async fn check_room(&self, api_key: &ApiKey, metric: QuotaMetric, count: u64) -> Result<(), AppError> {
+        let now = Utc::now();
+        for quota in api_key.quotas.iter().filter(|quota| quota.metric == metric) {
+            let used = self.cache_service.get_api_key_quota_usage(api_key.quota_id(), metric, &quota.period.window(now)).await?;
+            if used.saturating_add(count) > quota.limit {
+                tracing::info!("API key {} is out of its {} {} quota", api_key.id, quota.period, metric);
+                return Err(AppError::QuotaExceeded {
+                    metric: metric.to_string(),
+                    limit: quota.limit,
+                    resets_at: quota.period.resets_at(now),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Counts one action that went through
+    pub async fn record(&self, api_key: &ApiKey, metric: QuotaMetric) -> Result<(), AppError> {
+        let now = Utc::now();
+        for quota in api_key.quotas.iter().filter(|quota| quota.metric == metric) {
+            let window = quota.period.window(now);
+            let ttl = ttl_seconds(quota, now);
+            let used = self.cache_service.count_api_key_quota_usage(api_key.quota_id(), metric, &window, ttl).await?;
+            if used as f64 >= quota.limit as f64 * QUOTA_WARNING_RATIO
+                && self.cache_service.claim_api_key_quota_warning(api_key.quota_id(), metric, &window, ttl).await?
+            {
+                self.warn(api_key, quota, used, now).await;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn usage(&self, api_key: &ApiKey) -> Result<ApiKeyUsage, AppError> {
+        let now = Utc::now();
+        let mut quotas = Vec::with_capacity(api_key.quotas.len());
+        for quota in &api_key.quotas {
+            let used = self.cache_service.get_api_key_quota_usage(api_key.quota_id(), quota.metric, &quota.period.window(now)).await?;
+            quotas.push(QuotaUsage {
+                metric: quota.metric,
+                period: quota.period,
+                limit: quota.limit,
+                used,
+                remaining: quota.limit.saturating_sub(used),
+                resets_at: quota.period.resets_at(now),
+            });
+        }
+        Ok(ApiKeyUsage {
+            key_id: api_key.id.clone(),
+            quotas,
+        })
+    }
+
+    // The warning is a courtesy; failing to send it doesn't fail the action
+    async fn warn(&self, api_key: &ApiKey, quota: &QuotaLimit, used: u64, now: DateTime<Utc>) {
+        tracing::info!("API key {} has used {} of {} {} {}", api_key.id, used, quota.limit, quota.period, quota.metric);
+        let message = NotificationMessage::new(
+            "⚠️ API quota almost used up",
+            &format!(
+                "Your key \"{}\" has used {} of its {} {} quota of {}. It resets on {}.",
+                api_key.name, used, quota.period, quota.metric, quota.limit, quota.period.resets_at(now).format("%Y-%m-%d"),
+            ),
+        ).with_data(json!({
+            "type": "quota_warning",
+            "key_id": api_key.id,
+            "metric": quota.metric,
+            "period": quota.period,
+            "used": used,
+            "limit": quota.limit,
+        }));
+        if let Err(e) = self.notification_service.send_to_user(&api_key.merchant_id, message).await {
+            tracing::warn!("Failed to warn merchant {} about quota on key {}: {}", api_key.merchant_id, api_key.id, e);
+        }
+    }
+}
+
+fn ttl_seconds(quota: &QuotaLimit, now: DateTime<Utc>) -> u64 {
+    ((quota.period.resets_at(now) - now).num_seconds() + COUNTER_GRACE_SECONDS).max(1) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker, messaging::RecordingNotificationService},
+        models::{api_key::{ApiScope, CreateApiKeyRequest}, quota::QuotaPeriod, user::UserType},
+    };
+
+    #[tokio::test]
+    async fn test_quota_warns_at_eighty_percent_and_refuses_past_the_limit() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let notifications = Arc::new(RecordingNotificationService::new());
+        let quotas = QuotaService::new(state.cache_service.clone(), notifications.clone());
+
+        let merchant = Faker::seeded(73).user(UserType::Business);
+        state.cache_service.cache_user(&merchant).await.unwrap();
+        let issued = state.api_key_service.issue_key(CreateApiKeyRequest {
+            merchant_id: merchant.id.clone(),
+            name: "Storefront".to_string(),
+            scopes: vec![ApiScope::CreateJobs],
+            rate_limit_per_minute: None,
+            quotas: vec![QuotaLimit { metric: QuotaMetric::JobsCreated, period: QuotaPeriod::Daily, limit: 5 }],
+        }).await.unwrap();
+        let api_key = state.api_key_service.get_key(&issued.key.id).await.unwrap();
+
+        for _ in 0..4 {
+            quotas.check(&api_key, QuotaMetric::JobsCreated).await.unwrap();
+            quotas.record(&api_key, QuotaMetric::JobsCreated).await.unwrap();
+        }
+        assert_eq!(notifications.of_kind("quota_warning").len(), 1, "warned once on reaching 4 of 5");
+        quotas.check(&api_key, QuotaMetric::SmsSent).await.unwrap();
+
+        quotas.record(&api_key, QuotaMetric::JobsCreated).await.unwrap();
+        assert_eq!(notifications.of_kind("quota_warning").len(), 1);
+        let error = quotas.check(&api_key, QuotaMetric::JobsCreated).await.unwrap_err();
+        assert!(matches!(error, AppError::QuotaExceeded { limit: 5, .. }));
+        assert!(error.retry_after_seconds().is_some_and(|seconds| seconds <= 86400));
+
+        // A rotated key carries on with the same usage
+        let rotated = state.api_key_service.rotate_key(&api_key.id).await.unwrap();
+        let rotated = state.api_key_service.get_key(&rotated.key.id).await.unwrap();
+        assert!(quotas.check(&rotated, QuotaMetric::JobsCreated).await.is_err());
+        let usage = quotas.usage(&rotated).await.unwrap();
+        assert_eq!((usage.quotas[0].used, usage.quotas[0].remaining), (5, 0));
+    }
+}
@@ -0,0 +1,106 @@
+// src/services/realtime.rs
+// Frame encoding on the driver channels. JSON stays the default; drivers paying for every
+// kilobyte can ask for CBOR instead, with the `sparrow.cbor.v1` WebSocket subprotocol or
+// their fleet's MQTT setting. Both carry the same serde shapes (`DriverSocketEvent` down,
+// `DriverSocketReply` up), so the Rust types are the schema and the two never drift apart.
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::errors::SparrowError as AppError;
+
+pub const JSON_SUBPROTOCOL: &str = "sparrow.json.v1";
+pub const CBOR_SUBPROTOCOL: &str = "sparrow.cbor.v1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameFormat {
+    #[default]
+    Json,
+    Cbor,
+}
+
+impl FrameFormat {
+    pub fn subprotocol(self) -> &'static str {
+        match self {
+            FrameFormat::Json => JSON_SUBPROTOCOL,
+            FrameFormat::Cbor => CBOR_SUBPROTOCOL,
+        }
+    }
+
+    /// Format of the subprotocol a socket agreed on; JSON when it named none
+    pub fn from_subprotocol(subprotocol: Option<&str>) -> Self {
+        match subprotocol {
+            Some(CBOR_SUBPROTOCOL) => FrameFormat::Cbor,
+            _ => FrameFormat::Json,
+        }
+    }
+
+    // A CBOR map never starts with `{` or whitespace, so MQTT payloads can be told apart
+    pub fn sniff(bytes: &[u8]) -> Self {
+        match bytes.iter().find(|byte| !byte.is_ascii_whitespace()) {
+            Some(b'{') | Some(b'[') => FrameFormat::Json,
+            Some(_) => FrameFormat::Cbor,
+            None => FrameFormat::Json,
+        }
+    }
+
+    pub fn encode<T: Serialize>(self, value: &T) -> Result<Vec<u8>, AppError> {
+        match self {
+            FrameFormat::Json => serde_json::to_vec(value).map_err(|e| AppError::JsonSerialization(e.to_string())),
+            FrameFormat::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(value, &mut bytes).map_err(|e| AppError::InvalidFormat(e.to_string()))?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    pub fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T, AppError> {
+        match self {
+            FrameFormat::Json => serde_json::from_slice(bytes).map_err(|e| AppError::JsonParsing(e.to_string())),
+            FrameFormat::Cbor => ciborium::from_reader(bytes).map_err(|e| AppError::InvalidFormat(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    use crate::models::{
+        dispatch::{DriverSocketEvent, DriverSocketReply},
+        ids::JobId,
+        job::LocationUpdate,
+    };
+
+    #[test]
+    fn test_frames_round_trip_in_both_formats() {
+        let offer = DriverSocketEvent::JobOffer {
+            job_id: JobId::generate(),
+            expires_at: Utc::now(),
+            offer: serde_json::json!({ "type": "job_offer", "amount": 42.5 }),
+        };
+        let locations = DriverSocketReply::Locations {
+            locations: vec![LocationUpdate {
+                latitude: 5.5565,
+                longitude: -0.1825,
+                heading: Some(90.0),
+                speed: Some(8.5),
+                is_mock: false,
+                accuracy: Some(5.0),
+                timestamp: Utc::now(),
+            }],
+        };
+
+        for format in [FrameFormat::Json, FrameFormat::Cbor] {
+            let bytes = format.encode(&offer).unwrap();
+            assert_eq!(FrameFormat::sniff(&bytes), format);
+            assert_eq!(format.decode::<DriverSocketEvent>(&bytes).unwrap(), offer);
+            let bytes = format.encode(&locations).unwrap();
+            assert_eq!(format.decode::<DriverSocketReply>(&bytes).unwrap(), locations);
+        }
+        assert!(FrameFormat::Cbor.encode(&locations).unwrap().len() < FrameFormat::Json.encode(&locations).unwrap().len());
+        assert_eq!(FrameFormat::from_subprotocol(Some(CBOR_SUBPROTOCOL)), FrameFormat::Cbor);
+        assert_eq!(FrameFormat::from_subprotocol(None), FrameFormat::Json);
+    }
+}
@@ -0,0 +1,178 @@
+// src/services/realtime_bus.rs
+// Fan-out of realtime events between instances. Everything meant for a live socket is
+// published to a Redis channel keyed by who it is for (`driver:<id>`), and every instance
+// hands what it hears to the sockets it holds, so an event raised on one instance reaches
+// a driver connected to another. Without Redis, deliveries stay local.
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::ids::{DriverId, UserId},
+    services::tenant_service::current_tenant_id,
+};
+
+#[derive(Debug, Clone)]
+pub struct RealtimeBusConfig {
+    pub channel_prefix: String,
+    pub reconnect_delay_seconds: u64,
+}
+
+impl Default for RealtimeBusConfig {
+    fn default() -> Self {
+        Self {
+            channel_prefix: "realtime".to_string(),
+            reconnect_delay_seconds: 5,
+        }
+    }
+}
+
+struct Subscriber {
+    id: u64,
+    sender: mpsc::UnboundedSender<Vec<u8>>,
+}
+
+pub struct RealtimeBus {
+    redis: OnceLock<redis::Client>, // Attached by the server; tests and tools run without it
+    subscribers: Mutex<HashMap<String, Vec<Subscriber>>>, // By full channel name, this instance only
+    next_subscriber_id: AtomicU64,
+    config: RealtimeBusConfig,
+}
+
+impl RealtimeBus {
+    pub fn new(config: RealtimeBusConfig) -> Self {
+        Self {
+            redis: OnceLock::new(),
+            subscribers: Mutex::new(HashMap::new()),
+            next_subscriber_id: AtomicU64::new(1),
+            config,
+        }
+    }
+
+    pub fn attach_redis(&self, client: redis::Client) {
+        if self.redis.set(client).is_err() {
+            tracing::warn!("Realtime bus already attached to Redis");
+        }
+    }
+
+    // Channels are namespaced by tenant, like cache keys
+    fn channel(&self, topic: &str) -> String {
+        format!("{}:{}:{}", self.config.channel_prefix, current_tenant_id(), topic)
+    }
+
+    /// Receive what is published to `topic` in the current tenant, on any instance.
+    /// Returns the subscription's ID, needed to unsubscribe.
+    pub fn subscribe(&self, topic: &str) -> (u64, mpsc::UnboundedReceiver<Vec<u8>>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let id = self.next_subscriber_id.fetch_add(1, Ordering::Relaxed);
+        self.subscribers.lock().unwrap()
+            .entry(self.channel(topic))
+            .or_default()
+            .push(Subscriber { id, sender });
+        (id, receiver)
+    }
+
+    // Closes the subscription's receiver
+    pub fn unsubscribe(&self, topic: &str, subscription_id: u64) {
+        let channel = self.channel(topic);
+        let mut subscribers = self.subscribers.lock().unwrap();
+        if let Some(listening) = subscribers.get_mut(&channel) {
+            listening.retain(|subscriber| subscriber.id != subscription_id);
+            if listening.is_empty() {
+                subscribers.remove(&channel);
+            }
+        }
+    }
+
+    pub async fn publish(&self, topic: &str, payload: Vec<u8>) -> Result<(), AppError> {
+        let channel = self.channel(topic);
+        let Some(client) = self.redis.get() else {
+            self.deliver(&channel, &payload);
+            return Ok(());
+        };
+        // Comes back through `run`, to this instance's subscribers as to everyone else's
+        let mut connection = client.get_async_connection().await?;
+        let _: i64 = redis::cmd("PUBLISH")
+            .arg(&channel)
+            .arg(payload)
+            .query_async(&mut connection)
+            .await?;
+        Ok(())
+    }
+
+    fn deliver(&self, channel: &str, payload: &[u8]) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        if let Some(listening) = subscribers.get_mut(channel) {
+            listening.retain(|subscriber| subscriber.sender.send(payload.to_vec()).is_ok());
+            if listening.is_empty() {
+                subscribers.remove(channel);
+            }
+        }
+    }
+
+    /// Listen on Redis for the life of the process, reconnecting whenever the link drops.
+    /// One pattern subscription covers every channel; filtering happens locally.
+    pub async fn run(self: Arc<Self>) {
+        let Some(client) = self.redis.get() else {
+            return;
+        };
+        let pattern = format!("{}:*", self.config.channel_prefix);
+        loop {
+            match client.get_async_connection().await {
+                Ok(connection) => {
+                    let mut pubsub = connection.into_pubsub();
+                    match pubsub.psubscribe(&pattern).await {
+                        Ok(()) => {
+                            tracing::info!("Listening for realtime events on {}", pattern);
+                            let mut messages = pubsub.on_message();
+                            while let Some(message) = messages.next().await {
+                                self.deliver(message.get_channel_name(), message.get_payload_bytes());
+                            }
+                            tracing::warn!("Realtime event subscription ended");
+                        }
+                        Err(e) => tracing::error!("Failed to subscribe to {}: {}", pattern, e),
+                    }
+                }
+                Err(e) => tracing::warn!("Realtime bus failed to reach Redis: {}", e),
+            }
+            tokio::time::sleep(Duration::from_secs(self.config.reconnect_delay_seconds)).await;
+        }
+    }
+}
+
+pub fn driver_topic(driver_id: &DriverId) -> String {
+    format!("driver:{}", driver_id)
+}
+
+pub fn user_topic(user_id: &UserId) -> String {
+    format!("user:{}", user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::tenant_service::with_tenant;
+
+    #[tokio::test]
+    async fn test_local_delivery_is_scoped_by_topic_and_tenant() {
+        let bus = RealtimeBus::new(RealtimeBusConfig::default());
+        let driver_id = DriverId::generate();
+        let topic = driver_topic(&driver_id);
+
+        let (id, mut receiver) = bus.subscribe(&topic);
+        let (_, mut other_tenant) = with_tenant("acme".to_string(), async { bus.subscribe(&topic) }).await;
+        bus.publish(&topic, b"offer".to_vec()).await.unwrap();
+        bus.publish(&driver_topic(&DriverId::generate()), b"elsewhere".to_vec()).await.unwrap();
+        assert_eq!(receiver.try_recv().unwrap(), b"offer".to_vec());
+        assert!(receiver.try_recv().is_err());
+        assert!(other_tenant.try_recv().is_err());
+
+        bus.unsubscribe(&topic, id);
+        assert!(receiver.recv().await.is_none());
+    }
+}
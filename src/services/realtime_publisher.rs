@@ -0,0 +1,192 @@
+// src/services/realtime_publisher.rs
+// Where live updates for app users go. Deployments pick a provider in config: our own
+// WebSockets fed through the realtime bus, Ably's managed channels, or a mock that only logs.
+// Services publish to a user or driver and never see which one is behind it.
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        dispatch::DriverSocketEvent,
+        ids::{DriverId, UserId},
+    },
+    services::realtime_bus::{driver_topic, user_topic, RealtimeBus},
+};
+#[cfg(feature = "realtime-ably")]
+use crate::services::ably_auth::AblyAuthService;
+
+#[cfg(feature = "realtime-ably")]
+const ABLY_REST_URL: &str = "https://rest.ably.io";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum RealtimeProvider {
+    #[default]
+    SelfHosted, // Redis pub/sub to the app's own WebSockets
+    Ably,       // Ably channels, published over REST with the configured API key
+    Mock,       // Logged, never delivered
+}
+
+impl RealtimeProvider {
+    /// `REALTIME_PROVIDER`: `self_hosted` (the default), `ably` or `mock`
+    pub fn from_env() -> Self {
+        match std::env::var("REALTIME_PROVIDER").ok().as_deref().map(str::trim) {
+            None | Some("") | Some("self_hosted") => RealtimeProvider::SelfHosted,
+            Some("ably") => RealtimeProvider::Ably,
+            Some("mock") => RealtimeProvider::Mock,
+            Some(other) => {
+                tracing::warn!("Unknown REALTIME_PROVIDER {:?}, using self-hosted sockets", other);
+                RealtimeProvider::SelfHosted
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RealtimeChannel {
+    User(UserId),
+    Driver(DriverId),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RealtimeMessage {
+    pub name: String, // e.g. "job_status"
+    pub data: serde_json::Value,
+}
+
+#[async_trait]
+pub trait RealtimePublisher: Send + Sync {
+    /// Fire-and-forget: nobody listening is not an error
+    async fn publish(&self, channel: &RealtimeChannel, message: RealtimeMessage) -> Result<(), AppError>;
+}
+
+// Self-hosted: the bus carries the message to whichever instance holds the socket
+pub struct SocketPublisher {
+    bus: Arc<RealtimeBus>,
+}
+
+impl SocketPublisher {
+    pub fn new(bus: Arc<RealtimeBus>) -> Self {
+        Self { bus }
+    }
+}
+
+#[async_trait]
+impl RealtimePublisher for SocketPublisher {
+    async fn publish(&self, channel: &RealtimeChannel, message: RealtimeMessage) -> Result<(), AppError> {
+        let encoded = match channel {
+            RealtimeChannel::User(user_id) => serde_json::to_vec(&message).map(|payload| (user_topic(user_id), payload)),
+            // Driver sockets only carry their own event type
+            RealtimeChannel::Driver(driver_id) => {
+                let event = DriverSocketEvent::Message { name: message.name, data: message.data };
+                serde_json::to_vec(&event).map(|payload| (driver_topic(driver_id), payload))
+            }
+        };
+        let (topic, payload) = encoded.map_err(|e| AppError::JsonSerialization(e.to_string()))?;
+        self.bus.publish(&topic, payload).await
+    }
+}
+
+// Managed: one REST publish per message, to the channels clients get tokens for
+#[cfg(feature = "realtime-ably")]
+pub struct AblyPublisher {
+    client: reqwest::Client,
+    key_name: String,
+    key_secret: String,
+    channels: Arc<AblyAuthService>,
+}
+
+#[cfg(feature = "realtime-ably")]
+impl AblyPublisher {
+    /// None unless `api_key` looks like `keyName:keySecret`
+    pub fn new(api_key: &str, channels: Arc<AblyAuthService>) -> Option<Self> {
+        let (key_name, key_secret) = api_key.split_once(':').filter(|(name, secret)| name.contains('.') && !secret.is_empty())?;
+        Some(Self {
+            client: reqwest::Client::new(),
+            key_name: key_name.to_string(),
+            key_secret: key_secret.to_string(),
+            channels,
+        })
+    }
+}
+
+#[cfg(feature = "realtime-ably")]
+#[async_trait]
+impl RealtimePublisher for AblyPublisher {
+    async fn publish(&self, channel: &RealtimeChannel, message: RealtimeMessage) -> Result<(), AppError> {
+        let channel = match channel {
+            RealtimeChannel::User(user_id) => self.channels.user_channel(user_id),
+            RealtimeChannel::Driver(driver_id) => self.channels.driver_channel(driver_id),
+        };
+        let mut url = reqwest::Url::parse(ABLY_REST_URL).map_err(|e| AppError::InvalidUrl(e.to_string()))?;
+        url.path_segments_mut()
+            .map_err(|_| AppError::InvalidUrl(ABLY_REST_URL.to_string()))?
+            .extend(["channels", channel.as_str(), "messages"]);
+
+        let response = self.client.post(url)
+            .basic_auth(&self.key_name, Some(&self.key_secret))
+            .json(&message)
+            .send()
+            .await
+            .map_err(|e| AppError::HttpClient(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(AppError::HttpClient(format!("Ably rejected publish to {}: {}", channel, response.status())));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct MockRealtimePublisher;
+
+#[async_trait]
+impl RealtimePublisher for MockRealtimePublisher {
+    async fn publish(&self, channel: &RealtimeChannel, message: RealtimeMessage) -> Result<(), AppError> {
+        tracing::info!("[MOCK] Would publish {} to {:?}", message.name, channel);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::user::UserType,
+        services::{job_service::JobOperations, user_service::UserOperations},
+    };
+
+    #[tokio::test]
+    async fn test_job_updates_reach_self_hosted_sockets() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(53);
+
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let job = state.job_service.create_job(faker.job_request(&customer.id)).await.unwrap();
+        let mut driver = faker.driver();
+        driver.status = crate::models::driver::DriverStatus::Online;
+        state.cache_service.cache_driver(&driver).await.unwrap();
+
+        let (_, mut customer_socket) = state.realtime_bus.subscribe(&user_topic(&customer.id));
+        let (_, mut driver_socket) = state.realtime_bus.subscribe(&driver_topic(&driver.id));
+        state.job_service.assign_driver_to_job(&job.id, &driver.id).await.unwrap();
+
+        let message: RealtimeMessage = serde_json::from_slice(&customer_socket.try_recv().unwrap()).unwrap();
+        assert_eq!(message.name, "job_status");
+        assert_eq!(message.data["job_id"], job.id.to_string());
+        assert_eq!(message.data["status"], "DriverAssigned");
+
+        // Drivers get the same message wrapped in their socket's event type
+        let publisher = SocketPublisher::new(state.realtime_bus.clone());
+        publisher.publish(&RealtimeChannel::Driver(driver.id.clone()), message.clone()).await.unwrap();
+        let event: DriverSocketEvent = serde_json::from_slice(&driver_socket.try_recv().unwrap()).unwrap();
+        assert_eq!(event, DriverSocketEvent::Message { name: message.name, data: message.data });
+
+        #[cfg(feature = "realtime-ably")]
+        assert!(AblyPublisher::new("not-a-key", state.ably_auth.clone()).is_none());
+    }
+}
@@ -0,0 +1,271 @@
+// src/services/reconciliation.rs
+// Matches the payment providers' settlement reports against the ledger by merchant
+// reference. Anything that doesn't line up goes into a queue for finance to review and
+// resolve, optionally with a correcting ledger entry.
+use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        ledger::{CreateLedgerEntryRequest, LedgerAccount, LedgerEntry, PlatformAccount},
+        money::Money,
+        reconciliation::{
+            Discrepancy, DiscrepancyKind, DiscrepancyResolution, DiscrepancyStatus, ReconciliationRun,
+            ResolveDiscrepancyRequest, SettlementRecord,
+        },
+    },
+    services::{cache_service::CacheService, ledger::LedgerService},
+    utils::id_generator::{IdGenerator, IdType},
+};
+
+#[async_trait]
+pub trait SettlementProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// Everything the provider settled to us on `day` (UTC)
+    async fn settlements(&self, day: NaiveDate) -> Result<Vec<SettlementRecord>, AppError>;
+}
+
+pub struct ReconciliationService {
+    cache_service: Arc<CacheService>,
+    ledger: Arc<LedgerService>,
+    providers: Vec<Arc<dyn SettlementProvider>>,
+}
+
+impl ReconciliationService {
+    pub fn new(cache_service: Arc<CacheService>, ledger: Arc<LedgerService>, providers: Vec<Arc<dyn SettlementProvider>>) -> Self {
+        Self { cache_service, ledger, providers }
+    }
+
+    pub fn has_providers(&self) -> bool {
+        !self.providers.is_empty()
+    }
+
+    /// Compare `day`'s settlements with the ledger and queue anything that doesn't match.
+    /// Safe to run again: a discrepancy already queued is not flagged twice.
+    pub async fn reconcile_day(&self, day: NaiveDate) -> Result<ReconciliationRun, AppError> {
+        let mut settlements = Vec::new();
+        for provider in &self.providers {
+            settlements.extend(provider.settlements(day).await?);
+        }
+
+        let journal = self.cache_service.get_ledger_journal().await?;
+        let by_reference: HashMap<&str, &LedgerEntry> = journal.iter()
+            .filter_map(|entry| entry.provider_reference.as_deref().map(|reference| (reference, entry)))
+            .collect();
+        let cash = LedgerAccount::Platform(PlatformAccount::Cash);
+
+        let mut found = Vec::new();
+        let mut matched = 0;
+        for record in &settlements {
+            let discrepancy = |kind, entry: Option<&LedgerEntry>, ledger_amount: Option<Money>| Discrepancy {
+                id: IdGenerator::generate(IdType::Discrepancy),
+                day,
+                kind,
+                reference: record.reference.clone(),
+                provider: Some(record.provider.clone()),
+                ledger_entry_id: entry.map(|entry| entry.id.clone()),
+                ledger_amount,
+                provider_amount: Some(record.amount),
+                status: DiscrepancyStatus::Open,
+                resolution: None,
+                flagged_at: Utc::now(),
+            };
+            match by_reference.get(record.reference.as_str()) {
+                None => found.push(discrepancy(DiscrepancyKind::MissingFromLedger, None, None)),
+                Some(entry) => {
+                    let recorded = entry.net_for(&cash);
+                    if recorded == Some(record.amount) {
+                        matched += 1;
+                    } else {
+                        found.push(discrepancy(DiscrepancyKind::AmountMismatch, Some(entry), recorded));
+                    }
+                }
+            }
+        }
+
+        // Payments we took that day that no provider has owned up to
+        let reported: HashSet<&str> = settlements.iter().map(|record| record.reference.as_str()).collect();
+        for entry in journal.iter().filter(|entry| entry.posted_at.date_naive() == day) {
+            let (Some(reference), Some(amount)) = (&entry.provider_reference, entry.net_for(&cash)) else {
+                continue;
+            };
+            if amount.is_zero() || reported.contains(reference.as_str()) {
+                continue;
+            }
+            found.push(Discrepancy {
+                id: IdGenerator::generate(IdType::Discrepancy),
+                day,
+                kind: DiscrepancyKind::MissingFromProvider,
+                reference: reference.clone(),
+                provider: None,
+                ledger_entry_id: Some(entry.id.clone()),
+                ledger_amount: Some(amount),
+                provider_amount: None,
+                status: DiscrepancyStatus::Open,
+                resolution: None,
+                flagged_at: Utc::now(),
+            });
+        }
+
+        let mut queue = self.cache_service.get_discrepancies().await?;
+        let mut flagged = 0;
+        for discrepancy in found {
+            let queued = queue.iter().any(|existing| existing.kind == discrepancy.kind && existing.reference == discrepancy.reference);
+            if !queued {
+                tracing::warn!("Reconciliation of {} flagged {:?} for {}", day, discrepancy.kind, discrepancy.reference);
+                queue.push(discrepancy);
+                flagged += 1;
+            }
+        }
+        if flagged > 0 {
+            self.cache_service.cache_discrepancies(&queue).await?;
+        }
+
+        Ok(ReconciliationRun {
+            day,
+            settlements: settlements.len(),
+            matched,
+            flagged,
+        })
+    }
+
+    /// The queue, oldest first, optionally only those with `status`
+    pub async fn discrepancies(&self, status: Option<DiscrepancyStatus>) -> Result<Vec<Discrepancy>, AppError> {
+        Ok(self.cache_service.get_discrepancies().await?
+            .into_iter()
+            .filter(|discrepancy| status.is_none_or(|status| discrepancy.status == status))
+            .collect())
+    }
+
+    pub async fn resolve(&self, discrepancy_id: &str, request: ResolveDiscrepancyRequest) -> Result<Discrepancy, AppError> {
+        let note = request.note.trim();
+        if note.is_empty() {
+            return Err(AppError::validation_error("note", "Must not be empty"));
+        }
+        let mut queue = self.cache_service.get_discrepancies().await?;
+        let discrepancy = queue.iter_mut()
+            .find(|discrepancy| discrepancy.id == discrepancy_id)
+            .ok_or_else(|| AppError::NotFound(format!("Discrepancy {} not found", discrepancy_id)))?;
+        if discrepancy.status == DiscrepancyStatus::Resolved {
+            return Err(AppError::Conflict(format!("Discrepancy {} is already resolved", discrepancy_id)));
+        }
+
+        let adjustment_entry_id = if request.adjustment.is_empty() {
+            None
+        } else {
+            let entry = self.ledger.post(CreateLedgerEntryRequest {
+                posting_key: format!("reconciliation:{}", discrepancy.id),
+                description: format!("Reconciliation adjustment for {}", discrepancy.reference),
+                job_id: None,
+                provider_reference: None,
+                postings: request.adjustment,
+            }).await?;
+            Some(entry.id)
+        };
+        discrepancy.status = DiscrepancyStatus::Resolved;
+        discrepancy.resolution = Some(DiscrepancyResolution {
+            note: note.to_string(),
+            adjustment_entry_id,
+            resolved_at: Utc::now(),
+        });
+        let resolved = discrepancy.clone();
+        self.cache_service.cache_discrepancies(&queue).await?;
+
+        tracing::info!("Resolved reconciliation discrepancy {} for {}", resolved.id, resolved.reference);
+        Ok(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::{ids::UserId, ledger::Posting, money::Currency},
+    };
+
+    struct StaticProvider(Vec<SettlementRecord>);
+
+    #[async_trait]
+    impl SettlementProvider for StaticProvider {
+        fn name(&self) -> &'static str {
+            "static"
+        }
+
+        async fn settlements(&self, day: NaiveDate) -> Result<Vec<SettlementRecord>, AppError> {
+            Ok(self.0.iter().filter(|record| record.settled_at.date_naive() == day).cloned().collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mismatches_are_queued_once_and_resolved_with_an_adjustment() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(62);
+        let cedis = |amount: f64| Money::from_major(amount, Currency::GHS);
+
+        let mut settle = |total: f64| {
+            let mut job = faker.job(&UserId::generate());
+            job.pricing.total = cedis(total);
+            job.pricing.tax = cedis(0.0);
+            job
+        };
+        let (exact, short, unreported) = (settle(40.0), settle(25.0), settle(18.0));
+        for job in [&exact, &short, &unreported] {
+            state.ledger_service.post_job_settlement(job, None).await.unwrap();
+        }
+
+        let now = Utc::now();
+        let record = |reference: String, amount: f64| SettlementRecord {
+            provider: "mtn-momo".to_string(),
+            reference,
+            amount: cedis(amount),
+            settled_at: now,
+        };
+        let provider = StaticProvider(vec![
+            record(exact.id.to_string(), 40.0),
+            record(short.id.to_string(), 24.5),
+            record("job-unknown".to_string(), 12.0),
+        ]);
+        let reconciliation = ReconciliationService::new(
+            state.cache_service.clone(),
+            state.ledger_service.clone(),
+            vec![Arc::new(provider)],
+        );
+
+        let run = reconciliation.reconcile_day(now.date_naive()).await.unwrap();
+        assert_eq!((run.settlements, run.matched, run.flagged), (3, 1, 3));
+        let again = reconciliation.reconcile_day(now.date_naive()).await.unwrap();
+        assert_eq!(again.flagged, 0);
+        assert!(reconciliation.reconcile_day(now.date_naive() - Duration::days(1)).await.unwrap().settlements == 0);
+
+        let open = reconciliation.discrepancies(Some(DiscrepancyStatus::Open)).await.unwrap();
+        let kinds: Vec<DiscrepancyKind> = open.iter().map(|discrepancy| discrepancy.kind).collect();
+        assert!(kinds.contains(&DiscrepancyKind::MissingFromLedger) && kinds.contains(&DiscrepancyKind::MissingFromProvider));
+        let mismatch = open.iter().find(|discrepancy| discrepancy.kind == DiscrepancyKind::AmountMismatch).unwrap();
+        assert_eq!(mismatch.ledger_amount, Some(cedis(25.0)));
+
+        // The provider kept a 0.50 fee; finance writes it off against commission
+        let resolved = reconciliation.resolve(&mismatch.id, ResolveDiscrepancyRequest {
+            note: "Provider fee".to_string(),
+            adjustment: vec![
+                Posting::debit(LedgerAccount::Platform(PlatformAccount::Commission), cedis(0.5)),
+                Posting::credit(LedgerAccount::Platform(PlatformAccount::Cash), cedis(0.5)),
+            ],
+        }).await.unwrap();
+        assert!(resolved.resolution.unwrap().adjustment_entry_id.is_some());
+        let cash = state.ledger_service.balance(&LedgerAccount::Platform(PlatformAccount::Cash), Currency::GHS).await.unwrap();
+        assert_eq!(cash.balance, cedis(82.5));
+        assert!(matches!(
+            reconciliation.resolve(&mismatch.id, ResolveDiscrepancyRequest { note: "Again".to_string(), adjustment: Vec::new() }).await,
+            Err(AppError::Conflict(_))
+        ));
+        assert_eq!(reconciliation.discrepancies(Some(DiscrepancyStatus::Open)).await.unwrap().len(), 2);
+    }
+}
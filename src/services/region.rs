@@ -0,0 +1,177 @@
+// src/services/region.rs
+// The regions this deployment serves and the per-request region context.
+//
+// Like the tenant, the region of a request is held in a task-local, set by the
+// `resolve_region` middleware or, for a new booking, from the country of its pickup.
+// The cache layer namespaces every key under it, so one deployment can run several
+// markets without their drivers, jobs or zones ever mixing.
+use std::future::Future;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        money::Currency,
+        region::{Region, RegionSummary, DEFAULT_REGION_ID},
+        tenant::{PricingConfig, Tenant},
+    },
+};
+
+tokio::task_local! {
+    static CURRENT_REGION: String;
+}
+
+/// Region of the running request or worker pass; the default region outside any scope
+pub fn current_region_id() -> String {
+    CURRENT_REGION
+        .try_with(|region_id| region_id.clone())
+        .unwrap_or_else(|_| DEFAULT_REGION_ID.to_string())
+}
+
+/// Run `future` with `region_id` as the current region
+pub async fn with_region<F: Future>(region_id: String, future: F) -> F::Output {
+    CURRENT_REGION.scope(region_id, future).await
+}
+
+// Every region the deployment serves, each with its own rates and phone numbering
+pub struct RegionRegistry {
+    regions: Vec<Region>,
+}
+
+impl RegionRegistry {
+    pub fn new(regions: Vec<Region>) -> Self {
+        let mut regions = regions;
+        if !regions.iter().any(|region| region.id == DEFAULT_REGION_ID) {
+            regions.extend(Region::builtin(DEFAULT_REGION_ID));
+        }
+        Self { regions }
+    }
+
+    /// The built-in regions `ids` names, served besides Ghana, e.g. ["ng", "ci"]
+    pub fn serving(ids: &[String]) -> Self {
+        let mut regions = Vec::new();
+        for id in ids {
+            match Region::builtin(&id.to_ascii_lowercase()) {
+                Some(region) => regions.push(region),
+                None => tracing::warn!("Ignoring unknown region {}", id),
+            }
+        }
+        Self::new(regions)
+    }
+
+    /// `SPARROW_REGIONS` lists the regions served besides Ghana, e.g. "ng,ci"
+    pub fn ids_from_env() -> Vec<String> {
+        std::env::var("SPARROW_REGIONS").unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub fn get(&self, region_id: &str) -> Option<&Region> {
+        self.regions.iter().find(|region| region.id == region_id)
+    }
+
+    // Region of the running request
+    pub fn current(&self) -> &Region {
+        let region_id = current_region_id();
+        self.get(&region_id)
+            .or_else(|| self.get(DEFAULT_REGION_ID))
+            .expect("the default region is always served")
+    }
+
+    pub fn ids(&self) -> Vec<String> {
+        self.regions.iter().map(|region| region.id.clone()).collect()
+    }
+
+    /// The region a job picked up in `country` belongs to
+    pub fn for_country(&self, country: &str) -> Result<&Region, AppError> {
+        self.regions.iter()
+            .find(|region| region.covers_country(country))
+            .ok_or_else(|| AppError::validation_error("pickup_location", format!("We don't operate in {} yet", country.trim())))
+    }
+
+    pub fn for_dialing_code(&self, dialing_code: &str) -> Result<&Region, AppError> {
+        let dialing_code = dialing_code.trim();
+        self.regions.iter()
+            .find(|region| region.dialing_code == dialing_code)
+            .ok_or_else(|| AppError::validation_error("country_code", format!("We don't operate under {} yet", dialing_code)))
+    }
+
+    pub fn summaries(&self) -> Vec<RegionSummary> {
+        self.regions.iter().map(|region| RegionSummary {
+            id: region.id.clone(),
+            name: region.name.clone(),
+            countries: region.countries.clone(),
+            dialing_code: region.dialing_code.clone(),
+            currency: region.currency,
+            is_home: region.id == DEFAULT_REGION_ID,
+        }).collect()
+    }
+}
+
+/// Rates for a booking in `region`. A named currency must be one the tenant prices in, or the
+/// region's own; otherwise the tenant's rates in the region's currency, falling back to the
+/// region's defaults. Ghana keeps pricing in the tenant's home currency, as it always has.
+pub fn pricing_in<'a>(tenant: &'a Tenant, region: &'a Region, currency: Option<Currency>) -> Result<&'a PricingConfig, AppError> {
+    match currency {
+        Some(currency) if currency == region.currency && region.id != DEFAULT_REGION_ID => {
+            Ok(tenant.pricing_for(Some(currency)).unwrap_or(&region.pricing))
+        }
+        Some(currency) => tenant.pricing_for(Some(currency)),
+        None if region.id == DEFAULT_REGION_ID => tenant.pricing_for(None),
+        None => Ok(tenant.pricing_for(Some(region.currency)).unwrap_or(&region.pricing)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::ids::UserId, services::cache_service::{CacheConfig, CacheService}};
+
+    #[test]
+    fn test_regions_route_countries_phones_and_prices() {
+        let registry = RegionRegistry::new(vec![Region::builtin("ng").unwrap()]);
+        assert_eq!(registry.ids(), vec!["ng".to_string(), "gh".to_string()]);
+        assert_eq!(registry.for_country(" ghana ").unwrap().id, "gh");
+        assert_eq!(registry.for_country("NG").unwrap().id, "ng");
+        assert!(registry.for_country("Kenya").is_err());
+
+        let ghana = registry.get("gh").unwrap();
+        assert_eq!(ghana.normalize_phone("024 123 4567").unwrap(), "+233241234567");
+        assert_eq!(ghana.normalize_phone("+233241234567").unwrap(), "+233241234567");
+        assert!(ghana.normalize_phone("2412345").is_err());
+        let nigeria = registry.for_dialing_code("+234").unwrap();
+        assert_eq!(nigeria.normalize_phone("08031234567").unwrap(), "+2348031234567");
+
+        let tenant = Tenant::default_tenant();
+        assert_eq!(pricing_in(&tenant, ghana, None).unwrap().currency, Currency::GHS);
+        assert_eq!(pricing_in(&tenant, nigeria, None).unwrap().currency.as_str(), "NGN");
+        assert!(pricing_in(&tenant, ghana, Some(nigeria.currency)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_current_region_defaults_outside_scope() {
+        assert_eq!(current_region_id(), DEFAULT_REGION_ID);
+        let inside = with_region("ng".to_string(), async { current_region_id() }).await;
+        assert_eq!(inside, "ng");
+    }
+
+    #[tokio::test]
+    async fn test_region_data_is_isolated_within_a_tenant() {
+        let cache = CacheService::new_memory(CacheConfig::default());
+
+        let (accra_user, lagos_user) = (UserId::generate(), UserId::generate());
+        cache.cache_user_by_email("kofi@example.com", &accra_user).await.unwrap();
+        with_region("ng".to_string(), cache.cache_user_by_email("kofi@example.com", &lagos_user)).await.unwrap();
+
+        let home = cache.get_user_id_by_email("kofi@example.com").await.unwrap();
+        let lagos = with_region("ng".to_string(), cache.get_user_id_by_email("kofi@example.com")).await.unwrap();
+        let nairobi = with_region("ke".to_string(), cache.get_user_id_by_email("kofi@example.com")).await.unwrap();
+
+        assert_eq!(home, Some(accra_user));
+        assert_eq!(lagos, Some(lagos_user));
+        assert_eq!(nairobi, None);
+    }
+}
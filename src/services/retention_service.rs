@@ -0,0 +1,277 @@
+// src/services/retention_service.rs
+// How long each class of job data is kept. Route traces and lifecycle events are deleted
+// once past their policy; the job itself is anonymized rather than deleted, so ledger
+// entries and invoices keep pointing at something. A legal hold keeps all of one job's
+// data until it is released. Administered through `/admin/retention`, with a dry-run
+// report of what the purge worker would do.
+//
+// Jobs are found through the per-day job sets, walked oldest first. Each class keeps a
+// cursor of the last day it was purged through, so a run only looks at days newly past
+// the cutoff; jobs skipped for a hold are caught up when the hold is released.
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        ids::JobId,
+        job::Job,
+        retention::{ClassRetentionReport, DataClass, LegalHold, PlaceLegalHoldRequest, RetentionReport, RetentionSettings, UpdateRetentionSettingsRequest},
+    },
+    services::cache_service::CacheService,
+};
+
+pub struct RetentionService {
+    cache_service: Arc<CacheService>,
+}
+
+impl RetentionService {
+    pub fn new(cache_service: Arc<CacheService>) -> Self {
+        Self { cache_service }
+    }
+
+    /// Stored policies; classes without one use their default
+    pub async fn settings(&self) -> Result<RetentionSettings, AppError> {
+        Ok(self.cache_service.get_retention_settings().await?.unwrap_or_default())
+    }
+
+    pub async fn update_settings(&self, request: UpdateRetentionSettingsRequest) -> Result<RetentionSettings, AppError> {
+        for (index, policy) in request.policies.iter().enumerate() {
+            if policy.retain_days == 0 {
+                return Err(AppError::validation_error(format!("policies[{}].retain_days", index), "Must be at least 1"));
+            }
+            if request.policies[..index].iter().any(|earlier| earlier.class == policy.class) {
+                return Err(AppError::validation_error(format!("policies[{}].class", index), "Class is listed twice"));
+            }
+        }
+
+        let settings = RetentionSettings {
+            policies: request.policies,
+            updated_at: Some(Utc::now()),
+        };
+        self.cache_service.cache_retention_settings(&settings).await?;
+
+        tracing::info!("Updated retention policies ({} classes)", settings.policies.len());
+        Ok(settings)
+    }
+
+    pub async fn holds(&self) -> Result<Vec<LegalHold>, AppError> {
+        let mut holds = Vec::new();
+        for job_id in self.cache_service.get_legal_holds().await? {
+            if let Some(hold) = self.cache_service.get_legal_hold(&job_id).await? {
+                holds.push(hold);
+            }
+        }
+        holds.sort_by_key(|hold| hold.placed_at);
+        Ok(holds)
+    }
+
+    pub async fn place_hold(&self, request: PlaceLegalHoldRequest) -> Result<LegalHold, AppError> {
+        let reason = request.reason.trim();
+        if reason.is_empty() {
+            return Err(AppError::validation_error("reason", "A reason is required"));
+        }
+        if self.cache_service.load_job(&request.job_id).await?.is_none() {
+            return Err(AppError::NotFound(format!("Job {} not found", request.job_id)));
+        }
+        if self.cache_service.get_legal_hold(&request.job_id).await?.is_some() {
+            return Err(AppError::Conflict(format!("Job {} is already on hold", request.job_id)));
+        }
+
+        let hold = LegalHold {
+            job_id: request.job_id,
+            reason: reason.to_string(),
+            placed_at: Utc::now(),
+        };
+        self.cache_service.cache_legal_hold(&hold).await?;
+        tracing::info!("Placed legal hold on job {}: {}", hold.job_id, hold.reason);
+        Ok(hold)
+    }
+
+    /// Lifts the hold and applies whatever the policies would have done meanwhile
+    pub async fn release_hold(&self, job_id: &JobId) -> Result<LegalHold, AppError> {
+        let hold = self.cache_service.get_legal_hold(job_id).await?
+            .ok_or_else(|| AppError::NotFound(format!("No legal hold on job {}", job_id)))?;
+        self.cache_service.delete_legal_hold(job_id).await?;
+
+        if let Some(mut job) = self.cache_service.load_job(job_id).await? {
+            let settings = self.settings().await?;
+            let now = Utc::now();
+            for class in DataClass::ALL {
+                if job.status.is_terminal() && job.created_at < cutoff(&settings, class, now) {
+                    self.purge(class, &mut job, now, false).await?;
+                }
+            }
+        }
+        tracing::info!("Released legal hold on job {}", job_id);
+        Ok(hold)
+    }
+
+    /// Applies every policy as of `now`; with `dry_run` nothing changes and the report says
+    /// what would have
+    pub async fn enforce(&self, now: DateTime<Utc>, dry_run: bool) -> Result<RetentionReport, AppError> {
+        let settings = self.settings().await?;
+        let days = self.cache_service.get_job_days().await?;
+        let mut classes = Vec::new();
+
+        for class in DataClass::ALL {
+            let cutoff = cutoff(&settings, class, now);
+            // Everything created on a day before the cutoff's day is due
+            let cutoff_day = cutoff.date_naive();
+            let cursor = self.cache_service.get_retention_cursor(class).await?;
+            let mut report = ClassRetentionReport {
+                class,
+                action: class.action(),
+                cutoff,
+                affected: 0,
+                held: Vec::new(),
+            };
+            let mut purged_through: Option<NaiveDate> = None;
+            let mut blocked = false;
+
+            for day in days.iter().filter(|day| **day < cutoff_day && cursor.is_none_or(|cursor| **day > cursor)) {
+                let mut day_done = true;
+                for job_id in self.cache_service.get_jobs_for_day(day).await? {
+                    if self.cache_service.get_legal_hold(&job_id).await?.is_some() {
+                        report.held.push(job_id);
+                        continue;
+                    }
+                    let Some(mut job) = self.cache_service.load_job(&job_id).await? else {
+                        continue;
+                    };
+                    // Still open this long after creation means it is stuck; it, and the cursor, wait for a person
+                    if !job.status.is_terminal() {
+                        day_done = false;
+                        continue;
+                    }
+                    if self.purge(class, &mut job, now, dry_run).await? {
+                        report.affected += 1;
+                    }
+                }
+                blocked |= !day_done;
+                if !blocked {
+                    purged_through = Some(*day);
+                }
+            }
+
+            if !dry_run && let Some(day) = purged_through {
+                self.cache_service.set_retention_cursor(class, day).await?;
+            }
+            if !dry_run && report.affected > 0 {
+                tracing::info!("Retention purge: {} {:?} for {} jobs created before {}", class, report.action, report.affected, cutoff_day);
+            }
+            classes.push(report);
+        }
+
+        Ok(RetentionReport {
+            dry_run,
+            generated_at: now,
+            classes,
+        })
+    }
+
+    // Whether the job had any `class` data left to remove
+    async fn purge(&self, class: DataClass, job: &mut Job, now: DateTime<Utc>, dry_run: bool) -> Result<bool, AppError> {
+        match class {
+            DataClass::LocationTraces => {
+                let present = !self.cache_service.get_route_segments(&job.id).await?.is_empty();
+                if present && !dry_run {
+                    self.cache_service.delete_route_segments(&job.id).await?;
+                }
+                Ok(present)
+            }
+            DataClass::JobEvents => {
+                let present = !self.cache_service.get_job_events(&job.id).await?.is_empty();
+                if present && !dry_run {
+                    self.cache_service.delete_job_events(&job.id).await?;
+                }
+                Ok(present)
+            }
+            DataClass::Jobs => {
+                if job.anonymized_at.is_some() {
+                    return Ok(false);
+                }
+                if !dry_run {
+                    job.anonymize(now);
+                    self.cache_service.cache_job(job).await?;
+                }
+                Ok(true)
+            }
+        }
+    }
+}
+
+fn cutoff(settings: &RetentionSettings, class: DataClass, now: DateTime<Utc>) -> DateTime<Utc> {
+    now - Duration::days(settings.policy(class).retain_days as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::{job::{JobEvent, JobEventType, JobStatus, RouteSegment}, retention::RetentionPolicy, user::UserType},
+    };
+
+    #[tokio::test]
+    async fn test_purge_respects_policies_holds_and_dry_runs() {
+        let app = TestApp::new();
+        let cache = app.state.cache_service.clone();
+        let retention = RetentionService::new(cache.clone());
+        let mut faker = Faker::seeded(71);
+        let customer = faker.user(UserType::Customer);
+        let now = Utc::now();
+
+        let mut jobs = Vec::new();
+        for age_days in [40, 40, 5] {
+            let mut job = faker.job(&customer.id);
+            job.status = JobStatus::DeliveryCompleted;
+            job.created_at = now - Duration::days(age_days);
+            cache.cache_job(&job).await.unwrap();
+            cache.add_job_to_day(&job).await.unwrap();
+            cache.append_route_segment(&job.id, &RouteSegment {
+                polyline: "_p~iF~ps|U".to_string(),
+                point_count: 1,
+                started_at: job.created_at,
+                ended_at: job.created_at,
+            }).await.unwrap();
+            cache.append_job_event(&job.id, &JobEvent {
+                event_type: JobEventType::JobCreated,
+                timestamp: job.created_at,
+                location: None,
+                actor: "system".to_string(),
+                notes: None,
+            }).await.unwrap();
+            jobs.push(job);
+        }
+        retention.update_settings(UpdateRetentionSettingsRequest {
+            policies: vec![RetentionPolicy { class: DataClass::Jobs, retain_days: 30 }],
+        }).await.unwrap();
+        retention.place_hold(PlaceLegalHoldRequest { job_id: jobs[1].id.clone(), reason: "Case 114".to_string() }).await.unwrap();
+
+        // Traces and jobs are past 30 days for the two older jobs; events keep their year
+        let preview = retention.enforce(now, true).await.unwrap();
+        let traces = &preview.classes[0];
+        assert_eq!((traces.affected, traces.held.clone()), (1, vec![jobs[1].id.clone()]));
+        assert_eq!(preview.classes[1].affected, 0);
+        assert_eq!(preview.classes[2].affected, 1);
+        assert!(!cache.get_route_segments(&jobs[0].id).await.unwrap().is_empty());
+
+        let report = retention.enforce(now, false).await.unwrap();
+        assert_eq!(report.classes[0].affected, 1);
+        assert!(cache.get_route_segments(&jobs[0].id).await.unwrap().is_empty());
+        assert!(!cache.get_job_events(&jobs[0].id).await.unwrap().is_empty());
+        let anonymized = cache.load_job(&jobs[0].id).await.unwrap().unwrap();
+        assert!(anonymized.anonymized_at.is_some() && anonymized.pickup_location.contact_phone.is_empty());
+        assert!(!cache.get_route_segments(&jobs[2].id).await.unwrap().is_empty());
+        assert_eq!(retention.enforce(now, false).await.unwrap().classes[0].affected, 0);
+
+        // Releasing the hold applies what was skipped
+        retention.release_hold(&jobs[1].id).await.unwrap();
+        assert!(cache.get_route_segments(&jobs[1].id).await.unwrap().is_empty());
+        assert!(cache.load_job(&jobs[1].id).await.unwrap().unwrap().anonymized_at.is_some());
+        assert!(retention.holds().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+// src/services/risk_service.rs
+// Keeps track of drivers whose phones send locations that can't be real. The points
+// themselves are quarantined for ops to look at; every batch that had any counts as a
+// strike, and a driver who collects enough strikes within the window is flagged and
+// ops are paged once. The flag stays until someone in ops clears it.
+use chrono::Utc;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        alert::{Alert, AlertKind},
+        ids::DriverId,
+        risk::{DriverRiskReport, QuarantinedLocation, RiskFlag},
+    },
+    services::{alert_service::AlertService, cache_service::CacheService, tenant_service::current_tenant_id},
+};
+
+#[derive(Debug, Clone)]
+pub struct RiskConfig {
+    pub strikes_to_flag: u32,
+    pub strike_window_hours: u64,
+    pub quarantine_days: u64,
+    pub report_limit: usize, // Quarantined points shown per driver
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            strikes_to_flag: 3,
+            strike_window_hours: 24,
+            quarantine_days: 7,
+            report_limit: 50,
+        }
+    }
+}
+
+pub struct RiskService {
+    cache_service: Arc<CacheService>,
+    alert_service: Arc<AlertService>,
+    config: RiskConfig,
+}
+
+impl RiskService {
+    pub fn new(cache_service: Arc<CacheService>, alert_service: Arc<AlertService>, config: RiskConfig) -> Self {
+        Self { cache_service, alert_service, config }
+    }
+
+    /// Quarantines one batch's spoofed points and counts the strike. Returns the driver's flag, if they have one.
+    pub async fn report_spoofing(&self, driver_id: &DriverId, points: &[QuarantinedLocation]) -> Result<Option<RiskFlag>, AppError> {
+        let Some(last) = points.last() else {
+            return Ok(None);
+        };
+        for point in points {
+            self.cache_service.quarantine_location(driver_id, point, self.config.quarantine_days * 86400).await?;
+        }
+        let strikes = self.cache_service.add_spoof_strike(driver_id, self.config.strike_window_hours * 3600).await?;
+        tracing::warn!("Driver {} sent {} spoofed location(s), strike {}", driver_id, points.len(), strikes);
+
+        let now = Utc::now();
+        if let Some(mut flag) = self.cache_service.get_risk_flag(driver_id).await? {
+            flag.strikes = flag.strikes.max(strikes);
+            flag.last_reason = last.reason;
+            flag.last_strike_at = now;
+            self.cache_service.cache_risk_flag(&flag).await?;
+            return Ok(Some(flag));
+        }
+        if strikes < self.config.strikes_to_flag {
+            return Ok(None);
+        }
+
+        let flag = RiskFlag {
+            driver_id: driver_id.clone(),
+            strikes,
+            last_reason: last.reason,
+            flagged_at: now,
+            last_strike_at: now,
+        };
+        self.cache_service.cache_risk_flag(&flag).await?;
+        self.alert_service.page(&Alert {
+            kind: AlertKind::GpsSpoofing,
+            tenant_id: Some(current_tenant_id()),
+            summary: format!("Driver {} keeps sending spoofed locations", driver_id),
+            value: strikes as f64,
+            threshold: self.config.strikes_to_flag as f64,
+            raised_at: now,
+        }).await;
+        Ok(Some(flag))
+    }
+
+    /// Flagged drivers, most recently flagged first
+    pub async fn flagged_drivers(&self) -> Result<Vec<RiskFlag>, AppError> {
+        let mut flags = Vec::new();
+        for driver_id in self.cache_service.get_flagged_driver_ids().await? {
+            if let Some(flag) = self.cache_service.get_risk_flag(&driver_id).await? {
+                flags.push(flag);
+            }
+        }
+        flags.sort_by_key(|flag| std::cmp::Reverse(flag.flagged_at));
+        Ok(flags)
+    }
+
+    pub async fn driver_report(&self, driver_id: &DriverId) -> Result<DriverRiskReport, AppError> {
+        self.cache_service.get_driver(driver_id).await?
+            .ok_or_else(|| AppError::driver_not_found(driver_id.as_str()))?;
+        Ok(DriverRiskReport {
+            driver_id: driver_id.clone(),
+            strikes: self.cache_service.get_spoof_strikes(driver_id).await?,
+            flag: self.cache_service.get_risk_flag(driver_id).await?,
+            quarantined: self.cache_service.get_quarantined_locations(driver_id, self.config.report_limit).await?,
+        })
+    }
+
+    /// Clears the flag and the strikes behind it; quarantined points stay for the record
+    pub async fn clear_flag(&self, driver_id: &DriverId) -> Result<DriverRiskReport, AppError> {
+        self.cache_service.clear_risk_flag(driver_id).await?;
+        tracing::info!("Cleared risk flag on driver {}", driver_id);
+        self.driver_report(driver_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{mocks::{app::TestApp, fixtures::{Faker, ACCRA}}, models::risk::SpoofReason};
+
+    #[tokio::test]
+    async fn test_a_driver_who_keeps_mocking_their_location_is_flagged_until_cleared() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(112);
+        let driver = faker.driver();
+        state.cache_service.cache_driver(&driver).await.unwrap();
+
+        for strike in 1..=3 {
+            let honest = faker.location_update(ACCRA);
+            let mut mocked = faker.location_update(ACCRA);
+            mocked.is_mock = true;
+            let response = state.location_service.ingest_batch(&driver.id, vec![honest, mocked]).await.unwrap();
+            assert_eq!((response.accepted, response.quarantined), (1, 1));
+
+            let report = state.risk_service.driver_report(&driver.id).await.unwrap();
+            assert_eq!(report.strikes, strike);
+            assert_eq!(report.flag.is_some(), strike == 3);
+        }
+        let flagged = state.risk_service.flagged_drivers().await.unwrap();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].last_reason, SpoofReason::MockLocation);
+
+        let cleared = state.risk_service.clear_flag(&driver.id).await.unwrap();
+        assert!(cleared.flag.is_none() && cleared.strikes == 0);
+        assert_eq!(cleared.quarantined.len(), 3);
+        assert!(state.risk_service.flagged_drivers().await.unwrap().is_empty());
+    }
+}
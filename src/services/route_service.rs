@@ -0,0 +1,311 @@
+// src/services/route_service.rs
+use chrono::Utc;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{ids::{DriverId, JobId}, job::{Job, JobRoute, Location, LocationUpdate, NavigationPlan, NavigationStop, RouteSegment, StopKind}},
+    services::cache_service::CacheService,
+    utils::{geo, polyline},
+};
+
+// Roads wind; straight-line distance is stretched by this much before it is timed
+const ROAD_DETOUR_FACTOR: f64 = 1.3;
+const AVERAGE_SPEED_KMH: f64 = 30.0; // As for job duration estimates
+const GOOGLE_MAPS_DIRECTIONS_URL: &str = "https://www.google.com/maps/dir/?api=1";
+
+pub struct RouteService {
+    cache_service: Arc<CacheService>,
+}
+
+impl RouteService {
+    pub fn new(cache_service: Arc<CacheService>) -> Self {
+        Self { cache_service }
+    }
+
+    // Append points to the route of every job the driver is actively working. Returns those jobs.
+    pub async fn record_driver_points(&self, driver_id: &DriverId, points: &[LocationUpdate]) -> Result<Vec<Job>, AppError> {
+        if points.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut active = Vec::new();
+        for job_id in self.cache_service.get_driver_jobs(driver_id).await? {
+            let Some(job) = self.cache_service.load_job(&job_id).await? else {
+                continue;
+            };
+            if job.status.is_active() {
+                self.record_points(&job_id, points).await?;
+                active.push(job);
+            }
+        }
+
+        Ok(active)
+    }
+
+    pub async fn record_points(&self, job_id: &JobId, points: &[LocationUpdate]) -> Result<(), AppError> {
+        let (Some(first), Some(last)) = (points.first(), points.last()) else {
+            return Ok(());
+        };
+
+        let coordinates: Vec<(f64, f64)> = points.iter().map(|p| (p.latitude, p.longitude)).collect();
+        let segment = RouteSegment {
+            polyline: polyline::encode(&coordinates),
+            point_count: coordinates.len(),
+            started_at: first.timestamp,
+            ended_at: last.timestamp,
+        };
+
+        self.cache_service.append_route_segment(job_id, &segment).await?;
+        tracing::debug!("Recorded {} route points for job {}", segment.point_count, job_id);
+        Ok(())
+    }
+
+    /// Distance and minutes quoted for a trip from `from` to `to`, as job prices are
+    /// metered on
+    pub fn trip_estimate(&self, from: &Location, to: &Location) -> (f64, i32) {
+        let distance_km = geo::haversine_km((from.latitude, from.longitude), (to.latitude, to.longitude));
+        (distance_km, ((distance_km / AVERAGE_SPEED_KMH) * 60.0) as i32)
+    }
+
+    /// Minutes for the driver to reach `destination` from their last reported position.
+    /// None when they haven't reported one lately.
+    pub async fn eta_minutes(&self, driver_id: &DriverId, destination: &Location) -> Result<Option<i64>, AppError> {
+        let Some(position) = self.cache_service.get_driver_location(driver_id).await? else {
+            return Ok(None);
+        };
+        let distance_km = geo::haversine_km(
+            (position.latitude, position.longitude),
+            (destination.latitude, destination.longitude),
+        ) * ROAD_DETOUR_FACTOR;
+        Ok(Some(((distance_km / AVERAGE_SPEED_KMH) * 60.0).ceil().max(1.0) as i64))
+    }
+
+    /// The stops the driver still has to make for `job` and directions through them. A
+    /// pooled job includes every other job in its pool, ordered nearest first from the
+    /// driver's position without dropping anything off before it is collected.
+    pub async fn navigation(&self, job: &Job) -> Result<NavigationPlan, AppError> {
+        let mut jobs = vec![job.clone()];
+        if let Some(pool_id) = &job.pool_id
+            && let Some(pool) = self.cache_service.get_job_pool(pool_id).await?
+        {
+            for job_id in pool.job_ids.iter().filter(|id| **id != job.id) {
+                if let Some(pooled) = self.cache_service.load_job(job_id).await? {
+                    jobs.push(pooled);
+                }
+            }
+        }
+
+        let origin = match &job.driver_id {
+            Some(driver_id) => self.cache_service.get_driver_location(driver_id).await?
+                .map(|position| (position.latitude, position.longitude)),
+            None => None,
+        };
+
+        let mut stops = order_stops(origin, remaining_stops(&jobs));
+        // A job that's already done still gets directions to where it ended
+        let destination = stops.pop().unwrap_or_else(|| stop(job, StopKind::Dropoff));
+        Ok(NavigationPlan {
+            job_id: job.id.clone(),
+            origin,
+            google_maps_url: google_maps_url(origin, &destination, &stops),
+            google_maps_app_url: google_maps_app_url(origin, &destination, &stops),
+            destination,
+            waypoints: stops,
+            generated_at: Utc::now(),
+        })
+    }
+
+    pub async fn get_navigation(&self, job_id: &JobId) -> Result<NavigationPlan, AppError> {
+        let job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::job_not_found(job_id.as_str()))?;
+        self.navigation(&job).await
+    }
+
+    pub async fn get_route(&self, job_id: &JobId) -> Result<JobRoute, AppError> {
+        let job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::job_not_found(job_id.as_str()))?;
+
+        // Stored as batches arrived; a backlog uploaded after the driver was offline belongs
+        // before the segments recorded since
+        let mut segments = self.cache_service.get_route_segments(job_id).await?;
+        segments.sort_by_key(|segment| segment.started_at);
+
+        let mut coordinates = Vec::new();
+        for segment in &segments {
+            let points = polyline::decode(&segment.polyline)
+                .ok_or_else(|| AppError::InvalidFormat(format!("Corrupt route segment for job {}", job_id)))?;
+            coordinates.extend(points);
+        }
+
+        Ok(JobRoute {
+            job_id: job.id,
+            polyline: polyline::encode(&coordinates),
+            point_count: coordinates.len(),
+            actual_distance_km: geo::path_length_km(&coordinates),
+            estimated_distance_km: job.estimated_distance_km,
+            started_at: segments.first().map(|s| s.started_at),
+            ended_at: segments.last().map(|s| s.ended_at),
+        })
+    }
+}
+
+fn stop(job: &Job, kind: StopKind) -> NavigationStop {
+    let location = match kind {
+        StopKind::Pickup => &job.pickup_location,
+        StopKind::Dropoff => &job.dropoff_location,
+    };
+    NavigationStop {
+        job_id: job.id.clone(),
+        kind,
+        latitude: location.latitude,
+        longitude: location.longitude,
+        address: location.address.clone(),
+    }
+}
+
+// Pickups for packages not yet collected, dropoffs for jobs not yet finished
+fn remaining_stops(jobs: &[Job]) -> Vec<NavigationStop> {
+    let mut stops = Vec::with_capacity(jobs.len() * 2);
+    for job in jobs.iter().filter(|job| !job.status.is_terminal()) {
+        if job.pickup_time.is_none() {
+            stops.push(stop(job, StopKind::Pickup));
+        }
+        stops.push(stop(job, StopKind::Dropoff));
+    }
+    stops
+}
+
+// Nearest next stop each time, a dropoff only once its pickup is behind. Starts from the
+// first stop when the driver's position is unknown.
+fn order_stops(origin: Option<(f64, f64)>, mut remaining: Vec<NavigationStop>) -> Vec<NavigationStop> {
+    let mut ordered: Vec<NavigationStop> = Vec::with_capacity(remaining.len());
+    let mut from = origin;
+    while !remaining.is_empty() {
+        let ready = |candidate: &NavigationStop| {
+            candidate.kind == StopKind::Pickup
+                || !remaining.iter().any(|other| other.kind == StopKind::Pickup && other.job_id == candidate.job_id)
+        };
+        let next = (0..remaining.len())
+            .filter(|&i| ready(&remaining[i]))
+            .min_by(|&a, &b| {
+                let distance = |stop: &NavigationStop| from
+                    .map_or(0.0, |from| geo::haversine_km(from, (stop.latitude, stop.longitude)));
+                distance(&remaining[a]).total_cmp(&distance(&remaining[b]))
+            })
+            .unwrap_or(0);
+        let stop = remaining.remove(next);
+        from = Some((stop.latitude, stop.longitude));
+        ordered.push(stop);
+    }
+    ordered
+}
+
+fn coordinates(latitude: f64, longitude: f64) -> String {
+    format!("{:.6},{:.6}", latitude, longitude)
+}
+
+fn google_maps_url(origin: Option<(f64, f64)>, destination: &NavigationStop, waypoints: &[NavigationStop]) -> String {
+    let mut url = format!(
+        "{}&destination={}&travelmode=driving",
+        GOOGLE_MAPS_DIRECTIONS_URL,
+        coordinates(destination.latitude, destination.longitude),
+    );
+    if let Some((latitude, longitude)) = origin {
+        url.push_str(&format!("&origin={}", coordinates(latitude, longitude)));
+    }
+    if !waypoints.is_empty() {
+        let waypoints: Vec<String> = waypoints.iter().map(|stop| coordinates(stop.latitude, stop.longitude)).collect();
+        url.push_str(&format!("&waypoints={}", waypoints.join("%7C")));
+    }
+    url
+}
+
+// The app scheme takes further stops as "+to:" on the destination
+fn google_maps_app_url(origin: Option<(f64, f64)>, destination: &NavigationStop, waypoints: &[NavigationStop]) -> String {
+    let daddr: Vec<String> = waypoints.iter().chain(std::iter::once(destination))
+        .map(|stop| coordinates(stop.latitude, stop.longitude))
+        .collect();
+    let mut url = format!("comgooglemaps://?daddr={}&directionsmode=driving", daddr.join("+to:"));
+    if let Some((latitude, longitude)) = origin {
+        url.push_str(&format!("&saddr={}", coordinates(latitude, longitude)));
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::{Faker, ACCRA}},
+        models::{ids::UserId, job::{JobPool, JobStatus}},
+    };
+
+    #[tokio::test]
+    async fn test_pooled_navigation_collects_before_dropping_off_and_follows_edits() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(104);
+        let driver = faker.driver();
+        let customer_id = UserId::generate();
+        let at = |location: &mut Location, latitude: f64| {
+            location.latitude = latitude;
+            location.longitude = -0.2;
+        };
+
+        let (mut first, mut second) = (faker.job(&customer_id), faker.job(&customer_id));
+        at(&mut first.pickup_location, 5.61);
+        at(&mut first.dropoff_location, 5.70);
+        at(&mut second.pickup_location, 5.62);
+        at(&mut second.dropoff_location, 5.65);
+        let pool = JobPool {
+            id: "pool-navigation".to_string(),
+            job_ids: vec![first.id.clone(), second.id.clone()],
+            stops: Vec::new(),
+            discount_rate: 0.1,
+            combined_weight_kg: 4.0,
+            created_at: Utc::now(),
+        };
+        for job in [&mut first, &mut second] {
+            job.pool_id = Some(pool.id.clone());
+            job.driver_id = Some(driver.id.clone());
+            job.status = JobStatus::DriverAssigned;
+            state.cache_service.cache_job(job).await.unwrap();
+        }
+        state.cache_service.cache_job_pool(&pool).await.unwrap();
+        let mut position = faker.location_update(ACCRA);
+        (position.latitude, position.longitude) = (5.60, -0.2);
+        state.cache_service.cache_driver_locations(&[(driver.id.clone(), position)]).await.unwrap();
+
+        let plan = state.route_service.navigation(&first).await.unwrap();
+        let order: Vec<(JobId, StopKind)> = plan.waypoints.iter().chain([&plan.destination])
+            .map(|stop| (stop.job_id.clone(), stop.kind))
+            .collect();
+        assert_eq!(order, vec![
+            (first.id.clone(), StopKind::Pickup),
+            (second.id.clone(), StopKind::Pickup),
+            (second.id.clone(), StopKind::Dropoff),
+            (first.id.clone(), StopKind::Dropoff),
+        ]);
+        assert_eq!(plan.origin, Some((5.60, -0.2)));
+        assert_eq!(
+            plan.google_maps_url,
+            "https://www.google.com/maps/dir/?api=1&destination=5.700000,-0.200000&travelmode=driving\
+             &origin=5.600000,-0.200000&waypoints=5.610000,-0.200000%7C5.620000,-0.200000%7C5.650000,-0.200000",
+        );
+        assert!(plan.google_maps_app_url.contains("daddr=5.610000,-0.200000+to:5.620000,-0.200000"));
+
+        // Once the first package is aboard and the second dropoff moves further out
+        first.pickup_time = Some(Utc::now());
+        state.cache_service.cache_job(&first).await.unwrap();
+        at(&mut second.dropoff_location, 5.80);
+        state.cache_service.cache_job(&second).await.unwrap();
+        let plan = state.route_service.get_navigation(&second.id).await.unwrap();
+        assert_eq!(plan.waypoints.len(), 2);
+        assert_eq!((plan.waypoints[0].job_id.clone(), plan.waypoints[0].kind), (second.id.clone(), StopKind::Pickup));
+        assert_eq!(plan.waypoints[1].job_id, first.id);
+        assert_eq!((plan.destination.job_id.clone(), plan.destination.latitude), (second.id.clone(), 5.80));
+    }
+}
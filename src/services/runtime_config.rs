@@ -0,0 +1,285 @@
+// src/services/runtime_config.rs
+// Settings that change without a restart: default pricing for new tenants, the platform
+// dispatch defaults and feature flags, read from the JSON file at RUNTIME_CONFIG_PATH.
+// Each reload builds a new snapshot and swaps it in whole, so a reader sees either the old
+// settings or the new ones, never half of each. An instance reloads when the file's
+// modification time changes, or when another instance announces a change on the
+// `config-changed` Redis channel; notification templates kept in memory are dropped then
+// too, so admin edits reach every instance straight away.
+use arc_swap::ArcSwap;
+use chrono::Utc;
+use futures::StreamExt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+use tracing;
+use uuid::Uuid;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        runtime_config::{RuntimeConfig, RuntimeConfigSnapshot},
+        tenant::PricingConfig,
+    },
+    services::dispatch_settings::validate_override,
+};
+
+#[derive(Debug, Clone)]
+pub struct RuntimeConfigWatch {
+    pub path: Option<PathBuf>, // Without one, the built-in defaults apply
+    pub poll_seconds: u64,     // How often the file's modification time is checked
+    pub channel: String,
+    pub reconnect_delay_seconds: u64,
+}
+
+impl Default for RuntimeConfigWatch {
+    fn default() -> Self {
+        Self {
+            path: None,
+            poll_seconds: 5,
+            channel: "config-changed".to_string(),
+            reconnect_delay_seconds: 5,
+        }
+    }
+}
+
+impl RuntimeConfigWatch {
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let defaults = Self::default();
+        Self {
+            path: var("RUNTIME_CONFIG_PATH").map(PathBuf::from),
+            poll_seconds: var("RUNTIME_CONFIG_POLL_SECONDS").and_then(|v| v.parse().ok()).unwrap_or(defaults.poll_seconds),
+            ..defaults
+        }
+    }
+}
+
+pub struct RuntimeConfigService {
+    current: ArcSwap<RuntimeConfigSnapshot>,
+    modified: Mutex<Option<SystemTime>>, // Of the file as last read
+    redis: OnceLock<redis::Client>,      // Attached by the server; tests and tools run without it
+    instance_id: String,                 // So an instance ignores its own announcements
+    watch: RuntimeConfigWatch,
+}
+
+impl RuntimeConfigService {
+    /// Starts from the file if there is a readable one, from the defaults otherwise
+    pub fn new(watch: RuntimeConfigWatch) -> Self {
+        let mut snapshot = RuntimeConfigSnapshot {
+            config: RuntimeConfig::default(),
+            version: 0,
+            loaded_at: Utc::now(),
+            source: None,
+        };
+        let mut modified = None;
+        if let Some(path) = &watch.path {
+            match read(path) {
+                Ok((config, file_modified)) => {
+                    tracing::info!("Loaded runtime config from {}", path.display());
+                    snapshot.config = config;
+                    snapshot.source = Some(path.display().to_string());
+                    modified = file_modified;
+                }
+                Err(e) => tracing::error!("Failed to load runtime config from {}, using defaults: {}", path.display(), e),
+            }
+        }
+        Self {
+            current: ArcSwap::from_pointee(snapshot),
+            modified: Mutex::new(modified),
+            redis: OnceLock::new(),
+            instance_id: Uuid::new_v4().to_string(),
+            watch,
+        }
+    }
+
+    pub fn attach_redis(&self, client: redis::Client) {
+        if self.redis.set(client).is_err() {
+            tracing::warn!("Runtime config already attached to Redis");
+        }
+    }
+
+    pub fn current(&self) -> Arc<RuntimeConfigSnapshot> {
+        self.current.load_full()
+    }
+
+    pub fn version(&self) -> u64 {
+        self.current.load().version
+    }
+
+    pub fn feature_enabled(&self, name: &str) -> bool {
+        self.current.load().config.feature_enabled(name)
+    }
+
+    /// Rereads the file and swaps in what it holds. A file that fails to parse or validate
+    /// leaves the current settings in force. Without a file the settings stay as they are,
+    /// but the version still goes up so copies made from them are refreshed.
+    pub async fn reload(&self) -> Result<Arc<RuntimeConfigSnapshot>, AppError> {
+        let previous = self.current();
+        let (config, source) = match &self.watch.path {
+            Some(path) => {
+                let (config, modified) = read(path)?;
+                *self.modified.lock().unwrap() = modified;
+                (config, Some(path.display().to_string()))
+            }
+            None => (previous.config.clone(), None),
+        };
+        let snapshot = Arc::new(RuntimeConfigSnapshot {
+            config,
+            version: previous.version + 1,
+            loaded_at: Utc::now(),
+            source,
+        });
+        self.current.store(snapshot.clone());
+        tracing::info!("Runtime config reloaded (version {})", snapshot.version);
+        Ok(snapshot)
+    }
+
+    /// Tells the other instances to reload
+    pub async fn announce(&self) -> Result<(), AppError> {
+        let Some(client) = self.redis.get() else {
+            return Ok(());
+        };
+        let mut connection = client.get_async_connection().await?;
+        let _: i64 = redis::cmd("PUBLISH")
+            .arg(&self.watch.channel)
+            .arg(&self.instance_id)
+            .query_async(&mut connection)
+            .await?;
+        Ok(())
+    }
+
+    /// Reload whenever the file changes, for the life of the process
+    pub async fn watch_file(self: Arc<Self>) {
+        let Some(path) = self.watch.path.clone() else {
+            return;
+        };
+        let mut interval = tokio::time::interval(Duration::from_secs(self.watch.poll_seconds));
+        loop {
+            interval.tick().await;
+            let modified = match std::fs::metadata(&path).and_then(|metadata| metadata.modified()) {
+                Ok(modified) => modified,
+                Err(e) => {
+                    tracing::debug!("Failed to check runtime config file {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            if *self.modified.lock().unwrap() == Some(modified) {
+                continue;
+            }
+            match self.reload().await {
+                Ok(_) => {
+                    if let Err(e) = self.announce().await {
+                        tracing::warn!("Failed to announce runtime config change: {}", e);
+                    }
+                }
+                Err(e) => {
+                    // Not retried until the file changes again
+                    *self.modified.lock().unwrap() = Some(modified);
+                    tracing::error!("Runtime config file changed but was not applied: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Reload on every other instance's announcement, reconnecting whenever the link drops
+    pub async fn listen(self: Arc<Self>) {
+        let Some(client) = self.redis.get() else {
+            return;
+        };
+        loop {
+            match client.get_async_connection().await {
+                Ok(connection) => {
+                    let mut pubsub = connection.into_pubsub();
+                    match pubsub.subscribe(&self.watch.channel).await {
+                        Ok(()) => {
+                            tracing::info!("Listening for runtime config changes on {}", self.watch.channel);
+                            let mut messages = pubsub.on_message();
+                            while let Some(message) = messages.next().await {
+                                let sender: String = message.get_payload().unwrap_or_default();
+                                if sender == self.instance_id {
+                                    continue;
+                                }
+                                if let Err(e) = self.reload().await {
+                                    tracing::error!("Runtime config change announced but not applied: {}", e);
+                                }
+                            }
+                            tracing::warn!("Runtime config subscription ended");
+                        }
+                        Err(e) => tracing::error!("Failed to subscribe to {}: {}", self.watch.channel, e),
+                    }
+                }
+                Err(e) => tracing::warn!("Runtime config failed to reach Redis: {}", e),
+            }
+            tokio::time::sleep(Duration::from_secs(self.watch.reconnect_delay_seconds)).await;
+        }
+    }
+}
+
+fn read(path: &PathBuf) -> Result<(RuntimeConfig, Option<SystemTime>), AppError> {
+    let file_error = |e: std::io::Error| AppError::InternalServer(format!("Failed to read {}: {}", path.display(), e));
+    let modified = std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    let text = std::fs::read_to_string(path).map_err(file_error)?;
+    let config: RuntimeConfig = serde_json::from_str(&text)
+        .map_err(|e| AppError::validation_error("runtime_config", e.to_string()))?;
+    validate(&config)?;
+    Ok((config, modified))
+}
+
+fn validate(config: &RuntimeConfig) -> Result<(), AppError> {
+    validate_override("dispatch", &config.dispatch)?;
+    validate_pricing(&config.pricing)?;
+    if config.features.keys().any(|name| name.trim().is_empty()) {
+        return Err(AppError::validation_error("features", "Feature names must not be empty"));
+    }
+    Ok(())
+}
+
+fn validate_pricing(pricing: &PricingConfig) -> Result<(), AppError> {
+    let rates = [
+        ("base_fare_standard", pricing.base_fare_standard),
+        ("base_fare_express", pricing.base_fare_express),
+        ("base_fare_same_day", pricing.base_fare_same_day),
+        ("base_fare_emergency", pricing.base_fare_emergency),
+        ("per_km", pricing.per_km),
+        ("per_minute", pricing.per_minute),
+        ("service_fee_rate", pricing.service_fee_rate),
+    ];
+    if let Some((name, _)) = rates.iter().find(|(_, rate)| !rate.is_finite() || *rate < 0.0) {
+        return Err(AppError::validation_error(format!("pricing.{}", name), "Must not be negative"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reload_swaps_in_a_valid_file_and_keeps_the_old_snapshot_otherwise() {
+        let path = std::env::temp_dir().join(format!("sparrow-runtime-config-{}.json", Uuid::new_v4()));
+        std::fs::write(&path, r#"{"dispatch": {"search_radius_km": 6.0}, "features": {"pooling": true}}"#).unwrap();
+        let service = RuntimeConfigService::new(RuntimeConfigWatch { path: Some(path.clone()), ..Default::default() });
+
+        let first = service.current();
+        assert_eq!(first.config.dispatch.search_radius_km, Some(6.0));
+        assert!(service.feature_enabled("pooling"));
+        assert!(!service.feature_enabled("sms_receipts"));
+
+        std::fs::write(&path, r#"{"dispatch": {"search_radius_km": 12.0, "max_candidates": 4}}"#).unwrap();
+        let second = service.reload().await.unwrap();
+        assert_eq!((second.version, second.config.dispatch.max_candidates), (1, Some(4)));
+        assert!(!service.feature_enabled("pooling"));
+        // Readers holding the first snapshot still see it whole
+        assert_eq!(first.config.dispatch.search_radius_km, Some(6.0));
+
+        std::fs::write(&path, r#"{"dispatch": {"max_candidates": 0}}"#).unwrap();
+        assert!(service.reload().await.is_err());
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(service.reload().await.is_err());
+        assert_eq!(service.current().version, 1);
+        assert_eq!(service.current().config.dispatch.search_radius_km, Some(12.0));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,403 @@
+// src/services/saga.rs
+// Workflows that touch several systems with no transaction spanning them, such as
+// settling a completed job: post it to the ledger, bill it, tell everyone. A `Saga` is the
+// list of steps, each able to undo itself; the orchestrator runs them in order, saving
+// progress after each, so a crash or a failing step leaves a record to resume from.
+//
+// A failing step is retried, on the next run, until it has failed `max_attempts` times;
+// then the steps already done are undone, newest first. A forward-only saga undoes nothing
+// and is left failed for someone to look at instead. Running an undone or failed saga again
+// starts it over. Steps must be safe to run twice, since a saga that died mid-step repeats
+// that step when it resumes, and only one runner holds a saga at a time.
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::saga::{SagaKind, SagaState, SagaStatus, SagaStepState, StepStatus},
+    services::{
+        cache_service::CacheService,
+        region::{current_region_id, with_region},
+        tenant_service::{current_tenant_id, with_tenant},
+    },
+    utils::id_generator::{IdGenerator, IdType},
+};
+
+#[async_trait]
+pub trait SagaStep<C>: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn execute(&self, context: &C) -> Result<(), AppError>;
+    /// Undo `execute`. Steps with nothing to undo, or that can't be undone and so come
+    /// last, keep the default.
+    async fn compensate(&self, _context: &C) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+pub struct Saga<C> {
+    kind: SagaKind,
+    steps: Vec<Box<dyn SagaStep<C>>>,
+    forward_only: bool,
+}
+
+impl<C> Saga<C> {
+    pub fn new(kind: SagaKind) -> Self {
+        Self { kind, steps: Vec::new(), forward_only: false }
+    }
+
+    /// For work that must not be rolled back: a step that runs out of attempts fails the
+    /// saga without undoing the steps before it
+    pub fn forward_only(mut self) -> Self {
+        self.forward_only = true;
+        self
+    }
+
+    pub fn step(mut self, step: impl SagaStep<C> + 'static) -> Self {
+        self.steps.push(Box::new(step));
+        self
+    }
+
+    pub fn kind(&self) -> SagaKind {
+        self.kind
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SagaConfig {
+    pub max_attempts: u32,        // Failures of a step, or of undoing it, before giving up on it
+    pub resume_after_seconds: i64, // A saga this long without progress is taken to have lost its runner
+    pub claim_seconds: u64,        // How long a runner holds a saga before another may take it
+}
+
+impl Default for SagaConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            resume_after_seconds: 300,
+            claim_seconds: 60,
+        }
+    }
+}
+
+pub struct SagaOrchestrator {
+    cache_service: Arc<CacheService>,
+    config: SagaConfig,
+}
+
+impl SagaOrchestrator {
+    pub fn new(cache_service: Arc<CacheService>, config: SagaConfig) -> Self {
+        Self { cache_service, config }
+    }
+
+    /// Run the saga for `key`, or carry on with the one already started for it. One that
+    /// was undone or gave up is started again from the top, under a new id.
+    pub async fn run<C: Serialize + Sync>(&self, saga: &Saga<C>, key: &str, context: &C) -> Result<SagaState, AppError> {
+        self.claimed(saga.kind, key, async {
+            let state = match self.cache_service.get_saga(saga.kind, key).await? {
+                Some(state) if !matches!(state.status, SagaStatus::Compensated | SagaStatus::Failed) => state,
+                previous => {
+                    if let Some(previous) = previous {
+                        tracing::info!("Saga {} for {} ended {:?}; starting again", previous.id, key, previous.status);
+                    }
+                    let now = Utc::now();
+                    SagaState {
+                        id: IdGenerator::generate(IdType::Saga),
+                        kind: saga.kind,
+                        key: key.to_string(),
+                        tenant_id: current_tenant_id(),
+                        region_id: current_region_id(),
+                        context: serde_json::to_value(context)?,
+                        status: SagaStatus::Running,
+                        steps: saga.steps.iter()
+                            .map(|step| SagaStepState {
+                                name: step.name().to_string(),
+                                status: StepStatus::Pending,
+                                attempts: 0,
+                                last_error: None,
+                                updated_at: None,
+                            })
+                            .collect(),
+                        started_at: now,
+                        updated_at: now,
+                    }
+                }
+            };
+            self.drive(saga, state, context).await
+        }).await
+    }
+
+    /// Carry on with a saved saga, with the context and in the tenant and region it started in
+    pub async fn resume<C: DeserializeOwned + Sync>(&self, saga: &Saga<C>, state: SagaState) -> Result<SagaState, AppError> {
+        let (tenant_id, region_id) = (state.tenant_id.clone(), state.region_id.clone());
+        let (kind, key) = (state.kind, state.key.clone());
+        with_region(region_id, with_tenant(tenant_id, self.claimed(kind, &key, async {
+            // `state` may be a stale copy: another runner can have moved it on, or finished
+            // it, between it being read and the claim being taken
+            let state = match self.cache_service.get_saga(kind, &key).await? {
+                Some(current) if current.status.is_finished() => return Ok(current),
+                Some(current) => current,
+                None => state,
+            };
+            let context: C = serde_json::from_value(state.context.clone())?;
+            self.drive(saga, state, &context).await
+        }))).await
+    }
+
+    // Only one runner at a time per saga, so two requests can't both take its steps
+    async fn claimed<F>(&self, kind: SagaKind, key: &str, run: F) -> Result<SagaState, AppError>
+    where
+        F: std::future::Future<Output = Result<SagaState, AppError>>,
+    {
+        if !self.cache_service.claim_saga(kind, key, self.config.claim_seconds).await? {
+            return Err(AppError::Conflict(format!("Saga {} for {} is already being run", kind.as_str(), key)));
+        }
+        let result = run.await;
+        if let Err(e) = self.cache_service.release_saga(kind, key).await {
+            tracing::warn!("Failed to release saga {} for {}: {}", kind.as_str(), key, e);
+        }
+        result
+    }
+
+    /// Sagas not yet completed or undone, failed ones included
+    pub async fn open(&self) -> Result<Vec<SagaState>, AppError> {
+        let mut sagas = self.cache_service.get_open_sagas().await?;
+        sagas.sort_by_key(|saga| saga.started_at);
+        Ok(sagas)
+    }
+
+    /// Sagas of `kind` still in progress that haven't moved for a while
+    pub async fn stalled(&self, kind: SagaKind, now: DateTime<Utc>) -> Result<Vec<SagaState>, AppError> {
+        let cutoff = now - Duration::seconds(self.config.resume_after_seconds);
+        Ok(self.open().await?.into_iter()
+            .filter(|saga| saga.kind == kind && !saga.status.is_finished() && saga.updated_at <= cutoff)
+            .collect())
+    }
+
+    async fn drive<C: Sync>(&self, saga: &Saga<C>, mut state: SagaState, context: &C) -> Result<SagaState, AppError> {
+        if state.status.is_finished() {
+            return Ok(state);
+        }
+        // Saved progress only means something against the steps it was saved with
+        let names: Vec<&str> = saga.steps.iter().map(|step| step.name()).collect();
+        if state.steps.iter().map(|step| step.name.as_str()).ne(names.iter().copied()) {
+            return Err(AppError::Conflict(format!("Saga {} was started with different steps", state.id)));
+        }
+
+        while state.status == SagaStatus::Running {
+            let Some(index) = state.steps.iter().position(|step| step.status == StepStatus::Pending) else {
+                state.status = SagaStatus::Completed;
+                break;
+            };
+            match saga.steps[index].execute(context).await {
+                Ok(()) => settle_step(&mut state.steps[index], StepStatus::Done),
+                Err(e) => {
+                    let step = &mut state.steps[index];
+                    tracing::warn!("Saga {} step {} failed (attempt {}): {}", state.id, step.name, step.attempts + 1, e);
+                    if fail_step(step, e) < self.config.max_attempts {
+                        return self.save(state).await;
+                    }
+                    if saga.forward_only {
+                        tracing::error!("Saga {} for {} gave up on step {} and needs attention", state.id, state.key, step.name);
+                        state.status = SagaStatus::Failed;
+                        break;
+                    }
+                    state.status = SagaStatus::Compensating;
+                }
+            }
+            state = self.save(state).await?;
+        }
+
+        while state.status == SagaStatus::Compensating {
+            let Some(index) = state.steps.iter().rposition(|step| step.status == StepStatus::Done) else {
+                state.status = SagaStatus::Compensated;
+                break;
+            };
+            match saga.steps[index].compensate(context).await {
+                Ok(()) => settle_step(&mut state.steps[index], StepStatus::Compensated),
+                Err(e) => {
+                    let step = &mut state.steps[index];
+                    tracing::warn!("Saga {} failed to undo step {} (attempt {}): {}", state.id, step.name, step.attempts + 1, e);
+                    if fail_step(step, e) < self.config.max_attempts {
+                        return self.save(state).await;
+                    }
+                    tracing::error!("Saga {} for {} couldn't be undone and needs attention", state.id, state.key);
+                    state.status = SagaStatus::Failed;
+                }
+            }
+            state = self.save(state).await?;
+        }
+
+        let state = self.save(state).await?;
+        tracing::info!("Saga {} ({} {}) finished as {:?}", state.id, state.kind.as_str(), state.key, state.status);
+        Ok(state)
+    }
+
+    async fn save(&self, mut state: SagaState) -> Result<SagaState, AppError> {
+        state.updated_at = Utc::now();
+        self.cache_service.cache_saga(&state).await?;
+        Ok(state)
+    }
+}
+
+fn settle_step(step: &mut SagaStepState, status: StepStatus) {
+    step.status = status;
+    step.attempts = 0;
+    step.updated_at = Some(Utc::now());
+}
+
+// Attempts so far at what the step is doing now
+fn fail_step(step: &mut SagaStepState, error: AppError) -> u32 {
+    step.attempts += 1;
+    step.last_error = Some(error.to_string());
+    step.updated_at = Some(Utc::now());
+    step.attempts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::cache_service::CacheConfig;
+    use std::sync::Mutex;
+
+    // Records what ran; fails while `failures` has some left
+    struct Recorded {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+        failures: Mutex<u32>,
+    }
+
+    fn recorded(name: &'static str, log: &Arc<Mutex<Vec<String>>>, failures: u32) -> Recorded {
+        Recorded { name, log: log.clone(), failures: Mutex::new(failures) }
+    }
+
+    #[async_trait]
+    impl SagaStep<String> for Recorded {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn execute(&self, context: &String) -> Result<(), AppError> {
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(AppError::internal_error("not yet"));
+            }
+            self.log.lock().unwrap().push(format!("{} {}", self.name, context));
+            Ok(())
+        }
+
+        async fn compensate(&self, context: &String) -> Result<(), AppError> {
+            self.log.lock().unwrap().push(format!("undo {} {}", self.name, context));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sagas_resume_where_they_stopped_and_undo_after_giving_up() {
+        let cache_service = Arc::new(CacheService::new_memory(CacheConfig::default()));
+        let orchestrator = SagaOrchestrator::new(cache_service, SagaConfig { max_attempts: 3, resume_after_seconds: 0, claim_seconds: 60 });
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let context = "job-1".to_string();
+
+        // The second step fails once; the next run picks up from it, not from the top
+        let saga = Saga::new(SagaKind::JobCompletion)
+            .step(recorded("capture", &log, 0))
+            .step(recorded("credit", &log, 1))
+            .step(recorded("notify", &log, 0));
+        let state = orchestrator.run(&saga, "job-1", &context).await.unwrap();
+        assert_eq!(state.status, SagaStatus::Running);
+        assert_eq!(state.steps[1].attempts, 1);
+        let stalled = orchestrator.stalled(SagaKind::JobCompletion, Utc::now()).await.unwrap();
+        assert_eq!(stalled.len(), 1);
+        let state = orchestrator.resume(&saga, stalled.into_iter().next().unwrap()).await.unwrap();
+        assert_eq!(state.status, SagaStatus::Completed);
+        assert_eq!(*log.lock().unwrap(), vec!["capture job-1", "credit job-1", "notify job-1"]);
+        assert!(orchestrator.open().await.unwrap().is_empty());
+        // A finished saga isn't run again
+        orchestrator.run(&saga, "job-1", &context).await.unwrap();
+        assert_eq!(log.lock().unwrap().len(), 3);
+
+        // A step that keeps failing has the ones before it undone, newest first
+        log.lock().unwrap().clear();
+        let saga = Saga::new(SagaKind::JobRefund)
+            .step(recorded("reverse", &log, 0))
+            .step(recorded("mark", &log, 0))
+            .step(recorded("payout", &log, 5));
+        let mut state = orchestrator.run(&saga, "job-1", &context).await.unwrap();
+        while !state.status.is_finished() {
+            state = orchestrator.run(&saga, "job-1", &context).await.unwrap();
+        }
+        assert_eq!(state.status, SagaStatus::Compensated);
+        assert_eq!(state.steps[2].last_error.as_deref(), Some("Internal server error: not yet"));
+        assert_eq!(*log.lock().unwrap(), vec!["reverse job-1", "mark job-1", "undo mark job-1", "undo reverse job-1"]);
+        // Running it again is a new attempt, not the undone one handed back
+        let retried = orchestrator.run(&saga, "job-1", &context).await.unwrap();
+        assert_ne!(retried.id, state.id);
+        assert_eq!(retried.status, SagaStatus::Running);
+        assert_eq!(retried.steps[1].status, StepStatus::Done);
+        // and nobody else can run it while it's held
+        assert!(orchestrator.cache_service.claim_saga(SagaKind::JobRefund, "job-1", 60).await.unwrap());
+        assert!(matches!(orchestrator.run(&saga, "job-1", &context).await, Err(AppError::Conflict(_))));
+        orchestrator.cache_service.release_saga(SagaKind::JobRefund, "job-1").await.unwrap();
+        assert!(orchestrator.run(&saga, "job-1", &context).await.is_ok());
+
+        // A forward-only saga leaves what it did in place and waits for someone
+        log.lock().unwrap().clear();
+        let saga = Saga::new(SagaKind::JobCompletion)
+            .forward_only()
+            .step(recorded("settle", &log, 0))
+            .step(recorded("bill", &log, 5));
+        let mut state = orchestrator.run(&saga, "job-3", &context).await.unwrap();
+        while !state.status.is_finished() {
+            state = orchestrator.run(&saga, "job-3", &context).await.unwrap();
+        }
+        assert_eq!(state.status, SagaStatus::Failed);
+        assert_eq!(state.steps[0].status, StepStatus::Done);
+        assert_eq!(*log.lock().unwrap(), vec!["settle job-1"]);
+        assert!(orchestrator.open().await.unwrap().iter().any(|open| open.key == "job-3"));
+
+        // Saved progress isn't applied to a saga whose steps have changed
+        let changed = Saga::new(SagaKind::JobCompletion).step(recorded("capture", &log, 0));
+        orchestrator.cache_service.cache_saga(&SagaState { status: SagaStatus::Running, key: "job-2".to_string(), ..state }).await.unwrap();
+        let saved = orchestrator.cache_service.get_saga(SagaKind::JobCompletion, "job-2").await.unwrap().unwrap();
+        assert!(orchestrator.resume(&changed, saved).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_resumes_run_each_step_once() {
+        let cache_service = Arc::new(CacheService::new_memory(CacheConfig::default()));
+        let orchestrator = SagaOrchestrator::new(cache_service, SagaConfig { max_attempts: 3, resume_after_seconds: 0, claim_seconds: 60 });
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let context = "job-1".to_string();
+        let saga = Saga::new(SagaKind::JobRefund)
+            .step(recorded("reverse", &log, 0))
+            .step(recorded("mark", &log, 1))
+            .step(recorded("announce", &log, 0));
+        orchestrator.run(&saga, "job-1", &context).await.unwrap();
+
+        // Two recovery passes pick up the same stalled saga
+        let stalled = orchestrator.stalled(SagaKind::JobRefund, Utc::now()).await.unwrap();
+        assert_eq!(stalled.len(), 1);
+        let (first, second) = tokio::join!(
+            orchestrator.resume(&saga, stalled[0].clone()),
+            orchestrator.resume(&saga, stalled[0].clone()),
+        );
+        for result in [first, second] {
+            match result {
+                Ok(state) => assert_eq!(state.status, SagaStatus::Completed),
+                Err(e) => assert!(matches!(e, AppError::Conflict(_))),
+            }
+        }
+        assert_eq!(*log.lock().unwrap(), vec!["reverse job-1", "mark job-1", "announce job-1"]);
+
+        // The stale copy doesn't undo a finished saga or run its steps again
+        let state = orchestrator.resume(&saga, stalled[0].clone()).await.unwrap();
+        assert_eq!(state.status, SagaStatus::Completed);
+        assert_eq!(log.lock().unwrap().len(), 3);
+        let saved = orchestrator.cache_service.get_saga(SagaKind::JobRefund, "job-1").await.unwrap().unwrap();
+        assert_eq!(saved.status, SagaStatus::Completed);
+    }
+}
@@ -0,0 +1,197 @@
+// src/services/send_queue.rs
+// Bounded outbound queue per WebSocket, so a phone on a weak signal can't make the server
+// buffer without limit. Location frames only matter as the newest of their kind, so an
+// older one still waiting is dropped; status frames (offers, answers, withdrawals) never
+// are. A connection whose queue fills with those, or whose writes keep stalling, is closed.
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing;
+
+use crate::models::dispatch::DriverSocketEvent;
+
+#[derive(Debug, Clone)]
+pub struct SendQueueConfig {
+    pub capacity: usize,            // Frames waiting per connection
+    pub slow_write_seconds: u64,    // A write taking longer than this counts as slow
+    pub max_slow_writes: u32,       // Slow writes in a row before the connection is closed
+}
+
+impl Default for SendQueueConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 64,
+            slow_write_seconds: 5,
+            max_slow_writes: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendQueueMetrics {
+    pub capacity: usize,
+    pub queued: u64,
+    pub sent: u64,
+    pub coalesced: u64,             // Location frames superseded before they were written
+    pub slow_writes: u64,
+    pub slow_consumers_closed: u64, // Connections closed for falling behind
+}
+
+#[derive(Default)]
+struct Counters {
+    queued: AtomicU64,
+    sent: AtomicU64,
+    coalesced: AtomicU64,
+    slow_writes: AtomicU64,
+    slow_consumers_closed: AtomicU64,
+}
+
+/// Hands out a queue per connection and keeps the counters they share
+pub struct SendQueues {
+    config: SendQueueConfig,
+    counters: Counters,
+}
+
+impl SendQueues {
+    pub fn new(config: SendQueueConfig) -> Self {
+        Self {
+            config,
+            counters: Counters::default(),
+        }
+    }
+
+    pub fn queue(self: &Arc<Self>) -> SendQueue {
+        SendQueue {
+            frames: Mutex::new(VecDeque::new()),
+            ready: Notify::new(),
+            slow_writes: Mutex::new(0),
+            queues: self.clone(),
+        }
+    }
+
+    pub fn metrics(&self) -> SendQueueMetrics {
+        SendQueueMetrics {
+            capacity: self.config.capacity,
+            queued: self.counters.queued.load(Ordering::Relaxed),
+            sent: self.counters.sent.load(Ordering::Relaxed),
+            coalesced: self.counters.coalesced.load(Ordering::Relaxed),
+            slow_writes: self.counters.slow_writes.load(Ordering::Relaxed),
+            slow_consumers_closed: self.counters.slow_consumers_closed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+// The consumer fell too far behind to be worth keeping
+#[derive(Debug, PartialEq)]
+pub struct SlowConsumer;
+
+pub struct SendQueue {
+    frames: Mutex<VecDeque<DriverSocketEvent>>,
+    ready: Notify,
+    slow_writes: Mutex<u32>, // In a row
+    queues: Arc<SendQueues>,
+}
+
+impl SendQueue {
+    /// Queue a frame without waiting. Fails when status frames alone fill the queue.
+    pub fn push(&self, event: DriverSocketEvent) -> Result<(), SlowConsumer> {
+        let counters = &self.queues.counters;
+        {
+            let mut frames = self.frames.lock().unwrap();
+            // A full queue also makes room by dropping its oldest location frame
+            let full = frames.len() >= self.queues.config.capacity;
+            let superseded = if event.is_location() || full {
+                frames.iter().position(DriverSocketEvent::is_location)
+            } else {
+                None
+            };
+            if let Some(index) = superseded {
+                frames.remove(index);
+                counters.coalesced.fetch_add(1, Ordering::Relaxed);
+            }
+            if frames.len() >= self.queues.config.capacity {
+                counters.slow_consumers_closed.fetch_add(1, Ordering::Relaxed);
+                return Err(SlowConsumer);
+            }
+            frames.push_back(event);
+        }
+        counters.queued.fetch_add(1, Ordering::Relaxed);
+        self.ready.notify_one();
+        Ok(())
+    }
+
+    /// The next frame to write, waiting for one if need be
+    pub async fn pop(&self) -> DriverSocketEvent {
+        loop {
+            if let Some(event) = self.frames.lock().unwrap().pop_front() {
+                return event;
+            }
+            self.ready.notified().await;
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.frames.lock().unwrap().len()
+    }
+
+    /// Record how long a write took; fails once too many in a row were slow
+    pub fn written(&self, took: Duration) -> Result<(), SlowConsumer> {
+        let config = &self.queues.config;
+        let counters = &self.queues.counters;
+        counters.sent.fetch_add(1, Ordering::Relaxed);
+        let mut slow_writes = self.slow_writes.lock().unwrap();
+        if took <= Duration::from_secs(config.slow_write_seconds) {
+            *slow_writes = 0;
+            return Ok(());
+        }
+        *slow_writes += 1;
+        counters.slow_writes.fetch_add(1, Ordering::Relaxed);
+        tracing::debug!("Slow socket write ({:?}, {} in a row)", took, *slow_writes);
+        if *slow_writes >= config.max_slow_writes {
+            counters.slow_consumers_closed.fetch_add(1, Ordering::Relaxed);
+            return Err(SlowConsumer);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ids::JobId;
+
+    #[tokio::test]
+    async fn test_location_frames_coalesce_and_status_frames_overflow() {
+        let queues = Arc::new(SendQueues::new(SendQueueConfig { capacity: 3, ..Default::default() }));
+        let queue = queues.queue();
+        let locations = |accepted| DriverSocketEvent::LocationsReceived { received: 10, accepted };
+        let withdrawn = |job_id: &JobId| DriverSocketEvent::OfferWithdrawn { job_id: job_id.clone() };
+        let (first, second) = (JobId::generate(), JobId::generate());
+
+        // Only the newest location frame waits
+        queue.push(locations(1)).unwrap();
+        queue.push(withdrawn(&first)).unwrap();
+        queue.push(locations(2)).unwrap();
+        assert_eq!(queue.depth(), 2);
+
+        // A full queue gives up location frames before status ones, then gives up
+        queue.push(withdrawn(&second)).unwrap();
+        queue.push(withdrawn(&first)).unwrap();
+        assert_eq!(queue.push(withdrawn(&second)), Err(SlowConsumer));
+        assert_eq!(queue.pop().await, withdrawn(&first));
+        assert_eq!(queue.pop().await, withdrawn(&second));
+
+        assert!(queue.written(Duration::from_secs(6)).is_ok());
+        assert!(queue.written(Duration::from_millis(20)).is_ok());
+        assert!(queue.written(Duration::from_secs(6)).is_ok());
+        assert!(queue.written(Duration::from_secs(6)).is_ok());
+        assert_eq!(queue.written(Duration::from_secs(6)), Err(SlowConsumer));
+
+        let metrics = queues.metrics();
+        assert_eq!((metrics.queued, metrics.coalesced, metrics.sent), (5, 2, 5));
+        assert_eq!((metrics.slow_writes, metrics.slow_consumers_closed), (4, 2));
+    }
+}
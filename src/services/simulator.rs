@@ -0,0 +1,437 @@
+// src/services/simulator.rs
+// Replays a recorded day of jobs against other dispatch and pricing settings, offline, so a
+// change can be judged before it reaches drivers. Jobs are taken in the order they were
+// created; each is offered the way the scenario says to the drivers online, free and in
+// range at the time, ranked as automatic dispatch ranks them. Drivers answer as they did on
+// the day: one who turned the job down does so again, one who let the offer lapse lets it
+// lapse again, and anyone else accepts. A driver who takes a job is busy until they could
+// have reached the pickup and made the delivery, and then waits at the dropoff.
+//
+// Drivers are placed where they were recorded, which the day's own dispatch influenced, so
+// the figures are best read against each other rather than as forecasts.
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::{HashMap, HashSet};
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        driver::default_reliability_score,
+        ids::DriverId,
+        job::{Job, JobPriority},
+        money::{Currency, Money},
+        simulation::{
+            ComparisonReport, DriverPosition, OfferStrategy, SimulatedDriver, SimulatedJob, SimulationDay,
+            SimulationReport, SimulationScenario,
+        },
+        tenant::PricingConfig,
+    },
+    services::{
+        cache_service::CacheService,
+        dispatch::{rank_candidates, DispatchCandidate, DispatchConfig},
+        tenant_service::current_tenant_id,
+    },
+    utils::geo::haversine_km,
+};
+
+#[derive(Debug, Clone)]
+pub struct SimulatorConfig {
+    pub defaults: DispatchConfig, // What each scenario's settings apply over
+    pub accept_seconds: i64,      // From an offer to a willing driver accepting it
+    pub reject_seconds: i64,
+    pub retry_seconds: i64,       // Before offering a job nobody took again
+    pub pickup_speed_kmh: f64,    // For the drive to the pickup
+}
+
+impl Default for SimulatorConfig {
+    fn default() -> Self {
+        Self {
+            defaults: DispatchConfig::default(),
+            accept_seconds: 8,
+            reject_seconds: 10,
+            retry_seconds: 30,
+            pickup_speed_kmh: 25.0,
+        }
+    }
+}
+
+/// The current tenant's jobs created on `day`, and where their drivers were recorded
+pub async fn load_day(cache_service: &CacheService, day: NaiveDate) -> Result<SimulationDay, AppError> {
+    let tenant_id = current_tenant_id();
+    let mut jobs: Vec<Job> = Vec::new();
+    for job_id in cache_service.get_jobs_for_day(&day).await? {
+        if let Some(job) = cache_service.load_job(&job_id).await? {
+            jobs.push(job);
+        }
+    }
+    jobs.sort_by_key(|job| job.created_at);
+
+    let currency = jobs.first().map_or(Currency::GHS, |job| job.pricing.currency);
+    let mut positions: HashMap<DriverId, Vec<DriverPosition>> = HashMap::new();
+    let mut simulated = Vec::with_capacity(jobs.len());
+    for job in jobs {
+        if job.pricing.currency != currency {
+            tracing::warn!("Leaving job {} out of the replay: priced in {}, not {}", job.id, job.pricing.currency, currency);
+            continue;
+        }
+        if let Some(driver_id) = &job.driver_id {
+            let recorded = positions.entry(driver_id.clone()).or_default();
+            for event in cache_service.get_job_events(&job.id).await? {
+                if let Some(location) = event.location.filter(|_| event.actor.starts_with("driver:")) {
+                    recorded.push(DriverPosition { at: location.timestamp, latitude: location.latitude, longitude: location.longitude });
+                }
+            }
+            let stops = [(job.pickup_time, &job.pickup_location), (job.dropoff_time, &job.dropoff_location)];
+            for (at, stop) in stops {
+                if let Some(at) = at {
+                    recorded.push(DriverPosition { at, latitude: stop.latitude, longitude: stop.longitude });
+                }
+            }
+        }
+        simulated.push(simulated_job(job));
+    }
+
+    let mut drivers = Vec::with_capacity(positions.len());
+    for (driver_id, mut positions) in positions {
+        positions.sort_by_key(|position| position.at);
+        let (Some(first), Some(last)) = (positions.first(), positions.last()) else {
+            continue;
+        };
+        let reliability_score = match cache_service.get_driver(&driver_id).await? {
+            Some(driver) => driver.reliability_score,
+            None => default_reliability_score(),
+        };
+        drivers.push(SimulatedDriver {
+            driver_id,
+            reliability_score,
+            online_from: first.at,
+            online_until: last.at,
+            positions,
+        });
+    }
+    drivers.sort_by(|a, b| a.driver_id.as_str().cmp(b.driver_id.as_str()));
+
+    Ok(SimulationDay { day, tenant_id, currency, jobs: simulated, drivers })
+}
+
+fn simulated_job(job: Job) -> SimulatedJob {
+    let answered: HashSet<&DriverId> = job.rejected_by_drivers.iter().chain(job.driver_id.as_ref()).collect();
+    let ignored_by = job.offered_to_drivers.iter().filter(|driver_id| !answered.contains(driver_id)).cloned().collect();
+    SimulatedJob {
+        job_id: job.id,
+        priority: job.priority,
+        created_at: job.created_at,
+        expires_at: job.expires_at,
+        pickup: (job.pickup_location.latitude, job.pickup_location.longitude),
+        dropoff: (job.dropoff_location.latitude, job.dropoff_location.longitude),
+        distance_km: job.estimated_distance_km,
+        duration_min: job.estimated_duration_min,
+        rejected_by: job.rejected_by_drivers,
+        ignored_by,
+        driver_id: job.driver_id,
+        assigned_at: job.accepted_at,
+        completed_at: job.dropoff_time,
+        fare: job.pricing.total,
+    }
+}
+
+/// The day as it happened, then each scenario replayed
+pub fn compare(day: &SimulationDay, scenarios: &[SimulationScenario], config: &SimulatorConfig) -> ComparisonReport {
+    ComparisonReport {
+        day: day.day,
+        tenant_id: day.tenant_id.clone(),
+        historical: historical(day),
+        scenarios: scenarios.iter().map(|scenario| simulate(day, scenario, config)).collect(),
+    }
+}
+
+/// What the day's own dispatch achieved, from the recorded assignments
+pub fn historical(day: &SimulationDay) -> SimulationReport {
+    let mut tally = Tally::new(day, day.currency);
+    for job in &day.jobs {
+        let (Some(driver_id), Some(assigned_at)) = (&job.driver_id, job.assigned_at) else {
+            continue;
+        };
+        let pickup_km = day.drivers.iter()
+            .find(|driver| &driver.driver_id == driver_id)
+            .and_then(|driver| position_at(driver, assigned_at))
+            .map_or(0.0, |position| haversine_km(position, job.pickup));
+        let busy_until = job.completed_at.unwrap_or(assigned_at + Duration::minutes(job.duration_min as i64));
+        tally.assign(driver_id, job.created_at, assigned_at, busy_until, pickup_km, job.fare);
+    }
+    tally.report("historical", day)
+}
+
+/// Replays the day with `scenario`'s settings
+pub fn simulate(day: &SimulationDay, scenario: &SimulationScenario, config: &SimulatorConfig) -> SimulationReport {
+    let mut dispatch = config.defaults.with_override(&scenario.dispatch);
+    if let Some(weight) = scenario.reliability_weight {
+        dispatch.reliability_weight = weight.clamp(0.0, 1.0);
+    }
+    let currency = scenario.pricing.as_ref().map_or(day.currency, |pricing| pricing.currency);
+    let mut tally = Tally::new(day, currency);
+    let mut drivers: Vec<DriverState> = day.drivers.iter().map(DriverState::new).collect();
+
+    for job in &day.jobs {
+        let mut at = job.created_at;
+        let assignment = loop {
+            if at > job.expires_at {
+                break None;
+            }
+            let candidates: Vec<DispatchCandidate> = drivers.iter()
+                .filter(|driver| driver.available_at(at))
+                .filter_map(|driver| {
+                    let distance_km = haversine_km(driver.position(at)?, job.pickup);
+                    (distance_km <= dispatch.search_radius_km).then(|| DispatchCandidate {
+                        driver_id: driver.recorded.driver_id.clone(),
+                        distance_km,
+                        reliability_score: driver.recorded.reliability_score,
+                    })
+                })
+                .collect();
+            let ranked = rank_candidates(candidates, &dispatch);
+            let (taken, answered_at) = offer(job, &ranked, at, scenario.strategy, &dispatch, config);
+            match taken {
+                Some(candidate) => break Some((candidate, answered_at)),
+                None => at = answered_at.max(at) + Duration::seconds(config.retry_seconds),
+            }
+        };
+        let Some((candidate, assigned_at)) = assignment else {
+            continue;
+        };
+
+        let to_pickup = Duration::seconds((candidate.distance_km / config.pickup_speed_kmh * 3600.0) as i64);
+        let busy_until = assigned_at + to_pickup + Duration::minutes(job.duration_min as i64);
+        let fare = match &scenario.pricing {
+            Some(pricing) => fare(job, pricing),
+            None => job.fare,
+        };
+        tally.assign(&candidate.driver_id, job.created_at, assigned_at, busy_until, candidate.distance_km, fare);
+        if let Some(driver) = drivers.iter_mut().find(|driver| driver.recorded.driver_id == candidate.driver_id) {
+            driver.free_at = busy_until;
+            driver.parked = Some(job.dropoff);
+        }
+    }
+    tally.report(&scenario.name, day)
+}
+
+// Who takes the job from one round of offers and when the round ends
+fn offer(
+    job: &SimulatedJob,
+    ranked: &[DispatchCandidate],
+    at: DateTime<Utc>,
+    strategy: OfferStrategy,
+    dispatch: &DispatchConfig,
+    config: &SimulatorConfig,
+) -> (Option<DispatchCandidate>, DateTime<Utc>) {
+    let answer = |candidate: &DispatchCandidate| {
+        if job.rejected_by.contains(&candidate.driver_id) {
+            Err(config.reject_seconds)
+        } else if job.ignored_by.contains(&candidate.driver_id) {
+            Err(dispatch.offer_timeout_seconds)
+        } else {
+            Ok(config.accept_seconds)
+        }
+    };
+    match strategy {
+        OfferStrategy::Sequential => {
+            let mut at = at;
+            for candidate in ranked {
+                match answer(candidate) {
+                    Ok(seconds) => return (Some(candidate.clone()), at + Duration::seconds(seconds)),
+                    Err(seconds) => at += Duration::seconds(seconds),
+                }
+            }
+            (None, at)
+        }
+        OfferStrategy::Broadcast => {
+            let taken = ranked.iter().find(|candidate| answer(candidate).is_ok()).cloned();
+            let seconds = match &taken {
+                Some(_) => config.accept_seconds,
+                None => ranked.iter().filter_map(|candidate| answer(candidate).err()).max().unwrap_or(0),
+            };
+            (taken, at + Duration::seconds(seconds))
+        }
+    }
+}
+
+/// Base, distance and time fare plus the service fee; surcharges and taxes are the same
+/// under any rates and are left out
+pub fn fare(job: &SimulatedJob, pricing: &PricingConfig) -> Money {
+    let base_fare = match job.priority {
+        JobPriority::Standard => pricing.base_fare_standard,
+        JobPriority::Express => pricing.base_fare_express,
+        JobPriority::SameDay => pricing.base_fare_same_day,
+        JobPriority::Emergency => pricing.base_fare_emergency,
+    };
+    let fare = base_fare + job.distance_km * pricing.per_km + job.duration_min as f64 * pricing.per_minute;
+    Money::from_major(fare * (1.0 + pricing.service_fee_rate), pricing.currency)
+}
+
+fn position_at(driver: &SimulatedDriver, at: DateTime<Utc>) -> Option<(f64, f64)> {
+    let position = driver.positions.iter().rev().find(|position| position.at <= at).or(driver.positions.first())?;
+    Some((position.latitude, position.longitude))
+}
+
+struct DriverState<'a> {
+    recorded: &'a SimulatedDriver,
+    free_at: DateTime<Utc>,
+    parked: Option<(f64, f64)>, // At the last simulated dropoff, rather than where they were recorded
+}
+
+impl<'a> DriverState<'a> {
+    fn new(recorded: &'a SimulatedDriver) -> Self {
+        Self { recorded, free_at: recorded.online_from, parked: None }
+    }
+
+    fn available_at(&self, at: DateTime<Utc>) -> bool {
+        self.free_at <= at && self.recorded.online_from <= at && at <= self.recorded.online_until
+    }
+
+    fn position(&self, at: DateTime<Utc>) -> Option<(f64, f64)> {
+        self.parked.or_else(|| position_at(self.recorded, at))
+    }
+}
+
+struct Tally {
+    jobs: usize,
+    waits: Vec<i64>,
+    pickup_km: f64,
+    busy_seconds: HashMap<DriverId, i64>,
+    online_seconds: HashMap<DriverId, i64>,
+    revenue: Money,
+}
+
+impl Tally {
+    fn new(day: &SimulationDay, currency: Currency) -> Self {
+        Self {
+            jobs: day.jobs.len(),
+            waits: Vec::new(),
+            pickup_km: 0.0,
+            busy_seconds: HashMap::new(),
+            online_seconds: day.drivers.iter()
+                .map(|driver| (driver.driver_id.clone(), (driver.online_until - driver.online_from).num_seconds()))
+                .collect(),
+            revenue: Money::zero(currency),
+        }
+    }
+
+    fn assign(&mut self, driver_id: &DriverId, created_at: DateTime<Utc>, assigned_at: DateTime<Utc>, busy_until: DateTime<Utc>, pickup_km: f64, fare: Money) {
+        self.waits.push((assigned_at - created_at).num_seconds().max(0));
+        self.pickup_km += pickup_km;
+        *self.busy_seconds.entry(driver_id.clone()).or_default() += (busy_until - assigned_at).num_seconds().max(0);
+        self.revenue += fare;
+    }
+
+    fn report(mut self, scenario: &str, day: &SimulationDay) -> SimulationReport {
+        self.waits.sort_unstable();
+        let assigned = self.waits.len();
+        let mean = |total: f64| if assigned == 0 { 0.0 } else { total / assigned as f64 };
+        // Busy time past the end of a driver's recorded shift still counts as time online
+        let utilization: Vec<f64> = self.online_seconds.iter()
+            .map(|(driver_id, online)| {
+                let busy = self.busy_seconds.get(driver_id).copied().unwrap_or(0);
+                if busy == 0 { 0.0 } else { busy as f64 / busy.max(*online) as f64 }
+            })
+            .collect();
+        SimulationReport {
+            scenario: scenario.to_string(),
+            jobs: self.jobs,
+            assigned,
+            unassigned: day.jobs.len() - assigned,
+            mean_assignment_seconds: mean(self.waits.iter().sum::<i64>() as f64),
+            p90_assignment_seconds: self.waits.get((assigned * 9).div_ceil(10).saturating_sub(1)).copied().unwrap_or(0),
+            mean_pickup_km: mean(self.pickup_km),
+            drivers_used: self.busy_seconds.len(),
+            driver_utilization: if utilization.is_empty() { 0.0 } else { utilization.iter().sum::<f64>() / utilization.len() as f64 },
+            revenue: self.revenue,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    use crate::models::{dispatch::DispatchOverride, ids::JobId};
+
+    fn driver(position: (f64, f64), reliability_score: f32, start: DateTime<Utc>) -> SimulatedDriver {
+        SimulatedDriver {
+            driver_id: DriverId::generate(),
+            reliability_score,
+            online_from: start,
+            online_until: start + Duration::hours(8),
+            positions: vec![DriverPosition { at: start, latitude: position.0, longitude: position.1 }],
+        }
+    }
+
+    fn job(created_at: DateTime<Utc>, pickup: (f64, f64)) -> SimulatedJob {
+        SimulatedJob {
+            job_id: JobId::generate(),
+            priority: JobPriority::Standard,
+            created_at,
+            expires_at: created_at + Duration::minutes(30),
+            pickup,
+            dropoff: (pickup.0 + 0.05, pickup.1),
+            distance_km: 5.5,
+            duration_min: 20,
+            rejected_by: Vec::new(),
+            ignored_by: Vec::new(),
+            driver_id: None,
+            assigned_at: None,
+            completed_at: None,
+            fare: Money::from_major(30.0, Currency::GHS),
+        }
+    }
+
+    #[test]
+    fn test_replay_compares_strategies_and_pricing() {
+        let start = Utc.with_ymd_and_hms(2025, 9, 1, 8, 0, 0).unwrap();
+        let accra = (5.6037, -0.1870);
+        // Closest but let the first job's offer lapse; a little further out and reliable
+        let near = driver((5.6040, -0.1870), 40.0, start);
+        let further = driver((5.6200, -0.1870), 100.0, start);
+        let mut first = job(start + Duration::minutes(5), accra);
+        first.ignored_by = vec![near.driver_id.clone()];
+        let out_of_range = job(start + Duration::minutes(6), (6.6885, -1.6244)); // Kumasi
+        let day = SimulationDay {
+            day: start.date_naive(),
+            tenant_id: "default".to_string(),
+            currency: Currency::GHS,
+            jobs: vec![first, out_of_range],
+            drivers: vec![near, further],
+        };
+        let config = SimulatorConfig::default();
+
+        let nearest = SimulationScenario {
+            name: "nearest".to_string(),
+            strategy: OfferStrategy::Sequential,
+            dispatch: DispatchOverride::default(),
+            reliability_weight: Some(0.0),
+            pricing: None,
+        };
+        let broadcast = SimulationScenario {
+            name: "broadcast".to_string(),
+            strategy: OfferStrategy::Broadcast,
+            pricing: Some(PricingConfig { per_km: 5.0, ..Default::default() }),
+            ..nearest.clone()
+        };
+        let report = compare(&day, &[nearest, broadcast], &config);
+        assert_eq!(report.historical.assigned, 0);
+
+        // The nearest driver's offer times out before the next one accepts
+        let nearest = &report.scenarios[0];
+        assert_eq!((nearest.assigned, nearest.unassigned), (1, 1));
+        assert_eq!(nearest.p90_assignment_seconds, config.defaults.offer_timeout_seconds + config.accept_seconds);
+        assert_eq!(nearest.revenue, Money::from_major(30.0, Currency::GHS));
+
+        // Offered to both at once, the willing driver takes it straight away, at the new rates
+        let broadcast = &report.scenarios[1];
+        assert_eq!(broadcast.p90_assignment_seconds, config.accept_seconds);
+        assert_eq!(broadcast.revenue, Money::from_major((15.0 + 5.5 * 5.0 + 20.0 * 0.2) * 1.1, Currency::GHS));
+        assert_eq!(broadcast.drivers_used, 1);
+        assert!(broadcast.driver_utilization > 0.0 && broadcast.driver_utilization < 0.1);
+        assert!(report.to_string().contains("broadcast"));
+    }
+}
@@ -0,0 +1,83 @@
+// src/services/sms.rs
+// Text messages to phone numbers that have no app or account behind them, such as a
+// delivery's recipient. Sent through an HTTP SMS gateway when one is configured; otherwise
+// they are only logged.
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+use tracing;
+
+use crate::errors::SparrowError as AppError;
+
+#[async_trait]
+pub trait SmsSender: Send + Sync {
+    async fn send(&self, to: &str, body: &str) -> Result<(), AppError>;
+}
+
+// POSTs `{"from", "to", "body"}` with the gateway's bearer token
+pub struct HttpSmsGateway {
+    url: String,
+    token: String,
+    sender_id: String,
+    client: reqwest::Client,
+}
+
+impl HttpSmsGateway {
+    pub fn new(url: String, token: String, sender_id: String) -> Self {
+        Self { url, token, sender_id, client: reqwest::Client::new() }
+    }
+}
+
+#[async_trait]
+impl SmsSender for HttpSmsGateway {
+    async fn send(&self, to: &str, body: &str) -> Result<(), AppError> {
+        let response = self.client.post(&self.url)
+            .bearer_auth(&self.token)
+            .json(&json!({ "from": self.sender_id, "to": to, "body": body }))
+            .send()
+            .await
+            .map_err(|e| AppError::HttpClient(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(AppError::HttpClient(format!("SMS gateway rejected message: {}", response.status())));
+        }
+        Ok(())
+    }
+}
+
+pub struct MockSmsSender;
+
+#[async_trait]
+impl SmsSender for MockSmsSender {
+    async fn send(&self, to: &str, _body: &str) -> Result<(), AppError> {
+        tracing::info!("[MOCK] Would text {}", mask_phone(to));
+        Ok(())
+    }
+}
+
+/// The gateway configured through SMS_GATEWAY_URL and SMS_GATEWAY_TOKEN, sending as
+/// SMS_SENDER_ID; the mock without them
+pub fn sms_sender_from_env() -> Arc<dyn SmsSender> {
+    let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+    match (var("SMS_GATEWAY_URL"), var("SMS_GATEWAY_TOKEN")) {
+        (Some(url), Some(token)) => Arc::new(HttpSmsGateway::new(url, token, var("SMS_SENDER_ID").unwrap_or_else(|| "Sparrow".to_string()))),
+        _ => {
+            tracing::warn!("SMS_GATEWAY_URL or SMS_GATEWAY_TOKEN not set, texts will only be logged");
+            Arc::new(MockSmsSender)
+        }
+    }
+}
+
+/// All but the last three digits hidden, for logs and anything shown to the driver
+pub fn mask_phone(phone: &str) -> String {
+    let digits = phone.chars().filter(char::is_ascii_digit).count();
+    let mut seen = 0;
+    phone.chars()
+        .map(|c| {
+            if !c.is_ascii_digit() {
+                return c;
+            }
+            seen += 1;
+            if seen + 3 > digits { c } else { '•' }
+        })
+        .collect()
+}
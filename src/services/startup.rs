@@ -0,0 +1,207 @@
+// src/services/startup.rs
+// Boot-time checks of what the app depends on, made in order. Each dependency is tried a
+// bounded number of times with exponential backoff, so Redis restarting alongside the app
+// doesn't kill it. A required dependency that never comes up fails boot, but only after
+// the rest have been checked, so the error names everything that is wrong at once. An
+// optional one (FCM, Ably) being down leaves the app running degraded.
+use chrono::Utc;
+use std::fmt;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tracing;
+
+use crate::models::startup::{DependencyCheck, DependencyStatus, StartupReport};
+
+pub const FCM_PROBE_URL: &str = "https://fcm.googleapis.com/";
+pub const ABLY_PROBE_URL: &str = "https://rest.ably.io/time";
+
+#[derive(Debug, Clone)]
+pub struct StartupConfig {
+    pub required_attempts: u32,
+    pub optional_attempts: u32, // Fewer, as boot goes ahead without them anyway
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub attempt_timeout_seconds: u64,
+}
+
+impl Default for StartupConfig {
+    fn default() -> Self {
+        Self {
+            required_attempts: 6,
+            optional_attempts: 2,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 8000,
+            attempt_timeout_seconds: 5,
+        }
+    }
+}
+
+impl StartupConfig {
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let defaults = Self::default();
+        Self {
+            required_attempts: var("STARTUP_ATTEMPTS").and_then(|v| v.parse().ok()).unwrap_or(defaults.required_attempts),
+            attempt_timeout_seconds: var("STARTUP_ATTEMPT_TIMEOUT_SECONDS").and_then(|v| v.parse().ok()).unwrap_or(defaults.attempt_timeout_seconds),
+            ..defaults
+        }
+    }
+
+    // Doubles from `initial_backoff_ms` up to `max_backoff_ms`
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+        Duration::from_millis(self.initial_backoff_ms.saturating_mul(factor).min(self.max_backoff_ms))
+    }
+}
+
+/// Boot failed on required dependencies; the report says which and why
+#[derive(Debug)]
+pub struct StartupFailed(pub StartupReport);
+
+impl fmt::Display for StartupFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = self.0.failed().iter().map(|check| check.name.as_str()).collect();
+        write!(f, "Required dependencies unavailable: {}\n{}", names.join(", "), self.0)
+    }
+}
+
+impl std::error::Error for StartupFailed {}
+
+pub struct StartupChecks {
+    config: StartupConfig,
+    checks: Vec<DependencyCheck>,
+    started_at: chrono::DateTime<Utc>,
+}
+
+impl StartupChecks {
+    pub fn new(config: StartupConfig) -> Self {
+        Self {
+            config,
+            checks: Vec::new(),
+            started_at: Utc::now(),
+        }
+    }
+
+    /// Connects to a dependency boot can't do without
+    pub async fn require<T, E, F, Fut>(&mut self, name: &str, connect: F) -> Option<T>
+    where
+        E: fmt::Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        self.attempt(name, true, self.config.required_attempts, connect).await
+    }
+
+    /// Connects to a dependency the app can run without
+    pub async fn check<T, E, F, Fut>(&mut self, name: &str, connect: F) -> Option<T>
+    where
+        E: fmt::Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        self.attempt(name, false, self.config.optional_attempts, connect).await
+    }
+
+    /// The report, or the failure when a required dependency never came up
+    pub fn finish(self) -> Result<StartupReport, StartupFailed> {
+        let report = StartupReport {
+            degraded: self.checks.iter().any(|check| !check.required && !check.is_up()),
+            checks: self.checks,
+            started_at: self.started_at,
+            finished_at: Utc::now(),
+        };
+        if !report.failed().is_empty() {
+            return Err(StartupFailed(report));
+        }
+        if report.degraded {
+            tracing::warn!("Starting degraded. {}", report);
+        } else {
+            tracing::info!("{}", report);
+        }
+        Ok(report)
+    }
+
+    async fn attempt<T, E, F, Fut>(&mut self, name: &str, required: bool, attempts: u32, mut connect: F) -> Option<T>
+    where
+        E: fmt::Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let started = Instant::now();
+        let timeout = Duration::from_secs(self.config.attempt_timeout_seconds);
+        let attempts = attempts.max(1);
+        let mut last_error = String::new();
+        for attempt in 1..=attempts {
+            let error = match tokio::time::timeout(timeout, connect()).await {
+                Ok(Ok(value)) => {
+                    self.record(name, required, DependencyStatus::Up, attempt, started, None);
+                    return Some(value);
+                }
+                Ok(Err(e)) => e.to_string(),
+                Err(_) => format!("no answer within {} s", timeout.as_secs()),
+            };
+            if attempt < attempts {
+                let backoff = self.config.backoff(attempt);
+                tracing::warn!("{} unavailable (attempt {} of {}), retrying in {} ms: {}", name, attempt, attempts, backoff.as_millis(), error);
+                tokio::time::sleep(backoff).await;
+            }
+            last_error = error;
+        }
+        self.record(name, required, DependencyStatus::Down, attempts, started, Some(last_error));
+        None
+    }
+
+    fn record(&mut self, name: &str, required: bool, status: DependencyStatus, attempts: u32, started: Instant, error: Option<String>) {
+        self.checks.push(DependencyCheck {
+            name: name.to_string(),
+            required,
+            status,
+            attempts,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            error,
+        });
+    }
+}
+
+/// Whether `url` answers at all; any HTTP response, errors included, counts
+pub async fn probe(client: &reqwest::Client, url: &str) -> Result<(), reqwest::Error> {
+    client.get(url).send().await.map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn test_checks_retry_then_report_every_dependency() {
+        let config = StartupConfig { initial_backoff_ms: 1, max_backoff_ms: 2, ..Default::default() };
+        let mut checks = StartupChecks::new(config);
+
+        // Comes up on the third try
+        let tries = AtomicU32::new(0);
+        let redis = checks.require("redis", || async {
+            match tries.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err("connection refused"),
+                _ => Ok("connected"),
+            }
+        }).await;
+        assert_eq!(redis, Some("connected"));
+
+        let ably = checks.check("ably", || async { Err::<(), _>("dns error") }).await;
+        assert!(ably.is_none());
+        let report = checks.finish().unwrap();
+        assert!(report.degraded);
+        assert_eq!(report.checks[0].attempts, 3);
+        assert_eq!((report.checks[1].attempts, report.checks[1].error.as_deref()), (2, Some("dns error")));
+
+        // A required dependency that never answers fails boot, naming it
+        let mut checks = StartupChecks::new(StartupConfig { required_attempts: 2, initial_backoff_ms: 1, ..Default::default() });
+        checks.require("redis", || async { Err::<(), _>("connection refused") }).await;
+        checks.check("fcm", || async { Ok::<_, String>(()) }).await;
+        let failed = checks.finish().unwrap_err();
+        let message = failed.to_string();
+        assert!(message.starts_with("Required dependencies unavailable: redis"), "{}", message);
+        assert!(message.contains("fcm (optional): up"), "{}", message);
+    }
+}
@@ -0,0 +1,506 @@
+// src/services/status_feed.rs
+// Keeps live updates down to what consumers can use. A driver in transit reports every few
+// seconds; their customer gets at most one location frame per job per interval, carrying
+// every point since the last. Status changes for consumers with a webhook are held and POSTed
+// in bulk, with each job once at its latest state and nothing the consumer was already told.
+// Each consumer picks its own intervals.
+//
+// Pending frames and deliveries live in this process: with several instances each delivers
+// what passed through it.
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use ring::hmac;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        ids::{JobId, UserId},
+        job::{Job, JobStatus, LocationUpdate},
+        status_feed::{FeedSettings, StatusFeedDelivery, StatusFeedEvent, UpdateFeedSettingsRequest},
+    },
+    services::{
+        cache_service::CacheService,
+        realtime_publisher::{RealtimeChannel, RealtimeMessage, RealtimePublisher},
+        tenant_service::current_tenant_id,
+    },
+};
+
+pub const SIGNATURE_HEADER: &str = "X-Sparrow-Signature";
+const SECRET_PREFIX: &str = "whsec_";
+
+#[derive(Debug, Clone)]
+pub struct StatusFeedConfig {
+    pub location_interval_seconds: u64, // For consumers that haven't chosen
+    pub webhook_interval_seconds: u64,
+    pub min_interval_seconds: u64,      // Bounds on what a consumer may choose
+    pub max_interval_seconds: u64,
+    pub max_frame_points: usize,        // Oldest points are dropped from a frame beyond this
+    pub delivery_timeout_seconds: u64,
+}
+
+impl Default for StatusFeedConfig {
+    fn default() -> Self {
+        Self {
+            location_interval_seconds: 5,
+            webhook_interval_seconds: 30,
+            min_interval_seconds: 1,
+            max_interval_seconds: 300,
+            max_frame_points: 50,
+            delivery_timeout_seconds: 10,
+        }
+    }
+}
+
+impl StatusFeedConfig {
+    /// `STATUS_FEED_LOCATION_INTERVAL_SECONDS` and `STATUS_FEED_WEBHOOK_INTERVAL_SECONDS`
+    /// override the defaults consumers get
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let defaults = Self::default();
+        Self {
+            location_interval_seconds: var("STATUS_FEED_LOCATION_INTERVAL_SECONDS")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.location_interval_seconds),
+            webhook_interval_seconds: var("STATUS_FEED_WEBHOOK_INTERVAL_SECONDS")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.webhook_interval_seconds),
+            ..defaults
+        }
+    }
+}
+
+// Points waiting for the job's next location frame
+struct PendingFrame {
+    job: Job,
+    points: Vec<LocationUpdate>,
+    interval: Duration,
+    last_sent: Option<DateTime<Utc>>,
+}
+
+// Status changes waiting for the consumer's next webhook delivery
+struct PendingWebhook {
+    settings: FeedSettings,
+    events: Vec<StatusFeedEvent>, // One per job, in the order they first changed
+    suppressed: usize,
+    delivered: HashMap<JobId, StatusFeedEvent>, // Last state delivered, until the job ends
+    last_sent: Option<DateTime<Utc>>,
+}
+
+impl PendingWebhook {
+    fn queue(&mut self, mut event: StatusFeedEvent) {
+        let pending = self.events.iter().position(|pending| pending.job_id == event.job_id);
+        if event.location.is_none() {
+            // A status change keeps the last position we have for the job
+            let known = pending.map(|index| &self.events[index]).or(self.delivered.get(&event.job_id));
+            event.location = known.and_then(|known| known.location.clone());
+        }
+        match pending {
+            Some(index) => {
+                self.events[index] = event;
+                self.suppressed += 1;
+            }
+            None if self.delivered.get(&event.job_id).is_some_and(|delivered| same_state(delivered, &event)) => {
+                self.suppressed += 1;
+            }
+            None => self.events.push(event),
+        }
+    }
+
+    fn is_due(&self, now: DateTime<Utc>) -> bool {
+        !self.events.is_empty()
+            && self.last_sent.is_none_or(|sent| now - sent >= Duration::seconds(self.settings.webhook_interval_seconds as i64))
+    }
+}
+
+// Nothing a consumer would act on differs; the position only counts for those taking locations
+fn same_state(a: &StatusFeedEvent, b: &StatusFeedEvent) -> bool {
+    a.status == b.status && a.driver_id == b.driver_id && a.location == b.location
+}
+
+pub struct StatusFeedService {
+    cache_service: Arc<CacheService>,
+    realtime: Arc<dyn RealtimePublisher>,
+    client: reqwest::Client,
+    config: StatusFeedConfig,
+    // Keyed by tenant too, so each worker pass only flushes its own tenant
+    frames: Mutex<HashMap<(String, JobId), PendingFrame>>,
+    webhooks: Mutex<HashMap<(String, UserId), PendingWebhook>>,
+}
+
+impl StatusFeedService {
+    pub fn new(cache_service: Arc<CacheService>, realtime: Arc<dyn RealtimePublisher>, config: StatusFeedConfig) -> Self {
+        Self {
+            cache_service,
+            realtime,
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(config.delivery_timeout_seconds))
+                .build()
+                .unwrap_or_default(),
+            config,
+            frames: Mutex::new(HashMap::new()),
+            webhooks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The consumer's settings, or the defaults when they haven't set any
+    pub async fn settings(&self, consumer_id: &UserId) -> Result<FeedSettings, AppError> {
+        Ok(self.cache_service.get_feed_settings(consumer_id).await?.unwrap_or_else(|| FeedSettings {
+            consumer_id: consumer_id.clone(),
+            location_interval_seconds: self.config.location_interval_seconds,
+            webhook_url: None,
+            webhook_secret: generate_secret(),
+            webhook_interval_seconds: self.config.webhook_interval_seconds,
+            webhook_locations: false,
+            updated_at: Utc::now(),
+        }))
+    }
+
+    pub async fn update_settings(&self, consumer_id: &UserId, request: UpdateFeedSettingsRequest) -> Result<FeedSettings, AppError> {
+        let mut settings = self.settings(consumer_id).await?;
+        let bounds = self.config.min_interval_seconds..=self.config.max_interval_seconds;
+        for (field, value) in [
+            ("location_interval_seconds", request.location_interval_seconds),
+            ("webhook_interval_seconds", request.webhook_interval_seconds),
+        ] {
+            if let Some(value) = value && !bounds.contains(&value) {
+                return Err(AppError::validation_error(
+                    field,
+                    format!("Must be between {} and {} seconds", bounds.start(), bounds.end()),
+                ));
+            }
+        }
+        if let Some(url) = request.webhook_url {
+            let url = url.trim();
+            if !(url.is_empty() || url.starts_with("https://") || url.starts_with("http://")) {
+                return Err(AppError::validation_error("webhook_url", "Must be an http(s) URL"));
+            }
+            settings.webhook_url = Some(url.to_string()).filter(|url| !url.is_empty());
+        }
+        settings.location_interval_seconds = request.location_interval_seconds.unwrap_or(settings.location_interval_seconds);
+        settings.webhook_interval_seconds = request.webhook_interval_seconds.unwrap_or(settings.webhook_interval_seconds);
+        settings.webhook_locations = request.webhook_locations.unwrap_or(settings.webhook_locations);
+        settings.updated_at = Utc::now();
+        self.cache_service.cache_feed_settings(&settings).await?;
+
+        // Held deliveries go out under the new settings, or are dropped with the webhook
+        let key = (current_tenant_id(), consumer_id.clone());
+        let mut webhooks = self.webhooks.lock().unwrap();
+        match settings.webhook_url {
+            Some(_) => if let Some(pending) = webhooks.get_mut(&key) {
+                pending.settings = settings.clone();
+            },
+            None => {
+                webhooks.remove(&key);
+            }
+        }
+        Ok(settings)
+    }
+
+    /// A driver reported `points` while working `jobs`. Each job's customer gets them in a
+    /// frame now if their interval has passed, otherwise with the next one.
+    pub async fn record_locations(&self, jobs: &[Job], points: &[LocationUpdate]) -> Result<(), AppError> {
+        let Some(latest) = points.last() else {
+            return Ok(());
+        };
+        let tenant_id = current_tenant_id();
+        let now = Utc::now();
+        for job in jobs {
+            let settings = self.settings(&job.customer_id).await?;
+            let frame = {
+                let mut frames = self.frames.lock().unwrap();
+                let pending = frames.entry((tenant_id.clone(), job.id.clone())).or_insert_with(|| PendingFrame {
+                    job: job.clone(),
+                    points: Vec::new(),
+                    interval: Duration::zero(),
+                    last_sent: None,
+                });
+                pending.job = job.clone();
+                pending.interval = Duration::seconds(settings.location_interval_seconds as i64);
+                pending.points.extend_from_slice(points);
+                let overflow = pending.points.len().saturating_sub(self.config.max_frame_points);
+                pending.points.drain(..overflow);
+                take_frame(pending, now)
+            };
+            if let Some(frame) = frame {
+                self.publish_frame(frame).await;
+            }
+
+            if settings.webhook_url.is_some() && settings.webhook_locations && job.status == JobStatus::InTransit {
+                self.queue(settings, event_for(job, Some(latest.clone())));
+            }
+        }
+        Ok(())
+    }
+
+    /// Queue the job's new status for its customer's webhook, if they have one
+    pub async fn record_status(&self, job: &Job) -> Result<(), AppError> {
+        if job.status.is_terminal() {
+            // No more points are coming for a finished job
+            self.frames.lock().unwrap().remove(&(current_tenant_id(), job.id.clone()));
+        }
+        let settings = self.settings(&job.customer_id).await?;
+        if settings.webhook_url.is_none() {
+            return Ok(());
+        }
+        self.queue(settings, event_for(job, None));
+        Ok(())
+    }
+
+    fn queue(&self, settings: FeedSettings, event: StatusFeedEvent) {
+        let mut webhooks = self.webhooks.lock().unwrap();
+        let pending = webhooks.entry((current_tenant_id(), settings.consumer_id.clone())).or_insert_with(|| PendingWebhook {
+            settings: settings.clone(),
+            events: Vec::new(),
+            suppressed: 0,
+            delivered: HashMap::new(),
+            last_sent: None,
+        });
+        pending.settings = settings;
+        pending.queue(event);
+    }
+
+    /// Send the current tenant's frames and deliveries whose interval has passed. Returns
+    /// the number of webhook deliveries made.
+    pub async fn flush(&self) -> Result<usize, AppError> {
+        let now = Utc::now();
+        for frame in self.take_due_frames(now) {
+            self.publish_frame(frame).await;
+        }
+
+        let mut delivered = 0;
+        for (settings, delivery) in self.take_due_deliveries(now) {
+            match self.deliver(&settings, &delivery).await {
+                Ok(()) => delivered += 1,
+                Err(e) => {
+                    tracing::warn!("Status webhook to {} failed, retrying next interval: {}", settings.consumer_id, e);
+                    self.requeue(delivery);
+                }
+            }
+        }
+        Ok(delivered)
+    }
+
+    fn take_due_frames(&self, now: DateTime<Utc>) -> Vec<Frame> {
+        let tenant_id = current_tenant_id();
+        let mut frames = self.frames.lock().unwrap();
+        // A frame that has had nothing new for a whole interval is finished with
+        frames.retain(|(tenant, _), pending| {
+            *tenant != tenant_id
+                || !pending.points.is_empty()
+                || pending.last_sent.is_none_or(|sent| now - sent < pending.interval * 2)
+        });
+        frames
+            .iter_mut()
+            .filter(|((tenant, _), _)| *tenant == tenant_id)
+            .filter_map(|(_, pending)| take_frame(pending, now))
+            .collect()
+    }
+
+    fn take_due_deliveries(&self, now: DateTime<Utc>) -> Vec<(FeedSettings, StatusFeedDelivery)> {
+        let tenant_id = current_tenant_id();
+        let mut webhooks = self.webhooks.lock().unwrap();
+        webhooks
+            .iter_mut()
+            .filter(|((tenant, _), pending)| *tenant == tenant_id && pending.is_due(now))
+            .map(|(_, pending)| {
+                pending.last_sent = Some(now);
+                let events = std::mem::take(&mut pending.events);
+                for event in &events {
+                    if event.status.is_terminal() {
+                        pending.delivered.remove(&event.job_id);
+                    } else {
+                        pending.delivered.insert(event.job_id.clone(), event.clone());
+                    }
+                }
+                let delivery = StatusFeedDelivery {
+                    consumer_id: pending.settings.consumer_id.clone(),
+                    sent_at: now,
+                    events,
+                    suppressed: std::mem::take(&mut pending.suppressed),
+                };
+                (pending.settings.clone(), delivery)
+            })
+            .collect()
+    }
+
+    // A failed delivery's events go back in front of anything newer for the same jobs
+    fn requeue(&self, delivery: StatusFeedDelivery) {
+        let mut webhooks = self.webhooks.lock().unwrap();
+        let Some(pending) = webhooks.get_mut(&(current_tenant_id(), delivery.consumer_id)) else {
+            return;
+        };
+        for event in &delivery.events {
+            pending.delivered.remove(&event.job_id);
+        }
+        let newer = std::mem::replace(&mut pending.events, delivery.events);
+        pending.suppressed += delivery.suppressed;
+        for event in newer {
+            pending.queue(event);
+        }
+    }
+
+    async fn deliver(&self, settings: &FeedSettings, delivery: &StatusFeedDelivery) -> Result<(), AppError> {
+        let Some(url) = &settings.webhook_url else {
+            return Ok(());
+        };
+        let body = serde_json::to_vec(delivery)?;
+        let response = self.client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header(SIGNATURE_HEADER, sign(&settings.webhook_secret, &body))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| AppError::service_unavailable(format!("Status webhook: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::service_unavailable(format!("Status webhook returned {}", response.status())));
+        }
+        tracing::debug!("Delivered {} job updates to {} ({} suppressed)", delivery.events.len(), settings.consumer_id, delivery.suppressed);
+        Ok(())
+    }
+
+    async fn publish_frame(&self, frame: Frame) {
+        let message = RealtimeMessage {
+            name: "driver_location".to_string(),
+            data: serde_json::json!({
+                "job_id": frame.job.id,
+                "driver_id": frame.job.driver_id,
+                "status": frame.job.status,
+                "points": frame.points,
+            }),
+        };
+        if let Err(e) = self.realtime.publish(&RealtimeChannel::User(frame.job.customer_id.clone()), message).await {
+            tracing::warn!("Failed to publish driver location for job {}: {}", frame.job.id, e);
+        }
+    }
+}
+
+struct Frame {
+    job: Job,
+    points: Vec<LocationUpdate>,
+}
+
+fn take_frame(pending: &mut PendingFrame, now: DateTime<Utc>) -> Option<Frame> {
+    let due = pending.last_sent.is_none_or(|sent| now - sent >= pending.interval);
+    if !due || pending.points.is_empty() {
+        return None;
+    }
+    pending.last_sent = Some(now);
+    Some(Frame { job: pending.job.clone(), points: std::mem::take(&mut pending.points) })
+}
+
+fn event_for(job: &Job, location: Option<LocationUpdate>) -> StatusFeedEvent {
+    StatusFeedEvent {
+        job_id: job.id.clone(),
+        tracking_code: job.tracking_code.clone(),
+        status: job.status.clone(),
+        driver_id: job.driver_id.clone(),
+        location,
+        updated_at: job.updated_at,
+    }
+}
+
+/// `sha256=` and the hex HMAC of the body under the consumer's webhook secret
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let tag = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()), body);
+    let hex: String = tag.as_ref().iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256={}", hex)
+}
+
+fn generate_secret() -> String {
+    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    let mut rng = rand::rng();
+    let body: String = (0..32).map(|_| CHARSET[rng.random_range(0..CHARSET.len())] as char).collect();
+    format!("{}{}", SECRET_PREFIX, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::user::UserType,
+        services::{job_service::JobOperations, realtime_bus::user_topic, realtime_publisher::SocketPublisher, user_service::UserOperations},
+    };
+
+    fn point(seconds: i64) -> LocationUpdate {
+        LocationUpdate {
+            latitude: 5.6 + seconds as f64 * 0.0001,
+            longitude: -0.18,
+            timestamp: Utc::now() + Duration::seconds(seconds),
+            accuracy: None,
+            heading: None,
+            speed: None,
+            is_mock: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_locations_and_webhook_updates_are_coalesced_per_consumer() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(102);
+        // Its own service, so the running flusher can't take what the test expects
+        let feed = StatusFeedService::new(
+            state.cache_service.clone(),
+            Arc::new(SocketPublisher::new(state.realtime_bus.clone())),
+            StatusFeedConfig::default(),
+        );
+
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let created = state.job_service.create_job(faker.job_request(&customer.id)).await.unwrap();
+        let job = &mut state.cache_service.load_job(&created.id).await.unwrap().unwrap();
+        job.status = JobStatus::InTransit;
+        let (_, mut socket) = state.realtime_bus.subscribe(&user_topic(&customer.id));
+
+        // The first points go out at once; the next wait out the interval and go together
+        feed.record_locations(std::slice::from_ref(job), &[point(0)]).await.unwrap();
+        feed.record_locations(std::slice::from_ref(job), &[point(1)]).await.unwrap();
+        feed.record_locations(std::slice::from_ref(job), &[point(2)]).await.unwrap();
+        let first: RealtimeMessage = serde_json::from_slice(&socket.try_recv().unwrap()).unwrap();
+        assert_eq!(first.name, "driver_location");
+        assert_eq!(first.data["points"].as_array().unwrap().len(), 1);
+        assert!(socket.try_recv().is_err());
+        assert!(feed.take_due_frames(Utc::now()).is_empty());
+        let held = feed.take_due_frames(Utc::now() + Duration::seconds(5));
+        assert_eq!(held.len(), 1);
+        assert_eq!(held[0].points.len(), 2);
+
+        // Without a webhook nothing is held for delivery
+        feed.record_status(job).await.unwrap();
+        assert!(feed.take_due_deliveries(Utc::now()).is_empty());
+
+        assert!(feed.update_settings(&customer.id, UpdateFeedSettingsRequest { webhook_interval_seconds: Some(0), ..Default::default() }).await.is_err());
+        let settings = feed.update_settings(&customer.id, UpdateFeedSettingsRequest {
+            webhook_url: Some("https://merchant.example/hooks/sparrow".to_string()),
+            webhook_interval_seconds: Some(30),
+            ..Default::default()
+        }).await.unwrap();
+        assert!(settings.webhook_secret.starts_with(SECRET_PREFIX));
+
+        // Repeats of a job fold into one event
+        feed.record_status(job).await.unwrap();
+        feed.record_status(job).await.unwrap();
+        let now = Utc::now();
+        let deliveries = feed.take_due_deliveries(now);
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].1.events.len(), 1);
+        assert_eq!(deliveries[0].1.suppressed, 1);
+
+        // Nothing new since the last delivery, then a change held until the interval passes
+        feed.record_status(job).await.unwrap();
+        job.status = JobStatus::ArrivedAtDropoff;
+        feed.record_status(job).await.unwrap();
+        assert!(feed.take_due_deliveries(now + Duration::seconds(10)).is_empty());
+        let deliveries = feed.take_due_deliveries(now + Duration::seconds(30));
+        assert_eq!(deliveries[0].1.events.len(), 1);
+        assert_eq!(deliveries[0].1.events[0].status, JobStatus::ArrivedAtDropoff);
+        assert_eq!(deliveries[0].1.suppressed, 1);
+
+        assert!(!sign(&settings.webhook_secret, b"{}").is_empty());
+    }
+}
@@ -0,0 +1,67 @@
+// src/services/tax.rs
+// Tax schedules per tenant and region. A new schedule can only start now or later, and every
+// job keeps the levies it was priced with, so a rate change only ever reaches new jobs.
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::tax::{schedule_at, CreateTaxScheduleRequest, TaxSchedule},
+    services::{cache_service::CacheService, region::RegionRegistry},
+};
+
+pub struct TaxEngine {
+    cache_service: Arc<CacheService>,
+    regions: Arc<RegionRegistry>,
+}
+
+impl TaxEngine {
+    pub fn new(cache_service: Arc<CacheService>, regions: Arc<RegionRegistry>) -> Self {
+        Self { cache_service, regions }
+    }
+
+    /// Every schedule, oldest first; the region's standard levies until a tenant sets its own
+    pub async fn schedules(&self) -> Result<Vec<TaxSchedule>, AppError> {
+        Ok(self.cache_service.get_tax_schedules().await?
+            .unwrap_or_else(|| vec![self.regions.current().taxes.clone()]))
+    }
+
+    pub async fn schedule_at(&self, at: DateTime<Utc>) -> Result<TaxSchedule, AppError> {
+        let schedules = self.schedules().await?;
+        Ok(schedule_at(&schedules, at).cloned().unwrap_or_else(|| self.regions.current().taxes.clone()))
+    }
+
+    pub async fn add_schedule(&self, request: CreateTaxScheduleRequest) -> Result<Vec<TaxSchedule>, AppError> {
+        let now = Utc::now();
+        let effective_from = request.effective_from.unwrap_or(now);
+        if effective_from < now {
+            return Err(AppError::validation_error("effective_from", "Must not be in the past"));
+        }
+        if request.levies.is_empty() {
+            return Err(AppError::validation_error("levies", "At least one levy is required"));
+        }
+        let mut levies = request.levies;
+        let mut codes = HashSet::new();
+        for (index, levy) in levies.iter_mut().enumerate() {
+            levy.code = levy.code.trim().to_ascii_uppercase();
+            if levy.code.is_empty() || !codes.insert(levy.code.clone()) {
+                return Err(AppError::validation_error(format!("levies[{}].code", index), "Must be present and unique"));
+            }
+            if !(0.0..=1.0).contains(&levy.rate) {
+                return Err(AppError::validation_error(format!("levies[{}].rate", index), "Must be between 0 and 1"));
+            }
+        }
+
+        // A later schedule for the same start replaces the earlier one
+        let mut schedules = self.schedules().await?;
+        schedules.retain(|schedule| schedule.effective_from != effective_from);
+        schedules.push(TaxSchedule { effective_from, levies });
+        schedules.sort_by_key(|schedule| schedule.effective_from);
+        self.cache_service.cache_tax_schedules(&schedules).await?;
+
+        tracing::info!("Tax schedule added, effective from {}", effective_from);
+        Ok(schedules)
+    }
+}
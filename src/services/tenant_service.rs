@@ -0,0 +1,248 @@
+// src/services/tenant_service.rs
+// Tenant registry and the per-request tenant context.
+//
+// The tenant for a request is held in a task-local set by the `resolve_tenant`
+// middleware. The cache layer reads it to namespace every tenant-owned key, so
+// services never have to pass a tenant around and cannot read across brands.
+use chrono::Utc;
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::tenant::{CreateTenantRequest, Tenant, DEFAULT_TENANT_ID},
+    services::{cache_service::CacheService, runtime_config::RuntimeConfigService},
+};
+
+tokio::task_local! {
+    static CURRENT_TENANT: String;
+}
+
+/// Tenant of the running request or worker pass; the default tenant outside any scope
+pub fn current_tenant_id() -> String {
+    CURRENT_TENANT
+        .try_with(|tenant_id| tenant_id.clone())
+        .unwrap_or_else(|_| DEFAULT_TENANT_ID.to_string())
+}
+
+/// Run `future` with `tenant_id` as the current tenant
+pub async fn with_tenant<F: Future>(tenant_id: String, future: F) -> F::Output {
+    CURRENT_TENANT.scope(tenant_id, future).await
+}
+
+pub struct TenantService {
+    cache_service: Arc<CacheService>,
+    runtime_config: Arc<RuntimeConfigService>, // Pricing for tenants that don't set their own
+}
+
+impl TenantService {
+    pub fn new(cache_service: Arc<CacheService>, runtime_config: Arc<RuntimeConfigService>) -> Self {
+        Self { cache_service, runtime_config }
+    }
+
+    pub async fn get_tenant(&self, tenant_id: &str) -> Result<Option<Tenant>, AppError> {
+        let tenant = self.cache_service.get_tenant(tenant_id).await?;
+        if tenant.is_none() && tenant_id == DEFAULT_TENANT_ID {
+            let pricing = self.runtime_config.current().config.pricing.clone();
+            return Ok(Some(Tenant { pricing, ..Tenant::default_tenant() }));
+        }
+        Ok(tenant)
+    }
+
+    // Settings of the tenant the current request belongs to
+    pub async fn current_tenant(&self) -> Result<Tenant, AppError> {
+        let tenant_id = current_tenant_id();
+        self.get_tenant(&tenant_id).await?
+            .ok_or_else(|| AppError::NotFound(format!("Tenant {} not found", tenant_id)))
+    }
+
+    pub async fn create_tenant(&self, request: CreateTenantRequest) -> Result<Tenant, AppError> {
+        if !is_valid_slug(&request.id) {
+            return Err(AppError::validation_error("id", "Use 2-32 lowercase letters, digits or dashes"));
+        }
+        if request.id == DEFAULT_TENANT_ID || self.cache_service.get_tenant(&request.id).await?.is_some() {
+            return Err(AppError::Conflict(format!("Tenant {} already exists", request.id)));
+        }
+
+        let hosts: Vec<String> = request.hosts.iter().map(|host| normalize_host(host)).collect();
+        for host in &hosts {
+            if let Some(owner) = self.cache_service.get_tenant_id_by_host(host).await? {
+                return Err(AppError::Conflict(format!("Host {} is already used by tenant {}", host, owner)));
+            }
+        }
+
+        // One set of rates per currency
+        let pricing = request.pricing.unwrap_or_else(|| self.runtime_config.current().config.pricing.clone());
+        let mut currencies = HashSet::from([pricing.currency]);
+        for (index, rates) in request.currency_pricing.iter().enumerate() {
+            if !currencies.insert(rates.currency) {
+                return Err(AppError::validation_error(
+                    format!("currency_pricing[{}].currency", index),
+                    format!("{} is already priced", rates.currency),
+                ));
+            }
+        }
+
+        let now = Utc::now();
+        let tenant = Tenant {
+            id: request.id,
+            name: request.name,
+            hosts,
+            pricing,
+            currency_pricing: request.currency_pricing,
+            service_regions: request.service_regions,
+            mqtt_enabled: request.mqtt_enabled,
+            mqtt_format: request.mqtt_format,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        };
+        self.cache_service.cache_tenant(&tenant).await?;
+
+        tracing::info!("Created tenant {} ({} hosts)", tenant.id, tenant.hosts.len());
+        Ok(tenant)
+    }
+
+    pub async fn list_tenants(&self) -> Result<Vec<Tenant>, AppError> {
+        let mut tenants = Vec::new();
+        for tenant_id in self.tenant_ids().await? {
+            if let Some(tenant) = self.get_tenant(&tenant_id).await? {
+                tenants.push(tenant);
+            }
+        }
+        Ok(tenants)
+    }
+
+    // Every tenant, default included - background workers run once per tenant
+    pub async fn tenant_ids(&self) -> Result<Vec<String>, AppError> {
+        let mut ids = self.cache_service.get_tenant_ids().await?;
+        if !ids.iter().any(|id| id == DEFAULT_TENANT_ID) {
+            ids.push(DEFAULT_TENANT_ID.to_string());
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    pub async fn resolve_host(&self, host: &str) -> Result<Option<String>, AppError> {
+        self.cache_service.get_tenant_id_by_host(&normalize_host(host)).await
+    }
+}
+
+fn is_valid_slug(id: &str) -> bool {
+    (2..=32).contains(&id.len())
+        && id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+// Lowercase and drop any port, so "Kwik.example.com:443" matches "kwik.example.com"
+pub fn normalize_host(host: &str) -> String {
+    host.split(':').next().unwrap_or_default().trim().to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ids::UserId, tenant::PricingConfig};
+    use crate::services::{cache_service::{CacheConfig, CacheKey}, realtime::FrameFormat, runtime_config::RuntimeConfigWatch};
+
+    fn tenant_request(id: &str, host: &str) -> CreateTenantRequest {
+        CreateTenantRequest {
+            id: id.to_string(),
+            name: id.to_uppercase(),
+            hosts: vec![host.to_string()],
+            pricing: Some(PricingConfig::default()),
+            currency_pricing: Vec::new(),
+            service_regions: Vec::new(),
+            mqtt_enabled: false,
+            mqtt_format: FrameFormat::Json,
+        }
+    }
+
+    #[test]
+    fn test_scoped_keys() {
+        let key = CacheKey::Simple("user:id:usr-1".to_string());
+        assert_eq!(key.scoped(DEFAULT_TENANT_ID).to_string(), "user:id:usr-1");
+        assert_eq!(key.scoped("kwik").to_string(), "t:kwik:user:id:usr-1");
+
+        let global = CacheKey::Global("tenants:all".to_string());
+        assert_eq!(global.scoped("kwik").to_string(), "tenants:all");
+    }
+
+    #[test]
+    fn test_normalize_host() {
+        assert_eq!(normalize_host("Kwik.Example.com:443"), "kwik.example.com");
+        assert_eq!(normalize_host(" sparrow.gh "), "sparrow.gh");
+    }
+
+    #[tokio::test]
+    async fn test_current_tenant_defaults_outside_scope() {
+        assert_eq!(current_tenant_id(), DEFAULT_TENANT_ID);
+        let inside = with_tenant("kwik".to_string(), async { current_tenant_id() }).await;
+        assert_eq!(inside, "kwik");
+    }
+
+    #[tokio::test]
+    async fn test_tenant_data_is_isolated() {
+        let cache = CacheService::new_memory(CacheConfig::default());
+
+        let user_id = UserId::generate();
+        with_tenant("kwik".to_string(), cache.cache_user_by_email("ama@example.com", &user_id)).await.unwrap();
+
+        let same = with_tenant("kwik".to_string(), cache.get_user_id_by_email("ama@example.com")).await.unwrap();
+        let other = with_tenant("swift".to_string(), cache.get_user_id_by_email("ama@example.com")).await.unwrap();
+        let default = cache.get_user_id_by_email("ama@example.com").await.unwrap();
+
+        assert_eq!(same, Some(user_id));
+        assert_eq!(other, None);
+        assert_eq!(default, None);
+    }
+
+    #[tokio::test]
+    async fn test_same_key_holds_separate_values_per_tenant() {
+        let cache = CacheService::new_memory(CacheConfig::default());
+
+        let (default_user, kwik_user) = (UserId::generate(), UserId::generate());
+        cache.cache_user_by_email("kofi@example.com", &default_user).await.unwrap();
+        with_tenant("kwik".to_string(), cache.cache_user_by_email("kofi@example.com", &kwik_user)).await.unwrap();
+
+        let default = cache.get_user_id_by_email("kofi@example.com").await.unwrap();
+        let kwik = with_tenant("kwik".to_string(), cache.get_user_id_by_email("kofi@example.com")).await.unwrap();
+
+        assert_eq!(default, Some(default_user));
+        assert_eq!(kwik, Some(kwik_user));
+    }
+
+    #[tokio::test]
+    async fn test_tenant_registry_is_shared() {
+        let service = TenantService::new(
+            Arc::new(CacheService::new_memory(CacheConfig::default())),
+            Arc::new(RuntimeConfigService::new(RuntimeConfigWatch::default())),
+        );
+
+        with_tenant("swift".to_string(), service.create_tenant(tenant_request("kwik", "Kwik.example.com")))
+            .await
+            .unwrap();
+
+        assert_eq!(service.resolve_host("kwik.example.com:443").await.unwrap().as_deref(), Some("kwik"));
+        let seen_from_other = with_tenant("swift".to_string(), service.get_tenant("kwik")).await.unwrap();
+        assert!(seen_from_other.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_create_tenant_rejects_taken_host() {
+        let service = TenantService::new(
+            Arc::new(CacheService::new_memory(CacheConfig::default())),
+            Arc::new(RuntimeConfigService::new(RuntimeConfigWatch::default())),
+        );
+
+        service.create_tenant(tenant_request("kwik", "kwik.example.com")).await.unwrap();
+        let result = service.create_tenant(tenant_request("swift", "KWIK.example.com")).await;
+
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        assert!(matches!(
+            service.create_tenant(tenant_request(DEFAULT_TENANT_ID, "other.example.com")).await,
+            Err(AppError::Conflict(_))
+        ));
+    }
+}
@@ -0,0 +1,415 @@
+// src/services/tracking_service.rs
+// The public tracking link, keyed by a job's tracking code. The dropoff contact, who often
+// has no account, can see where the delivery is and leave instructions, a safe-drop spot or
+// a later delivery time; each change is recorded on the job and sent to its driver.
+//
+// Customers can also share a trip: a link that only shows the delivery's progress and the
+// driver on a map, lasts a few hours at most, ends with the delivery and can be revoked.
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{Duration, Utc};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::sync::Arc;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{
+        dispatch::DriverSocketEvent,
+        ids::{JobId, UserId},
+        job::{
+            Job, JobEvent, JobEventType, RecipientPreferences, ShareTripRequest, SharedDriverLocation, SharedTripView, TrackingView,
+            TripShare, UpdateRecipientPreferencesRequest,
+        },
+    },
+    services::{
+        cache_service::CacheService,
+        driver_channel::DriverChannel,
+        messaging_service::{NotificationMessage, NotificationService},
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct TrackingConfig {
+    pub reschedule_window_hours: i64, // How far ahead the recipient may push delivery
+    pub max_text_length: usize,       // Instructions and safe-drop descriptions
+    pub share_minutes: i64,           // How long a share link lasts unless the customer says
+    pub max_share_minutes: i64,
+}
+
+impl Default for TrackingConfig {
+    fn default() -> Self {
+        Self {
+            reschedule_window_hours: 48,
+            max_text_length: 500,
+            share_minutes: 4 * 60,
+            max_share_minutes: 24 * 60,
+        }
+    }
+}
+
+pub struct TrackingService {
+    cache_service: Arc<CacheService>,
+    driver_channel: Arc<DriverChannel>,
+    notification_service: Arc<dyn NotificationService>,
+    random: SystemRandom,
+    config: TrackingConfig,
+}
+
+impl TrackingService {
+    pub fn new(
+        cache_service: Arc<CacheService>,
+        driver_channel: Arc<DriverChannel>,
+        notification_service: Arc<dyn NotificationService>,
+        config: TrackingConfig,
+    ) -> Self {
+        Self {
+            cache_service,
+            driver_channel,
+            notification_service,
+            random: SystemRandom::new(),
+            config,
+        }
+    }
+
+    async fn load_job(&self, tracking_code: &str) -> Result<Job, AppError> {
+        let not_found = || AppError::NotFound("No delivery with this tracking code".to_string());
+        let job_id = self.cache_service.get_job_id_by_tracking_code(tracking_code.trim()).await?
+            .ok_or_else(not_found)?;
+        self.cache_service.load_job(&job_id).await?.ok_or_else(not_found)
+    }
+
+    pub async fn track(&self, tracking_code: &str) -> Result<TrackingView, AppError> {
+        let job = self.load_job(tracking_code).await?;
+        let driver_name = match &job.driver_id {
+            Some(driver_id) => self.cache_service.get_driver(driver_id).await?.map(|driver| driver.first_name),
+            None => None,
+        };
+        Ok(TrackingView {
+            tracking_code: job.tracking_code,
+            status: job.status,
+            dropoff_address: job.dropoff_location.address,
+            driver_name,
+            pickup_time: job.pickup_time,
+            dropoff_time: job.dropoff_time,
+            promised_by: job.sla.map(|sla| sla.promised_by),
+            recipient_preferences: job.recipient_preferences,
+        })
+    }
+
+    pub async fn update_preferences(
+        &self,
+        tracking_code: &str,
+        request: UpdateRecipientPreferencesRequest,
+    ) -> Result<TrackingView, AppError> {
+        let mut job = self.load_job(tracking_code).await?;
+        if job.status.is_terminal() {
+            return Err(AppError::Conflict("This delivery is already closed".to_string()));
+        }
+
+        let now = Utc::now();
+        let mut preferences = job.recipient_preferences.clone().unwrap_or_default();
+        if let Some(instructions) = request.instructions {
+            preferences.instructions = self.text("instructions", instructions)?;
+        }
+        if let Some(safe_drop) = request.safe_drop {
+            preferences.safe_drop = self.text("safe_drop", safe_drop)?;
+        }
+        if let Some(deliver_after) = request.deliver_after {
+            // Once the package is on board the driver can't hold on to it
+            if job.status.is_carrying_package() {
+                return Err(AppError::Conflict("The package is already on its way and can't be rescheduled".to_string()));
+            }
+            let latest = now + Duration::hours(self.config.reschedule_window_hours);
+            if deliver_after <= now || deliver_after > latest {
+                return Err(AppError::validation_error(
+                    "deliver_after",
+                    format!("Must be within the next {} hours", self.config.reschedule_window_hours),
+                ));
+            }
+            preferences.deliver_after = Some(deliver_after);
+        }
+        preferences.updated_at = Some(now);
+
+        job.recipient_preferences = Some(preferences.clone());
+        job.updated_at = now;
+        self.cache_service.cache_job(&job).await?;
+        self.cache_service.append_job_event(&job.id, &JobEvent {
+            event_type: JobEventType::RecipientPreferencesUpdated,
+            timestamp: now,
+            location: None,
+            actor: "recipient".to_string(),
+            notes: Some(describe(&preferences)),
+        }).await?;
+
+        // Drivers with the app open see it straight away; the rest get a push. Best-effort:
+        // the change is saved and shows on the job either way.
+        if let Some(driver_id) = &job.driver_id {
+            let event = DriverSocketEvent::DeliveryPreferencesUpdated {
+                job_id: job.id.clone(),
+                preferences: preferences.clone(),
+            };
+            if !self.driver_channel.send(driver_id, event).await {
+                let message = NotificationMessage::delivery_preferences_updated(&job, &preferences);
+                if let Err(e) = self.notification_service.send_to_driver(driver_id, message).await {
+                    tracing::warn!("Failed to tell driver {} about new preferences for job {}: {}", driver_id, job.id, e);
+                }
+            }
+        }
+
+        tracing::info!("Recipient updated delivery preferences for job {}", job.id);
+        self.track(&job.tracking_code).await
+    }
+
+    /// A new share link for the customer's delivery, while it's still under way
+    pub async fn share(&self, job_id: &JobId, customer_id: &UserId, request: ShareTripRequest) -> Result<TripShare, AppError> {
+        let job = self.customer_job(job_id, customer_id).await?;
+        if job.status.is_terminal() {
+            return Err(AppError::Conflict("This delivery is already closed".to_string()));
+        }
+        let minutes = request.expires_in_minutes.unwrap_or(self.config.share_minutes);
+        if minutes <= 0 || minutes > self.config.max_share_minutes {
+            return Err(AppError::validation_error(
+                "expires_in_minutes",
+                format!("Must be between 1 and {}", self.config.max_share_minutes),
+            ));
+        }
+
+        let mut secret = [0u8; 18];
+        self.random.fill(&mut secret)
+            .map_err(|_| AppError::internal_error("System randomness unavailable"))?;
+        let token = URL_SAFE_NO_PAD.encode(secret);
+        let now = Utc::now();
+        let share = TripShare {
+            path: format!("/shared/{}", token),
+            token,
+            job_id: job.id.clone(),
+            created_by: customer_id.clone(),
+            created_at: now,
+            expires_at: now + Duration::minutes(minutes),
+        };
+        self.cache_service.cache_trip_share(&share, minutes as u64 * 60).await?;
+        tracing::info!("Customer {} shared job {} for {} minutes", customer_id, job.id, minutes);
+        Ok(share)
+    }
+
+    /// The customer's share links for a job that still work
+    pub async fn shares(&self, job_id: &JobId, customer_id: &UserId) -> Result<Vec<TripShare>, AppError> {
+        let job = self.customer_job(job_id, customer_id).await?;
+        let now = Utc::now();
+        let mut shares = Vec::new();
+        for token in self.cache_service.get_trip_share_tokens(&job.id).await? {
+            match self.cache_service.get_trip_share(&token).await? {
+                Some(share) if share.expires_at > now => shares.push(share),
+                // Expired out of the cache, or about to be
+                _ => self.cache_service.delete_trip_share(&job.id, &token).await?,
+            }
+        }
+        shares.sort_by_key(|share| share.created_at);
+        Ok(shares)
+    }
+
+    /// Returns the share links left
+    pub async fn revoke_share(&self, job_id: &JobId, customer_id: &UserId, token: &str) -> Result<Vec<TripShare>, AppError> {
+        let job = self.customer_job(job_id, customer_id).await?;
+        let share = self.cache_service.get_trip_share(token).await?
+            .filter(|share| share.job_id == job.id)
+            .ok_or_else(|| AppError::NotFound("Share link not found".to_string()))?;
+        self.cache_service.delete_trip_share(&job.id, &share.token).await?;
+        tracing::info!("Customer {} revoked a share link for job {}", customer_id, job.id);
+        self.shares(&job.id, customer_id).await
+    }
+
+    /// What a share link shows. Links stop working once the delivery is over, whatever their
+    /// expiry.
+    pub async fn shared_trip(&self, token: &str) -> Result<SharedTripView, AppError> {
+        let not_found = || AppError::NotFound("This shared trip has ended or was never shared".to_string());
+        let share = self.cache_service.get_trip_share(token.trim()).await?
+            .filter(|share| share.expires_at > Utc::now())
+            .ok_or_else(not_found)?;
+        let job = self.cache_service.load_job(&share.job_id).await?
+            .filter(|job| !job.status.is_terminal())
+            .ok_or_else(not_found)?;
+
+        let (driver_name, driver_location) = match &job.driver_id {
+            Some(driver_id) => {
+                let name = self.cache_service.get_driver(driver_id).await?.map(|driver| driver.first_name);
+                let location = self.cache_service.get_driver_location(driver_id).await?.map(|last| SharedDriverLocation {
+                    latitude: last.latitude,
+                    longitude: last.longitude,
+                    updated_at: last.timestamp,
+                });
+                (name, location)
+            }
+            None => (None, None),
+        };
+        Ok(SharedTripView {
+            status: job.status,
+            driver_name,
+            driver_location,
+            dropoff_address: job.dropoff_location.address,
+            dropoff_latitude: job.dropoff_location.latitude,
+            dropoff_longitude: job.dropoff_location.longitude,
+            promised_by: job.sla.map(|sla| sla.promised_by),
+            expires_at: share.expires_at,
+        })
+    }
+
+    async fn customer_job(&self, job_id: &JobId, customer_id: &UserId) -> Result<Job, AppError> {
+        let job = self.cache_service.load_job(job_id).await?
+            .ok_or_else(|| AppError::job_not_found(job_id.as_str()))?;
+        if job.customer_id != *customer_id {
+            return Err(AppError::Forbidden(format!("Job {} belongs to another customer", job_id)));
+        }
+        Ok(job)
+    }
+
+    // Trimmed; empty clears the field
+    fn text(&self, field: &str, value: String) -> Result<Option<String>, AppError> {
+        let value = value.trim();
+        if value.chars().count() > self.config.max_text_length {
+            return Err(AppError::validation_error(
+                field,
+                format!("Must be at most {} characters", self.config.max_text_length),
+            ));
+        }
+        Ok((!value.is_empty()).then(|| value.to_string()))
+    }
+}
+
+fn describe(preferences: &RecipientPreferences) -> String {
+    let mut parts = Vec::new();
+    if let Some(instructions) = &preferences.instructions {
+        parts.push(format!("instructions: {}", instructions));
+    }
+    if let Some(safe_drop) = &preferences.safe_drop {
+        parts.push(format!("safe drop: {}", safe_drop));
+    }
+    if let Some(deliver_after) = &preferences.deliver_after {
+        parts.push(format!("deliver after {}", deliver_after.to_rfc3339()));
+    }
+    if parts.is_empty() {
+        return "Cleared delivery preferences".to_string();
+    }
+    parts.join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::{Faker, ACCRA}, messaging::RecordingNotificationService},
+        models::{driver::DriverStatus, job::{JobStatus, JobStatusUpdate}, user::UserType},
+        services::{job_service::JobOperations, user_service::UserOperations},
+    };
+
+    #[tokio::test]
+    async fn test_recipient_updates_reach_driver_and_job_events() {
+        let notifications = RecordingNotificationService::new();
+        let app = TestApp::builder().notification_service(Arc::new(notifications.clone())).build();
+        let state = &app.state;
+        let mut faker = Faker::seeded(41);
+
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let job = state.job_service.create_job(faker.job_request(&customer.id)).await.unwrap();
+        let mut driver = faker.driver();
+        driver.status = DriverStatus::Online;
+        state.cache_service.cache_driver(&driver).await.unwrap();
+        state.job_service.assign_driver_to_job(&job.id, &driver.id).await.unwrap();
+
+        let tracking = &state.tracking_service;
+        assert!(tracking.track("GH-NOPE").await.is_err());
+        let view = tracking.track(&job.tracking_code).await.unwrap();
+        assert_eq!(view.driver_name.as_deref(), Some(driver.first_name.as_str()));
+
+        let view = tracking.update_preferences(&job.tracking_code, UpdateRecipientPreferencesRequest {
+            safe_drop: Some("  With the gate attendant ".to_string()),
+            deliver_after: Some(Utc::now() + Duration::hours(3)),
+            ..Default::default()
+        }).await.unwrap();
+        let preferences = view.recipient_preferences.unwrap();
+        assert_eq!(preferences.safe_drop.as_deref(), Some("With the gate attendant"));
+        let pushed = notifications.of_kind("delivery_preferences_updated");
+        assert_eq!(pushed.len(), 1);
+        assert_eq!(pushed[0].message.data.as_ref().unwrap()["safe_drop"], "With the gate attendant");
+        let stored = state.job_service.get_job(&job.id).await.unwrap().unwrap();
+        assert!(stored.recipient_preferences.unwrap().deliver_after.is_some());
+
+        // Outside the window, then too late once the package is on board
+        let too_late = UpdateRecipientPreferencesRequest {
+            deliver_after: Some(Utc::now() + Duration::days(5)),
+            ..Default::default()
+        };
+        assert!(tracking.update_preferences(&job.tracking_code, too_late).await.is_err());
+        state.job_service.update_job_status(JobStatusUpdate {
+            job_id: job.id.clone(),
+            status: JobStatus::PackagePickedUp,
+            driver_id: Some(driver.id.clone()),
+            notes: None,
+        }).await.unwrap();
+        let reschedule = UpdateRecipientPreferencesRequest {
+            deliver_after: Some(Utc::now() + Duration::hours(1)),
+            ..Default::default()
+        };
+        assert!(matches!(tracking.update_preferences(&job.tracking_code, reschedule).await, Err(AppError::Conflict(_))));
+        let cleared = tracking.update_preferences(&job.tracking_code, UpdateRecipientPreferencesRequest {
+            safe_drop: Some(String::new()),
+            instructions: Some("Call on arrival".to_string()),
+            ..Default::default()
+        }).await.unwrap();
+        let preferences = cleared.recipient_preferences.unwrap();
+        assert!(preferences.safe_drop.is_none());
+        assert_eq!(preferences.instructions.as_deref(), Some("Call on arrival"));
+
+        let events = state.cache_service.get_job_events(&job.id).await.unwrap();
+        let updates = events.iter().filter(|event| event.event_type == JobEventType::RecipientPreferencesUpdated).count();
+        assert_eq!(updates, 2);
+    }
+
+    #[tokio::test]
+    async fn test_share_links_follow_the_trip_until_revoked_or_delivered() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let tracking = &state.tracking_service;
+        let mut faker = Faker::seeded(109);
+
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let job = state.job_service.create_job(faker.job_request(&customer.id)).await.unwrap();
+        let stranger = UserId::generate();
+        assert!(matches!(tracking.share(&job.id, &stranger, ShareTripRequest::default()).await, Err(AppError::Forbidden(_))));
+        let too_long = ShareTripRequest { expires_in_minutes: Some(7 * 24 * 60) };
+        assert!(tracking.share(&job.id, &customer.id, too_long).await.is_err());
+
+        let first = tracking.share(&job.id, &customer.id, ShareTripRequest::default()).await.unwrap();
+        let second = tracking.share(&job.id, &customer.id, ShareTripRequest { expires_in_minutes: Some(30) }).await.unwrap();
+        assert_ne!(first.token, second.token);
+        assert_eq!(first.path, format!("/shared/{}", first.token));
+        let view = tracking.shared_trip(&first.token).await.unwrap();
+        assert!(view.driver_location.is_none());
+
+        // The driver shows on the map once they have the job
+        let mut driver = faker.driver();
+        driver.status = DriverStatus::Online;
+        state.cache_service.cache_driver(&driver).await.unwrap();
+        state.job_service.assign_driver_to_job(&job.id, &driver.id).await.unwrap();
+        state.cache_service.cache_driver_locations(&[(driver.id.clone(), faker.location_update(ACCRA))]).await.unwrap();
+        let view = tracking.shared_trip(&second.token).await.unwrap();
+        assert_eq!(view.driver_name.as_deref(), Some(driver.first_name.as_str()));
+        assert!(view.driver_location.is_some());
+
+        let left = tracking.revoke_share(&job.id, &customer.id, &first.token).await.unwrap();
+        assert_eq!(left, vec![second.clone()]);
+        assert!(matches!(tracking.shared_trip(&first.token).await, Err(AppError::NotFound(_))));
+
+        // Delivered, so the link left ends too
+        state.job_service.update_job_status(JobStatusUpdate {
+            job_id: job.id.clone(),
+            status: JobStatus::PackagePickedUp,
+            driver_id: Some(driver.id.clone()),
+            notes: None,
+        }).await.unwrap();
+        state.job_service.complete_job(&job.id).await.unwrap();
+        assert!(matches!(tracking.shared_trip(&second.token).await, Err(AppError::NotFound(_))));
+        assert!(matches!(tracking.share(&job.id, &customer.id, ShareTripRequest::default()).await, Err(AppError::Conflict(_))));
+    }
+}
@@ -6,64 +6,61 @@ use tracing;
 
 use crate::{
     errors::SparrowError as AppError,
-    models::user::{
-        Address, PaymentMethod, User, UserLogin, UserPreferences, UserRegistration, UserResponse, UserStatus, UserUpdate
-    },
-    services::{cache_service::{CacheKey, CacheService}, messaging_service::{self, NotificationService}},
-    utils::id_generator::{IdGenerator, IdType, WithGeneratedId}, ValidationError,
+    models::{device::DeviceToken, ids::UserId, scope::grants_any, user::{
+        default_language, Address, NameVisibility, CreditBalance, PaymentMethod, User, UserLogin, UserPreferences, UserRegistration, UserResponse, UserStatus, UserUpdate
+    }},
+    services::{cache_service::CacheService, device_service::{DeviceService, LoginDevice}, messaging_service::{self, NotificationService}, otp_service::{self, OtpService}, region::RegionRegistry, tenant_service::current_tenant_id},
+    utils::{id_generator::{IdGenerator, IdType}, secret_hash::{random_hex, salted_hash, verify_salted}}, ValidationError,
 };
 
 #[async_trait]
 pub trait UserOperations: Send + Sync {
     async fn register_user(&self, registration: UserRegistration) -> Result<UserResponse, AppError>;
     async fn login_user(&self, login: UserLogin) -> Result<(UserResponse, String), AppError>; // Returns user + auth token
-    async fn get_user(&self, user_id: &str) -> Result<Option<UserResponse>, AppError>;
+    async fn get_user(&self, user_id: &UserId) -> Result<Option<UserResponse>, AppError>;
     async fn get_user_by_email(&self, email: &str) -> Result<Option<UserResponse>, AppError>;
     async fn get_user_by_phone(&self, phone: &str) -> Result<Option<UserResponse>, AppError>;
-    async fn update_user(&self, user_id: &str, update: UserUpdate) -> Result<UserResponse, AppError>;
-    async fn update_user_device_token(&self, user_id: &str, device_token: String) -> Result<UserResponse, AppError>;
-    async fn add_user_address(&self, user_id: &str, address: Address) -> Result<UserResponse, AppError>;
-    async fn set_primary_address(&self, user_id: &str, address_id: &str) -> Result<UserResponse, AppError>;
-    async fn add_payment_method(&self, user_id: &str, payment_method: PaymentMethod) -> Result<UserResponse, AppError>;
-    async fn set_primary_payment_method(&self, user_id: &str, payment_id: &str) -> Result<UserResponse, AppError>;
-    async fn update_user_preferences(&self, user_id: &str, preferences: UserPreferences) -> Result<UserResponse, AppError>;
-    async fn verify_user_email(&self, user_id: &str) -> Result<UserResponse, AppError>;
-    async fn verify_user_phone(&self, user_id: &str) -> Result<UserResponse, AppError>;
-    async fn deactivate_user(&self, user_id: &str) -> Result<(), AppError>;
+    async fn update_user(&self, user_id: &UserId, update: UserUpdate) -> Result<UserResponse, AppError>;
+    async fn update_user_device_token(&self, user_id: &UserId, device_token: DeviceToken) -> Result<UserResponse, AppError>;
+    async fn get_user_addresses(&self, user_id: &UserId) -> Result<Vec<Address>, AppError>;
+    async fn add_user_address(&self, user_id: &UserId, address: Address) -> Result<UserResponse, AppError>;
+    async fn set_primary_address(&self, user_id: &UserId, address_id: &str) -> Result<UserResponse, AppError>;
+    async fn add_payment_method(&self, user_id: &UserId, payment_method: PaymentMethod) -> Result<UserResponse, AppError>;
+    async fn set_primary_payment_method(&self, user_id: &UserId, payment_id: &str) -> Result<UserResponse, AppError>;
+    async fn update_user_preferences(&self, user_id: &UserId, preferences: UserPreferences) -> Result<UserResponse, AppError>;
+    async fn verify_user_email(&self, user_id: &UserId) -> Result<UserResponse, AppError>;
+    async fn verify_user_phone(&self, user_id: &UserId) -> Result<UserResponse, AppError>;
+    async fn deactivate_user(&self, user_id: &UserId) -> Result<(), AppError>;
+    async fn get_credit_balance(&self, user_id: &UserId) -> Result<CreditBalance, AppError>;
 }
 
 pub struct UserService {
     cache_service: Arc<CacheService>,
     notification_service: Arc<dyn NotificationService>,
+    device_service: Arc<DeviceService>,
+    otp_service: Arc<OtpService>,
+    regions: Arc<RegionRegistry>,
 }
 
 impl UserService {
     pub fn new(
         cache_service: Arc<CacheService>,
         notification_service: Arc<dyn NotificationService>,
+        device_service: Arc<DeviceService>,
+        otp_service: Arc<OtpService>,
+        regions: Arc<RegionRegistry>,
     ) -> Self {
         Self {
             cache_service,
             notification_service,
+            device_service,
+            otp_service,
+            regions,
         }
     }
     
     fn to_response(&self, user: User) -> UserResponse {
-        UserResponse {
-            id: user.id,
-            user_type: user.user_type,
-            status: user.status,
-            email: user.email,
-            phone_number: user.phone_number,
-            country_code: user.country_code,
-            first_name: user.first_name,
-            last_name: user.last_name,
-            display_name: user.display_name,
-            is_email_verified: user.is_email_verified,
-            is_phone_verified: user.is_phone_verified,
-            profile_picture: None, // Would come from user profile
-            created_at: user.created_at,
-        }
+        UserResponse::from(user)
     }
     
     async fn hash_password(&self, password: &str) -> Result<String, AppError> {
@@ -76,26 +73,46 @@ impl UserService {
         Ok(hashed_password == format!("hashed_{}", password))
     }
     
-    async fn generate_auth_token(&self, user_id: &str) -> Result<String, AppError> {
-        // In production, use JWT or similar
-        // For now, simple token generation
-        Ok(format!("token_{}_{}", user_id, Utc::now().timestamp()))
+    // Opaque and unguessable; only a salted hash of it is kept on the user
+    async fn generate_auth_token(&self, user_id: &UserId) -> Result<String, AppError> {
+        Ok(format!("token_{}_{}", user_id, random_hex(24)))
+    }
+    
+    /// The user a login token belongs to, as long as it is still their current session
+    pub async fn authenticate_session(&self, token: &str) -> Result<User, AppError> {
+        let invalid = || AppError::unauthorized("Invalid or expired session");
+        let user_id = token.strip_prefix("token_")
+            .and_then(|rest| rest.rsplit_once('_'))
+            .and_then(|(user_id, _)| UserId::parse(user_id).ok())
+            .ok_or_else(invalid)?;
+        let user = self.cache_service.load_user(&user_id).await?.ok_or_else(invalid)?;
+        if !user.current_session.as_deref().is_some_and(|stored| verify_salted(token, stored)) {
+            return Err(invalid());
+        }
+        if user.status == UserStatus::Banned {
+            return Err(AppError::Forbidden("Account is banned".to_string()));
+        }
+        Ok(user)
     }
 }
 
 #[async_trait]
 impl UserOperations for UserService {
-    async fn register_user(&self, registration: UserRegistration) -> Result<UserResponse, AppError> {
+    async fn register_user(&self, mut registration: UserRegistration) -> Result<UserResponse, AppError> {
         tracing::info!("Registering user: {}", registration.email);
         
+        // Numbers follow the numbering plan of the region they're dialled in, and are kept without the trunk 0
+        let region = self.regions.for_dialing_code(&registration.country_code)?;
+        let phone_number = region.normalize_phone(&registration.phone_number)?;
+        registration.phone_number = phone_number[region.dialing_code.len()..].to_string();
+        registration.country_code = region.dialing_code.clone();
+        
         // Check if user already exists
-        if let phone_number = &registration.phone_number {
-            if self.get_user_by_phone(phone_number).await?.is_some() {
-                return Err(AppError::validation_error("phone_number", "User already exists with this phone number"));
-            }
+        if self.get_user_by_phone(&registration.phone_number).await?.is_some() {
+            return Err(AppError::validation_error("phone_number", "User already exists with this phone number"));
         }
         
-        if let Some(_) = self.get_user_by_email(&registration.email).await? {
+        if self.get_user_by_email(&registration.email).await?.is_some() {
             return Err(AppError::ValidationFailed(vec![ValidationError {
                 message: "User already exists with this email".to_string(),
                 field: "email".to_string(),
@@ -106,8 +123,9 @@ impl UserOperations for UserService {
         let hashed_password = self.hash_password(&registration.password).await?;
         
         // Create user with our ID generator
-        let mut user = User {
-            id: String::new(), // Will be set by with_generated_id
+        let user = User {
+            id: UserId::generate(),
+            tenant_id: current_tenant_id(),
             user_type: registration.user_type,
             status: UserStatus::PendingVerification,
             email: registration.email,
@@ -116,18 +134,21 @@ impl UserOperations for UserService {
             first_name: registration.first_name,
             last_name: registration.last_name,
             display_name: None,
+            language: default_language(),
+            name_visibility: NameVisibility::default(),
+            quiet_hours: None,
             is_email_verified: false,
             is_phone_verified: false,
             device_tokens: Vec::new(),
             last_login: None,
             current_session: None,
+            session_scopes: None,
+            session_device: None,
+            ban: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
         
-        // Use our WithGeneratedId trait to set the ID
-        user.set_generated_id(IdType::User);
-        
         // Cache the user
         self.cache_service.cache_user(&user).await?;
         
@@ -149,6 +170,7 @@ impl UserOperations for UserService {
                     "timestamp": Utc::now().to_rfc3339(),
                 })),
                 priority: messaging_service::NotificationPriority::Normal,
+                data_only: false,
             }
         ).await?;
         
@@ -170,29 +192,58 @@ impl UserOperations for UserService {
         };
         
         let user = user.ok_or_else(|| AppError::Unauthorized("User not found".to_string()))?;
+        if user.status == UserStatus::Banned {
+            return Err(AppError::Forbidden("Account is banned".to_string()));
+        }
         
         // Verify password (in production, get from auth service)
         let hashed_password = self.cache_service.get_user_credentials(&user.id).await?
             .ok_or_else(|| AppError::Unauthorized("Invalid credentials".to_string()))?;
         
+        // Too many wrong passwords lock the account out for a while, whoever is guessing
+        self.otp_service.ensure_unlocked(otp_service::PASSWORD, user.id.as_str()).await?;
         if !self.verify_password(&login.password, &hashed_password).await? {
+            self.otp_service.record_failure(otp_service::PASSWORD, user.id.as_str()).await?;
             return Err(AppError::Unauthorized("Invalid password".to_string()));
         }
-        
+        self.otp_service.clear_failures(otp_service::PASSWORD, user.id.as_str()).await?;
+        
+        // An unseen device in a new city has to be confirmed from one the user already has.
+        // A login that doesn't name its device is from an unseen one, and one with no city
+        // from a new city.
+        let session_device = self.device_service.check_login(&user.id, LoginDevice {
+            info: login.device.as_ref(),
+            platform: login.device_platform,
+            push_token: login.device_token.as_deref(),
+            city: login.city.as_deref(),
+            verification_code: login.verification_code.as_deref(),
+        }).await?;
+
         // Update device token if provided
         if let Some(device_token) = login.device_token {
+            let device_token = DeviceToken::new(device_token, login.device_platform);
             self.update_user_device_token(&user.id, device_token).await?;
         }
         
+        // A login may ask for less than the account allows, never more
+        let defaults = user.user_type.default_scopes();
+        if let Some(scopes) = &login.scopes
+            && let Some(scope) = scopes.iter().find(|scope| !grants_any(&defaults, scope))
+        {
+            return Err(AppError::validation_error("scopes", format!("{} is not available to this account", scope)));
+        }
+
         // Generate auth token
         let auth_token = self.generate_auth_token(&user.id).await?;
         
         // Update last login
-        let mut user_full: User = self.cache_service.get_user(&CacheKey::Simple(user.id)).await?
+        let mut user_full: User = self.cache_service.load_user(&user.id).await?
             .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
         
         user_full.last_login = Some(Utc::now());
-        user_full.current_session = Some(auth_token.clone());
+        user_full.current_session = Some(salted_hash(&auth_token));
+        user_full.session_scopes = login.scopes;
+        user_full.session_device = session_device;
         user_full.updated_at = Utc::now();
         
         self.cache_service.cache_user(&user_full).await?;
@@ -202,15 +253,10 @@ impl UserOperations for UserService {
         Ok((self.to_response(user_full), auth_token))
     }
     
-    async fn get_user(&self, user_id: &str) -> Result<Option<UserResponse>, AppError> {
-        if !IdGenerator::validate_id(user_id, Some(IdType::User)) {
-            tracing::warn!("Invalid user ID format: {}", user_id);
-            return Ok(None);
-        }
-        
+    async fn get_user(&self, user_id: &UserId) -> Result<Option<UserResponse>, AppError> {
         tracing::debug!("Getting user: {}", user_id);
         
-        if let Some(user) = self.cache_service.get_user(&CacheKey::Simple(user_id.to_string())).await? {
+        if let Some(user) = self.cache_service.load_user(user_id).await? {
             return Ok(Some(self.to_response(user)));
         }
         
@@ -237,17 +283,10 @@ impl UserOperations for UserService {
         Ok(None)
     }
     
-    async fn update_user(&self, user_id: &str, update: UserUpdate) -> Result<UserResponse, AppError> {
-        if !IdGenerator::validate_id(user_id, Some(IdType::User)) {
-            return Err(AppError::ValidationFailed(vec![ ValidationError{
-            message: "Invalid user ID format".to_string(),
-            field: "user_id".to_string(),
-        }]));
-        }
-        
+    async fn update_user(&self, user_id: &UserId, update: UserUpdate) -> Result<UserResponse, AppError> {
         tracing::info!("Updating user: {}", user_id);
         
-        let mut user: User = self.cache_service.get_user(&CacheKey::Simple(user_id.to_string())).await?
+        let mut user: User = self.cache_service.load_user(user_id).await?
             .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
         
         // Apply updates
@@ -282,21 +321,16 @@ impl UserOperations for UserService {
         Ok(self.to_response(user))
     }
     
-    async fn update_user_device_token(&self, user_id: &str, device_token: String) -> Result<UserResponse, AppError> {
-        if !IdGenerator::validate_id(user_id, Some(IdType::User)) {
-            return Err(AppError::ValidationFailed(vec![ValidationError {
-                message: "Invalid user ID format".to_string(),
-                field: user_id.to_string(),
-            }]));
-        }
-        
+    async fn update_user_device_token(&self, user_id: &UserId, device_token: DeviceToken) -> Result<UserResponse, AppError> {
         tracing::debug!("Updating device token for user: {}", user_id);
         
-        let mut user: User = self.cache_service.get_user(&CacheKey::Simple(user_id.to_string())).await?
+        let mut user: User = self.cache_service.load_user(user_id).await?
             .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
         
-        // Add or update device token
+        // Add or update device token; an app reinstalled on another platform can't reuse one,
+        // so a known token keeps its place and only its platform is refreshed
         if !user.device_tokens.contains(&device_token) {
+            user.device_tokens.retain(|device| device.token != device_token.token);
             user.device_tokens.push(device_token);
             user.updated_at = Utc::now();
             self.cache_service.cache_user(&user).await?;
@@ -305,48 +339,98 @@ impl UserOperations for UserService {
         Ok(self.to_response(user))
     }
     
-    async fn add_user_address(&self, user_id: &str, address: Address) -> Result<UserResponse, AppError> {
-        // Implementation would handle address management
-        // For now, placeholder
-        self.get_user(user_id).await?
-            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+    async fn get_user_addresses(&self, user_id: &UserId) -> Result<Vec<Address>, AppError> {
+        self.cache_service.get_user_addresses(user_id).await
     }
     
-    async fn set_primary_address(&self, user_id: &str, address_id: &str) -> Result<UserResponse, AppError> {
-        // Implementation would handle address management
-        self.get_user(user_id).await?
-            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+    async fn add_user_address(&self, user_id: &UserId, mut address: Address) -> Result<UserResponse, AppError> {
+        let user = self.get_user(user_id).await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        
+        tracing::info!("Adding address '{}' for user: {}", address.label, user_id);
+        
+        let mut addresses = self.cache_service.get_user_addresses(user_id).await?;
+        
+        if address.id.is_empty() {
+            address.id = IdGenerator::generate(IdType::Address);
+        }
+        
+        // First address is always primary, and there can only be one
+        if addresses.is_empty() {
+            address.is_primary = true;
+        }
+        if address.is_primary {
+            for existing in addresses.iter_mut() {
+                existing.is_primary = false;
+            }
+        }
+        
+        addresses.push(address);
+        self.cache_service.cache_user_addresses(user_id, &addresses).await?;
+        
+        Ok(user)
     }
     
-    async fn add_payment_method(&self, user_id: &str, payment_method: PaymentMethod) -> Result<UserResponse, AppError> {
-        // Implementation would handle payment methods
-        self.get_user(user_id).await?
-            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+    async fn set_primary_address(&self, user_id: &UserId, address_id: &str) -> Result<UserResponse, AppError> {
+        let user = self.get_user(user_id).await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        
+        let mut addresses = self.cache_service.get_user_addresses(user_id).await?;
+        if !addresses.iter().any(|a| a.id == address_id) {
+            return Err(AppError::NotFound("Address not found".to_string()));
+        }
+        
+        for address in addresses.iter_mut() {
+            address.is_primary = address.id == address_id;
+            address.updated_at = Utc::now();
+        }
+        
+        self.cache_service.cache_user_addresses(user_id, &addresses).await?;
+        
+        tracing::debug!("Primary address for user {} set to {}", user_id, address_id);
+        
+        Ok(user)
     }
     
-    async fn set_primary_payment_method(&self, user_id: &str, payment_id: &str) -> Result<UserResponse, AppError> {
-        // Implementation would handle payment methods
+    // Payment methods aren't stored yet; jobs carry the payment method they're paid with
+    async fn add_payment_method(&self, user_id: &UserId, _payment_method: PaymentMethod) -> Result<UserResponse, AppError> {
         self.get_user(user_id).await?
-            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        Err(AppError::bad_request("Saved payment methods are not supported"))
     }
     
-    async fn update_user_preferences(&self, user_id: &str, preferences: UserPreferences) -> Result<UserResponse, AppError> {
-        // Implementation would handle preferences
+    async fn set_primary_payment_method(&self, user_id: &UserId, _payment_id: &str) -> Result<UserResponse, AppError> {
         self.get_user(user_id).await?
-            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        Err(AppError::bad_request("Saved payment methods are not supported"))
     }
     
-    async fn verify_user_email(&self, user_id: &str) -> Result<UserResponse, AppError> {
-        if !IdGenerator::validate_id(user_id, Some(IdType::User)) {
-            return Err(AppError::ValidationFailed(vec![ValidationError {
-                message: "Invalid user ID format".to_string(),
-                field: user_id.to_string(),
-            }]));
+    async fn update_user_preferences(&self, user_id: &UserId, preferences: UserPreferences) -> Result<UserResponse, AppError> {
+        let mut user: User = self.cache_service.load_user(user_id).await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        let mut quiet_hours = preferences.notifications.quiet_hours;
+        if let Some(quiet_hours) = &mut quiet_hours {
+            let utc_offset_minutes = *quiet_hours.utc_offset_minutes.get_or_insert(self.regions.current().utc_offset_minutes);
+            if utc_offset_minutes.abs() > 14 * 60 {
+                return Err(AppError::validation_error("notifications.quiet_hours.utc_offset_minutes", "Must be within 14 hours of UTC"));
+            }
         }
-        
+
+        // Only the language, name visibility and quiet hours live on the account; the rest belongs to the profile
+        user.language = preferences.language;
+        user.name_visibility = preferences.name_visibility;
+        user.quiet_hours = quiet_hours;
+        user.updated_at = Utc::now();
+        self.cache_service.cache_user(&user).await?;
+
+        Ok(self.to_response(user))
+    }
+    
+    async fn verify_user_email(&self, user_id: &UserId) -> Result<UserResponse, AppError> {
         tracing::info!("Verifying email for user: {}", user_id);
         
-        let mut user: User = self.cache_service.get_user(&CacheKey::Simple(user_id.to_string())).await?
+        let mut user: User = self.cache_service.load_user(user_id).await?
             .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
         
         user.is_email_verified = true;
@@ -362,17 +446,10 @@ impl UserOperations for UserService {
         Ok(self.to_response(user))
     }
     
-    async fn verify_user_phone(&self, user_id: &str) -> Result<UserResponse, AppError> {
-        if !IdGenerator::validate_id(user_id, Some(IdType::User)) {
-            return Err(AppError::ValidationFailed(vec![ValidationError {
-                message: "Invalid user ID format".to_string(),
-                field: user_id.to_string(),
-            }]));
-        }
-        
+    async fn verify_user_phone(&self, user_id: &UserId) -> Result<UserResponse, AppError> {
         tracing::info!("Verifying phone for user: {}", user_id);
         
-        let mut user: User = self.cache_service.get_user(&CacheKey::Simple(user_id.to_string())).await?
+        let mut user: User = self.cache_service.load_user(user_id).await?
             .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
         
         user.is_phone_verified = true;
@@ -388,17 +465,10 @@ impl UserOperations for UserService {
         Ok(self.to_response(user))
     }
     
-    async fn deactivate_user(&self, user_id: &str) -> Result<(), AppError> {
-        if !IdGenerator::validate_id(user_id, Some(IdType::User)) {
-            return Err(AppError::ValidationFailed(vec![ValidationError {
-                message: "Invalid user ID format".to_string(),
-                field: user_id.to_string(),
-            }]));
-        }
-        
+    async fn deactivate_user(&self, user_id: &UserId) -> Result<(), AppError> {
         tracing::info!("Deactivating user: {}", user_id);
         
-        let mut user: User = self.cache_service.get_user(&CacheKey::Simple(user_id.to_string())).await?
+        let mut user: User = self.cache_service.load_user(user_id).await?
             .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
         
         user.status = UserStatus::Inactive;
@@ -410,4 +480,12 @@ impl UserOperations for UserService {
         
         Ok(())
     }
+    
+    async fn get_credit_balance(&self, user_id: &UserId) -> Result<CreditBalance, AppError> {
+        if self.cache_service.load_user(user_id).await?.is_none() {
+            return Err(AppError::user_not_found(user_id.clone()));
+        }
+        let credits = self.cache_service.get_user_credits(user_id).await?;
+        CreditBalance::new(user_id.clone(), credits)
+    }
 }
@@ -0,0 +1,96 @@
+// src/services/voice.rs
+// Automated voice calls: a spoken message read to a phone number by a telephony provider.
+// Placed through an HTTP voice gateway when one is configured; otherwise only logged.
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use tracing;
+
+use crate::{errors::SparrowError as AppError, models::job::CallLanguage, services::sms::mask_phone};
+
+#[async_trait]
+pub trait VoiceGateway: Send + Sync {
+    /// Place the call and return the provider's ID for it, which its status callbacks carry
+    async fn call(&self, to: &str, language: CallLanguage, message: &str) -> Result<String, AppError>;
+}
+
+// POSTs `{"from", "to", "language", "message", "status_callback"}` with the gateway's bearer
+// token; the gateway reads the message out and answers `{"call_id"}`
+pub struct HttpVoiceGateway {
+    url: String,
+    token: String,
+    caller_id: String,
+    status_callback: Option<String>,
+    client: reqwest::Client,
+}
+
+impl HttpVoiceGateway {
+    pub fn new(url: String, token: String, caller_id: String, status_callback: Option<String>) -> Self {
+        Self { url, token, caller_id, status_callback, client: reqwest::Client::new() }
+    }
+}
+
+#[derive(Deserialize)]
+struct PlacedCall {
+    call_id: String,
+}
+
+// Speech codes the gateway's text-to-speech takes
+fn speech_code(language: CallLanguage) -> &'static str {
+    match language {
+        CallLanguage::English => "en-GH",
+        CallLanguage::Twi => "tw-GH",
+    }
+}
+
+#[async_trait]
+impl VoiceGateway for HttpVoiceGateway {
+    async fn call(&self, to: &str, language: CallLanguage, message: &str) -> Result<String, AppError> {
+        let response = self.client.post(&self.url)
+            .bearer_auth(&self.token)
+            .json(&json!({
+                "from": self.caller_id,
+                "to": to,
+                "language": speech_code(language),
+                "message": message,
+                "status_callback": self.status_callback,
+            }))
+            .send()
+            .await
+            .map_err(|e| AppError::HttpClient(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(AppError::HttpClient(format!("Voice gateway rejected call: {}", response.status())));
+        }
+        let placed: PlacedCall = response.json().await.map_err(|e| AppError::HttpClient(e.to_string()))?;
+        Ok(placed.call_id)
+    }
+}
+
+pub struct MockVoiceGateway;
+
+#[async_trait]
+impl VoiceGateway for MockVoiceGateway {
+    async fn call(&self, to: &str, language: CallLanguage, _message: &str) -> Result<String, AppError> {
+        tracing::info!("[MOCK] Would call {} in {:?}", mask_phone(to), language);
+        Ok(format!("mock-{}", uuid::Uuid::new_v4()))
+    }
+}
+
+/// The gateway configured through VOICE_GATEWAY_URL and VOICE_GATEWAY_TOKEN, calling from
+/// VOICE_CALLER_ID and reporting back to VOICE_STATUS_CALLBACK_URL; the mock without them
+pub fn voice_gateway_from_env() -> Arc<dyn VoiceGateway> {
+    let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+    match (var("VOICE_GATEWAY_URL"), var("VOICE_GATEWAY_TOKEN")) {
+        (Some(url), Some(token)) => Arc::new(HttpVoiceGateway::new(
+            url,
+            token,
+            var("VOICE_CALLER_ID").unwrap_or_else(|| "Sparrow".to_string()),
+            var("VOICE_STATUS_CALLBACK_URL"),
+        )),
+        _ => {
+            tracing::warn!("VOICE_GATEWAY_URL or VOICE_GATEWAY_TOKEN not set, calls will only be logged");
+            Arc::new(MockVoiceGateway)
+        }
+    }
+}
@@ -0,0 +1,325 @@
+// src/services/write_behind.rs
+// Fire-and-forget cache writes for high-frequency, non-critical data: live driver
+// positions and presence. Requests enqueue and return; one flusher task drains the
+// queue in batches. A full queue drops new writes and counts them instead of blocking.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc::{self, error::TrySendError}, oneshot, Mutex};
+use tokio::task::JoinHandle;
+use tracing;
+
+use crate::{
+    models::{ids::DriverId, job::LocationUpdate},
+    services::{cache_service::CacheService, region::{current_region_id, with_region}, tenant_service::{current_tenant_id, with_tenant}},
+};
+
+#[derive(Debug, Clone)]
+pub struct WriteBehindConfig {
+    pub capacity: usize,       // Queued writes before new ones are dropped
+    pub max_batch_size: usize, // Writes drained and coalesced per flush
+}
+
+impl Default for WriteBehindConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10_000,
+            max_batch_size: 500,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum WriteOp {
+    DriverLocation { driver_id: DriverId, location: LocationUpdate },
+    DriverLastSeen { driver_id: DriverId, at: DateTime<Utc> },
+}
+
+enum Message {
+    // Region and tenant are captured on enqueue; the flusher runs outside any request scope
+    Write { region_id: String, tenant_id: String, op: WriteOp },
+    Shutdown(oneshot::Sender<()>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WriteBehindMetrics {
+    pub capacity: usize,
+    pub depth: usize,     // Writes waiting for the flusher
+    pub enqueued: u64,
+    pub written: u64,     // Entries written to the cache
+    pub coalesced: u64,   // Superseded by a newer write in the same batch
+    pub dropped: u64,     // Rejected because the queue was full or shut down
+    pub failed: u64,      // Lost to a cache error
+}
+
+#[derive(Default)]
+struct Counters {
+    enqueued: AtomicU64,
+    written: AtomicU64,
+    coalesced: AtomicU64,
+    dropped: AtomicU64,
+    failed: AtomicU64,
+}
+
+// One flush worth of writes for a tenant in a region, latest value per driver
+#[derive(Default)]
+struct TenantWrites {
+    locations: HashMap<DriverId, LocationUpdate>,
+    last_seen: HashMap<DriverId, DateTime<Utc>>,
+}
+
+pub struct WriteBehindQueue {
+    config: WriteBehindConfig,
+    cache_service: Arc<CacheService>,
+    sender: mpsc::Sender<Message>,
+    // Taken by whichever of the flusher or `shutdown` starts draining first
+    receiver: Mutex<Option<mpsc::Receiver<Message>>>,
+    counters: Counters,
+}
+
+impl WriteBehindQueue {
+    pub fn new(cache_service: Arc<CacheService>, config: WriteBehindConfig) -> Self {
+        let (sender, receiver) = mpsc::channel(config.capacity);
+        Self {
+            config,
+            cache_service,
+            sender,
+            receiver: Mutex::new(Some(receiver)),
+            counters: Counters::default(),
+        }
+    }
+
+    /// Queue `op` for the current region and tenant. Never waits; returns false if it was dropped.
+    pub fn enqueue(&self, op: WriteOp) -> bool {
+        let message = Message::Write { region_id: current_region_id(), tenant_id: current_tenant_id(), op };
+        match self.sender.try_send(message) {
+            Ok(()) => {
+                self.counters.enqueued.fetch_add(1, Ordering::Relaxed);
+                true
+            }
+            Err(e) => {
+                let dropped = self.counters.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                // Log the 1st, 2nd, 4th, 8th... drop so a saturated queue doesn't flood the logs
+                if dropped.is_power_of_two() {
+                    let reason = match e {
+                        TrySendError::Full(_) => "full",
+                        TrySendError::Closed(_) => "shut down",
+                    };
+                    tracing::warn!("Write-behind queue {}, {} writes dropped so far", reason, dropped);
+                }
+                false
+            }
+        }
+    }
+
+    pub fn metrics(&self) -> WriteBehindMetrics {
+        WriteBehindMetrics {
+            capacity: self.config.capacity,
+            depth: self.sender.max_capacity() - self.sender.capacity(),
+            enqueued: self.counters.enqueued.load(Ordering::Relaxed),
+            written: self.counters.written.load(Ordering::Relaxed),
+            coalesced: self.counters.coalesced.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+            failed: self.counters.failed.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn spawn_flusher(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let Some(mut receiver) = self.receiver.lock().await.take() else {
+                return;
+            };
+            let mut batch = Vec::with_capacity(self.config.max_batch_size);
+            loop {
+                if receiver.recv_many(&mut batch, self.config.max_batch_size).await == 0 {
+                    return;
+                }
+                let acks = self.write_batch(&mut batch).await;
+                if !acks.is_empty() {
+                    self.drain(receiver).await;
+                    for ack in acks {
+                        let _ = ack.send(());
+                    }
+                    return;
+                }
+            }
+        })
+    }
+
+    /// Stop accepting writes and flush everything already queued
+    pub async fn shutdown(&self) {
+        // Flusher never started (or hasn't yet): drain here instead
+        if let Some(receiver) = self.receiver.lock().await.take() {
+            self.drain(receiver).await;
+            return;
+        }
+        let (ack, done) = oneshot::channel();
+        if self.sender.send(Message::Shutdown(ack)).await.is_ok() {
+            let _ = done.await;
+        }
+    }
+
+    async fn drain(&self, mut receiver: mpsc::Receiver<Message>) {
+        receiver.close();
+        let mut batch = Vec::with_capacity(self.config.max_batch_size);
+        while receiver.recv_many(&mut batch, self.config.max_batch_size).await > 0 {
+            for ack in self.write_batch(&mut batch).await {
+                let _ = ack.send(());
+            }
+        }
+        let metrics = self.metrics();
+        tracing::info!("Write-behind queue drained: {} written, {} dropped", metrics.written, metrics.dropped);
+    }
+
+    // Coalesce to the latest value per (region, tenant, driver) and write one batch per region and tenant.
+    // Returns any shutdown requests found in the batch.
+    async fn write_batch(&self, batch: &mut Vec<Message>) -> Vec<oneshot::Sender<()>> {
+        let mut by_tenant: HashMap<(String, String), TenantWrites> = HashMap::new();
+        let mut acks = Vec::new();
+        let mut coalesced = 0;
+
+        for message in batch.drain(..) {
+            match message {
+                Message::Write { region_id, tenant_id, op: WriteOp::DriverLocation { driver_id, location } } => {
+                    match by_tenant.entry((region_id, tenant_id)).or_default().locations.entry(driver_id) {
+                        Entry::Occupied(mut existing) => {
+                            if location.timestamp > existing.get().timestamp {
+                                existing.insert(location);
+                            }
+                            coalesced += 1;
+                        }
+                        Entry::Vacant(slot) => {
+                            slot.insert(location);
+                        }
+                    }
+                }
+                Message::Write { region_id, tenant_id, op: WriteOp::DriverLastSeen { driver_id, at } } => {
+                    match by_tenant.entry((region_id, tenant_id)).or_default().last_seen.entry(driver_id) {
+                        Entry::Occupied(mut existing) => {
+                            if at > *existing.get() {
+                                existing.insert(at);
+                            }
+                            coalesced += 1;
+                        }
+                        Entry::Vacant(slot) => {
+                            slot.insert(at);
+                        }
+                    }
+                }
+                Message::Shutdown(ack) => acks.push(ack),
+            }
+        }
+        self.counters.coalesced.fetch_add(coalesced, Ordering::Relaxed);
+
+        for ((region_id, tenant_id), writes) in by_tenant {
+            if !writes.locations.is_empty() {
+                let locations: Vec<_> = writes.locations.into_iter().collect();
+                let result = with_region(region_id.clone(), with_tenant(tenant_id.clone(), self.cache_service.cache_driver_locations(&locations))).await;
+                self.record(&region_id, &tenant_id, "driver locations", locations.len(), result);
+            }
+            if !writes.last_seen.is_empty() {
+                let last_seen: Vec<_> = writes.last_seen.into_iter().collect();
+                let result = with_region(region_id.clone(), with_tenant(tenant_id.clone(), self.cache_service.cache_driver_last_seen(&last_seen))).await;
+                self.record(&region_id, &tenant_id, "driver presence", last_seen.len(), result);
+            }
+        }
+        acks
+    }
+
+    fn record<E: std::fmt::Display>(&self, region_id: &str, tenant_id: &str, what: &str, count: usize, result: Result<(), E>) {
+        match result {
+            Ok(()) => {
+                self.counters.written.fetch_add(count as u64, Ordering::Relaxed);
+            }
+            Err(e) => {
+                self.counters.failed.fetch_add(count as u64, Ordering::Relaxed);
+                tracing::warn!("Failed to write {} {} for tenant {} in region {}: {}", count, what, tenant_id, region_id, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    use crate::services::cache_service::CacheConfig;
+
+    fn point(latitude: f64, at: DateTime<Utc>) -> LocationUpdate {
+        LocationUpdate {
+            latitude,
+            longitude: -0.19,
+            heading: None,
+            speed: None,
+            is_mock: false,
+            accuracy: None,
+            timestamp: at,
+        }
+    }
+
+    fn queue(capacity: usize) -> (Arc<CacheService>, WriteBehindQueue) {
+        let cache_service = Arc::new(CacheService::new_memory(CacheConfig::default()));
+        let config = WriteBehindConfig { capacity, ..Default::default() };
+        (cache_service.clone(), WriteBehindQueue::new(cache_service, config))
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_flushes_latest_writes() {
+        let (cache_service, queue) = queue(16);
+        let driver_id = DriverId::generate();
+        let now = Utc::now();
+
+        queue.enqueue(WriteOp::DriverLocation { driver_id: driver_id.clone(), location: point(5.60, now) });
+        queue.enqueue(WriteOp::DriverLocation { driver_id: driver_id.clone(), location: point(5.50, now - Duration::seconds(10)) });
+        queue.enqueue(WriteOp::DriverLastSeen { driver_id: driver_id.clone(), at: now });
+        queue.shutdown().await;
+
+        let stored = cache_service.get_driver_location(&driver_id).await.unwrap().unwrap();
+        assert_eq!(stored.latitude, 5.60);
+        assert_eq!(cache_service.get_driver_last_seen(&driver_id).await.unwrap(), Some(now));
+
+        let metrics = queue.metrics();
+        assert_eq!((metrics.enqueued, metrics.written, metrics.coalesced), (3, 2, 1));
+        assert_eq!(metrics.depth, 0);
+
+        // Closed for good once drained
+        assert!(!queue.enqueue(WriteOp::DriverLastSeen { driver_id, at: now }));
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_drops_instead_of_blocking() {
+        let (_, queue) = queue(2);
+        let driver_id = DriverId::generate();
+
+        let accepted: Vec<bool> = (0..4)
+            .map(|_| queue.enqueue(WriteOp::DriverLastSeen { driver_id: driver_id.clone(), at: Utc::now() }))
+            .collect();
+        assert_eq!(accepted, vec![true, true, false, false]);
+
+        let metrics = queue.metrics();
+        assert_eq!((metrics.depth, metrics.dropped), (2, 2));
+    }
+
+    #[tokio::test]
+    async fn test_flusher_writes_under_the_enqueuing_tenant() {
+        let (cache_service, queue) = queue(16);
+        let queue = Arc::new(queue);
+        let flusher = queue.clone().spawn_flusher();
+        let driver_id = DriverId::generate();
+
+        with_tenant("acme".to_string(), async {
+            queue.enqueue(WriteOp::DriverLastSeen { driver_id: driver_id.clone(), at: Utc::now() });
+        })
+        .await;
+        queue.shutdown().await;
+        flusher.await.unwrap();
+
+        assert!(cache_service.get_driver_last_seen(&driver_id).await.unwrap().is_none());
+        let seen = with_tenant("acme".to_string(), cache_service.get_driver_last_seen(&driver_id)).await;
+        assert!(seen.unwrap().is_some());
+    }
+}
@@ -0,0 +1,324 @@
+// src/services/zone_service.rs
+// Service zones drawn by operations through `/admin/zones`, imported as GeoJSON polygons.
+// Edits add versions and only an activated version counts. Each instance keeps the active
+// boundaries in memory and reloads them when the shared generation stamp in Redis moves,
+// so a change made on one instance is picked up everywhere without a restart.
+use chrono::Utc;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::zone::{CreateZoneRequest, ServiceZone, UpdateZoneRequest, ZoneMatch, ZoneVersion},
+    services::{cache_service::CacheService, tenant_service::current_tenant_id},
+    utils::id_generator::{IdGenerator, IdType},
+};
+
+#[derive(Debug, Clone)]
+pub struct ZoneConfig {
+    pub max_vertices: usize,
+    pub max_name_length: usize,
+}
+
+impl Default for ZoneConfig {
+    fn default() -> Self {
+        Self {
+            max_vertices: 500,
+            max_name_length: 100,
+        }
+    }
+}
+
+// One tenant's active boundaries, as of `generation`
+struct LoadedZones {
+    generation: Option<i64>,
+    zones: Vec<(ZoneMatch, Vec<[f64; 2]>)>,
+}
+
+pub struct ZoneService {
+    cache_service: Arc<CacheService>,
+    loaded: RwLock<HashMap<String, LoadedZones>>, // By tenant
+    config: ZoneConfig,
+}
+
+impl ZoneService {
+    pub fn new(cache_service: Arc<CacheService>, config: ZoneConfig) -> Self {
+        Self {
+            cache_service,
+            loaded: RwLock::new(HashMap::new()),
+            config,
+        }
+    }
+
+    pub async fn list_zones(&self) -> Result<Vec<ServiceZone>, AppError> {
+        let mut zones = Vec::new();
+        for zone_id in self.cache_service.get_service_zone_ids().await? {
+            if let Some(zone) = self.cache_service.get_service_zone(&zone_id).await? {
+                zones.push(zone);
+            }
+        }
+        zones.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(zones)
+    }
+
+    pub async fn get_zone(&self, zone_id: &str) -> Result<ServiceZone, AppError> {
+        self.cache_service.get_service_zone(zone_id).await?
+            .ok_or_else(|| AppError::NotFound(format!("Zone {} not found", zone_id)))
+    }
+
+    /// A new zone with its boundary as version 1, not yet active
+    pub async fn create_zone(&self, request: CreateZoneRequest) -> Result<ServiceZone, AppError> {
+        let name = self.name(&request.name)?;
+        let boundary = parse_boundary(&request.geometry, self.config.max_vertices)?;
+        let now = Utc::now();
+        let zone = ServiceZone {
+            id: IdGenerator::generate(IdType::Zone),
+            tenant_id: current_tenant_id(),
+            name,
+            versions: vec![ZoneVersion { version: 1, boundary, created_at: now }],
+            active_version: None,
+            created_at: now,
+            updated_at: now,
+        };
+        self.save(&zone).await?;
+        tracing::info!("Created zone {} ({})", zone.id, zone.name);
+        Ok(zone)
+    }
+
+    pub async fn update_zone(&self, zone_id: &str, request: UpdateZoneRequest) -> Result<ServiceZone, AppError> {
+        let mut zone = self.get_zone(zone_id).await?;
+        if let Some(name) = request.name {
+            zone.name = self.name(&name)?;
+        }
+        if let Some(geometry) = request.geometry {
+            let boundary = parse_boundary(&geometry, self.config.max_vertices)?;
+            let version = zone.versions.last().map_or(1, |latest| latest.version + 1);
+            zone.versions.push(ZoneVersion { version, boundary, created_at: Utc::now() });
+        }
+        zone.updated_at = Utc::now();
+        self.save(&zone).await?;
+        Ok(zone)
+    }
+
+    /// Make `version` the zone's live boundary; an earlier one may be reactivated to roll back
+    pub async fn activate(&self, zone_id: &str, version: u32) -> Result<ServiceZone, AppError> {
+        let mut zone = self.get_zone(zone_id).await?;
+        if zone.version(version).is_none() {
+            return Err(AppError::validation_error("version", format!("Zone {} has no version {}", zone_id, version)));
+        }
+        zone.active_version = Some(version);
+        zone.updated_at = Utc::now();
+        self.save(&zone).await?;
+        tracing::info!("Activated version {} of zone {}", version, zone_id);
+        Ok(zone)
+    }
+
+    pub async fn delete_zone(&self, zone_id: &str) -> Result<ServiceZone, AppError> {
+        let zone = self.get_zone(zone_id).await?;
+        self.cache_service.delete_service_zone(zone_id).await?;
+        self.invalidate().await?;
+        tracing::info!("Deleted zone {}", zone_id);
+        Ok(zone)
+    }
+
+    /// The active zone containing the point, if any
+    pub async fn zone_at(&self, latitude: f64, longitude: f64) -> Result<Option<ZoneMatch>, AppError> {
+        let tenant_id = current_tenant_id();
+        let generation = self.cache_service.get_zones_generation().await?;
+        let fresh = self.loaded.read().await
+            .get(&tenant_id)
+            .is_some_and(|loaded| loaded.generation == generation);
+        if !fresh {
+            let zones = self.list_zones().await?
+                .into_iter()
+                .filter_map(|zone| {
+                    let active = zone.active()?;
+                    let found = ZoneMatch { zone_id: zone.id.clone(), name: zone.name.clone(), version: active.version };
+                    Some((found, active.boundary.clone()))
+                })
+                .collect();
+            self.loaded.write().await.insert(tenant_id.clone(), LoadedZones { generation, zones });
+        }
+
+        let loaded = self.loaded.read().await;
+        Ok(loaded.get(&tenant_id).and_then(|loaded| {
+            loaded.zones.iter()
+                .find(|(_, boundary)| contains(boundary, [longitude, latitude]))
+                .map(|(found, _)| found.clone())
+        }))
+    }
+
+    async fn save(&self, zone: &ServiceZone) -> Result<(), AppError> {
+        self.cache_service.cache_service_zone(zone).await?;
+        self.invalidate().await
+    }
+
+    async fn invalidate(&self) -> Result<(), AppError> {
+        self.cache_service.set_zones_generation(Utc::now().timestamp_micros()).await
+    }
+
+    fn name(&self, name: &str) -> Result<String, AppError> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > self.config.max_name_length {
+            return Err(AppError::validation_error(
+                "name",
+                format!("Must be between 1 and {} characters", self.config.max_name_length),
+            ));
+        }
+        Ok(name.to_string())
+    }
+}
+
+// The outer ring of a GeoJSON Polygon (or a Feature wrapping one), checked to be a simple,
+// closed ring of at most `max_vertices` corners
+fn parse_boundary(geometry: &Value, max_vertices: usize) -> Result<Vec<[f64; 2]>, AppError> {
+    let invalid = |message: &str| AppError::validation_error("geometry", message);
+    let geometry = match geometry["type"].as_str() {
+        Some("Feature") => &geometry["geometry"],
+        _ => geometry,
+    };
+    if geometry["type"].as_str() != Some("Polygon") {
+        return Err(invalid("Expected a GeoJSON Polygon"));
+    }
+    let rings = geometry["coordinates"].as_array().ok_or_else(|| invalid("Missing coordinates"))?;
+    let [ring] = rings.as_slice() else {
+        return Err(invalid("Expected exactly one ring; holes aren't supported"));
+    };
+    let ring = ring.as_array().ok_or_else(|| invalid("A ring must be an array of positions"))?;
+
+    let mut boundary = Vec::with_capacity(ring.len());
+    for position in ring {
+        let (Some(longitude), Some(latitude)) = (position[0].as_f64(), position[1].as_f64()) else {
+            return Err(invalid("Each position must be [longitude, latitude]"));
+        };
+        if !(-180.0..=180.0).contains(&longitude) || !(-90.0..=90.0).contains(&latitude) {
+            return Err(invalid("Positions must be within longitude -180..180 and latitude -90..90"));
+        }
+        boundary.push([longitude, latitude]);
+    }
+    if boundary.len() < 4 || boundary.first() != boundary.last() {
+        return Err(invalid("A ring must be closed, with at least 3 corners and the first position repeated last"));
+    }
+    let corners = boundary.len() - 1;
+    if corners > max_vertices {
+        return Err(invalid(&format!("At most {} vertices are allowed", max_vertices)));
+    }
+    if signed_area(&boundary).abs() < f64::EPSILON {
+        return Err(invalid("The polygon has no area"));
+    }
+    for first in 0..corners {
+        // Neighbouring edges share a corner; every other pair must stay apart
+        for second in first + 2..corners {
+            if first == 0 && second == corners - 1 {
+                continue;
+            }
+            let (a, b) = (boundary[first], boundary[first + 1]);
+            let (c, d) = (boundary[second], boundary[second + 1]);
+            if segments_intersect(a, b, c, d) {
+                return Err(invalid(&format!("The boundary crosses itself between edges {} and {}", first, second)));
+            }
+        }
+    }
+    Ok(boundary)
+}
+
+fn signed_area(ring: &[[f64; 2]]) -> f64 {
+    ring.windows(2).map(|pair| pair[0][0] * pair[1][1] - pair[1][0] * pair[0][1]).sum::<f64>() / 2.0
+}
+
+fn orientation(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> f64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+fn on_segment(a: [f64; 2], b: [f64; 2], point: [f64; 2]) -> bool {
+    point[0] >= a[0].min(b[0]) && point[0] <= a[0].max(b[0]) && point[1] >= a[1].min(b[1]) && point[1] <= a[1].max(b[1])
+}
+
+fn segments_intersect(a: [f64; 2], b: [f64; 2], c: [f64; 2], d: [f64; 2]) -> bool {
+    let (o1, o2) = (orientation(a, b, c), orientation(a, b, d));
+    let (o3, o4) = (orientation(c, d, a), orientation(c, d, b));
+    if o1 * o2 < 0.0 && o3 * o4 < 0.0 {
+        return true;
+    }
+    // Touching or overlapping along a line
+    (o1 == 0.0 && on_segment(a, b, c))
+        || (o2 == 0.0 && on_segment(a, b, d))
+        || (o3 == 0.0 && on_segment(c, d, a))
+        || (o4 == 0.0 && on_segment(c, d, b))
+}
+
+// Ray casting on a closed ring
+fn contains(ring: &[[f64; 2]], point: [f64; 2]) -> bool {
+    let mut inside = false;
+    for pair in ring.windows(2) {
+        let ([x1, y1], [x2, y2]) = (pair[0], pair[1]);
+        if (y1 > point[1]) != (y2 > point[1]) && point[0] < (x2 - x1) * (point[1] - y1) / (y2 - y1) + x1 {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::mocks::app::TestApp;
+
+    fn square(west: f64, south: f64, size: f64) -> Value {
+        let (east, north) = (west + size, south + size);
+        json!({
+            "type": "Feature",
+            "properties": {},
+            "geometry": {
+                "type": "Polygon",
+                "coordinates": [[[west, south], [east, south], [east, north], [west, north], [west, south]]],
+            },
+        })
+    }
+
+    #[tokio::test]
+    async fn test_zone_versions_go_live_on_activation_across_instances() {
+        let app = TestApp::new();
+        let zones = &app.state.zone_service;
+        // A second instance sharing the same Redis
+        let other = ZoneService::new(app.state.cache_service.clone(), ZoneConfig::default());
+
+        let bowtie = json!({
+            "type": "Polygon",
+            "coordinates": [[[0.0, 0.0], [2.0, 2.0], [2.0, 0.0], [0.0, 1.0], [0.0, 0.0]]],
+        });
+        let open_ring = json!({ "type": "Polygon", "coordinates": [[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]]] });
+        for geometry in [bowtie, open_ring, json!({ "type": "Point", "coordinates": [0.0, 0.0] })] {
+            let request = CreateZoneRequest { name: "Broken".to_string(), geometry };
+            assert!(matches!(zones.create_zone(request).await, Err(AppError::ValidationFailed(_))));
+        }
+        let tight = ZoneService::new(app.state.cache_service.clone(), ZoneConfig { max_vertices: 3, ..Default::default() });
+        assert!(tight.create_zone(CreateZoneRequest { name: "Square".to_string(), geometry: square(0.0, 0.0, 1.0) }).await.is_err());
+
+        // Central Accra, then widened to take in Tema
+        let accra = zones.create_zone(CreateZoneRequest { name: " Accra ".to_string(), geometry: square(-0.30, 5.50, 0.15) }).await.unwrap();
+        assert_eq!(accra.name, "Accra");
+        let (osu, tema) = ((5.556, -0.182), (5.669, -0.017));
+        assert_eq!(other.zone_at(osu.0, osu.1).await.unwrap(), None); // Drafts don't count
+
+        zones.activate(&accra.id, 1).await.unwrap();
+        assert_eq!(other.zone_at(osu.0, osu.1).await.unwrap().map(|found| found.version), Some(1));
+        assert_eq!(other.zone_at(tema.0, tema.1).await.unwrap(), None);
+
+        let widened = UpdateZoneRequest { geometry: Some(square(-0.30, 5.50, 0.30)), ..Default::default() };
+        let accra = zones.update_zone(&accra.id, widened).await.unwrap();
+        assert_eq!((accra.versions.len(), accra.active_version), (2, Some(1)));
+        assert!(zones.activate(&accra.id, 3).await.is_err());
+        zones.activate(&accra.id, 2).await.unwrap();
+        assert_eq!(other.zone_at(tema.0, tema.1).await.unwrap().map(|found| found.version), Some(2));
+
+        zones.delete_zone(&accra.id).await.unwrap();
+        assert_eq!(other.zone_at(osu.0, osu.1).await.unwrap(), None);
+        assert!(zones.list_zones().await.unwrap().is_empty());
+    }
+}
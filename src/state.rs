@@ -1,21 +1,143 @@
 // src/state.rs
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 
 use crate::services::{
-    cache_service::CacheService, 
-    driver_service::DriverService, 
-    job_service::JobService, 
+    cache_codec::CacheFormat,
+    database::Repository,
+    cache_service::{CacheConfig, CacheService}, 
+    dispatch::DispatchConfig, dispatch_settings::DispatchSettingsService, package_analysis::{NoPackageAnalysis, PackageAnalysisConfig, PackageAnalysisService, PackageImageAnalyzer}, handoff::{HandoffConfig, HandoffService}, driver_service::{DriverConfig, DriverService}, 
+    onboarding_service::{OnboardingConfig, OnboardingService},
+    dispatcher_service::{DispatcherConfig, DispatcherService},
+    driver_channel::DriverChannel,
+    presence_service::{PresenceConfig, PresenceService},
+    realtime_bus::{RealtimeBus, RealtimeBusConfig},
+    event_bus::{EventBus, RedisEventBus, RedisEventBusConfig},
+    event_subscribers::{AssignmentNotifications, DeliveryNotifications, DemandAnalytics, DriverOutcomes, PickupNotifications, RefundNotifications},
+    saga::{SagaConfig, SagaOrchestrator},
+    send_queue::{SendQueueConfig, SendQueues},
+    earnings::{EarningsCalculator, EarningsConfig},
+    tax::TaxEngine,
+    calendar::{CalendarConfig, CalendarService},
+    ledger::{LedgerConfig, LedgerService},
+    invoice_service::{InvoiceConfig, InvoiceService, NoInvoiceMailer},
+    exchange_rates::ExchangeRateService,
+    job_service::{JobHistoryConfig, JobService}, 
     user_service::UserService, 
-    messaging_service::{FcmNotificationService, MockNotificationService, NotificationService}
+    device_service::DeviceService,
+    otp_service::{OtpConfig, OtpService},
+    retention_service::RetentionService,
+    alert_service::{channels_from_env, AlertConfig, AlertService},
+    incident_service::IncidentService,
+    risk_service::{RiskConfig, RiskService},
+    canary_service::{CanaryConfig, CanaryService},
+    quota_service::QuotaService,
+    runtime_config::{RuntimeConfigService, RuntimeConfigWatch},
+    startup::{probe, StartupChecks, StartupConfig, FCM_PROBE_URL},
+    events_export::{EventsExportConfig, EventsExportService, ObjectStoreConfig, ObjectStoreSink},
+    location_service::{LocationConfig, LocationService},
+    odometer::{OdometerConfig, OdometerService},
+    route_service::RouteService,
+    status_feed::{StatusFeedConfig, StatusFeedService},
+    delivery_code::{DeliveryCodeConfig, DeliveryCodeService},
+    business_accounts::BusinessAccountService,
+    arrival_calls::{ArrivalCallConfig, ArrivalCallService},
+    voice::voice_gateway_from_env,
+    sms::sms_sender_from_env,
+    dashboard_service::{DashboardConfig, DashboardService},
+    directory_service::{DirectoryConfig, DirectoryService},
+    moderation_service::ModerationService,
+    tracking_service::{TrackingConfig, TrackingService},
+    pooling::{PoolingConfig, PoolingService},
+    zone_service::{ZoneConfig, ZoneService},
+    realtime_publisher::{MockRealtimePublisher, RealtimeProvider, RealtimePublisher, SocketPublisher},
+    demand_service::{DemandConfig, DemandService},
+    export_service::{ExportConfig, ExportService},
+    api_key_service::{ApiKeyConfig, ApiKeyService},
+    tenant_service::TenantService,
+    region::RegionRegistry,
+    write_behind::{WriteBehindConfig, WriteBehindQueue},
+    messaging_service::{FcmNotificationService, MockNotificationService, NotificationService},
+    apns::{ApnsConfig, ApnsNotificationService},
+    multi_channel::MultiChannelNotificationService,
+    notification_batching::{BatchingNotificationService, NotificationBatchConfig},
+    notification_templates::{NotificationTemplateConfig, NotificationTemplateService},
+    broadcast_service::BroadcastService,
 };
+#[cfg(feature = "payments")]
+use crate::services::{dispute_service::{DisputeConfig, DisputeService}, reconciliation::ReconciliationService};
+#[cfg(feature = "payments")]
+use crate::workers::reconciliation::{Reconciliation, ReconciliationConfig};
+#[cfg(feature = "realtime-ably")]
+use crate::services::{ably_auth::{AblyAuthConfig, AblyAuthService}, realtime_publisher::AblyPublisher, startup::ABLY_PROBE_URL};
+#[cfg(feature = "mqtt")]
+use crate::services::mqtt_bridge::{MqttBridge, MqttConfig};
+use crate::handlers::request_log::RequestLogConfig;
+use crate::models::startup::StartupReport;
+use crate::utils::{field_crypto::FieldCipher, id_generator::{IdFormat, IdGenerator}};
+use crate::workers::{assignment_watchdog::{AssignmentWatchdog, AssignmentWatchdogConfig}, break_monitor::{BreakMonitor, BreakMonitorConfig}, broadcast_scheduler::{BroadcastScheduler, BroadcastSchedulerConfig}, canary::{Canary, CanaryWorkerConfig}, chained_offers::{ChainedOffers, ChainedOffersConfig}, deferred_notifications::{DeferredNotifications, DeferredNotificationsConfig}, demand_forecast::DemandForecaster, document_expiry::{DocumentExpiry, DocumentExpiryConfig}, events_export::{EventsExportWorkerConfig, EventsExporter}, invoicing::{Invoicing, InvoicingConfig}, saga_recovery::{SagaRecovery, SagaRecoveryConfig}, driver_analytics::{DriverAnalytics, DriverAnalyticsConfig}, job_escalation::{JobEscalationConfig, JobEscalator}, job_expiry::{JobExpiry, JobExpiryConfig}, notification_digest::{NotificationDigest, NotificationDigestConfig}, otp_cleanup::{OtpCleanup, OtpCleanupConfig}, retention_purge::{RetentionPurge, RetentionPurgeConfig}, sla_monitor::{SlaConfig, SlaMonitor}, status_feed::{StatusFeedFlusher, StatusFeedWorkerConfig}, surge_pricing::{SurgePricing, SurgePricingConfig}, WorkerRuntime};
 
 pub struct AppState {
     pub user_service: Arc<UserService>,
+    pub device_service: Arc<DeviceService>,
+    pub otp_service: Arc<OtpService>,
+    pub retention_service: Arc<RetentionService>,
+    pub events_export: Arc<EventsExportService>,
+    pub alert_service: Arc<AlertService>,
+    pub canary_service: Arc<CanaryService>,
+    pub incident_service: Arc<IncidentService>,
+    pub risk_service: Arc<RiskService>,
     pub driver_service: Arc<DriverService>,
+    pub onboarding_service: Arc<OnboardingService>,
     pub job_service: Arc<JobService>,
+    pub business_accounts: Arc<BusinessAccountService>,
+    pub arrival_calls: Arc<ArrivalCallService>,
     pub cache_service: Arc<CacheService>,
+    pub location_service: Arc<LocationService>,
+    pub odometer: Arc<OdometerService>,
+    pub route_service: Arc<RouteService>,
+    pub status_feed: Arc<StatusFeedService>,
+    pub dashboard_service: Arc<DashboardService>,
+    pub directory_service: Arc<DirectoryService>,
+    pub moderation_service: Arc<ModerationService>,
+    pub demand_service: Arc<DemandService>,
+    pub export_service: Arc<ExportService>,
+    pub api_key_service: Arc<ApiKeyService>,
+    pub quota_service: Arc<QuotaService>,
+    pub dispatcher_service: Arc<DispatcherService>,
+    pub dispatch_settings: Arc<DispatchSettingsService>,
+    pub package_analysis: Arc<PackageAnalysisService>,
+    pub handoff_service: Arc<HandoffService>,
+    pub tracking_service: Arc<TrackingService>,
+    pub pooling_service: Arc<PoolingService>,
+    #[cfg(feature = "realtime-ably")]
+    pub ably_auth: Arc<AblyAuthService>,
+    pub zone_service: Arc<ZoneService>,
+    pub driver_channel: Arc<DriverChannel>,
+    pub presence_service: Arc<PresenceService>,
+    pub realtime_bus: Arc<RealtimeBus>,
+    pub event_bus: Arc<RedisEventBus>,
+    pub send_queues: Arc<SendQueues>,
+    pub earnings_calculator: Arc<EarningsCalculator>,
+    pub tax_engine: Arc<TaxEngine>,
+    pub calendar_service: Arc<CalendarService>,
+    pub ledger_service: Arc<LedgerService>,
+    #[cfg(feature = "payments")]
+    pub reconciliation_service: Arc<ReconciliationService>,
+    #[cfg(feature = "payments")]
+    pub dispute_service: Arc<DisputeService>,
+    pub invoice_service: Arc<InvoiceService>,
+    pub exchange_rates: Arc<ExchangeRateService>,
+    pub tenant_service: Arc<TenantService>,
+    pub regions: Arc<RegionRegistry>,
     pub notification_service: Arc<dyn NotificationService>,
+    pub notification_batcher: Arc<BatchingNotificationService>,
+    pub notification_templates: Arc<NotificationTemplateService>,
+    pub broadcast_service: Arc<BroadcastService>,
+    pub write_behind: Arc<WriteBehindQueue>,
+    pub runtime_config: Arc<RuntimeConfigService>,
+    pub startup: OnceLock<StartupReport>, // What boot found; unset when built without `new`
+    pub workers: WorkerRuntime,
     pub config: AppConfig,
 }
 
@@ -26,51 +148,632 @@ pub struct AppConfig {
     pub redis_url: String,
     pub fcm_server_key: Option<String>,  // Changed from fcm_api_key to fcm_server_key
     pub ably_api_key: String,
+    pub realtime_provider: RealtimeProvider, // Where live updates for app users are published
+    pub id_format: IdFormat,              // Legacy dated IDs or time-sortable ULID-style IDs
+    pub cache_format: CacheFormat,        // Encoding of values written to Redis
+    pub request_log: RequestLogConfig,
+    pub regions: Vec<String>,             // Served besides Ghana, e.g. from SPARROW_REGIONS
+}
+
+/// Services an embedding application supplies in place of the ones `AppState::new` builds
+#[derive(Default)]
+pub struct ServiceOverrides {
+    pub notification_service: Option<Arc<dyn NotificationService>>, // Replaces FCM and APNs
+    pub repository: Option<Arc<dyn Repository>>,
+    pub package_analyzer: Option<Arc<dyn PackageImageAnalyzer>>,
 }
 
 impl AppState {
     pub async fn new(config: AppConfig) -> Result<Self, Box<dyn std::error::Error>> {
-        let cache_service = Arc::new(CacheService::new(&config.redis_url).await?);
+        Self::with_overrides(config, ServiceOverrides::default()).await
+    }
+
+    // Connects to Redis and the configured providers, then wires everything on top
+    pub async fn with_overrides(config: AppConfig, overrides: ServiceOverrides) -> Result<Self, Box<dyn std::error::Error>> {
+        // Redis first, as everything sits on the cache, then the optional push and realtime providers
+        let mut startup = StartupChecks::new(StartupConfig::from_env());
+        let cache_config = CacheConfig {
+            redis_url: config.redis_url.clone(),
+            format: config.cache_format,
+            ..Default::default()
+        };
+        let cache_service = startup.require("redis", || CacheService::with_config(cache_config.clone())).await;
+        let probe_client = reqwest::Client::new();
+        if config.fcm_server_key.is_some() && overrides.notification_service.is_none() {
+            startup.check("fcm", || probe(&probe_client, FCM_PROBE_URL)).await;
+        }
+        #[cfg(feature = "realtime-ably")]
+        if config.realtime_provider == RealtimeProvider::Ably {
+            startup.check("ably", || probe(&probe_client, ABLY_PROBE_URL)).await;
+        }
+        let startup_report = startup.finish()?;
+        let Some(mut cache_service) = cache_service else {
+            return Err("Redis unavailable".into());
+        };
+        match FieldCipher::from_env()? {
+            Some(cipher) => {
+                tracing::info!("Encrypting personal data at rest with key {}", cipher.active_key_id());
+                cache_service = cache_service.with_cipher(Arc::new(cipher));
+            }
+            None => tracing::warn!("PII_ENCRYPTION_KEYS not set, personal data is stored unencrypted"),
+        }
+        if let Some(repository) = overrides.repository {
+            cache_service = cache_service.with_repository(repository);
+        }
+        let cache_service = Arc::new(cache_service);
         
         // Initialize notification service first since other services might need it
-        let notification_service: Arc<dyn NotificationService> = 
-            match config.fcm_server_key.clone() {
-                Some(server_key) => {
-                    tracing::info!("Using FCM notification service with server key");
-                    Arc::new(FcmNotificationService::with_server_key(
-                        server_key, 
-                        cache_service.clone()
-                    ))
-                }
-                None => {
-                    tracing::warn!("FCM_SERVER_KEY not set, using mock notification service");
-                    Arc::new(MockNotificationService)
-                }
-            };
+        let notification_service: Arc<dyn NotificationService> = match overrides.notification_service {
+            Some(notification_service) => notification_service,
+            None => Self::push_services(&config, &cache_service)?,
+        };
+
+        #[cfg(feature = "chaos")]
+        tracing::warn!("Built with the chaos feature: faults can be injected through /admin/chaos/faults");
+        let redis_url = config.redis_url.clone();
+        // No image-analysis provider yet unless one is supplied: package photos are kept but not sized up
+        let package_analyzer = overrides.package_analyzer.unwrap_or_else(|| Arc::new(NoPackageAnalysis));
+        let state = Self::with_services(config, cache_service, notification_service, package_analyzer);
+        // Sockets may be held by any instance, so their events go through Redis
+        state.realtime_bus.attach_redis(redis::Client::open(redis_url.clone())?);
+        tokio::spawn(state.realtime_bus.clone().run());
+        // Each subscriber reads the event stream as a consumer group shared by every instance
+        state.event_bus.attach_redis(redis::Client::open(redis_url.clone())?);
+        tokio::spawn(state.event_bus.clone().run());
+        state.runtime_config.attach_redis(redis::Client::open(redis_url)?);
+        tokio::spawn(state.runtime_config.clone().listen());
+        tokio::spawn(state.runtime_config.clone().watch_file());
+        let alert_channels = channels_from_env();
+        if alert_channels.is_empty() {
+            tracing::warn!("No ALERT_SLACK_WEBHOOK_URL or ALERT_TELEGRAM_BOT_TOKEN set, ops alerts only go to the log");
+        }
+        for channel in alert_channels {
+            state.alert_service.add_channel(channel);
+        }
+        tokio::spawn(state.alert_service.clone().run());
+        match CanaryWorkerConfig::from_env() {
+            Some(canary) => state.workers.spawn(Arc::new(Canary::new(
+                state.cache_service.clone(),
+                state.canary_service.clone(),
+                canary,
+            ))),
+            None => tracing::info!("CANARY_ENABLED not set, no synthetic canary jobs in this environment"),
+        }
+        match ObjectStoreConfig::from_env() {
+            Some(store) => {
+                tracing::info!("Exporting events to bucket {} at {}", store.bucket, store.endpoint);
+                state.events_export.attach_sink(Arc::new(ObjectStoreSink::new(store)));
+            }
+            None => tracing::warn!("EVENTS_EXPORT_BUCKET not set, events are kept in the outbox but not exported"),
+        }
+        #[cfg(feature = "mqtt")]
+        if let Some(mqtt_config) = MqttConfig::from_env() {
+            tracing::info!("Bridging driver offers and locations over MQTT at {}", mqtt_config.broker_host);
+            let (bridge, event_loop) = MqttBridge::new(
+                mqtt_config,
+                state.tenant_service.clone(),
+                state.location_service.clone(),
+            );
+            let bridge = Arc::new(bridge);
+            state.driver_channel.attach_mqtt(bridge.clone());
+            tokio::spawn(bridge.run(event_loop));
+        }
+        if state.startup.set(startup_report).is_err() {
+            tracing::warn!("Startup report already set");
+        }
+        Ok(state)
+    }
+
+    // FCM, with APNs alongside when configured
+    fn push_services(config: &AppConfig, cache_service: &Arc<CacheService>) -> Result<Arc<dyn NotificationService>, Box<dyn std::error::Error>> {
+        let fcm_service: Arc<dyn NotificationService> = match config.fcm_server_key.clone() {
+            Some(server_key) => {
+                tracing::info!("Using FCM notification service with server key");
+                Arc::new(FcmNotificationService::with_server_key(
+                    server_key, 
+                    cache_service.clone()
+                ))
+            }
+            None => {
+                tracing::warn!("FCM_SERVER_KEY not set, using mock notification service");
+                Arc::new(MockNotificationService)
+            }
+        };
+        let apns_service: Option<Arc<dyn NotificationService>> = match ApnsConfig::from_env() {
+            Some(apns_config) => {
+                tracing::info!("Sending to iOS devices through APNs");
+                Some(Arc::new(ApnsNotificationService::new(apns_config, cache_service.clone())?))
+            }
+            None => None,
+        };
+        Ok(Arc::new(MultiChannelNotificationService::new(
+            cache_service.clone(),
+            fcm_service,
+            apns_service,
+        )))
+    }
+
+    /// Whether an optional dependency was down at boot
+    pub fn degraded(&self) -> bool {
+        self.startup.get().is_some_and(|report| report.degraded)
+    }
+
+    // Wire every service on top of an existing cache and notifier; lets tests run the
+    // whole app against the in-memory cache
+    pub fn with_services(
+        config: AppConfig,
+        cache_service: Arc<CacheService>,
+        notification_service: Arc<dyn NotificationService>,
+        package_analyzer: Arc<dyn PackageImageAnalyzer>,
+    ) -> Self {
+        IdGenerator::set_default_format(config.id_format);
+
+        let runtime_config = Arc::new(RuntimeConfigService::new(RuntimeConfigWatch::from_env()));
+
+        let notification_templates = Arc::new(NotificationTemplateService::new(
+            cache_service.clone(),
+            runtime_config.clone(),
+            NotificationTemplateConfig::default(),
+        ));
+
+        // Everything is sent through the batcher, which applies templates, rate caps and digests
+        let notification_batcher = Arc::new(BatchingNotificationService::new(
+            cache_service.clone(),
+            notification_service,
+            notification_templates.clone(),
+            NotificationBatchConfig::default(),
+        ));
+        let notification_service: Arc<dyn NotificationService> = notification_batcher.clone();
+
+        let tenant_service = Arc::new(TenantService::new(cache_service.clone(), runtime_config.clone()));
+        let regions = Arc::new(RegionRegistry::serving(&config.regions));
+
+        let broadcast_service = Arc::new(BroadcastService::new(
+            cache_service.clone(),
+            notification_service.clone(),
+        ));
+
+        let otp_service = Arc::new(OtpService::new(cache_service.clone(), OtpConfig::default()));
+
+        let retention_service = Arc::new(RetentionService::new(cache_service.clone()));
+
+        let events_export = Arc::new(EventsExportService::new(cache_service.clone(), EventsExportConfig::default()));
+
+        let device_service = Arc::new(DeviceService::new(
+            cache_service.clone(),
+            notification_service.clone(),
+            otp_service.clone(),
+        ));
 
         let user_service = Arc::new(UserService::new(
             cache_service.clone(),
             notification_service.clone(),
+            device_service.clone(),
+            otp_service.clone(),
+            regions.clone(),
         ));
 
         let driver_service = Arc::new(DriverService::new(
             cache_service.clone(),
             notification_service.clone(),
+            DriverConfig::default(),
         ));
 
+        let onboarding_service = Arc::new(OnboardingService::new(
+            cache_service.clone(),
+            notification_service.clone(),
+            OnboardingConfig::default(),
+        ));
+
+        let earnings_calculator = Arc::new(EarningsCalculator::new(
+            cache_service.clone(),
+            EarningsConfig::default(),
+        ));
+
+        let tax_engine = Arc::new(TaxEngine::new(cache_service.clone(), regions.clone()));
+        let exchange_rates = Arc::new(ExchangeRateService::new(cache_service.clone()));
+        let calendar_service = Arc::new(CalendarService::new(cache_service.clone(), regions.clone(), CalendarConfig::default()));
+        let ledger_service = Arc::new(LedgerService::new(cache_service.clone(), LedgerConfig::default()));
+        // No provider settlement reports are fetched yet, so nightly reconciliation is idle
+        #[cfg(feature = "payments")]
+        let reconciliation_service = Arc::new(ReconciliationService::new(cache_service.clone(), ledger_service.clone(), Vec::new()));
+        #[cfg(feature = "payments")]
+        let dispute_service = {
+            let dispute_config = DisputeConfig::from_env();
+            if dispute_config.webhook_secret.is_none() {
+                tracing::warn!("PAYMENT_WEBHOOK_SECRET not set, chargeback webhooks will be refused");
+            }
+            Arc::new(DisputeService::new(cache_service.clone(), ledger_service.clone(), dispute_config))
+        };
+        // No mail provider is wired in yet; merchants see their invoices under /merchant/invoices
+        let invoice_service = Arc::new(InvoiceService::new(
+            cache_service.clone(),
+            ledger_service.clone(),
+            Arc::new(NoInvoiceMailer),
+            InvoiceConfig::default(),
+        ));
+
+        let route_service = Arc::new(RouteService::new(cache_service.clone()));
+
+        let dispatch_settings = Arc::new(DispatchSettingsService::new(
+            cache_service.clone(),
+            runtime_config.clone(),
+            regions.clone(),
+            DispatchConfig::default(),
+        ));
+
+        let package_analysis = Arc::new(PackageAnalysisService::new(
+            package_analyzer,
+            PackageAnalysisConfig::default(),
+        ));
+
+        // Local until `new` attaches Redis, so everything in-process still reaches its sockets
+        let realtime_bus = Arc::new(RealtimeBus::new(RealtimeBusConfig::default()));
+
+        #[cfg(feature = "realtime-ably")]
+        let ably_auth = Arc::new(AblyAuthService::new(cache_service.clone(), &config.ably_api_key, AblyAuthConfig::default()));
+
+        let realtime_publisher: Arc<dyn RealtimePublisher> = match config.realtime_provider {
+            #[cfg(feature = "realtime-ably")]
+            RealtimeProvider::Ably => match AblyPublisher::new(&config.ably_api_key, ably_auth.clone()) {
+                Some(publisher) => Arc::new(publisher),
+                None => {
+                    tracing::warn!("Ably selected without a usable API key, using self-hosted sockets");
+                    Arc::new(SocketPublisher::new(realtime_bus.clone()))
+                }
+            },
+            #[cfg(not(feature = "realtime-ably"))]
+            RealtimeProvider::Ably => {
+                tracing::warn!("Ably selected in a build without the realtime-ably feature, using self-hosted sockets");
+                Arc::new(SocketPublisher::new(realtime_bus.clone()))
+            }
+            RealtimeProvider::SelfHosted => Arc::new(SocketPublisher::new(realtime_bus.clone())),
+            RealtimeProvider::Mock => Arc::new(MockRealtimePublisher),
+        };
+
+        let status_feed = Arc::new(StatusFeedService::new(
+            cache_service.clone(),
+            realtime_publisher.clone(),
+            StatusFeedConfig::from_env(),
+        ));
+
+        let delivery_codes = Arc::new(DeliveryCodeService::new(
+            cache_service.clone(),
+            sms_sender_from_env(),
+            DeliveryCodeConfig::from_env(),
+        ));
+
+        let business_accounts = Arc::new(BusinessAccountService::new(cache_service.clone()));
+        let arrival_calls = Arc::new(ArrivalCallService::new(
+            cache_service.clone(),
+            voice_gateway_from_env(),
+            ArrivalCallConfig::from_env(),
+        ));
+
+        // Notifications and analytics react to what the services announce
+        let event_bus = Arc::new(RedisEventBus::new(cache_service.clone(), RedisEventBusConfig::from_env()));
+        event_bus.subscribe(Arc::new(DemandAnalytics::new(cache_service.clone())));
+        event_bus.subscribe(Arc::new(DeliveryNotifications::new(cache_service.clone(), notification_service.clone())));
+        event_bus.subscribe(Arc::new(RefundNotifications::new(cache_service.clone(), notification_service.clone())));
+        event_bus.subscribe(Arc::new(AssignmentNotifications::new(
+            cache_service.clone(),
+            route_service.clone(),
+            earnings_calculator.clone(),
+            notification_service.clone(),
+        )));
+        event_bus.subscribe(Arc::new(PickupNotifications::new(cache_service.clone(), route_service.clone(), notification_service.clone())));
+        event_bus.subscribe(Arc::new(DriverOutcomes::new(cache_service.clone(), driver_service.clone())));
+        let sagas = Arc::new(SagaOrchestrator::new(cache_service.clone(), SagaConfig::default()));
+
         let job_service = Arc::new(JobService::new(
             cache_service.clone(),
             driver_service.clone(),
+            route_service.clone(),
+            notification_service.clone(),
+            tenant_service.clone(),
+            regions.clone(),
+            earnings_calculator.clone(),
+            tax_engine.clone(),
+            dispatch_settings.clone(),
+            package_analysis.clone(),
+            realtime_publisher,
+            status_feed.clone(),
+            delivery_codes,
+            business_accounts.clone(),
+            arrival_calls.clone(),
+            calendar_service.clone(),
+            ledger_service.clone(),
+            invoice_service.clone(),
+            event_bus.clone(),
+            sagas,
+            JobHistoryConfig::from_env(),
+        ));
+
+        // Location and presence writes are queued and flushed to Redis off the request path
+        let write_behind = Arc::new(WriteBehindQueue::new(
+            cache_service.clone(),
+            WriteBehindConfig::default(),
+        ));
+        write_behind.clone().spawn_flusher();
+
+        let odometer = Arc::new(OdometerService::new(
+            cache_service.clone(),
+            notification_service.clone(),
+            OdometerConfig::from_env(),
+        ));
+
+        let dashboard_service = Arc::new(DashboardService::new(
+            cache_service.clone(),
+            DashboardConfig::default(),
+        ));
+
+        let alert_service = Arc::new(AlertService::new(
+            cache_service.clone(),
+            tenant_service.clone(),
+            dashboard_service.clone(),
+            AlertConfig::default(),
+        ));
+
+        let incident_service = Arc::new(IncidentService::new(cache_service.clone(), alert_service.clone()));
+
+        let risk_service = Arc::new(RiskService::new(
+            cache_service.clone(),
+            alert_service.clone(),
+            RiskConfig::default(),
+        ));
+
+        let location_service = Arc::new(LocationService::new(
+            cache_service.clone(),
+            risk_service.clone(),
+            route_service.clone(),
+            status_feed.clone(),
+            odometer.clone(),
+            write_behind.clone(),
+            LocationConfig::default(),
+        ));
+
+        let canary_service = Arc::new(CanaryService::new(
+            cache_service.clone(),
+            tenant_service.clone(),
+            user_service.clone(),
+            driver_service.clone(),
+            job_service.clone(),
+            alert_service.clone(),
+            CanaryConfig::default(),
+        ));
+
+        let handoff_config = HandoffConfig::from_env().unwrap_or_else(|| {
+            tracing::warn!("HANDOFF_SIGNING_KEY not set, handoff codes will only verify on this instance");
+            HandoffConfig::ephemeral()
+        });
+        let handoff_service = Arc::new(HandoffService::new(
+            cache_service.clone(),
+            job_service.clone(),
+            handoff_config,
+        ));
+
+        let directory_service = Arc::new(DirectoryService::new(
+            cache_service.clone(),
+            DirectoryConfig::default(),
+        ));
+
+        let demand_service = Arc::new(DemandService::new(
+            cache_service.clone(),
+            DemandConfig::default(),
+        ));
+
+        let export_service = Arc::new(ExportService::new(
+            cache_service.clone(),
+            ExportConfig::default(),
+        ));
+
+        let api_key_service = Arc::new(ApiKeyService::new(
+            cache_service.clone(),
+            ApiKeyConfig::default(),
+        ));
+
+        let quota_service = Arc::new(QuotaService::new(
+            cache_service.clone(),
+            notification_service.clone(),
+        ));
+
+        let presence_service = Arc::new(PresenceService::new(
+            cache_service.clone(),
+            PresenceConfig::default(),
+        ));
+
+        let driver_channel = Arc::new(DriverChannel::new(
+            cache_service.clone(),
+            presence_service.clone(),
+            realtime_bus.clone(),
+            event_bus.clone(),
+        ));
+
+        let send_queues = Arc::new(SendQueues::new(SendQueueConfig::default()));
+
+        let tracking_service = Arc::new(TrackingService::new(
+            cache_service.clone(),
+            driver_channel.clone(),
+            notification_service.clone(),
+            TrackingConfig::default(),
+        ));
+
+        let pooling_service = Arc::new(PoolingService::new(cache_service.clone(), PoolingConfig::default()));
+
+        let zone_service = Arc::new(ZoneService::new(cache_service.clone(), ZoneConfig::default()));
+
+        let moderation_service = Arc::new(ModerationService::new(
+            cache_service.clone(),
+            api_key_service.clone(),
+            driver_channel.clone(),
+        ));
+
+        let dispatcher_service = Arc::new(DispatcherService::new(
+            cache_service.clone(),
+            driver_service.clone(),
+            job_service.clone(),
             notification_service.clone(),
+            driver_channel.clone(),
+            earnings_calculator.clone(),
+            dispatch_settings.clone(),
+            DispatcherConfig::default(),
         ));
 
-        Ok(Self {
+        let workers = WorkerRuntime::new(tenant_service.clone(), regions.clone());
+        workers.spawn(Arc::new(SlaMonitor::new(
+            cache_service.clone(),
+            notification_service.clone(),
+            SlaConfig::default(),
+        )));
+        workers.spawn(Arc::new(DemandForecaster::new(demand_service.clone())));
+        workers.spawn(Arc::new(SurgePricing::new(
+            demand_service.clone(),
+            SurgePricingConfig::default(),
+        )));
+        workers.spawn(Arc::new(BroadcastScheduler::new(
+            broadcast_service.clone(),
+            BroadcastSchedulerConfig::default(),
+        )));
+        workers.spawn(Arc::new(NotificationDigest::new(
+            notification_batcher.clone(),
+            NotificationDigestConfig::default(),
+        )));
+        workers.spawn(Arc::new(DeferredNotifications::new(
+            notification_batcher.clone(),
+            DeferredNotificationsConfig::default(),
+        )));
+        workers.spawn(Arc::new(JobExpiry::new(
+            cache_service.clone(),
+            job_service.clone(),
+            JobExpiryConfig::default(),
+        )));
+        workers.spawn(Arc::new(JobEscalator::new(
+            cache_service.clone(),
+            job_service.clone(),
+            JobEscalationConfig::default(),
+        )));
+        workers.spawn(Arc::new(ChainedOffers::new(
+            cache_service.clone(),
+            dispatcher_service.clone(),
+            ChainedOffersConfig::default(),
+        )));
+        workers.spawn(Arc::new(AssignmentWatchdog::new(
+            cache_service.clone(),
+            job_service.clone(),
+            AssignmentWatchdogConfig::default(),
+        )));
+        workers.spawn(Arc::new(DriverAnalytics::new(
+            cache_service.clone(),
+            driver_service.clone(),
+            DriverAnalyticsConfig::default(),
+        )));
+        workers.spawn(Arc::new(BreakMonitor::new(
+            driver_service.clone(),
+            BreakMonitorConfig::default(),
+        )));
+        workers.spawn(Arc::new(DocumentExpiry::new(
+            onboarding_service.clone(),
+            DocumentExpiryConfig::default(),
+        )));
+        #[cfg(feature = "payments")]
+        workers.spawn(Arc::new(Reconciliation::new(
+            cache_service.clone(),
+            reconciliation_service.clone(),
+            ReconciliationConfig::default(),
+        )));
+        workers.spawn(Arc::new(Invoicing::new(
+            invoice_service.clone(),
+            InvoicingConfig::default(),
+        )));
+        workers.spawn(Arc::new(SagaRecovery::new(
+            job_service.clone(),
+            SagaRecoveryConfig::default(),
+        )));
+        workers.spawn(Arc::new(OtpCleanup::new(
+            otp_service.clone(),
+            OtpCleanupConfig::default(),
+        )));
+        workers.spawn(Arc::new(RetentionPurge::new(
+            retention_service.clone(),
+            RetentionPurgeConfig::default(),
+        )));
+        workers.spawn(Arc::new(EventsExporter::new(
+            events_export.clone(),
+            EventsExportWorkerConfig::default(),
+        )));
+        workers.spawn(Arc::new(StatusFeedFlusher::new(
+            status_feed.clone(),
+            StatusFeedWorkerConfig::default(),
+        )));
+
+        Self {
             user_service,
+            device_service,
+            otp_service,
+            retention_service,
+            events_export,
+            alert_service,
+            canary_service,
+            incident_service,
+            risk_service,
             driver_service,
+            onboarding_service,
             job_service,
+            business_accounts,
+            arrival_calls,
             cache_service,
+            location_service,
+            odometer,
+            route_service,
+            status_feed,
+            dashboard_service,
+            directory_service,
+            moderation_service,
+            demand_service,
+            export_service,
+            api_key_service,
+            quota_service,
+            dispatcher_service,
+            dispatch_settings,
+            package_analysis,
+            handoff_service,
+            tracking_service,
+            pooling_service,
+            #[cfg(feature = "realtime-ably")]
+            ably_auth,
+            zone_service,
+            driver_channel,
+            presence_service,
+            realtime_bus,
+            event_bus,
+            send_queues,
+            earnings_calculator,
+            tax_engine,
+            calendar_service,
+            ledger_service,
+            #[cfg(feature = "payments")]
+            reconciliation_service,
+            #[cfg(feature = "payments")]
+            dispute_service,
+            invoice_service,
+            exchange_rates,
+            tenant_service,
+            regions,
             notification_service,
+            notification_batcher,
+            notification_templates,
+            broadcast_service,
+            write_behind,
+            runtime_config,
+            startup: OnceLock::new(),
+            workers,
             config,
-        })
+        }
+    }
+
+    // Stop background workers and flush queued cache writes; call after the server stops
+    pub async fn shutdown(&self) {
+        self.workers.shutdown();
+        self.write_behind.shutdown().await;
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,189 @@
+// src/utils/field_crypto.rs
+// Field-level encryption for personal data at rest. Phone numbers, emails and payment
+// account numbers are sealed with AES-256-GCM before a record reaches Redis or the
+// repository, and opened again on the way out; see `CacheService::with_cipher`.
+//
+// A sealed value is "enc:<key id>:<base64 nonce + ciphertext>". Keys are rotated by
+// putting a new one first: values sealed under an older key still open, and are resealed
+// under the new one the next time their record is written. Values written before
+// encryption was turned on don't carry the prefix and are read as they are.
+//
+// Lookups by email or phone can't use the ciphertext, which differs on every write, so
+// they go through a blind index: a keyed hash of the normalised value.
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::{aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN}, hmac, rand::{SecureRandom, SystemRandom}};
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{driver::Driver, job::Job, user::{PaymentMethod, User}},
+};
+
+const SEALED_PREFIX: &str = "enc:";
+
+pub struct FieldCipher {
+    keys: Vec<(String, LessSafeKey)>, // New values are sealed with the first
+    index_key: hmac::Key,
+    rng: SystemRandom,
+}
+
+impl FieldCipher {
+    /// `keys` are (id, 32-byte key), newest first
+    pub fn new(keys: Vec<(String, Vec<u8>)>, index_key: &[u8]) -> Result<Self, AppError> {
+        if keys.is_empty() {
+            return Err(AppError::InternalServer("At least one PII encryption key is required".to_string()));
+        }
+        let keys = keys.into_iter()
+            .map(|(id, key)| {
+                if id.is_empty() || id.contains(':') {
+                    return Err(AppError::InternalServer(format!("Invalid PII key id {:?}", id)));
+                }
+                let key = UnboundKey::new(&AES_256_GCM, &key)
+                    .map_err(|_| AppError::InternalServer(format!("PII key {} must be 32 bytes", id)))?;
+                Ok((id, LessSafeKey::new(key)))
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+        Ok(Self {
+            keys,
+            index_key: hmac::Key::new(hmac::HMAC_SHA256, index_key),
+            rng: SystemRandom::new(),
+        })
+    }
+
+    /// From `PII_ENCRYPTION_KEYS` ("id:base64,id:base64", newest first) and `PII_INDEX_KEY`;
+    /// None when no keys are configured
+    pub fn from_env() -> Result<Option<Self>, AppError> {
+        let Some(spec) = std::env::var("PII_ENCRYPTION_KEYS").ok().filter(|value| !value.is_empty()) else {
+            return Ok(None);
+        };
+        let index_key = std::env::var("PII_INDEX_KEY").ok().filter(|value| !value.is_empty())
+            .ok_or_else(|| AppError::InternalServer("PII_INDEX_KEY must be set with PII_ENCRYPTION_KEYS".to_string()))?;
+        let keys = spec.split(',')
+            .map(|entry| {
+                let (id, key) = entry.trim().split_once(':')
+                    .ok_or_else(|| AppError::InternalServer("PII_ENCRYPTION_KEYS entries are id:base64".to_string()))?;
+                let key = STANDARD.decode(key)
+                    .map_err(|_| AppError::InternalServer(format!("PII key {} is not valid base64", id)))?;
+                Ok((id.to_string(), key))
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+        Self::new(keys, index_key.as_bytes()).map(Some)
+    }
+
+    pub fn active_key_id(&self) -> &str {
+        &self.keys[0].0
+    }
+
+    pub fn seal(&self, plaintext: &str) -> String {
+        let (key_id, key) = &self.keys[0];
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng.fill(&mut nonce).expect("system randomness unavailable");
+        let mut sealed = plaintext.as_bytes().to_vec();
+        key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut sealed)
+            .expect("AES-GCM sealing cannot fail for in-memory buffers");
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&sealed);
+        format!("{}{}:{}", SEALED_PREFIX, key_id, STANDARD.encode(payload))
+    }
+
+    pub fn open(&self, value: &str) -> Result<String, AppError> {
+        let Some(sealed) = value.strip_prefix(SEALED_PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let corrupt = || AppError::InternalServer("Encrypted field could not be opened".to_string());
+        let (key_id, payload) = sealed.split_once(':').ok_or_else(corrupt)?;
+        let (_, key) = self.keys.iter()
+            .find(|(id, _)| id == key_id)
+            .ok_or_else(|| AppError::InternalServer(format!("No PII key {} to open a field with", key_id)))?;
+        let mut payload = STANDARD.decode(payload).map_err(|_| corrupt())?;
+        if payload.len() < NONCE_LEN {
+            return Err(corrupt());
+        }
+        let mut ciphertext = payload.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&payload).map_err(|_| corrupt())?;
+        let plaintext = key.open_in_place(nonce, Aad::empty(), &mut ciphertext).map_err(|_| corrupt())?;
+        String::from_utf8(plaintext.to_vec()).map_err(|_| corrupt())
+    }
+
+    /// Stable stand-in for a value in lookup keys; case and surrounding space don't matter
+    pub fn blind_index(&self, value: &str) -> String {
+        hmac::sign(&self.index_key, value.trim().to_lowercase().as_bytes())
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    pub fn seal_fields<T: PiiFields>(&self, record: &mut T) {
+        for field in record.pii_fields() {
+            if !field.starts_with(SEALED_PREFIX) {
+                *field = self.seal(field);
+            }
+        }
+    }
+
+    pub fn open_fields<T: PiiFields>(&self, record: &mut T) -> Result<(), AppError> {
+        for field in record.pii_fields() {
+            *field = self.open(field)?;
+        }
+        Ok(())
+    }
+}
+
+/// Records carrying personal data that is sealed before they are stored
+pub trait PiiFields {
+    fn pii_fields(&mut self) -> Vec<&mut String>;
+}
+
+impl PiiFields for User {
+    fn pii_fields(&mut self) -> Vec<&mut String> {
+        vec![&mut self.email, &mut self.phone_number]
+    }
+}
+
+impl PiiFields for Driver {
+    fn pii_fields(&mut self) -> Vec<&mut String> {
+        vec![&mut self.email, &mut self.phone_number]
+    }
+}
+
+impl PiiFields for Job {
+    fn pii_fields(&mut self) -> Vec<&mut String> {
+        vec![&mut self.pickup_location.contact_phone, &mut self.dropoff_location.contact_phone]
+    }
+}
+
+impl PiiFields for PaymentMethod {
+    fn pii_fields(&mut self) -> Vec<&mut String> {
+        vec![&mut self.account_number]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{mocks::fixtures::Faker, models::user::UserType};
+
+    #[test]
+    fn test_rotated_keys_still_open_older_values() {
+        let old = FieldCipher::new(vec![("k1".to_string(), vec![1; 32])], b"index").unwrap();
+        let rotated = FieldCipher::new(vec![("k2".to_string(), vec![2; 32]), ("k1".to_string(), vec![1; 32])], b"index").unwrap();
+
+        let sealed = old.seal("+233241234567");
+        assert!(sealed.starts_with("enc:k1:") && !sealed.contains("241234567"));
+        assert_ne!(sealed, old.seal("+233241234567"));
+        assert_eq!(rotated.open(&sealed).unwrap(), "+233241234567");
+        assert!(rotated.seal("+233241234567").starts_with("enc:k2:"));
+        assert!(old.open(&rotated.seal("x")).is_err());
+        assert_eq!(old.open("written before encryption").unwrap(), "written before encryption");
+        assert_eq!(old.blind_index(" Ama@Example.com"), rotated.blind_index("ama@example.com"));
+
+        let mut user = Faker::seeded(70).user(UserType::Customer);
+        let email = user.email.clone();
+        rotated.seal_fields(&mut user);
+        assert!(user.email.starts_with("enc:k2:"));
+        rotated.open_fields(&mut user).unwrap();
+        assert_eq!(user.email, email);
+        assert!(FieldCipher::new(vec![("short".to_string(), vec![0; 16])], b"index").is_err());
+    }
+}
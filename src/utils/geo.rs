@@ -0,0 +1,33 @@
+// src/utils/geo.rs
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// Great-circle distance between two (latitude, longitude) points in kilometres
+pub fn haversine_km(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let lat1_rad = from.0.to_radians();
+    let lat2_rad = to.0.to_radians();
+    let delta_lat = (to.0 - from.0).to_radians();
+    let delta_lon = (to.1 - from.1).to_radians();
+
+    let a = (delta_lat / 2.0).sin().powi(2) +
+           lat1_rad.cos() * lat2_rad.cos() * (delta_lon / 2.0).sin().powi(2);
+    let c = 2.0 * a.sqrt().atan2((1.0 - a).sqrt());
+
+    EARTH_RADIUS_KM * c
+}
+
+/// Total length of a path in kilometres
+pub fn path_length_km(points: &[(f64, f64)]) -> f64 {
+    points.windows(2).map(|pair| haversine_km(pair[0], pair[1])).sum()
+}
+
+/// Initial compass bearing from one point towards another, in degrees from north (0..360)
+pub fn bearing_deg(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let lat1_rad = from.0.to_radians();
+    let lat2_rad = to.0.to_radians();
+    let delta_lon = (to.1 - from.1).to_radians();
+
+    let y = delta_lon.sin() * lat2_rad.cos();
+    let x = lat1_rad.cos() * lat2_rad.sin() - lat1_rad.sin() * lat2_rad.cos() * delta_lon.cos();
+    (y.atan2(x).to_degrees() + 360.0) % 360.0
+}
@@ -0,0 +1,88 @@
+// src/utils/geohash.rs
+// Standard base32 geohash, used to bucket points into map cells
+
+const BASE32: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+pub const MAX_PRECISION: usize = 12;
+
+/// Encode a (latitude, longitude) point to a geohash of `precision` characters
+pub fn encode(latitude: f64, longitude: f64, precision: usize) -> String {
+    let precision = precision.clamp(1, MAX_PRECISION);
+    let (mut lat_range, mut lon_range) = ((-90.0, 90.0), (-180.0, 180.0));
+    let mut hash = String::with_capacity(precision);
+    let mut even_bit = true; // Bits alternate, starting with longitude
+    let (mut bits, mut char_index) = (0, 0usize);
+
+    while hash.len() < precision {
+        let (range, value): (&mut (f64, f64), f64) = if even_bit {
+            (&mut lon_range, longitude)
+        } else {
+            (&mut lat_range, latitude)
+        };
+        let mid = (range.0 + range.1) / 2.0;
+        char_index <<= 1;
+        if value >= mid {
+            char_index |= 1;
+            range.0 = mid;
+        } else {
+            range.1 = mid;
+        }
+        even_bit = !even_bit;
+
+        bits += 1;
+        if bits == 5 {
+            hash.push(BASE32[char_index] as char);
+            bits = 0;
+            char_index = 0;
+        }
+    }
+    hash
+}
+
+/// Centre point of a geohash cell, or None if it contains invalid characters
+pub fn decode_center(hash: &str) -> Option<(f64, f64)> {
+    let (mut lat_range, mut lon_range) = ((-90.0, 90.0), (-180.0, 180.0));
+    let mut even_bit = true;
+
+    for c in hash.bytes() {
+        let index = BASE32.iter().position(|&b| b == c)?;
+        for shift in (0..5).rev() {
+            let range: &mut (f64, f64) = if even_bit { &mut lon_range } else { &mut lat_range };
+            let mid = (range.0 + range.1) / 2.0;
+            if (index >> shift) & 1 == 1 {
+                range.0 = mid;
+            } else {
+                range.1 = mid;
+            }
+            even_bit = !even_bit;
+        }
+    }
+
+    Some(((lat_range.0 + lat_range.1) / 2.0, (lon_range.0 + lon_range.1) / 2.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_known_value() {
+        // Reference value from the original geohash.org examples
+        assert_eq!(encode(57.64911, 10.40744, 11), "u4pruydqqvj");
+    }
+
+    #[test]
+    fn test_prefix_is_coarser_cell() {
+        let fine = encode(5.6037, -0.1870, 7); // Accra
+        assert_eq!(&fine[..5], encode(5.6037, -0.1870, 5));
+    }
+
+    #[test]
+    fn test_decode_center_roundtrip() {
+        let hash = encode(5.6037, -0.1870, 8);
+        let (lat, lon) = decode_center(&hash).unwrap();
+        assert!((lat - 5.6037).abs() < 0.001);
+        assert!((lon + 0.1870).abs() < 0.001);
+        assert!(decode_center("abc!").is_none());
+    }
+}
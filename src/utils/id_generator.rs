@@ -1,7 +1,9 @@
 // src/utils/id_generator.rs
-use chrono::{DateTime, Utc, TimeZone};
+use chrono::{DateTime, Datelike, Utc, TimeZone};
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum IdType {
@@ -15,6 +17,19 @@ pub enum IdType {
     SupportTicket,
     Verification,
     Reward,
+    Credit,
+    Batch,
+    ApiKey,
+    Broadcast,
+    Pool,
+    Zone,
+    Period,
+    LedgerEntry,
+    Discrepancy,
+    Dispute,
+    Invoice,
+    Incident,
+    Saga,
 }
 
 impl IdType {
@@ -30,11 +45,74 @@ impl IdType {
             IdType::SupportTicket => "tic",
             IdType::Verification => "ver",
             IdType::Reward => "rew",
+            IdType::Credit => "crd",
+            IdType::Batch => "bat",
+            IdType::ApiKey => "key",
+            IdType::Broadcast => "brd",
+            IdType::Pool => "pol",
+            IdType::Zone => "zon",
+            IdType::Period => "per",
+            IdType::LedgerEntry => "led",
+            IdType::Discrepancy => "dsc",
+            IdType::Dispute => "dsp",
+            IdType::Invoice => "inv",
+            IdType::Incident => "icd",
+            IdType::Saga => "sag",
+        }
+    }
+
+    pub fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix {
+            "usr" => Some(IdType::User),
+            "drv" => Some(IdType::Driver),
+            "job" => Some(IdType::Job),
+            "veh" => Some(IdType::Vehicle),
+            "pay" => Some(IdType::Payment),
+            "add" => Some(IdType::Address),
+            "not" => Some(IdType::Notification),
+            "tic" => Some(IdType::SupportTicket),
+            "ver" => Some(IdType::Verification),
+            "rew" => Some(IdType::Reward),
+            "crd" => Some(IdType::Credit),
+            "bat" => Some(IdType::Batch),
+            "key" => Some(IdType::ApiKey),
+            "brd" => Some(IdType::Broadcast),
+            "pol" => Some(IdType::Pool),
+            "zon" => Some(IdType::Zone),
+            "per" => Some(IdType::Period),
+            "led" => Some(IdType::LedgerEntry),
+            "dsc" => Some(IdType::Discrepancy),
+            "dsp" => Some(IdType::Dispute),
+            "inv" => Some(IdType::Invoice),
+            "icd" => Some(IdType::Incident),
+            "sag" => Some(IdType::Saga),
+            _ => None,
         }
     }
 }
 
+/// Which format `IdGenerator::generate` produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum IdFormat {
+    /// {prefix}-{YYMMDD}-{5 random chars}
+    #[default]
+    Legacy,
+    /// {prefix}-{26 char ULID}: 48-bit millisecond timestamp + 80 random bits,
+    /// Crockford base32, sorts by creation time
+    Sortable,
+}
+
+// Process-wide format switch, set once at startup from `AppConfig`
+static SORTABLE_BY_DEFAULT: AtomicBool = AtomicBool::new(false);
+
+// Last (millisecond, random) pair handed out, so IDs minted within the same
+// millisecond still sort in generation order
+static LAST_SORTABLE: Mutex<(u64, u128)> = Mutex::new((0, 0));
 
+const CROCKFORD_CHARS: &[u8] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const SORTABLE_LEN: usize = 26;
+const RANDOM_BITS: u32 = 80;
+const RANDOM_MASK: u128 = (1 << RANDOM_BITS) - 1;
 
 impl fmt::Display for IdType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -45,10 +123,79 @@ impl fmt::Display for IdType {
 pub struct IdGenerator;
 
 impl IdGenerator {
-    /// Generate a unique ID with format: {prefix}-{date}-{random_suffix}
+    /// Generate a unique ID in the configured format (see `set_default_format`).
+    /// Legacy format: {prefix}-{date}-{random_suffix}
     /// Where random_suffix is 5 characters: 3 hexchars + 2 alphanumeric or 3 alphanumeric + 2 hexchars
     pub fn generate(id_type: IdType) -> String {
-        Self::generate_with_timestamp(id_type, Utc::now())
+        match Self::default_format() {
+            IdFormat::Legacy => Self::generate_with_timestamp(id_type, Utc::now()),
+            IdFormat::Sortable => Self::generate_sortable(id_type),
+        }
+    }
+
+    pub fn set_default_format(format: IdFormat) {
+        SORTABLE_BY_DEFAULT.store(format == IdFormat::Sortable, Ordering::Relaxed);
+    }
+
+    pub fn default_format() -> IdFormat {
+        if SORTABLE_BY_DEFAULT.load(Ordering::Relaxed) {
+            IdFormat::Sortable
+        } else {
+            IdFormat::Legacy
+        }
+    }
+
+    /// Generate a time-ordered ID: {prefix}-{ULID}.
+    /// IDs are strictly increasing within this process, even within one millisecond.
+    pub fn generate_sortable(id_type: IdType) -> String {
+        let now_ms = Utc::now().timestamp_millis().max(0) as u64;
+        let (millis, random) = {
+            let mut last = LAST_SORTABLE.lock().unwrap();
+            let next = if now_ms > last.0 {
+                (now_ms, rand::random::<u128>() & RANDOM_MASK)
+            } else if last.1 < RANDOM_MASK {
+                (last.0, last.1 + 1)
+            } else {
+                // Random space for this millisecond is exhausted; borrow the next one
+                (last.0 + 1, rand::random::<u128>() & RANDOM_MASK)
+            };
+            *last = next;
+            next
+        };
+        format!("{}-{}", id_type.to_prefix(), Self::encode_sortable(millis, random))
+    }
+
+    /// Generate a sortable ID for a specific timestamp (useful for testing and backfills)
+    pub fn generate_sortable_with_timestamp(id_type: IdType, timestamp: DateTime<Utc>) -> String {
+        let millis = timestamp.timestamp_millis().max(0) as u64;
+        let random = rand::random::<u128>() & RANDOM_MASK;
+        format!("{}-{}", id_type.to_prefix(), Self::encode_sortable(millis, random))
+    }
+
+    fn encode_sortable(millis: u64, random: u128) -> String {
+        let value = ((millis as u128) << RANDOM_BITS) | (random & RANDOM_MASK);
+        (0..SORTABLE_LEN)
+            .map(|i| {
+                let shift = 5 * (SORTABLE_LEN - 1 - i);
+                CROCKFORD_CHARS[((value >> shift) & 0x1f) as usize] as char
+            })
+            .collect()
+    }
+
+    fn decode_sortable(encoded: &str) -> Option<(u64, u128)> {
+        if encoded.len() != SORTABLE_LEN {
+            return None;
+        }
+        let mut value: u128 = 0;
+        for (i, c) in encoded.bytes().enumerate() {
+            let digit = CROCKFORD_CHARS.iter().position(|&d| d == c)? as u128;
+            // 26 chars carry 130 bits; the first char may only use the low 3
+            if i == 0 && digit > 7 {
+                return None;
+            }
+            value = (value << 5) | digit;
+        }
+        Some(((value >> RANDOM_BITS) as u64, value & RANDOM_MASK))
     }
 
     /// Generate ID with a specific timestamp (useful for testing)
@@ -94,17 +241,24 @@ impl IdGenerator {
     fn generate_from_chars(charset: &[u8], n: usize) -> String {
         use rand::Rng;
         
-        let mut rng = rand::thread_rng();
+        let mut rng = rand::rng();
         (0..n)
             .map(|_| {
-                let idx = rng.gen_range(0..charset.len());
+                let idx = rng.random_range(0..charset.len());
                 charset[idx] as char
             })
             .collect()
     }
 
-    /// Parse an ID to extract its components
+    /// Parse an ID to extract its components; accepts both legacy and sortable IDs
     pub fn parse_id(id: &str) -> Option<ParsedId> {
+        if let Some((prefix, encoded)) = id.split_once('-')
+            && encoded.len() == SORTABLE_LEN
+            && !encoded.contains('-')
+        {
+            return Self::parse_sortable(prefix, encoded);
+        }
+
         let parts: Vec<&str> = id.split('-').collect();
         if parts.len() != 3 {
             return None;
@@ -119,19 +273,7 @@ impl IdGenerator {
         }
 
         // Determine ID type from prefix
-        let id_type = match prefix {
-            "usr" => IdType::User,
-            "drv" => IdType::Driver,
-            "job" => IdType::Job,
-            "veh" => IdType::Vehicle,
-            "pay" => IdType::Payment,
-            "add" => IdType::Address,
-            "not" => IdType::Notification,
-            "tic" => IdType::SupportTicket,
-            "ver" => IdType::Verification,
-            "rew" => IdType::Reward,
-            _ => return None,
-        };
+        let id_type = IdType::from_prefix(prefix)?;
 
         // Parse date (YYMMDD format)
         let year = format!("20{}", &date_part[0..2]).parse::<i32>().ok()?;
@@ -139,16 +281,34 @@ impl IdGenerator {
         let day = date_part[4..6].parse::<u32>().ok()?;
 
         // Validate date components
-        if month < 1 || month > 12 || day < 1 || day > 31 {
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
             return None;
         }
 
         Some(ParsedId {
             id_type,
+            format: IdFormat::Legacy,
             year,
             month,
             day,
             random_suffix: random_suffix.to_string(),
+            created_at: None,
+        })
+    }
+
+    fn parse_sortable(prefix: &str, encoded: &str) -> Option<ParsedId> {
+        let id_type = IdType::from_prefix(prefix)?;
+        let (millis, _) = Self::decode_sortable(encoded)?;
+        let created_at = Utc.timestamp_millis_opt(i64::try_from(millis).ok()?).single()?;
+
+        Some(ParsedId {
+            id_type,
+            format: IdFormat::Sortable,
+            year: created_at.year(),
+            month: created_at.month(),
+            day: created_at.day(),
+            random_suffix: encoded[10..].to_string(),
+            created_at: Some(created_at),
         })
     }
 
@@ -188,10 +348,12 @@ impl IdGenerator {
 #[derive(Debug, Clone, PartialEq)]
 pub struct ParsedId {
     pub id_type: IdType,
+    pub format: IdFormat,
     pub year: i32,
     pub month: u32,
     pub day: u32,
     pub random_suffix: String,
+    pub created_at: Option<DateTime<Utc>>, // Exact creation time; sortable IDs only
 }
 
 // Custom error type for ID generation
@@ -220,24 +382,7 @@ pub trait WithGeneratedId {
     }
 }
 
-// Example implementation for your models
-impl WithGeneratedId for crate::models::user::User {
-    fn set_generated_id(&mut self, id_type: IdType) {
-        self.id = IdGenerator::generate(id_type);
-    }
-}
-
-impl WithGeneratedId for crate::models::driver::Driver {
-    fn set_generated_id(&mut self, id_type: IdType) {
-        self.id = IdGenerator::generate(id_type);
-    }
-}
-
-impl WithGeneratedId for crate::models::job::Job {
-    fn set_generated_id(&mut self, id_type: IdType) {
-        self.id = IdGenerator::generate(id_type);
-    }
-}
+// Users, drivers and jobs carry typed IDs (`UserId::generate()` etc.) instead
 
 // Utility functions for common ID types
 pub fn generate_user_id() -> String {
@@ -260,6 +405,29 @@ pub fn generate_payment_id() -> String {
     IdGenerator::generate(IdType::Payment)
 }
 
+impl IdGenerator {
+    // Add date parsing capability to our ID generator
+    pub fn parse_creation_date(id: &str) -> Option<DateTime<Utc>> {
+        // Sortable IDs carry the exact time; legacy ones only the day
+        // Example: "231207" -> December 7, 2023
+        let parsed = Self::parse_id(id)?;
+        parsed.created_at.or_else(|| parsed.to_datetime())
+    }
+    
+    pub fn is_id_recent(id: &str, max_age_days: i64) -> Option<bool> {
+        Self::parse_creation_date(id).map(|created_at| {
+            let age = Utc::now().signed_duration_since(created_at);
+            age.num_days() <= max_age_days
+        })
+    }
+}
+
+impl ParsedId {
+    pub fn to_datetime(&self) -> Option<DateTime<Utc>> {
+        Utc.with_ymd_and_hms(self.year, self.month, self.day, 0, 0, 0).single()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -304,40 +472,56 @@ mod tests {
             let suffix = IdGenerator::generate_random_suffix();
             assert_eq!(suffix.len(), 5);
             
-            // Check that it contains both hex and alphanumeric characters
-            let has_hex = suffix.chars().any(|c| c.is_ascii_hexdigit() && c.is_ascii_lowercase());
-            let has_alnum = suffix.chars().any(|c| c.is_ascii_alphanumeric());
-            
-            assert!(has_hex, "Suffix should contain hex characters: {}", suffix);
-            assert!(has_alnum, "Suffix should contain alphanumeric characters: {}", suffix);
+            // Three lowercase hex characters then two alphanumeric, or the other way round.
+            // Hex can be all digits, so this checks the characters, not that a letter appears.
+            let is_hex = |part: &str| part.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c));
+            assert!(is_hex(&suffix[..3]) || is_hex(&suffix[3..]), "Suffix should contain a run of lowercase hex: {}", suffix);
+            assert!(suffix.chars().all(|c| c.is_ascii_alphanumeric()), "Suffix should be alphanumeric: {}", suffix);
         }
     }
-}
 
-impl IdGenerator {
-    // Add date parsing capability to our ID generator
-    pub fn parse_creation_date(id: &str) -> Option<DateTime<Utc>> {
-        if let Some(parsed) = Self::parse_id(id) {
-            // Convert YYMMDD to DateTime
-            // Example: "231207" -> December 7, 2023
-            let year = 2000 + parsed.year; // Assuming YY is years since 2000
-            let date = Utc.with_ymd_and_hms(year, parsed.month, parsed.day, 0, 0, 0);
-            date.single()
-        } else {
-            None
-        }
+    #[test]
+    fn test_sortable_id_round_trip() {
+        let created = Utc.with_ymd_and_hms(2024, 3, 9, 14, 30, 5).unwrap() + chrono::Duration::milliseconds(250);
+        let id = IdGenerator::generate_sortable_with_timestamp(IdType::Job, created);
+        assert!(id.starts_with("job-"));
+        assert_eq!(id.len(), 4 + 26);
+
+        let parsed = IdGenerator::parse_id(&id).unwrap();
+        assert_eq!(parsed.id_type, IdType::Job);
+        assert_eq!(parsed.format, IdFormat::Sortable);
+        assert_eq!((parsed.year, parsed.month, parsed.day), (2024, 3, 9));
+        assert_eq!(parsed.created_at, Some(created));
+        assert_eq!(IdGenerator::parse_creation_date(&id), Some(created));
+        assert!(IdGenerator::validate_id(&id, Some(IdType::Job)));
+        assert!(!IdGenerator::validate_id(&id, Some(IdType::User)));
     }
-    
-    pub fn is_id_recent(id: &str, max_age_days: i64) -> Option<bool> {
-        Self::parse_creation_date(id).map(|created_at| {
-            let age = Utc::now().signed_duration_since(created_at);
-            age.num_days() <= max_age_days
-        })
+
+    #[test]
+    fn test_sortable_ids_are_monotonic() {
+        let ids: Vec<String> = (0..1000).map(|_| IdGenerator::generate_sortable(IdType::Driver)).collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
     }
-}
 
-impl ParsedId {
-    pub fn to_datetime(&self) -> Option<DateTime<Utc>> {
-        Utc.with_ymd_and_hms(self.year, self.month, self.day, 0, 0, 0).single()
+    #[test]
+    fn test_sortable_ids_order_by_time() {
+        let earlier = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let later = earlier + chrono::Duration::milliseconds(1);
+        let a = IdGenerator::generate_sortable_with_timestamp(IdType::User, earlier);
+        let b = IdGenerator::generate_sortable_with_timestamp(IdType::User, later);
+        assert!(a < b);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_sortable_rejects_bad_characters() {
+        // 'U' is not in the Crockford alphabet, and a leading '8' would overflow 128 bits
+        assert!(IdGenerator::parse_id("usr-01HRZ3U0000000000000000000").is_none());
+        assert!(IdGenerator::parse_id("usr-80000000000000000000000000").is_none());
+    }
+
+    #[test]
+    fn test_legacy_creation_date() {
+        let created = IdGenerator::parse_creation_date("usr-231207-a1b2c").unwrap();
+        assert_eq!(created, Utc.with_ymd_and_hms(2023, 12, 7, 0, 0, 0).unwrap());
+    }
+}
@@ -0,0 +1,90 @@
+// src/utils/polyline.rs
+// Google encoded polyline format (precision 5), used to store route history compactly
+
+/// Encode (latitude, longitude) pairs into a polyline string
+pub fn encode(points: &[(f64, f64)]) -> String {
+    let mut encoded = String::new();
+    let mut previous = (0i64, 0i64);
+
+    for &(latitude, longitude) in points {
+        let current = (to_e5(latitude), to_e5(longitude));
+        encode_value(current.0 - previous.0, &mut encoded);
+        encode_value(current.1 - previous.1, &mut encoded);
+        previous = current;
+    }
+
+    encoded
+}
+
+/// Decode a polyline string back into (latitude, longitude) pairs
+pub fn decode(encoded: &str) -> Option<Vec<(f64, f64)>> {
+    let bytes = encoded.as_bytes();
+    let mut index = 0;
+    let mut current = (0i64, 0i64);
+    let mut points = Vec::new();
+
+    while index < bytes.len() {
+        current.0 += decode_value(bytes, &mut index)?;
+        current.1 += decode_value(bytes, &mut index)?;
+        points.push((current.0 as f64 / 1e5, current.1 as f64 / 1e5));
+    }
+
+    Some(points)
+}
+
+fn to_e5(value: f64) -> i64 {
+    (value * 1e5).round() as i64
+}
+
+fn encode_value(value: i64, out: &mut String) {
+    let mut value = if value < 0 { !(value << 1) } else { value << 1 };
+    while value >= 0x20 {
+        out.push((((value & 0x1f) | 0x20) as u8 + 63) as char);
+        value >>= 5;
+    }
+    out.push((value as u8 + 63) as char);
+}
+
+fn decode_value(bytes: &[u8], index: &mut usize) -> Option<i64> {
+    let mut result = 0i64;
+    let mut shift = 0;
+
+    loop {
+        let byte = (*bytes.get(*index)? as i64) - 63;
+        *index += 1;
+        if !(0..0x40).contains(&byte) || shift > 60 {
+            return None;
+        }
+        result |= (byte & 0x1f) << shift;
+        shift += 5;
+        if byte < 0x20 {
+            break;
+        }
+    }
+
+    Some(if result & 1 == 1 { !(result >> 1) } else { result >> 1 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_reference_example() {
+        // Example from the Google polyline documentation
+        let points = vec![(38.5, -120.2), (40.7, -120.95), (43.252, -126.453)];
+        assert_eq!(encode(&points), "_p~iF~ps|U_ulLnnqC_mqNvxq`@");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let points = vec![(5.6037, -0.1870), (5.5560, -0.1820), (6.6885, -1.6244)];
+        let decoded = decode(&encode(&points)).unwrap();
+        assert_eq!(decoded, points);
+    }
+
+    #[test]
+    fn test_decode_rejects_truncated_input() {
+        assert!(decode("_p~iF~ps|U_").is_none());
+    }
+}
@@ -0,0 +1,51 @@
+// src/utils/secret_hash.rs
+// Salted hashes for secrets we only ever need to check, never read back: one-time codes
+// and session tokens. Stored as "<salt>.<tag>" in URL-safe base64, where the tag is an
+// HMAC-SHA256 of the secret keyed by a random per-secret salt.
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ring::{hmac, rand::{SecureRandom, SystemRandom}};
+
+const SALT_LENGTH: usize = 16;
+
+pub fn salted_hash(secret: &str) -> String {
+    let mut salt = [0u8; SALT_LENGTH];
+    SystemRandom::new().fill(&mut salt).expect("system randomness unavailable");
+    let tag = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &salt), secret.as_bytes());
+    format!("{}.{}", URL_SAFE_NO_PAD.encode(salt), URL_SAFE_NO_PAD.encode(tag.as_ref()))
+}
+
+/// Whether `secret` is the one `stored` was made from. The tag comparison is
+/// constant-time, and anything malformed simply doesn't match.
+pub fn verify_salted(secret: &str, stored: &str) -> bool {
+    let Some((salt, tag)) = stored.split_once('.') else {
+        return false;
+    };
+    let (Ok(salt), Ok(tag)) = (URL_SAFE_NO_PAD.decode(salt), URL_SAFE_NO_PAD.decode(tag)) else {
+        return false;
+    };
+    hmac::verify(&hmac::Key::new(hmac::HMAC_SHA256, &salt), secret.as_bytes(), &tag).is_ok()
+}
+
+/// Random lowercase hex, e.g. for the unguessable part of a session token
+pub fn random_hex(bytes: usize) -> String {
+    let mut buffer = vec![0u8; bytes];
+    SystemRandom::new().fill(&mut buffer).expect("system randomness unavailable");
+    buffer.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_secret_hashes_differently_but_still_verifies() {
+        let first = salted_hash("482913");
+        let second = salted_hash("482913");
+        assert_ne!(first, second);
+        assert!(verify_salted("482913", &first) && verify_salted("482913", &second));
+        assert!(!verify_salted("482914", &first));
+        for malformed in ["", "482913", "not base64.at all", "."] {
+            assert!(!verify_salted("482913", malformed), "{}", malformed);
+        }
+    }
+}
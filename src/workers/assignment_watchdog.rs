@@ -0,0 +1,152 @@
+// src/workers/assignment_watchdog.rs
+// Puts jobs back up for dispatch when the assigned driver stops responding before pickup
+use async_trait::async_trait;
+use chrono::{Duration as ChronoDuration, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    services::{cache_service::CacheService, job_service::{JobOperations, JobService}},
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct AssignmentWatchdogConfig {
+    pub check_interval_seconds: u64,
+    pub unresponsive_after_seconds: i64, // Silence before pickup that counts as abandonment
+}
+
+impl Default for AssignmentWatchdogConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 30,
+            unresponsive_after_seconds: 300,
+        }
+    }
+}
+
+pub struct AssignmentWatchdog {
+    cache_service: Arc<CacheService>,
+    job_service: Arc<JobService>,
+    config: AssignmentWatchdogConfig,
+}
+
+impl AssignmentWatchdog {
+    pub fn new(cache_service: Arc<CacheService>, job_service: Arc<JobService>, config: AssignmentWatchdogConfig) -> Self {
+        Self {
+            cache_service,
+            job_service,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for AssignmentWatchdog {
+    fn name(&self) -> &'static str {
+        "assignment_watchdog"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        let cutoff = Utc::now() - ChronoDuration::seconds(self.config.unresponsive_after_seconds);
+
+        for job_id in self.cache_service.get_active_jobs().await? {
+            let Some(job) = self.cache_service.load_job(&job_id).await? else {
+                continue;
+            };
+            let Some(driver_id) = job.driver_id.as_ref() else {
+                continue;
+            };
+            if !job.status.is_awaiting_pickup() {
+                continue;
+            }
+
+            // Location pings and status changes both count as signs of life; a driver
+            // who never pinged still gets the full window from the assignment
+            let last_seen = self.cache_service.get_driver_last_seen(driver_id).await?;
+            let last_heard = last_seen.map_or(job.updated_at, |seen| seen.max(job.updated_at));
+            if last_heard > cutoff {
+                continue;
+            }
+
+            if let Err(e) = self.job_service.reassign_unresponsive_job(&job_id, driver_id).await {
+                tracing::warn!("Failed to reassign job {} from driver {}: {}", job_id, driver_id, e);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker, messaging::RecordingNotificationService},
+        models::{
+            driver::{DriverStatus, MAX_RELIABILITY_SCORE},
+            job::{JobEventType, JobStatus},
+            user::UserType,
+        },
+        services::user_service::UserOperations,
+    };
+
+    #[tokio::test]
+    async fn test_silent_driver_is_released_and_penalized() {
+        let notifications = RecordingNotificationService::new();
+        let app = TestApp::builder()
+            .notification_service(Arc::new(notifications.clone()))
+            .build();
+        let state = &app.state;
+        let mut faker = Faker::seeded(21);
+
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let mut silent = faker.driver();
+        silent.status = DriverStatus::Online;
+        let mut active = faker.driver();
+        active.status = DriverStatus::Online;
+        for driver in [&silent, &active] {
+            state.cache_service.cache_driver(driver).await.unwrap();
+        }
+
+        let abandoned = state.job_service.create_job(faker.job_request(&customer.id)).await.unwrap();
+        let healthy = state.job_service.create_job(faker.job_request(&customer.id)).await.unwrap();
+        state.job_service.assign_driver_to_job(&abandoned.id, &silent.id).await.unwrap();
+        state.job_service.assign_driver_to_job(&healthy.id, &active.id).await.unwrap();
+
+        // Assigned ten minutes ago; only one of the drivers has pinged since
+        let long_ago = Utc::now() - ChronoDuration::minutes(10);
+        for job_id in [&abandoned.id, &healthy.id] {
+            let mut job = state.cache_service.load_job(job_id).await.unwrap().unwrap();
+            job.updated_at = long_ago;
+            state.cache_service.cache_job(&job).await.unwrap();
+        }
+        state.cache_service.cache_driver_last_seen(&[(active.id.clone(), Utc::now())]).await.unwrap();
+
+        let worker = AssignmentWatchdog::new(state.cache_service.clone(), state.job_service.clone(), AssignmentWatchdogConfig::default());
+        worker.run_once().await.unwrap();
+
+        let job = state.cache_service.load_job(&abandoned.id).await.unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Searching);
+        assert!(job.driver_id.is_none());
+        assert!(state.cache_service.get_driver_jobs(&silent.id).await.unwrap().is_empty());
+        let events = state.cache_service.get_job_events(&abandoned.id).await.unwrap();
+        assert_eq!(events.last().unwrap().event_type, JobEventType::DriverUnresponsive);
+
+        let driver = state.cache_service.get_driver(&silent.id).await.unwrap().unwrap();
+        assert!(driver.reliability_score < MAX_RELIABILITY_SCORE);
+        let driver = state.cache_service.get_driver(&active.id).await.unwrap().unwrap();
+        assert_eq!(driver.reliability_score, MAX_RELIABILITY_SCORE);
+
+        let job = state.cache_service.load_job(&healthy.id).await.unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::DriverAssigned);
+        assert_eq!(notifications.of_kind("driver_reassigning").len(), 1);
+    }
+}
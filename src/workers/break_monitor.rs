@@ -0,0 +1,103 @@
+// src/workers/break_monitor.rs
+// Takes drivers offline when they overrun their break
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    services::driver_service::DriverService,
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct BreakMonitorConfig {
+    pub check_interval_seconds: u64,
+}
+
+impl Default for BreakMonitorConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 60,
+        }
+    }
+}
+
+pub struct BreakMonitor {
+    driver_service: Arc<DriverService>,
+    config: BreakMonitorConfig,
+}
+
+impl BreakMonitor {
+    pub fn new(driver_service: Arc<DriverService>, config: BreakMonitorConfig) -> Self {
+        Self {
+            driver_service,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for BreakMonitor {
+    fn name(&self) -> &'static str {
+        "break_monitor"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        let ended = self.driver_service.end_overdue_breaks(Utc::now()).await?;
+        if ended > 0 {
+            tracing::info!("Set {} drivers offline after overdue breaks", ended);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker, messaging::RecordingNotificationService},
+        models::driver::DriverStatus,
+        services::driver_service::DriverOperations,
+    };
+
+    #[tokio::test]
+    async fn test_overdue_break_sets_driver_offline() {
+        let notifications = RecordingNotificationService::new();
+        let app = TestApp::builder()
+            .notification_service(Arc::new(notifications.clone()))
+            .build();
+        let state = &app.state;
+        let mut faker = Faker::seeded(5);
+
+        let overdue = faker.driver();
+        let resting = faker.driver();
+        for driver in [&overdue, &resting] {
+            let mut driver = driver.clone();
+            driver.status = DriverStatus::Online;
+            state.cache_service.cache_driver(&driver).await.unwrap();
+            state.driver_service.start_break(&driver.id, Some(15)).await.unwrap();
+        }
+        let mut driver = state.cache_service.get_driver(&overdue.id).await.unwrap().unwrap();
+        driver.break_ends_at = Some(Utc::now() - ChronoDuration::minutes(1));
+        state.cache_service.cache_driver(&driver).await.unwrap();
+
+        let worker = BreakMonitor::new(state.driver_service.clone(), BreakMonitorConfig::default());
+        worker.run_once().await.unwrap();
+
+        let driver = state.cache_service.get_driver(&overdue.id).await.unwrap().unwrap();
+        assert_eq!(driver.status, DriverStatus::Offline);
+        assert!(driver.break_ends_at.is_none());
+        let driver = state.cache_service.get_driver(&resting.id).await.unwrap().unwrap();
+        assert_eq!(driver.status, DriverStatus::OnBreak);
+        assert_eq!(notifications.of_kind("break_overdue").len(), 1);
+    }
+}
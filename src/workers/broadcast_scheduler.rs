@@ -0,0 +1,59 @@
+// src/workers/broadcast_scheduler.rs
+// Sends scheduled promotional broadcasts once they fall due
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    services::broadcast_service::BroadcastService,
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct BroadcastSchedulerConfig {
+    pub check_interval_seconds: u64,
+}
+
+impl Default for BroadcastSchedulerConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 60,
+        }
+    }
+}
+
+pub struct BroadcastScheduler {
+    broadcast_service: Arc<BroadcastService>,
+    config: BroadcastSchedulerConfig,
+}
+
+impl BroadcastScheduler {
+    pub fn new(broadcast_service: Arc<BroadcastService>, config: BroadcastSchedulerConfig) -> Self {
+        Self {
+            broadcast_service,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for BroadcastScheduler {
+    fn name(&self) -> &'static str {
+        "broadcast_scheduler"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        let sent = self.broadcast_service.send_due(Utc::now()).await?;
+        if sent > 0 {
+            tracing::info!("Sent {} scheduled broadcasts", sent);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+// src/workers/canary.rs
+// Runs the synthetic canary job on a schedule, in environments that turn it on
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::tenant::DEFAULT_TENANT_ID,
+    services::{cache_service::CacheService, canary_service::CanaryService, tenant_service::current_tenant_id},
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct CanaryWorkerConfig {
+    pub check_interval_seconds: u64,
+}
+
+impl Default for CanaryWorkerConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 300,
+        }
+    }
+}
+
+impl CanaryWorkerConfig {
+    /// From `CANARY_ENABLED=true`, with an optional `CANARY_INTERVAL_SECONDS`; None when off
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        if var("CANARY_ENABLED").is_none_or(|enabled| enabled != "true") {
+            return None;
+        }
+        let defaults = Self::default();
+        Some(Self {
+            check_interval_seconds: var("CANARY_INTERVAL_SECONDS")
+                .and_then(|seconds| seconds.parse().ok())
+                .unwrap_or(defaults.check_interval_seconds),
+        })
+    }
+}
+
+pub struct Canary {
+    cache_service: Arc<CacheService>,
+    canary: Arc<CanaryService>,
+    config: CanaryWorkerConfig,
+}
+
+impl Canary {
+    pub fn new(cache_service: Arc<CacheService>, canary: Arc<CanaryService>, config: CanaryWorkerConfig) -> Self {
+        Self { cache_service, canary, config }
+    }
+}
+
+#[async_trait]
+impl Worker for Canary {
+    fn name(&self) -> &'static str {
+        "canary"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        // The canary keeps to a tenant of its own, so one pass per interval is enough,
+        // and only on whichever instance gets there first
+        if current_tenant_id() != DEFAULT_TENANT_ID {
+            return Ok(());
+        }
+        let now = Utc::now();
+        let slot = now.timestamp() / self.config.check_interval_seconds as i64;
+        if !self.cache_service.claim_canary_slot(slot, self.config.check_interval_seconds).await? {
+            return Ok(());
+        }
+        self.canary.run(now).await?;
+        Ok(())
+    }
+}
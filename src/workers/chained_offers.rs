@@ -0,0 +1,146 @@
+// src/workers/chained_offers.rs
+// Offers drivers on their way to a dropoff a job that picks up nearby, so they can go
+// straight from one delivery to the next (see `DispatcherService::offer_next_job`)
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::job::JobStatus,
+    services::{cache_service::CacheService, dispatcher_service::DispatcherService},
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct ChainedOffersConfig {
+    pub check_interval_seconds: u64,
+}
+
+impl Default for ChainedOffersConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 30,
+        }
+    }
+}
+
+pub struct ChainedOffers {
+    cache_service: Arc<CacheService>,
+    dispatcher_service: Arc<DispatcherService>,
+    config: ChainedOffersConfig,
+}
+
+impl ChainedOffers {
+    pub fn new(cache_service: Arc<CacheService>, dispatcher_service: Arc<DispatcherService>, config: ChainedOffersConfig) -> Self {
+        Self {
+            cache_service,
+            dispatcher_service,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for ChainedOffers {
+    fn name(&self) -> &'static str {
+        "chained_offers"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        for job_id in self.cache_service.get_active_jobs().await? {
+            let Some(job) = self.cache_service.load_job(&job_id).await? else {
+                continue;
+            };
+            if job.status != JobStatus::InTransit || job.driver_id.is_none() {
+                continue;
+            }
+            if let Err(e) = self.dispatcher_service.offer_next_job(&job).await {
+                tracing::warn!("Failed to offer a next job after {}: {}", job_id, e);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker, messaging::RecordingNotificationService},
+        models::{
+            driver::DriverStatus,
+            job::{JobStatusUpdate, Location},
+            user::UserType,
+        },
+        services::{job_service::JobOperations, user_service::UserOperations},
+    };
+
+    #[tokio::test]
+    async fn test_next_job_is_offered_queued_and_started_at_dropoff() {
+        let notifications = RecordingNotificationService::new();
+        let app = TestApp::builder()
+            .notification_service(Arc::new(notifications.clone()))
+            .build();
+        let state = &app.state;
+        let mut faker = Faker::seeded(43);
+
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let current = state.job_service.create_job(faker.job_request(&customer.id)).await.unwrap();
+        // One pickup at the current dropoff, one across the country
+        let near_dropoff = |location: &Location, offset: f64| Location {
+            latitude: location.latitude + offset,
+            ..location.clone()
+        };
+        let mut request = faker.job_request(&customer.id);
+        request.pickup_location = Some(near_dropoff(&current.dropoff_location, 0.005));
+        let next = state.job_service.create_job(request).await.unwrap();
+        let mut request = faker.job_request(&customer.id);
+        request.pickup_location = Some(near_dropoff(&current.dropoff_location, 2.0));
+        let far = state.job_service.create_job(request).await.unwrap();
+
+        let mut driver = faker.driver();
+        driver.status = DriverStatus::Online;
+        state.cache_service.cache_driver(&driver).await.unwrap();
+        state.job_service.assign_driver_to_job(&current.id, &driver.id).await.unwrap();
+        let worker = ChainedOffers::new(state.cache_service.clone(), state.dispatcher_service.clone(), ChainedOffersConfig::default());
+        let set_status = |status: JobStatus| JobStatusUpdate {
+            job_id: current.id.clone(),
+            status,
+            driver_id: Some(driver.id.clone()),
+            notes: None,
+        };
+
+        // Nothing until the package is on its way
+        worker.run_once().await.unwrap();
+        assert!(notifications.of_kind("job_offer").is_empty());
+        state.job_service.update_job_status(set_status(JobStatus::PackagePickedUp)).await.unwrap();
+        state.job_service.update_job_status(set_status(JobStatus::InTransit)).await.unwrap();
+        worker.run_once().await.unwrap();
+        let offers = notifications.of_kind("job_offer");
+        assert_eq!(offers.len(), 1);
+        assert_eq!(offers[0].message.data.as_ref().unwrap()["job_id"], next.id.as_str());
+        // Not offered again while the driver thinks it over
+        worker.run_once().await.unwrap();
+        assert_eq!(notifications.of_kind("job_offer").len(), 1);
+
+        state.job_service.assign_driver_to_job(&next.id, &driver.id).await.unwrap();
+        let queued = state.cache_service.get_driver(&driver.id).await.unwrap().unwrap();
+        assert_eq!(queued.queued_job_id.as_ref(), Some(&next.id));
+        assert!(state.job_service.assign_driver_to_job(&far.id, &driver.id).await.is_err());
+
+        state.job_service.complete_job(&current.id).await.unwrap();
+        let started = state.cache_service.load_job(&next.id).await.unwrap().unwrap();
+        assert_eq!(started.status, JobStatus::DriverEnRoute);
+        let driver = state.cache_service.get_driver(&driver.id).await.unwrap().unwrap();
+        assert!(driver.queued_job_id.is_none());
+        assert_eq!(driver.current_ride_id.as_ref(), Some(&next.id));
+    }
+}
@@ -0,0 +1,58 @@
+// src/workers/deferred_notifications.rs
+// Sends notifications held back by quiet hours once each user's window is over
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    services::notification_batching::BatchingNotificationService,
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct DeferredNotificationsConfig {
+    pub check_interval_seconds: u64, // Longest a held message waits past the end of quiet hours
+}
+
+impl Default for DeferredNotificationsConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 5 * 60,
+        }
+    }
+}
+
+pub struct DeferredNotifications {
+    notifier: Arc<BatchingNotificationService>,
+    config: DeferredNotificationsConfig,
+}
+
+impl DeferredNotifications {
+    pub fn new(notifier: Arc<BatchingNotificationService>, config: DeferredNotificationsConfig) -> Self {
+        Self {
+            notifier,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for DeferredNotifications {
+    fn name(&self) -> &'static str {
+        "deferred_notifications"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        let sent = self.notifier.flush_deferred().await?;
+        if sent > 0 {
+            tracing::info!("Sent {} notifications held for quiet hours", sent);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+// src/workers/demand_forecast.rs
+// Rebuilds the per-zone demand forecast once a day
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{
+    errors::SparrowError as AppError,
+    services::demand_service::DemandService,
+    workers::Worker,
+};
+
+pub struct DemandForecaster {
+    demand_service: Arc<DemandService>,
+}
+
+impl DemandForecaster {
+    pub fn new(demand_service: Arc<DemandService>) -> Self {
+        Self { demand_service }
+    }
+}
+
+#[async_trait]
+impl Worker for DemandForecaster {
+    fn name(&self) -> &'static str {
+        "demand_forecast"
+    }
+
+    // First run happens at startup so a fresh deploy has a forecast straight away
+    fn interval(&self) -> Duration {
+        Duration::from_secs(24 * 60 * 60)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        self.demand_service.refresh_forecast().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+// src/workers/document_expiry.rs
+// Reminds drivers of expiring documents and suspends them when one lapses
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    services::onboarding_service::OnboardingService,
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct DocumentExpiryConfig {
+    pub check_interval_seconds: u64,
+}
+
+impl Default for DocumentExpiryConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 3600,
+        }
+    }
+}
+
+pub struct DocumentExpiry {
+    onboarding_service: Arc<OnboardingService>,
+    config: DocumentExpiryConfig,
+}
+
+impl DocumentExpiry {
+    pub fn new(onboarding_service: Arc<OnboardingService>, config: DocumentExpiryConfig) -> Self {
+        Self {
+            onboarding_service,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for DocumentExpiry {
+    fn name(&self) -> &'static str {
+        "document_expiry"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        let sweep = self.onboarding_service.check_document_expiry(Utc::now().date_naive()).await?;
+        if sweep.reminders_sent > 0 || sweep.drivers_suspended > 0 {
+            tracing::info!(
+                "Sent {} document expiry reminders, suspended {} drivers",
+                sweep.reminders_sent,
+                sweep.drivers_suspended,
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker, messaging::RecordingNotificationService},
+        models::driver::{DocumentKind, DriverDocument, DriverDocumentUpload, DriverStatus},
+        services::onboarding_service::ExpirySweep,
+    };
+
+    #[tokio::test]
+    async fn test_reminders_then_suspension_then_reinstatement() {
+        let notifications = RecordingNotificationService::new();
+        let app = TestApp::builder()
+            .notification_service(Arc::new(notifications.clone()))
+            .build();
+        let state = &app.state;
+        let today = Utc::now().date_naive();
+        let document = |kind, days: i64| DriverDocument {
+            kind,
+            url: "https://files.example/doc".to_string(),
+            submitted_at: Utc::now(),
+            expires_at: Some(today + ChronoDuration::days(days)),
+            reminded_days_before: None,
+        };
+
+        let mut driver = Faker::seeded(9).driver();
+        driver.status = DriverStatus::Online;
+        driver.documents = vec![document(DocumentKind::Insurance, 6), document(DocumentKind::DriversLicense, 40)];
+        state.cache_service.cache_driver(&driver).await.unwrap();
+
+        // One reminder per threshold reached, however often the check runs
+        let worker = DocumentExpiry::new(state.onboarding_service.clone(), DocumentExpiryConfig::default());
+        worker.run_once().await.unwrap();
+        worker.run_once().await.unwrap();
+        assert_eq!(notifications.of_kind("document_expiring").len(), 1);
+        let tomorrow_lapses = state.onboarding_service.check_document_expiry(today + ChronoDuration::days(5)).await.unwrap();
+        assert_eq!(tomorrow_lapses, ExpirySweep { reminders_sent: 1, drivers_suspended: 0 });
+
+        let lapsed = state.onboarding_service.check_document_expiry(today + ChronoDuration::days(7)).await.unwrap();
+        assert_eq!(lapsed.drivers_suspended, 1);
+        let suspended = state.cache_service.get_driver(&driver.id).await.unwrap().unwrap();
+        assert!(!suspended.can_take_work());
+        assert_eq!(suspended.status, DriverStatus::Offline);
+        assert_eq!(notifications.of_kind("driver_suspended").len(), 1);
+
+        // Renewing the lapsed document lifts the suspension
+        let renewal = DriverDocumentUpload {
+            kind: DocumentKind::Insurance,
+            url: "https://files.example/renewed".to_string(),
+            expires_at: Some(today + ChronoDuration::days(365)),
+        };
+        state.onboarding_service.submit_documents(&driver.id, vec![renewal]).await.unwrap();
+        let reinstated = state.cache_service.get_driver(&driver.id).await.unwrap().unwrap();
+        assert!(reinstated.is_active && reinstated.suspended_reason.is_none());
+        assert_eq!(notifications.of_kind("driver_reinstated").len(), 1);
+    }
+}
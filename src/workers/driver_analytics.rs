@@ -0,0 +1,111 @@
+// src/workers/driver_analytics.rs
+// Keeps every driver's reliability score current as their dispatch history ages
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    services::{cache_service::CacheService, driver_service::DriverService},
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct DriverAnalyticsConfig {
+    pub refresh_interval_seconds: u64,
+}
+
+impl Default for DriverAnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            refresh_interval_seconds: 900,
+        }
+    }
+}
+
+pub struct DriverAnalytics {
+    cache_service: Arc<CacheService>,
+    driver_service: Arc<DriverService>,
+    config: DriverAnalyticsConfig,
+}
+
+impl DriverAnalytics {
+    pub fn new(cache_service: Arc<CacheService>, driver_service: Arc<DriverService>, config: DriverAnalyticsConfig) -> Self {
+        Self {
+            cache_service,
+            driver_service,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for DriverAnalytics {
+    fn name(&self) -> &'static str {
+        "driver_analytics"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.refresh_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        let now = Utc::now();
+        for driver_id in self.cache_service.get_all_driver_ids().await? {
+            if let Err(e) = self.driver_service.refresh_reliability(&driver_id, now).await {
+                tracing::warn!("Failed to score driver {}: {}", driver_id, e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::{driver::{DispatchOutcomeKind, MAX_RELIABILITY_SCORE}, ids::JobId},
+        services::driver_service::DriverOperations,
+    };
+
+    #[tokio::test]
+    async fn test_scores_drivers_from_dispatch_history() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(3);
+
+        let driver = faker.driver();
+        state.cache_service.cache_driver(&driver).await.unwrap();
+        // Took one of two offers, then picked up an hour after accepting
+        let job_id = JobId::generate();
+        let history = [
+            (DispatchOutcomeKind::Offered, None),
+            (DispatchOutcomeKind::Offered, None),
+            (DispatchOutcomeKind::Accepted, None),
+            (DispatchOutcomeKind::PickedUp, Some(60)),
+        ];
+        for (kind, minutes) in history {
+            state.driver_service.record_outcome(&driver.id, kind, &job_id, minutes).await;
+        }
+
+        let worker = DriverAnalytics::new(state.cache_service.clone(), state.driver_service.clone(), DriverAnalyticsConfig::default());
+        worker.run_once().await.unwrap();
+
+        let scored = state.cache_service.get_driver(&driver.id).await.unwrap().unwrap();
+        assert!(scored.reliability_score < MAX_RELIABILITY_SCORE);
+
+        let profile = state.driver_service.get_driver_profile(&driver.id).await.unwrap();
+        let reliability = profile.reliability.unwrap();
+        assert_eq!(reliability.acceptance_rate, 0.5);
+        assert_eq!(reliability.on_time_pickup_rate, 0.0);
+        assert_eq!(reliability.score, scored.reliability_score);
+
+        // The public view leaves the breakdown out
+        let public = state.driver_service.get_driver(&driver.id).await.unwrap().unwrap();
+        assert!(serde_json::to_value(&public).unwrap().get("reliability").is_none());
+    }
+}
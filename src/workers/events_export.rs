@@ -0,0 +1,53 @@
+// src/workers/events_export.rs
+// Uploads outbox events to the warehouse's object store
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{
+    errors::SparrowError as AppError,
+    services::events_export::EventsExportService,
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct EventsExportWorkerConfig {
+    pub check_interval_seconds: u64,
+}
+
+impl Default for EventsExportWorkerConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 300,
+        }
+    }
+}
+
+pub struct EventsExporter {
+    export: Arc<EventsExportService>,
+    config: EventsExportWorkerConfig,
+}
+
+impl EventsExporter {
+    pub fn new(export: Arc<EventsExportService>, config: EventsExportWorkerConfig) -> Self {
+        Self { export, config }
+    }
+}
+
+#[async_trait]
+impl Worker for EventsExporter {
+    fn name(&self) -> &'static str {
+        "events_export"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_seconds)
+    }
+
+    // A failed upload leaves the cursor where it was, so the next run retries it
+    async fn run_once(&self) -> Result<(), AppError> {
+        self.export.export_pending(Utc::now()).await?;
+        Ok(())
+    }
+}
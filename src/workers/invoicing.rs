@@ -0,0 +1,58 @@
+// src/workers/invoicing.rs
+// Issues last month's invoices to merchants on monthly billing and chases overdue ones
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    services::invoice_service::InvoiceService,
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct InvoicingConfig {
+    pub check_interval_seconds: u64,
+}
+
+impl Default for InvoicingConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 3600,
+        }
+    }
+}
+
+pub struct Invoicing {
+    invoices: Arc<InvoiceService>,
+    config: InvoicingConfig,
+}
+
+impl Invoicing {
+    pub fn new(invoices: Arc<InvoiceService>, config: InvoicingConfig) -> Self {
+        Self { invoices, config }
+    }
+}
+
+#[async_trait]
+impl Worker for Invoicing {
+    fn name(&self) -> &'static str {
+        "invoicing"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        let now = Utc::now();
+        let issued = self.invoices.issue_due(now).await?;
+        let reminded = self.invoices.remind_overdue(now).await?;
+        if !issued.is_empty() || reminded > 0 {
+            tracing::info!("Issued {} invoices, sent {} overdue reminders", issued.len(), reminded);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+// src/workers/job_escalation.rs
+// Escalates jobs that sit unassigned: past each step's age a job is dispatched at a higher
+// priority, searched for over a wider radius and pays the driver a bonus
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::job::{Job, JobEscalation, JobPriority, JobStatus},
+    services::{cache_service::CacheService, job_service::{JobOperations, JobService}},
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct EscalationStep {
+    pub after_minutes: i64,     // Time since booking before this step applies
+    pub priority: JobPriority,
+    pub radius_multiplier: f64,
+    pub payout_bonus_rate: f64, // Share of the driver's fare paid on top
+}
+
+#[derive(Debug, Clone)]
+pub struct JobEscalationConfig {
+    pub check_interval_seconds: u64,
+    pub steps: Vec<EscalationStep>, // In order of `after_minutes`
+}
+
+impl Default for JobEscalationConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 60,
+            steps: vec![
+                EscalationStep {
+                    after_minutes: 45,
+                    priority: JobPriority::Express,
+                    radius_multiplier: 1.5,
+                    payout_bonus_rate: 0.1,
+                },
+                EscalationStep {
+                    after_minutes: 90,
+                    priority: JobPriority::Emergency,
+                    radius_multiplier: 2.0,
+                    payout_bonus_rate: 0.25,
+                },
+            ],
+        }
+    }
+}
+
+pub struct JobEscalator {
+    cache_service: Arc<CacheService>,
+    job_service: Arc<JobService>,
+    config: JobEscalationConfig,
+}
+
+impl JobEscalator {
+    pub fn new(cache_service: Arc<CacheService>, job_service: Arc<JobService>, config: JobEscalationConfig) -> Self {
+        Self {
+            cache_service,
+            job_service,
+            config,
+        }
+    }
+
+    // Steps the job is old enough for, counted from the first
+    fn due_level(&self, job: &Job, now: DateTime<Utc>) -> usize {
+        let waiting_minutes = (now - job.created_at).num_minutes();
+        self.config.steps.iter().take_while(|step| waiting_minutes >= step.after_minutes).count()
+    }
+
+    // A later step never narrows the search or shrinks the bonus the job already has
+    fn escalation(&self, job: &Job, level: usize, now: DateTime<Utc>) -> JobEscalation {
+        let step = &self.config.steps[level - 1];
+        let current = job.escalation.as_ref();
+        let bonus = job.pricing.driver_fare().times(step.payout_bonus_rate);
+        JobEscalation {
+            level,
+            priority: step.priority.clone(),
+            radius_multiplier: current.map_or(step.radius_multiplier, |current| current.radius_multiplier.max(step.radius_multiplier)),
+            payout_bonus: match current {
+                Some(current) if current.payout_bonus.minor() > bonus.minor() => current.payout_bonus,
+                _ => bonus,
+            },
+            escalated_at: now,
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for JobEscalator {
+    fn name(&self) -> &'static str {
+        "job_escalation"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        let now = Utc::now();
+
+        for job_id in self.cache_service.get_active_jobs().await? {
+            let Some(mut job) = self.cache_service.load_job(&job_id).await? else {
+                continue;
+            };
+            let unassigned = job.driver_id.is_none()
+                && matches!(job.status, JobStatus::Pending | JobStatus::Searching);
+            if !unassigned {
+                continue;
+            }
+
+            // Every step the job has aged past is applied and recorded, even when a pass was missed
+            let level = job.escalation.as_ref().map_or(0, |escalation| escalation.level);
+            for next in level + 1..=self.due_level(&job, now) {
+                let escalation = self.escalation(&job, next, now);
+                job.escalation = Some(escalation.clone());
+                if let Err(e) = self.job_service.escalate_job(&job_id, escalation).await {
+                    tracing::warn!("Failed to escalate job {}: {}", job_id, e);
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker},
+        models::{driver::VehicleType, ids::JobId, job::JobEventType, user::UserType},
+        services::{dispatch::DispatchConfig, user_service::UserOperations},
+        state::AppState,
+    };
+
+    async fn age(state: &AppState, job_id: &JobId, minutes: i64) {
+        let mut job = state.cache_service.load_job(job_id).await.unwrap().unwrap();
+        job.created_at = Utc::now() - ChronoDuration::minutes(minutes);
+        state.cache_service.cache_job(&job).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_aging_standard_job_escalates_step_by_step() {
+        let app = TestApp::new();
+        let state = &app.state;
+        let mut faker = Faker::seeded(17);
+
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let mut request = faker.job_request(&customer.id);
+        request.priority = JobPriority::Standard;
+        let created = state.job_service.create_job(request).await.unwrap();
+        let worker = JobEscalator::new(state.cache_service.clone(), state.job_service.clone(), JobEscalationConfig::default());
+
+        // Too fresh to escalate
+        age(state, &created.id, 30).await;
+        worker.run_once().await.unwrap();
+        assert!(state.cache_service.load_job(&created.id).await.unwrap().unwrap().escalation.is_none());
+
+        age(state, &created.id, 50).await;
+        worker.run_once().await.unwrap();
+        let job = state.cache_service.load_job(&created.id).await.unwrap().unwrap();
+        let escalation = job.escalation.clone().unwrap();
+        assert_eq!(escalation.level, 1);
+        assert_eq!(*job.effective_priority(), JobPriority::Express);
+        assert_eq!(job.priority, JobPriority::Standard);
+        assert_eq!(DispatchConfig::default().search_radius_for(&job), 15.0);
+        assert_eq!(escalation.payout_bonus, job.pricing.driver_fare().times(0.1));
+
+        let earnings = state.earnings_calculator.preview(&job, &VehicleType::Motorcycle).await;
+        assert_eq!(earnings.escalation_bonus, escalation.payout_bonus);
+
+        // Nothing more until the next step is due
+        worker.run_once().await.unwrap();
+        age(state, &created.id, 95).await;
+        worker.run_once().await.unwrap();
+        let job = state.cache_service.load_job(&created.id).await.unwrap().unwrap();
+        assert_eq!(job.escalation.as_ref().unwrap().level, 2);
+        assert_eq!(*job.effective_priority(), JobPriority::Emergency);
+
+        let events = state.cache_service.get_job_events(&created.id).await.unwrap();
+        let escalations = events.iter().filter(|event| event.event_type == JobEventType::PriorityEscalated).count();
+        assert_eq!(escalations, 2);
+    }
+}
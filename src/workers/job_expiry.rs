@@ -0,0 +1,140 @@
+// src/workers/job_expiry.rs
+// Expires jobs that no driver accepted before their `expires_at`
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::job::JobStatus,
+    services::{cache_service::CacheService, job_service::{JobOperations, JobService}},
+    workers::Worker,
+};
+
+// Recorded in the job's event log and sent in the notification data
+pub const NO_DRIVER_ACCEPTED: &str = "no_driver_accepted";
+
+#[derive(Debug, Clone)]
+pub struct JobExpiryConfig {
+    pub check_interval_seconds: u64,
+}
+
+impl Default for JobExpiryConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 60,
+        }
+    }
+}
+
+pub struct JobExpiry {
+    cache_service: Arc<CacheService>,
+    job_service: Arc<JobService>,
+    config: JobExpiryConfig,
+}
+
+impl JobExpiry {
+    pub fn new(cache_service: Arc<CacheService>, job_service: Arc<JobService>, config: JobExpiryConfig) -> Self {
+        Self {
+            cache_service,
+            job_service,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for JobExpiry {
+    fn name(&self) -> &'static str {
+        "job_expiry"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        let now = Utc::now();
+
+        for job_id in self.cache_service.get_active_jobs().await? {
+            let Some(job) = self.cache_service.load_job(&job_id).await? else {
+                continue;
+            };
+            let unaccepted = job.driver_id.is_none()
+                && matches!(job.status, JobStatus::Pending | JobStatus::Searching);
+            if !unaccepted || job.expires_at > now {
+                continue;
+            }
+
+            // One bad job must not hold up the rest of the scan
+            if let Err(e) = self.job_service.expire_job(&job_id, NO_DRIVER_ACCEPTED).await {
+                tracing::warn!("Failed to expire job {}: {}", job_id, e);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker, messaging::RecordingNotificationService},
+        models::{job::{JobEventType, PaymentStatus}, user::UserType},
+        services::user_service::UserOperations,
+    };
+
+    #[tokio::test]
+    async fn test_expired_job_releases_payment_and_tells_customer() {
+        let notifications = RecordingNotificationService::new();
+        let app = TestApp::builder()
+            .notification_service(Arc::new(notifications.clone()))
+            .build();
+        let state = &app.state;
+        let mut faker = Faker::seeded(11);
+
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let mut user = state.cache_service.load_user(&customer.id).await.unwrap().unwrap();
+        user.language = "fr-GH".to_string();
+        state.cache_service.cache_user(&user).await.unwrap();
+
+        let created = state.job_service.create_job(faker.job_request(&customer.id)).await.unwrap();
+        let fresh = state.job_service.create_job(faker.job_request(&customer.id)).await.unwrap();
+        let mut job = state.cache_service.load_job(&created.id).await.unwrap().unwrap();
+        job.expires_at = Utc::now() - ChronoDuration::minutes(1);
+        job.payment_status = PaymentStatus::Authorized;
+        state.cache_service.cache_job(&job).await.unwrap();
+
+        let worker = JobExpiry::new(state.cache_service.clone(), state.job_service.clone(), JobExpiryConfig::default());
+        worker.run_once().await.unwrap();
+
+        let expired = state.cache_service.load_job(&created.id).await.unwrap().unwrap();
+        assert_eq!(expired.status, JobStatus::Expired);
+        assert_eq!(expired.payment_status, PaymentStatus::Voided);
+        assert!(!state.cache_service.get_active_jobs().await.unwrap().contains(&created.id));
+        let untouched = state.cache_service.load_job(&fresh.id).await.unwrap().unwrap();
+        assert_eq!(untouched.status, JobStatus::Pending);
+
+        let events = state.cache_service.get_job_events(&created.id).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, JobEventType::JobExpired);
+        assert_eq!(events[0].notes.as_deref(), Some(NO_DRIVER_ACCEPTED));
+
+        let sent = notifications.of_kind("job_expired");
+        assert_eq!(sent.len(), 1);
+        let data = sent[0].message.data.as_ref().unwrap();
+        assert_eq!(data["language"], "fr");
+        let suggested = job.priority.next_tier().map(|tier| tier.to_string());
+        assert_eq!(data["suggested_priority"].as_str(), suggested.as_deref());
+        assert!(sent[0].message.body.contains("pas été débité"));
+
+        // A second pass finds nothing left to expire
+        worker.run_once().await.unwrap();
+        assert_eq!(notifications.of_kind("job_expired").len(), 1);
+    }
+}
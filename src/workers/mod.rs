@@ -0,0 +1,101 @@
+// src/workers/mod.rs
+// Background workers: periodic jobs that run alongside the HTTP server
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    services::{region::{with_region, RegionRegistry}, tenant_service::{with_tenant, TenantService}},
+};
+
+pub mod assignment_watchdog;
+pub mod break_monitor;
+pub mod broadcast_scheduler;
+pub mod canary;
+pub mod chained_offers;
+pub mod deferred_notifications;
+pub mod demand_forecast;
+pub mod document_expiry;
+pub mod events_export;
+pub mod invoicing;
+pub mod driver_analytics;
+pub mod job_escalation;
+pub mod job_expiry;
+pub mod notification_digest;
+pub mod otp_cleanup;
+pub mod retention_purge;
+pub mod saga_recovery;
+#[cfg(feature = "payments")]
+pub mod reconciliation;
+pub mod sla_monitor;
+pub mod status_feed;
+pub mod surge_pricing;
+
+#[async_trait]
+pub trait Worker: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn interval(&self) -> Duration;
+    async fn run_once(&self) -> Result<(), AppError>;
+}
+
+// Owns the task handles of every spawned worker
+pub struct WorkerRuntime {
+    tenant_service: Arc<TenantService>,
+    regions: Arc<RegionRegistry>,
+    handles: Mutex<Vec<(&'static str, JoinHandle<()>)>>,
+}
+
+impl WorkerRuntime {
+    pub fn new(tenant_service: Arc<TenantService>, regions: Arc<RegionRegistry>) -> Self {
+        Self {
+            tenant_service,
+            regions,
+            handles: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn spawn(&self, worker: Arc<dyn Worker>) {
+        let name = worker.name();
+        tracing::info!("Starting worker: {} (every {:?})", name, worker.interval());
+
+        let tenant_service = self.tenant_service.clone();
+        let region_ids = self.regions.ids();
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(worker.interval());
+            loop {
+                ticker.tick().await;
+                let tenant_ids = match tenant_service.tenant_ids().await {
+                    Ok(tenant_ids) => tenant_ids,
+                    Err(e) => {
+                        tracing::error!("Worker {} could not list tenants: {}", worker.name(), e);
+                        continue;
+                    }
+                };
+                // Each pass runs inside one region's and tenant's scope; a failed run is logged and retried on the next tick
+                for region_id in &region_ids {
+                    for tenant_id in &tenant_ids {
+                        if let Err(e) = with_region(region_id.clone(), with_tenant(tenant_id.clone(), worker.run_once())).await {
+                            tracing::error!("Worker {} failed for tenant {} in region {}: {}", worker.name(), tenant_id, region_id, e);
+                        }
+                    }
+                }
+            }
+        });
+
+        self.handles.lock().unwrap().push((name, handle));
+    }
+
+    pub fn worker_names(&self) -> Vec<&'static str> {
+        self.handles.lock().unwrap().iter().map(|(name, _)| *name).collect()
+    }
+
+    pub fn shutdown(&self) {
+        for (name, handle) in self.handles.lock().unwrap().drain(..) {
+            tracing::info!("Stopping worker: {}", name);
+            handle.abort();
+        }
+    }
+}
@@ -0,0 +1,58 @@
+// src/workers/notification_digest.rs
+// Sends each user's held low-priority notifications as one summary
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    services::notification_batching::BatchingNotificationService,
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct NotificationDigestConfig {
+    pub digest_interval_seconds: u64,
+}
+
+impl Default for NotificationDigestConfig {
+    fn default() -> Self {
+        Self {
+            digest_interval_seconds: 4 * 60 * 60,
+        }
+    }
+}
+
+pub struct NotificationDigest {
+    notifier: Arc<BatchingNotificationService>,
+    config: NotificationDigestConfig,
+}
+
+impl NotificationDigest {
+    pub fn new(notifier: Arc<BatchingNotificationService>, config: NotificationDigestConfig) -> Self {
+        Self {
+            notifier,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for NotificationDigest {
+    fn name(&self) -> &'static str {
+        "notification_digest"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.digest_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        let sent = self.notifier.flush_digests().await?;
+        if sent > 0 {
+            tracing::info!("Sent {} notification digests", sent);
+        }
+        Ok(())
+    }
+}
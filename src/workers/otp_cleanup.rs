@@ -0,0 +1,56 @@
+// src/workers/otp_cleanup.rs
+// Purges one-time codes that expired without being used
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    services::otp_service::OtpService,
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct OtpCleanupConfig {
+    pub check_interval_seconds: u64,
+}
+
+impl Default for OtpCleanupConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 300,
+        }
+    }
+}
+
+pub struct OtpCleanup {
+    otp: Arc<OtpService>,
+    config: OtpCleanupConfig,
+}
+
+impl OtpCleanup {
+    pub fn new(otp: Arc<OtpService>, config: OtpCleanupConfig) -> Self {
+        Self { otp, config }
+    }
+}
+
+#[async_trait]
+impl Worker for OtpCleanup {
+    fn name(&self) -> &'static str {
+        "otp_cleanup"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        let purged = self.otp.purge_expired(Utc::now()).await?;
+        if purged > 0 {
+            tracing::info!("Purged {} expired one-time codes", purged);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+// src/workers/reconciliation.rs
+// Reconciles the previous UTC day once providers have published their settlement reports
+use async_trait::async_trait;
+use chrono::{Duration as ChronoDuration, Timelike, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    services::{cache_service::CacheService, reconciliation::ReconciliationService},
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct ReconciliationConfig {
+    pub check_interval_seconds: u64,
+    pub reports_ready_hour: u32, // UTC hour by which yesterday's reports are out
+}
+
+impl Default for ReconciliationConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 3600,
+            reports_ready_hour: 3,
+        }
+    }
+}
+
+pub struct Reconciliation {
+    cache_service: Arc<CacheService>,
+    reconciliation: Arc<ReconciliationService>,
+    config: ReconciliationConfig,
+}
+
+impl Reconciliation {
+    pub fn new(cache_service: Arc<CacheService>, reconciliation: Arc<ReconciliationService>, config: ReconciliationConfig) -> Self {
+        Self {
+            cache_service,
+            reconciliation,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for Reconciliation {
+    fn name(&self) -> &'static str {
+        "reconciliation"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        let now = Utc::now();
+        if !self.reconciliation.has_providers() || now.hour() < self.config.reports_ready_hour {
+            return Ok(());
+        }
+        let yesterday = now.date_naive() - ChronoDuration::days(1);
+        if self.cache_service.get_last_reconciled_day().await?.is_some_and(|day| day >= yesterday) {
+            return Ok(());
+        }
+
+        let run = self.reconciliation.reconcile_day(yesterday).await?;
+        self.cache_service.set_last_reconciled_day(yesterday).await?;
+        tracing::info!(
+            "Reconciled {}: {} settlements, {} matched, {} newly flagged",
+            run.day,
+            run.settlements,
+            run.matched,
+            run.flagged,
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+// src/workers/retention_purge.rs
+// Applies the retention policies: deletes old route traces and job events, anonymizes old jobs
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    services::retention_service::RetentionService,
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct RetentionPurgeConfig {
+    pub check_interval_seconds: u64,
+}
+
+impl Default for RetentionPurgeConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 86400, // Policies are in days
+        }
+    }
+}
+
+pub struct RetentionPurge {
+    retention: Arc<RetentionService>,
+    config: RetentionPurgeConfig,
+}
+
+impl RetentionPurge {
+    pub fn new(retention: Arc<RetentionService>, config: RetentionPurgeConfig) -> Self {
+        Self { retention, config }
+    }
+}
+
+#[async_trait]
+impl Worker for RetentionPurge {
+    fn name(&self) -> &'static str {
+        "retention_purge"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        let report = self.retention.enforce(Utc::now(), false).await?;
+        let held: usize = report.classes.iter().map(|class| class.held.len()).sum();
+        if held > 0 {
+            tracing::info!("Retention purge left {} records alone for legal holds", held);
+        }
+        Ok(())
+    }
+}
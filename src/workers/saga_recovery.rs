@@ -0,0 +1,57 @@
+// src/workers/saga_recovery.rs
+// Picks up job completions and refunds whose saga stopped partway, whether a step kept
+// failing or the instance running it went down
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    services::job_service::JobService,
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct SagaRecoveryConfig {
+    pub check_interval_seconds: u64,
+}
+
+impl Default for SagaRecoveryConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 60,
+        }
+    }
+}
+
+pub struct SagaRecovery {
+    job_service: Arc<JobService>,
+    config: SagaRecoveryConfig,
+}
+
+impl SagaRecovery {
+    pub fn new(job_service: Arc<JobService>, config: SagaRecoveryConfig) -> Self {
+        Self { job_service, config }
+    }
+}
+
+#[async_trait]
+impl Worker for SagaRecovery {
+    fn name(&self) -> &'static str {
+        "saga_recovery"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        let resumed = self.job_service.resume_sagas(Utc::now()).await?;
+        if resumed > 0 {
+            tracing::info!("Resumed {} stalled sagas", resumed);
+        }
+        Ok(())
+    }
+}
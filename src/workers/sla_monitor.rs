@@ -0,0 +1,278 @@
+// src/workers/sla_monitor.rs
+// Watches Express/SameDay jobs against their promised delivery time, and checks the ones
+// delivered since the last pass, so a job completed late between two passes is still credited
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    models::{job::Job, user::{credit_total, UserCredit}},
+    services::{
+        cache_service::CacheService,
+        messaging_service::{NotificationMessage, NotificationPriority, NotificationService},
+    },
+    utils::id_generator::{IdGenerator, IdType},
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct SlaConfig {
+    pub check_interval_seconds: u64,
+    pub at_risk_margin_minutes: i64, // Warn when less than this is left and the package is not yet moving
+    pub goodwill_credit_rate: f64,   // Share of the priority surcharge credited back on breach
+}
+
+impl Default for SlaConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 60,
+            at_risk_margin_minutes: 30,
+            goodwill_credit_rate: 1.0, // Refund the full express/same-day premium
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum SlaCheck {
+    OnTrack,
+    AtRisk,
+    Breached,
+}
+
+pub struct SlaMonitor {
+    cache_service: Arc<CacheService>,
+    notification_service: Arc<dyn NotificationService>,
+    config: SlaConfig,
+}
+
+impl SlaMonitor {
+    pub fn new(
+        cache_service: Arc<CacheService>,
+        notification_service: Arc<dyn NotificationService>,
+        config: SlaConfig,
+    ) -> Self {
+        Self {
+            cache_service,
+            notification_service,
+            config,
+        }
+    }
+
+    fn check(&self, job: &Job, now: DateTime<Utc>) -> SlaCheck {
+        let Some(sla) = &job.sla else {
+            return SlaCheck::OnTrack;
+        };
+
+        if now > sla.promised_by {
+            return SlaCheck::Breached;
+        }
+
+        // Time still needed: the whole trip if not picked up yet, otherwise the safety margin
+        let minutes_left = (sla.promised_by - now).num_minutes();
+        let minutes_needed = if job.pickup_time.is_none() {
+            job.estimated_duration_min as i64 + self.config.at_risk_margin_minutes
+        } else {
+            self.config.at_risk_margin_minutes
+        };
+
+        if minutes_left < minutes_needed {
+            SlaCheck::AtRisk
+        } else {
+            SlaCheck::OnTrack
+        }
+    }
+
+    async fn notify_at_risk(&self, job: &mut Job, now: DateTime<Utc>) -> Result<(), AppError> {
+        let Some(sla) = job.sla.as_mut() else {
+            return Ok(());
+        };
+        if sla.at_risk_notified_at.is_some() {
+            return Ok(());
+        }
+
+        let message = NotificationMessage::new(
+            "⏱️ Your delivery may be late",
+            "We're doing our best to get your package there on time. We'll keep you posted.",
+        )
+        .with_data(json!({
+            "type": "sla_at_risk",
+            "job_id": job.id,
+            "promised_by": sla.promised_by.to_rfc3339(),
+        }))
+        .with_priority(NotificationPriority::Normal);
+
+        sla.at_risk_notified_at = Some(now);
+        self.notification_service.send_to_user(&job.customer_id, message).await
+    }
+
+    async fn apply_breach(&self, job: &mut Job, now: DateTime<Utc>) -> Result<(), AppError> {
+        let amount = job.pricing.priority_surcharge.times(self.config.goodwill_credit_rate);
+        if job.sla.as_ref().is_none_or(|sla| sla.breached_at.is_some()) {
+            return Ok(());
+        }
+
+        // A wallet holds one currency; support settles a credit in any other by hand
+        let credits = self.cache_service.get_user_credits(&job.customer_id).await?;
+        let wallet = credit_total(&credits)
+            .and_then(|balance| balance.map_or(Ok(()), |balance| balance.ensure_same_currency(&amount)));
+        let Some(sla) = job.sla.as_mut() else {
+            return Ok(());
+        };
+        if let Err(e) = wallet {
+            sla.breached_at = Some(now);
+            tracing::warn!("SLA breached for job {} - not credited: {}", job.id, e);
+            return Ok(());
+        }
+
+        let credit = UserCredit {
+            id: IdGenerator::generate(IdType::Credit),
+            user_id: job.customer_id.clone(),
+            amount,
+            reason: "sla_breach".to_string(),
+            job_id: Some(job.id.clone()),
+            created_at: now,
+        };
+        self.cache_service.append_user_credit(&credit).await?;
+
+        sla.breached_at = Some(now);
+        sla.goodwill_credit = Some(amount);
+
+        tracing::warn!("SLA breached for job {} - credited {}", job.id, amount);
+
+        let message = NotificationMessage::new(
+            "🙏 Sorry, we're running late",
+            &format!("We missed our delivery promise. {} has been added to your account.", amount),
+        )
+        .with_data(json!({
+            "type": "sla_breached",
+            "job_id": job.id,
+            "credit_id": credit.id,
+            "amount": amount.to_major(),
+        }));
+
+        self.notification_service.send_to_user(&job.customer_id, message).await
+    }
+
+    // Persist the SLA flags even if the push failed so we don't credit twice. Only the
+    // flags: the job may have moved on while we were notifying.
+    async fn save_flags(&self, job: &Job, result: Result<(), AppError>, now: DateTime<Utc>) -> Result<(), AppError> {
+        if let Err(e) = result {
+            tracing::warn!("SLA notification failed for job {}: {}", job.id, e);
+        }
+        self.cache_service.update_job(&job.id, |current| {
+            current.sla = job.sla.clone();
+            current.updated_at = now;
+            Ok(())
+        }).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Worker for SlaMonitor {
+    fn name(&self) -> &'static str {
+        "sla_monitor"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        let now = Utc::now();
+
+        for job_id in self.cache_service.get_active_jobs().await? {
+            let Some(mut job) = self.cache_service.load_job(&job_id).await? else {
+                continue;
+            };
+            if job.sla.is_none() || job.status.is_terminal() {
+                continue;
+            }
+
+            let result = match self.check(&job, now) {
+                SlaCheck::OnTrack => continue,
+                SlaCheck::AtRisk => self.notify_at_risk(&mut job, now).await,
+                SlaCheck::Breached => self.apply_breach(&mut job, now).await,
+            };
+            self.save_flags(&job, result, now).await?;
+        }
+
+        for job_id in self.cache_service.get_completed_sla_jobs().await? {
+            if let Some(mut job) = self.cache_service.load_job(&job_id).await? {
+                let late = job.sla.as_ref()
+                    .zip(job.dropoff_time)
+                    .is_some_and(|(sla, dropoff_time)| dropoff_time > sla.promised_by);
+                if late {
+                    let result = self.apply_breach(&mut job, now).await;
+                    self.save_flags(&job, result, now).await?;
+                }
+            }
+            self.cache_service.remove_completed_sla_job(&job_id).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    use crate::{
+        mocks::{app::TestApp, fixtures::Faker, messaging::RecordingNotificationService},
+        models::{job::{DeliverySla, JobPriority, JobStatus}, money::{Currency, Money}, user::UserType},
+        services::{job_service::JobOperations, user_service::UserOperations},
+    };
+
+    #[tokio::test]
+    async fn test_job_completed_late_between_passes_is_credited() {
+        let notifications = Arc::new(RecordingNotificationService::new());
+        let app = TestApp::builder()
+            .notification_service(notifications.clone())
+            .build();
+        let state = &app.state;
+        let mut faker = Faker::seeded(29);
+        let monitor = SlaMonitor::new(state.cache_service.clone(), state.notification_service.clone(), SlaConfig::default());
+
+        let customer = state.user_service.register_user(faker.user_registration(UserType::Customer)).await.unwrap();
+        let mut user = state.cache_service.load_user(&customer.id).await.unwrap().unwrap();
+        user.language = "fr-GH".to_string();
+        state.cache_service.cache_user(&user).await.unwrap();
+        let mut request = faker.job_request(&customer.id);
+        request.priority = JobPriority::Express;
+        let created = state.job_service.create_job(request).await.unwrap();
+        let mut job = state.cache_service.load_job(&created.id).await.unwrap().unwrap();
+        job.pricing.priority_surcharge = Money::from_major(15.0, Currency::GHS);
+        job.sla = Some(DeliverySla::new(Utc::now() + ChronoDuration::hours(2)));
+        state.cache_service.cache_job(&job).await.unwrap();
+        monitor.run_once().await.unwrap();
+
+        // The deadline passes and the driver delivers before the next pass
+        job = state.cache_service.load_job(&created.id).await.unwrap().unwrap();
+        job.sla.as_mut().unwrap().promised_by = Utc::now() - ChronoDuration::minutes(5);
+        job.status = JobStatus::InTransit;
+        state.cache_service.cache_job(&job).await.unwrap();
+        state.job_service.complete_job(&created.id).await.unwrap();
+        assert!(!state.cache_service.get_active_jobs().await.unwrap().contains(&created.id));
+
+        monitor.run_once().await.unwrap();
+        let credited = state.cache_service.load_job(&created.id).await.unwrap().unwrap();
+        let sla = credited.sla.unwrap();
+        assert!(sla.breached_at.is_some());
+        assert_eq!(sla.goodwill_credit, Some(Money::from_major(15.0, Currency::GHS)));
+        let sent = notifications.of_kind("sla_breached");
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].message.title, "🙏 Désolés, nous sommes en retard");
+        assert!(sent[0].message.body.ends_with("a été crédité sur votre compte."));
+
+        // Checked once
+        monitor.run_once().await.unwrap();
+        assert_eq!(notifications.of_kind("sla_breached").len(), 1);
+        assert!(state.cache_service.get_completed_sla_jobs().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,58 @@
+// src/workers/status_feed.rs
+// Sends location frames and webhook deliveries held back by their consumer's interval
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing;
+
+use crate::{
+    errors::SparrowError as AppError,
+    services::status_feed::StatusFeedService,
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct StatusFeedWorkerConfig {
+    pub flush_interval_seconds: u64, // The finest interval a consumer can choose
+}
+
+impl Default for StatusFeedWorkerConfig {
+    fn default() -> Self {
+        Self {
+            flush_interval_seconds: 1,
+        }
+    }
+}
+
+pub struct StatusFeedFlusher {
+    status_feed: Arc<StatusFeedService>,
+    config: StatusFeedWorkerConfig,
+}
+
+impl StatusFeedFlusher {
+    pub fn new(status_feed: Arc<StatusFeedService>, config: StatusFeedWorkerConfig) -> Self {
+        Self {
+            status_feed,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl Worker for StatusFeedFlusher {
+    fn name(&self) -> &'static str {
+        "status_feed"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.flush_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        let delivered = self.status_feed.flush().await?;
+        if delivered > 0 {
+            tracing::debug!("Made {} status webhook deliveries", delivered);
+        }
+        Ok(())
+    }
+}
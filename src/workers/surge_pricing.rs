@@ -0,0 +1,52 @@
+// src/workers/surge_pricing.rs
+// Keeps the surge multipliers in step with demand, for driver earnings and the ops dashboard
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{
+    errors::SparrowError as AppError,
+    services::demand_service::DemandService,
+    workers::Worker,
+};
+
+#[derive(Debug, Clone)]
+pub struct SurgePricingConfig {
+    pub check_interval_seconds: u64,
+}
+
+impl Default for SurgePricingConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: 300,
+        }
+    }
+}
+
+pub struct SurgePricing {
+    demand_service: Arc<DemandService>,
+    config: SurgePricingConfig,
+}
+
+impl SurgePricing {
+    pub fn new(demand_service: Arc<DemandService>, config: SurgePricingConfig) -> Self {
+        Self { demand_service, config }
+    }
+}
+
+#[async_trait]
+impl Worker for SurgePricing {
+    fn name(&self) -> &'static str {
+        "surge_pricing"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.check_interval_seconds)
+    }
+
+    async fn run_once(&self) -> Result<(), AppError> {
+        self.demand_service.refresh_surge(Utc::now()).await?;
+        Ok(())
+    }
+}